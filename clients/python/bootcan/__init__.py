@@ -0,0 +1,126 @@
+"""Client for the bootCAN JSON-RPC bridge."""
+
+import itertools
+import json
+import queue
+import socket
+import threading
+
+DEFAULT_PORT = 47800
+
+__all__ = ["Client", "RpcError", "DEFAULT_PORT"]
+
+
+class RpcError(Exception):
+    """Error returned by the bootCAN backend.
+
+    ``kind`` names the error class, e.g. ``notFound``, ``busy`` or ``timeout``.
+    """
+
+    def __init__(self, message, kind="other"):
+        super().__init__(message)
+        self.kind = kind
+
+
+class Client:
+    """Connection to a running bootCAN instance.
+
+    Requests may be issued from several threads; received frames of
+    subscribed channels are queued and read with :meth:`recv`.
+    """
+
+    def __init__(self, host="127.0.0.1", port=DEFAULT_PORT, timeout=10.0):
+        self._sock = socket.create_connection((host, port), timeout=timeout)
+        self._sock.settimeout(None)
+        self._file = self._sock.makefile("r", encoding="utf-8")
+        self._timeout = timeout
+        self._ids = itertools.count(1)
+        self._lock = threading.Lock()
+        self._pending = {}
+        self._frames = queue.Queue()
+        self._reader = threading.Thread(target=self._read_loop, daemon=True)
+        self._reader.start()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+    def close(self):
+        try:
+            self._sock.shutdown(socket.SHUT_RDWR)
+        except OSError:
+            pass
+        self._sock.close()
+
+    def call(self, method, timeout=None, **params):
+        """Send a request and wait for its result."""
+        request_id = next(self._ids)
+        reply = queue.Queue(maxsize=1)
+        with self._lock:
+            self._pending[request_id] = reply
+            line = json.dumps({"id": request_id, "method": method, "params": params})
+            self._sock.sendall(line.encode("utf-8") + b"\n")
+        try:
+            response = reply.get(timeout=timeout or self._timeout)
+        except queue.Empty:
+            raise TimeoutError(f"{method} timed out") from None
+        finally:
+            with self._lock:
+                self._pending.pop(request_id, None)
+        if response is None:
+            raise ConnectionError("Connection to bootCAN closed")
+        if "error" in response:
+            error = response["error"]
+            raise RpcError(error.get("message", ""), error.get("kind", "other"))
+        return response.get("result")
+
+    def _read_loop(self):
+        for line in self._file:
+            message = json.loads(line)
+            if message.get("method") == "frame":
+                self._frames.put(message["params"])
+                continue
+            with self._lock:
+                reply = self._pending.get(message.get("id"))
+            if reply is not None:
+                reply.put(message)
+        # Wake up callers still waiting for a response
+        with self._lock:
+            for reply in self._pending.values():
+                reply.put(None)
+
+    def list_channels(self):
+        return self.call("listChannels")
+
+    def send(self, channel_id, can_id, data, extended=False, remote=False):
+        self.call("send", channelId=channel_id, id=can_id, data=list(data),
+                  isExtended=extended, isRemote=remote)
+
+    def subscribe(self, channel_id):
+        self.call("subscribe", channelId=channel_id)
+
+    def unsubscribe(self, channel_id):
+        self.call("unsubscribe", channelId=channel_id)
+
+    def recv(self, timeout=None):
+        """Next frame of a subscribed channel, or None on timeout."""
+        try:
+            return self._frames.get(timeout=timeout)
+        except queue.Empty:
+            return None
+
+    def decode(self, channel_id, can_id, data):
+        return self.call("decode", channelId=channel_id, id=can_id, data=list(data))
+
+    def start_periodic(self, channel_id, can_id, data, interval_ms, extended=False):
+        return self.call("startPeriodic", channelId=channel_id, id=can_id, data=list(data),
+                         isExtended=extended, intervalMs=interval_ms)
+
+    def stop_periodic(self, job_id):
+        self.call("stopPeriodic", jobId=job_id)
+
+    def run_scenario(self, file_path, timeout=None):
+        """Run a scenario file; blocks until the report is available."""
+        return self.call("runScenario", timeout=timeout or 3600.0, filePath=file_path)
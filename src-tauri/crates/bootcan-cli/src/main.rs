@@ -0,0 +1,543 @@
+//! Headless bootCAN
+//!
+//! Runs the same interfaces, trace formats, DBC decoder and bootloader as the
+//! desktop app, for CI machines and embedded gateways without a display.
+
+use bootcan_core::core::bootloader::{self, BootloaderTarget, FirmwareImage, FlashStage};
+use bootcan_core::core::bus_stats::StatsEmission;
+use bootcan_core::core::channel::{ChannelConfig, ChannelHandle};
+use bootcan_core::core::dbc::{DbcDatabase, DbcParser, LdfParser, SymParser};
+use bootcan_core::core::frame_link::{ChannelLink, FrameLink};
+use bootcan_core::core::isotp::{IsoTpConfig, DEFAULT_MAX_MESSAGE_LEN};
+use bootcan_core::core::message::CanFrame;
+use bootcan_core::core::subscriber::{FrameSubscriber, DEFAULT_BROADCAST_CAPACITY};
+use bootcan_core::core::trace_export;
+use bootcan_core::core::trace_logger::{TraceFormat, TraceLogger, TraceLoggerConfig};
+use bootcan_core::core::trace_player::TracePlayer;
+use bootcan_core::error::BootCanError;
+use bootcan_core::hal::traits::enumerate_interfaces;
+use clap::{Args, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser)]
+#[command(name = "bootcan-cli", version, about = "Capture, transmit, replay and flash CAN without the bootCAN UI")]
+struct Cli {
+    /// Log progress and protocol details to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct BusArgs {
+    /// Interface ID, e.g. can0 or vcan0 (see `interfaces`)
+    #[arg(short, long)]
+    interface: String,
+    #[arg(short, long, default_value_t = 500_000)]
+    bitrate: u32,
+    /// Frames the output can fall behind the bus before frames are lost
+    #[arg(long, default_value_t = DEFAULT_BROADCAST_CAPACITY)]
+    buffer: usize,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List available CAN interfaces
+    Interfaces,
+    /// Print bus traffic
+    Dump {
+        #[command(flatten)]
+        bus: BusArgs,
+        /// Decode signals with a DBC, SYM or LDF file
+        #[arg(long)]
+        dbc: Option<PathBuf>,
+        /// Only show these IDs (hex, comma separated)
+        #[arg(long, value_parser = parse_hex, value_delimiter = ',')]
+        ids: Vec<u32>,
+        /// Print one JSON object per frame
+        #[arg(long)]
+        json: bool,
+        /// Stop after this many frames
+        #[arg(short = 'n', long)]
+        count: Option<u64>,
+    },
+    /// Send a frame given as ID#DATA (e.g. 123#DEADBEEF, 18DAF110#0201, 123#R)
+    Send {
+        #[command(flatten)]
+        bus: BusArgs,
+        #[arg(value_parser = parse_frame)]
+        frame: CanFrame,
+        /// Number of times to send the frame
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: u64,
+        #[arg(long, default_value_t = 100)]
+        interval_ms: u64,
+    },
+    /// Record bus traffic to a .csv or .trc file until Ctrl-C
+    Log {
+        #[command(flatten)]
+        bus: BusArgs,
+        output: PathBuf,
+        /// Stop after this many seconds
+        #[arg(long)]
+        duration_s: Option<u64>,
+    },
+    /// Transmit a recorded .csv, .trc, .pcap or .pcapng trace with its original timing
+    Replay {
+        #[command(flatten)]
+        bus: BusArgs,
+        input: PathBuf,
+        /// Playback speed factor (0.1 to 5)
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+    /// Convert a trace to CSV, TRC or ASC (format taken from the extensions)
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+        /// Annotate frames with message and signal names from a DBC, SYM or LDF file
+        #[arg(long)]
+        dbc: Option<PathBuf>,
+    },
+    /// Flash a firmware image (.hex or .bin) through the UDS bootloader
+    Flash {
+        #[command(flatten)]
+        bus: BusArgs,
+        firmware: PathBuf,
+        /// Request (tester to ECU) ID, hex
+        #[arg(long, value_parser = parse_hex)]
+        tx_id: u32,
+        /// Response (ECU to tester) ID, hex
+        #[arg(long, value_parser = parse_hex)]
+        rx_id: u32,
+        /// Load address of .bin images, hex
+        #[arg(long, value_parser = parse_hex, default_value = "0")]
+        base_address: u32,
+        /// Pad ISO-TP frames to 8 bytes with this value, hex
+        #[arg(long, value_parser = parse_hex_byte)]
+        padding: Option<u8>,
+    },
+}
+
+fn parse_hex(value: &str) -> Result<u32, BootCanError> {
+    let digits = value.trim_start_matches("0x").trim_start_matches("0X");
+    u32::from_str_radix(digits, 16)
+        .map_err(|e| BootCanError::InvalidInput(format!("Invalid hex value {}: {}", value, e)))
+}
+
+fn parse_hex_byte(value: &str) -> Result<u8, BootCanError> {
+    let value = parse_hex(value)?;
+    u8::try_from(value).map_err(|_| BootCanError::InvalidInput(format!("0x{:X} does not fit in a byte", value)))
+}
+
+/// Parse a frame in cansend notation; 8-digit IDs are extended
+fn parse_frame(value: &str) -> Result<CanFrame, BootCanError> {
+    let (id, data) = value
+        .split_once('#')
+        .ok_or_else(|| BootCanError::InvalidInput(format!("Expected ID#DATA, got {}", value)))?;
+    let frame_id = parse_hex(id)?;
+    let is_extended = id.len() == 8 || frame_id > 0x7FF;
+    if frame_id > 0x1FFF_FFFF {
+        return Err(BootCanError::InvalidInput(format!("ID 0x{:X} is out of range", frame_id)));
+    }
+
+    if data.eq_ignore_ascii_case("r") {
+        let mut frame = CanFrame::new(frame_id, &[]);
+        frame.is_extended = is_extended;
+        frame.is_remote = true;
+        return Ok(frame);
+    }
+
+    let digits: String = data.chars().filter(|c| *c != '.').collect();
+    if !digits.len().is_multiple_of(2) || digits.len() > 16 {
+        return Err(BootCanError::InvalidInput(format!("Invalid data {}: expected up to 8 hex bytes", data)));
+    }
+    let bytes = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| BootCanError::InvalidInput(format!("Invalid data {}: {}", data, e)))?;
+
+    Ok(if is_extended {
+        CanFrame::new_extended(frame_id, &bytes)
+    } else {
+        CanFrame::new(frame_id, &bytes)
+    })
+}
+
+fn trace_format(path: &Path) -> Result<TraceFormat, BootCanError> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(TraceFormat::from_extension)
+        .ok_or_else(|| BootCanError::InvalidInput(format!("{}: expected a .csv, .trc or .asc file", path.display())))
+}
+
+fn load_database(path: &Path) -> Result<DbcDatabase, BootCanError> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("sym") => SymParser::parse_file(path),
+        Some("ldf") => LdfParser::parse_file(path),
+        _ => DbcParser::parse_file(path),
+    }
+}
+
+/// Connect an interface and start receiving on it
+async fn open_channel(bus: &BusArgs) -> Result<ChannelHandle, BootCanError> {
+    let channel = ChannelHandle::spawn(&bus.interface, StatsEmission::default(), bus.buffer.max(1));
+    let config = ChannelConfig {
+        interface_id: bus.interface.clone(),
+        bitrate: bus.bitrate,
+        listen_only: false,
+    };
+    channel.connect(config, Box::new(|_| {})).await?;
+    Ok(channel)
+}
+
+async fn close_channel(channel: &ChannelHandle) -> Result<(), BootCanError> {
+    channel.disconnect().await
+}
+
+fn format_frame(frame: &CanFrame) -> String {
+    let id = if frame.is_extended {
+        format!("{:08X}", frame.id)
+    } else {
+        format!("{:03X}", frame.id)
+    };
+    let data = if frame.is_remote {
+        "remote request".to_string()
+    } else {
+        frame.data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ")
+    };
+    format!(
+        "({:>12.6}) {} {} {:>8} [{}] {}",
+        frame.timestamp, frame.channel, frame.direction, id, frame.dlc, data
+    )
+}
+
+/// Wait for the next frame on a channel; None once Ctrl-C was pressed
+async fn next_frame(rx: &mut FrameSubscriber) -> Option<CanFrame> {
+    tokio::select! {
+        frame = rx.recv() => frame,
+        _ = tokio::signal::ctrl_c() => None,
+    }
+}
+
+/// Tell how many frames were lost because the output was too slow
+fn report_dropped(rx: &FrameSubscriber) {
+    if rx.dropped() > 0 {
+        eprintln!("Lost {} frames, output too slow (see --buffer)", rx.dropped());
+    }
+}
+
+async fn dump(
+    bus: BusArgs,
+    dbc: Option<PathBuf>,
+    ids: Vec<u32>,
+    json: bool,
+    count: Option<u64>,
+) -> Result<(), BootCanError> {
+    let database = dbc.as_deref().map(load_database).transpose()?;
+    let channel = open_channel(&bus).await?;
+    let mut rx = channel.subscribe("Dump");
+
+    let mut printed = 0;
+    while count.is_none_or(|count| printed < count) {
+        let Some(frame) = next_frame(&mut rx).await else {
+            break;
+        };
+        if !ids.is_empty() && !ids.contains(&frame.id) {
+            continue;
+        }
+        let signals = database
+            .as_ref()
+            .map(|db| db.decode_message(frame.id, &frame.data))
+            .unwrap_or_default();
+
+        if json {
+            let mut value = serde_json::to_value(&frame).map_err(|e| BootCanError::Other(e.to_string()))?;
+            if database.is_some() {
+                value["signals"] = serde_json::to_value(&signals).map_err(|e| BootCanError::Other(e.to_string()))?;
+            }
+            println!("{}", value);
+        } else {
+            println!("{}", format_frame(&frame));
+            for signal in &signals {
+                let value = signal
+                    .value_name
+                    .clone()
+                    .unwrap_or_else(|| format!("{} {}", signal.physical_value, signal.unit));
+                println!("    {} = {}", signal.name, value.trim_end());
+            }
+        }
+        printed += 1;
+    }
+
+    report_dropped(&rx);
+    close_channel(&channel).await
+}
+
+async fn send(bus: BusArgs, frame: CanFrame, count: u64, interval_ms: u64) -> Result<(), BootCanError> {
+    let channel = open_channel(&bus).await?;
+    let mut link = ChannelLink::new(channel.clone());
+    for i in 0..count {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+        }
+        link.send(frame.clone()).await?;
+    }
+    close_channel(&channel).await
+}
+
+async fn log_trace(bus: BusArgs, output: PathBuf, duration_s: Option<u64>) -> Result<(), BootCanError> {
+    let format = trace_format(&output)?;
+    let mut logger = TraceLogger::new(TraceLoggerConfig {
+        format,
+        file_path: output.clone(),
+        ..TraceLoggerConfig::default()
+    });
+    let sender = logger
+        .get_sender()
+        .ok_or_else(|| BootCanError::NotConnected("Trace logger unavailable".to_string()))?;
+    logger.start().await?;
+
+    let channel = open_channel(&bus).await?;
+    let mut rx = channel.subscribe("Trace logger");
+    eprintln!("Logging {} to {}, press Ctrl-C to stop", bus.interface, output.display());
+
+    let deadline = duration_s.map(|s| tokio::time::Instant::now() + Duration::from_secs(s));
+    let mut frames = 0u64;
+    loop {
+        let frame = match deadline {
+            Some(deadline) => tokio::select! {
+                frame = next_frame(&mut rx) => frame,
+                _ = tokio::time::sleep_until(deadline) => None,
+            },
+            None => next_frame(&mut rx).await,
+        };
+        let Some(frame) = frame else {
+            break;
+        };
+        if sender.send(frame).is_err() {
+            break;
+        }
+        frames += 1;
+    }
+
+    drop(sender);
+    close_channel(&channel).await?;
+    logger.stop().await?;
+    eprintln!("Logged {} frames", frames);
+    report_dropped(&rx);
+    Ok(())
+}
+
+async fn replay(bus: BusArgs, input: PathBuf, speed: f64) -> Result<(), BootCanError> {
+    let mut player = TracePlayer::new();
+    let total = player.load_file(input, None, None).await?;
+    player.set_speed(speed);
+    player.start()?;
+
+    let channel = open_channel(&bus).await?;
+    let mut link = ChannelLink::new(channel.clone());
+    eprintln!("Replaying {} frames on {}", total, bus.interface);
+
+    let mut sent = 0;
+    while let Some((frame, delay)) = player.get_next_frame() {
+        if let Err(e) = link.send(frame).await {
+            tracing::warn!("Replay: {}", e);
+        } else {
+            sent += 1;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    close_channel(&channel).await?;
+    eprintln!("Sent {} of {} frames", sent, total);
+    Ok(())
+}
+
+async fn convert(input: PathBuf, output: PathBuf, dbc: Option<PathBuf>) -> Result<(), BootCanError> {
+    let format = trace_format(&output)?;
+    let database = dbc.as_deref().map(load_database).transpose()?;
+    let mut player = TracePlayer::new();
+    if player.load_file(input.clone(), None, None).await? == 0 {
+        return Err(BootCanError::NotFound(format!("No frames found in {}", input.display())));
+    }
+    let frames = player.get_all_frames();
+
+    let file = std::fs::File::create(&output)
+        .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", output.display(), e)))?;
+    let out = std::io::BufWriter::new(file);
+    // One database for the frames of every channel
+    let lookup = |_: &str| database.as_ref();
+    if database.is_some() {
+        trace_export::write_trace(out, format, &frames, Some(&lookup))?;
+    } else {
+        trace_export::write_trace(out, format, &frames, None)?;
+    }
+    eprintln!("Wrote {} frames to {}", frames.len(), output.display());
+    Ok(())
+}
+
+async fn flash(
+    bus: BusArgs,
+    firmware: PathBuf,
+    isotp: IsoTpConfig,
+    base_address: u32,
+) -> Result<(), BootCanError> {
+    let image = FirmwareImage::load(&firmware, base_address)?;
+    let total = image.total_size();
+    let channel = open_channel(&bus).await?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = cancel_tx.send(true);
+        }
+    });
+
+    let mut link = ChannelLink::new(channel.clone());
+    let mut protocol = BootloaderTarget::Uds(isotp).connect(&mut link);
+    let mut last_stage = None;
+    let result = bootloader::run_flash(protocol.as_mut(), &image, &cancel_rx, |stage, done| {
+        if last_stage != Some(stage) {
+            eprintln!();
+            last_stage = Some(stage);
+        }
+        match stage {
+            FlashStage::Program => eprint!("\rProgram: {}/{} bytes", done, total),
+            stage => eprint!("{:?}", stage),
+        }
+    })
+    .await;
+    eprintln!();
+    drop(protocol);
+
+    close_channel(&channel).await?;
+    result
+}
+
+async fn run(command: Command) -> Result<(), BootCanError> {
+    match command {
+        Command::Interfaces => {
+            for interface in enumerate_interfaces() {
+                let status = if interface.available { "" } else { " (unavailable)" };
+                println!("{:<12} {:<10} {}{}", interface.id, interface.interface_type, interface.name, status);
+            }
+            Ok(())
+        }
+        Command::Dump {
+            bus,
+            dbc,
+            ids,
+            json,
+            count,
+        } => dump(bus, dbc, ids, json, count).await,
+        Command::Send {
+            bus,
+            frame,
+            count,
+            interval_ms,
+        } => send(bus, frame, count, interval_ms).await,
+        Command::Log { bus, output, duration_s } => log_trace(bus, output, duration_s).await,
+        Command::Replay { bus, input, speed } => replay(bus, input, speed).await,
+        Command::Convert { input, output, dbc } => convert(input, output, dbc).await,
+        Command::Flash {
+            bus,
+            firmware,
+            tx_id,
+            rx_id,
+            base_address,
+            padding,
+        } => {
+            let isotp = IsoTpConfig {
+                tx_id,
+                rx_id,
+                is_extended: tx_id > 0x7FF,
+                padding,
+                block_size: 0,
+                st_min: 0,
+                max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            };
+            flash(bus, firmware, isotp, base_address).await
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let level = if cli.verbose { "info" } else { "warn" };
+    // Logs go to stderr, stdout carries captured frames
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level)))
+        .with_writer(std::io::stderr)
+        .init();
+
+    match run(cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frame() {
+        let frame = parse_frame("123#DEADBEEF").unwrap();
+        assert_eq!(frame.id, 0x123);
+        assert!(!frame.is_extended);
+        assert_eq!(frame.data, vec![0xDE, 0xAD, 0xBE, 0xEF]);
+
+        let frame = parse_frame("00000123#11.22").unwrap();
+        assert!(frame.is_extended);
+        assert_eq!(frame.data, vec![0x11, 0x22]);
+
+        let frame = parse_frame("7DF#R").unwrap();
+        assert!(frame.is_remote);
+        assert!(frame.data.is_empty());
+
+        assert!(parse_frame("123").is_err());
+        assert!(parse_frame("123#ABC").is_err());
+        assert!(parse_frame("123#001122334455667788").is_err());
+        assert!(parse_frame("3FFFFFFF#00").is_err());
+    }
+
+    #[test]
+    fn test_cli_arguments() {
+        let cli = Cli::try_parse_from([
+            "bootcan-cli", "flash", "-i", "can0", "app.hex", "--tx-id", "7E0", "--rx-id", "0x7E8", "--padding", "CC",
+        ])
+        .unwrap();
+        let Command::Flash {
+            bus,
+            tx_id,
+            rx_id,
+            padding,
+            ..
+        } = cli.command
+        else {
+            panic!("expected flash command");
+        };
+        assert_eq!(bus.bitrate, 500_000);
+        assert_eq!((tx_id, rx_id, padding), (0x7E0, 0x7E8, Some(0xCC)));
+
+        assert!(Cli::try_parse_from(["bootcan-cli", "send", "-i", "vcan0", "zz#00"]).is_err());
+        assert_eq!(trace_format(Path::new("out.TRC")).unwrap(), TraceFormat::Trc);
+        assert_eq!(trace_format(Path::new("out.asc")).unwrap(), TraceFormat::Asc);
+        assert!(trace_format(Path::new("out.blf")).is_err());
+    }
+}
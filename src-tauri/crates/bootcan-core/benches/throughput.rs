@@ -0,0 +1,136 @@
+//! Frames per second through the receive path
+//!
+//! `hot_path` measures the filter, DBC decoding and event batching on their
+//! own; `pipeline` runs a channel connected to the synthetic source and
+//! measures frames received, filtered, broadcast, decoded and batched.
+//!
+//! Run with `cargo bench -p bootcan-core`; compare against a saved baseline
+//! with `--save-baseline main` and `--baseline main`.
+
+use bootcan_core::core::bus_stats::StatsEmission;
+use bootcan_core::core::channel::{ChannelConfig, ChannelHandle};
+use bootcan_core::core::dbc::{DbcDatabase, DbcParser};
+use bootcan_core::core::filter::{FilterLogic, FilterRule, FilterSet};
+use bootcan_core::core::frame_batch::{FrameBatcher, MAX_PENDING_FRAMES};
+use bootcan_core::core::subscriber::DEFAULT_BROADCAST_CAPACITY;
+use bootcan_core::hal::synthetic::{synthetic_frame, SYNTHETIC_FIRST_ID, SYNTHETIC_ID_COUNT};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Frames per measured iteration
+const FRAMES: u64 = 10_000;
+/// Frames between two flushes of the event batcher
+const FLUSH_EVERY: u64 = 1_000;
+
+/// A message with four signals for every synthetic ID
+fn database() -> DbcDatabase {
+    let mut dbc = String::from("VERSION \"\"\n\n");
+    for i in 0..SYNTHETIC_ID_COUNT {
+        dbc.push_str(&format!("BO_ {} Message{}: 8 ECU\n", SYNTHETIC_FIRST_ID + i, i));
+        dbc.push_str(" SG_ Counter : 0|16@1+ (1,0) [0|65535] \"\" Vector__XXX\n");
+        dbc.push_str(" SG_ Speed : 16|16@1+ (0.1,0) [0|6553.5] \"km/h\" Vector__XXX\n");
+        dbc.push_str(" SG_ Torque : 32|16@1- (0.5,0) [-16384|16383.5] \"Nm\" Vector__XXX\n");
+        dbc.push_str(" SG_ Flags : 48|8@1+ (1,0) [0|255] \"\" Vector__XXX\n\n");
+    }
+    DbcParser::parse(&dbc).expect("valid DBC")
+}
+
+/// Filter passing every synthetic frame after checking a few rules
+fn filter() -> FilterSet {
+    FilterSet::new(
+        vec![
+            FilterRule::IdRange { min: SYNTHETIC_FIRST_ID, max: SYNTHETIC_FIRST_ID + SYNTHETIC_ID_COUNT },
+            FilterRule::DlcRange { min: 1, max: 8 },
+            FilterRule::Direction { rx: true, tx: true },
+        ],
+        FilterLogic::And,
+    )
+}
+
+fn hot_path(c: &mut Criterion) {
+    let frames: Vec<_> = (0..FRAMES).map(synthetic_frame).collect();
+    let filter = filter();
+    let db = database();
+
+    let mut group = c.benchmark_group("hot_path");
+    group.throughput(Throughput::Elements(FRAMES));
+    group.bench_function("filter", |b| {
+        b.iter(|| frames.iter().filter(|frame| filter.matches(frame)).count())
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                black_box(db.decode_message(frame.id, &frame.data));
+            }
+        })
+    });
+    group.bench_function("batch", |b| {
+        let mut batcher = FrameBatcher::new("bench", MAX_PENDING_FRAMES);
+        b.iter(|| {
+            for chunk in frames.chunks(FLUSH_EVERY as usize) {
+                chunk.iter().for_each(|frame| batcher.push(frame));
+                if let Some(batch) = batcher.flush() {
+                    batcher.recycle(black_box(batch).frames);
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+fn pipeline(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let db = database();
+    let batcher = Arc::new(Mutex::new(FrameBatcher::new("bench", MAX_PENDING_FRAMES)));
+    let pending = batcher.clone();
+    let channel = runtime.block_on(async {
+        let channel = ChannelHandle::spawn("bench", StatsEmission::default(), DEFAULT_BROADCAST_CAPACITY);
+        channel.set_filter(filter()).await.expect("filter");
+        let config = ChannelConfig {
+            interface_id: "synth0".to_string(),
+            // As fast as the channel takes the frames
+            bitrate: u32::MAX,
+            listen_only: false,
+        };
+        channel
+            .connect(config, Box::new(move |frame| pending.lock().push(frame)))
+            .await
+            .expect("connect");
+        channel
+    });
+
+    let mut group = c.benchmark_group("pipeline");
+    group.throughput(Throughput::Elements(FRAMES));
+    group.sample_size(20);
+    group.measurement_time(Duration::from_secs(10));
+    group.bench_function("receive_filter_broadcast_decode_batch", |b| {
+        b.iter_custom(|iters| {
+            runtime.block_on(async {
+                let mut rx = channel.subscribe("bench");
+                let start = Instant::now();
+                let mut decoded = 0;
+                // Frames lost to a lagging subscriber are not counted
+                while decoded < iters * FRAMES {
+                    let frame = rx.recv().await.expect("channel closed");
+                    black_box(db.decode_message(frame.id, &frame.data));
+                    decoded += 1;
+                    if decoded % FLUSH_EVERY == 0 {
+                        let mut batcher = batcher.lock();
+                        if let Some(batch) = batcher.flush() {
+                            batcher.recycle(batch.frames);
+                        }
+                    }
+                }
+                start.elapsed()
+            })
+        })
+    });
+    group.finish();
+
+    runtime.block_on(channel.disconnect()).expect("disconnect");
+}
+
+criterion_group!(benches, hot_path, pipeline);
+criterion_main!(benches);
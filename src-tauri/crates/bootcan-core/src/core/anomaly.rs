@@ -0,0 +1,224 @@
+//! Lightweight bus anomaly detection
+//!
+//! After a learning period the detector flags IDs never seen before, DLC
+//! changes on known IDs and sudden rate changes. A rate change is reported
+//! when a short moving average of an ID's period leaves the band of
+//! `rate_change_factor` around its long-term average, and again only after
+//! it has returned to the band.
+
+use super::message::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Weight of a new period in the long-term and short-term averages
+const BASELINE_ALPHA: f64 = 0.02;
+const RECENT_ALPHA: f64 = 0.3;
+/// Periods needed before an ID's rate is judged
+const MIN_PERIODS: u32 = 10;
+
+fn default_learning_period_s() -> f64 {
+    5.0
+}
+
+fn default_rate_change_factor() -> f64 {
+    3.0
+}
+
+/// Settings of the anomaly monitor
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyConfig {
+    /// Time after the first frame during which IDs are learned without alerts
+    #[serde(default = "default_learning_period_s")]
+    pub learning_period_s: f64,
+    /// Ratio between recent and usual period that counts as a rate change
+    #[serde(default = "default_rate_change_factor")]
+    pub rate_change_factor: f64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            learning_period_s: default_learning_period_s(),
+            rate_change_factor: default_rate_change_factor(),
+        }
+    }
+}
+
+/// What was unusual about a frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AnomalyKind {
+    /// ID not seen during learning or since
+    NewId,
+    /// Frame length differs from the previous frame of the ID
+    DlcChange { previous: u8, current: u8 },
+    /// Period moved away from its usual value
+    #[serde(rename_all = "camelCase")]
+    RateChange { usual_period_ms: f64, recent_period_ms: f64 },
+}
+
+/// Anomaly found on a channel, emitted as `bus-anomaly`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusAnomaly {
+    pub channel_id: String,
+    pub id: u32,
+    pub is_extended: bool,
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub kind: AnomalyKind,
+}
+
+#[derive(Debug, Clone)]
+struct IdProfile {
+    dlc: u8,
+    last_seen: f64,
+    periods: u32,
+    baseline_ms: f64,
+    recent_ms: f64,
+    rate_alert: bool,
+}
+
+/// Per-channel anomaly detector
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    channel_id: String,
+    config: AnomalyConfig,
+    learning_until: Option<f64>,
+    profiles: HashMap<(u32, bool), IdProfile>,
+}
+
+impl AnomalyDetector {
+    pub fn new(channel_id: &str, config: AnomalyConfig) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+            config,
+            learning_until: None,
+            profiles: HashMap::new(),
+        }
+    }
+
+    /// Check a frame against the learned profile of its ID and update the profile
+    pub fn process(&mut self, frame: &CanFrame) -> Vec<BusAnomaly> {
+        let now = frame.timestamp;
+        let learning_until = *self.learning_until.get_or_insert(now + self.config.learning_period_s);
+        let learning = now < learning_until;
+        let factor = self.config.rate_change_factor.max(1.0);
+
+        let mut kinds = Vec::new();
+        match self.profiles.get_mut(&(frame.id, frame.is_extended)) {
+            None => {
+                if !learning {
+                    kinds.push(AnomalyKind::NewId);
+                }
+                self.profiles.insert(
+                    (frame.id, frame.is_extended),
+                    IdProfile {
+                        dlc: frame.dlc,
+                        last_seen: now,
+                        periods: 0,
+                        baseline_ms: 0.0,
+                        recent_ms: 0.0,
+                        rate_alert: false,
+                    },
+                );
+            }
+            Some(profile) => {
+                if profile.dlc != frame.dlc {
+                    kinds.push(AnomalyKind::DlcChange {
+                        previous: profile.dlc,
+                        current: frame.dlc,
+                    });
+                    profile.dlc = frame.dlc;
+                }
+
+                let period = (now - profile.last_seen).max(0.0) * 1000.0;
+                profile.last_seen = now;
+                if profile.periods == 0 {
+                    profile.baseline_ms = period;
+                    profile.recent_ms = period;
+                } else {
+                    profile.recent_ms += RECENT_ALPHA * (period - profile.recent_ms);
+                }
+                profile.periods = profile.periods.saturating_add(1);
+
+                if profile.periods > MIN_PERIODS && profile.baseline_ms > 0.0 {
+                    let ratio = profile.recent_ms / profile.baseline_ms;
+                    let outside = ratio > factor || ratio < 1.0 / factor;
+                    if outside && !profile.rate_alert {
+                        kinds.push(AnomalyKind::RateChange {
+                            usual_period_ms: profile.baseline_ms,
+                            recent_period_ms: profile.recent_ms,
+                        });
+                    }
+                    profile.rate_alert = outside;
+                }
+                // Keep the usual period from following a changed rate while it is flagged
+                if !profile.rate_alert {
+                    profile.baseline_ms += BASELINE_ALPHA * (period - profile.baseline_ms);
+                }
+            }
+        }
+
+        kinds
+            .into_iter()
+            .map(|kind| BusAnomaly {
+                channel_id: self.channel_id.clone(),
+                id: frame.id,
+                is_extended: frame.is_extended,
+                timestamp: now,
+                kind,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: u32, len: usize, timestamp: f64) -> CanFrame {
+        let mut frame = CanFrame::new(id, &vec![0; len]);
+        frame.timestamp = timestamp;
+        frame
+    }
+
+    #[test]
+    fn test_new_id_and_dlc_change() {
+        let mut detector = AnomalyDetector::new("can0", AnomalyConfig::default());
+        assert!(detector.process(&frame(0x100, 8, 0.0)).is_empty());
+        assert!(detector.process(&frame(0x200, 8, 4.0)).is_empty());
+
+        let anomalies = detector.process(&frame(0x300, 8, 6.0));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!((anomalies[0].id, &anomalies[0].kind), (0x300, &AnomalyKind::NewId));
+        assert!(detector.process(&frame(0x300, 8, 6.1)).is_empty());
+
+        let anomalies = detector.process(&frame(0x100, 4, 6.2));
+        assert_eq!(anomalies[0].kind, AnomalyKind::DlcChange { previous: 8, current: 4 });
+    }
+
+    #[test]
+    fn test_rate_change_reported_once() {
+        let mut detector = AnomalyDetector::new("can0", AnomalyConfig::default());
+        let mut t = 0.0;
+        for _ in 0..50 {
+            assert!(detector.process(&frame(0x100, 8, t)).is_empty());
+            t += 0.1;
+        }
+        // The ECU starts sending ten times as fast
+        let mut reports = Vec::new();
+        for _ in 0..50 {
+            reports.extend(detector.process(&frame(0x100, 8, t)));
+            t += 0.01;
+        }
+        assert_eq!(reports.len(), 1);
+        let AnomalyKind::RateChange { usual_period_ms, recent_period_ms } = reports[0].kind else {
+            panic!("expected a rate change");
+        };
+        // The usual period only drifts a little before the change is flagged
+        assert!(usual_period_ms > 90.0 && usual_period_ms <= 100.0);
+        assert!(recent_period_ms < 100.0 / 3.0);
+    }
+}
@@ -0,0 +1,175 @@
+//! Declarative request/response emulation
+//!
+//! Rules match received frames by ID and byte pattern and answer with a frame
+//! built from a template, which can copy request bytes and add constants.
+
+use super::frame_link::FrameLink;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// One byte of a response template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TemplateByte {
+    /// Fixed value
+    Const { value: u8 },
+    /// Request byte at `index` plus `add` (wrapping)
+    Request {
+        index: usize,
+        #[serde(default)]
+        add: u8,
+    },
+}
+
+/// Request pattern and the response it triggers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseRule {
+    pub request_id: u32,
+    #[serde(default)]
+    pub request_extended: bool,
+    /// Expected leading request bytes (None matches any value)
+    #[serde(default)]
+    pub request_data: Vec<Option<u8>>,
+    pub response_id: u32,
+    #[serde(default)]
+    pub response_extended: bool,
+    pub response: Vec<TemplateByte>,
+    /// Delay before the response is sent
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+impl ResponseRule {
+    fn matches(&self, frame: &CanFrame) -> bool {
+        frame.id == self.request_id
+            && frame.is_extended == self.request_extended
+            && !frame.is_remote
+            && frame.data.len() >= self.request_data.len()
+            && self
+                .request_data
+                .iter()
+                .zip(&frame.data)
+                .all(|(expected, byte)| expected.is_none_or(|e| e == *byte))
+    }
+
+    /// Build the response to `request` (None if it references a missing request byte)
+    fn render(&self, request: &CanFrame) -> Option<CanFrame> {
+        let data = self
+            .response
+            .iter()
+            .map(|byte| match *byte {
+                TemplateByte::Const { value } => Some(value),
+                TemplateByte::Request { index, add } => request.data.get(index).map(|b| b.wrapping_add(add)),
+            })
+            .collect::<Option<Vec<u8>>>()?;
+
+        Some(if self.response_extended {
+            CanFrame::new_extended(self.response_id, &data)
+        } else {
+            CanFrame::new(self.response_id, &data)
+        })
+    }
+}
+
+/// Rule set answering frames; the first matching rule wins
+pub struct AutoResponder {
+    rules: Vec<ResponseRule>,
+}
+
+impl AutoResponder {
+    pub fn new(rules: Vec<ResponseRule>) -> Result<Self, BootCanError> {
+        for (i, rule) in rules.iter().enumerate() {
+            if rule.response.len() > 8 || rule.request_data.len() > 8 {
+                return Err(BootCanError::InvalidInput(format!(
+                    "Rule {}: request and response are limited to 8 bytes", i + 1
+                )));
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    /// Response to a received frame and the delay before sending it
+    pub fn respond(&self, frame: &CanFrame) -> Option<(CanFrame, Duration)> {
+        let rule = self.rules.iter().find(|rule| rule.matches(frame))?;
+        match rule.render(frame) {
+            Some(response) => Some((response, Duration::from_millis(rule.delay_ms))),
+            None => {
+                tracing::debug!("Auto-responder: request 0x{:X} too short for its response template", frame.id);
+                None
+            }
+        }
+    }
+}
+
+/// Answer frames received on `link` until cancelled
+///
+/// Responses are sent in request order, so a delayed response holds back later ones.
+pub async fn run_auto_responder<L: FrameLink>(
+    mut link: L,
+    responder: AutoResponder,
+    mut cancel: watch::Receiver<bool>,
+) -> Result<(), BootCanError> {
+    loop {
+        let frame = tokio::select! {
+            frame = link.recv(Duration::from_secs(1)) => frame?,
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+
+        let Some((response, delay)) = frame.and_then(|f| responder.respond(&f)) else {
+            continue;
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        link.send(response).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule() -> ResponseRule {
+        // Echo a counter request with byte 1 incremented
+        ResponseRule {
+            request_id: 0x600,
+            request_extended: false,
+            request_data: vec![Some(0x40), None],
+            response_id: 0x580,
+            response_extended: false,
+            response: vec![
+                TemplateByte::Const { value: 0x60 },
+                TemplateByte::Request { index: 1, add: 1 },
+                TemplateByte::Request { index: 2, add: 0 },
+            ],
+            delay_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_template_response() {
+        let responder = AutoResponder::new(vec![rule()]).unwrap();
+        let (response, delay) = responder.respond(&CanFrame::new(0x600, &[0x40, 0xFF, 0x07])).unwrap();
+        assert_eq!(response.id, 0x580);
+        assert_eq!(response.data, vec![0x60, 0x00, 0x07]);
+        assert!(delay.is_zero());
+    }
+
+    #[test]
+    fn test_no_match() {
+        let responder = AutoResponder::new(vec![rule()]).unwrap();
+        // Wrong first byte, wrong ID, and too short for the template
+        assert!(responder.respond(&CanFrame::new(0x600, &[0x41, 0x00, 0x00])).is_none());
+        assert!(responder.respond(&CanFrame::new(0x601, &[0x40, 0x00, 0x00])).is_none());
+        assert!(responder.respond(&CanFrame::new(0x600, &[0x40, 0x00])).is_none());
+    }
+}
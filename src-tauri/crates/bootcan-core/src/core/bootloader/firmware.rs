@@ -0,0 +1,163 @@
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Contiguous block of firmware data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareSegment {
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+/// Firmware image made of one or more segments, sorted by address
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareImage {
+    pub segments: Vec<FirmwareSegment>,
+}
+
+impl FirmwareImage {
+    /// Load an Intel HEX file, or a raw binary placed at `base_address`
+    pub fn load<P: AsRef<Path>>(path: P, base_address: u32) -> Result<Self, BootCanError> {
+        let path = path.as_ref();
+        let is_hex = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("hex") || e.eq_ignore_ascii_case("ihex"));
+
+        if is_hex {
+            let content = fs::read_to_string(path)
+                .map_err(|e| BootCanError::Io(format!("Failed to read firmware file: {}", e)))?;
+            Self::parse_intel_hex(&content)
+        } else {
+            let data = fs::read(path).map_err(|e| BootCanError::Io(format!("Failed to read firmware file: {}", e)))?;
+            Ok(Self {
+                segments: vec![FirmwareSegment {
+                    address: base_address,
+                    data,
+                }],
+            })
+        }
+    }
+
+    /// Parse Intel HEX content, merging adjacent records into segments
+    pub fn parse_intel_hex(content: &str) -> Result<Self, BootCanError> {
+        let mut image = Self::default();
+        let mut base = 0u32;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let record = line
+                .strip_prefix(':')
+                .ok_or_else(|| BootCanError::Parse(format!("Line {}: missing ':'", line_num + 1)))?;
+            let bytes = decode_hex(record.as_bytes()).map_err(|e| e.context(format!("Line {}", line_num + 1)))?;
+
+            if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+                return Err(BootCanError::Parse(format!("Line {}: invalid record length", line_num + 1)));
+            }
+            if bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b)) != 0 {
+                return Err(BootCanError::Parse(format!("Line {}: checksum mismatch", line_num + 1)));
+            }
+
+            let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as u32;
+            let data = &bytes[4..bytes.len() - 1];
+            match bytes[3] {
+                0x00 => image.add(base.wrapping_add(offset), data),
+                0x01 => break,
+                0x02 if data.len() == 2 => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 4,
+                0x04 if data.len() == 2 => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16,
+                // Start address records do not carry data
+                0x03 | 0x05 => {}
+                other => {
+                    return Err(BootCanError::Parse(format!(
+                        "Line {}: unsupported record type {:02X}",
+                        line_num + 1,
+                        other
+                    )))
+                }
+            }
+        }
+
+        if image.segments.is_empty() {
+            return Err(BootCanError::Parse("Firmware file contains no data".to_string()));
+        }
+        image.segments.sort_by_key(|s| s.address);
+        Ok(image)
+    }
+
+    /// Total number of data bytes
+    pub fn total_size(&self) -> usize {
+        self.segments.iter().map(|s| s.data.len()).sum()
+    }
+
+    fn add(&mut self, address: u32, data: &[u8]) {
+        if let Some(last) = self.segments.last_mut() {
+            if last.address as usize + last.data.len() == address as usize {
+                last.data.extend_from_slice(data);
+                return;
+            }
+        }
+        self.segments.push(FirmwareSegment {
+            address,
+            data: data.to_vec(),
+        });
+    }
+}
+
+/// Bytes of a string of hex digit pairs
+fn decode_hex(digits: &[u8]) -> Result<Vec<u8>, BootCanError> {
+    if !digits.len().is_multiple_of(2) {
+        return Err(BootCanError::Parse("odd number of hex digits".to_string()));
+    }
+    let digit = |c: u8| {
+        (c as char)
+            .to_digit(16)
+            .ok_or_else(|| BootCanError::Parse(format!("invalid hex digit {:?}", c as char)))
+    };
+    digits
+        .chunks(2)
+        .map(|pair| Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_intel_hex() {
+        let hex = ":020000040800F2\n\
+                   :0400000001020304F2\n\
+                   :0400040005060708DE\n\
+                   :02010000AABB98\n\
+                   :00000001FF\n";
+        let image = FirmwareImage::parse_intel_hex(hex).unwrap();
+
+        assert_eq!(image.segments.len(), 2);
+        assert_eq!(image.segments[0].address, 0x0800_0000);
+        assert_eq!(image.segments[0].data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(image.segments[1].address, 0x0800_0100);
+        assert_eq!(image.total_size(), 10);
+    }
+
+    #[test]
+    fn test_checksum_error() {
+        assert!(FirmwareImage::parse_intel_hex(":0400000001020304F3\n").is_err());
+    }
+
+    #[test]
+    fn test_malformed_hex_digits() {
+        // A non-ASCII character where a digit pair would be split
+        let err = FirmwareImage::parse_intel_hex(":04000000010é304F2\n").unwrap_err();
+        assert_eq!(err.kind(), "parse");
+        assert!(err.message().starts_with("Line 1: invalid hex digit"));
+
+        let err = FirmwareImage::parse_intel_hex(":0400000001020304F2\n:00000001F\n").unwrap_err();
+        assert_eq!(err.message(), "Line 2: odd number of hex digits");
+    }
+}
@@ -0,0 +1,221 @@
+//! Firmware flashing over CAN
+//!
+//! Flash jobs are written against the `BootloaderProtocol` trait so the same
+//! firmware parsing, progress reporting and job handling can drive different
+//! bootloaders. UDS is the built-in backend; proprietary bootloaders add a
+//! `BootloaderTarget` variant and an implementation of the trait.
+
+pub mod firmware;
+pub mod uds;
+
+pub use firmware::FirmwareImage;
+
+use super::frame_link::FrameLink;
+use super::isotp::IsoTpConfig;
+use crate::error::BootCanError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+/// Default number of bytes passed to `program_block` at a time
+pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+/// Operations a CAN bootloader has to provide to be driven by a flash job
+#[async_trait]
+pub trait BootloaderProtocol: Send {
+    /// Put the ECU into its bootloader / programming mode
+    async fn enter_bootloader(&mut self) -> Result<(), BootCanError>;
+
+    /// Erase a memory range before programming
+    async fn erase(&mut self, address: u32, length: u32) -> Result<(), BootCanError>;
+
+    /// Program one block of data at the given address
+    async fn program_block(&mut self, address: u32, data: &[u8]) -> Result<(), BootCanError>;
+
+    /// Check the programmed image on the ECU
+    async fn verify(&mut self, image: &FirmwareImage) -> Result<(), BootCanError>;
+
+    /// Leave the bootloader and restart the application
+    async fn reset(&mut self) -> Result<(), BootCanError>;
+
+    /// Largest block handed to `program_block`
+    fn block_size(&self) -> usize {
+        DEFAULT_BLOCK_SIZE
+    }
+}
+
+/// Bootloader selected for a flash job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "protocol", rename_all = "camelCase")]
+pub enum BootloaderTarget {
+    /// UDS programming session over ISO-TP
+    Uds(IsoTpConfig),
+}
+
+impl BootloaderTarget {
+    /// Create the protocol implementation on top of a frame link
+    pub fn connect<'a>(self, link: &'a mut dyn FrameLink) -> Box<dyn BootloaderProtocol + 'a> {
+        match self {
+            Self::Uds(config) => Box::new(uds::UdsBootloader::new(link, config)),
+        }
+    }
+}
+
+/// Step of a flash job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FlashStage {
+    EnterBootloader,
+    Erase,
+    Program,
+    Verify,
+    Reset,
+    Done,
+    Failed,
+}
+
+/// Progress of a flash job, emitted to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlashProgress {
+    pub job_id: String,
+    pub stage: FlashStage,
+    pub bytes_done: usize,
+    pub bytes_total: usize,
+    pub error: Option<String>,
+}
+
+/// Flash an image; `on_progress` is called with the stage and bytes programmed so far
+pub async fn run_flash<F>(
+    protocol: &mut dyn BootloaderProtocol,
+    image: &FirmwareImage,
+    cancel: &watch::Receiver<bool>,
+    mut on_progress: F,
+) -> Result<(), BootCanError>
+where
+    F: FnMut(FlashStage, usize) + Send,
+{
+    let check_cancel = || {
+        if *cancel.borrow() {
+            Err(BootCanError::Cancelled("Flash job cancelled".to_string()))
+        } else {
+            Ok(())
+        }
+    };
+
+    on_progress(FlashStage::EnterBootloader, 0);
+    protocol.enter_bootloader().await?;
+
+    on_progress(FlashStage::Erase, 0);
+    for segment in image.segments.iter() {
+        check_cancel()?;
+        protocol.erase(segment.address, segment.data.len() as u32).await?;
+    }
+
+    let block_size = protocol.block_size().max(1);
+    let mut done = 0;
+    on_progress(FlashStage::Program, 0);
+    for segment in image.segments.iter() {
+        for (i, block) in segment.data.chunks(block_size).enumerate() {
+            check_cancel()?;
+            let address = segment.address + (i * block_size) as u32;
+            protocol.program_block(address, block).await?;
+            done += block.len();
+            on_progress(FlashStage::Program, done);
+        }
+    }
+
+    on_progress(FlashStage::Verify, done);
+    protocol.verify(image).await?;
+
+    on_progress(FlashStage::Reset, done);
+    protocol.reset().await?;
+
+    on_progress(FlashStage::Done, done);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::firmware::FirmwareSegment;
+    use super::*;
+
+    /// Bootloader that records the calls made by the flash job
+    #[derive(Default)]
+    struct RecordingBootloader {
+        calls: Vec<String>,
+    }
+
+    #[async_trait]
+    impl BootloaderProtocol for RecordingBootloader {
+        async fn enter_bootloader(&mut self) -> Result<(), BootCanError> {
+            self.calls.push("enter".to_string());
+            Ok(())
+        }
+
+        async fn erase(&mut self, address: u32, length: u32) -> Result<(), BootCanError> {
+            self.calls.push(format!("erase {:X} {}", address, length));
+            Ok(())
+        }
+
+        async fn program_block(&mut self, address: u32, data: &[u8]) -> Result<(), BootCanError> {
+            self.calls.push(format!("program {:X} {}", address, data.len()));
+            Ok(())
+        }
+
+        async fn verify(&mut self, _image: &FirmwareImage) -> Result<(), BootCanError> {
+            self.calls.push("verify".to_string());
+            Ok(())
+        }
+
+        async fn reset(&mut self) -> Result<(), BootCanError> {
+            self.calls.push("reset".to_string());
+            Ok(())
+        }
+
+        fn block_size(&self) -> usize {
+            4
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flash_job_sequence() {
+        let image = FirmwareImage {
+            segments: vec![FirmwareSegment {
+                address: 0x1000,
+                data: vec![0; 6],
+            }],
+        };
+        let (_cancel_tx, cancel_rx) = watch::channel(false);
+        let mut bootloader = RecordingBootloader::default();
+        let mut progress = vec![];
+
+        run_flash(&mut bootloader, &image, &cancel_rx, |stage, done| progress.push((stage, done)))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bootloader.calls,
+            vec!["enter", "erase 1000 6", "program 1000 4", "program 1004 2", "verify", "reset"]
+        );
+        assert_eq!(progress.last(), Some(&(FlashStage::Done, 6)));
+    }
+
+    #[tokio::test]
+    async fn test_flash_job_cancelled() {
+        let image = FirmwareImage {
+            segments: vec![FirmwareSegment {
+                address: 0,
+                data: vec![0; 4],
+            }],
+        };
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        cancel_tx.send(true).unwrap();
+        let mut bootloader = RecordingBootloader::default();
+
+        let err = run_flash(&mut bootloader, &image, &cancel_rx, |_, _| {}).await.unwrap_err();
+        assert!(matches!(err, BootCanError::Cancelled(_)));
+        assert!(err.message().contains("cancelled"));
+        assert_eq!(bootloader.calls, vec!["enter"]);
+    }
+}
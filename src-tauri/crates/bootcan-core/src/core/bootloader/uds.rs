@@ -0,0 +1,67 @@
+use super::{BootloaderProtocol, FirmwareImage};
+use crate::core::frame_link::FrameLink;
+use crate::core::isotp::IsoTpConfig;
+use crate::core::uds::{
+    UdsClient, ROUTINE_CHECK_PROGRAMMING_DEPENDENCIES, ROUTINE_ERASE_MEMORY, SESSION_PROGRAMMING,
+};
+use crate::error::BootCanError;
+use async_trait::async_trait;
+
+/// ECU reset type: hard reset
+const RESET_HARD: u8 = 0x01;
+
+/// Bootloader backend using the standard UDS programming sequence
+pub struct UdsBootloader<'a> {
+    client: UdsClient<'a>,
+}
+
+impl<'a> UdsBootloader<'a> {
+    pub fn new(link: &'a mut dyn FrameLink, config: IsoTpConfig) -> Self {
+        Self {
+            client: UdsClient::new(link, config),
+        }
+    }
+}
+
+#[async_trait]
+impl BootloaderProtocol for UdsBootloader<'_> {
+    async fn enter_bootloader(&mut self) -> Result<(), BootCanError> {
+        self.client.diagnostic_session_control(SESSION_PROGRAMMING).await
+    }
+
+    async fn erase(&mut self, address: u32, length: u32) -> Result<(), BootCanError> {
+        let mut params = vec![0x44];
+        params.extend_from_slice(&address.to_be_bytes());
+        params.extend_from_slice(&length.to_be_bytes());
+        self.client.start_routine(ROUTINE_ERASE_MEMORY, &params).await?;
+        Ok(())
+    }
+
+    async fn program_block(&mut self, address: u32, data: &[u8]) -> Result<(), BootCanError> {
+        let max_payload = self.client.request_download(address, data.len() as u32).await?;
+
+        // Block sequence counter starts at 1 and wraps to 0
+        let mut sequence = 1u8;
+        for chunk in data.chunks(max_payload) {
+            self.client.transfer_data(sequence, chunk).await?;
+            sequence = sequence.wrapping_add(1);
+        }
+
+        self.client.request_transfer_exit().await
+    }
+
+    async fn verify(&mut self, _image: &FirmwareImage) -> Result<(), BootCanError> {
+        let status = self.client.start_routine(ROUTINE_CHECK_PROGRAMMING_DEPENDENCIES, &[]).await?;
+        match status.first() {
+            Some(0x00) | None => Ok(()),
+            Some(code) => Err(BootCanError::Protocol(format!(
+                "Programming dependency check failed (status 0x{:02X})",
+                code
+            ))),
+        }
+    }
+
+    async fn reset(&mut self) -> Result<(), BootCanError> {
+        self.client.ecu_reset(RESET_HARD).await
+    }
+}
@@ -0,0 +1,245 @@
+use crate::hal::traits::{BusState, ControllerStatus};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_stats_interval_ms() -> u64 {
+    100
+}
+
+/// How often a channel emits its statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsEmission {
+    #[serde(default = "default_stats_interval_ms")]
+    pub interval_ms: u64,
+    /// Skip an emission when the statistics did not change since the previous one
+    #[serde(default)]
+    pub on_change: bool,
+}
+
+impl Default for StatsEmission {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_stats_interval_ms(),
+            on_change: false,
+        }
+    }
+}
+
+impl StatsEmission {
+    /// Shortest interval accepted
+    pub const MIN_INTERVAL_MS: u64 = 10;
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.max(Self::MIN_INTERVAL_MS))
+    }
+}
+
+/// Change of the controller error state
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateTransition {
+    pub from: BusState,
+    pub to: BusState,
+    /// Seconds since connect
+    pub timestamp: f64,
+}
+
+/// Statistics for a CAN bus channel
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusStats {
+    /// Estimated bus load percentage (0-100)
+    pub bus_load: f64,
+    /// Total number of transmitted frames
+    pub tx_count: u64,
+    /// Total number of received frames
+    pub rx_count: u64,
+    /// Total number of error frames detected
+    pub error_count: u64,
+    /// Payload bytes transmitted
+    pub tx_bytes: u64,
+    /// Payload bytes received
+    pub rx_bytes: u64,
+    /// Transmit error counter (TEC), past 255 once bus-off
+    pub tx_error_counter: u16,
+    /// Receive error counter (REC)
+    pub rx_error_counter: u16,
+    /// Controller error state as last reported by the hardware
+    pub controller_state: BusState,
+    /// Number of controller state changes since connect
+    pub state_transitions: u32,
+    /// Most recent controller state change
+    pub last_transition: Option<StateTransition>,
+}
+
+impl BusStats {
+    /// Create new empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset all counters to zero
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Account a transmitted frame with `payload_len` data bytes
+    pub fn record_tx(&mut self, payload_len: usize) {
+        self.tx_count += 1;
+        self.tx_bytes += payload_len as u64;
+    }
+
+    /// Account a received frame with `payload_len` data bytes
+    pub fn record_rx(&mut self, payload_len: usize) {
+        self.rx_count += 1;
+        self.rx_bytes += payload_len as u64;
+    }
+
+    /// Record an error
+    pub fn record_error(&mut self) {
+        self.error_count += 1;
+    }
+
+    /// Apply a controller status read from the hardware; returns the state change, if any
+    pub fn update_controller(&mut self, status: ControllerStatus, timestamp: f64) -> Option<StateTransition> {
+        if let Some(tec) = status.tx_error_counter {
+            self.tx_error_counter = tec;
+        }
+        if let Some(rec) = status.rx_error_counter {
+            self.rx_error_counter = rec;
+        }
+        if status.state == self.controller_state {
+            return None;
+        }
+        let transition = StateTransition {
+            from: self.controller_state,
+            to: status.state,
+            timestamp,
+        };
+        self.controller_state = status.state;
+        // The first report after connect is the initial state, not a change
+        if transition.from == BusState::Unknown {
+            return None;
+        }
+        self.state_transitions += 1;
+        self.last_transition = Some(transition);
+        Some(transition)
+    }
+
+    /// Update bus load estimate
+    /// This is a simplified calculation based on message rate
+    pub fn update_bus_load(&mut self, messages_per_second: f64, bitrate: u32) {
+        // Assume average message is ~100 bits (including overhead)
+        // Bus load = (bits transmitted per second) / bitrate * 100
+        let bits_per_message = 100.0;
+        let bits_per_second = messages_per_second * bits_per_message;
+        self.bus_load = (bits_per_second / bitrate as f64 * 100.0).min(100.0);
+    }
+}
+
+/// Extended statistics with timing information
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendedBusStats {
+    /// Basic statistics
+    #[serde(flatten)]
+    pub base: BusStats,
+    /// Average message rate (messages per second)
+    pub msg_rate: f64,
+    /// Timestamp of first message
+    pub first_msg_time: Option<f64>,
+    /// Timestamp of last message
+    pub last_msg_time: Option<f64>,
+    /// Number of unique message IDs seen
+    pub unique_ids: u32,
+    /// Frames subscribers lost by falling behind, since the channel was created
+    pub dropped_frames: u64,
+}
+
+impl ExtendedBusStats {
+    /// Combine the counters with the time span and ID count of the session
+    pub fn new(base: BusStats, first_msg_time: Option<f64>, last_msg_time: Option<f64>, unique_ids: u32) -> Self {
+        let total = base.tx_count + base.rx_count;
+        let msg_rate = match (first_msg_time, last_msg_time) {
+            (Some(first), Some(last)) if last > first => total as f64 / (last - first),
+            _ => 0.0,
+        };
+        Self {
+            base,
+            msg_rate,
+            first_msg_time,
+            last_msg_time,
+            unique_ids,
+            dropped_frames: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(state: BusState, tec: u16, rec: u16) -> ControllerStatus {
+        ControllerStatus {
+            state,
+            tx_error_counter: Some(tec),
+            rx_error_counter: Some(rec),
+        }
+    }
+
+    #[test]
+    fn test_controller_transitions() {
+        let mut stats = BusStats::new();
+        assert!(stats.update_controller(status(BusState::Active, 0, 0), 0.1).is_none());
+        assert!(stats.update_controller(status(BusState::Active, 40, 2), 0.2).is_none());
+        assert_eq!((stats.tx_error_counter, stats.rx_error_counter), (40, 2));
+
+        let transition = stats.update_controller(status(BusState::Passive, 130, 2), 0.3).unwrap();
+        assert_eq!((transition.from, transition.to), (BusState::Active, BusState::Passive));
+        assert!(stats.update_controller(status(BusState::BusOff, 255, 2), 0.4).is_some());
+        assert_eq!(stats.state_transitions, 2);
+        assert_eq!(stats.last_transition.unwrap().to, BusState::BusOff);
+        assert_eq!(stats.controller_state, BusState::BusOff);
+    }
+
+    #[test]
+    fn test_counters_kept_when_unreported() {
+        let mut stats = BusStats::new();
+        stats.update_controller(status(BusState::Warning, 96, 10), 0.0);
+        let state_only = ControllerStatus {
+            state: BusState::Warning,
+            ..Default::default()
+        };
+        assert!(stats.update_controller(state_only, 1.0).is_none());
+        assert_eq!((stats.tx_error_counter, stats.rx_error_counter), (96, 10));
+
+        stats.reset();
+        assert_eq!(stats.controller_state, BusState::Unknown);
+    }
+
+    #[test]
+    fn test_stats_emission() {
+        let emission: StatsEmission = serde_json::from_str(r#"{"onChange":true}"#).unwrap();
+        assert_eq!(emission.interval(), Duration::from_millis(100));
+        assert!(emission.on_change);
+        let fast = StatsEmission {
+            interval_ms: 1,
+            on_change: false,
+        };
+        assert_eq!(fast.interval(), Duration::from_millis(StatsEmission::MIN_INTERVAL_MS));
+    }
+
+    #[test]
+    fn test_extended_stats() {
+        let base = BusStats {
+            tx_count: 10,
+            rx_count: 40,
+            ..Default::default()
+        };
+        let extended = ExtendedBusStats::new(base.clone(), Some(1.0), Some(6.0), 3);
+        assert!((extended.msg_rate - 10.0).abs() < 1e-9);
+        assert_eq!(extended.unique_ids, 3);
+        assert_eq!(ExtendedBusStats::new(base, Some(1.0), Some(1.0), 1).msg_rate, 0.0);
+    }
+}
@@ -0,0 +1,295 @@
+use super::pdo::{MappedObject, PdoDirection, PdoMapping};
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// CANopen basic data types (CiA 301 data type indices)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataType {
+    Boolean,
+    Integer,
+    Unsigned,
+    Real32,
+    Real64,
+    Other,
+}
+
+impl DataType {
+    pub fn from_index(index: u16) -> Self {
+        match index {
+            0x0001 => Self::Boolean,
+            0x0002 | 0x0003 | 0x0004 | 0x0010 | 0x0012 | 0x0013 | 0x0014 | 0x0015 => Self::Integer,
+            0x0005 | 0x0006 | 0x0007 | 0x0016 | 0x0018 | 0x0019 | 0x001A | 0x001B => Self::Unsigned,
+            0x0008 => Self::Real32,
+            0x0011 => Self::Real64,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Object dictionary entry described by an EDS file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectEntry {
+    pub index: u16,
+    pub subindex: u8,
+    pub name: String,
+    pub data_type: DataType,
+    pub default_value: Option<String>,
+}
+
+/// Object dictionary of one node
+#[derive(Debug, Clone, Default)]
+pub struct ObjectDictionary {
+    pub entries: HashMap<(u16, u8), ObjectEntry>,
+}
+
+impl ObjectDictionary {
+    /// Get an entry by index and subindex
+    pub fn get(&self, index: u16, subindex: u8) -> Option<&ObjectEntry> {
+        self.entries.get(&(index, subindex))
+    }
+
+    /// Build the mapped object description for an entry, falling back to a generic name
+    pub fn mapped_object(&self, index: u16, subindex: u8, bit_length: u8) -> MappedObject {
+        let entry = self.get(index, subindex);
+        MappedObject {
+            index,
+            subindex,
+            bit_length,
+            name: entry
+                .map(|e| e.name.clone())
+                .unwrap_or_else(|| format!("0x{:04X}sub{}", index, subindex)),
+            data_type: entry.map(|e| e.data_type).unwrap_or(DataType::Unsigned),
+        }
+    }
+}
+
+/// Parsed Electronic Data Sheet
+#[derive(Debug, Clone)]
+pub struct EdsFile {
+    pub dictionary: ObjectDictionary,
+    /// Default PDO mappings with $NODEID resolved
+    pub pdo_mappings: Vec<PdoMapping>,
+}
+
+/// Parser for CANopen EDS/DCF files (INI format)
+pub struct EdsParser;
+
+impl EdsParser {
+    /// Parse an EDS file from a path
+    pub fn parse_file<P: AsRef<Path>>(path: P, node_id: u8) -> Result<EdsFile, BootCanError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| BootCanError::Io(format!("Failed to read EDS file: {}", e)))?;
+        Self::parse(&content, node_id)
+    }
+
+    /// Parse EDS content, resolving $NODEID expressions for the given node
+    pub fn parse(content: &str, node_id: u8) -> Result<EdsFile, BootCanError> {
+        let sections = Self::parse_sections(content);
+        let mut dictionary = ObjectDictionary::default();
+
+        for (section, keys) in sections.iter() {
+            let Some((index, subindex)) = Self::parse_section_name(section) else {
+                continue;
+            };
+            // Skip records/arrays themselves, their subentries carry the values
+            if subindex.is_none() && keys.contains_key("subnumber") {
+                continue;
+            }
+            let subindex = subindex.unwrap_or(0);
+
+            let data_type = keys
+                .get("datatype")
+                .and_then(|v| Self::parse_number(v, node_id))
+                .map(|v| DataType::from_index(v as u16))
+                .unwrap_or(DataType::Other);
+
+            dictionary.entries.insert(
+                (index, subindex),
+                ObjectEntry {
+                    index,
+                    subindex,
+                    name: keys.get("parametername").cloned().unwrap_or_default(),
+                    data_type,
+                    default_value: keys.get("defaultvalue").cloned(),
+                },
+            );
+        }
+
+        if dictionary.entries.is_empty() {
+            return Err(BootCanError::Parse("EDS file contains no object dictionary entries".to_string()));
+        }
+
+        let mut pdo_mappings = Vec::new();
+        for (direction, comm_base, map_base) in [
+            (PdoDirection::Rpdo, 0x1400u16, 0x1600u16),
+            (PdoDirection::Tpdo, 0x1800u16, 0x1A00u16),
+        ] {
+            for number in 0..512u16 {
+                let Some(cob_id) = Self::default_number(&dictionary, comm_base + number, 1, node_id) else {
+                    continue;
+                };
+                let Some(count) = Self::default_number(&dictionary, map_base + number, 0, node_id) else {
+                    continue;
+                };
+
+                let objects = (1..=count.min(64) as u8)
+                    .filter_map(|sub| Self::default_number(&dictionary, map_base + number, sub, node_id))
+                    .map(|value| {
+                        let (index, subindex, bit_length) = PdoMapping::split_mapping_entry(value as u32);
+                        dictionary.mapped_object(index, subindex, bit_length)
+                    })
+                    .collect();
+
+                if let Some(mapping) = PdoMapping::new(direction, number + 1, node_id, cob_id as u32, objects) {
+                    pdo_mappings.push(mapping);
+                }
+            }
+        }
+
+        Ok(EdsFile {
+            dictionary,
+            pdo_mappings,
+        })
+    }
+
+    /// Split INI content into sections of lowercase keys
+    fn parse_sections(content: &str) -> HashMap<String, HashMap<String, String>> {
+        let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut current: Option<String> = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len() - 1].trim().to_lowercase();
+                sections.entry(name.clone()).or_default();
+                current = Some(name);
+            } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+                sections
+                    .entry(section.clone())
+                    .or_default()
+                    .insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        sections
+    }
+
+    /// Parse "1a00" or "1a00sub1" section names
+    fn parse_section_name(name: &str) -> Option<(u16, Option<u8>)> {
+        let (index, sub) = match name.split_once("sub") {
+            Some((index, sub)) => (index, Some(u8::from_str_radix(sub, 16).ok()?)),
+            None => (name, None),
+        };
+        if index.len() != 4 {
+            return None;
+        }
+        Some((u16::from_str_radix(index, 16).ok()?, sub))
+    }
+
+    fn default_number(dictionary: &ObjectDictionary, index: u16, subindex: u8, node_id: u8) -> Option<u64> {
+        let value = dictionary.get(index, subindex)?.default_value.as_ref()?;
+        Self::parse_number(value, node_id)
+    }
+
+    /// Parse a numeric value, supporting hex, octal and "$NODEID+" expressions
+    fn parse_number(value: &str, node_id: u8) -> Option<u64> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+
+        let mut total = 0u64;
+        for term in value.split('+') {
+            let term = term.trim();
+            total += if term.eq_ignore_ascii_case("$NODEID") {
+                node_id as u64
+            } else if let Some(hex) = term.strip_prefix("0x").or_else(|| term.strip_prefix("0X")) {
+                u64::from_str_radix(hex, 16).ok()?
+            } else if term.len() > 1 && term.starts_with('0') {
+                u64::from_str_radix(&term[1..], 8).ok()?
+            } else {
+                term.parse::<u64>().ok()?
+            };
+        }
+        Some(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDS: &str = r#"
+[6041]
+ParameterName=Statusword
+ObjectType=0x7
+DataType=0x0006
+AccessType=ro
+PDOMapping=1
+
+[6064]
+ParameterName=Position actual value
+ObjectType=0x7
+DataType=0x0004
+AccessType=ro
+
+[1800]
+SubNumber=2
+ParameterName=TPDO communication parameter 1
+
+[1800sub1]
+ParameterName=COB-ID used by TPDO
+DataType=0x0007
+DefaultValue=$NODEID+0x180
+
+[1A00]
+SubNumber=3
+ParameterName=TPDO mapping parameter 1
+
+[1A00sub0]
+ParameterName=Number of mapped objects
+DataType=0x0005
+DefaultValue=2
+
+[1A00sub1]
+ParameterName=Mapped object 1
+DataType=0x0007
+DefaultValue=0x60410010
+
+[1A00sub2]
+ParameterName=Mapped object 2
+DataType=0x0007
+DefaultValue=0x60640020
+"#;
+
+    #[test]
+    fn test_parse_eds_pdo_mapping() {
+        let eds = EdsParser::parse(EDS, 5).unwrap();
+        assert_eq!(eds.dictionary.get(0x6041, 0).unwrap().name, "Statusword");
+
+        assert_eq!(eds.pdo_mappings.len(), 1);
+        let mapping = &eds.pdo_mappings[0];
+        assert_eq!(mapping.cob_id, 0x185);
+        assert_eq!(mapping.direction, PdoDirection::Tpdo);
+        assert_eq!(mapping.objects.len(), 2);
+        assert_eq!(mapping.objects[1].name, "Position actual value");
+        assert_eq!(mapping.objects[1].bit_length, 32);
+        assert_eq!(mapping.objects[1].data_type, DataType::Integer);
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(EdsParser::parse_number("0x180+$NODEID", 3), Some(0x183));
+        assert_eq!(EdsParser::parse_number("$NODEID+0x200", 1), Some(0x201));
+        assert_eq!(EdsParser::parse_number("42", 1), Some(42));
+        assert_eq!(EdsParser::parse_number("010", 1), Some(8));
+    }
+}
@@ -0,0 +1,39 @@
+//! CANopen (CiA 301) support
+//!
+//! Decodes NMT and heartbeat traffic per node, provides an SDO client
+//! for reading and writing object dictionary entries and decodes PDOs
+//! using mappings from EDS files or read from the node.
+
+pub mod eds;
+pub mod nmt;
+pub mod pdo;
+pub mod sdo;
+
+pub use eds::EdsParser;
+pub use nmt::{NmtCommand, NmtMonitor, NodeStatus};
+pub use pdo::{PdoDecoder, PdoMapping};
+
+use crate::error::BootCanError;
+
+/// NMT module control (master to all nodes)
+pub const COB_ID_NMT: u32 = 0x000;
+/// SDO server-to-client (response) base COB-ID
+pub const COB_ID_SDO_TX: u32 = 0x580;
+/// SDO client-to-server (request) base COB-ID
+pub const COB_ID_SDO_RX: u32 = 0x600;
+/// NMT error control (heartbeat / node guarding) base COB-ID
+pub const COB_ID_HEARTBEAT: u32 = 0x700;
+
+/// Split an 11-bit COB-ID into its function code base and node ID
+pub fn split_cob_id(cob_id: u32) -> (u32, u8) {
+    (cob_id & 0x780, (cob_id & 0x7F) as u8)
+}
+
+/// Check that a node ID is in the valid range (1-127)
+pub fn validate_node_id(node_id: u8) -> Result<(), BootCanError> {
+    if (1..=127).contains(&node_id) {
+        Ok(())
+    } else {
+        Err(BootCanError::InvalidInput(format!("Invalid CANopen node ID {} (must be 1-127)", node_id)))
+    }
+}
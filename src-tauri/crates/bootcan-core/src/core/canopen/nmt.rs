@@ -0,0 +1,210 @@
+use super::{split_cob_id, COB_ID_HEARTBEAT, COB_ID_NMT};
+use crate::core::message::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// NMT state reported in heartbeat / node guarding messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NmtState {
+    BootUp,
+    Stopped,
+    Operational,
+    PreOperational,
+    Unknown,
+}
+
+impl NmtState {
+    /// Decode the state byte (toggle bit is ignored)
+    pub fn from_byte(byte: u8) -> Self {
+        match byte & 0x7F {
+            0x00 => Self::BootUp,
+            0x04 => Self::Stopped,
+            0x05 => Self::Operational,
+            0x7F => Self::PreOperational,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// NMT module control command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NmtCommand {
+    Start,
+    Stop,
+    EnterPreOperational,
+    ResetNode,
+    ResetCommunication,
+}
+
+impl NmtCommand {
+    /// Command specifier byte
+    pub fn specifier(self) -> u8 {
+        match self {
+            Self::Start => 0x01,
+            Self::Stop => 0x02,
+            Self::EnterPreOperational => 0x80,
+            Self::ResetNode => 0x81,
+            Self::ResetCommunication => 0x82,
+        }
+    }
+
+    fn from_specifier(cs: u8) -> Option<Self> {
+        match cs {
+            0x01 => Some(Self::Start),
+            0x02 => Some(Self::Stop),
+            0x80 => Some(Self::EnterPreOperational),
+            0x81 => Some(Self::ResetNode),
+            0x82 => Some(Self::ResetCommunication),
+            _ => None,
+        }
+    }
+
+    /// Build the NMT frame addressing one node (0 = all nodes)
+    pub fn to_frame(self, node_id: u8) -> CanFrame {
+        CanFrame::new(COB_ID_NMT, &[self.specifier(), node_id])
+    }
+}
+
+/// Last known status of a CANopen node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus {
+    pub channel: String,
+    pub node_id: u8,
+    pub state: NmtState,
+    /// Timestamp of the last heartbeat or guarding response
+    pub last_seen: f64,
+    /// Measured heartbeat period in seconds
+    pub heartbeat_period: Option<f64>,
+    /// Number of boot-up messages seen from this node
+    pub boot_count: u32,
+    /// Last NMT command addressed to this node
+    pub last_command: Option<NmtCommand>,
+}
+
+/// Tracks NMT state of all nodes seen on a channel
+pub struct NmtMonitor {
+    nodes: HashMap<u8, NodeStatus>,
+}
+
+impl NmtMonitor {
+    pub fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Feed a received frame; returns the updated node status when it changed
+    /// state (boot-up, state transition, NMT command) rather than on every heartbeat
+    pub fn process(&mut self, frame: &CanFrame) -> Option<NodeStatus> {
+        if frame.is_extended || frame.is_remote {
+            return None;
+        }
+
+        if frame.id == COB_ID_NMT {
+            return self.process_command(frame);
+        }
+
+        let (function, node_id) = split_cob_id(frame.id);
+        if function != COB_ID_HEARTBEAT || node_id == 0 || frame.data.is_empty() {
+            return None;
+        }
+
+        let state = NmtState::from_byte(frame.data[0]);
+        let status = self.nodes.entry(node_id).or_insert_with(|| NodeStatus {
+            channel: frame.channel.to_string(),
+            node_id,
+            state: NmtState::Unknown,
+            last_seen: frame.timestamp,
+            heartbeat_period: None,
+            boot_count: 0,
+            last_command: None,
+        });
+
+        let changed = status.state != state || state == NmtState::BootUp;
+        if state == NmtState::BootUp {
+            status.boot_count += 1;
+            status.heartbeat_period = None;
+        } else if status.state == state {
+            status.heartbeat_period = Some(frame.timestamp - status.last_seen);
+        }
+        status.state = state;
+        status.last_seen = frame.timestamp;
+
+        if changed {
+            Some(status.clone())
+        } else {
+            None
+        }
+    }
+
+    fn process_command(&mut self, frame: &CanFrame) -> Option<NodeStatus> {
+        if frame.data.len() < 2 {
+            return None;
+        }
+        let command = NmtCommand::from_specifier(frame.data[0])?;
+        let target = frame.data[1];
+
+        let mut last = None;
+        for status in self.nodes.values_mut() {
+            if target == 0 || status.node_id == target {
+                status.last_command = Some(command);
+                last = Some(status.clone());
+            }
+        }
+        last
+    }
+
+    /// Get all known nodes sorted by node ID
+    pub fn nodes(&self) -> Vec<NodeStatus> {
+        let mut nodes: Vec<NodeStatus> = self.nodes.values().cloned().collect();
+        nodes.sort_by_key(|n| n.node_id);
+        nodes
+    }
+}
+
+impl Default for NmtMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(node_id: u8, state: u8, timestamp: f64) -> CanFrame {
+        let mut frame = CanFrame::new(COB_ID_HEARTBEAT + node_id as u32, &[state]);
+        frame.timestamp = timestamp;
+        frame
+    }
+
+    #[test]
+    fn test_heartbeat_state_changes() {
+        let mut monitor = NmtMonitor::new();
+
+        let status = monitor.process(&heartbeat(5, 0x00, 0.0)).unwrap();
+        assert_eq!(status.state, NmtState::BootUp);
+        assert_eq!(status.boot_count, 1);
+
+        let status = monitor.process(&heartbeat(5, 0x7F, 0.1)).unwrap();
+        assert_eq!(status.state, NmtState::PreOperational);
+
+        // Same state again only updates the period
+        assert!(monitor.process(&heartbeat(5, 0x7F, 0.2)).is_none());
+        let period = monitor.nodes()[0].heartbeat_period.unwrap();
+        assert!((period - 0.1).abs() < 1e-9);
+
+        let status = monitor.process(&heartbeat(5, 0x05, 0.3)).unwrap();
+        assert_eq!(status.state, NmtState::Operational);
+    }
+
+    #[test]
+    fn test_nmt_command_frame() {
+        let frame = NmtCommand::Start.to_frame(0x10);
+        assert_eq!(frame.id, 0x000);
+        assert_eq!(frame.data, vec![0x01, 0x10]);
+    }
+}
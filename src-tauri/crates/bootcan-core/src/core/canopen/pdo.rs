@@ -0,0 +1,266 @@
+use super::eds::{DataType, ObjectDictionary};
+use super::sdo::{SdoClient, ABORT_NO_OBJECT, ABORT_NO_SUBINDEX};
+use crate::core::dbc::DecodedSignal;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// COB-ID bit marking a PDO as not valid
+const COB_ID_INVALID: u32 = 0x8000_0000;
+/// COB-ID bit marking a 29-bit identifier
+const COB_ID_EXTENDED: u32 = 0x2000_0000;
+
+/// PDO direction as seen from the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PdoDirection {
+    /// Transmitted by the node
+    Tpdo,
+    /// Received by the node
+    Rpdo,
+}
+
+impl PdoDirection {
+    /// Communication and mapping parameter base indices
+    fn parameter_indices(self) -> (u16, u16) {
+        match self {
+            Self::Rpdo => (0x1400, 0x1600),
+            Self::Tpdo => (0x1800, 0x1A00),
+        }
+    }
+}
+
+/// One object mapped into a PDO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappedObject {
+    pub index: u16,
+    pub subindex: u8,
+    pub bit_length: u8,
+    pub name: String,
+    pub data_type: DataType,
+}
+
+/// PDO mapping of one node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdoMapping {
+    pub direction: PdoDirection,
+    /// PDO number (1-based, e.g. TPDO1)
+    pub number: u16,
+    pub node_id: u8,
+    pub cob_id: u32,
+    pub is_extended: bool,
+    pub objects: Vec<MappedObject>,
+}
+
+impl PdoMapping {
+    /// Create a mapping from a raw COB-ID parameter; returns None for invalid PDOs
+    pub fn new(
+        direction: PdoDirection,
+        number: u16,
+        node_id: u8,
+        cob_id_parameter: u32,
+        objects: Vec<MappedObject>,
+    ) -> Option<Self> {
+        if cob_id_parameter & COB_ID_INVALID != 0 || objects.is_empty() {
+            return None;
+        }
+        let is_extended = cob_id_parameter & COB_ID_EXTENDED != 0;
+        Some(Self {
+            direction,
+            number,
+            node_id,
+            cob_id: cob_id_parameter & if is_extended { 0x1FFF_FFFF } else { 0x7FF },
+            is_extended,
+            objects,
+        })
+    }
+
+    /// Split a mapping entry (index << 16 | subindex << 8 | bit length)
+    pub fn split_mapping_entry(value: u32) -> (u16, u8, u8) {
+        ((value >> 16) as u16, (value >> 8) as u8, value as u8)
+    }
+
+    /// Decode the mapped objects from PDO data (little-endian, packed in order)
+    pub fn decode(&self, data: &[u8]) -> Vec<DecodedSignal> {
+        let mut bit_offset = 0usize;
+        let mut values = Vec::with_capacity(self.objects.len());
+
+        for object in self.objects.iter() {
+            let length = object.bit_length as usize;
+            if length == 0 || length > 64 || bit_offset + length > data.len() * 8 {
+                break;
+            }
+
+            let mut raw: u64 = 0;
+            for bit in 0..length {
+                let position = bit_offset + bit;
+                let value = (data[position / 8] >> (position % 8)) & 1;
+                raw |= (value as u64) << bit;
+            }
+            bit_offset += length;
+
+            // Dummy mappings (index < 0x1000) only reserve space
+            if object.index < 0x1000 {
+                continue;
+            }
+
+            let (raw_value, physical_value) = match object.data_type {
+                DataType::Integer if length < 64 && raw & (1 << (length - 1)) != 0 => {
+                    let signed = (raw | !((1u64 << length) - 1)) as i64;
+                    (signed, signed as f64)
+                }
+                DataType::Integer => (raw as i64, raw as i64 as f64),
+                DataType::Real32 if length == 32 => (raw as i64, f32::from_bits(raw as u32) as f64),
+                DataType::Real64 if length == 64 => (raw as i64, f64::from_bits(raw)),
+                _ => (raw as i64, raw as f64),
+            };
+
+            values.push(DecodedSignal {
+                name: object.name.clone(),
+                raw_value,
+                physical_value,
+                unit: String::new(),
+                value_name: None,
+            });
+        }
+
+        values
+    }
+}
+
+/// Decodes PDOs on one channel using the configured mappings
+#[derive(Debug, Clone, Default)]
+pub struct PdoDecoder {
+    mappings: HashMap<u32, PdoMapping>,
+    dictionaries: HashMap<u8, ObjectDictionary>,
+}
+
+impl PdoDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace all mappings of a node
+    pub fn set_node_mappings(&mut self, node_id: u8, mappings: Vec<PdoMapping>) {
+        self.mappings.retain(|_, m| m.node_id != node_id);
+        for mapping in mappings {
+            self.mappings.insert(mapping.cob_id, mapping);
+        }
+    }
+
+    /// Store the object dictionary of a node for naming live-read mappings
+    pub fn set_dictionary(&mut self, node_id: u8, dictionary: ObjectDictionary) {
+        self.dictionaries.insert(node_id, dictionary);
+    }
+
+    /// Get the object dictionary of a node (empty if no EDS was loaded)
+    pub fn dictionary(&self, node_id: u8) -> ObjectDictionary {
+        self.dictionaries.get(&node_id).cloned().unwrap_or_default()
+    }
+
+    /// Get all mappings sorted by COB-ID
+    pub fn mappings(&self) -> Vec<PdoMapping> {
+        let mut mappings: Vec<PdoMapping> = self.mappings.values().cloned().collect();
+        mappings.sort_by_key(|m| m.cob_id);
+        mappings
+    }
+
+    /// Decode the frame with the given ID if it is a mapped PDO
+    pub fn decode(&self, id: u32, data: &[u8]) -> Option<Vec<DecodedSignal>> {
+        self.mappings.get(&id).map(|mapping| mapping.decode(data))
+    }
+}
+
+/// Read the active PDO mappings of a node via SDO (first four RPDOs and TPDOs)
+pub async fn read_pdo_mappings(
+    client: &mut SdoClient<'_>,
+    node_id: u8,
+    dictionary: &ObjectDictionary,
+) -> Result<Vec<PdoMapping>, BootCanError> {
+    let mut mappings = Vec::new();
+
+    for direction in [PdoDirection::Rpdo, PdoDirection::Tpdo] {
+        let (comm_base, map_base) = direction.parameter_indices();
+        for number in 0..4u16 {
+            let cob_id = match client.upload(comm_base + number, 1).await {
+                Ok(data) => read_u32(&data),
+                // PDO not implemented on this node
+                Err(e) if matches!(e.sdo_abort_code(), Some(ABORT_NO_OBJECT | ABORT_NO_SUBINDEX)) => continue,
+                Err(e) => return Err(e),
+            };
+
+            let count = client.upload(map_base + number, 0).await?;
+            let count = count.first().copied().unwrap_or(0).min(64);
+
+            let mut objects = Vec::with_capacity(count as usize);
+            for sub in 1..=count {
+                let entry = read_u32(&client.upload(map_base + number, sub).await?);
+                let (index, subindex, bit_length) = PdoMapping::split_mapping_entry(entry);
+                objects.push(dictionary.mapped_object(index, subindex, bit_length));
+            }
+
+            if let Some(mapping) = PdoMapping::new(direction, number + 1, node_id, cob_id, objects) {
+                mappings.push(mapping);
+            }
+        }
+    }
+
+    Ok(mappings)
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    let len = data.len().min(4);
+    bytes[..len].copy_from_slice(&data[..len]);
+    u32::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(index: u16, bit_length: u8, data_type: DataType) -> MappedObject {
+        MappedObject {
+            index,
+            subindex: 0,
+            bit_length,
+            name: format!("obj_{:04X}", index),
+            data_type,
+        }
+    }
+
+    #[test]
+    fn test_decode_pdo() {
+        let mapping = PdoMapping::new(
+            PdoDirection::Tpdo,
+            1,
+            5,
+            0x185,
+            vec![
+                object(0x6041, 16, DataType::Unsigned),
+                object(0x6064, 32, DataType::Integer),
+            ],
+        )
+        .unwrap();
+
+        let values = mapping.decode(&[0x37, 0x06, 0xFE, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].raw_value, 0x0637);
+        assert_eq!(values[1].name, "obj_6064");
+        assert_eq!(values[1].physical_value, -2.0);
+    }
+
+    #[test]
+    fn test_invalid_pdo_is_skipped() {
+        let mapping = PdoMapping::new(
+            PdoDirection::Rpdo,
+            1,
+            5,
+            0x8000_0205,
+            vec![object(0x6040, 16, DataType::Unsigned)],
+        );
+        assert!(mapping.is_none());
+    }
+}
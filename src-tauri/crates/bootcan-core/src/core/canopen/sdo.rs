@@ -0,0 +1,373 @@
+use super::{validate_node_id, COB_ID_SDO_RX, COB_ID_SDO_TX};
+use crate::core::frame_link::FrameLink;
+use crate::core::message::CanFrame;
+use crate::error::BootCanError;
+use std::time::Duration;
+
+/// Client command specifiers (upper 3 bits of byte 0)
+const CCS_DOWNLOAD_SEGMENT: u8 = 0;
+const CCS_INITIATE_DOWNLOAD: u8 = 1;
+const CCS_INITIATE_UPLOAD: u8 = 2;
+const CCS_UPLOAD_SEGMENT: u8 = 3;
+const CS_ABORT: u8 = 4;
+
+/// Server command specifiers (upper 3 bits of byte 0)
+const SCS_UPLOAD_SEGMENT: u8 = 0;
+const SCS_DOWNLOAD_SEGMENT: u8 = 1;
+const SCS_INITIATE_UPLOAD: u8 = 2;
+const SCS_INITIATE_DOWNLOAD: u8 = 3;
+
+/// Abort code sent when the server response is not understood
+const ABORT_INVALID_COMMAND: u32 = 0x0504_0001;
+/// Abort code sent when the toggle bit did not alternate
+const ABORT_TOGGLE_BIT: u32 = 0x0503_0000;
+/// Abort code sent when an upload is longer than the client accepts
+const ABORT_OUT_OF_MEMORY: u32 = 0x0504_0005;
+/// Abort code of a server without the requested object
+pub const ABORT_NO_OBJECT: u32 = 0x0602_0000;
+/// Abort code of a server without the requested sub-index
+pub const ABORT_NO_SUBINDEX: u32 = 0x0609_0011;
+
+/// Default SDO response timeout
+pub const DEFAULT_SDO_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Default limit on the length of uploaded values
+pub const DEFAULT_MAX_UPLOAD_LEN: usize = 1024 * 1024;
+
+/// SDO client talking to one node over a frame link
+pub struct SdoClient<'a> {
+    link: &'a mut dyn FrameLink,
+    node_id: u8,
+    timeout: Duration,
+    /// Longest value accepted from an upload; longer ones are aborted
+    max_upload_len: usize,
+}
+
+impl<'a> SdoClient<'a> {
+    pub fn new(link: &'a mut dyn FrameLink, node_id: u8, timeout: Duration) -> Result<Self, BootCanError> {
+        validate_node_id(node_id)?;
+        Ok(Self {
+            link,
+            node_id,
+            timeout,
+            max_upload_len: DEFAULT_MAX_UPLOAD_LEN,
+        })
+    }
+
+    /// Override the longest value accepted from an upload
+    pub fn with_max_upload_len(mut self, max_upload_len: usize) -> Self {
+        self.max_upload_len = max_upload_len;
+        self
+    }
+
+    /// Read an object dictionary entry (expedited or segmented upload)
+    pub async fn upload(&mut self, index: u16, subindex: u8) -> Result<Vec<u8>, BootCanError> {
+        let response = self
+            .request(index, subindex, [CCS_INITIATE_UPLOAD << 5, 0, 0, 0, 0])
+            .await?;
+        Self::expect_scs(&response, SCS_INITIATE_UPLOAD)?;
+
+        let command = response[0];
+        let expedited = command & 0x02 != 0;
+        let size_indicated = command & 0x01 != 0;
+
+        if expedited {
+            let len = if size_indicated {
+                4 - ((command >> 2) & 0x03) as usize
+            } else {
+                4
+            };
+            return Ok(response[4..4 + len].to_vec());
+        }
+
+        let expected_size = if size_indicated {
+            Some(u32::from_le_bytes([response[4], response[5], response[6], response[7]]) as usize)
+        } else {
+            None
+        };
+
+        if let Some(size) = expected_size.filter(|&size| size > self.max_upload_len) {
+            return Err(self.abort_too_long(index, subindex, size).await);
+        }
+
+        // The indicated size is the server's claim; grow as segments actually arrive
+        let mut data = Vec::new();
+        let mut toggle = 0u8;
+        loop {
+            let request = [(CCS_UPLOAD_SEGMENT << 5) | (toggle << 4), 0, 0, 0, 0, 0, 0, 0];
+            let segment = self.exchange(&request).await?;
+            Self::expect_scs(&segment, SCS_UPLOAD_SEGMENT)?;
+
+            if (segment[0] >> 4) & 0x01 != toggle {
+                self.abort(index, subindex, ABORT_TOGGLE_BIT).await;
+                return Err(BootCanError::Protocol("SDO upload toggle bit mismatch".to_string()));
+            }
+
+            let unused = ((segment[0] >> 1) & 0x07) as usize;
+            data.extend_from_slice(&segment[1..8 - unused]);
+            if data.len() > self.max_upload_len {
+                return Err(self.abort_too_long(index, subindex, data.len()).await);
+            }
+
+            if segment[0] & 0x01 != 0 {
+                break;
+            }
+            toggle ^= 1;
+        }
+
+        if let Some(size) = expected_size {
+            if data.len() != size {
+                return Err(BootCanError::Protocol(format!(
+                    "SDO upload size mismatch: expected {} bytes, got {}",
+                    size,
+                    data.len()
+                )));
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Write an object dictionary entry (expedited for up to 4 bytes, segmented otherwise)
+    pub async fn download(&mut self, index: u16, subindex: u8, data: &[u8]) -> Result<(), BootCanError> {
+        if data.len() <= 4 {
+            let unused = (4 - data.len()) as u8;
+            let mut payload = [(CCS_INITIATE_DOWNLOAD << 5) | (unused << 2) | 0x03, 0, 0, 0, 0];
+            payload[1..1 + data.len()].copy_from_slice(data);
+            let response = self.request(index, subindex, payload).await?;
+            return Self::expect_scs(&response, SCS_INITIATE_DOWNLOAD);
+        }
+
+        let size = (data.len() as u32).to_le_bytes();
+        let response = self
+            .request(
+                index,
+                subindex,
+                [(CCS_INITIATE_DOWNLOAD << 5) | 0x01, size[0], size[1], size[2], size[3]],
+            )
+            .await?;
+        Self::expect_scs(&response, SCS_INITIATE_DOWNLOAD)?;
+
+        let mut toggle = 0u8;
+        let mut chunks = data.chunks(7).peekable();
+        while let Some(chunk) = chunks.next() {
+            let last = chunks.peek().is_none();
+            let unused = (7 - chunk.len()) as u8;
+            let mut request = [0u8; 8];
+            request[0] = (CCS_DOWNLOAD_SEGMENT << 5) | (toggle << 4) | (unused << 1) | last as u8;
+            request[1..1 + chunk.len()].copy_from_slice(chunk);
+
+            let response = self.exchange(&request).await?;
+            Self::expect_scs(&response, SCS_DOWNLOAD_SEGMENT)?;
+            if (response[0] >> 4) & 0x01 != toggle {
+                self.abort(index, subindex, ABORT_TOGGLE_BIT).await;
+                return Err(BootCanError::Protocol("SDO download toggle bit mismatch".to_string()));
+            }
+            toggle ^= 1;
+        }
+
+        Ok(())
+    }
+
+    /// Send an initiate request carrying index/subindex and wait for the response
+    async fn request(&mut self, index: u16, subindex: u8, payload: [u8; 5]) -> Result<[u8; 8], BootCanError> {
+        let index_bytes = index.to_le_bytes();
+        let request = [
+            payload[0],
+            index_bytes[0],
+            index_bytes[1],
+            subindex,
+            payload[1],
+            payload[2],
+            payload[3],
+            payload[4],
+        ];
+        let response = self.exchange(&request).await?;
+
+        let response_index = u16::from_le_bytes([response[1], response[2]]);
+        if response[0] >> 5 != CS_ABORT && (response_index != index || response[3] != subindex) {
+            self.abort(index, subindex, ABORT_INVALID_COMMAND).await;
+            return Err(BootCanError::Protocol(format!(
+                "SDO response for 0x{:04X}:{} does not match request 0x{:04X}:{}",
+                response_index, response[3], index, subindex
+            )));
+        }
+
+        Ok(response)
+    }
+
+    /// Send one request frame and wait for the server's response frame
+    async fn exchange(&mut self, request: &[u8; 8]) -> Result<[u8; 8], BootCanError> {
+        let tx_id = COB_ID_SDO_RX + self.node_id as u32;
+        let rx_id = COB_ID_SDO_TX + self.node_id as u32;
+
+        self.link.send(CanFrame::new(tx_id, request)).await?;
+        let frame = self
+            .link
+            .recv_id(rx_id, self.timeout)
+            .await?
+            .ok_or_else(|| BootCanError::Timeout(format!("SDO timeout waiting for node {}", self.node_id)))?;
+
+        if frame.data.len() < 8 {
+            return Err(BootCanError::Protocol(format!("SDO response too short ({} bytes)", frame.data.len())));
+        }
+
+        let mut response = [0u8; 8];
+        response.copy_from_slice(&frame.data[..8]);
+
+        if response[0] >> 5 == CS_ABORT {
+            let code = u32::from_le_bytes([response[4], response[5], response[6], response[7]]);
+            return Err(BootCanError::SdoAbort {
+                code,
+                message: format!("SDO aborted by node {}: 0x{:08X} ({})", self.node_id, code, abort_description(code)),
+            });
+        }
+
+        Ok(response)
+    }
+
+    fn expect_scs(response: &[u8; 8], scs: u8) -> Result<(), BootCanError> {
+        if response[0] >> 5 == scs {
+            Ok(())
+        } else {
+            Err(BootCanError::Protocol(format!("Unexpected SDO server command 0x{:02X}", response[0])))
+        }
+    }
+
+    /// Abort an upload longer than the limit with "out of memory"
+    async fn abort_too_long(&mut self, index: u16, subindex: u8, len: usize) -> BootCanError {
+        self.abort(index, subindex, ABORT_OUT_OF_MEMORY).await;
+        BootCanError::Protocol(format!(
+            "SDO upload of 0x{:04X}:{} is {} bytes or more, over the limit of {}",
+            index, subindex, len, self.max_upload_len
+        ))
+    }
+
+    /// Abort the transfer (best effort)
+    async fn abort(&mut self, index: u16, subindex: u8, code: u32) {
+        let index_bytes = index.to_le_bytes();
+        let code_bytes = code.to_le_bytes();
+        let frame = CanFrame::new(
+            COB_ID_SDO_RX + self.node_id as u32,
+            &[
+                CS_ABORT << 5,
+                index_bytes[0],
+                index_bytes[1],
+                subindex,
+                code_bytes[0],
+                code_bytes[1],
+                code_bytes[2],
+                code_bytes[3],
+            ],
+        );
+        if let Err(e) = self.link.send(frame).await {
+            tracing::warn!("Failed to send SDO abort: {}", e);
+        }
+    }
+}
+
+/// Description of a standard SDO abort code (CiA 301)
+pub fn abort_description(code: u32) -> &'static str {
+    match code {
+        0x0503_0000 => "Toggle bit not alternated",
+        0x0504_0000 => "SDO protocol timed out",
+        0x0504_0001 => "Client/server command specifier not valid or unknown",
+        0x0504_0005 => "Out of memory",
+        0x0601_0000 => "Unsupported access to an object",
+        0x0601_0001 => "Attempt to read a write only object",
+        0x0601_0002 => "Attempt to write a read only object",
+        0x0602_0000 => "Object does not exist in the object dictionary",
+        0x0604_0041 => "Object cannot be mapped to the PDO",
+        0x0604_0042 => "The number and length of the objects to be mapped would exceed PDO length",
+        0x0604_0043 => "General parameter incompatibility reason",
+        0x0604_0047 => "General internal incompatibility in the device",
+        0x0606_0000 => "Access failed due to a hardware error",
+        0x0607_0010 => "Data type does not match, length of service parameter does not match",
+        0x0607_0012 => "Data type does not match, length of service parameter too high",
+        0x0607_0013 => "Data type does not match, length of service parameter too low",
+        0x0609_0011 => "Sub-index does not exist",
+        0x0609_0030 => "Invalid value for parameter",
+        0x0609_0031 => "Value of parameter written too high",
+        0x0609_0032 => "Value of parameter written too low",
+        0x0609_0036 => "Maximum value is less than minimum value",
+        0x0800_0000 => "General error",
+        0x0800_0020 => "Data cannot be transferred or stored to the application",
+        0x0800_0021 => "Data cannot be transferred or stored to the application because of local control",
+        0x0800_0022 => "Data cannot be transferred or stored to the application because of the present device state",
+        0x0800_0023 => "Object dictionary dynamic generation fails or no object dictionary is present",
+        0x0800_0024 => "No data available",
+        _ => "Unknown abort code",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_link::scripted::ScriptedLink;
+
+    /// Node answering each request with the next scripted response
+    fn node(responses: Vec<Vec<u8>>) -> ScriptedLink {
+        ScriptedLink::new(responses, |request, data| CanFrame::new(request.id - COB_ID_SDO_RX + COB_ID_SDO_TX, data))
+    }
+
+    #[tokio::test]
+    async fn test_expedited_upload() {
+        // Device type 0x1000:00 = 0x00020192
+        let mut link = node(vec![vec![0x43, 0x00, 0x10, 0x00, 0x92, 0x01, 0x02, 0x00]]);
+        let mut client = SdoClient::new(&mut link, 5, DEFAULT_SDO_TIMEOUT).unwrap();
+        let data = client.upload(0x1000, 0).await.unwrap();
+
+        assert_eq!(data, vec![0x92, 0x01, 0x02, 0x00]);
+        assert_eq!(link.sent[0].id, 0x605);
+        assert_eq!(link.sent[0].data, vec![0x40, 0x00, 0x10, 0x00, 0, 0, 0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_segmented_upload() {
+        // Device name 0x1008:00 = "bootCAN device" (14 bytes)
+        let mut link = node(vec![
+            vec![0x41, 0x08, 0x10, 0x00, 14, 0, 0, 0],
+            vec![0x00, b'b', b'o', b'o', b't', b'C', b'A', b'N'],
+            vec![0x11, b' ', b'd', b'e', b'v', b'i', b'c', b'e'],
+        ]);
+        let mut client = SdoClient::new(&mut link, 5, DEFAULT_SDO_TIMEOUT).unwrap();
+        let data = client.upload(0x1008, 0).await.unwrap();
+
+        assert_eq!(data, b"bootCAN device".to_vec());
+        assert_eq!(link.sent[1].data[0], 0x60);
+        assert_eq!(link.sent[2].data[0], 0x70);
+    }
+
+    #[tokio::test]
+    async fn test_overlong_upload_is_aborted() {
+        // Announces 4 GiB
+        let mut link = node(vec![vec![0x41, 0x08, 0x10, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]]);
+        let mut client = SdoClient::new(&mut link, 5, DEFAULT_SDO_TIMEOUT).unwrap();
+        assert!(client.upload(0x1008, 0).await.is_err());
+        assert_eq!(link.sent[1].data, vec![0x80, 0x08, 0x10, 0x00, 0x05, 0x00, 0x04, 0x05]);
+
+        // Announces nothing and keeps sending segments
+        let mut link = node(vec![
+            vec![0x40, 0x08, 0x10, 0x00, 0, 0, 0, 0],
+            vec![0x00, 1, 2, 3, 4, 5, 6, 7],
+            vec![0x10, 1, 2, 3, 4, 5, 6, 7],
+        ]);
+        let mut client = SdoClient::new(&mut link, 5, DEFAULT_SDO_TIMEOUT).unwrap().with_max_upload_len(10);
+        let err = client.upload(0x1008, 0).await.unwrap_err();
+        assert!(err.message().contains("over the limit of 10"));
+        assert_eq!(link.sent.len(), 4);
+        assert_eq!(link.sent[3].data[4..], [0x05, 0x00, 0x04, 0x05]);
+    }
+
+    #[tokio::test]
+    async fn test_download_abort() {
+        let mut link = node(vec![vec![0x80, 0x17, 0x10, 0x00, 0x02, 0x00, 0x01, 0x06]]);
+        let mut client = SdoClient::new(&mut link, 5, DEFAULT_SDO_TIMEOUT).unwrap();
+        let err = client.download(0x1017, 0, &[0xE8, 0x03]).await.unwrap_err();
+
+        assert_eq!(err.sdo_abort_code(), Some(0x0601_0002));
+        assert_eq!(err.kind(), "protocol");
+        assert!(err.message().contains("0x06010002"));
+        assert_eq!(link.sent[0].data[0], 0x2B);
+    }
+}
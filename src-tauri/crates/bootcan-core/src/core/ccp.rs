@@ -0,0 +1,321 @@
+//! CCP (CAN Calibration Protocol 2.1) master
+//!
+//! Talks to one ECU over a frame link using its CRO (command) and DTO
+//! (response) identifiers. Data larger than one frame is transferred
+//! through the memory transfer address (MTA) in 5-byte chunks.
+
+use super::dbc::ByteOrder;
+use super::frame_link::FrameLink;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Command codes (CCP 2.1)
+const CMD_CONNECT: u8 = 0x01;
+const CMD_SET_MTA: u8 = 0x02;
+const CMD_DNLOAD: u8 = 0x03;
+const CMD_UPLOAD: u8 = 0x04;
+const CMD_DISCONNECT: u8 = 0x07;
+const CMD_GET_DAQ_SIZE: u8 = 0x14;
+const CMD_SET_DAQ_PTR: u8 = 0x15;
+const CMD_WRITE_DAQ: u8 = 0x16;
+const CMD_GET_CCP_VERSION: u8 = 0x1B;
+
+/// Packet ID of a command return message
+const PID_CRM: u8 = 0xFF;
+/// Maximum payload of UPLOAD / DNLOAD
+const MAX_TRANSFER: usize = 5;
+
+/// Default response timeout (CCP specifies 25 ms for most commands, ECUs are often slower)
+pub const DEFAULT_CCP_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// ECU addressed by a CCP master
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CcpTarget {
+    /// Command receive object (master to ECU)
+    pub cro_id: u32,
+    /// Data transmission object (ECU to master)
+    pub dto_id: u32,
+    #[serde(default)]
+    pub is_extended: bool,
+    pub station_address: u16,
+    /// Byte order of addresses and multi-byte parameters on the ECU
+    pub byte_order: ByteOrder,
+}
+
+impl CcpTarget {
+    /// ID of a session with this target on `channel_id`, from its CRO and DTO identifiers
+    pub fn session_id(&self, channel_id: &str) -> String {
+        format!("{}:{:X}:{:X}", channel_id, self.cro_id, self.dto_id)
+    }
+}
+
+/// Size of a DAQ list as reported by GET_DAQ_SIZE
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaqListSize {
+    pub odt_count: u8,
+    pub first_pid: u8,
+}
+
+/// One entry of an ODT: which memory location to sample into which slot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaqElement {
+    pub daq_list: u8,
+    pub odt: u8,
+    pub element: u8,
+    /// Element size in bytes (1, 2 or 4)
+    pub size: u8,
+    pub extension: u8,
+    pub address: u32,
+}
+
+/// CCP master for one target over a frame link
+///
+/// The command counter runs on for the lifetime of the master, so a session
+/// kept in one master never takes a late response to a command that timed out
+/// for the response to the next one.
+pub struct CcpMaster<L: FrameLink> {
+    link: L,
+    target: CcpTarget,
+    timeout: Duration,
+    counter: u8,
+}
+
+impl<L: FrameLink> CcpMaster<L> {
+    pub fn new(link: L, target: CcpTarget, timeout: Duration) -> Self {
+        Self {
+            link,
+            target,
+            timeout,
+            counter: 0,
+        }
+    }
+
+    pub fn target(&self) -> &CcpTarget {
+        &self.target
+    }
+
+    /// Open a session with the target's station address
+    pub async fn connect(&mut self) -> Result<(), BootCanError> {
+        // Station address is always Intel byte order
+        let station = self.target.station_address.to_le_bytes();
+        self.command(CMD_CONNECT, &[station[0], station[1]]).await?;
+        Ok(())
+    }
+
+    /// End the session (or only pause it when `temporary` is set)
+    pub async fn disconnect(&mut self, temporary: bool) -> Result<(), BootCanError> {
+        let station = self.target.station_address.to_le_bytes();
+        self.command(CMD_DISCONNECT, &[!temporary as u8, 0, station[0], station[1]])
+            .await?;
+        Ok(())
+    }
+
+    /// Query the protocol version implemented by the ECU (main, release)
+    pub async fn get_version(&mut self) -> Result<(u8, u8), BootCanError> {
+        let response = self.command(CMD_GET_CCP_VERSION, &[2, 1]).await?;
+        Ok((response[3], response[4]))
+    }
+
+    /// Read `size` bytes of ECU memory
+    pub async fn upload(&mut self, address: u32, extension: u8, size: usize) -> Result<Vec<u8>, BootCanError> {
+        self.set_mta(0, address, extension).await?;
+
+        let mut data = Vec::with_capacity(size);
+        while data.len() < size {
+            let chunk = (size - data.len()).min(MAX_TRANSFER);
+            let response = self.command(CMD_UPLOAD, &[chunk as u8]).await?;
+            data.extend_from_slice(&response[3..3 + chunk]);
+        }
+        Ok(data)
+    }
+
+    /// Write data to ECU memory
+    pub async fn download(&mut self, address: u32, extension: u8, data: &[u8]) -> Result<(), BootCanError> {
+        self.set_mta(0, address, extension).await?;
+
+        for chunk in data.chunks(MAX_TRANSFER) {
+            let mut params = [0u8; 1 + MAX_TRANSFER];
+            params[0] = chunk.len() as u8;
+            params[1..1 + chunk.len()].copy_from_slice(chunk);
+            self.command(CMD_DNLOAD, &params[..1 + chunk.len()]).await?;
+        }
+        Ok(())
+    }
+
+    /// Get the size of a DAQ list and clear it; `dto_id` is the identifier DAQ data will be sent on
+    pub async fn get_daq_size(&mut self, daq_list: u8, dto_id: u32) -> Result<DaqListSize, BootCanError> {
+        let id = self.encode_u32(dto_id);
+        let response = self
+            .command(CMD_GET_DAQ_SIZE, &[daq_list, 0, id[0], id[1], id[2], id[3]])
+            .await?;
+        Ok(DaqListSize {
+            odt_count: response[3],
+            first_pid: response[4],
+        })
+    }
+
+    /// Point at one element of an ODT for a following WRITE_DAQ
+    pub async fn set_daq_ptr(&mut self, daq_list: u8, odt: u8, element: u8) -> Result<(), BootCanError> {
+        self.command(CMD_SET_DAQ_PTR, &[daq_list, odt, element]).await?;
+        Ok(())
+    }
+
+    /// Write the element selected by SET_DAQ_PTR (size 1, 2 or 4 bytes)
+    pub async fn write_daq(&mut self, size: u8, extension: u8, address: u32) -> Result<(), BootCanError> {
+        if !matches!(size, 1 | 2 | 4) {
+            return Err(BootCanError::InvalidInput(format!("Invalid DAQ element size {} (must be 1, 2 or 4)", size)));
+        }
+        let addr = self.encode_u32(address);
+        self.command(CMD_WRITE_DAQ, &[size, extension, addr[0], addr[1], addr[2], addr[3]])
+            .await?;
+        Ok(())
+    }
+
+    /// Configure one DAQ element (SET_DAQ_PTR followed by WRITE_DAQ)
+    pub async fn configure_daq_element(&mut self, element: &DaqElement) -> Result<(), BootCanError> {
+        self.set_daq_ptr(element.daq_list, element.odt, element.element).await?;
+        self.write_daq(element.size, element.extension, element.address).await
+    }
+
+    async fn set_mta(&mut self, mta: u8, address: u32, extension: u8) -> Result<(), BootCanError> {
+        let addr = self.encode_u32(address);
+        self.command(CMD_SET_MTA, &[mta, extension, addr[0], addr[1], addr[2], addr[3]])
+            .await?;
+        Ok(())
+    }
+
+    fn encode_u32(&self, value: u32) -> [u8; 4] {
+        match self.target.byte_order {
+            ByteOrder::BigEndian => value.to_be_bytes(),
+            ByteOrder::LittleEndian => value.to_le_bytes(),
+        }
+    }
+
+    /// Send a CRO and wait for the matching command return message
+    async fn command(&mut self, code: u8, params: &[u8]) -> Result<[u8; 8], BootCanError> {
+        let counter = self.counter;
+        self.counter = self.counter.wrapping_add(1);
+
+        let mut request = [0u8; 8];
+        request[0] = code;
+        request[1] = counter;
+        request[2..2 + params.len()].copy_from_slice(params);
+
+        let frame = if self.target.is_extended {
+            CanFrame::new_extended(self.target.cro_id, &request)
+        } else {
+            CanFrame::new(self.target.cro_id, &request)
+        };
+        self.link.send(frame).await?;
+
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            let frame = self
+                .link
+                .recv_id(self.target.dto_id, remaining)
+                .await?
+                .ok_or_else(|| {
+                    BootCanError::Timeout(format!("CCP timeout waiting for response to command 0x{:02X}", code))
+                })?;
+
+            // DAQ DTOs share the identifier; only take the CRM for our counter
+            if frame.data.len() < 8 || frame.data[0] != PID_CRM || frame.data[2] != counter {
+                continue;
+            }
+
+            let mut response = [0u8; 8];
+            response.copy_from_slice(&frame.data[..8]);
+            if response[1] != 0 {
+                return Err(BootCanError::Protocol(format!(
+                    "CCP command 0x{:02X} failed: 0x{:02X} ({})",
+                    code,
+                    response[1],
+                    error_description(response[1])
+                )));
+            }
+            return Ok(response);
+        }
+    }
+}
+
+/// Description of a CCP command return code
+pub fn error_description(code: u8) -> &'static str {
+    match code {
+        0x00 => "Acknowledge",
+        0x01 => "DAQ processor overload",
+        0x10 => "Command processor busy",
+        0x11 => "DAQ processor busy",
+        0x12 => "Internal timeout",
+        0x13 => "Key request",
+        0x14 => "Session status request",
+        0x20 => "Cold start request",
+        0x21 => "Calibration data initialization request",
+        0x22 => "DAQ list initialization request",
+        0x23 => "Code update request",
+        0x30 => "Unknown command",
+        0x31 => "Command syntax",
+        0x32 => "Parameter out of range",
+        0x33 => "Access denied",
+        0x34 => "Overload",
+        0x35 => "Access locked",
+        0x36 => "Resource/function not available",
+        _ => "Unknown error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_link::scripted::ScriptedLink;
+
+    /// ECU answering each CRO with the next scripted CRM, with the command counter filled in
+    fn ecu(responses: Vec<Vec<u8>>) -> ScriptedLink {
+        ScriptedLink::new(responses, |request, data| {
+            let mut data = data.to_vec();
+            data[2] = request.data[1];
+            CanFrame::new(0x7E1, &data)
+        })
+    }
+
+    fn target() -> CcpTarget {
+        CcpTarget {
+            cro_id: 0x7E0,
+            dto_id: 0x7E1,
+            is_extended: false,
+            station_address: 0x0039,
+            byte_order: ByteOrder::BigEndian,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_upload() {
+        let mut ecu = ecu(vec![
+            vec![0xFF, 0x00, 0, 0, 0, 0, 0, 0],
+            vec![0xFF, 0x00, 0, 0, 0, 0, 0, 0],
+            vec![0xFF, 0x00, 0, 1, 2, 3, 4, 5],
+            vec![0xFF, 0x00, 0, 6, 7, 0, 0, 0],
+        ]);
+        let mut master = CcpMaster::new(&mut ecu, target(), DEFAULT_CCP_TIMEOUT);
+        master.connect().await.unwrap();
+        let data = master.upload(0x0001_2000, 0, 7).await.unwrap();
+
+        assert_eq!(data, vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(ecu.sent[0].data, vec![CMD_CONNECT, 0, 0x39, 0x00, 0, 0, 0, 0]);
+        assert_eq!(ecu.sent[1].data, vec![CMD_SET_MTA, 1, 0, 0, 0x00, 0x01, 0x20, 0x00]);
+        assert_eq!(ecu.sent[3].data[..3], [CMD_UPLOAD, 3, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_error_code() {
+        let mut ecu = ecu(vec![vec![0xFF, 0x33, 0, 0, 0, 0, 0, 0]]);
+        let mut master = CcpMaster::new(&mut ecu, target(), DEFAULT_CCP_TIMEOUT);
+        let err = master.set_daq_ptr(0, 0, 0).await.unwrap_err();
+        assert!(err.message().contains("Access denied"));
+    }
+}
@@ -0,0 +1,993 @@
+//! CAN channels
+//!
+//! Every channel lives in its own task that owns the channel state and the
+//! interface, and receives frames as they arrive. Everything else talks to it
+//! through a cloneable `ChannelHandle`, so the receive path, the statistics
+//! loop, periodic jobs and commands never contend for a lock on the channel.
+
+use super::bus_stats::{BusStats, ExtendedBusStats, StatsEmission};
+use super::filter::FilterSet;
+use super::id_stats::{IdStats, IdStatsTracker};
+use super::message::{BusType, CanFrame, ChannelName, Direction};
+use super::stats_history::{HistoryTier, HistoryView, StatsHistory, ThroughputSample};
+use super::subscriber::{FrameSubscriber, SubscriberDrops, SubscriberRegistry, DEFAULT_BROADCAST_CAPACITY};
+use crate::error::BootCanError;
+use crate::hal::interface_task::InterfaceHandle;
+use crate::hal::lin::{LinCanAdapter, VirtualLinInterface};
+use crate::hal::slcan_lin::SlcanLinInterface;
+use crate::hal::synthetic::SyntheticCanInterface;
+use crate::hal::traits::{CanInterface, FaultConfig, FrameReceiver};
+use crate::hal::virtual_can::{SharedVirtualBus, VirtualBusAttachment, VirtualCanInterface};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tracing::Instrument;
+
+/// Connection state for a CAN channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Error(String),
+}
+
+impl fmt::Display for ChannelState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disconnected => write!(f, "disconnected"),
+            Self::Connecting => write!(f, "connecting"),
+            Self::Connected => write!(f, "connected"),
+            Self::Error(e) => write!(f, "error: {}", e),
+        }
+    }
+}
+
+/// Configuration for a CAN channel
+#[derive(Debug, Clone)]
+pub struct ChannelConfig {
+    pub interface_id: String,
+    pub bitrate: u32,
+    pub listen_only: bool,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            interface_id: String::new(),
+            bitrate: 500_000,
+            listen_only: false,
+        }
+    }
+}
+
+/// Callback getting every received frame that passed the filter
+pub type FrameHandler = Box<dyn Fn(&CanFrame) + Send + Sync>;
+
+/// State of a channel at one point in time
+#[derive(Debug, Clone)]
+pub struct ChannelSnapshot {
+    pub id: String,
+    pub config: ChannelConfig,
+    pub state: ChannelState,
+    pub stats: ExtendedBusStats,
+    /// Interval of the statistics events, kept across reconnects
+    pub stats_emission: StatsEmission,
+    pub faults: Option<FaultConfig>,
+    /// Name of the virtual bus the channel is attached to
+    pub virtual_bus: Option<String>,
+}
+
+impl ChannelSnapshot {
+    pub fn is_connected(&self) -> bool {
+        self.state == ChannelState::Connected
+    }
+}
+
+/// Whether a channel task is alive and answering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskState {
+    Running,
+    /// The task did not answer in time, e.g. because it is stuck in an interface call
+    Unresponsive,
+    Stopped,
+}
+
+/// Liveness and queue depths of a channel, returned by `get_diagnostics`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelDiagnostics {
+    pub channel_id: String,
+    pub task: TaskState,
+    /// Connection state, if the task answered
+    pub state: Option<String>,
+    /// Commands waiting for the task
+    pub command_queue: usize,
+    /// Frames from the interface waiting for the task
+    pub rx_queue: usize,
+    /// Frames in the broadcast channel not yet seen by every subscriber
+    pub broadcast_queue: usize,
+    /// Frames a subscriber can fall behind before it loses frames
+    pub broadcast_capacity: usize,
+    /// Frames lost by each subscriber
+    pub subscribers: Vec<SubscriberDrops>,
+    /// Frames lost by all subscribers, including the ones gone since
+    pub dropped_frames: u64,
+    pub rx_count: u64,
+    pub tx_count: u64,
+}
+
+type Reply<T> = oneshot::Sender<Result<T, BootCanError>>;
+
+enum ChannelCommand {
+    Connect { config: ChannelConfig, on_frame: FrameHandler, reply: Reply<()> },
+    Disconnect { reply: Reply<()> },
+    Configure { config: ChannelConfig },
+    Send { frame: CanFrame, reply: Reply<CanFrame> },
+    SetFilter { filter: FilterSet },
+    Snapshot { reply: oneshot::Sender<ChannelSnapshot> },
+    UpdateStats { reply: oneshot::Sender<Option<ChannelSnapshot>> },
+    ResetStats,
+    IdStats { reply: oneshot::Sender<Vec<IdStats>> },
+    History { tier: HistoryTier, reply: oneshot::Sender<HistoryView> },
+    Throughput { seconds: usize, reply: oneshot::Sender<Vec<ThroughputSample>> },
+    SetStatsEmission { emission: StatsEmission },
+    SetFaultInjection { config: Option<FaultConfig>, reply: Reply<()> },
+    AttachVirtualBus { bus: Option<(String, SharedVirtualBus)>, reply: Reply<()> },
+    Diagnostics { reply: oneshot::Sender<ChannelDiagnostics> },
+    WaitActivity { seen: u64, reply: oneshot::Sender<()> },
+}
+
+/// A single CAN channel, owned by its task
+struct Channel {
+    id: String,
+    /// Shared copy of `id` marked on every frame
+    name: ChannelName,
+    config: ChannelConfig,
+    state: ChannelState,
+    stats: BusStats,
+    id_stats: IdStatsTracker,
+    stats_emission: StatsEmission,
+    /// Per-second, per-minute and per-hour statistics since connect
+    history: StatsHistory,
+    /// Task owning the interface while connected
+    interface: Option<InterfaceHandle>,
+    /// Frames from the interface while connected
+    receiver: Option<FrameReceiver>,
+    /// Bus of the connected interface, marked on every frame
+    bus: BusType,
+    on_frame: Option<FrameHandler>,
+    start_time: Option<Instant>,
+    /// Frame count and time of the previous bus load update
+    load_window: (u64, Instant),
+    message_tx: broadcast::Sender<CanFrame>,
+    subscribers: Arc<SubscriberRegistry>,
+    filter: FilterSet,
+    /// Fault injection settings, re-applied on every connect
+    faults: Option<FaultConfig>,
+    /// Named virtual bus this channel is attached to, re-applied on every connect
+    virtual_bus: Option<(String, SharedVirtualBus)>,
+    /// Waiting for the next frame, error or state change
+    activity_waiters: Vec<oneshot::Sender<()>>,
+}
+
+impl Channel {
+    fn new(
+        id: String,
+        stats_emission: StatsEmission,
+        message_tx: broadcast::Sender<CanFrame>,
+        subscribers: Arc<SubscriberRegistry>,
+    ) -> Self {
+        Self {
+            name: id.as_str().into(),
+            id,
+            config: ChannelConfig::default(),
+            state: ChannelState::Disconnected,
+            stats: BusStats::new(),
+            id_stats: IdStatsTracker::new(),
+            stats_emission,
+            history: StatsHistory::new(),
+            interface: None,
+            receiver: None,
+            bus: BusType::Can,
+            on_frame: None,
+            start_time: None,
+            load_window: (0, Instant::now()),
+            message_tx,
+            subscribers,
+            filter: FilterSet::default(),
+            faults: None,
+            virtual_bus: None,
+            activity_waiters: Vec::new(),
+        }
+    }
+
+    async fn handle(&mut self, command: ChannelCommand) {
+        match command {
+            ChannelCommand::Connect { config, on_frame, reply } => {
+                let _ = reply.send(self.connect(config, on_frame).await);
+            }
+            ChannelCommand::Disconnect { reply } => {
+                let _ = reply.send(self.disconnect().await);
+            }
+            ChannelCommand::Configure { config } => {
+                if self.state != ChannelState::Connected {
+                    self.config = config;
+                }
+            }
+            ChannelCommand::Send { frame, reply } => {
+                let _ = reply.send(self.transmit(frame).await);
+            }
+            ChannelCommand::SetFilter { filter } => self.filter = filter,
+            ChannelCommand::Snapshot { reply } => {
+                let _ = reply.send(self.snapshot());
+            }
+            ChannelCommand::UpdateStats { reply } => {
+                let _ = reply.send(self.update_stats().await);
+            }
+            ChannelCommand::ResetStats => {
+                self.stats.reset();
+                self.id_stats.reset();
+                // The history holds rates over time and is kept; only its baseline follows the counters
+                self.history.counters_reset();
+                self.load_window = (0, Instant::now());
+            }
+            ChannelCommand::IdStats { reply } => {
+                let _ = reply.send(self.id_stats.snapshot());
+            }
+            ChannelCommand::History { tier, reply } => {
+                let _ = reply.send(self.history.view(tier));
+            }
+            ChannelCommand::Throughput { seconds, reply } => {
+                let _ = reply.send(self.history.throughput(seconds));
+            }
+            ChannelCommand::SetStatsEmission { emission } => self.stats_emission = emission,
+            ChannelCommand::SetFaultInjection { config, reply } => {
+                let _ = reply.send(self.set_fault_injection(config).await);
+            }
+            ChannelCommand::AttachVirtualBus { bus, reply } => {
+                let _ = reply.send(self.attach_virtual_bus(bus).await);
+            }
+            ChannelCommand::Diagnostics { reply } => {
+                let _ = reply.send(self.diagnostics());
+            }
+            ChannelCommand::WaitActivity { seen, reply } => {
+                if self.activity_count() != seen {
+                    let _ = reply.send(());
+                } else {
+                    // Waiters whose caller gave up would pile up on a silent bus
+                    self.activity_waiters.retain(|waiter| !waiter.is_closed());
+                    self.activity_waiters.push(reply);
+                }
+            }
+        }
+    }
+
+    /// Frames and errors since connect, as passed to `wait_for_activity`
+    fn activity_count(&self) -> u64 {
+        self.stats.rx_count + self.stats.tx_count + self.stats.error_count
+    }
+
+    /// Wake everyone waiting for activity
+    fn wake_idle(&mut self) {
+        for waiter in self.activity_waiters.drain(..) {
+            let _ = waiter.send(());
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(interface = %config.interface_id, bitrate = config.bitrate))]
+    async fn connect(&mut self, config: ChannelConfig, on_frame: FrameHandler) -> Result<(), BootCanError> {
+        self.state = ChannelState::Connecting;
+        self.config = config.clone();
+        self.receiver = None;
+        self.on_frame = None;
+
+        let result = match create_interface(&config.interface_id) {
+            Ok(interface) => {
+                self.bus = interface.bus();
+                let iface = InterfaceHandle::spawn(interface);
+                iface.connect(config.bitrate).await.map(|()| iface)
+            }
+            Err(e) => Err(e),
+        };
+        let iface = match result {
+            Ok(iface) => iface,
+            Err(e) => {
+                self.state = ChannelState::Error(e.to_string());
+                self.interface = None;
+                self.wake_idle();
+                return Err(e);
+            }
+        };
+
+        if self.faults.is_some() {
+            if let Err(e) = iface.set_fault_injection(self.faults).await {
+                tracing::warn!("Fault injection not applied: {}", e);
+            }
+        }
+        if let Some((_, bus)) = &self.virtual_bus {
+            let attachment = VirtualBusAttachment {
+                bus: bus.clone(),
+                node_id: self.id.clone(),
+            };
+            if let Err(e) = iface.attach_virtual_bus(Some(attachment)).await {
+                tracing::warn!("Virtual bus not attached: {}", e);
+            }
+        }
+        match iface.take_receiver().await {
+            Ok(receiver) => self.receiver = Some(receiver),
+            Err(e) => tracing::warn!("No receiver: {}", e),
+        }
+
+        self.state = ChannelState::Connected;
+        self.start_time = Some(Instant::now());
+        self.load_window = (0, Instant::now());
+        self.stats.reset();
+        self.id_stats.reset();
+        self.history = StatsHistory::new();
+        self.interface = Some(iface);
+        self.on_frame = Some(on_frame);
+        self.wake_idle();
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), BootCanError> {
+        if let Some(iface) = &self.interface {
+            iface.disconnect().await?;
+        }
+        self.interface = None;
+        self.receiver = None;
+        self.on_frame = None;
+        self.state = ChannelState::Disconnected;
+        self.start_time = None;
+        self.wake_idle();
+        Ok(())
+    }
+
+    /// Transmit a frame, returning it as broadcast to subscribers
+    #[tracing::instrument(name = "send", level = "trace", skip_all, fields(id = frame.id))]
+    async fn transmit(&mut self, mut frame: CanFrame) -> Result<CanFrame, BootCanError> {
+        if self.state != ChannelState::Connected {
+            return Err(BootCanError::NotConnected("Channel not connected".to_string()));
+        }
+        let iface = self
+            .interface
+            .as_ref()
+            .ok_or_else(|| BootCanError::NotConnected("No interface connected".to_string()))?;
+        iface.send(frame.clone()).await?;
+
+        self.stats.record_tx(frame.data.len());
+        frame.direction = Direction::Tx;
+        frame.channel.clone_from(&self.name);
+        frame.timestamp = self.get_timestamp();
+        frame.bus = self.bus;
+        self.id_stats.record(&frame);
+        let _ = self.message_tx.send(frame.clone());
+        self.wake_idle();
+        Ok(frame)
+    }
+
+    /// Account a frame from the interface and pass it on if it passes the filter
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn receive(&mut self, result: Result<CanFrame, BootCanError>) {
+        if self.state != ChannelState::Connected {
+            return;
+        }
+        self.wake_idle();
+        let mut frame = match result {
+            Ok(frame) => frame,
+            Err(e) => {
+                self.stats.record_error();
+                tracing::error!("Receive error: {}", e);
+                return;
+            }
+        };
+
+        self.stats.record_rx(frame.data.len());
+        frame.direction = Direction::Rx;
+        frame.channel.clone_from(&self.name);
+        frame.timestamp = self.get_timestamp();
+        frame.bus = self.bus;
+        self.id_stats.record(&frame);
+        if self.filter.matches(&frame) {
+            if let Some(on_frame) = &self.on_frame {
+                on_frame(&frame);
+            }
+            let _ = self.message_tx.send(frame);
+        }
+    }
+
+    /// The part of the diagnostics only the task knows
+    fn diagnostics(&self) -> ChannelDiagnostics {
+        ChannelDiagnostics {
+            channel_id: self.id.clone(),
+            task: TaskState::Running,
+            state: Some(self.state.to_string()),
+            command_queue: 0,
+            rx_queue: self.receiver.as_ref().map_or(0, |rx| rx.len()),
+            broadcast_queue: self.message_tx.len(),
+            broadcast_capacity: 0,
+            subscribers: Vec::new(),
+            dropped_frames: 0,
+            rx_count: self.stats.rx_count,
+            tx_count: self.stats.tx_count,
+        }
+    }
+
+    fn snapshot(&self) -> ChannelSnapshot {
+        let mut stats = ExtendedBusStats::new(
+            self.stats.clone(),
+            self.id_stats.first_seen(),
+            self.id_stats.last_seen(),
+            self.id_stats.len() as u32,
+        );
+        stats.dropped_frames = self.subscribers.total_dropped();
+        ChannelSnapshot {
+            id: self.id.clone(),
+            config: self.config.clone(),
+            state: self.state.clone(),
+            stats,
+            stats_emission: self.stats_emission,
+            faults: self.faults,
+            virtual_bus: self.virtual_bus.as_ref().map(|(name, _)| name.clone()),
+        }
+    }
+
+    /// Refresh controller status, bus load and history of a connected channel
+    async fn update_stats(&mut self) -> Option<ChannelSnapshot> {
+        if self.state != ChannelState::Connected {
+            return None;
+        }
+        if let Some(status) = self.interface.as_ref()?.controller_status().await {
+            let timestamp = self.get_timestamp();
+            if let Some(transition) = self.stats.update_controller(status, timestamp) {
+                tracing::warn!("Controller state {:?} -> {:?}", transition.from, transition.to);
+            }
+        }
+
+        let now = Instant::now();
+        let (last_total, last_update) = self.load_window;
+        let elapsed = now.duration_since(last_update).as_secs_f64();
+        let total_messages = self.stats.tx_count + self.stats.rx_count;
+        if elapsed > 0.0 {
+            let messages_per_second = total_messages.saturating_sub(last_total) as f64 / elapsed;
+            self.stats.update_bus_load(messages_per_second, self.config.bitrate);
+            self.load_window = (total_messages, now);
+        }
+
+        let t = self.get_timestamp();
+        self.history.update(t, &self.stats);
+        Some(self.snapshot())
+    }
+
+    /// Current timestamp relative to connection start
+    fn get_timestamp(&self) -> f64 {
+        self.start_time
+            .map(|t| t.elapsed().as_secs_f64())
+            .unwrap_or(0.0)
+    }
+
+    async fn set_fault_injection(&mut self, config: Option<FaultConfig>) -> Result<(), BootCanError> {
+        if let Some(config) = &config {
+            config.validate()?;
+        }
+        if let Some(iface) = &self.interface {
+            iface.set_fault_injection(config).await?;
+        }
+        self.faults = config;
+        Ok(())
+    }
+
+    async fn attach_virtual_bus(&mut self, bus: Option<(String, SharedVirtualBus)>) -> Result<(), BootCanError> {
+        if let Some(iface) = &self.interface {
+            match &bus {
+                Some((_, bus)) => {
+                    let attachment = VirtualBusAttachment {
+                        bus: bus.clone(),
+                        node_id: self.id.clone(),
+                    };
+                    iface.attach_virtual_bus(Some(attachment)).await?;
+                }
+                None if self.virtual_bus.is_some() => iface.attach_virtual_bus(None).await?,
+                None => {}
+            }
+        }
+        self.virtual_bus = bus;
+        Ok(())
+    }
+}
+
+async fn run(mut channel: Channel, mut commands: mpsc::Receiver<ChannelCommand>) {
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let Some(command) = command else {
+                    break;
+                };
+                channel.handle(command).await;
+            }
+            result = next_frame(&mut channel.receiver), if channel.receiver.is_some() => {
+                match result {
+                    Some(result) => channel.receive(result),
+                    None => {
+                        tracing::info!("Receive loop ended");
+                        channel.receiver = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn next_frame(receiver: &mut Option<FrameReceiver>) -> Option<Result<CanFrame, BootCanError>> {
+    receiver.as_mut()?.recv().await
+}
+
+/// Cloneable handle for the task of a channel
+///
+/// The task ends, dropping the interface, once every handle is dropped.
+#[derive(Clone)]
+pub struct ChannelHandle {
+    id: String,
+    commands: mpsc::Sender<ChannelCommand>,
+    message_tx: broadcast::Sender<CanFrame>,
+    broadcast_capacity: usize,
+    subscribers: Arc<SubscriberRegistry>,
+}
+
+impl ChannelHandle {
+    /// Start the task of a disconnected channel
+    ///
+    /// `broadcast_capacity` is how many frames a subscriber can fall behind
+    /// before it loses frames.
+    pub fn spawn(id: &str, stats_emission: StatsEmission, broadcast_capacity: usize) -> Self {
+        let (message_tx, _) = broadcast::channel(broadcast_capacity);
+        let (commands, rx) = mpsc::channel(256);
+        let subscribers = Arc::new(SubscriberRegistry::default());
+        let channel = Channel::new(id.to_string(), stats_emission, message_tx.clone(), subscribers.clone());
+        tokio::spawn(run(channel, rx).instrument(tracing::info_span!("channel", channel = %id)));
+        Self {
+            id: id.to_string(),
+            commands,
+            message_tx,
+            broadcast_capacity,
+            subscribers,
+        }
+    }
+
+    /// ID of the channel
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get a receiver for transmitted and received messages
+    ///
+    /// Received frames are only broadcast if they pass the filter. `name`
+    /// identifies the subscriber in warnings and diagnostics.
+    pub fn subscribe(&self, name: &str) -> FrameSubscriber {
+        FrameSubscriber::new(name, &self.id, self.message_tx.subscribe(), self.subscribers.clone())
+    }
+
+    async fn request<T>(&self, command: ChannelCommand, response: oneshot::Receiver<T>) -> Result<T, BootCanError> {
+        let closed = || BootCanError::NotFound(format!("Channel {} is gone", self.id));
+        self.commands.send(command).await.map_err(|_| closed())?;
+        response.await.map_err(|_| closed())
+    }
+
+    async fn notify(&self, command: ChannelCommand) -> Result<(), BootCanError> {
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| BootCanError::NotFound(format!("Channel {} is gone", self.id)))
+    }
+
+    /// Task state and queue depths; a task not answering within `timeout` is unresponsive
+    pub async fn diagnostics(&self, timeout: Duration) -> ChannelDiagnostics {
+        let mut diagnostics = ChannelDiagnostics {
+            channel_id: self.id.clone(),
+            task: TaskState::Stopped,
+            state: None,
+            command_queue: self.commands.max_capacity() - self.commands.capacity(),
+            rx_queue: 0,
+            broadcast_queue: self.message_tx.len(),
+            broadcast_capacity: self.broadcast_capacity,
+            subscribers: self.subscribers.drops(),
+            dropped_frames: self.subscribers.total_dropped(),
+            rx_count: 0,
+            tx_count: 0,
+        };
+        let (reply, response) = oneshot::channel();
+        match self.commands.try_send(ChannelCommand::Diagnostics { reply }) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                diagnostics.task = TaskState::Unresponsive;
+                return diagnostics;
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => return diagnostics,
+        }
+        match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(task)) => ChannelDiagnostics {
+                command_queue: diagnostics.command_queue,
+                broadcast_capacity: diagnostics.broadcast_capacity,
+                subscribers: diagnostics.subscribers,
+                dropped_frames: diagnostics.dropped_frames,
+                ..task
+            },
+            Ok(Err(_)) => diagnostics,
+            Err(_) => {
+                diagnostics.task = TaskState::Unresponsive;
+                diagnostics
+            }
+        }
+    }
+
+    /// Wait until the channel sends or receives a frame, counts an error,
+    /// connects or disconnects
+    ///
+    /// `seen` is the sum of the tx, rx and error counts of the last snapshot;
+    /// if the channel counted more since, this returns right away.
+    pub async fn wait_for_activity(&self, seen: u64) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::WaitActivity { seen, reply }, response).await
+    }
+
+    /// Connect to the CAN interface in `config`, passing every received frame
+    /// that passes the filter to `on_frame` until disconnected
+    pub async fn connect(&self, config: ChannelConfig, on_frame: FrameHandler) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::Connect { config, on_frame, reply }, response).await?
+    }
+
+    pub async fn disconnect(&self) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::Disconnect { reply }, response).await?
+    }
+
+    /// Set the interface and bitrate shown until the next connect; ignored while connected
+    pub async fn configure(&self, config: ChannelConfig) -> Result<(), BootCanError> {
+        self.notify(ChannelCommand::Configure { config }).await
+    }
+
+    /// Transmit a frame, returning it as broadcast to subscribers
+    pub async fn transmit(&self, frame: CanFrame) -> Result<CanFrame, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::Send { frame, reply }, response).await?
+    }
+
+    /// Transmit a frame
+    pub async fn send(&self, frame: CanFrame) -> Result<(), BootCanError> {
+        self.transmit(frame).await.map(|_| ())
+    }
+
+    /// Set the filter received frames must pass
+    pub async fn set_filter(&self, filter: FilterSet) -> Result<(), BootCanError> {
+        self.notify(ChannelCommand::SetFilter { filter }).await
+    }
+
+    pub async fn snapshot(&self) -> Result<ChannelSnapshot, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::Snapshot { reply }, response).await
+    }
+
+    /// Whether the channel is connected; false once its task is gone
+    pub async fn is_connected(&self) -> bool {
+        self.snapshot().await.is_ok_and(|s| s.is_connected())
+    }
+
+    /// Refresh controller status, bus load and history from the hardware
+    ///
+    /// Returns None while the channel is not connected.
+    pub async fn update_stats(&self) -> Result<Option<ChannelSnapshot>, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::UpdateStats { reply }, response).await
+    }
+
+    /// Reset the bus and per-ID statistics
+    ///
+    /// The throughput history since connect is kept; it restarts on the next connect.
+    pub async fn reset_stats(&self) -> Result<(), BootCanError> {
+        self.notify(ChannelCommand::ResetStats).await
+    }
+
+    /// Per-ID counters and timing, ordered by ID
+    pub async fn id_stats(&self) -> Result<Vec<IdStats>, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::IdStats { reply }, response).await
+    }
+
+    pub async fn history(&self, tier: HistoryTier) -> Result<HistoryView, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::History { tier, reply }, response).await
+    }
+
+    pub async fn throughput(&self, seconds: usize) -> Result<Vec<ThroughputSample>, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::Throughput { seconds, reply }, response).await
+    }
+
+    pub async fn set_stats_emission(&self, emission: StatsEmission) -> Result<(), BootCanError> {
+        self.notify(ChannelCommand::SetStatsEmission { emission }).await
+    }
+
+    /// Set fault injection (virtual interfaces only)
+    pub async fn set_fault_injection(&self, config: Option<FaultConfig>) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::SetFaultInjection { config, reply }, response).await?
+    }
+
+    /// Attach to a named virtual bus (virtual interfaces only)
+    pub async fn attach_virtual_bus(&self, name: &str, bus: SharedVirtualBus) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        let bus = Some((name.to_string(), bus));
+        self.request(ChannelCommand::AttachVirtualBus { bus, reply }, response).await?
+    }
+
+    /// Detach from the virtual bus
+    pub async fn detach_virtual_bus(&self) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(ChannelCommand::AttachVirtualBus { bus: None, reply }, response).await?
+    }
+}
+
+/// Create the interface named by `interface_id`
+fn create_interface(interface_id: &str) -> Result<Box<dyn CanInterface>, BootCanError> {
+    if interface_id.starts_with("vcan") {
+        Ok(Box::new(VirtualCanInterface::new(interface_id)))
+    } else if interface_id.starts_with("synth") {
+        Ok(Box::new(SyntheticCanInterface::new(interface_id)))
+    } else if interface_id.starts_with("vlin") {
+        Ok(Box::new(LinCanAdapter::new(Box::new(VirtualLinInterface::new(interface_id)))))
+    } else if let Some(port) = interface_id.strip_prefix("lin:") {
+        Ok(Box::new(LinCanAdapter::new(Box::new(SlcanLinInterface::new(port)))))
+    } else if interface_id.starts_with("can") {
+        #[cfg(target_os = "linux")]
+        {
+            use crate::hal::socketcan::SocketCanInterface;
+            Ok(Box::new(SocketCanInterface::new(interface_id)))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(BootCanError::Hal("SocketCAN is only available on Linux".to_string()))
+        }
+    } else if interface_id.starts_with("pcan") {
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        {
+            use crate::hal::pcan::PcanInterface;
+            Ok(Box::new(PcanInterface::new(interface_id)))
+        }
+        #[cfg(target_os = "linux")]
+        {
+            // On Linux, prefer SocketCAN for PCAN devices
+            Err(BootCanError::Hal("On Linux, PCAN devices should be accessed via SocketCAN".to_string()))
+        }
+    } else {
+        Err(BootCanError::NotFound(format!("Unknown interface type: {}", interface_id)))
+    }
+}
+
+/// Manager for multiple CAN channels
+pub struct ChannelManager {
+    channels: HashMap<String, ChannelHandle>,
+    active_channel: Option<String>,
+    /// Statistics emission of channels created from now on
+    default_stats_emission: StatsEmission,
+    /// Broadcast capacity of channels created from now on
+    broadcast_capacity: usize,
+}
+
+impl ChannelManager {
+    /// Create a new channel manager
+    pub fn new() -> Self {
+        Self {
+            channels: HashMap::new(),
+            active_channel: None,
+            default_stats_emission: StatsEmission::default(),
+            broadcast_capacity: DEFAULT_BROADCAST_CAPACITY,
+        }
+    }
+
+    /// Statistics emission given to channels created later
+    pub fn set_default_stats_emission(&mut self, emission: StatsEmission) {
+        self.default_stats_emission = emission;
+    }
+
+    /// Broadcast capacity given to channels created later
+    pub fn set_broadcast_capacity(&mut self, capacity: usize) {
+        self.broadcast_capacity = capacity;
+    }
+
+    /// Get or create a channel
+    pub fn get_or_create_channel(&mut self, id: &str) -> ChannelHandle {
+        self.channels
+            .entry(id.to_string())
+            .or_insert_with(|| ChannelHandle::spawn(id, self.default_stats_emission, self.broadcast_capacity))
+            .clone()
+    }
+
+    /// Get the active channel
+    pub fn get_active_channel(&self) -> Option<ChannelHandle> {
+        self.active_channel
+            .as_ref()
+            .and_then(|id| self.channels.get(id))
+            .cloned()
+    }
+
+    /// Get the active channel ID
+    pub fn get_active_channel_id(&self) -> Option<&String> {
+        self.active_channel.as_ref()
+    }
+
+    /// Set the active channel
+    pub fn set_active_channel(&mut self, id: &str) {
+        if self.channels.contains_key(id) {
+            self.active_channel = Some(id.to_string());
+        }
+    }
+
+    /// Get all channel IDs
+    pub fn get_channel_ids(&self) -> Vec<String> {
+        self.channels.keys().cloned().collect()
+    }
+
+    /// Remove a channel
+    pub fn remove_channel(&mut self, id: &str) {
+        self.channels.remove(id);
+        if self.active_channel.as_deref() == Some(id) {
+            self.active_channel = None;
+        }
+    }
+
+    /// Get a channel by ID
+    pub fn get_channel(&self, id: &str) -> Option<ChannelHandle> {
+        self.channels.get(id).cloned()
+    }
+}
+
+impl Default for ChannelManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::filter::{FilterLogic, FilterRule};
+    use std::sync::{Arc, Mutex};
+
+    fn vcan(id: &str) -> ChannelConfig {
+        ChannelConfig {
+            interface_id: id.to_string(),
+            ..ChannelConfig::default()
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_activity_waiters_wake_and_are_pruned() {
+        let (message_tx, _) = broadcast::channel(DEFAULT_BROADCAST_CAPACITY);
+        let mut channel = Channel::new("ch0".into(), StatsEmission::default(), message_tx, Arc::default());
+
+        // A stats loop that times out on a silent bus leaves its waiter behind every round
+        for _ in 0..10 {
+            let (reply, response) = oneshot::channel();
+            channel.handle(ChannelCommand::WaitActivity { seen: 0, reply }).await;
+            drop(response);
+        }
+        let (reply, mut response) = oneshot::channel();
+        channel.handle(ChannelCommand::WaitActivity { seen: 0, reply }).await;
+        assert_eq!(channel.activity_waiters.len(), 1);
+        assert!(response.try_recv().is_err());
+
+        channel.wake_idle();
+        assert!(response.try_recv().is_ok());
+        assert!(channel.activity_waiters.is_empty());
+
+        // Activity since the count the caller saw answers at once
+        channel.stats.rx_count = 1;
+        let (reply, mut response) = oneshot::channel();
+        channel.handle(ChannelCommand::WaitActivity { seen: 0, reply }).await;
+        assert!(response.try_recv().is_ok());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_channel_task_sends_and_receives() {
+        let channel = ChannelHandle::spawn("ch0", StatsEmission::default(), DEFAULT_BROADCAST_CAPACITY);
+        assert!(channel.send(CanFrame::new(0x1, &[])).await.is_err());
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let on_frame: FrameHandler = Box::new(move |frame| sink.lock().unwrap().push(frame.id));
+        channel.connect(vcan("vcan0"), on_frame).await.unwrap();
+        let mut rx = channel.subscribe("test");
+
+        let sent = channel.transmit(CanFrame::new(0x123, &[1, 2])).await.unwrap();
+        assert_eq!((sent.channel.as_str(), sent.direction.as_str()), ("ch0", "tx"));
+        assert_eq!(rx.recv().await.unwrap().direction, Direction::Tx);
+        // The virtual interface echoes the frame back
+        let echoed = rx.recv().await.unwrap();
+        assert_eq!((echoed.id, echoed.direction.as_str()), (0x123, "rx"));
+        assert_eq!(*received.lock().unwrap(), vec![0x123]);
+
+        let snapshot = channel.update_stats().await.unwrap().unwrap();
+        assert_eq!((snapshot.stats.base.tx_count, snapshot.stats.base.rx_count), (1, 1));
+        // Counted more than seen, so no waiting
+        channel.wait_for_activity(0).await.unwrap();
+        let waiting = channel.clone();
+        let idle = tokio::spawn(async move { waiting.wait_for_activity(2).await });
+        tokio::task::yield_now().await;
+        assert!(!idle.is_finished());
+        channel.send(CanFrame::new(0x123, &[])).await.unwrap();
+        idle.await.unwrap().unwrap();
+        assert_eq!(channel.id_stats().await.unwrap().len(), 1);
+        let diagnostics = channel.diagnostics(Duration::from_secs(1)).await;
+        assert_eq!(diagnostics.task, TaskState::Running);
+        assert_eq!(diagnostics.state.as_deref(), Some("connected"));
+        assert_eq!((diagnostics.subscribers.len(), diagnostics.tx_count), (1, 2));
+        assert_eq!(diagnostics.broadcast_capacity, DEFAULT_BROADCAST_CAPACITY);
+
+        // Counters start over, the history since connect stays
+        let history = channel.history(HistoryTier::Seconds).await.unwrap();
+        channel.reset_stats().await.unwrap();
+        assert!(channel.id_stats().await.unwrap().is_empty());
+        assert_eq!(channel.history(HistoryTier::Seconds).await.unwrap().samples.len(), history.samples.len());
+        let snapshot = channel.update_stats().await.unwrap().unwrap();
+        assert_eq!((snapshot.stats.base.tx_count, snapshot.stats.base.bus_load), (0, 0.0));
+
+        channel.disconnect().await.unwrap();
+        assert!(!channel.is_connected().await);
+        assert!(channel.update_stats().await.unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_filter_applies_to_received_frames() {
+        let mut manager = ChannelManager::new();
+        let channel = manager.get_or_create_channel("ch0");
+        assert_eq!(manager.get_channel("ch0").unwrap().id(), "ch0");
+        channel.connect(vcan("vcan1"), Box::new(|_| {})).await.unwrap();
+        channel
+            .set_filter(FilterSet {
+                rules: vec![FilterRule::IdRange { min: 0x200, max: 0x2FF }],
+                logic: FilterLogic::And,
+            })
+            .await
+            .unwrap();
+
+        let mut rx = channel.subscribe("test");
+        channel.send(CanFrame::new(0x100, &[])).await.unwrap();
+        channel.send(CanFrame::new(0x200, &[])).await.unwrap();
+        let frames: Vec<(u32, Direction)> = [rx.recv().await, rx.recv().await, rx.recv().await]
+            .into_iter()
+            .map(|f| f.map(|f| (f.id, f.direction)).unwrap())
+            .collect();
+        assert!(frames.contains(&(0x200, Direction::Rx)));
+        assert!(!frames.contains(&(0x100, Direction::Rx)));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_lagging_subscriber_drops_are_reported() {
+        let mut manager = ChannelManager::new();
+        manager.set_broadcast_capacity(4);
+        let channel = manager.get_or_create_channel("ch0");
+        channel.connect(vcan("vcan2"), Box::new(|_| {})).await.unwrap();
+
+        let mut rx = channel.subscribe("slow");
+        for id in 0..3 {
+            channel.send(CanFrame::new(id, &[])).await.unwrap();
+        }
+        // Three transmitted and three echoed frames through a queue of four
+        while channel.update_stats().await.unwrap().unwrap().stats.base.rx_count < 3 {}
+        assert!(rx.recv().await.is_some());
+        assert_eq!(rx.dropped(), 2);
+
+        let snapshot = channel.snapshot().await.unwrap();
+        assert_eq!(snapshot.stats.dropped_frames, 2);
+        let diagnostics = channel.diagnostics(Duration::from_secs(1)).await;
+        assert_eq!(diagnostics.broadcast_capacity, 4);
+        assert_eq!(diagnostics.subscribers[0].name, "slow");
+        assert_eq!((diagnostics.subscribers[0].dropped, diagnostics.dropped_frames), (2, 2));
+    }
+}
@@ -0,0 +1,231 @@
+//! Cycle-time supervision of periodic messages
+//!
+//! Every period between two frames of a supervised ID is compared with the
+//! expected cycle time. Periods outside the tolerance are counted; an alert
+//! is raised when an ID starts violating, not for every late frame.
+
+use super::dbc::DbcDatabase;
+use super::message::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn default_tolerance_percent() -> f64 {
+    20.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Expected period of a message, overriding the DBC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleExpectation {
+    pub message_id: u32,
+    pub cycle_time_ms: f64,
+}
+
+/// Settings of a cycle-time monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleMonitorConfig {
+    /// Allowed deviation from the expected period in percent
+    #[serde(default = "default_tolerance_percent")]
+    pub tolerance_percent: f64,
+    /// Supervise every message with a cycle time in the channel's DBC
+    #[serde(default = "default_true")]
+    pub use_dbc: bool,
+    #[serde(default)]
+    pub expectations: Vec<CycleExpectation>,
+}
+
+impl Default for CycleMonitorConfig {
+    fn default() -> Self {
+        Self {
+            tolerance_percent: default_tolerance_percent(),
+            use_dbc: true,
+            expectations: Vec::new(),
+        }
+    }
+}
+
+/// Alert raised when an ID starts violating its cycle time, emitted as `cycle-violation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleViolation {
+    pub channel_id: String,
+    pub message_id: u32,
+    pub expected_ms: f64,
+    pub measured_ms: f64,
+    /// Signed deviation from the expected period in percent
+    pub deviation_percent: f64,
+    pub timestamp: f64,
+}
+
+/// Supervision counters of one ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleCounts {
+    pub message_id: u32,
+    pub expected_ms: f64,
+    /// Periods measured
+    pub checked: u64,
+    /// Periods shorter than the tolerance allows
+    pub too_fast: u64,
+    /// Periods longer than the tolerance allows
+    pub too_slow: u64,
+    pub last_measured_ms: Option<f64>,
+    /// Largest absolute deviation seen, in percent
+    pub max_deviation_percent: f64,
+    #[serde(skip)]
+    last_seen: Option<f64>,
+    #[serde(skip)]
+    violating: bool,
+}
+
+/// Cycle-time supervision of one channel
+#[derive(Debug, Clone)]
+pub struct CycleMonitor {
+    channel_id: String,
+    tolerance: f64,
+    counts: HashMap<u32, CycleCounts>,
+}
+
+impl CycleMonitor {
+    /// Supervise the DBC cycle times (when enabled) and the configured expectations
+    pub fn new(channel_id: &str, config: &CycleMonitorConfig, db: Option<&DbcDatabase>) -> Self {
+        let mut expected: HashMap<u32, f64> = HashMap::new();
+        if config.use_dbc {
+            for message in db.into_iter().flat_map(|db| db.messages.values()) {
+                if let Some(cycle_time) = message.cycle_time_ms.filter(|ms| *ms > 0) {
+                    expected.insert(message.id, cycle_time as f64);
+                }
+            }
+        }
+        for expectation in &config.expectations {
+            expected.insert(expectation.message_id, expectation.cycle_time_ms);
+        }
+
+        let counts = expected
+            .into_iter()
+            .filter(|(_, ms)| *ms > 0.0)
+            .map(|(message_id, expected_ms)| {
+                let counts = CycleCounts {
+                    message_id,
+                    expected_ms,
+                    ..Default::default()
+                };
+                (message_id, counts)
+            })
+            .collect();
+        Self {
+            channel_id: channel_id.to_string(),
+            tolerance: config.tolerance_percent.abs(),
+            counts,
+        }
+    }
+
+    /// Number of supervised IDs
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Measure the period of a frame; returns an alert when its ID starts violating
+    pub fn process(&mut self, frame: &CanFrame) -> Option<CycleViolation> {
+        // DBC IDs of extended frames carry bit 31
+        let dbc_id = if frame.is_extended { frame.id | 0x8000_0000 } else { frame.id };
+        let key = if self.counts.contains_key(&dbc_id) { dbc_id } else { frame.id };
+        let counts = self.counts.get_mut(&key)?;
+
+        let now = frame.timestamp;
+        let last_seen = counts.last_seen.replace(now)?;
+        let measured_ms = (now - last_seen).max(0.0) * 1000.0;
+        let deviation = (measured_ms - counts.expected_ms) / counts.expected_ms * 100.0;
+        counts.checked += 1;
+        counts.last_measured_ms = Some(measured_ms);
+        counts.max_deviation_percent = counts.max_deviation_percent.max(deviation.abs());
+
+        let violating = deviation.abs() > self.tolerance;
+        if violating {
+            if deviation < 0.0 {
+                counts.too_fast += 1;
+            } else {
+                counts.too_slow += 1;
+            }
+        }
+        let starts = violating && !counts.violating;
+        counts.violating = violating;
+        starts.then(|| CycleViolation {
+            channel_id: self.channel_id.clone(),
+            message_id: counts.message_id,
+            expected_ms: counts.expected_ms,
+            measured_ms,
+            deviation_percent: deviation,
+            timestamp: now,
+        })
+    }
+
+    /// Counters of all supervised IDs, ordered by ID
+    pub fn counts(&self) -> Vec<CycleCounts> {
+        let mut counts: Vec<CycleCounts> = self.counts.values().cloned().collect();
+        counts.sort_by_key(|c| c.message_id);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Engine: 8 ECU\n SG_ RPM : 0|16@1+ (1,0) [0|8000] \"rpm\" Vector__XXX\nBO_ 512 Brake: 8 ECU\n\nBA_ \"GenMsgCycleTime\" BO_ 256 100;\nBA_ \"GenMsgCycleTime\" BO_ 512 0;\n";
+
+    fn frame(id: u32, timestamp: f64) -> CanFrame {
+        let mut frame = CanFrame::new(id, &[0; 8]);
+        frame.timestamp = timestamp;
+        frame
+    }
+
+    #[test]
+    fn test_expectations_from_dbc_and_config() {
+        let db = DbcParser::parse(DBC).unwrap();
+        assert_eq!(db.get_message(256).unwrap().cycle_time_ms, Some(100));
+
+        let monitor = CycleMonitor::new("can0", &CycleMonitorConfig::default(), Some(&db));
+        // A cycle time of 0 marks a non-periodic message
+        assert_eq!(monitor.len(), 1);
+
+        let config = CycleMonitorConfig {
+            expectations: vec![CycleExpectation { message_id: 256, cycle_time_ms: 50.0 }],
+            ..Default::default()
+        };
+        let monitor = CycleMonitor::new("can0", &config, Some(&db));
+        assert_eq!(monitor.counts()[0].expected_ms, 50.0);
+        let config = CycleMonitorConfig { use_dbc: false, ..Default::default() };
+        assert!(CycleMonitor::new("can0", &config, Some(&db)).is_empty());
+    }
+
+    #[test]
+    fn test_violations_counted_and_alerted_once() {
+        let db = DbcParser::parse(DBC).unwrap();
+        let mut monitor = CycleMonitor::new("can0", &CycleMonitorConfig::default(), Some(&db));
+
+        let mut alerts = Vec::new();
+        // On time, twice late, back on time, then too fast
+        for t in [0.0, 0.1, 0.205, 0.35, 0.5, 0.6, 0.65] {
+            alerts.extend(monitor.process(&frame(256, t)));
+        }
+        assert!(monitor.process(&frame(0x300, 1.0)).is_none());
+
+        let counts = &monitor.counts()[0];
+        assert_eq!((counts.checked, counts.too_slow, counts.too_fast), (6, 2, 1));
+        assert!((counts.max_deviation_percent - 50.0).abs() < 1e-6);
+        assert_eq!(alerts.len(), 2);
+        assert!((alerts[0].measured_ms - 145.0).abs() < 1e-6);
+        assert!(alerts[1].deviation_percent < 0.0);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::core::dbc::models::*;
+use crate::error::BootCanError;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Parser for LIN description files (.ldf)
+///
+/// Reads the nodes, signals, frames and signal encodings into a `DbcDatabase`
+/// keyed by frame ID, so LIN frames decode like CAN messages. LIN signals are
+/// little endian and unsigned. Schedule tables, diagnostics and node attributes
+/// are skipped.
+pub struct LdfParser;
+
+/// Size and subscribers of a signal from the `Signals` section
+struct LdfSignal {
+    size: u8,
+    subscribers: Vec<String>,
+}
+
+/// Scaling and value names from the `Signal_encoding_types` section
+#[derive(Default)]
+struct Encoding {
+    /// First physical range: raw min, raw max, factor, offset, unit
+    physical: Option<(f64, f64, f64, f64, String)>,
+    logical: HashMap<i64, String>,
+}
+
+impl LdfParser {
+    /// Parse an LDF file from a path
+    pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<DbcDatabase, BootCanError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| BootCanError::Io(format!("Failed to read LDF file: {}", e)))?;
+        Self::parse(&content)
+    }
+
+    /// Parse LDF content from a string
+    pub fn parse(content: &str) -> Result<DbcDatabase, BootCanError> {
+        let content = strip_comments(content);
+        let sections: HashMap<&str, &str> = blocks(&content).into_iter().collect();
+        let frames = sections
+            .get("Frames")
+            .ok_or_else(|| BootCanError::Parse("LDF has no Frames section".to_string()))?;
+
+        let mut db = DbcDatabase::new();
+        let version = Regex::new(r#"LIN_protocol_version\s*=\s*"([^"]*)""#).unwrap();
+        db.version = version.captures(&content).map(|c| c[1].to_string());
+
+        if let Some(nodes) = sections.get("Nodes") {
+            for statement in statements(nodes) {
+                if let Some((kind, names)) = statement.split_once(':') {
+                    let names = names.split(',').map(str::trim);
+                    // The master lists its time base and jitter after its name
+                    let count = if kind.trim() == "Master" { 1 } else { usize::MAX };
+                    db.nodes.extend(names.take(count).filter(|n| !n.is_empty()).map(String::from));
+                }
+            }
+        }
+
+        let mut signals = HashMap::new();
+        for statement in sections.get("Signals").map(|s| statements(s)).unwrap_or_default() {
+            let (name, signal) = Self::parse_signal(statement)?;
+            signals.insert(name, signal);
+        }
+
+        let mut encodings = HashMap::new();
+        for (name, body) in sections.get("Signal_encoding_types").map(|s| blocks(s)).unwrap_or_default() {
+            encodings.insert(name, Self::parse_encoding(body)?);
+        }
+        let mut representations = HashMap::new();
+        for statement in sections.get("Signal_representation").map(|s| statements(s)).unwrap_or_default() {
+            if let Some((encoding, names)) = statement.split_once(':') {
+                for name in names.split(',').map(str::trim) {
+                    representations.insert(name.to_string(), encoding.trim());
+                }
+            }
+        }
+        for (name, encoding) in &encodings {
+            if !encoding.logical.is_empty() {
+                db.value_tables.insert(
+                    name.to_string(),
+                    ValueTable {
+                        name: name.to_string(),
+                        values: encoding.logical.clone(),
+                    },
+                );
+            }
+        }
+
+        for (header, body) in blocks(frames) {
+            let (name, fields) = header
+                .split_once(':')
+                .ok_or_else(|| BootCanError::Parse(format!("Invalid LDF frame: {}", header)))?;
+            let fields: Vec<&str> = fields.split(',').map(str::trim).collect();
+            let (id, publisher, length) = match fields.as_slice() {
+                [id, publisher, length] => (parse_int(id), *publisher, parse_int(length)),
+                _ => (None, "", None),
+            };
+            let (Some(id), Some(length)) = (id, length) else {
+                return Err(BootCanError::Parse(format!("Invalid LDF frame: {}", header)));
+            };
+
+            let mut message = Message {
+                id: id as u32,
+                name: name.trim().to_string(),
+                dlc: length as u8,
+                sender: Some(publisher.to_string()),
+                signals: Vec::new(),
+                comment: None,
+                cycle_time_ms: None,
+            };
+            for statement in statements(body) {
+                let Some((signal_name, offset)) = statement.split_once(',') else {
+                    continue;
+                };
+                let signal_name = signal_name.trim();
+                let (Some(signal), Some(offset)) = (signals.get(signal_name), parse_int(offset)) else {
+                    let error = format!("Invalid signal in LDF frame {}: {}", message.name, statement);
+                    return Err(BootCanError::Parse(error));
+                };
+                let encoding_name = representations.get(signal_name).copied();
+                let encoding = encoding_name.and_then(|name| encodings.get(name));
+                let (factor, offset_value, minimum, maximum, unit) = match encoding.and_then(|e| e.physical.as_ref()) {
+                    Some((min, max, factor, offset, unit)) => {
+                        (*factor, *offset, Some(min * factor + offset), Some(max * factor + offset), unit.clone())
+                    }
+                    None => (1.0, 0.0, None, None, String::new()),
+                };
+                message.signals.push(Signal {
+                    name: signal_name.to_string(),
+                    start_bit: offset as u8,
+                    length: signal.size,
+                    byte_order: ByteOrder::LittleEndian,
+                    value_type: ValueType::Unsigned,
+                    factor,
+                    offset: offset_value,
+                    minimum,
+                    maximum,
+                    unit,
+                    receivers: signal.subscribers.clone(),
+                    comment: None,
+                    value_table: encoding
+                        .filter(|encoding| !encoding.logical.is_empty())
+                        .and(encoding_name)
+                        .map(String::from),
+                });
+            }
+            db.messages.insert(message.id, message);
+        }
+
+        Ok(db)
+    }
+
+    /// `Name: size, init_value, publisher, subscriber, ...`
+    fn parse_signal(statement: &str) -> Result<(String, LdfSignal), BootCanError> {
+        let invalid = || BootCanError::Parse(format!("Invalid LDF signal: {}", statement));
+        let (name, fields) = statement.split_once(':').ok_or_else(invalid)?;
+        // Byte array signals have an initial value in braces, which may contain commas
+        let fields = match (fields.find('{'), fields.find('}')) {
+            (Some(open), Some(close)) if open < close => format!("{}0{}", &fields[..open], &fields[close + 1..]),
+            _ => fields.to_string(),
+        };
+        let fields: Vec<&str> = fields.split(',').map(str::trim).collect();
+        if fields.len() < 3 {
+            return Err(invalid());
+        }
+        let size = parse_int(fields[0]).filter(|&size| (1..=64).contains(&size)).ok_or_else(invalid)?;
+        Ok((
+            name.trim().to_string(),
+            LdfSignal {
+                size: size as u8,
+                subscribers: fields[3..].iter().map(|s| s.to_string()).collect(),
+            },
+        ))
+    }
+
+    /// `physical_value, min, max, scale, offset, "unit";` and `logical_value, value, "text";` entries
+    fn parse_encoding(body: &str) -> Result<Encoding, BootCanError> {
+        let mut encoding = Encoding::default();
+        for statement in statements(body) {
+            let fields: Vec<&str> = statement.split(',').map(str::trim).collect();
+            let invalid = || BootCanError::Parse(format!("Invalid LDF signal encoding: {}", statement));
+            match fields[0] {
+                "physical_value" if encoding.physical.is_none() => {
+                    let number = |i: usize| fields.get(i).and_then(|f| f.parse::<f64>().ok()).ok_or_else(invalid);
+                    let unit = fields.get(5).map(|u| u.trim_matches('"').to_string()).unwrap_or_default();
+                    encoding.physical = Some((number(1)?, number(2)?, number(3)?, number(4)?, unit));
+                }
+                "logical_value" => {
+                    let value = fields.get(1).and_then(|v| parse_int(v)).ok_or_else(invalid)?;
+                    let text = fields.get(2).map(|t| t.trim_matches('"').to_string()).unwrap_or_default();
+                    encoding.logical.insert(value, text);
+                }
+                // Further physical ranges, BCD and ASCII values
+                _ => {}
+            }
+        }
+        Ok(encoding)
+    }
+}
+
+/// Decimal or `0x` hexadecimal integer
+fn parse_int(text: &str) -> Option<i64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Content without `//` and `/* */` comments
+fn strip_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find('/') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start..];
+        if after.starts_with("//") {
+            rest = after.find('\n').map_or("", |end| &after[end..]);
+        } else if after.starts_with("/*") {
+            rest = after.find("*/").map_or("", |end| &after[end + 2..]);
+        } else {
+            result.push('/');
+            rest = &after[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The `header { body }` blocks at the top level of `text`
+fn blocks(text: &str) -> Vec<(&str, &str)> {
+    let mut blocks = Vec::new();
+    let (mut depth, mut header_start, mut body_start) = (0usize, 0, 0);
+    let mut header = "";
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => {
+                if depth == 0 {
+                    header = text[header_start..i].trim();
+                    body_start = i + 1;
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    blocks.push((header, &text[body_start..i]));
+                    header_start = i + 1;
+                }
+            }
+            ';' if depth == 0 => header_start = i + 1,
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// The `;` terminated statements of a section, without nested blocks
+fn statements(text: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => {
+                let statement = text[start..i].trim();
+                if !statement.is_empty() {
+                    statements.push(statement);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LDF: &str = r#"
+LIN_description_file;
+LIN_protocol_version = "2.1";
+LIN_language_version = "2.1";
+LIN_speed = 19.2 kbps;
+
+Nodes {
+  Master: BCM, 5 ms, 0.1 ms ;
+  Slaves: Motor, Switch ;
+}
+
+Signals {
+  MotorSpeed: 16, 0, Motor, BCM ; // rpm
+  MotorState: 2, 0, Motor, BCM ;
+  Serial: 16, {0, 0}, Motor, BCM ;
+}
+
+Frames {
+  MotorStatus: 0x10, Motor, 4 {
+    MotorSpeed, 0 ;
+    MotorState, 16 ;
+  }
+  MotorInfo: 17, Motor, 2 {
+    Serial, 0 ;
+  }
+}
+
+/* Scaling of the motor signals */
+Signal_encoding_types {
+  SpeedEncoding {
+    physical_value, 0, 65534, 0.5, 0, "rpm" ;
+    logical_value, 65535, "Invalid" ;
+  }
+  StateEncoding {
+    logical_value, 0, "Off" ;
+    logical_value, 1, "Running" ;
+  }
+}
+
+Signal_representation {
+  SpeedEncoding: MotorSpeed ;
+  StateEncoding: MotorState ;
+}
+"#;
+
+    #[test]
+    fn test_parse_ldf() {
+        let db = LdfParser::parse(LDF).unwrap();
+        assert_eq!(db.version.as_deref(), Some("2.1"));
+        assert_eq!(db.nodes, vec!["BCM", "Motor", "Switch"]);
+        assert_eq!(db.messages.len(), 2);
+
+        let status = db.get_message(0x10).unwrap();
+        assert_eq!((status.name.as_str(), status.dlc, status.sender.as_deref()), ("MotorStatus", 4, Some("Motor")));
+        let speed = &status.signals[0];
+        assert_eq!((speed.start_bit, speed.length, speed.factor), (0, 16, 0.5));
+        assert_eq!((speed.unit.as_str(), speed.maximum), ("rpm", Some(32767.0)));
+        assert_eq!(status.signals[1].value_table.as_deref(), Some("StateEncoding"));
+        assert_eq!(db.get_message(17).unwrap().signals[0].length, 16);
+    }
+
+    #[test]
+    fn test_decode_lin_frame() {
+        let db = LdfParser::parse(LDF).unwrap();
+        let decoded = db.decode_message(0x10, &[0xD0, 0x07, 0x01, 0x00]);
+        assert_eq!(decoded[0].physical_value, 1000.0);
+        assert_eq!(decoded[1].raw_value, 1);
+
+        assert!(LdfParser::parse("Nodes { Master: BCM, 5 ms, 0.1 ms ; }").is_err());
+        assert!(LdfParser::parse("Signals { Broken ; } Frames { }").is_err());
+    }
+}
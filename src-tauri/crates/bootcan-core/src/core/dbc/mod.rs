@@ -1,7 +1,9 @@
+pub mod ldf_parser;
 pub mod models;
 pub mod parser;
 pub mod sym_parser;
 
+pub use ldf_parser::LdfParser;
 pub use models::*;
 pub use parser::DbcParser;
 pub use sym_parser::SymParser;
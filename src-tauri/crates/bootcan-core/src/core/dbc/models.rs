@@ -19,6 +19,9 @@ pub struct Message {
     pub sender: Option<String>,
     pub signals: Vec<Signal>,
     pub comment: Option<String>,
+    /// Expected transmission period (DBC `GenMsgCycleTime`, SYM `CycleTime`)
+    #[serde(default)]
+    pub cycle_time_ms: Option<u32>,
 }
 
 /// Signal definition within a message
@@ -127,15 +130,71 @@ impl DbcDatabase {
             vec![]
         }
     }
+
+    /// Encode a physical signal value into message data, leaving other bits untouched
+    pub fn encode_signal(&self, message_id: u32, signal_name: &str, data: &mut [u8], value: f64) -> Option<()> {
+        let message = self.get_message(message_id)?;
+        let signal = message.signals.iter().find(|s| s.name == signal_name)?;
+        signal.insert_physical_value(data, value)
+    }
 }
 
 impl Signal {
-    /// Extract raw integer value from CAN data
-    fn extract_raw_value(&self, data: &[u8]) -> Option<i64> {
-        if data.len() < 8 {
+    /// Write a physical value into CAN data (rounded and clamped to the raw range)
+    pub fn insert_physical_value(&self, data: &mut [u8], value: f64) -> Option<()> {
+        if self.length == 0 || self.length > 64 {
+            return None;
+        }
+        let raw = (value - self.offset) / self.factor;
+        let start_byte = (self.start_bit / 8) as usize;
+
+        match self.value_type {
+            ValueType::Unsigned => {
+                let max = (u64::MAX >> (64 - self.length)) as f64;
+                self.insert_unsigned(data, raw.round().clamp(0.0, max) as u64)
+            }
+            ValueType::Signed => {
+                let max = (i64::MAX >> (64 - self.length)) as f64;
+                let value = raw.round().clamp(-max - 1.0, max) as i64;
+                self.insert_unsigned(data, value as u64 & (u64::MAX >> (64 - self.length)))
+            }
+            ValueType::Float if self.length == 32 => {
+                let bytes = (raw as f32).to_le_bytes();
+                data.get_mut(start_byte..start_byte + 4)?.copy_from_slice(&bytes);
+                Some(())
+            }
+            ValueType::Double if self.length == 64 => {
+                let bytes = raw.to_le_bytes();
+                data.get_mut(start_byte..start_byte + 8)?.copy_from_slice(&bytes);
+                Some(())
+            }
+            ValueType::Float | ValueType::Double => None,
+        }
+    }
+
+    fn insert_unsigned(&self, data: &mut [u8], value: u64) -> Option<()> {
+        let last_bit = self.start_bit as usize + self.length as usize - 1;
+        if last_bit / 8 >= data.len() {
             return None;
         }
 
+        for i in 0..self.length as usize {
+            let bit = self.start_bit as usize + i;
+            let mask = 1u8 << (bit % 8);
+            if (value >> i) & 1 != 0 {
+                data[bit / 8] |= mask;
+            } else {
+                data[bit / 8] &= !mask;
+            }
+        }
+        Some(())
+    }
+
+    /// Extract raw integer value from CAN data
+    ///
+    /// Shorter payloads decode as long as they hold all bits of the signal,
+    /// as LIN frames are often only a few bytes long.
+    fn extract_raw_value(&self, data: &[u8]) -> Option<i64> {
         let start_byte = (self.start_bit / 8) as usize;
         let start_bit_in_byte = (self.start_bit % 8) as u8;
 
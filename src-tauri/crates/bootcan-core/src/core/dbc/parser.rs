@@ -1,4 +1,5 @@
 use crate::core::dbc::models::*;
+use crate::error::BootCanError;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -7,14 +8,14 @@ pub struct DbcParser;
 
 impl DbcParser {
     /// Parse a DBC file from a path
-    pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<DbcDatabase, String> {
+    pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<DbcDatabase, BootCanError> {
         let content = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read DBC file: {}", e))?;
+            .map_err(|e| BootCanError::Io(format!("Failed to read DBC file: {}", e)))?;
         Self::parse(&content)
     }
 
     /// Parse DBC content from a string
-    pub fn parse(content: &str) -> Result<DbcDatabase, String> {
+    pub fn parse(content: &str) -> Result<DbcDatabase, BootCanError> {
         let mut db = DbcDatabase::new();
         let mut current_message_id: Option<u32> = None;
         let mut value_tables: HashMap<String, HashMap<i64, String>> = HashMap::new();
@@ -41,6 +42,7 @@ impl DbcParser {
                         sender,
                         signals: vec![],
                         comment: None,
+                        cycle_time_ms: None,
                     };
                     db.messages.insert(id, message);
                     current_message_id = Some(id);
@@ -66,6 +68,14 @@ impl DbcParser {
             else if line.starts_with("CM_") {
                 Self::parse_comment(line, &mut db, current_message_id);
             }
+            // Parse message cycle time: BA_ "GenMsgCycleTime" BO_ <message_id> <ms>;
+            else if line.starts_with("BA_ ") {
+                if let Some((id, cycle_time)) = Self::parse_cycle_time(line) {
+                    if let Some(message) = db.messages.get_mut(&id) {
+                        message.cycle_time_ms = Some(cycle_time);
+                    }
+                }
+            }
             // Parse node: BU_: <node1> <node2> ...
             else if line.starts_with("BU_:") {
                 db.nodes = Self::parse_nodes(line);
@@ -237,6 +247,16 @@ impl DbcParser {
         }
     }
 
+    fn parse_cycle_time(line: &str) -> Option<(u32, u32)> {
+        let parts: Vec<&str> = line.trim_end_matches(';').split_whitespace().collect();
+        if parts.len() != 5 || parts[1] != "\"GenMsgCycleTime\"" || parts[2] != "BO_" {
+            return None;
+        }
+        let id = parts[3].parse::<u32>().ok()?;
+        let cycle_time = parts[4].parse::<u32>().ok()?;
+        Some((id, cycle_time))
+    }
+
     fn parse_nodes(line: &str) -> Vec<String> {
         // BU_: <node1> <node2> ...
         line.trim_start_matches("BU_:")
@@ -1,4 +1,5 @@
 use crate::core::dbc::models::*;
+use crate::error::BootCanError;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -8,14 +9,14 @@ pub struct SymParser;
 
 impl SymParser {
     /// Parse a SYM file from a path
-    pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<DbcDatabase, String> {
+    pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<DbcDatabase, BootCanError> {
         let content = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read SYM file: {}", e))?;
+            .map_err(|e| BootCanError::Io(format!("Failed to read SYM file: {}", e)))?;
         Self::parse(&content)
     }
 
     /// Parse SYM content from a string
-    pub fn parse(content: &str) -> Result<DbcDatabase, String> {
+    pub fn parse(content: &str) -> Result<DbcDatabase, BootCanError> {
         let mut db = DbcDatabase::new();
         let mut signal_definitions: HashMap<String, Signal> = HashMap::new();
         let mut value_tables: HashMap<String, HashMap<i64, String>> = HashMap::new();
@@ -101,6 +102,15 @@ impl SymParser {
                     }
                 }
             }
+            // Parse CycleTime=x of the message just created
+            else if in_sendreceive_section && line.starts_with("CycleTime=") {
+                let cycle_time = line.trim_start_matches("CycleTime=").split_whitespace().next();
+                if let (Some(id), Some(Ok(cycle_time))) = (current_message_id, cycle_time.map(str::parse::<u32>)) {
+                    if let Some(message) = db.messages.get_mut(&id) {
+                        message.cycle_time_ms = Some(cycle_time);
+                    }
+                }
+            }
             // Parse DLC=x or Len=x (part of message header)
             else if in_sendreceive_section && current_message_name.is_some() && (line.starts_with("DLC=") || line.starts_with("Len=")) {
                 let prefix = if line.starts_with("DLC=") { "DLC=" } else { "Len=" };
@@ -289,6 +299,7 @@ impl SymParser {
                 sender: None,
                 signals: vec![],
                 comment: None,
+                cycle_time_ms: None,
             };
             db.messages.insert(final_id, message);
             // Restore id for signal parsing
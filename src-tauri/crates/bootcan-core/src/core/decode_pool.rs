@@ -0,0 +1,307 @@
+//! Sharded live DBC decoding
+//!
+//! Several FD channels near saturation carry more frames than one core can
+//! decode. A `DecodePool` spreads the frames over worker threads by channel
+//! and ID, so the frames of one message always go to the same worker and stay
+//! in order. Decoded frames are broadcast to the consumers, like signal
+//! watches and the InfluxDB export, which then only compare and keep values.
+
+use super::channel::ChannelHandle;
+use super::dbc::{DbcDatabase, DecodedSignal};
+use super::message::CanFrame;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use tokio::sync::broadcast;
+use tokio::task::AbortHandle;
+
+/// Most workers started when the count is left to the pool
+pub const MAX_DECODE_WORKERS: usize = 8;
+/// Frames waiting for one worker before further frames are dropped
+const SHARD_QUEUE_LEN: usize = 4096;
+/// Decoded frames a consumer can fall behind before it loses frames
+pub const DECODED_QUEUE_LEN: usize = 4096;
+
+/// A frame with all signals of its message
+#[derive(Debug, Clone)]
+pub struct DecodedFrame {
+    pub frame: CanFrame,
+    pub signals: Vec<DecodedSignal>,
+}
+
+/// Counters of the pool, returned in the diagnostics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodePoolStats {
+    pub workers: usize,
+    /// Channels whose frames are decoded
+    pub channels: Vec<String>,
+    pub decoded: u64,
+    /// Frames not decoded because their worker was too far behind
+    pub dropped: u64,
+    /// Decoded frames lost by consumers that fell behind
+    pub lost: u64,
+}
+
+struct Job {
+    frame: CanFrame,
+    db: Arc<DbcDatabase>,
+}
+
+/// Channel feeding the pool while someone holds it
+///
+/// Shared by every consumer of the channel, so each frame is decoded once.
+pub struct DecodeFeed {
+    channel: String,
+    /// Database the frames are decoded with, replaced when attached again
+    db: Arc<Mutex<Arc<DbcDatabase>>>,
+    task: AbortHandle,
+}
+
+impl DecodeFeed {
+    /// ID of the channel fed
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+}
+
+impl Drop for DecodeFeed {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Worker threads decoding the frames of the attached channels
+pub struct DecodePool {
+    shards: Vec<SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    decoded_tx: broadcast::Sender<Arc<DecodedFrame>>,
+    feeds: Mutex<HashMap<String, Weak<DecodeFeed>>>,
+    decoded: Arc<AtomicU64>,
+    dropped: AtomicU64,
+    lost: AtomicU64,
+}
+
+impl DecodePool {
+    /// Start `workers` threads; 0 starts one per core, up to `MAX_DECODE_WORKERS`
+    pub fn new(workers: usize) -> Self {
+        let workers = match workers {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get().min(MAX_DECODE_WORKERS)),
+            n => n,
+        };
+        let (decoded_tx, _) = broadcast::channel(DECODED_QUEUE_LEN);
+        let decoded = Arc::new(AtomicU64::new(0));
+        let (shards, handles) = (0..workers)
+            .filter_map(|i| {
+                let (tx, rx) = sync_channel(SHARD_QUEUE_LEN);
+                let (decoded_tx, decoded) = (decoded_tx.clone(), decoded.clone());
+                let handle = std::thread::Builder::new()
+                    .name(format!("decode-{}", i))
+                    .spawn(move || decode_worker(rx, decoded_tx, decoded));
+                match handle {
+                    Ok(handle) => Some((tx, handle)),
+                    Err(e) => {
+                        tracing::error!("Failed to start decode worker: {}", e);
+                        None
+                    }
+                }
+            })
+            .unzip();
+        Self {
+            shards,
+            workers: handles,
+            decoded_tx,
+            feeds: Mutex::new(HashMap::new()),
+            decoded,
+            dropped: AtomicU64::new(0),
+            lost: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of worker threads
+    pub fn workers(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Worker of the frames with `id` on `channel`
+    fn shard(&self, channel: &str, id: u32) -> usize {
+        let mut hasher = DefaultHasher::new();
+        (channel, id).hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Queue a frame for decoding with `db`; false if it was dropped
+    pub fn decode(&self, frame: CanFrame, db: &Arc<DbcDatabase>) -> bool {
+        if self.shards.is_empty() {
+            return false;
+        }
+        let shard = &self.shards[self.shard(&frame.channel, frame.id)];
+        match shard.try_send(Job { frame, db: db.clone() }) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// Receiver of every decoded frame, in order per channel and ID
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<DecodedFrame>> {
+        self.decoded_tx.subscribe()
+    }
+
+    /// Count decoded frames a consumer lost, for the diagnostics
+    pub fn count_lost(&self, frames: u64) {
+        self.lost.fetch_add(frames, Ordering::Relaxed);
+    }
+
+    /// Decode the frames of `channel` with `db` while the returned feed is held
+    ///
+    /// A channel already fed returns the existing feed, which decodes with
+    /// `db` from then on, so every consumer follows a newly loaded DBC.
+    pub fn attach(self: &Arc<Self>, channel: &ChannelHandle, db: DbcDatabase) -> Arc<DecodeFeed> {
+        let mut feeds = self.feeds.lock();
+        feeds.retain(|_, feed| feed.strong_count() > 0);
+        if let Some(feed) = feeds.get(channel.id()).and_then(Weak::upgrade) {
+            *feed.db.lock() = Arc::new(db);
+            return feed;
+        }
+
+        let mut rx = channel.subscribe("Decode pool");
+        let (pool, db) = (Arc::downgrade(self), Arc::new(Mutex::new(Arc::new(db))));
+        let current = db.clone();
+        let task = tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+                let db = current.lock().clone();
+                if db.get_message(frame.id).is_some() {
+                    pool.decode(frame, &db);
+                }
+            }
+        });
+        let feed = Arc::new(DecodeFeed {
+            channel: channel.id().to_string(),
+            db,
+            task: task.abort_handle(),
+        });
+        feeds.insert(channel.id().to_string(), Arc::downgrade(&feed));
+        feed
+    }
+
+    pub fn stats(&self) -> DecodePoolStats {
+        let mut channels: Vec<String> = self
+            .feeds
+            .lock()
+            .iter()
+            .filter(|(_, feed)| feed.strong_count() > 0)
+            .map(|(id, _)| id.clone())
+            .collect();
+        channels.sort();
+        DecodePoolStats {
+            workers: self.workers(),
+            channels,
+            decoded: self.decoded.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            lost: self.lost.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for DecodePool {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Drop for DecodePool {
+    fn drop(&mut self) {
+        // Workers end once their queue is closed
+        self.shards.clear();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn decode_worker(jobs: Receiver<Job>, decoded_tx: broadcast::Sender<Arc<DecodedFrame>>, decoded: Arc<AtomicU64>) {
+    while let Ok(Job { frame, db }) = jobs.recv() {
+        let signals = db.decode_message(frame.id, &frame.data);
+        decoded.fetch_add(1, Ordering::Relaxed);
+        // Nobody listening is fine, the frame is just not needed
+        let _ = decoded_tx.send(Arc::new(DecodedFrame { frame, signals }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bus_stats::StatsEmission;
+    use crate::core::channel::ChannelConfig;
+    use crate::core::dbc::DbcParser;
+    use crate::core::subscriber::DEFAULT_BROADCAST_CAPACITY;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Engine: 8 Vector__XXX\n SG_ RPM : 0|16@1+ (1,0) [0|8000] \"rpm\" Vector__XXX\n\nBO_ 257 Brake: 8 Vector__XXX\n SG_ Pressure : 0|16@1+ (0.1,0) [0|6553.5] \"bar\" Vector__XXX\n";
+
+    fn frame(id: u32, value: u16) -> CanFrame {
+        let mut frame = CanFrame::new(id, &[0; 8]);
+        frame.data[..2].copy_from_slice(&value.to_le_bytes());
+        frame.channel = "can0".into();
+        frame
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_frames_of_a_message_stay_in_order() {
+        let pool = DecodePool::new(4);
+        let db = Arc::new(DbcParser::parse(DBC).unwrap());
+        let mut rx = pool.subscribe();
+        for value in 0..100 {
+            assert!(pool.decode(frame(256 + (value as u32 % 2), value), &db));
+        }
+
+        let mut last: HashMap<u32, f64> = HashMap::new();
+        for _ in 0..100 {
+            let decoded = rx.recv().await.unwrap();
+            let raw = decoded.signals[0].raw_value as f64;
+            assert!(last.insert(decoded.frame.id, raw).is_none_or(|previous| previous < raw));
+        }
+        assert_eq!((pool.stats().workers, pool.stats().decoded, pool.stats().dropped), (4, 100, 0));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_feed_is_shared_per_channel() {
+        let pool = Arc::new(DecodePool::new(2));
+        let channel = ChannelHandle::spawn("can0", StatsEmission::default(), DEFAULT_BROADCAST_CAPACITY);
+        let config = ChannelConfig {
+            interface_id: "vcan5".to_string(),
+            ..ChannelConfig::default()
+        };
+        channel.connect(config, Box::new(|_| {})).await.unwrap();
+
+        let db = DbcParser::parse(DBC).unwrap();
+        let feed = pool.attach(&channel, db.clone());
+        // Attaching again with another DBC swaps the database of the shared feed
+        let reloaded = DbcParser::parse(&DBC.replace("RPM : 0|16@1+ (1,0)", "RPM : 0|16@1+ (2,0)")).unwrap();
+        let again = pool.attach(&channel, reloaded);
+        assert!(Arc::ptr_eq(&feed, &again));
+        assert_eq!(pool.stats().channels, vec!["can0"]);
+
+        let mut rx = pool.subscribe();
+        channel.send(CanFrame::new(0x300, &[1])).await.unwrap();
+        channel.send(frame(256, 850)).await.unwrap();
+        // Messages not in the DBC are skipped; the echo is decoded along with the transmission
+        let decoded = rx.recv().await.unwrap();
+        assert_eq!((decoded.frame.id, decoded.signals[0].physical_value), (256, 1700.0));
+
+        drop((feed, again));
+        assert!(pool.stats().channels.is_empty());
+    }
+}
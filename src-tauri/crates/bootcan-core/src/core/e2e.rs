@@ -0,0 +1,114 @@
+//! AUTOSAR E2E protection (profiles 1 and 2, standard layout)
+//!
+//! Both profiles keep the CRC in byte 0 and a 4-bit alive counter in the low
+//! nibble of byte 1. Used to re-protect frames after their payload changed.
+
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+
+/// E2E profile and its data ID configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "profile", rename_all = "camelCase")]
+pub enum E2eProfile {
+    /// CRC8 SAE J1850 over both data ID bytes and the payload; counter 0..=14
+    #[serde(rename_all = "camelCase")]
+    Profile1 { data_id: u16 },
+    /// CRC8H2F over the payload and a per-counter data ID; counter 0..=15
+    #[serde(rename_all = "camelCase")]
+    Profile2 { data_id_list: Vec<u8> },
+}
+
+fn crc8(poly: u8, bytes: impl IntoIterator<Item = u8>) -> u8 {
+    let crc = bytes.into_iter().fold(0xFFu8, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ poly } else { crc << 1 };
+        }
+        crc
+    });
+    crc ^ 0xFF
+}
+
+impl E2eProfile {
+    pub fn validate(&self) -> Result<(), BootCanError> {
+        match self {
+            Self::Profile1 { .. } => Ok(()),
+            Self::Profile2 { data_id_list } if data_id_list.len() == 16 => Ok(()),
+            Self::Profile2 { data_id_list } => Err(BootCanError::InvalidInput(format!(
+                "E2E profile 2 needs 16 data IDs, got {}",
+                data_id_list.len()
+            ))),
+        }
+    }
+
+    /// Counter value following `counter`
+    pub fn next_counter(&self, counter: u8) -> u8 {
+        match self {
+            Self::Profile1 { .. } => (counter + 1) % 15,
+            Self::Profile2 { .. } => (counter + 1) % 16,
+        }
+    }
+
+    /// Write `counter` (if given) and recompute the CRC of `data`
+    pub fn protect(&self, data: &mut [u8], counter: Option<u8>) -> Result<(), BootCanError> {
+        if data.len() < 2 {
+            return Err(BootCanError::InvalidInput("E2E protected payload needs at least 2 bytes".to_string()));
+        }
+        if let Some(counter) = counter {
+            data[1] = (data[1] & 0xF0) | (counter & 0x0F);
+        }
+
+        data[0] = match self {
+            Self::Profile1 { data_id } => {
+                let id = data_id.to_le_bytes();
+                crc8(0x1D, id.into_iter().chain(data[1..].iter().copied()))
+            }
+            Self::Profile2 { data_id_list } => {
+                let data_id = *data_id_list
+                    .get((data[1] & 0x0F) as usize)
+                    .ok_or_else(|| BootCanError::InvalidInput("E2E profile 2 data ID list too short".to_string()))?;
+                crc8(0x2F, data[1..].iter().copied().chain(std::iter::once(data_id)))
+            }
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crc8_sae_j1850(bytes: &[u8]) -> u8 {
+        crc8(0x1D, bytes.iter().copied())
+    }
+
+    fn crc8_h2f(bytes: &[u8]) -> u8 {
+        crc8(0x2F, bytes.iter().copied())
+    }
+
+    #[test]
+    fn test_crc_check_values() {
+        // Check values from the AUTOSAR CRC specification
+        assert_eq!(crc8_sae_j1850(b"123456789"), 0x4B);
+        assert_eq!(crc8_h2f(b"123456789"), 0xDF);
+        assert_eq!(crc8_sae_j1850(&[0x00, 0x00, 0x00, 0x00]), 0x59);
+    }
+
+    #[test]
+    fn test_protect() {
+        let profile = E2eProfile::Profile2 {
+            data_id_list: (0..16).collect(),
+        };
+        let mut data = [0x00, 0xA3, 0x11, 0x22];
+        profile.protect(&mut data, Some(5)).unwrap();
+        assert_eq!(data[1], 0xA5);
+        assert_eq!(data[0], crc8_h2f(&[0xA5, 0x11, 0x22, 5]));
+        assert_eq!(profile.next_counter(15), 0);
+
+        let profile = E2eProfile::Profile1 { data_id: 0x0123 };
+        profile.protect(&mut data, None).unwrap();
+        assert_eq!(data[1], 0xA5);
+        assert_eq!(data[0], crc8_sae_j1850(&[0x23, 0x01, 0xA5, 0x11, 0x22]));
+        assert_eq!(profile.next_counter(14), 0);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::message::CanFrame;
+use crate::error::BootCanError;
 use serde::{Deserialize, Serialize};
 
 /// Filter rule for CAN messages
@@ -30,6 +31,7 @@ pub struct DataByteMatch {
 
 /// Filter set with logical operators
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FilterSet {
     pub rules: Vec<FilterRule>,
     pub logic: FilterLogic,
@@ -66,7 +68,7 @@ impl FilterRule {
                 frame.dlc >= *min && frame.dlc <= *max
             }
             FilterRule::Direction { rx, tx } => {
-                (frame.direction == "rx" && *rx) || (frame.direction == "tx" && *tx)
+                (frame.direction.is_rx() && *rx) || (frame.direction.is_tx() && *tx)
             }
             FilterRule::ExtendedId(extended) => {
                 frame.is_extended == *extended
@@ -100,6 +102,25 @@ impl FilterSet {
         }
     }
 
+    /// Check that every rule can match at all
+    pub fn validate(&self) -> Result<(), BootCanError> {
+        for rule in &self.rules {
+            match rule {
+                FilterRule::IdRange { min, max } if min > max => {
+                    return Err(BootCanError::InvalidInput(format!("ID range 0x{:X}-0x{:X} is empty", min, max)));
+                }
+                FilterRule::DlcRange { min, max } if min > max => {
+                    return Err(BootCanError::InvalidInput(format!("DLC range {}-{} is empty", min, max)));
+                }
+                FilterRule::DataPattern { pattern } if pattern.iter().any(|m| m.position >= 64) => {
+                    return Err(BootCanError::InvalidInput("Data pattern byte position beyond 63".to_string()));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Check if filter set is empty (no filtering)
     pub fn is_empty(&self) -> bool {
         self.rules.is_empty()
@@ -118,6 +139,7 @@ impl Default for FilterSet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::message::Direction;
 
     #[test]
     fn test_id_range_filter() {
@@ -147,9 +169,9 @@ mod tests {
             ],
         };
         let mut frame1 = CanFrame::default();
-        frame1.data = vec![0x01, 0x02, 0x03];
+        frame1.data = vec![0x01, 0x02, 0x03].into();
         let mut frame2 = CanFrame::default();
-        frame2.data = vec![0x02, 0x02, 0x03];
+        frame2.data = vec![0x02, 0x02, 0x03].into();
 
         assert!(filter.matches(&frame1));
         assert!(!filter.matches(&frame2));
@@ -167,11 +189,11 @@ mod tests {
 
         let mut frame1 = CanFrame::default();
         frame1.id = 0x150;
-        frame1.direction = "rx".to_string();
+        frame1.direction = Direction::Rx;
 
         let mut frame2 = CanFrame::default();
         frame2.id = 0x150;
-        frame2.direction = "tx".to_string();
+        frame2.direction = Direction::Tx;
 
         assert!(filter_set.matches(&frame1));
         assert!(!filter_set.matches(&frame2));
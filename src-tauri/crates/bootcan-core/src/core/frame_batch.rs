@@ -0,0 +1,121 @@
+//! Batching of frames for the frontend
+//!
+//! Emitting one event per frame saturates the IPC bridge at high bus load.
+//! Received frames are collected per channel and flushed as one array at a
+//! fixed interval. Every frame gets a sequence number, and a batch carries the
+//! number of its first frame, so the frontend sees frames that were dropped
+//! between batches as a gap. Frames of emitted batches can be handed back to
+//! be overwritten by later frames.
+
+use super::frame_pool::FramePool;
+use super::message::CanFrame;
+use serde::{Deserialize, Serialize};
+
+/// Frames held between two flushes before newer frames are dropped
+pub const MAX_PENDING_FRAMES: usize = 50_000;
+
+/// Frames of one channel, emitted as `can-messages`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameBatch {
+    pub channel_id: String,
+    /// Sequence number of the first frame; the others follow without gaps
+    pub seq: u64,
+    pub frames: Vec<CanFrame>,
+}
+
+/// Frames collected since the last flush
+pub struct FrameBatcher {
+    channel_id: String,
+    pending: Vec<CanFrame>,
+    /// Sequence number of the first pending frame
+    seq: u64,
+    /// Frames dropped since the last flush
+    dropped: u64,
+    capacity: usize,
+    pool: FramePool,
+}
+
+impl FrameBatcher {
+    pub fn new(channel_id: &str, capacity: usize) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+            pending: Vec::new(),
+            seq: 0,
+            dropped: 0,
+            capacity,
+            pool: FramePool::default(),
+        }
+    }
+
+    /// Add a frame to the next batch, dropping it if the batch is full
+    pub fn push(&mut self, frame: &CanFrame) {
+        if self.pending.len() < self.capacity {
+            let frame = self.pool.copy(frame);
+            self.pending.push(frame);
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    /// Take the frames collected since the last flush
+    pub fn flush(&mut self) -> Option<FrameBatch> {
+        if self.pending.is_empty() {
+            self.seq += std::mem::take(&mut self.dropped);
+            return None;
+        }
+        let frames = std::mem::take(&mut self.pending);
+        let batch = FrameBatch {
+            channel_id: self.channel_id.clone(),
+            seq: self.seq,
+            frames,
+        };
+        // Dropped frames keep their numbers, leaving a gap before the next batch
+        self.seq += batch.frames.len() as u64 + std::mem::take(&mut self.dropped);
+        Some(batch)
+    }
+
+    /// Frames dropped since the last flush
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Hand back the frames of an emitted batch for reuse
+    pub fn recycle(&mut self, frames: Vec<CanFrame>) {
+        self.pool.recycle(frames);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batches_are_numbered_consecutively() {
+        let mut batcher = FrameBatcher::new("can0", 10);
+        assert!(batcher.flush().is_none());
+        for id in 0..3 {
+            batcher.push(&CanFrame::new(id, &[]));
+        }
+        let batch = batcher.flush().unwrap();
+        assert_eq!((batch.channel_id.as_str(), batch.seq, batch.frames.len()), ("can0", 0, 3));
+        assert!(batcher.flush().is_none());
+
+        batcher.push(&CanFrame::new(3, &[]));
+        assert_eq!(batcher.flush().unwrap().seq, 3);
+    }
+
+    #[test]
+    fn test_dropped_frames_leave_a_gap() {
+        let mut batcher = FrameBatcher::new("can0", 2);
+        for id in 0..5 {
+            batcher.push(&CanFrame::new(id, &[]));
+        }
+        assert_eq!(batcher.dropped(), 3);
+        let batch = batcher.flush().unwrap();
+        assert_eq!(batch.frames.iter().map(|f| f.id).collect::<Vec<_>>(), vec![0, 1]);
+
+        batcher.push(&CanFrame::new(5, &[]));
+        assert_eq!(batcher.flush().unwrap().seq, 5);
+    }
+}
@@ -0,0 +1,141 @@
+use super::channel::ChannelHandle;
+use super::message::CanFrame;
+use super::subscriber::FrameSubscriber;
+use crate::error::BootCanError;
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Bidirectional frame link used by request/response protocol clients
+#[async_trait]
+pub trait FrameLink: Send {
+    /// Transmit a frame
+    async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError>;
+
+    /// Wait for the next received frame (None on timeout)
+    async fn recv(&mut self, timeout: Duration) -> Result<Option<CanFrame>, BootCanError>;
+
+    /// Wait for the next received frame with the given ID (None on timeout)
+    async fn recv_id(&mut self, id: u32, timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.recv(remaining).await? {
+                Some(frame) if frame.id == id => return Ok(Some(frame)),
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// A borrowed link, so clients owning their link can also run on one owned elsewhere
+#[async_trait]
+impl<L: FrameLink + ?Sized> FrameLink for &mut L {
+    async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError> {
+        (**self).send(frame).await
+    }
+
+    async fn recv(&mut self, timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+        (**self).recv(timeout).await
+    }
+
+    async fn recv_id(&mut self, id: u32, timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+        (**self).recv_id(id, timeout).await
+    }
+}
+
+/// Frame link on top of a connected channel
+pub struct ChannelLink {
+    channel: ChannelHandle,
+    rx: FrameSubscriber,
+}
+
+impl ChannelLink {
+    /// Create a link; only frames received after this call are delivered
+    pub fn new(channel: ChannelHandle) -> Self {
+        let rx = channel.subscribe("Frame link");
+        Self { channel, rx }
+    }
+}
+
+#[async_trait]
+impl FrameLink for ChannelLink {
+    async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError> {
+        self.channel.send(frame).await
+    }
+
+    async fn recv(&mut self, timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match tokio::time::timeout_at(deadline, self.rx.recv()).await {
+                Err(_) => return Ok(None),
+                // Our own transmissions are broadcast too; only hand out received frames
+                Ok(Some(frame)) if frame.direction.is_rx() => return Ok(Some(frame)),
+                Ok(Some(_)) => continue,
+                Ok(None) => return Err(BootCanError::NotConnected("Channel closed".to_string())),
+            }
+        }
+    }
+}
+
+/// Scripted link shared by the protocol client tests
+#[cfg(test)]
+pub(crate) mod scripted {
+    use super::*;
+    use std::collections::VecDeque;
+
+    type Respond = Box<dyn Fn(&CanFrame, &[u8]) -> CanFrame + Send>;
+
+    /// Link that records sent frames and answers each with the next scripted frames
+    ///
+    /// `respond` builds a response frame from the request and the scripted payload.
+    pub(crate) struct ScriptedLink {
+        pub sent: Vec<CanFrame>,
+        responses: VecDeque<Vec<Vec<u8>>>,
+        pending: VecDeque<CanFrame>,
+        respond: Respond,
+    }
+
+    impl ScriptedLink {
+        /// One response frame per request
+        pub fn new(responses: Vec<Vec<u8>>, respond: impl Fn(&CanFrame, &[u8]) -> CanFrame + Send + 'static) -> Self {
+            Self::with_bursts(responses.into_iter().map(|data| vec![data]).collect(), respond)
+        }
+
+        /// Any number of response frames per request
+        pub fn with_bursts(
+            responses: Vec<Vec<Vec<u8>>>,
+            respond: impl Fn(&CanFrame, &[u8]) -> CanFrame + Send + 'static,
+        ) -> Self {
+            Self {
+                sent: Vec::new(),
+                responses: responses.into(),
+                pending: VecDeque::new(),
+                respond: Box::new(respond),
+            }
+        }
+
+        /// Frames received in order, whatever is sent
+        pub fn replay(frames: Vec<CanFrame>) -> Self {
+            let mut link = Self::with_bursts(Vec::new(), |request, _| request.clone());
+            link.pending = frames.into();
+            link
+        }
+    }
+
+    #[async_trait]
+    impl FrameLink for ScriptedLink {
+        async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError> {
+            if let Some(responses) = self.responses.pop_front() {
+                self.pending.extend(responses.iter().map(|data| (self.respond)(&frame, data)));
+            }
+            self.sent.push(frame);
+            Ok(())
+        }
+
+        async fn recv(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+            Ok(self.pending.pop_front())
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Recycling of frames on hot paths
+//!
+//! Buffers that keep frames for a while (batches, the frame store) take their
+//! copies from a pool of frames they are done with.
+
+use super::message::CanFrame;
+
+/// Frames kept for reuse by default
+pub const DEFAULT_POOL_SIZE: usize = 4096;
+
+/// Frames that can be overwritten instead of allocating new ones
+pub struct FramePool {
+    free: Vec<CanFrame>,
+    capacity: usize,
+}
+
+impl FramePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            free: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Copy of `frame`, made from a recycled frame when one is available
+    pub fn copy(&mut self, frame: &CanFrame) -> CanFrame {
+        match self.free.pop() {
+            Some(mut recycled) => {
+                recycled.clone_from(frame);
+                recycled
+            }
+            None => frame.clone(),
+        }
+    }
+
+    /// Keep frames that are no longer needed for later copies
+    pub fn recycle(&mut self, frames: impl IntoIterator<Item = CanFrame>) {
+        let room = self.capacity - self.free.len();
+        self.free.extend(frames.into_iter().take(room));
+    }
+
+    /// Frames available for reuse
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+impl Default for FramePool {
+    fn default() -> Self {
+        Self::new(DEFAULT_POOL_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copies_reuse_recycled_frames() {
+        let mut pool = FramePool::new(2);
+        pool.recycle(vec![CanFrame::new(0x1, &[1]).as_received("can1", 1.0)]);
+
+        let frame = CanFrame::new(0x123, &[4, 5]).as_received("can0", 2.0);
+        let copy = pool.copy(&frame);
+        assert_eq!((copy.id, copy.channel.as_str(), copy.timestamp), (0x123, "can0", 2.0));
+        assert_eq!(copy.data, vec![4, 5]);
+        assert!(pool.is_empty());
+
+        pool.recycle(vec![frame.clone(), frame.clone(), frame]);
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn test_empty_pool_clones() {
+        let mut pool = FramePool::default();
+        assert!(pool.is_empty());
+        let frame = CanFrame::new(0x7FF, &[1, 2, 3]);
+        let copy = pool.copy(&frame);
+        assert_eq!((copy.id, copy.dlc, copy.direction.as_str()), (0x7FF, 3, "tx"));
+    }
+}
@@ -0,0 +1,208 @@
+//! Backend-held message list
+//!
+//! At rates where one event per frame (or even per batch) is too much for the
+//! frontend, the backend keeps the authoritative view instead: the latest frame
+//! of every ID and a chronological ring buffer. The frontend polls for the
+//! changes since the sequence number it saw last.
+
+use super::message::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Frames kept in the chronological buffer
+pub const DEFAULT_STORE_CAPACITY: usize = 200_000;
+
+/// Latest frame of one ID on one channel and direction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestFrame {
+    pub frame: CanFrame,
+    pub count: u64,
+    /// Time since the previous frame of the ID in ms (0 for the first)
+    pub cycle_time_ms: f64,
+    /// Sequence number of the frame
+    #[serde(skip)]
+    seq: u64,
+}
+
+/// Changes since a sequence number, returned by `get_frame_delta`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameDelta {
+    /// Sequence number to ask for next time
+    pub next_seq: u64,
+    /// Frames since the requested sequence number no longer or not returned
+    pub missed: u64,
+    /// Newest frames since the requested sequence number, oldest first
+    pub frames: Vec<CanFrame>,
+    /// IDs that received frames since the requested sequence number
+    pub latest: Vec<LatestFrame>,
+}
+
+/// Latest frame per ID and a ring buffer of all frames
+pub struct FrameStore {
+    /// Latest frames per channel, keyed by ID and whether they were transmitted
+    latest: HashMap<String, HashMap<(u32, bool), LatestFrame>>,
+    frames: VecDeque<CanFrame>,
+    /// Sequence number of the oldest buffered frame
+    first_seq: u64,
+    capacity: usize,
+}
+
+impl FrameStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            latest: HashMap::new(),
+            frames: VecDeque::new(),
+            first_seq: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Sequence number the next frame gets
+    pub fn next_seq(&self) -> u64 {
+        self.first_seq + self.frames.len() as u64
+    }
+
+    pub fn push(&mut self, frame: &CanFrame) {
+        let seq = self.next_seq();
+        if !self.latest.contains_key(frame.channel.as_str()) {
+            self.latest.insert(frame.channel.to_string(), HashMap::new());
+        }
+        let latest = self.latest.get_mut(frame.channel.as_str()).expect("inserted above");
+        let key = (frame.id, frame.direction.is_tx());
+        match latest.get_mut(&key) {
+            Some(entry) => {
+                entry.cycle_time_ms = (frame.timestamp - entry.frame.timestamp) * 1000.0;
+                entry.count += 1;
+                entry.frame.clone_from(frame);
+                entry.seq = seq;
+            }
+            None => {
+                latest.insert(key, LatestFrame {
+                    frame: frame.clone(),
+                    count: 1,
+                    cycle_time_ms: 0.0,
+                    seq,
+                });
+            }
+        }
+
+        // A full buffer overwrites its oldest frame instead of allocating a new one
+        let stored = if self.frames.len() == self.capacity {
+            self.first_seq += 1;
+            self.frames.pop_front().map(|mut oldest| {
+                oldest.clone_from(frame);
+                oldest
+            })
+        } else {
+            None
+        };
+        self.frames.push_back(stored.unwrap_or_else(|| frame.clone()));
+    }
+
+    /// Changes since `since`, with at most `max_frames` of the newest frames
+    pub fn delta(&self, since: u64, max_frames: usize) -> FrameDelta {
+        let next_seq = self.next_seq();
+        let since = since.min(next_seq);
+        let evicted = self.first_seq.saturating_sub(since);
+        let start = since.saturating_sub(self.first_seq) as usize;
+        let available = self.frames.len() - start;
+        let skipped = available.saturating_sub(max_frames);
+        let frames = self.frames.range(start + skipped..).cloned().collect();
+        let mut latest: Vec<LatestFrame> = self
+            .latest
+            .values()
+            .flat_map(|ids| ids.values())
+            .filter(|e| e.seq >= since)
+            .cloned()
+            .collect();
+        latest.sort_by_key(|e| e.seq);
+
+        FrameDelta {
+            next_seq,
+            missed: evicted + skipped as u64,
+            frames,
+            latest,
+        }
+    }
+
+    /// Forget all frames; sequence numbers keep counting
+    pub fn clear(&mut self) {
+        self.first_seq = self.next_seq();
+        self.frames.clear();
+        self.latest.clear();
+    }
+}
+
+impl Default for FrameStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_STORE_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::message::Direction;
+
+    fn frame(id: u32, timestamp: f64) -> CanFrame {
+        CanFrame {
+            timestamp,
+            channel: "can0".into(),
+            ..CanFrame::new(id, &[])
+        }
+    }
+
+    #[test]
+    fn test_delta_since_last_poll() {
+        let mut store = FrameStore::new(100);
+        store.push(&frame(0x100, 0.0));
+        store.push(&frame(0x200, 0.005));
+        let delta = store.delta(0, 1000);
+        assert_eq!((delta.next_seq, delta.missed, delta.frames.len(), delta.latest.len()), (2, 0, 2, 2));
+
+        store.push(&frame(0x100, 0.010));
+        let delta = store.delta(delta.next_seq, 1000);
+        assert_eq!(delta.frames.len(), 1);
+        assert_eq!(delta.latest.len(), 1);
+        assert_eq!(delta.latest[0].count, 2);
+        assert!((delta.latest[0].cycle_time_ms - 10.0).abs() < 1e-9);
+        assert!(store.delta(delta.next_seq, 1000).latest.is_empty());
+    }
+
+    #[test]
+    fn test_sent_and_received_frames_of_an_id_are_kept_apart() {
+        let mut store = FrameStore::new(100);
+        store.push(&frame(0x100, 0.0).as_received("can0", 0.0));
+        store.push(&frame(0x100, 0.010));
+        store.push(&frame(0x100, 0.050).as_received("can0", 0.050));
+        store.push(&frame(0x100, 0.030));
+
+        let delta = store.delta(0, 1000);
+        assert_eq!(delta.frames.len(), 4);
+        let latest: Vec<_> = delta.latest.iter().map(|e| (e.frame.direction, e.count, e.cycle_time_ms)).collect();
+        assert_eq!(latest.len(), 2);
+        let (rx, tx) = (latest[0], latest[1]);
+        assert_eq!((rx.0, rx.1), (Direction::Rx, 2));
+        assert_eq!((tx.0, tx.1), (Direction::Tx, 2));
+        assert!((rx.2 - 50.0).abs() < 1e-9 && (tx.2 - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missed_frames_are_counted() {
+        let mut store = FrameStore::new(4);
+        for i in 0..10 {
+            store.push(&frame(i, i as f64));
+        }
+        // Six frames were evicted, two more are over the limit
+        let delta = store.delta(0, 2);
+        assert_eq!(delta.missed, 8);
+        assert_eq!(delta.frames.iter().map(|f| f.id).collect::<Vec<_>>(), vec![8, 9]);
+        assert_eq!(delta.latest.len(), 10);
+
+        store.clear();
+        let delta = store.delta(delta.next_seq, 10);
+        assert_eq!((delta.next_seq, delta.missed, delta.frames.len()), (10, 0, 0));
+    }
+}
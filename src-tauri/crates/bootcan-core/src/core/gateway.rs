@@ -0,0 +1,286 @@
+//! Two-channel gateway with signal overrides
+//!
+//! Frames received on one channel are forwarded to the other. Overridden
+//! signals are decoded, replaced and re-encoded on the way, and E2E protected
+//! messages get their CRC (and optionally counter) recomputed, so the
+//! receiving ECU accepts the manipulated frames.
+
+use super::dbc::DbcDatabase;
+use super::e2e::E2eProfile;
+use super::frame_link::FrameLink;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Forwarding direction a rule applies to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GatewayDirection {
+    AToB,
+    BToA,
+    #[default]
+    Both,
+}
+
+impl GatewayDirection {
+    fn includes(self, direction: GatewayDirection) -> bool {
+        self == GatewayDirection::Both || self == direction
+    }
+}
+
+/// Value forced onto a signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OverrideValue {
+    Fixed { value: f64 },
+    /// Received value plus a constant
+    Offset { delta: f64 },
+    /// Received value times a factor
+    Scale { factor: f64 },
+    /// Cycle through values, holding each for `hold_ms`
+    #[serde(rename_all = "camelCase")]
+    Sequence { values: Vec<f64>, hold_ms: u64 },
+}
+
+impl OverrideValue {
+    fn apply(&self, received: f64, elapsed: Duration) -> f64 {
+        match self {
+            Self::Fixed { value } => *value,
+            Self::Offset { delta } => received + delta,
+            Self::Scale { factor } => received * factor,
+            Self::Sequence { values, hold_ms } => {
+                if values.is_empty() {
+                    return received;
+                }
+                let step = elapsed.as_millis() / (*hold_ms).max(1) as u128;
+                values[(step % values.len() as u128) as usize]
+            }
+        }
+    }
+}
+
+/// Signal forced in forwarded frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalOverride {
+    pub message_id: u32,
+    pub signal: String,
+    pub value: OverrideValue,
+    #[serde(default)]
+    pub direction: GatewayDirection,
+}
+
+/// E2E protection to restore on a forwarded message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct E2eMessage {
+    pub message_id: u32,
+    #[serde(flatten)]
+    pub profile: E2eProfile,
+    /// Replace the alive counter with the gateway's own sequence
+    #[serde(default)]
+    pub rewrite_counter: bool,
+}
+
+/// Gateway settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayConfig {
+    pub channel_a: String,
+    pub channel_b: String,
+    #[serde(default)]
+    pub overrides: Vec<SignalOverride>,
+    #[serde(default)]
+    pub e2e: Vec<E2eMessage>,
+}
+
+/// Running gateway, kept in the application state
+pub struct GatewayHandle {
+    pub cancel: watch::Sender<bool>,
+    /// Overrides in effect; can be replaced while the gateway runs
+    pub overrides: Arc<RwLock<Vec<SignalOverride>>>,
+}
+
+/// Frame rewriting between the two channels
+pub struct Gateway {
+    overrides: Arc<RwLock<Vec<SignalOverride>>>,
+    e2e: HashMap<u32, E2eMessage>,
+    /// DBC used to decode frames received on A and B
+    databases: (Option<DbcDatabase>, Option<DbcDatabase>),
+    counters: HashMap<(u32, GatewayDirection), u8>,
+}
+
+impl Gateway {
+    pub fn new(
+        config: &GatewayConfig,
+        overrides: Arc<RwLock<Vec<SignalOverride>>>,
+        database_a: Option<DbcDatabase>,
+        database_b: Option<DbcDatabase>,
+    ) -> Result<Self, BootCanError> {
+        if config.channel_a == config.channel_b {
+            return Err(BootCanError::InvalidInput("Gateway needs two different channels".to_string()));
+        }
+        for message in &config.e2e {
+            message.profile.validate()?;
+        }
+
+        Ok(Self {
+            overrides,
+            e2e: config.e2e.iter().map(|m| (m.message_id, m.clone())).collect(),
+            databases: (database_a, database_b),
+            counters: HashMap::new(),
+        })
+    }
+
+    /// Rewrite a frame received in `direction`'s source channel
+    pub fn process(&mut self, frame: &mut CanFrame, direction: GatewayDirection, elapsed: Duration) {
+        let database = match direction {
+            GatewayDirection::BToA => self.databases.1.as_ref().or(self.databases.0.as_ref()),
+            _ => self.databases.0.as_ref().or(self.databases.1.as_ref()),
+        };
+
+        let mut modified = false;
+        if let Some(database) = database {
+            for rule in self.overrides.read().iter() {
+                if rule.message_id != frame.id || !rule.direction.includes(direction) {
+                    continue;
+                }
+                let Some(received) = database.decode_signal(frame.id, &rule.signal, &frame.data) else {
+                    continue;
+                };
+                let value = rule.value.apply(received.physical_value, elapsed);
+                modified |= database
+                    .encode_signal(frame.id, &rule.signal, &mut frame.data, value)
+                    .is_some();
+            }
+        }
+
+        if let Some(message) = self.e2e.get(&frame.id) {
+            let counter = if message.rewrite_counter {
+                let counter = self.counters.entry((frame.id, direction)).or_insert(0);
+                let current = *counter;
+                *counter = message.profile.next_counter(current);
+                Some(current)
+            } else {
+                None
+            };
+            if modified || counter.is_some() {
+                if let Err(e) = message.profile.protect(&mut frame.data, counter) {
+                    tracing::debug!("Gateway: cannot protect 0x{:X}: {}", frame.id, e);
+                }
+            }
+        }
+    }
+}
+
+/// Forward frames between two links until cancelled
+pub async fn run_gateway<L: FrameLink>(
+    mut link_a: L,
+    mut link_b: L,
+    mut gateway: Gateway,
+    mut cancel: watch::Receiver<bool>,
+) -> Result<(), BootCanError> {
+    let started = Instant::now();
+    loop {
+        let (frame, direction) = tokio::select! {
+            frame = link_a.recv(Duration::from_secs(1)) => (frame?, GatewayDirection::AToB),
+            frame = link_b.recv(Duration::from_secs(1)) => (frame?, GatewayDirection::BToA),
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+        let Some(mut frame) = frame else {
+            continue;
+        };
+
+        gateway.process(&mut frame, direction, started.elapsed());
+        let target = if direction == GatewayDirection::AToB { &mut link_b } else { &mut link_a };
+        if let Err(e) = target.send(frame).await {
+            tracing::warn!("Gateway: forwarding failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Torque: 8 Vector__XXX\n SG_ Request : 16|16@1+ (0.1,0) [0|1000] \"Nm\" Vector__XXX\n";
+
+    fn gateway(overrides: Vec<SignalOverride>, rewrite_counter: bool) -> Gateway {
+        let config = GatewayConfig {
+            channel_a: "can0".to_string(),
+            channel_b: "can1".to_string(),
+            overrides: vec![],
+            e2e: vec![E2eMessage {
+                message_id: 256,
+                profile: E2eProfile::Profile1 { data_id: 0x42 },
+                rewrite_counter,
+            }],
+        };
+        let database = DbcParser::parse(DBC).unwrap();
+        Gateway::new(&config, Arc::new(RwLock::new(overrides)), Some(database), None).unwrap()
+    }
+
+    #[test]
+    fn test_override_reprotects_frame() {
+        let mut gateway = gateway(
+            vec![SignalOverride {
+                message_id: 256,
+                signal: "Request".to_string(),
+                value: OverrideValue::Fixed { value: 50.0 },
+                direction: GatewayDirection::AToB,
+            }],
+            false,
+        );
+
+        let mut frame = CanFrame::new(256, &[0x00, 0x07, 0x10, 0x00, 0, 0, 0, 0]);
+        gateway.process(&mut frame, GatewayDirection::AToB, Duration::ZERO);
+        assert_eq!(frame.data[1..4], [0x07, 0xF4, 0x01]);
+        let mut expected = frame.data;
+        E2eProfile::Profile1 { data_id: 0x42 }.protect(&mut expected, None).unwrap();
+        assert_eq!(frame.data, expected);
+
+        // The rule only applies A to B; untouched frames keep their CRC
+        let mut frame = CanFrame::new(256, &[0x00, 0x07, 0x10, 0x00, 0, 0, 0, 0]);
+        gateway.process(&mut frame, GatewayDirection::BToA, Duration::ZERO);
+        assert_eq!(frame.data[..4], [0x00, 0x07, 0x10, 0x00]);
+    }
+
+    #[test]
+    fn test_sequence_and_counter() {
+        let mut gateway = gateway(
+            vec![SignalOverride {
+                message_id: 256,
+                signal: "Request".to_string(),
+                value: OverrideValue::Sequence {
+                    values: vec![1.0, 2.0],
+                    hold_ms: 100,
+                },
+                direction: GatewayDirection::Both,
+            }],
+            true,
+        );
+
+        let mut values = vec![];
+        let mut counters = vec![];
+        for elapsed_ms in [0, 150, 250] {
+            let mut frame = CanFrame::new(256, &[0x00, 0x0C, 0x00, 0x00, 0, 0, 0, 0]);
+            gateway.process(&mut frame, GatewayDirection::AToB, Duration::from_millis(elapsed_ms));
+            values.push(u16::from_le_bytes([frame.data[2], frame.data[3]]));
+            counters.push(frame.data[1] & 0x0F);
+        }
+        assert_eq!(values, vec![10, 20, 10]);
+        assert_eq!(counters, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,391 @@
+//! GVRET device emulation over TCP
+//!
+//! Implements the binary protocol of the GVRET/ESP32RET firmware so SavvyCAN
+//! can use this instance's channels as a remote interface. Every command
+//! starts with 0xF1 followed by a command byte; the 0xE7 bytes a client sends
+//! to switch into binary mode are skipped. Channel N of the server is GVRET
+//! bus N.
+
+use super::channel::{ChannelHandle, ChannelSnapshot};
+use super::message::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+
+/// Port SavvyCAN connects to for network GVRET devices
+pub const DEFAULT_GVRET_PORT: u16 = 23;
+
+const START: u8 = 0xF1;
+const BUILD_CAN_FRAME: u8 = 0x00;
+const TIME_SYNC: u8 = 0x01;
+const GET_DIG_INPUTS: u8 = 0x02;
+const GET_ANALOG_INPUTS: u8 = 0x03;
+const SET_DIG_OUTPUTS: u8 = 0x04;
+const SETUP_CANBUS: u8 = 0x05;
+const GET_CANBUS_PARAMS: u8 = 0x06;
+const GET_DEVICE_INFO: u8 = 0x07;
+const SET_SINGLEWIRE_MODE: u8 = 0x08;
+const KEEPALIVE: u8 = 0x09;
+const SET_SYSTYPE: u8 = 0x0A;
+const ECHO_CAN_FRAME: u8 = 0x0B;
+const GET_NUM_BUSES: u8 = 0x0C;
+const GET_EXT_BUSES: u8 = 0x0D;
+const SET_EXT_BUSES: u8 = 0x0E;
+
+/// Firmware build number reported to clients
+const BUILD_NUMBER: u16 = 618;
+const EXTENDED_FLAG: u32 = 1 << 31;
+
+fn default_port() -> u16 {
+    DEFAULT_GVRET_PORT
+}
+
+fn default_bind_address() -> String {
+    "0.0.0.0".to_string()
+}
+
+/// Settings of the GVRET server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GvretServerConfig {
+    /// Channels exposed as GVRET buses, in bus order
+    pub channels: Vec<String>,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_bind_address")]
+    pub bind_address: String,
+}
+
+/// Running server, kept in the application state
+pub struct GvretServerHandle {
+    pub port: u16,
+    pub cancel: watch::Sender<bool>,
+}
+
+/// Bus settings reported to the client
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GvretBus {
+    pub enabled: bool,
+    pub listen_only: bool,
+    pub bitrate: u32,
+}
+
+impl GvretBus {
+    fn of(channel: &ChannelSnapshot) -> Self {
+        Self {
+            enabled: channel.is_connected(),
+            listen_only: channel.config.listen_only,
+            bitrate: channel.config.bitrate,
+        }
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(self.enabled as u8 | (self.listen_only as u8) << 4);
+        out.extend_from_slice(&self.bitrate.to_le_bytes());
+    }
+}
+
+/// Command received from a client
+#[derive(Debug, Clone)]
+pub enum GvretCommand {
+    /// Transmit a frame on a bus; echoed frames are also sent back to the client
+    SendFrame { bus: u8, frame: CanFrame, echo: bool },
+    TimeSync,
+    DigitalInputs,
+    AnalogInputs,
+    BusParams,
+    DeviceInfo,
+    Keepalive,
+    NumBuses,
+    ExtBuses,
+    /// Settings commands that are accepted without effect or reply
+    Ignored(u8),
+}
+
+/// Incremental parser for the client byte stream
+#[derive(Default)]
+pub struct GvretDecoder {
+    buffer: Vec<u8>,
+}
+
+impl GvretDecoder {
+    /// Feed received bytes and return the complete commands
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<GvretCommand> {
+        self.buffer.extend_from_slice(bytes);
+        let mut commands = Vec::new();
+
+        loop {
+            // Skip binary mode requests and anything else between commands
+            match self.buffer.iter().position(|&b| b == START) {
+                Some(start) => {
+                    self.buffer.drain(..start);
+                }
+                None => {
+                    self.buffer.clear();
+                    break;
+                }
+            }
+            if self.buffer.len() < 2 {
+                break;
+            }
+
+            let command = self.buffer[1];
+            let length = match command {
+                BUILD_CAN_FRAME | ECHO_CAN_FRAME => {
+                    if self.buffer.len() < 8 {
+                        break;
+                    }
+                    // Header, data and a trailing checksum byte that clients leave at 0
+                    8 + (self.buffer[7] & 0x0F).min(8) as usize + 1
+                }
+                SET_DIG_OUTPUTS | SET_SINGLEWIRE_MODE | SET_SYSTYPE => 3,
+                SETUP_CANBUS => 10,
+                SET_EXT_BUSES => 14,
+                TIME_SYNC | GET_DIG_INPUTS | GET_ANALOG_INPUTS | GET_CANBUS_PARAMS | GET_DEVICE_INFO
+                | KEEPALIVE | GET_NUM_BUSES | GET_EXT_BUSES => 2,
+                _ => {
+                    tracing::debug!("Unknown GVRET command {:#04X}", command);
+                    self.buffer.drain(..1);
+                    continue;
+                }
+            };
+            if self.buffer.len() < length {
+                break;
+            }
+
+            let bytes: Vec<u8> = self.buffer.drain(..length).collect();
+            commands.push(match command {
+                BUILD_CAN_FRAME | ECHO_CAN_FRAME => {
+                    let raw_id = u32::from_le_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]);
+                    let data = &bytes[8..length - 1];
+                    let frame = if raw_id & EXTENDED_FLAG != 0 {
+                        CanFrame::new_extended(raw_id & 0x1FFF_FFFF, data)
+                    } else {
+                        CanFrame::new(raw_id & 0x7FF, data)
+                    };
+                    GvretCommand::SendFrame {
+                        bus: bytes[6],
+                        frame,
+                        echo: command == ECHO_CAN_FRAME,
+                    }
+                }
+                TIME_SYNC => GvretCommand::TimeSync,
+                GET_DIG_INPUTS => GvretCommand::DigitalInputs,
+                GET_ANALOG_INPUTS => GvretCommand::AnalogInputs,
+                GET_CANBUS_PARAMS => GvretCommand::BusParams,
+                GET_DEVICE_INFO => GvretCommand::DeviceInfo,
+                KEEPALIVE => GvretCommand::Keepalive,
+                GET_NUM_BUSES => GvretCommand::NumBuses,
+                GET_EXT_BUSES => GvretCommand::ExtBuses,
+                other => GvretCommand::Ignored(other),
+            });
+        }
+        commands
+    }
+}
+
+/// Encode a frame as sent from device to client
+pub fn encode_frame(frame: &CanFrame, bus: u8, timestamp_us: u32) -> Vec<u8> {
+    let len = frame.data.len().min(8);
+    let mut id = frame.id;
+    if frame.is_extended {
+        id |= EXTENDED_FLAG;
+    }
+
+    let mut out = Vec::with_capacity(12 + len);
+    out.extend_from_slice(&[START, BUILD_CAN_FRAME]);
+    out.extend_from_slice(&timestamp_us.to_le_bytes());
+    out.extend_from_slice(&id.to_le_bytes());
+    out.push(len as u8 | (bus << 4));
+    out.extend_from_slice(&frame.data[..len]);
+    out.push(0);
+    out
+}
+
+/// Reply to a command, if it expects one
+pub fn respond(command: &GvretCommand, buses: &[GvretBus], timestamp_us: u32) -> Option<Vec<u8>> {
+    let bus = |index: usize| buses.get(index).copied().unwrap_or_default();
+    let mut out = vec![START];
+    match command {
+        GvretCommand::SendFrame { bus, frame, echo: true } => return Some(encode_frame(frame, *bus, timestamp_us)),
+        GvretCommand::SendFrame { .. } | GvretCommand::Ignored(_) => return None,
+        GvretCommand::TimeSync => {
+            out.push(TIME_SYNC);
+            out.extend_from_slice(&timestamp_us.to_le_bytes());
+        }
+        GvretCommand::DigitalInputs => out.extend_from_slice(&[GET_DIG_INPUTS, 0, 0]),
+        GvretCommand::AnalogInputs => {
+            out.push(GET_ANALOG_INPUTS);
+            out.extend_from_slice(&[0; 15]);
+        }
+        GvretCommand::BusParams => {
+            out.push(GET_CANBUS_PARAMS);
+            bus(0).encode(&mut out);
+            bus(1).encode(&mut out);
+        }
+        GvretCommand::DeviceInfo => {
+            out.push(GET_DEVICE_INFO);
+            out.extend_from_slice(&BUILD_NUMBER.to_le_bytes());
+            // EEPROM version, file type, auto log, single wire
+            out.extend_from_slice(&[0x20, 0, 0, 0]);
+        }
+        GvretCommand::Keepalive => out.extend_from_slice(&[KEEPALIVE, 0xDE, 0xAD]),
+        GvretCommand::NumBuses => out.extend_from_slice(&[GET_NUM_BUSES, buses.len().min(15) as u8]),
+        GvretCommand::ExtBuses => {
+            out.push(GET_EXT_BUSES);
+            for index in 2..5 {
+                bus(index).encode(&mut out);
+            }
+        }
+    }
+    Some(out)
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    buses: Arc<Vec<ChannelHandle>>,
+    mut cancel: watch::Receiver<bool>,
+) {
+    let started = Instant::now();
+    let timestamp = || started.elapsed().as_micros() as u32;
+
+    // Frames received on the buses; our own transmissions are not reported,
+    // like on a real adapter
+    let (frame_tx, mut frames) = mpsc::unbounded_channel::<(u8, CanFrame)>();
+    let forwarders: Vec<_> = buses
+        .iter()
+        .enumerate()
+        .map(|(index, channel)| {
+            let mut rx = channel.subscribe("GVRET client");
+            let frame_tx = frame_tx.clone();
+            tokio::spawn(async move {
+                while let Some(frame) = rx.recv().await {
+                    if frame.direction.is_rx() && frame_tx.send((index as u8, frame)).is_err() {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(frame_tx);
+
+    let mut decoder = GvretDecoder::default();
+    let mut buffer = [0u8; 1024];
+    loop {
+        let out = tokio::select! {
+            read = stream.read(&mut buffer) => {
+                let n = match read {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) => {
+                        tracing::warn!("GVRET connection error: {}", e);
+                        break;
+                    }
+                };
+
+                let mut out = Vec::new();
+                for command in decoder.push(&buffer[..n]) {
+                    if let GvretCommand::SendFrame { bus, frame, .. } = &command {
+                        match buses.get(*bus as usize) {
+                            Some(channel) => {
+                                if let Err(e) = channel.send(frame.clone()).await {
+                                    tracing::warn!("GVRET send on bus {} failed: {}", bus, e);
+                                }
+                            }
+                            None => tracing::warn!("GVRET send on unknown bus {}", bus),
+                        }
+                    }
+                    let mut info = Vec::new();
+                    for channel in buses.iter() {
+                        if let Ok(snapshot) = channel.snapshot().await {
+                            info.push(GvretBus::of(&snapshot));
+                        }
+                    }
+                    if let Some(reply) = respond(&command, &info, timestamp()) {
+                        out.extend_from_slice(&reply);
+                    }
+                }
+                out
+            }
+            frame = frames.recv() => match frame {
+                Some((bus, frame)) => encode_frame(&frame, bus, timestamp()),
+                None => break,
+            },
+            _ = cancel.changed() => break,
+        };
+
+        if !out.is_empty() && stream.write_all(&out).await.is_err() {
+            break;
+        }
+    }
+
+    for task in forwarders {
+        task.abort();
+    }
+}
+
+/// Accept GVRET clients until cancelled; `buses` are the channels in bus order
+pub async fn serve(listener: TcpListener, buses: Vec<ChannelHandle>, mut cancel: watch::Receiver<bool>) {
+    let buses = Arc::new(buses);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    tracing::info!("GVRET client connected from {}", peer);
+                    let _ = stream.set_nodelay(true);
+                    tokio::spawn(handle_connection(stream, buses.clone(), cancel.clone()));
+                }
+                Err(e) => tracing::warn!("GVRET accept failed: {}", e),
+            },
+            _ = cancel.changed() => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_commands() {
+        let mut decoder = GvretDecoder::default();
+        // Binary mode switch, bus count query and the first half of a frame
+        let commands = decoder.push(&[0xE7, 0xE7, 0xF1, 0x0C, 0xF1, 0x00, 0x23, 0x01]);
+        assert!(matches!(commands[..], [GvretCommand::NumBuses]));
+
+        let commands = decoder.push(&[0x00, 0x80, 0x01, 0x02, 0xAA, 0xBB, 0x00, 0xF1, 0x09]);
+        assert_eq!(commands.len(), 2);
+        let GvretCommand::SendFrame { bus, frame, echo } = &commands[0] else {
+            panic!("expected a frame");
+        };
+        assert_eq!((*bus, *echo), (1, false));
+        assert_eq!(frame.id, 0x123);
+        assert!(frame.is_extended);
+        assert_eq!(frame.data, vec![0xAA, 0xBB]);
+        assert!(matches!(commands[1], GvretCommand::Keepalive));
+    }
+
+    #[test]
+    fn test_encode_replies() {
+        let frame = CanFrame::new(0x7E8, &[0x02, 0x41]);
+        assert_eq!(
+            encode_frame(&frame, 1, 0x0102_0304),
+            vec![0xF1, 0x00, 0x04, 0x03, 0x02, 0x01, 0xE8, 0x07, 0x00, 0x00, 0x12, 0x02, 0x41, 0x00]
+        );
+
+        let buses = [GvretBus {
+            enabled: true,
+            listen_only: true,
+            bitrate: 500_000,
+        }];
+        assert_eq!(
+            respond(&GvretCommand::BusParams, &buses, 0).unwrap(),
+            vec![0xF1, 0x06, 0x11, 0x20, 0xA1, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(respond(&GvretCommand::NumBuses, &buses, 0).unwrap(), vec![0xF1, 0x0C, 1]);
+        assert!(respond(&GvretCommand::Ignored(SETUP_CANBUS), &buses, 0).is_none());
+    }
+}
@@ -0,0 +1,287 @@
+use super::message::{CanFrame, FrameData};
+use super::signal_series::TimeRange;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Most time buckets of one heatmap; every ID row holds a count per bucket
+pub const MAX_HEATMAP_BUCKETS: usize = 4096;
+
+/// Statistics of one CAN ID on a channel
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdStats {
+    pub id: u32,
+    pub is_extended: bool,
+    /// Frames seen in both directions
+    pub count: u64,
+    /// Timestamp of the latest frame (seconds since connect)
+    pub last_seen: f64,
+    pub dlc: u8,
+    pub last_data: FrameData,
+    /// Shortest, mean and longest interval between frames in ms
+    pub cycle_min_ms: Option<f64>,
+    pub cycle_avg_ms: Option<f64>,
+    pub cycle_max_ms: Option<f64>,
+    /// Standard deviation of the interval in ms
+    pub jitter_ms: Option<f64>,
+    /// Frames whose payload differed from the previous one
+    pub data_changes: u64,
+    /// Payload changes per second since the ID was first seen
+    pub data_change_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    stats: IdStats,
+    first_seen: f64,
+    /// Running mean and sum of squared deviations of the interval (Welford)
+    interval_mean: f64,
+    interval_m2: f64,
+    intervals: u64,
+}
+
+/// Per-ID statistics of a channel, updated for every frame
+#[derive(Debug, Clone, Default)]
+pub struct IdStatsTracker {
+    entries: HashMap<(u32, bool), Entry>,
+}
+
+impl IdStatsTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account a frame; `frame.timestamp` must be set
+    pub fn record(&mut self, frame: &CanFrame) {
+        let now = frame.timestamp;
+        let entry = self
+            .entries
+            .entry((frame.id, frame.is_extended))
+            .or_insert_with(|| Entry {
+                stats: IdStats {
+                    id: frame.id,
+                    is_extended: frame.is_extended,
+                    last_seen: now,
+                    dlc: frame.dlc,
+                    last_data: frame.data,
+                    ..Default::default()
+                },
+                first_seen: now,
+                interval_mean: 0.0,
+                interval_m2: 0.0,
+                intervals: 0,
+            });
+
+        let stats = &mut entry.stats;
+        if stats.count > 0 {
+            let interval = (now - stats.last_seen).max(0.0) * 1000.0;
+            entry.intervals += 1;
+            let delta = interval - entry.interval_mean;
+            entry.interval_mean += delta / entry.intervals as f64;
+            entry.interval_m2 += delta * (interval - entry.interval_mean);
+
+            stats.cycle_min_ms = Some(stats.cycle_min_ms.map_or(interval, |min| min.min(interval)));
+            stats.cycle_max_ms = Some(stats.cycle_max_ms.map_or(interval, |max| max.max(interval)));
+            stats.cycle_avg_ms = Some(entry.interval_mean);
+            stats.jitter_ms = Some((entry.interval_m2 / entry.intervals as f64).sqrt());
+
+            if frame.data != stats.last_data {
+                stats.data_changes += 1;
+                stats.last_data = frame.data;
+            }
+            let span = now - entry.first_seen;
+            if span > 0.0 {
+                stats.data_change_rate = stats.data_changes as f64 / span;
+            }
+        }
+        stats.count += 1;
+        stats.last_seen = now;
+        stats.dlc = frame.dlc;
+    }
+
+    /// Statistics of the given ID, if seen
+    pub fn get(&self, id: u32, is_extended: bool) -> Option<&IdStats> {
+        self.entries.get(&(id, is_extended)).map(|entry| &entry.stats)
+    }
+
+    /// Statistics of all IDs seen, ordered by ID
+    pub fn snapshot(&self) -> Vec<IdStats> {
+        let mut stats: Vec<IdStats> = self.entries.values().map(|entry| entry.stats.clone()).collect();
+        stats.sort_by_key(|s| (s.id, s.is_extended));
+        stats
+    }
+
+    /// Timestamp of the earliest frame recorded
+    pub fn first_seen(&self) -> Option<f64> {
+        self.entries.values().map(|entry| entry.first_seen).reduce(f64::min)
+    }
+
+    /// Timestamp of the latest frame recorded
+    pub fn last_seen(&self) -> Option<f64> {
+        self.entries.values().map(|entry| entry.stats.last_seen).reduce(f64::max)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn reset(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Frame counts of one ID per time bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapRow {
+    pub id: u32,
+    pub is_extended: bool,
+    pub counts: Vec<u32>,
+    pub total: u64,
+}
+
+/// ID-vs-time frame counts; bucket `i` starts at `start + i * bucket_width`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdHeatmap {
+    pub start: f64,
+    /// Bucket width in seconds
+    pub bucket_width: f64,
+    pub bucket_count: usize,
+    /// One row per ID, ordered by ID
+    pub rows: Vec<HeatmapRow>,
+}
+
+/// Count frames per ID and time bucket
+///
+/// Only frames of `channel_id` are counted when given. The buckets span
+/// `range`, or the frames themselves where the range is open.
+pub fn id_heatmap<'a>(
+    frames: impl IntoIterator<Item = &'a CanFrame>,
+    channel_id: Option<&str>,
+    bucket_width: f64,
+    range: TimeRange,
+) -> Result<IdHeatmap, BootCanError> {
+    if !bucket_width.is_finite() || bucket_width <= 0.0 {
+        return Err(BootCanError::InvalidInput("Bucket width must be a positive number".to_string()));
+    }
+    let frames: Vec<&CanFrame> = frames
+        .into_iter()
+        .filter(|f| channel_id.is_none_or(|c| f.channel == c) && range.contains(f.timestamp))
+        .collect();
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).reduce(f64::min),
+        frames.iter().map(|f| f.timestamp).reduce(f64::max),
+    ) else {
+        return Ok(IdHeatmap {
+            bucket_width,
+            ..Default::default()
+        });
+    };
+    let start = range.start.unwrap_or(first);
+    let end = range.end.unwrap_or(last);
+    let span = ((end - start) / bucket_width).floor();
+    // Also catches NaN and infinite range bounds
+    if !(0.0..MAX_HEATMAP_BUCKETS as f64).contains(&span) {
+        return Err(BootCanError::InvalidInput(format!(
+            "A bucket width of {} s gives more than {} buckets", bucket_width, MAX_HEATMAP_BUCKETS
+        )));
+    }
+    let bucket_count = span as usize + 1;
+
+    let mut rows: BTreeMap<(u32, bool), HeatmapRow> = BTreeMap::new();
+    for frame in frames {
+        let bucket = (((frame.timestamp - start) / bucket_width) as usize).min(bucket_count - 1);
+        let row = rows.entry((frame.id, frame.is_extended)).or_insert_with(|| HeatmapRow {
+            id: frame.id,
+            is_extended: frame.is_extended,
+            counts: vec![0; bucket_count],
+            total: 0,
+        });
+        row.counts[bucket] += 1;
+        row.total += 1;
+    }
+    Ok(IdHeatmap {
+        start,
+        bucket_width,
+        bucket_count,
+        rows: rows.into_values().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: u32, data: &[u8], timestamp: f64) -> CanFrame {
+        let mut frame = CanFrame::new(id, data);
+        frame.timestamp = timestamp;
+        frame
+    }
+
+    #[test]
+    fn test_cycle_times() {
+        let mut tracker = IdStatsTracker::new();
+        for (i, t) in [0.0, 0.010, 0.020, 0.032, 0.040].iter().enumerate() {
+            tracker.record(&frame(0x100, &[i as u8 / 2], *t));
+        }
+        tracker.record(&frame(0x200, &[0], 0.5));
+
+        let stats = tracker.get(0x100, false).unwrap();
+        assert_eq!(stats.count, 5);
+        assert!((stats.last_seen - 0.040).abs() < 1e-9);
+        assert!((stats.cycle_min_ms.unwrap() - 8.0).abs() < 1e-6);
+        assert!((stats.cycle_max_ms.unwrap() - 12.0).abs() < 1e-6);
+        assert!((stats.cycle_avg_ms.unwrap() - 10.0).abs() < 1e-6);
+        // Intervals 10, 10, 12, 8 ms around a 10 ms mean
+        assert!((stats.jitter_ms.unwrap() - 2f64.sqrt()).abs() < 1e-6);
+
+        let ids: Vec<u32> = tracker.snapshot().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![0x100, 0x200]);
+        assert_eq!((tracker.first_seen(), tracker.last_seen()), (Some(0.0), Some(0.5)));
+        assert!(tracker.get(0x200, false).unwrap().cycle_avg_ms.is_none());
+    }
+
+    #[test]
+    fn test_data_changes() {
+        let mut tracker = IdStatsTracker::new();
+        // Payload changes at 1 s and 2 s, unchanged at 3 s
+        for (t, value) in [(0.0, 1u8), (1.0, 2), (2.0, 3), (3.0, 3)] {
+            tracker.record(&frame(0x100, &[value], t));
+        }
+        let stats = tracker.get(0x100, false).unwrap();
+        assert_eq!(stats.data_changes, 2);
+        assert!((stats.data_change_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.last_data, vec![3]);
+
+        tracker.reset();
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_id_heatmap() {
+        let mut frames: Vec<CanFrame> = [0.0, 0.1, 0.2, 2.5].iter().map(|t| frame(0x100, &[0], *t)).collect();
+        frames.push(frame(0x050, &[0], 1.1));
+        frames.iter_mut().for_each(|f| f.channel = "can0".into());
+        let mut other = frame(0x100, &[0], 1.0);
+        other.channel = "can1".into();
+        frames.push(other);
+
+        let heatmap = id_heatmap(&frames, Some("can0"), 1.0, TimeRange::default()).unwrap();
+        assert_eq!((heatmap.start, heatmap.bucket_count), (0.0, 3));
+        let rows: Vec<(u32, Vec<u32>)> = heatmap.rows.iter().map(|r| (r.id, r.counts.clone())).collect();
+        assert_eq!(rows, vec![(0x050, vec![0, 1, 0]), (0x100, vec![3, 0, 1])]);
+
+        assert_eq!(id_heatmap(&frames, None, 1.0, TimeRange::default()).unwrap().rows[1].total, 5);
+        assert!(id_heatmap(&frames, None, 0.0, TimeRange::default()).is_err());
+        assert!(id_heatmap(&frames, None, f64::NAN, TimeRange::default()).is_err());
+        assert!(id_heatmap(&frames, None, f64::MIN_POSITIVE, TimeRange::default()).is_err());
+        let endless = TimeRange { start: None, end: Some(f64::INFINITY) };
+        assert!(id_heatmap(&frames, None, 1.0, endless).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! InfluxDB line protocol export of decoded signals
+//!
+//! Received frames are decoded with the channel's DBC and the latest value of
+//! every signal is written once per interval, one line per message:
+//! `can,channel=can0,message=Engine RPM=850,CoolantTemp=90 <ns timestamp>`.
+//! Lines go to an InfluxDB v2 write endpoint or are appended to a file.
+
+use super::dbc::{DbcDatabase, DecodedSignal};
+use super::decode_pool::DecodedFrame;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, watch};
+
+fn default_measurement() -> String {
+    "can".to_string()
+}
+
+fn default_interval_ms() -> u64 {
+    1000
+}
+
+/// Destination of the exported lines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InfluxSink {
+    /// InfluxDB v2 write API, e.g. `http://localhost:8086`
+    Http {
+        url: String,
+        org: String,
+        bucket: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// Line protocol file, appended to
+    File { path: PathBuf },
+}
+
+/// Settings of an export job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfluxExportConfig {
+    pub sink: InfluxSink,
+    /// Channels to export; all channels with a DBC when empty
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default = "default_measurement")]
+    pub measurement: String,
+    /// Write interval; only the latest value per signal in an interval is kept
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Signal names to export; all decoded signals when empty
+    #[serde(default)]
+    pub signals: Vec<String>,
+}
+
+/// Counters of a running export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfluxExportStatus {
+    pub lines_written: u64,
+    pub write_errors: u64,
+    pub last_error: Option<String>,
+}
+
+/// Running export, kept in the application state
+pub struct InfluxExportHandle {
+    pub cancel: watch::Sender<bool>,
+    pub status: Arc<RwLock<InfluxExportStatus>>,
+}
+
+/// Escape a measurement name, tag key/value or field key
+///
+/// Line breaks cannot be escaped in line protocol, where they end the point,
+/// so they are dropped.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars().filter(|c| !matches!(c, '\n' | '\r')) {
+        if matches!(c, ',' | '=' | ' ') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Format one line protocol point with float fields
+///
+/// Non-finite values are left out; `None` if that leaves no field, as a point
+/// without fields is rejected by the server.
+pub fn format_line(
+    measurement: &str,
+    tags: &[(&str, &str)],
+    fields: &[(&str, f64)],
+    timestamp_ns: i64,
+) -> Option<String> {
+    let fields: Vec<String> = fields
+        .iter()
+        .filter(|(_, value)| value.is_finite())
+        .map(|(key, value)| format!("{}={}", escape(key), value))
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+    let mut line = escape(measurement);
+    for (key, value) in tags {
+        line.push_str(&format!(",{}={}", escape(key), escape(value)));
+    }
+    line.push(' ');
+    line.push_str(&fields.join(","));
+    line.push_str(&format!(" {}", timestamp_ns));
+    Some(line)
+}
+
+/// Latest decoded values per channel and message since the last flush
+pub struct SignalSampler {
+    measurement: String,
+    signals: HashSet<String>,
+    databases: HashMap<String, DbcDatabase>,
+    latest: BTreeMap<(String, String), BTreeMap<String, f64>>,
+}
+
+impl SignalSampler {
+    pub fn new(config: &InfluxExportConfig, databases: HashMap<String, DbcDatabase>) -> Self {
+        Self {
+            measurement: config.measurement.clone(),
+            signals: config.signals.iter().cloned().collect(),
+            databases,
+            latest: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, frame: &CanFrame) {
+        let Some(database) = self.databases.get(frame.channel.as_str()) else {
+            return;
+        };
+        let signals = database.decode_message(frame.id, &frame.data);
+        self.record_decoded(frame, signals);
+    }
+
+    /// Keep the signals of a frame decoded elsewhere
+    pub fn record_decoded(&mut self, frame: &CanFrame, signals: Vec<DecodedSignal>) {
+        let Some(message) = self.databases.get(frame.channel.as_str()).and_then(|db| db.get_message(frame.id)) else {
+            return;
+        };
+
+        let values = self
+            .latest
+            .entry((frame.channel.to_string(), message.name.clone()))
+            .or_default();
+        for signal in signals {
+            let wanted = self.signals.is_empty() || self.signals.contains(&signal.name);
+            if wanted && signal.physical_value.is_finite() {
+                values.insert(signal.name, signal.physical_value);
+            }
+        }
+    }
+
+    /// Lines for all values recorded since the last call
+    pub fn take_lines(&mut self, timestamp_ns: i64) -> Vec<String> {
+        std::mem::take(&mut self.latest)
+            .into_iter()
+            .filter_map(|((channel, message), values)| {
+                let fields: Vec<(&str, f64)> = values.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+                format_line(
+                    &self.measurement,
+                    &[("channel", &channel), ("message", &message)],
+                    &fields,
+                    timestamp_ns,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Blocking writer for one sink
+pub enum InfluxWriter {
+    Http { agent: ureq::Agent, url: String, token: Option<String> },
+    File(BufWriter<File>),
+}
+
+impl InfluxWriter {
+    pub fn open(sink: &InfluxSink) -> Result<Self, BootCanError> {
+        match sink {
+            InfluxSink::Http { url, org, bucket, token } => {
+                let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(10)).build();
+                let url = format!(
+                    "{}/api/v2/write?org={}&bucket={}&precision=ns",
+                    url.trim_end_matches('/'),
+                    urlencode(org),
+                    urlencode(bucket)
+                );
+                Ok(Self::Http {
+                    agent,
+                    url,
+                    token: token.clone(),
+                })
+            }
+            InfluxSink::File { path } => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| BootCanError::Io(format!("Failed to open {}: {}", path.display(), e)))?;
+                Ok(Self::File(BufWriter::new(file)))
+            }
+        }
+    }
+
+    pub fn write(&mut self, lines: &[String]) -> Result<(), BootCanError> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let body = lines.join("\n") + "\n";
+        match self {
+            Self::Http { agent, url, token } => {
+                let mut request = agent.post(url).set("Content-Type", "text/plain; charset=utf-8");
+                if let Some(token) = token {
+                    request = request.set("Authorization", &format!("Token {}", token));
+                }
+                request
+                    .send_string(&body)
+                    .map(|_| ())
+                    .map_err(|e| BootCanError::Io(format!("InfluxDB write failed: {}", e)))
+            }
+            Self::File(writer) => writer
+                .write_all(body.as_bytes())
+                .and_then(|_| writer.flush())
+                .map_err(|e| BootCanError::Io(format!("Line protocol write failed: {}", e))),
+        }
+    }
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn now_ns() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+/// Sample decoded frames from `frames` and write them every interval until cancelled
+pub async fn run_influx_export(
+    mut frames: mpsc::Receiver<Arc<DecodedFrame>>,
+    mut sampler: SignalSampler,
+    mut writer: InfluxWriter,
+    interval: Duration,
+    status: Arc<RwLock<InfluxExportStatus>>,
+    mut cancel: watch::Receiver<bool>,
+) {
+    let mut ticker = tokio::time::interval(interval.max(Duration::from_millis(10)));
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(decoded) => {
+                    sampler.record_decoded(&decoded.frame, decoded.signals.clone());
+                    continue;
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {}
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    break;
+                }
+                continue;
+            }
+        }
+
+        let lines = sampler.take_lines(now_ns());
+        if lines.is_empty() {
+            continue;
+        }
+        // HTTP and file writes block, keep them off the async workers
+        let Ok((returned, result)) = tokio::task::spawn_blocking(move || {
+            let result = writer.write(&lines);
+            (writer, result.map(|_| lines.len()))
+        })
+        .await
+        else {
+            tracing::error!("InfluxDB writer task failed");
+            return;
+        };
+        writer = returned;
+
+        let mut status = status.write();
+        match result {
+            Ok(count) => status.lines_written += count as u64,
+            Err(e) => {
+                tracing::warn!("{}", e);
+                status.write_errors += 1;
+                status.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    // Write what is left so short captures are not lost
+    let lines = sampler.take_lines(now_ns());
+    if let Err(e) = tokio::task::spawn_blocking(move || writer.write(&lines)).await.unwrap_or(Ok(())) {
+        tracing::warn!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Engine: 8 Vector__XXX\n SG_ RPM : 0|16@1+ (1,0) [0|8000] \"rpm\" Vector__XXX\n SG_ CoolantTemp : 16|8@1+ (1,-40) [-40|215] \"degC\" Vector__XXX\n";
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            format_line("can", &[("channel", "can 0"), ("message", "A,B")], &[("x=y", 1.5), ("nan", f64::NAN)], 7)
+                .unwrap(),
+            "can,channel=can\\ 0,message=A\\,B x\\=y=1.5 7"
+        );
+        assert_eq!(
+            format_line("can", &[("message", "A\nB\r")], &[("x", 1.0)], 7).unwrap(),
+            "can,message=AB x=1 7"
+        );
+        assert_eq!(format_line("can", &[], &[("nan", f64::NAN), ("inf", f64::INFINITY)], 7), None);
+        assert_eq!(urlencode("my org"), "my%20org");
+    }
+
+    #[test]
+    fn test_sampler_keeps_latest_values() {
+        let config = InfluxExportConfig {
+            sink: InfluxSink::File { path: PathBuf::from("unused.lp") },
+            channels: vec![],
+            measurement: default_measurement(),
+            interval_ms: default_interval_ms(),
+            signals: vec!["RPM".to_string()],
+        };
+        let databases = HashMap::from([("can0".to_string(), DbcParser::parse(DBC).unwrap())]);
+        let mut sampler = SignalSampler::new(&config, databases);
+
+        for rpm in [800u16, 850] {
+            let mut frame = CanFrame::new(256, &[0; 8]);
+            frame.data[..2].copy_from_slice(&rpm.to_le_bytes());
+            frame.channel = "can0".into();
+            sampler.record(&frame);
+        }
+        let mut other = CanFrame::new(256, &[0; 8]);
+        other.channel = "can1".into();
+        sampler.record(&other);
+
+        assert_eq!(sampler.take_lines(42), vec!["can,channel=can0,message=Engine RPM=850 42"]);
+        assert!(sampler.take_lines(43).is_empty());
+    }
+}
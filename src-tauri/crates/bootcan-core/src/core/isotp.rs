@@ -0,0 +1,364 @@
+//! ISO-TP (ISO 15765-2) transport over classic CAN
+//!
+//! Segments and reassembles messages of up to 4 GiB using single, first,
+//! consecutive and flow control frames on a physical TX/RX identifier pair.
+
+use super::frame_link::FrameLink;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+
+const PCI_SINGLE: u8 = 0x0;
+const PCI_FIRST: u8 = 0x1;
+const PCI_CONSECUTIVE: u8 = 0x2;
+const PCI_FLOW_CONTROL: u8 = 0x3;
+
+const FC_CONTINUE: u8 = 0x0;
+const FC_WAIT: u8 = 0x1;
+const FC_OVERFLOW: u8 = 0x2;
+
+/// Maximum number of consecutive FC.WAIT frames accepted before giving up
+const MAX_WAIT_FRAMES: u32 = 10;
+
+/// Default N_Bs / N_Cr timeout
+pub const DEFAULT_ISOTP_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Default limit on the length of received messages
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+fn default_max_message_len() -> usize {
+    DEFAULT_MAX_MESSAGE_LEN
+}
+
+/// Addressing and flow control parameters of one ISO-TP connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsoTpConfig {
+    /// Identifier used for frames we send
+    pub tx_id: u32,
+    /// Identifier of frames sent by the peer
+    pub rx_id: u32,
+    #[serde(default)]
+    pub is_extended: bool,
+    /// Pad frames to 8 bytes with this value
+    #[serde(default)]
+    pub padding: Option<u8>,
+    /// Block size announced in our flow control frames (0 = no limit)
+    #[serde(default)]
+    pub block_size: u8,
+    /// Separation time announced in our flow control frames (raw STmin byte)
+    #[serde(default)]
+    pub st_min: u8,
+    /// Longest message we accept; longer first frames are answered with FC.OVFLW
+    #[serde(default = "default_max_message_len")]
+    pub max_message_len: usize,
+}
+
+/// ISO-TP connection over a frame link
+pub struct IsoTpLink<'a> {
+    link: &'a mut dyn FrameLink,
+    config: IsoTpConfig,
+    timeout: Duration,
+}
+
+impl<'a> IsoTpLink<'a> {
+    pub fn new(link: &'a mut dyn FrameLink, config: IsoTpConfig, timeout: Duration) -> Self {
+        Self { link, config, timeout }
+    }
+
+    /// Send a complete message, segmenting it if needed
+    pub async fn send(&mut self, data: &[u8]) -> Result<(), BootCanError> {
+        if data.len() <= 7 {
+            let mut frame = vec![(PCI_SINGLE << 4) | data.len() as u8];
+            frame.extend_from_slice(data);
+            return self.send_frame(frame).await;
+        }
+
+        let (mut frame, mut offset) = if data.len() <= 0xFFF {
+            (vec![(PCI_FIRST << 4) | (data.len() >> 8) as u8, data.len() as u8], 6)
+        } else {
+            let mut frame = vec![PCI_FIRST << 4, 0];
+            frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            (frame, 2)
+        };
+        frame.extend_from_slice(&data[..offset]);
+        self.send_frame(frame).await?;
+
+        let mut sequence = 1u8;
+        loop {
+            let (block_size, st_min) = self.wait_flow_control().await?;
+            let mut sent_in_block = 0u8;
+
+            while offset < data.len() {
+                let end = (offset + 7).min(data.len());
+                let mut frame = vec![(PCI_CONSECUTIVE << 4) | sequence];
+                frame.extend_from_slice(&data[offset..end]);
+                self.send_frame(frame).await?;
+
+                offset = end;
+                sequence = (sequence + 1) & 0x0F;
+                sent_in_block += 1;
+
+                if offset >= data.len() {
+                    return Ok(());
+                }
+                if block_size != 0 && sent_in_block == block_size {
+                    break;
+                }
+                if !st_min.is_zero() {
+                    tokio::time::sleep(st_min).await;
+                }
+            }
+        }
+    }
+
+    /// Receive a complete message (None if nothing arrives within the timeout)
+    pub async fn recv(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, BootCanError> {
+        let deadline = Instant::now() + timeout;
+        let first = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.link.recv_id(self.config.rx_id, remaining).await? {
+                // Stray flow control or consecutive frames are not the start of a message
+                Some(frame) if matches!(pci_type(&frame), Some(PCI_SINGLE | PCI_FIRST)) => break frame,
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        };
+
+        let data = &first.data;
+        if pci_type(&first) == Some(PCI_SINGLE) {
+            let len = (data[0] & 0x0F) as usize;
+            if len == 0 || len + 1 > data.len() {
+                return Err(BootCanError::Protocol(format!("Invalid ISO-TP single frame length {}", len)));
+            }
+            return Ok(Some(data[1..1 + len].to_vec()));
+        }
+
+        if data.len() < 8 {
+            return Err(BootCanError::Protocol("ISO-TP first frame shorter than 8 bytes".to_string()));
+        }
+        let mut length = (((data[0] & 0x0F) as usize) << 8) | data[1] as usize;
+        let mut start = 2;
+        if length == 0 {
+            length = u32::from_be_bytes([data[2], data[3], data[4], data[5]]) as usize;
+            start = 6;
+        }
+
+        if length > self.config.max_message_len {
+            self.send_flow_control_status(FC_OVERFLOW).await?;
+            return Err(BootCanError::Protocol(format!(
+                "ISO-TP message of {} bytes exceeds the limit of {}",
+                length, self.config.max_message_len
+            )));
+        }
+
+        // The announced length is the peer's claim; grow as data actually arrives
+        let mut message = data[start..].to_vec();
+        if message.len() >= length {
+            message.truncate(length);
+            return Ok(Some(message));
+        }
+
+        let mut sequence = 1u8;
+        let mut received_in_block = 0u8;
+        self.send_flow_control().await?;
+
+        while message.len() < length {
+            let frame = self
+                .link
+                .recv_id(self.config.rx_id, self.timeout)
+                .await?
+                .ok_or_else(|| BootCanError::Timeout("ISO-TP timeout waiting for consecutive frame".to_string()))?;
+            if pci_type(&frame) != Some(PCI_CONSECUTIVE) {
+                return Err(BootCanError::Protocol("ISO-TP message interrupted by unexpected frame".to_string()));
+            }
+            if frame.data[0] & 0x0F != sequence {
+                return Err(BootCanError::Protocol(format!(
+                    "ISO-TP sequence error: expected {}, got {}",
+                    sequence,
+                    frame.data[0] & 0x0F
+                )));
+            }
+
+            let take = (length - message.len()).min(frame.data.len() - 1);
+            message.extend_from_slice(&frame.data[1..1 + take]);
+            sequence = (sequence + 1) & 0x0F;
+
+            received_in_block += 1;
+            if self.config.block_size != 0
+                && received_in_block == self.config.block_size
+                && message.len() < length
+            {
+                received_in_block = 0;
+                self.send_flow_control().await?;
+            }
+        }
+
+        Ok(Some(message))
+    }
+
+    async fn wait_flow_control(&mut self) -> Result<(u8, Duration), BootCanError> {
+        let mut waits = 0;
+        loop {
+            let frame = self
+                .link
+                .recv_id(self.config.rx_id, self.timeout)
+                .await?
+                .ok_or_else(|| BootCanError::Timeout("ISO-TP timeout waiting for flow control".to_string()))?;
+            if pci_type(&frame) != Some(PCI_FLOW_CONTROL) || frame.data.len() < 3 {
+                continue;
+            }
+
+            match frame.data[0] & 0x0F {
+                FC_CONTINUE => return Ok((frame.data[1], st_min_duration(frame.data[2]))),
+                FC_WAIT => {
+                    waits += 1;
+                    if waits > MAX_WAIT_FRAMES {
+                        return Err(BootCanError::Busy("ISO-TP receiver kept sending FC.WAIT".to_string()));
+                    }
+                }
+                FC_OVERFLOW => {
+                    return Err(BootCanError::Protocol("ISO-TP receiver reported buffer overflow".to_string()))
+                }
+                status => return Err(BootCanError::Protocol(format!("Invalid ISO-TP flow status {}", status))),
+            }
+        }
+    }
+
+    async fn send_flow_control(&mut self) -> Result<(), BootCanError> {
+        self.send_flow_control_status(FC_CONTINUE).await
+    }
+
+    async fn send_flow_control_status(&mut self, status: u8) -> Result<(), BootCanError> {
+        let frame = vec![
+            (PCI_FLOW_CONTROL << 4) | status,
+            self.config.block_size,
+            self.config.st_min,
+        ];
+        self.send_frame(frame).await
+    }
+
+    async fn send_frame(&mut self, mut data: Vec<u8>) -> Result<(), BootCanError> {
+        if let Some(padding) = self.config.padding {
+            data.resize(8, padding);
+        }
+        let frame = if self.config.is_extended {
+            CanFrame::new_extended(self.config.tx_id, &data)
+        } else {
+            CanFrame::new(self.config.tx_id, &data)
+        };
+        self.link.send(frame).await
+    }
+}
+
+fn pci_type(frame: &CanFrame) -> Option<u8> {
+    frame.data.first().map(|b| b >> 4)
+}
+
+/// Convert a raw STmin byte to a duration (reserved values mean 127 ms)
+fn st_min_duration(st_min: u8) -> Duration {
+    match st_min {
+        0x00..=0x7F => Duration::from_millis(st_min as u64),
+        0xF1..=0xF9 => Duration::from_micros((st_min - 0xF0) as u64 * 100),
+        _ => Duration::from_millis(0x7F),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::VecDeque;
+
+    /// Link that records sent frames and replays queued frames
+    struct QueueLink {
+        sent: Vec<CanFrame>,
+        incoming: VecDeque<CanFrame>,
+    }
+
+    #[async_trait]
+    impl FrameLink for QueueLink {
+        async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError> {
+            self.sent.push(frame);
+            Ok(())
+        }
+
+        async fn recv(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+            Ok(self.incoming.pop_front())
+        }
+    }
+
+    fn config() -> IsoTpConfig {
+        IsoTpConfig {
+            tx_id: 0x7E0,
+            rx_id: 0x7E8,
+            is_extended: false,
+            padding: Some(0xCC),
+            block_size: 0,
+            st_min: 0,
+            max_message_len: 64,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_multi_frame() {
+        let mut link = QueueLink {
+            sent: vec![],
+            incoming: vec![CanFrame::new(0x7E8, &[0x30, 0, 0])].into(),
+        };
+        let data: Vec<u8> = (0..20).collect();
+        IsoTpLink::new(&mut link, config(), DEFAULT_ISOTP_TIMEOUT)
+            .send(&data)
+            .await
+            .unwrap();
+
+        assert_eq!(link.sent.len(), 3);
+        assert_eq!(link.sent[0].data, vec![0x10, 20, 0, 1, 2, 3, 4, 5]);
+        assert_eq!(link.sent[1].data, vec![0x21, 6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(link.sent[2].data, vec![0x22, 13, 14, 15, 16, 17, 18, 19]);
+    }
+
+    #[tokio::test]
+    async fn test_recv_multi_frame() {
+        let mut link = QueueLink {
+            sent: vec![],
+            incoming: vec![
+                CanFrame::new(0x7E8, &[0x10, 10, b'b', b'o', b'o', b't', b'C', b'A']),
+                CanFrame::new(0x7E8, &[0x21, b'N', b' ', b'E', b'C', 0xCC, 0xCC, 0xCC]),
+            ]
+            .into(),
+        };
+        let message = IsoTpLink::new(&mut link, config(), DEFAULT_ISOTP_TIMEOUT)
+            .recv(DEFAULT_ISOTP_TIMEOUT)
+            .await
+            .unwrap();
+
+        assert_eq!(message, Some(b"bootCAN EC".to_vec()));
+        assert_eq!(link.sent[0].data, vec![0x30, 0, 0, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC]);
+    }
+
+    #[tokio::test]
+    async fn test_recv_rejects_oversized_first_frame() {
+        let mut link = QueueLink {
+            sent: vec![],
+            // Escape first frame announcing 4 GiB
+            incoming: vec![CanFrame::new(0x7E8, &[0x10, 0, 0xFF, 0xFF, 0xFF, 0xFF, 1, 2])].into(),
+        };
+        let result = IsoTpLink::new(&mut link, config(), DEFAULT_ISOTP_TIMEOUT)
+            .recv(DEFAULT_ISOTP_TIMEOUT)
+            .await;
+
+        assert!(matches!(result, Err(BootCanError::Protocol(_))));
+        assert_eq!(link.sent[0].data[0], (PCI_FLOW_CONTROL << 4) | FC_OVERFLOW);
+
+        // A first frame carrying more than it announces is cut to the announced length
+        link.incoming.push_back(CanFrame::new(0x7E8, &[0x10, 3, 1, 2, 3, 4, 5, 6]));
+        let message = IsoTpLink::new(&mut link, config(), DEFAULT_ISOTP_TIMEOUT)
+            .recv(DEFAULT_ISOTP_TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(message, Some(vec![1, 2, 3]));
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+/// Diagnostic message type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DmType {
+    /// DM1 - active DTCs
+    Dm1,
+    /// DM2 - previously active DTCs
+    Dm2,
+}
+
+/// State of a single indicator lamp (2-bit field)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LampState {
+    Off,
+    On,
+    Error,
+    NotAvailable,
+}
+
+impl LampState {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x03 {
+            0 => Self::Off,
+            1 => Self::On,
+            2 => Self::Error,
+            _ => Self::NotAvailable,
+        }
+    }
+}
+
+/// Flash pattern of a single indicator lamp (2-bit field)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FlashState {
+    SlowFlash,
+    FastFlash,
+    Reserved,
+    NoFlash,
+}
+
+impl FlashState {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x03 {
+            0 => Self::SlowFlash,
+            1 => Self::FastFlash,
+            2 => Self::Reserved,
+            _ => Self::NoFlash,
+        }
+    }
+}
+
+/// State and flash pattern of one lamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lamp {
+    pub state: LampState,
+    pub flash: FlashState,
+}
+
+/// Lamp status reported in bytes 1-2 of DM1/DM2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LampStatus {
+    pub malfunction_indicator: Lamp,
+    pub red_stop: Lamp,
+    pub amber_warning: Lamp,
+    pub protect: Lamp,
+}
+
+impl LampStatus {
+    fn from_bytes(status: u8, flash: u8) -> Self {
+        let lamp = |shift: u8| Lamp {
+            state: LampState::from_bits(status >> shift),
+            flash: FlashState::from_bits(flash >> shift),
+        };
+        Self {
+            malfunction_indicator: lamp(6),
+            red_stop: lamp(4),
+            amber_warning: lamp(2),
+            protect: lamp(0),
+        }
+    }
+}
+
+/// Diagnostic trouble code (SPN conversion method version 4)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dtc {
+    /// Suspect parameter number (19 bits)
+    pub spn: u32,
+    /// Failure mode identifier (5 bits)
+    pub fmi: u8,
+    /// Human-readable FMI description
+    pub fmi_description: String,
+    /// Occurrence count (7 bits, 127 = not available)
+    pub occurrence_count: u8,
+    /// SPN conversion method bit
+    pub conversion_method: bool,
+}
+
+impl Dtc {
+    /// Decode a 4-byte DTC field
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let spn = bytes[0] as u32 | (bytes[1] as u32) << 8 | ((bytes[2] as u32) >> 5) << 16;
+        let fmi = bytes[2] & 0x1F;
+        Self {
+            spn,
+            fmi,
+            fmi_description: fmi_description(fmi).to_string(),
+            occurrence_count: bytes[3] & 0x7F,
+            conversion_method: bytes[3] & 0x80 != 0,
+        }
+    }
+}
+
+/// Decoded DM1/DM2 message from one source address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticMessage {
+    pub dm_type: DmType,
+    pub channel: String,
+    pub source_address: u8,
+    pub timestamp: f64,
+    pub lamp_status: LampStatus,
+    pub dtcs: Vec<Dtc>,
+}
+
+impl DiagnosticMessage {
+    /// Parse the payload of a DM1/DM2 message (single frame or reassembled)
+    pub fn parse(dm_type: DmType, data: &[u8]) -> Option<Self> {
+        if data.len() < 2 {
+            return None;
+        }
+
+        let dtcs = data[2..]
+            .chunks_exact(4)
+            // An all-zero DTC means "no DTCs", all-ones is padding
+            .filter(|bytes| bytes.iter().any(|b| *b != 0x00) && bytes.iter().any(|b| *b != 0xFF))
+            .map(Dtc::from_bytes)
+            .collect();
+
+        Some(Self {
+            dm_type,
+            channel: String::new(),
+            source_address: 0,
+            timestamp: 0.0,
+            lamp_status: LampStatus::from_bytes(data[0], data[1]),
+            dtcs,
+        })
+    }
+}
+
+/// Standard description of a failure mode identifier (J1939-73)
+pub fn fmi_description(fmi: u8) -> &'static str {
+    match fmi {
+        0 => "Data valid but above normal operational range - most severe level",
+        1 => "Data valid but below normal operational range - most severe level",
+        2 => "Data erratic, intermittent or incorrect",
+        3 => "Voltage above normal, or shorted to high source",
+        4 => "Voltage below normal, or shorted to low source",
+        5 => "Current below normal or open circuit",
+        6 => "Current above normal or grounded circuit",
+        7 => "Mechanical system not responding or out of adjustment",
+        8 => "Abnormal frequency or pulse width or period",
+        9 => "Abnormal update rate",
+        10 => "Abnormal rate of change",
+        11 => "Root cause not known",
+        12 => "Bad intelligent device or component",
+        13 => "Out of calibration",
+        14 => "Special instructions",
+        15 => "Data valid but above normal operating range - least severe level",
+        16 => "Data valid but above normal operating range - moderately severe level",
+        17 => "Data valid but below normal operating range - least severe level",
+        18 => "Data valid but below normal operating range - moderately severe level",
+        19 => "Received network data in error",
+        20 => "Data drifted high",
+        21 => "Data drifted low",
+        31 => "Condition exists",
+        _ => "Reserved",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dm1_single_dtc() {
+        // MIL on, amber warning on; SPN 100 (oil pressure) FMI 1, OC 3
+        let data = [0x44, 0xFF, 0x64, 0x00, 0x01, 0x03, 0xFF, 0xFF];
+        let dm = DiagnosticMessage::parse(DmType::Dm1, &data).unwrap();
+
+        assert_eq!(dm.lamp_status.malfunction_indicator.state, LampState::On);
+        assert_eq!(dm.lamp_status.amber_warning.state, LampState::On);
+        assert_eq!(dm.lamp_status.red_stop.state, LampState::Off);
+        assert_eq!(dm.dtcs.len(), 1);
+        assert_eq!(dm.dtcs[0].spn, 100);
+        assert_eq!(dm.dtcs[0].fmi, 1);
+        assert_eq!(dm.dtcs[0].occurrence_count, 3);
+    }
+
+    #[test]
+    fn test_parse_dm1_no_dtcs() {
+        let data = [0x00, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF];
+        let dm = DiagnosticMessage::parse(DmType::Dm1, &data).unwrap();
+        assert!(dm.dtcs.is_empty());
+    }
+
+    #[test]
+    fn test_parse_spn_high_bits() {
+        // SPN 520192 (0x7F000) uses the upper 3 bits in byte 3
+        let data = [0x00, 0xFF, 0x00, 0xF0, 0xE4, 0x01];
+        let dm = DiagnosticMessage::parse(DmType::Dm2, &data).unwrap();
+        assert_eq!(dm.dtcs[0].spn, 0x7F000);
+        assert_eq!(dm.dtcs[0].fmi, 4);
+    }
+}
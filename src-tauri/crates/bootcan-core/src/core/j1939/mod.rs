@@ -0,0 +1,153 @@
+//! SAE J1939 support
+//!
+//! Provides identifier handling, transport protocol reassembly,
+//! decoding of diagnostic messages (DM1/DM2) and on-demand PGN requests
+//! on top of the raw CAN frames.
+
+pub mod dm;
+pub mod request;
+pub mod transport;
+
+pub use dm::{DiagnosticMessage, DmType};
+pub use request::PgnResponse;
+pub use transport::TransportReassembler;
+
+use crate::core::message::CanFrame;
+
+/// DM1 - active diagnostic trouble codes
+pub const PGN_DM1: u32 = 0xFECA;
+/// DM2 - previously active diagnostic trouble codes
+pub const PGN_DM2: u32 = 0xFECB;
+/// Transport protocol connection management
+pub const PGN_TP_CM: u32 = 0xEC00;
+/// Transport protocol data transfer
+pub const PGN_TP_DT: u32 = 0xEB00;
+/// Request PGN (59904)
+pub const PGN_REQUEST: u32 = 0xEA00;
+/// Acknowledgment (ACK/NACK)
+pub const PGN_ACKNOWLEDGMENT: u32 = 0xE800;
+
+/// Global (broadcast) destination address
+pub const GLOBAL_ADDRESS: u8 = 0xFF;
+
+/// Fields of a 29-bit J1939 identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct J1939Id {
+    pub priority: u8,
+    pub pgn: u32,
+    pub source_address: u8,
+    /// Destination address (GLOBAL_ADDRESS for PDU2 messages)
+    pub destination_address: u8,
+}
+
+impl J1939Id {
+    /// Split a 29-bit CAN identifier into its J1939 fields
+    pub fn from_can_id(id: u32) -> Self {
+        let priority = ((id >> 26) & 0x07) as u8;
+        let pgn = (id >> 8) & 0x3FFFF; // EDP, DP, PF and PS
+        let pdu_format = (pgn >> 8) & 0xFF;
+        let source_address = (id & 0xFF) as u8;
+
+        if pdu_format < 0xF0 {
+            // PDU1: PS field carries the destination address
+            Self {
+                priority,
+                pgn: pgn & 0x3FF00,
+                source_address,
+                destination_address: (pgn & 0xFF) as u8,
+            }
+        } else {
+            // PDU2: PS field is the group extension
+            Self {
+                priority,
+                pgn,
+                source_address,
+                destination_address: GLOBAL_ADDRESS,
+            }
+        }
+    }
+
+    /// Build the 29-bit CAN identifier for these fields
+    pub fn to_can_id(self) -> u32 {
+        let pdu_format = (self.pgn >> 8) & 0xFF;
+        let pgn = if pdu_format < 0xF0 {
+            (self.pgn & 0x3FF00) | self.destination_address as u32
+        } else {
+            self.pgn
+        };
+        ((self.priority as u32 & 0x07) << 26) | (pgn << 8) | self.source_address as u32
+    }
+}
+
+/// Stateful per-channel decoder turning raw frames into J1939 diagnostic messages
+pub struct J1939Decoder {
+    transport: TransportReassembler,
+}
+
+impl J1939Decoder {
+    pub fn new() -> Self {
+        Self {
+            transport: TransportReassembler::new(),
+        }
+    }
+
+    /// Feed a received frame; returns a diagnostic message once one is complete
+    pub fn process(&mut self, frame: &CanFrame) -> Option<DiagnosticMessage> {
+        if !frame.is_extended || frame.is_remote {
+            return None;
+        }
+
+        let id = J1939Id::from_can_id(frame.id);
+        let (pgn, source_address, data) = match id.pgn {
+            PGN_TP_CM | PGN_TP_DT => {
+                let message = self.transport.process(&id, frame)?;
+                (message.pgn, message.source_address, message.data)
+            }
+            pgn => (pgn, id.source_address, frame.data.to_vec()),
+        };
+
+        let dm_type = match pgn {
+            PGN_DM1 => DmType::Dm1,
+            PGN_DM2 => DmType::Dm2,
+            _ => return None,
+        };
+
+        DiagnosticMessage::parse(dm_type, &data).map(|mut message| {
+            message.channel = frame.channel.to_string();
+            message.source_address = source_address;
+            message.timestamp = frame.timestamp;
+            message
+        })
+    }
+}
+
+impl Default for J1939Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_j1939_id_pdu2() {
+        // DM1 from source 0x00 at priority 6
+        let id = J1939Id::from_can_id(0x18FECA00);
+        assert_eq!(id.priority, 6);
+        assert_eq!(id.pgn, PGN_DM1);
+        assert_eq!(id.source_address, 0x00);
+        assert_eq!(id.destination_address, GLOBAL_ADDRESS);
+    }
+
+    #[test]
+    fn test_j1939_id_pdu1() {
+        // TP.CM from 0x17 to 0x3D
+        let id = J1939Id::from_can_id(0x1CEC3D17);
+        assert_eq!(id.pgn, PGN_TP_CM);
+        assert_eq!(id.source_address, 0x17);
+        assert_eq!(id.destination_address, 0x3D);
+        assert_eq!(id.to_can_id(), 0x1CEC3D17);
+    }
+}
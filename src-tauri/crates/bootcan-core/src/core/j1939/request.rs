@@ -0,0 +1,281 @@
+use super::transport::{TP_CM_CTS, TP_CM_END_OF_MSG_ACK, TP_CM_RTS};
+use super::{J1939Id, TransportReassembler, GLOBAL_ADDRESS, PGN_ACKNOWLEDGMENT, PGN_REQUEST, PGN_TP_CM, PGN_TP_DT};
+use crate::core::frame_link::FrameLink;
+use crate::core::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Source address used for requests when none is given (off-board diagnostic tool #2)
+pub const DEFAULT_TOOL_ADDRESS: u8 = 0xF9;
+/// Time to wait for responses (T3 + margin)
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_millis(1250);
+
+/// Priority of the Request PGN and TP.CM frames we send
+const REQUEST_PRIORITY: u8 = 6;
+const TP_PRIORITY: u8 = 7;
+
+/// Response to a PGN request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PgnResponse {
+    pub pgn: u32,
+    pub source_address: u8,
+    pub data: Vec<u8>,
+}
+
+/// Connection mode transfer sent to us, which we have to pace with CTS
+struct InboundTransfer {
+    total_packets: u8,
+    /// Packets the sender allows per CTS (0xFF = no limit)
+    packets_per_cts: u8,
+    received: u8,
+}
+
+fn pgn_bytes(pgn: u32) -> [u8; 3] {
+    let bytes = pgn.to_le_bytes();
+    [bytes[0], bytes[1], bytes[2]]
+}
+
+fn pgn_from_bytes(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0])
+}
+
+/// Send a Request PGN and collect the responses
+///
+/// A request to a specific address returns as soon as that node answered,
+/// either directly or through a transport protocol transfer; RTS/CTS transfers
+/// addressed to `source_address` are acknowledged so the sender can proceed.
+/// A global request collects answers from all nodes until `timeout` expires.
+pub async fn request_pgn(
+    link: &mut dyn FrameLink,
+    pgn: u32,
+    destination: u8,
+    source_address: u8,
+    timeout: Duration,
+) -> Result<Vec<PgnResponse>, BootCanError> {
+    let request = J1939Id {
+        priority: REQUEST_PRIORITY,
+        pgn: PGN_REQUEST,
+        source_address,
+        destination_address: destination,
+    };
+    link.send(CanFrame::new_extended(request.to_can_id(), &pgn_bytes(pgn)))
+        .await?;
+
+    let is_global = destination == GLOBAL_ADDRESS;
+    let mut reassembler = TransportReassembler::new();
+    let mut transfers: HashMap<u8, InboundTransfer> = HashMap::new();
+    let mut responses = Vec::new();
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Some(frame) = link.recv(remaining).await? else {
+            break;
+        };
+        if !frame.is_extended || frame.is_remote {
+            continue;
+        }
+
+        let id = J1939Id::from_can_id(frame.id);
+        let from_destination = is_global || id.source_address == destination;
+        let response = match id.pgn {
+            PGN_TP_CM => {
+                let data = &frame.data;
+                if data.len() >= 8
+                    && data[0] == TP_CM_RTS
+                    && id.destination_address == source_address
+                    && pgn_from_bytes(&data[5..8]) == pgn
+                {
+                    let transfer = InboundTransfer {
+                        total_packets: data[3],
+                        packets_per_cts: data[4].max(1),
+                        received: 0,
+                    };
+                    send_cts(link, &id, &transfer, pgn).await?;
+                    transfers.insert(id.source_address, transfer);
+                }
+                reassembler.process(&id, &frame);
+                None
+            }
+            PGN_TP_DT => {
+                let message = reassembler.process(&id, &frame);
+                if id.destination_address == source_address {
+                    if let Some(transfer) = transfers.get_mut(&id.source_address) {
+                        transfer.received = transfer.received.saturating_add(1);
+                        if let Some(message) = &message {
+                            send_end_of_msg_ack(link, &id, message.data.len(), transfer.total_packets, pgn).await?;
+                            transfers.remove(&id.source_address);
+                        } else if transfer.received < transfer.total_packets
+                            && transfer.received.is_multiple_of(transfer.packets_per_cts)
+                        {
+                            send_cts(link, &id, transfer, pgn).await?;
+                        }
+                    }
+                }
+                message.map(|m| PgnResponse {
+                    pgn: m.pgn,
+                    source_address: m.source_address,
+                    data: m.data,
+                })
+            }
+            PGN_ACKNOWLEDGMENT => {
+                let data = &frame.data;
+                if !is_global
+                    && id.source_address == destination
+                    && data.len() >= 8
+                    && pgn_from_bytes(&data[5..8]) == pgn
+                {
+                    match data[0] {
+                        0x01 => {
+                            return Err(BootCanError::Protocol(format!(
+                                "PGN 0x{:04X} not supported by node 0x{:02X}",
+                                pgn, destination
+                            )))
+                        }
+                        0x02 => {
+                            return Err(BootCanError::Protocol(format!(
+                                "Access to PGN 0x{:04X} denied by node 0x{:02X}",
+                                pgn, destination
+                            )))
+                        }
+                        0x03 => {
+                            return Err(BootCanError::Busy(format!(
+                                "Node 0x{:02X} cannot respond to PGN 0x{:04X} now",
+                                destination, pgn
+                            )))
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            p if p == pgn => Some(PgnResponse {
+                pgn,
+                source_address: id.source_address,
+                data: frame.data.to_vec(),
+            }),
+            _ => None,
+        };
+
+        if let Some(response) = response.filter(|r| r.pgn == pgn && from_destination) {
+            responses.push(response);
+            if !is_global {
+                break;
+            }
+        }
+    }
+
+    if responses.is_empty() && !is_global {
+        return Err(BootCanError::Timeout(format!(
+            "No response to request for PGN 0x{:04X} from node 0x{:02X}",
+            pgn, destination
+        )));
+    }
+    Ok(responses)
+}
+
+/// Clear the sender of a connection mode transfer for its next packets
+async fn send_cts(
+    link: &mut dyn FrameLink,
+    id: &J1939Id,
+    transfer: &InboundTransfer,
+    pgn: u32,
+) -> Result<(), BootCanError> {
+    let remaining = transfer.total_packets.saturating_sub(transfer.received);
+    let [p0, p1, p2] = pgn_bytes(pgn);
+    let data = [
+        TP_CM_CTS,
+        remaining.min(transfer.packets_per_cts),
+        transfer.received + 1,
+        0xFF,
+        0xFF,
+        p0,
+        p1,
+        p2,
+    ];
+    link.send(CanFrame::new_extended(reply_id(id), &data)).await
+}
+
+/// Confirm a completed connection mode transfer
+async fn send_end_of_msg_ack(
+    link: &mut dyn FrameLink,
+    id: &J1939Id,
+    size: usize,
+    total_packets: u8,
+    pgn: u32,
+) -> Result<(), BootCanError> {
+    let size = (size as u16).to_le_bytes();
+    let [p0, p1, p2] = pgn_bytes(pgn);
+    let data = [TP_CM_END_OF_MSG_ACK, size[0], size[1], total_packets, 0xFF, p0, p1, p2];
+    link.send(CanFrame::new_extended(reply_id(id), &data)).await
+}
+
+/// TP.CM identifier for answering the sender of `id`
+fn reply_id(id: &J1939Id) -> u32 {
+    J1939Id {
+        priority: TP_PRIORITY,
+        pgn: PGN_TP_CM,
+        source_address: id.destination_address,
+        destination_address: id.source_address,
+    }
+    .to_can_id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_link::scripted::ScriptedLink;
+
+    /// Link replaying extended frames and recording transmissions
+    fn replay(incoming: Vec<(u32, &[u8])>) -> ScriptedLink {
+        ScriptedLink::replay(incoming.into_iter().map(|(id, data)| CanFrame::new_extended(id, data)).collect())
+    }
+
+    #[tokio::test]
+    async fn test_direct_response() {
+        // Unrelated traffic, then engine hours (PGN 0xFEE5) from 0x00
+        let mut link = replay(vec![
+            (0x18FEF100, &[0; 8]),
+            (0x18FEE500, &[1, 2, 3, 4, 5, 6, 7, 8]),
+        ]);
+        let responses = request_pgn(&mut link, 0xFEE5, 0x00, DEFAULT_TOOL_ADDRESS, DEFAULT_REQUEST_TIMEOUT)
+            .await
+            .unwrap();
+
+        assert_eq!(link.sent[0].id, 0x18EA00F9);
+        assert_eq!(link.sent[0].data, vec![0xE5, 0xFE, 0x00]);
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[tokio::test]
+    async fn test_rts_cts_response() {
+        // VIN (PGN 0xFEEC) of 10 bytes sent by 0x00 to us in 2 packets
+        let mut link = replay(vec![
+            (0x1CECF900, &[16, 10, 0, 2, 0xFF, 0xEC, 0xFE, 0x00]),
+            (0x1CEBF900, &[1, b'V', b'I', b'N', b'0', b'1', b'2', b'3']),
+            (0x1CEBF900, &[2, b'4', b'5', b'6', 0xFF, 0xFF, 0xFF, 0xFF]),
+        ]);
+        let responses = request_pgn(&mut link, 0xFEEC, 0x00, DEFAULT_TOOL_ADDRESS, DEFAULT_REQUEST_TIMEOUT)
+            .await
+            .unwrap();
+
+        assert_eq!(responses[0].data, b"VIN0123456".to_vec());
+        // Request, CTS for both packets, EndOfMsgAck
+        assert_eq!(link.sent.len(), 3);
+        assert_eq!(link.sent[1].id, 0x1CEC00F9);
+        assert_eq!(link.sent[1].data, vec![17, 2, 1, 0xFF, 0xFF, 0xEC, 0xFE, 0x00]);
+        assert_eq!(link.sent[2].data, vec![19, 10, 0, 2, 0xFF, 0xEC, 0xFE, 0x00]);
+    }
+
+    #[tokio::test]
+    async fn test_nack() {
+        let mut link = replay(vec![(0x18E8F900, &[1, 0xFF, 0xFF, 0xFF, 0xF9, 0xEC, 0xFE, 0x00])]);
+        let result = request_pgn(&mut link, 0xFEEC, 0x00, DEFAULT_TOOL_ADDRESS, DEFAULT_REQUEST_TIMEOUT).await;
+        assert!(result.unwrap_err().message().contains("not supported"));
+    }
+}
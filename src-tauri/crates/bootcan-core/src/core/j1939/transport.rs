@@ -0,0 +1,181 @@
+use super::{J1939Id, PGN_TP_CM, PGN_TP_DT};
+use crate::core::message::CanFrame;
+use std::collections::HashMap;
+
+/// TP.CM control bytes
+pub(crate) const TP_CM_RTS: u8 = 16;
+pub(crate) const TP_CM_CTS: u8 = 17;
+pub(crate) const TP_CM_END_OF_MSG_ACK: u8 = 19;
+pub(crate) const TP_CM_BAM: u8 = 32;
+pub(crate) const TP_CM_ABORT: u8 = 255;
+
+/// Maximum time between packets of one transfer (T1, 750 ms)
+const PACKET_TIMEOUT_SEC: f64 = 0.75;
+
+/// A multi-packet message reassembled from TP.CM/TP.DT frames
+#[derive(Debug, Clone)]
+pub struct ReassembledMessage {
+    pub pgn: u32,
+    pub source_address: u8,
+    pub data: Vec<u8>,
+}
+
+/// In-progress transfer between a source and a destination
+struct Session {
+    pgn: u32,
+    total_size: usize,
+    total_packets: u8,
+    next_sequence: u8,
+    data: Vec<u8>,
+    last_timestamp: f64,
+}
+
+/// Passive reassembler for J1939-21 transport protocol transfers
+///
+/// Handles both broadcast (BAM) and connection mode (RTS/CTS) transfers by
+/// following the data packets on the bus. It never transmits flow control.
+pub struct TransportReassembler {
+    sessions: HashMap<(u8, u8), Session>,
+}
+
+impl TransportReassembler {
+    pub fn new() -> Self {
+        Self {
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Feed a TP.CM or TP.DT frame; returns the message once all packets arrived
+    pub fn process(&mut self, id: &J1939Id, frame: &CanFrame) -> Option<ReassembledMessage> {
+        if frame.data.len() < 8 {
+            return None;
+        }
+
+        let key = (id.source_address, id.destination_address);
+        match id.pgn {
+            PGN_TP_CM => {
+                self.handle_connection_management(key, frame);
+                None
+            }
+            PGN_TP_DT => self.handle_data_transfer(key, frame),
+            _ => None,
+        }
+    }
+
+    fn handle_connection_management(&mut self, key: (u8, u8), frame: &CanFrame) {
+        let data = &frame.data;
+        match data[0] {
+            TP_CM_BAM | TP_CM_RTS => {
+                let total_size = u16::from_le_bytes([data[1], data[2]]) as usize;
+                let total_packets = data[3];
+                let pgn = u32::from_le_bytes([data[5], data[6], data[7], 0]);
+                self.sessions.insert(
+                    key,
+                    Session {
+                        pgn,
+                        total_size,
+                        total_packets,
+                        next_sequence: 1,
+                        data: Vec::with_capacity(total_size),
+                        last_timestamp: frame.timestamp,
+                    },
+                );
+            }
+            TP_CM_ABORT => {
+                // Abort is sent by either side of the connection
+                self.sessions.remove(&key);
+                self.sessions.remove(&(key.1, key.0));
+            }
+            TP_CM_CTS | TP_CM_END_OF_MSG_ACK => {}
+            other => {
+                tracing::debug!("Ignoring unknown TP.CM control byte {}", other);
+            }
+        }
+    }
+
+    fn handle_data_transfer(&mut self, key: (u8, u8), frame: &CanFrame) -> Option<ReassembledMessage> {
+        let session = self.sessions.get_mut(&key)?;
+
+        if frame.timestamp - session.last_timestamp > PACKET_TIMEOUT_SEC {
+            tracing::debug!("J1939 TP session {:?} timed out", key);
+            self.sessions.remove(&key);
+            return None;
+        }
+
+        let sequence = frame.data[0];
+        if sequence != session.next_sequence {
+            // Retransmissions in RTS/CTS mode repeat earlier sequence numbers
+            if sequence >= session.next_sequence {
+                tracing::debug!(
+                    "J1939 TP session {:?} lost packet (expected {}, got {})",
+                    key,
+                    session.next_sequence,
+                    sequence
+                );
+                self.sessions.remove(&key);
+            }
+            return None;
+        }
+
+        session.data.extend_from_slice(&frame.data[1..8]);
+        session.next_sequence = session.next_sequence.wrapping_add(1);
+        session.last_timestamp = frame.timestamp;
+
+        if sequence < session.total_packets {
+            return None;
+        }
+
+        let mut session = self.sessions.remove(&key)?;
+        session.data.truncate(session.total_size);
+        Some(ReassembledMessage {
+            pgn: session.pgn,
+            source_address: key.0,
+            data: session.data,
+        })
+    }
+}
+
+impl Default for TransportReassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: u32, data: &[u8], timestamp: f64) -> CanFrame {
+        let mut frame = CanFrame::new_extended(id, data);
+        frame.timestamp = timestamp;
+        frame
+    }
+
+    #[test]
+    fn test_bam_reassembly() {
+        let mut reassembler = TransportReassembler::new();
+        // BAM announcing 10 bytes of PGN 0xFECA in 2 packets from 0x00
+        let cm = frame(0x1CECFF00, &[32, 10, 0, 2, 0xFF, 0xCA, 0xFE, 0x00], 0.0);
+        let dt1 = frame(0x1CEBFF00, &[1, 0, 1, 2, 3, 4, 5, 6], 0.05);
+        let dt2 = frame(0x1CEBFF00, &[2, 7, 8, 9, 0xFF, 0xFF, 0xFF, 0xFF], 0.1);
+
+        assert!(reassembler.process(&J1939Id::from_can_id(cm.id), &cm).is_none());
+        assert!(reassembler.process(&J1939Id::from_can_id(dt1.id), &dt1).is_none());
+        let message = reassembler.process(&J1939Id::from_can_id(dt2.id), &dt2).unwrap();
+
+        assert_eq!(message.pgn, 0xFECA);
+        assert_eq!(message.source_address, 0x00);
+        assert_eq!(message.data, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_session_timeout() {
+        let mut reassembler = TransportReassembler::new();
+        let cm = frame(0x1CECFF00, &[32, 10, 0, 2, 0xFF, 0xCA, 0xFE, 0x00], 0.0);
+        let dt1 = frame(0x1CEBFF00, &[1, 0, 1, 2, 3, 4, 5, 6], 1.0);
+
+        reassembler.process(&J1939Id::from_can_id(cm.id), &cm);
+        assert!(reassembler.process(&J1939Id::from_can_id(dt1.id), &dt1).is_none());
+        assert!(reassembler.sessions.is_empty());
+    }
+}
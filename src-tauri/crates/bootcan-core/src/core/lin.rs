@@ -0,0 +1,168 @@
+//! LIN frames
+//!
+//! A LIN frame is a header sent by the master, carrying the protected ID, and
+//! a response of up to 8 bytes plus checksum, sent by whichever node publishes
+//! the frame. Inside the app LIN frames travel as `CanFrame`s marked with
+//! `BusType::Lin`, so logging, playback and the frame events handle both buses.
+
+use super::message::{BusType, CanFrame, ChannelName, Direction, FrameData};
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+
+/// Highest LIN frame ID
+pub const MAX_LIN_ID: u8 = 0x3F;
+/// Longest LIN response
+pub const MAX_LIN_DATA_LEN: usize = 8;
+/// Master request and slave response, which always use the classic checksum
+const DIAGNOSTIC_IDS: [u8; 2] = [0x3C, 0x3D];
+
+/// Checksum model of a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinChecksum {
+    /// LIN 1.x: data bytes only
+    Classic,
+    /// LIN 2.x: protected ID and data bytes
+    #[default]
+    Enhanced,
+}
+
+/// LIN frame as sent or received by a LIN interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinFrame {
+    /// Frame ID (0-63), without the parity bits
+    pub id: u8,
+    pub data: FrameData,
+    pub checksum_type: LinChecksum,
+    /// Timestamp in seconds since connection start
+    pub timestamp: f64,
+    pub channel: ChannelName,
+    /// Received, or published by us
+    pub direction: Direction,
+}
+
+impl LinFrame {
+    /// Frame with the checksum model LIN 2.x uses for `id`
+    pub fn new(id: u8, data: &[u8]) -> Self {
+        let checksum_type = if DIAGNOSTIC_IDS.contains(&id) {
+            LinChecksum::Classic
+        } else {
+            LinChecksum::Enhanced
+        };
+        Self {
+            id: id & MAX_LIN_ID,
+            data: FrameData::from_slice(&data[..data.len().min(MAX_LIN_DATA_LEN)]),
+            checksum_type,
+            timestamp: 0.0,
+            channel: ChannelName::default(),
+            direction: Direction::Tx,
+        }
+    }
+
+    /// ID with parity bits as sent in the header
+    pub fn protected_id(&self) -> u8 {
+        protected_id(self.id)
+    }
+
+    /// Checksum byte of the response
+    pub fn checksum(&self) -> u8 {
+        checksum(self.checksum_type, self.protected_id(), &self.data)
+    }
+
+    /// The frame as it travels through channels, loggers and events
+    pub fn to_can_frame(&self) -> CanFrame {
+        CanFrame {
+            id: self.id as u32,
+            dlc: self.data.len() as u8,
+            data: self.data,
+            timestamp: self.timestamp,
+            channel: self.channel.clone(),
+            direction: self.direction,
+            bus: BusType::Lin,
+            ..CanFrame::default()
+        }
+    }
+
+    /// LIN frame to publish for a frame sent to a LIN channel
+    pub fn from_can_frame(frame: &CanFrame) -> Result<Self, BootCanError> {
+        if frame.id > MAX_LIN_ID as u32 || frame.is_extended {
+            return Err(BootCanError::InvalidInput(format!(
+                "LIN frame ID 0x{:X} is out of range (0-0x3F)",
+                frame.id
+            )));
+        }
+        if frame.data.len() > MAX_LIN_DATA_LEN {
+            return Err(BootCanError::InvalidInput(format!(
+                "LIN frames carry at most {} bytes, got {}",
+                MAX_LIN_DATA_LEN,
+                frame.data.len()
+            )));
+        }
+        let mut lin = Self::new(frame.id as u8, &frame.data);
+        lin.timestamp = frame.timestamp;
+        lin.channel.clone_from(&frame.channel);
+        lin.direction = frame.direction;
+        Ok(lin)
+    }
+}
+
+/// Add the two parity bits P0 (bit 6) and P1 (bit 7) to a frame ID
+pub fn protected_id(id: u8) -> u8 {
+    let id = id & MAX_LIN_ID;
+    let bit = |n: u8| (id >> n) & 1;
+    let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+    let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+    id | (p0 << 6) | (p1 << 7)
+}
+
+/// Inverted sum with carry of the data, and of the protected ID for `Enhanced`
+pub fn checksum(kind: LinChecksum, protected_id: u8, data: &[u8]) -> u8 {
+    let start = match kind {
+        LinChecksum::Classic => 0,
+        LinChecksum::Enhanced => protected_id as u16,
+    };
+    let sum = data.iter().fold(start, |sum, &byte| {
+        let sum = sum + byte as u16;
+        if sum > 0xFF {
+            sum - 0xFF
+        } else {
+            sum
+        }
+    });
+    !(sum as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protected_id_and_checksum() {
+        assert_eq!(protected_id(0x00), 0x80);
+        assert_eq!(protected_id(0x01), 0xC1);
+        assert_eq!(protected_id(0x3C), 0x3C);
+        assert_eq!(protected_id(0x3D), 0x7D);
+
+        assert_eq!(checksum(LinChecksum::Classic, 0xC1, &[0x01]), 0xFE);
+        assert_eq!(checksum(LinChecksum::Enhanced, 0xC1, &[0x01]), 0x3D);
+        // 0xFF + 0x02 carries into 0x02
+        assert_eq!(checksum(LinChecksum::Classic, 0, &[0xFF, 0x02]), 0xFD);
+        assert_eq!(LinFrame::new(0x3C, &[0; 8]).checksum_type, LinChecksum::Classic);
+    }
+
+    #[test]
+    fn test_can_frame_round_trip() {
+        let mut lin = LinFrame::new(0x21, &[1, 2, 3]);
+        lin.channel = "lin0".into();
+        let frame = lin.to_can_frame();
+        assert_eq!((frame.id, frame.dlc, frame.bus), (0x21, 3, BusType::Lin));
+        assert_eq!(LinFrame::from_can_frame(&frame).unwrap(), lin);
+
+        assert!(LinFrame::from_can_frame(&CanFrame::new(0x40, &[])).is_err());
+        assert!(LinFrame::from_can_frame(&CanFrame::new(0x10, &[0; 9])).is_ok());
+        let mut long = CanFrame::new(0x10, &[]);
+        long.data = FrameData::from_slice(&[0; 12]);
+        assert!(LinFrame::from_can_frame(&long).is_err());
+    }
+}
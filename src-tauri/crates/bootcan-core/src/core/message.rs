@@ -0,0 +1,602 @@
+use crate::error::BootCanError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Largest payload of a frame (CAN FD)
+pub const MAX_DATA_LEN: usize = 64;
+
+/// Frame payload stored inline, so frames carry no heap allocation for their data
+///
+/// Dereferences to the bytes in use and serializes as a plain byte array.
+/// Bytes beyond 64 are dropped.
+#[derive(Clone, Copy)]
+pub struct FrameData {
+    bytes: [u8; MAX_DATA_LEN],
+    len: u8,
+}
+
+impl FrameData {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; MAX_DATA_LEN],
+            len: 0,
+        }
+    }
+
+    pub fn from_slice(data: &[u8]) -> Self {
+        let mut frame_data = Self::new();
+        frame_data.extend_from_slice(data);
+        frame_data
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    pub fn push(&mut self, byte: u8) {
+        if (self.len as usize) < MAX_DATA_LEN {
+            self.bytes[self.len as usize] = byte;
+            self.len += 1;
+        }
+    }
+
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        let start = self.len as usize;
+        let count = data.len().min(MAX_DATA_LEN - start);
+        self.bytes[start..start + count].copy_from_slice(&data[..count]);
+        self.len += count as u8;
+    }
+
+    /// Change the length, padding with `value`
+    pub fn resize(&mut self, len: usize, value: u8) {
+        let len = len.min(MAX_DATA_LEN);
+        if len > self.len as usize {
+            self.bytes[self.len as usize..len].fill(value);
+        }
+        self.len = len as u8;
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len.min(MAX_DATA_LEN) as u8);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for FrameData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for FrameData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for FrameData {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len as usize]
+    }
+}
+
+impl fmt::Debug for FrameData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl PartialEq for FrameData {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for FrameData {}
+
+impl PartialEq<Vec<u8>> for FrameData {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl PartialEq<[u8]> for FrameData {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for FrameData {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl From<&[u8]> for FrameData {
+    fn from(data: &[u8]) -> Self {
+        Self::from_slice(data)
+    }
+}
+
+impl From<Vec<u8>> for FrameData {
+    fn from(data: Vec<u8>) -> Self {
+        Self::from_slice(&data)
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for FrameData {
+    fn from(data: [u8; N]) -> Self {
+        Self::from_slice(&data)
+    }
+}
+
+impl From<FrameData> for Vec<u8> {
+    fn from(data: FrameData) -> Self {
+        data.to_vec()
+    }
+}
+
+impl FromIterator<u8> for FrameData {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut data = Self::new();
+        iter.into_iter().for_each(|byte| data.push(byte));
+        data
+    }
+}
+
+impl<'a> IntoIterator for &'a FrameData {
+    type Item = &'a u8;
+    type IntoIter = std::slice::Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl Serialize for FrameData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // A sequence of numbers, like the Vec<u8> the frontend expects
+        serializer.collect_seq(self.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for FrameData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = Vec::<u8>::deserialize(deserializer)?;
+        if data.len() > MAX_DATA_LEN {
+            return Err(serde::de::Error::custom(format!(
+                "frame data of {} bytes exceeds {} bytes",
+                data.len(),
+                MAX_DATA_LEN
+            )));
+        }
+        Ok(Self::from_slice(&data))
+    }
+}
+
+/// Name of the channel a frame belongs to
+///
+/// Shared between frames, so cloning a frame only bumps a reference count.
+/// Dereferences to `str` and serializes as a plain string.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChannelName(Arc<str>);
+
+impl ChannelName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for ChannelName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ChannelName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ChannelName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ChannelName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl fmt::Display for ChannelName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq<str> for ChannelName {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ChannelName {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for ChannelName {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl From<&str> for ChannelName {
+    fn from(name: &str) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<String> for ChannelName {
+    fn from(name: String) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<&String> for ChannelName {
+    fn from(name: &String) -> Self {
+        Self(name.as_str().into())
+    }
+}
+
+impl From<ChannelName> for String {
+    fn from(name: ChannelName) -> Self {
+        name.0.to_string()
+    }
+}
+
+impl Serialize for ChannelName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ChannelName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Whether a frame was received or transmitted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Rx,
+    Tx,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rx => "rx",
+            Self::Tx => "tx",
+        }
+    }
+
+    pub fn is_rx(&self) -> bool {
+        *self == Self::Rx
+    }
+
+    pub fn is_tx(&self) -> bool {
+        *self == Self::Tx
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Bus a frame was carried on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BusType {
+    #[default]
+    Can,
+    /// LIN frame; the ID is the 6-bit frame ID, see `core::lin`
+    Lin,
+}
+
+impl BusType {
+    pub fn is_can(&self) -> bool {
+        *self == Self::Can
+    }
+}
+
+/// Standard CAN frame representation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanFrame {
+    /// CAN identifier (11-bit standard or 29-bit extended)
+    pub id: u32,
+    /// Whether this is an extended (29-bit) ID
+    pub is_extended: bool,
+    /// Whether this is a remote transmission request
+    pub is_remote: bool,
+    /// Data length code (0-8 for classic CAN, 0-64 for CAN FD)
+    pub dlc: u8,
+    /// Frame data bytes
+    pub data: FrameData,
+    /// Timestamp in seconds since connection start
+    pub timestamp: f64,
+    /// Channel identifier this message was sent/received on
+    pub channel: ChannelName,
+    /// Received or transmitted; "rx" or "tx" in JSON
+    pub direction: Direction,
+    /// Bus the frame was carried on; left out of the JSON for CAN
+    #[serde(default, skip_serializing_if = "BusType::is_can")]
+    pub bus: BusType,
+}
+
+impl Default for CanFrame {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            is_extended: false,
+            is_remote: false,
+            dlc: 0,
+            data: FrameData::new(),
+            timestamp: 0.0,
+            channel: ChannelName::default(),
+            direction: Direction::Rx,
+            bus: BusType::Can,
+        }
+    }
+}
+
+impl CanFrame {
+    /// Create a new CAN frame
+    pub fn new(id: u32, data: &[u8]) -> Self {
+        let dlc = data.len().min(8) as u8;
+        Self {
+            id,
+            is_extended: id > 0x7FF,
+            is_remote: false,
+            dlc,
+            data: FrameData::from_slice(&data[..dlc as usize]),
+            timestamp: 0.0,
+            channel: ChannelName::default(),
+            direction: Direction::Tx,
+            bus: BusType::Can,
+        }
+    }
+
+    /// Create a new extended CAN frame
+    pub fn new_extended(id: u32, data: &[u8]) -> Self {
+        let dlc = data.len().min(8) as u8;
+        Self {
+            id,
+            is_extended: true,
+            is_remote: false,
+            dlc,
+            data: FrameData::from_slice(&data[..dlc as usize]),
+            timestamp: 0.0,
+            channel: ChannelName::default(),
+            direction: Direction::Tx,
+            bus: BusType::Can,
+        }
+    }
+
+    /// Create a remote transmission request frame
+    pub fn new_rtr(id: u32, dlc: u8) -> Self {
+        Self {
+            id,
+            is_extended: id > 0x7FF,
+            is_remote: true,
+            dlc: dlc.min(8),
+            data: FrameData::new(),
+            timestamp: 0.0,
+            channel: ChannelName::default(),
+            direction: Direction::Tx,
+            bus: BusType::Can,
+        }
+    }
+
+    /// Set the frame as received
+    pub fn as_received(mut self, channel: &str, timestamp: f64) -> Self {
+        self.direction = Direction::Rx;
+        self.channel = channel.into();
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set the frame as transmitted
+    pub fn as_transmitted(mut self, channel: &str, timestamp: f64) -> Self {
+        self.direction = Direction::Tx;
+        self.channel = channel.into();
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Get the formatted ID as hex string
+    pub fn id_hex(&self) -> String {
+        if self.is_extended {
+            format!("{:08X}", self.id)
+        } else {
+            format!("{:03X}", self.id)
+        }
+    }
+
+    /// Get the formatted data as hex string
+    pub fn data_hex(&self) -> String {
+        self.data
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// CAN FD frame with additional FD-specific fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanFdFrame {
+    /// Base CAN frame
+    #[serde(flatten)]
+    pub base: CanFrame,
+    /// Bit Rate Switch flag (data phase at higher bitrate)
+    pub brs: bool,
+    /// Error State Indicator
+    pub esi: bool,
+}
+
+impl CanFdFrame {
+    /// Create a new CAN FD frame
+    pub fn new(id: u32, data: &[u8], brs: bool) -> Self {
+        let dlc = data.len().min(64) as u8;
+        Self {
+            base: CanFrame {
+                id,
+                is_extended: id > 0x7FF,
+                is_remote: false,
+                dlc,
+                data: FrameData::from_slice(&data[..dlc as usize]),
+                timestamp: 0.0,
+                channel: ChannelName::default(),
+                direction: Direction::Tx,
+                bus: BusType::Can,
+            },
+            brs,
+            esi: false,
+        }
+    }
+}
+
+/// DLC for a payload of `len` bytes: up to 8, or a CAN FD length (12, 16, 20, 24, 32, 48, 64)
+pub fn dlc_for_len(len: usize) -> Result<u8, BootCanError> {
+    match len {
+        0..=8 | 12 | 16 | 20 | 24 | 32 | 48 | 64 => Ok(len as u8),
+        _ => Err(BootCanError::InvalidInput(format!(
+            "{} data bytes is no valid frame length (0-8, or 12, 16, 20, 24, 32, 48, 64 for CAN FD)", len
+        ))),
+    }
+}
+
+/// Frame that can be sent to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FramePayload {
+    pub id: u32,
+    pub is_extended: bool,
+    pub is_remote: bool,
+    pub dlc: u8,
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub channel: Option<String>,
+}
+
+impl From<&CanFrame> for FramePayload {
+    fn from(frame: &CanFrame) -> Self {
+        Self {
+            id: frame.id,
+            is_extended: frame.is_extended,
+            is_remote: frame.is_remote,
+            dlc: frame.dlc,
+            data: frame.data.to_vec(),
+            channel: if frame.channel.is_empty() {
+                None
+            } else {
+                Some(frame.channel.to_string())
+            },
+        }
+    }
+}
+
+impl From<FramePayload> for CanFrame {
+    fn from(payload: FramePayload) -> Self {
+        Self {
+            id: payload.id,
+            is_extended: payload.is_extended,
+            is_remote: payload.is_remote,
+            dlc: payload.dlc,
+            data: FrameData::from_slice(&payload.data),
+            timestamp: 0.0,
+            channel: payload.channel.map(ChannelName::from).unwrap_or_default(),
+            direction: Direction::Tx,
+            bus: BusType::Can,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_frame_new() {
+        let frame = CanFrame::new(0x123, &[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(frame.id, 0x123);
+        assert_eq!(frame.dlc, 4);
+        assert!(!frame.is_extended);
+        assert!(!frame.is_remote);
+    }
+
+    #[test]
+    fn test_dlc_for_len() {
+        assert_eq!(dlc_for_len(0).unwrap(), 0);
+        assert_eq!(dlc_for_len(8).unwrap(), 8);
+        assert_eq!(dlc_for_len(64).unwrap(), 64);
+        for len in [9, 13, 65, 256] {
+            assert!(dlc_for_len(len).is_err());
+        }
+    }
+
+    #[test]
+    fn test_can_frame_extended() {
+        let frame = CanFrame::new_extended(0x12345678, &[0xAA, 0xBB]);
+        assert_eq!(frame.id, 0x12345678);
+        assert!(frame.is_extended);
+    }
+
+    #[test]
+    fn test_frame_data_serializes_as_bytes() {
+        let frame = CanFrame::new(0x123, &[1, 2, 3]);
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["data"], serde_json::json!([1, 2, 3]));
+        let parsed: CanFrame = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.data, [1, 2, 3]);
+
+        let mut data = FrameData::from_slice(&[0xAA; 70]);
+        assert_eq!(data.len(), MAX_DATA_LEN);
+        data.resize(2, 0);
+        data.resize(4, 0xFF);
+        assert_eq!(data, [0xAA, 0xAA, 0xFF, 0xFF]);
+        assert!(serde_json::from_str::<FrameData>(&format!("{:?}", vec![0u8; 65])).is_err());
+    }
+
+    #[test]
+    fn test_can_frame_id_hex() {
+        let standard = CanFrame::new(0x123, &[]);
+        assert_eq!(standard.id_hex(), "123");
+
+        let extended = CanFrame::new_extended(0x12345678, &[]);
+        assert_eq!(extended.id_hex(), "12345678");
+    }
+}
+
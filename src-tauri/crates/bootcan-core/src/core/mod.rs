@@ -0,0 +1,46 @@
+pub mod channel;
+pub mod subscriber;
+pub mod message;
+pub mod lin;
+pub mod frame_pool;
+pub mod frame_batch;
+pub mod frame_store;
+pub mod bus_stats;
+pub mod id_stats;
+pub mod stats_history;
+pub mod anomaly;
+pub mod top_talkers;
+pub mod cycle_monitor;
+pub mod trace_logger;
+pub mod trace_player;
+pub mod pcap;
+pub mod trace_export;
+pub mod trace_mutation;
+pub mod trace_decode;
+pub mod decode_pool;
+pub mod dbc;
+pub mod signal_watch;
+pub mod signal_series;
+pub mod filter;
+pub mod j1939;
+pub mod frame_link;
+pub mod canopen;
+pub mod ccp;
+pub mod secoc;
+pub mod isotp;
+pub mod uds;
+pub mod bootloader;
+pub mod traffic_gen;
+pub mod auto_responder;
+pub mod virtual_ecu;
+pub mod scenario;
+pub mod e2e;
+pub mod gateway;
+pub mod plugin;
+pub mod obd;
+pub mod influx;
+pub mod udp_broadcast;
+pub mod gvret;
+pub mod tcp_bridge;
+pub mod zmq_bridge;
+pub mod variables;
@@ -0,0 +1,332 @@
+//! Simulated OBD-II ECU (SAE J1979 over ISO 15765-4)
+//!
+//! Answers functional requests on 0x7DF and physical requests on its own
+//! request ID for mode 01 (current data), 03 (stored DTCs), 04 (clear DTCs)
+//! and 09 (vehicle information). Values are configured in engineering units
+//! and encoded with the standard PID formulas.
+
+use super::frame_link::FrameLink;
+use super::isotp::{IsoTpConfig, IsoTpLink, DEFAULT_ISOTP_TIMEOUT, DEFAULT_MAX_MESSAGE_LEN};
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Functional (broadcast) request identifier
+pub const OBD_FUNCTIONAL_ID: u32 = 0x7DF;
+
+const MODE_CURRENT_DATA: u8 = 0x01;
+const MODE_STORED_DTCS: u8 = 0x03;
+const MODE_CLEAR_DTCS: u8 = 0x04;
+const MODE_VEHICLE_INFO: u8 = 0x09;
+const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+
+const PID_MONITOR_STATUS: u8 = 0x01;
+const INFO_VIN: u8 = 0x02;
+
+fn default_request_id() -> u32 {
+    0x7E0
+}
+
+fn default_response_id() -> u32 {
+    0x7E8
+}
+
+/// Mode 01 PID served by the simulator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObdPidValue {
+    pub pid: u8,
+    /// Physical value, encoded with the PID's standard formula
+    #[serde(default)]
+    pub value: f64,
+    /// Raw data bytes; overrides `value` (needed for PIDs without a known formula)
+    #[serde(default)]
+    pub raw: Option<Vec<u8>>,
+}
+
+/// Response table of a simulated OBD-II ECU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObdSimConfig {
+    /// Physical request ID (functional requests always use 0x7DF)
+    #[serde(default = "default_request_id")]
+    pub request_id: u32,
+    #[serde(default = "default_response_id")]
+    pub response_id: u32,
+    #[serde(default)]
+    pub pids: Vec<ObdPidValue>,
+    /// Stored DTCs such as "P0301"
+    #[serde(default)]
+    pub dtcs: Vec<String>,
+    #[serde(default)]
+    pub vin: String,
+}
+
+impl Default for ObdSimConfig {
+    /// Idling engine with one stored DTC
+    fn default() -> Self {
+        let pids = [(0x04, 18.0), (0x05, 88.0), (0x0C, 820.0), (0x0D, 0.0), (0x0F, 32.0), (0x11, 14.5), (0x2F, 62.0)];
+        Self {
+            request_id: default_request_id(),
+            response_id: default_response_id(),
+            pids: pids
+                .into_iter()
+                .map(|(pid, value)| ObdPidValue { pid, value, raw: None })
+                .collect(),
+            dtcs: vec!["P0420".to_string()],
+            vin: "1BOOTCAN0SIM00001".to_string(),
+        }
+    }
+}
+
+/// Encode a physical value with the standard formula of a mode 01 PID
+pub fn encode_pid(pid: u8, value: f64) -> Option<Vec<u8>> {
+    let byte = |v: f64| v.round().clamp(0.0, 255.0) as u8;
+    let word = |v: f64| (v.round().clamp(0.0, 65535.0) as u16).to_be_bytes().to_vec();
+    Some(match pid {
+        // Percentages over 0..=255
+        0x04 | 0x11 | 0x2F | 0x45 | 0x47 | 0x4C => vec![byte(value * 255.0 / 100.0)],
+        // Temperatures with -40 °C offset
+        0x05 | 0x0F | 0x46 | 0x5C => vec![byte(value + 40.0)],
+        // Plain 0..=255 units (kPa, km/h)
+        0x0A | 0x0B | 0x0D | 0x33 => vec![byte(value)],
+        0x0C => word(value * 4.0),
+        0x10 => word(value * 100.0),
+        // Seconds / distances / minutes
+        0x1F | 0x21 | 0x31 | 0x4D | 0x4E => word(value),
+        0x42 => word(value * 1000.0),
+        _ => return None,
+    })
+}
+
+/// Parse a DTC string such as "P0301" into its two-byte encoding
+pub fn parse_dtc(dtc: &str) -> Result<[u8; 2], BootCanError> {
+    let invalid = || BootCanError::InvalidInput(format!("Invalid DTC {}", dtc));
+    let mut chars = dtc.chars();
+    let system = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('P') => 0,
+        Some('C') => 1,
+        Some('B') => 2,
+        Some('U') => 3,
+        _ => return Err(invalid()),
+    };
+    let digits = chars.as_str();
+    if digits.len() != 4 {
+        return Err(invalid());
+    }
+    let code = u16::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    if code >> 12 > 3 {
+        return Err(invalid());
+    }
+    Ok(((system << 14) | code).to_be_bytes())
+}
+
+/// Bitmap answering a "PIDs supported" request for `base`
+fn supported_bitmap(base: u8, supported: impl Iterator<Item = u8> + Clone) -> [u8; 4] {
+    let mut bitmap = 0u32;
+    for pid in supported.clone() {
+        if pid > base && pid <= base.saturating_add(0x20) {
+            bitmap |= 1 << (32 - (pid - base));
+        }
+    }
+    // Bit 0 announces the next range
+    if base < 0xE0 && supported.clone().any(|pid| pid > base + 0x20) {
+        bitmap |= 1;
+    }
+    bitmap.to_be_bytes()
+}
+
+/// OBD-II request handler answering from a configured table
+pub struct ObdServer {
+    pids: BTreeMap<u8, Vec<u8>>,
+    dtcs: Vec<[u8; 2]>,
+    vin: String,
+}
+
+impl ObdServer {
+    pub fn new(config: &ObdSimConfig) -> Result<Self, BootCanError> {
+        let mut pids = BTreeMap::new();
+        for entry in &config.pids {
+            if entry.pid == 0 || entry.pid.is_multiple_of(0x20) {
+                return Err(BootCanError::InvalidInput(format!(
+                    "PID 0x{:02X} is reserved for the supported PID bitmap", entry.pid
+                )));
+            }
+            let data = match &entry.raw {
+                Some(raw) => raw.clone(),
+                None => encode_pid(entry.pid, entry.value)
+                    .ok_or_else(|| {
+                        BootCanError::InvalidInput(format!("No formula for PID 0x{:02X}, give raw bytes", entry.pid))
+                    })?,
+            };
+            pids.insert(entry.pid, data);
+        }
+        if !config.vin.is_empty() && config.vin.len() != 17 {
+            return Err(BootCanError::InvalidInput(format!("VIN must have 17 characters, got {}", config.vin.len())));
+        }
+
+        Ok(Self {
+            pids,
+            dtcs: config.dtcs.iter().map(|dtc| parse_dtc(dtc)).collect::<Result<_, _>>()?,
+            vin: config.vin.clone(),
+        })
+    }
+
+    fn current_data(&self, pid: u8) -> Option<Vec<u8>> {
+        if pid.is_multiple_of(0x20) {
+            let mut supported: Vec<u8> = self.pids.keys().copied().collect();
+            supported.push(PID_MONITOR_STATUS);
+            return Some(supported_bitmap(pid, supported.into_iter()).to_vec());
+        }
+        if pid == PID_MONITOR_STATUS && !self.pids.contains_key(&pid) {
+            // MIL on when DTCs are stored, no readiness tests reported
+            let mil = if self.dtcs.is_empty() { 0 } else { 0x80 };
+            return Some(vec![mil | self.dtcs.len().min(0x7F) as u8, 0, 0, 0]);
+        }
+        self.pids.get(&pid).cloned()
+    }
+
+    /// Handle one request; returns None when the ECU stays silent
+    pub fn handle(&mut self, request: &[u8]) -> Option<Vec<u8>> {
+        let (&mode, params) = request.split_first()?;
+        let mut response = vec![mode | POSITIVE_RESPONSE_OFFSET];
+        match mode {
+            MODE_CURRENT_DATA => {
+                // Up to six PIDs per request; unsupported ones are left out
+                for &pid in params.iter().take(6) {
+                    if let Some(data) = self.current_data(pid) {
+                        response.push(pid);
+                        response.extend(data);
+                    }
+                }
+                if response.len() == 1 {
+                    return None;
+                }
+            }
+            MODE_STORED_DTCS => {
+                response.push(self.dtcs.len() as u8);
+                response.extend(self.dtcs.iter().flatten());
+            }
+            MODE_CLEAR_DTCS => self.dtcs.clear(),
+            MODE_VEHICLE_INFO => {
+                let &pid = params.first()?;
+                response.push(pid);
+                match pid {
+                    0x00 => {
+                        let supported = (!self.vin.is_empty()).then_some(INFO_VIN);
+                        response.extend(supported_bitmap(0, supported.into_iter()));
+                    }
+                    INFO_VIN if !self.vin.is_empty() => {
+                        response.push(1);
+                        response.extend(self.vin.as_bytes());
+                    }
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        }
+        Some(response)
+    }
+}
+
+/// Serve OBD-II requests on a frame link until cancelled
+pub async fn run_obd_ecu<L: FrameLink>(
+    mut link: L,
+    config: ObdSimConfig,
+    mut cancel: watch::Receiver<bool>,
+) -> Result<(), BootCanError> {
+    let mut server = ObdServer::new(&config)?;
+    let isotp = IsoTpConfig {
+        tx_id: config.response_id,
+        rx_id: config.request_id,
+        is_extended: false,
+        // ISO 15765-4 requires 8-byte frames
+        padding: Some(0x55),
+        block_size: 0,
+        st_min: 0,
+        max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+    };
+
+    loop {
+        let frame = tokio::select! {
+            frame = link.recv(Duration::from_secs(1)) => frame?,
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+        let Some(frame) = frame else {
+            continue;
+        };
+        if frame.is_extended || (frame.id != OBD_FUNCTIONAL_ID && frame.id != config.request_id) {
+            continue;
+        }
+
+        // OBD requests always fit in a single frame
+        let Some(&pci) = frame.data.first() else {
+            continue;
+        };
+        let len = pci as usize;
+        if pci >> 4 != 0 || len == 0 || len + 1 > frame.data.len() {
+            continue;
+        }
+
+        if let Some(response) = server.handle(&frame.data[1..=len]) {
+            let mut transport = IsoTpLink::new(&mut link, isotp.clone(), DEFAULT_ISOTP_TIMEOUT);
+            if let Err(e) = transport.send(&response).await {
+                tracing::warn!("OBD simulator 0x{:X}: {}", config.response_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_data() {
+        let mut server = ObdServer::new(&ObdSimConfig::default()).unwrap();
+
+        // 820 rpm = 3280 / 4, 88 °C = 128 - 40
+        assert_eq!(server.handle(&[0x01, 0x0C]), Some(vec![0x41, 0x0C, 0x0C, 0xD0]));
+        assert_eq!(
+            server.handle(&[0x01, 0x05, 0x0D]),
+            Some(vec![0x41, 0x05, 0x80, 0x0D, 0x00])
+        );
+        assert_eq!(server.handle(&[0x01, 0x01]), Some(vec![0x41, 0x01, 0x81, 0, 0, 0]));
+
+        // 0x01, 0x04, 0x05, 0x0C, 0x0D, 0x0F, 0x11 and 0x21 range continues (0x2F)
+        assert_eq!(server.handle(&[0x01, 0x00]), Some(vec![0x41, 0x00, 0x98, 0x1A, 0x80, 0x01]));
+        assert_eq!(server.handle(&[0x01, 0x20]), Some(vec![0x41, 0x20, 0x00, 0x02, 0x00, 0x00]));
+        assert_eq!(server.handle(&[0x01, 0x42]), None);
+    }
+
+    #[test]
+    fn test_dtcs_and_vin() {
+        assert_eq!(parse_dtc("P0420").unwrap(), [0x04, 0x20]);
+        assert_eq!(parse_dtc("U0100").unwrap(), [0xC1, 0x00]);
+        assert!(parse_dtc("P4000").is_err());
+        assert!(parse_dtc("X0100").is_err());
+
+        let mut server = ObdServer::new(&ObdSimConfig::default()).unwrap();
+        assert_eq!(server.handle(&[0x03]), Some(vec![0x43, 1, 0x04, 0x20]));
+        assert_eq!(server.handle(&[0x04]), Some(vec![0x44]));
+        assert_eq!(server.handle(&[0x03]), Some(vec![0x43, 0]));
+
+        let vin = server.handle(&[0x09, 0x02]).unwrap();
+        assert_eq!(vin[..3], [0x49, 0x02, 0x01]);
+        assert_eq!(&vin[3..], b"1BOOTCAN0SIM00001");
+        assert_eq!(server.handle(&[0x09, 0x00]), Some(vec![0x49, 0x00, 0x40, 0, 0, 0]));
+
+        let config = ObdSimConfig {
+            vin: "SHORT".to_string(),
+            ..ObdSimConfig::default()
+        };
+        assert!(ObdServer::new(&config).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Reading Wireshark captures
+//!
+//! Loads CAN and CAN FD frames from pcap and pcapng files of SocketCAN
+//! interfaces (`LINKTYPE_CAN_SOCKETCAN`), as written by Wireshark, tcpdump or
+//! `candump`-style capture tools. Packets of other link types, error frames
+//! and CAN XL frames are skipped.
+
+use super::message::{CanFrame, ChannelName, Direction, FrameData, MAX_DATA_LEN};
+use crate::error::BootCanError;
+use std::collections::HashMap;
+
+/// Link type of SocketCAN frames, classic and FD
+pub const LINKTYPE_CAN_SOCKETCAN: u16 = 227;
+
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+const PCAPNG_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+const CAN_EFF_FLAG: u32 = 0x8000_0000;
+const CAN_RTR_FLAG: u32 = 0x4000_0000;
+const CAN_ERR_FLAG: u32 = 0x2000_0000;
+const CAN_EFF_MASK: u32 = 0x1FFF_FFFF;
+const CAN_SFF_MASK: u32 = 0x0000_07FF;
+/// Set in the FD flags byte of CAN XL frames, which have another layout
+const CANXL_XLF: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn u16(self, data: &[u8], offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(match self {
+            Self::Little => u16::from_le_bytes(bytes),
+            Self::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    fn u32(self, data: &[u8], offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match self {
+            Self::Little => u32::from_le_bytes(bytes),
+            Self::Big => u32::from_be_bytes(bytes),
+        })
+    }
+}
+
+/// Interface of a capture, with its frames' channel and timestamp unit
+struct Interface {
+    link_type: u16,
+    channel: ChannelName,
+    /// Seconds per timestamp tick
+    resolution: f64,
+}
+
+/// Frames of a pcap or pcapng capture, in file order
+///
+/// Timestamps are relative to the first packet. Frames of interface `n`
+/// (counting from 1) go to channel `bus_to_channel[n]`, else to the name the
+/// capture gives the interface, else to `channel_<n>`.
+pub fn read_capture(
+    bytes: &[u8],
+    bus_to_channel: Option<&HashMap<u8, String>>,
+) -> Result<Vec<CanFrame>, BootCanError> {
+    let magic = Endian::Little.u32(bytes, 0).ok_or_else(not_a_capture)?;
+    let mut frames = if magic == PCAPNG_SECTION_HEADER {
+        read_pcapng(bytes, bus_to_channel)?
+    } else {
+        read_pcap(bytes, bus_to_channel)?
+    };
+    if let Some(start) = frames.iter().map(|f| f.timestamp).reduce(f64::min) {
+        frames.iter_mut().for_each(|frame| frame.timestamp -= start);
+    }
+    Ok(frames)
+}
+
+fn not_a_capture() -> BootCanError {
+    BootCanError::Parse("Not a pcap or pcapng file".to_string())
+}
+
+fn channel_name(index: usize, name: Option<String>, bus_to_channel: Option<&HashMap<u8, String>>) -> String {
+    let bus = u8::try_from(index + 1).ok();
+    bus.and_then(|bus| bus_to_channel?.get(&bus).cloned())
+        .or(name)
+        .unwrap_or_else(|| format!("channel_{}", index + 1))
+}
+
+fn unsupported_link_type(link_type: u16) -> BootCanError {
+    BootCanError::Parse(format!(
+        "Link type {} is not a SocketCAN capture (expected {})",
+        link_type, LINKTYPE_CAN_SOCKETCAN
+    ))
+}
+
+fn read_pcap(bytes: &[u8], bus_to_channel: Option<&HashMap<u8, String>>) -> Result<Vec<CanFrame>, BootCanError> {
+    let (endian, resolution) = match (Endian::Little.u32(bytes, 0), Endian::Big.u32(bytes, 0)) {
+        (Some(PCAP_MAGIC_MICROS), _) => (Endian::Little, 1e-6),
+        (Some(PCAP_MAGIC_NANOS), _) => (Endian::Little, 1e-9),
+        (_, Some(PCAP_MAGIC_MICROS)) => (Endian::Big, 1e-6),
+        (_, Some(PCAP_MAGIC_NANOS)) => (Endian::Big, 1e-9),
+        _ => return Err(not_a_capture()),
+    };
+    // The upper bits of the link type field carry FCS information
+    let link_type = endian.u32(bytes, 20).ok_or_else(not_a_capture)? as u16;
+    if link_type != LINKTYPE_CAN_SOCKETCAN {
+        return Err(unsupported_link_type(link_type));
+    }
+    let channel = ChannelName::from(channel_name(0, None, bus_to_channel));
+
+    let mut frames = Vec::new();
+    let mut offset = 24;
+    while let (Some(seconds), Some(fraction), Some(captured)) = (
+        endian.u32(bytes, offset),
+        endian.u32(bytes, offset + 4),
+        endian.u32(bytes, offset + 8),
+    ) {
+        let start = offset + 16;
+        let Some(packet) = bytes.get(start..start + captured as usize) else {
+            tracing::warn!("pcap ends in a truncated packet");
+            break;
+        };
+        if let Some(mut frame) = parse_socketcan(packet) {
+            frame.timestamp = seconds as f64 + fraction as f64 * resolution;
+            frame.channel.clone_from(&channel);
+            frames.push(frame);
+        }
+        offset = start + captured as usize;
+    }
+    Ok(frames)
+}
+
+fn read_pcapng(bytes: &[u8], bus_to_channel: Option<&HashMap<u8, String>>) -> Result<Vec<CanFrame>, BootCanError> {
+    let mut frames = Vec::new();
+    let mut endian = Endian::Little;
+    let mut interfaces: Vec<Interface> = Vec::new();
+    // Interfaces are numbered across sections, like Wireshark does
+    let mut section_start = 0;
+    let mut offset = 0;
+
+    while bytes.len() >= offset + 12 {
+        let block_type = Endian::Little.u32(bytes, offset).ok_or_else(not_a_capture)?;
+        if block_type == PCAPNG_SECTION_HEADER {
+            endian = match Endian::Little.u32(bytes, offset + 8) {
+                Some(PCAPNG_BYTE_ORDER_MAGIC) => Endian::Little,
+                _ if Endian::Big.u32(bytes, offset + 8) == Some(PCAPNG_BYTE_ORDER_MAGIC) => Endian::Big,
+                _ => return Err(not_a_capture()),
+            };
+            section_start = interfaces.len();
+        }
+        let block_type = endian.u32(bytes, offset).ok_or_else(not_a_capture)?;
+        let length = endian.u32(bytes, offset + 4).ok_or_else(not_a_capture)? as usize;
+        if length < 12 || !length.is_multiple_of(4) {
+            return Err(BootCanError::Parse(format!("Invalid pcapng block length {} at {}", length, offset)));
+        }
+        let Some(body) = bytes.get(offset + 8..offset + length - 4) else {
+            tracing::warn!("pcapng ends in a truncated block");
+            break;
+        };
+
+        match block_type {
+            PCAPNG_INTERFACE_DESCRIPTION => {
+                let link_type = endian.u16(body, 0).ok_or_else(not_a_capture)?;
+                let options = parse_options(endian, body.get(8..).unwrap_or_default());
+                let name = options
+                    .get(&2)
+                    .map(|name| String::from_utf8_lossy(name).trim_end_matches('\0').to_string());
+                let resolution = match options.get(&9).and_then(|value| value.first()) {
+                    Some(&exponent) if exponent & 0x80 != 0 => 2f64.powi(-((exponent & 0x7F) as i32)),
+                    Some(&exponent) => 10f64.powi(-(exponent as i32)),
+                    None => 1e-6,
+                };
+                interfaces.push(Interface {
+                    link_type,
+                    channel: channel_name(interfaces.len(), name, bus_to_channel).into(),
+                    resolution,
+                });
+            }
+            PCAPNG_ENHANCED_PACKET => {
+                let field = |at| endian.u32(body, at).ok_or_else(not_a_capture);
+                let (interface_id, high, low, captured) = (field(0)?, field(4)?, field(8)?, field(12)? as usize);
+                let Some(interface) = interfaces.get(section_start + interface_id as usize) else {
+                    return Err(BootCanError::Parse(format!("Packet of unknown pcapng interface {}", interface_id)));
+                };
+                let packet = body.get(20..20 + captured).ok_or_else(not_a_capture)?;
+                if interface.link_type == LINKTYPE_CAN_SOCKETCAN {
+                    if let Some(mut frame) = parse_socketcan(packet) {
+                        let ticks = ((high as u64) << 32) | low as u64;
+                        frame.timestamp = ticks as f64 * interface.resolution;
+                        frame.channel.clone_from(&interface.channel);
+                        // Options follow the packet, padded to 32 bits
+                        let options_start = 20 + captured.div_ceil(4) * 4;
+                        let options = parse_options(endian, body.get(options_start..).unwrap_or_default());
+                        let flags = options.get(&2).and_then(|flags| endian.u32(flags, 0)).unwrap_or(0);
+                        if flags & 0b11 == 0b10 {
+                            frame.direction = Direction::Tx;
+                        }
+                        frames.push(frame);
+                    }
+                }
+            }
+            // Statistics, name resolution, simple and obsolete packet blocks
+            _ => {}
+        }
+        offset += length;
+    }
+
+    if !interfaces.iter().any(|interface| interface.link_type == LINKTYPE_CAN_SOCKETCAN) {
+        let link_type = interfaces.first().map_or(0, |interface| interface.link_type);
+        return Err(unsupported_link_type(link_type));
+    }
+    Ok(frames)
+}
+
+/// Options of a pcapng block by code; the first value wins for repeated codes
+fn parse_options(endian: Endian, mut data: &[u8]) -> HashMap<u16, &[u8]> {
+    let mut options = HashMap::new();
+    while let (Some(code), Some(length)) = (endian.u16(data, 0), endian.u16(data, 2)) {
+        let length = length as usize;
+        let Some(value) = data.get(4..4 + length) else {
+            break;
+        };
+        if code == 0 {
+            break;
+        }
+        options.entry(code).or_insert(value);
+        data = data.get(4 + length.div_ceil(4) * 4..).unwrap_or_default();
+    }
+    options
+}
+
+/// Frame in the SocketCAN layout, with the ID and flags in network byte order
+fn parse_socketcan(packet: &[u8]) -> Option<CanFrame> {
+    let id_flags = Endian::Big.u32(packet, 0)?;
+    let len = *packet.get(4)? as usize;
+    let fd_flags = *packet.get(5)?;
+    if id_flags & CAN_ERR_FLAG != 0 || fd_flags & CANXL_XLF != 0 || len > MAX_DATA_LEN {
+        return None;
+    }
+    let is_extended = id_flags & CAN_EFF_FLAG != 0;
+    let is_remote = id_flags & CAN_RTR_FLAG != 0;
+    let data = if is_remote { &[][..] } else { packet.get(8..8 + len)? };
+    Some(CanFrame {
+        id: id_flags & if is_extended { CAN_EFF_MASK } else { CAN_SFF_MASK },
+        is_extended,
+        is_remote,
+        dlc: len as u8,
+        data: FrameData::from_slice(data),
+        ..CanFrame::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SocketCAN frame as captured: ID and flags, length, FD flags, reserved, data
+    fn socketcan(id_flags: u32, fd_flags: u8, data: &[u8]) -> Vec<u8> {
+        let mut packet = id_flags.to_be_bytes().to_vec();
+        packet.extend_from_slice(&[data.len() as u8, fd_flags, 0, 0]);
+        packet.extend_from_slice(data);
+        packet
+    }
+
+    fn pcap_record(seconds: u32, micros: u32, packet: &[u8]) -> Vec<u8> {
+        let mut record = Vec::new();
+        for field in [seconds, micros, packet.len() as u32, packet.len() as u32] {
+            record.extend_from_slice(&field.to_le_bytes());
+        }
+        record.extend_from_slice(packet);
+        record
+    }
+
+    fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let length = (12 + body.len()) as u32;
+        let mut block = block_type.to_le_bytes().to_vec();
+        block.extend_from_slice(&length.to_le_bytes());
+        block.extend_from_slice(body);
+        block.extend_from_slice(&length.to_le_bytes());
+        block
+    }
+
+    fn pcapng_option(code: u16, value: &[u8]) -> Vec<u8> {
+        let mut option = code.to_le_bytes().to_vec();
+        option.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        option.extend_from_slice(value);
+        option.resize(4 + value.len().div_ceil(4) * 4, 0);
+        option
+    }
+
+    #[test]
+    fn test_read_pcap() {
+        let mut bytes = Vec::new();
+        for field in [PCAP_MAGIC_MICROS, 0x0004_0002, 0, 0, 65535, LINKTYPE_CAN_SOCKETCAN as u32] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        bytes.extend(pcap_record(100, 250_000, &socketcan(0x123, 0, &[1, 2, 3])));
+        bytes.extend(pcap_record(100, 750_000, &socketcan(0x18DA_F110 | CAN_EFF_FLAG, 0x05, &[0xAA; 12])));
+        bytes.extend(pcap_record(101, 0, &socketcan(0x20 | CAN_ERR_FLAG, 0, &[0; 8])));
+
+        let frames = read_capture(&bytes, None).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].id, frames[0].timestamp, frames[0].data.len()), (0x123, 0.0, 3));
+        assert_eq!(frames[0].channel, "channel_1");
+        assert!(frames[1].is_extended && frames[1].id == 0x18DA_F110);
+        assert_eq!((frames[1].dlc, frames[1].timestamp), (12, 0.5));
+
+        bytes[20] = 1;
+        assert!(read_capture(&bytes, None).is_err());
+        assert!(read_capture(b"Time,ID", None).is_err());
+    }
+
+    #[test]
+    fn test_read_pcapng() {
+        let mut section = PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes().to_vec();
+        section.extend_from_slice(&[1, 0, 0, 0]);
+        section.extend_from_slice(&(-1i64).to_le_bytes());
+        let mut interface = LINKTYPE_CAN_SOCKETCAN.to_le_bytes().to_vec();
+        interface.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        interface.extend(pcapng_option(2, b"can0"));
+        interface.extend(pcapng_option(9, &[9]));
+        interface.extend(pcapng_option(0, &[]));
+
+        let mut bytes = pcapng_block(PCAPNG_SECTION_HEADER, &section);
+        bytes.extend(pcapng_block(PCAPNG_INTERFACE_DESCRIPTION, &interface));
+        for (nanos, outbound) in [(2_000_000_000u64, false), (2_001_000_000, true)] {
+            let packet = socketcan(0x7E0, 0, &[0x02, 0x10, 0x03]);
+            let mut body = Vec::new();
+            for field in [0, (nanos >> 32) as u32, nanos as u32, packet.len() as u32, packet.len() as u32] {
+                body.extend_from_slice(&field.to_le_bytes());
+            }
+            body.extend_from_slice(&packet);
+            body.resize(body.len().div_ceil(4) * 4, 0);
+            if outbound {
+                body.extend(pcapng_option(2, &2u32.to_le_bytes()));
+            }
+            bytes.extend(pcapng_block(PCAPNG_ENHANCED_PACKET, &body));
+        }
+
+        let frames = read_capture(&bytes, None).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].channel.as_str(), frames[0].direction.as_str()), ("can0", "rx"));
+        assert_eq!(frames[1].direction, Direction::Tx);
+        assert!((frames[1].timestamp - 0.001).abs() < 1e-9);
+
+        let mapping = HashMap::from([(1, "bench".to_string())]);
+        assert_eq!(read_capture(&bytes, Some(&mapping)).unwrap()[0].channel, "bench");
+    }
+}
@@ -0,0 +1,319 @@
+//! WASM plugins for custom decoders and protocols
+//!
+//! A plugin is a WebAssembly module that receives frames and returns decoded
+//! records, events and optional frames to transmit. Interface:
+//!
+//! - exports `memory`, `alloc(len: i32) -> i32` and
+//!   `on_frame(ptr: i32, len: i32) -> i64`
+//! - `on_frame` gets the frame as JSON (same shape as "can-message" events)
+//!   and returns `(ptr << 32) | len` of a JSON [`PluginOutput`], or 0
+//! - optional export `plugin_name() -> i64` returns a name the same way
+//! - optional import `env.log(ptr: i32, len: i32)` writes to the app log
+//!
+//! Each call runs with a fuel budget, so a looping plugin cannot hang the app.
+
+use super::frame_link::FrameLink;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::watch;
+use wasmi::{AsContext, Caller, Config, Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+/// Instructions a plugin may execute per frame
+const FUEL_PER_CALL: u64 = 10_000_000;
+
+/// Decoded value produced by a plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginRecord {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+/// Frame a plugin asks to send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginTransmit {
+    pub id: u32,
+    #[serde(default)]
+    pub is_extended: bool,
+    pub data: Vec<u8>,
+}
+
+/// Result of a plugin for one frame
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginOutput {
+    #[serde(default)]
+    pub records: Vec<PluginRecord>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub transmit: Vec<PluginTransmit>,
+}
+
+impl PluginOutput {
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty() && self.events.is_empty() && self.transmit.is_empty()
+    }
+}
+
+/// Instantiated plugin module
+pub struct WasmPlugin {
+    name: String,
+    store: Store<()>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    on_frame: TypedFunc<(i32, i32), i64>,
+}
+
+/// Copy `len` bytes at `ptr` out of guest memory
+///
+/// The range is checked against the memory size first, so a bogus length
+/// from the plugin cannot make the host allocate.
+fn read_guest(memory: &Memory, store: impl AsContext, ptr: i32, len: i32) -> Result<Vec<u8>, BootCanError> {
+    let data = memory.data(store.as_context());
+    let start = ptr as u32 as usize;
+    start
+        .checked_add(len.max(0) as usize)
+        .and_then(|end| data.get(start..end))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            BootCanError::Protocol(format!(
+                "Plugin returned an invalid buffer: {} bytes at {} outside its {} byte memory",
+                len,
+                start,
+                data.len()
+            ))
+        })
+}
+
+fn unpack(packed: i64) -> (i32, i32) {
+    ((packed >> 32) as i32, packed as i32)
+}
+
+impl WasmPlugin {
+    pub fn load(path: &Path) -> Result<Self, BootCanError> {
+        let wasm = std::fs::read(path).map_err(|e| BootCanError::Io(format!("Failed to read plugin: {}", e)))?;
+        let fallback = path.file_stem().and_then(|s| s.to_str()).unwrap_or("plugin");
+        Self::from_bytes(&wasm, fallback)
+    }
+
+    /// Instantiate a module; `fallback_name` is used if it has no `plugin_name` export
+    pub fn from_bytes(wasm: &[u8], fallback_name: &str) -> Result<Self, BootCanError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm)
+            .map_err(|e| BootCanError::InvalidInput(format!("Invalid WASM module: {}", e)))?;
+
+        let mut store = Store::new(&engine, ());
+        let mut linker = Linker::<()>::new(&engine);
+        linker
+            .func_wrap("env", "log", |caller: Caller<'_, ()>, ptr: i32, len: i32| {
+                let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) else {
+                    return;
+                };
+                if let Ok(bytes) = read_guest(&memory, &caller, ptr, len) {
+                    tracing::info!("[plugin] {}", String::from_utf8_lossy(&bytes));
+                }
+            })
+            .map_err(|e| BootCanError::Protocol(e.to_string()))?;
+
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| BootCanError::Protocol(e.to_string()))?;
+        let instance: Instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| BootCanError::Protocol(format!("Failed to instantiate plugin: {}", e)))?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| BootCanError::Protocol("Plugin does not export memory".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| BootCanError::Protocol(format!("Plugin alloc export: {}", e)))?;
+        let on_frame = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "on_frame")
+            .map_err(|e| BootCanError::Protocol(format!("Plugin on_frame export: {}", e)))?;
+
+        let mut plugin = Self {
+            name: fallback_name.to_string(),
+            store,
+            memory,
+            alloc,
+            on_frame,
+        };
+        if let Ok(name_fn) = instance.get_typed_func::<(), i64>(&plugin.store, "plugin_name") {
+            plugin.store.set_fuel(FUEL_PER_CALL).map_err(|e| BootCanError::Protocol(e.to_string()))?;
+            let packed = name_fn
+                .call(&mut plugin.store, ())
+                .map_err(|e| BootCanError::Protocol(e.to_string()))?;
+            let (ptr, len) = unpack(packed);
+            let bytes = read_guest(&plugin.memory, &plugin.store, ptr, len)?;
+            plugin.name = String::from_utf8_lossy(&bytes).into_owned();
+        }
+        Ok(plugin)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Pass one frame to the plugin
+    pub fn process(&mut self, frame: &CanFrame) -> Result<PluginOutput, BootCanError> {
+        let input = serde_json::to_vec(frame).map_err(|e| BootCanError::Protocol(e.to_string()))?;
+        self.store.set_fuel(FUEL_PER_CALL).map_err(|e| BootCanError::Protocol(e.to_string()))?;
+
+        let len = input.len() as i32;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|e| BootCanError::Protocol(format!("Plugin {} alloc failed: {}", self.name, e)))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, &input)
+            .map_err(|e| BootCanError::Protocol(format!("Plugin {} returned an invalid buffer: {}", self.name, e)))?;
+
+        let packed = self
+            .on_frame
+            .call(&mut self.store, (ptr, len))
+            .map_err(|e| BootCanError::Protocol(format!("Plugin {} failed: {}", self.name, e)))?;
+        let (out_ptr, out_len) = unpack(packed);
+        if out_len <= 0 {
+            return Ok(PluginOutput::default());
+        }
+        let output = read_guest(&self.memory, &self.store, out_ptr, out_len)?;
+        serde_json::from_slice(&output)
+            .map_err(|e| BootCanError::Protocol(format!("Plugin {} returned invalid output: {}", self.name, e)))
+    }
+}
+
+/// Feed frames received on `link` to a plugin until cancelled
+///
+/// Non-empty outputs go to `on_output`; transmit requests are only honoured
+/// with `allow_transmit`.
+pub async fn run_plugin<L: FrameLink>(
+    mut link: L,
+    mut plugin: WasmPlugin,
+    allow_transmit: bool,
+    mut cancel: watch::Receiver<bool>,
+    mut on_output: impl FnMut(&CanFrame, &PluginOutput) + Send,
+) -> Result<(), BootCanError> {
+    loop {
+        let frame = tokio::select! {
+            frame = link.recv(Duration::from_secs(1)) => frame?,
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+        let Some(frame) = frame else {
+            continue;
+        };
+
+        let output = match plugin.process(&frame) {
+            Ok(output) => output,
+            Err(e) => {
+                tracing::warn!("{}", e);
+                continue;
+            }
+        };
+        if output.is_empty() {
+            continue;
+        }
+        on_output(&frame, &output);
+
+        if allow_transmit {
+            for tx in &output.transmit {
+                let frame = if tx.is_extended {
+                    CanFrame::new_extended(tx.id, &tx.data)
+                } else {
+                    CanFrame::new(tx.id, &tx.data)
+                };
+                link.send(frame).await?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plugin answering every frame with a fixed record and transmit request
+    const ECHO: &str = r#"
+(module
+  (import "env" "log" (func $log (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 16) "demo")
+  (data (i32.const 32) "{\"records\":[{\"name\":\"rpm\",\"value\":1500}],\"transmit\":[{\"id\":1793,\"data\":[1]}]}")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "plugin_name") (result i64) (i64.const 0x0000001000000004))
+  (func (export "on_frame") (param i32 i32) (result i64)
+    (call $log (i32.const 16) (i32.const 4))
+    ;; Only answer frames with ID 0x100 (JSON starts with {"id":256,)
+    (if (result i64) (i32.eq (i32.load8_u (i32.const 1030)) (i32.const 50))
+      (then (i64.const 0x000000200000004d))
+      (else (i64.const 0))))
+)
+"#;
+
+    #[test]
+    fn test_plugin_output() {
+        let wasm = wat::parse_str(ECHO).unwrap();
+        let mut plugin = WasmPlugin::from_bytes(&wasm, "fallback").unwrap();
+        assert_eq!(plugin.name(), "demo");
+
+        let output = plugin.process(&CanFrame::new(0x100, &[1, 2])).unwrap();
+        assert_eq!(output.records[0].name, "rpm");
+        assert_eq!(output.records[0].value, Some(1500.0));
+        assert_eq!(output.transmit[0].id, 0x701);
+
+        assert!(plugin.process(&CanFrame::new(0x300, &[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_runaway_plugin() {
+        let wasm = wat::parse_str(
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "on_frame") (param i32 i32) (result i64)
+                   (loop $spin (br $spin))
+                   (i64.const 0)))"#,
+        )
+        .unwrap();
+        let mut plugin = WasmPlugin::from_bytes(&wasm, "spin").unwrap();
+        assert_eq!(plugin.name(), "spin");
+        assert!(plugin.process(&CanFrame::new(0x100, &[])).is_err());
+        // The plugin stays usable after running out of fuel
+        assert!(plugin.process(&CanFrame::new(0x100, &[])).is_err());
+
+        let missing = wat::parse_str(r#"(module (memory (export "memory") 1))"#).unwrap();
+        assert!(WasmPlugin::from_bytes(&missing, "empty").is_err());
+    }
+
+    #[test]
+    fn test_output_outside_memory() {
+        // Claims 2 GiB of output at the end of its single 64 KiB page
+        let wasm = wat::parse_str(
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "on_frame") (param i32 i32) (result i64)
+                   (i64.const 0x0000fff07fffffff)))"#,
+        )
+        .unwrap();
+        let mut plugin = WasmPlugin::from_bytes(&wasm, "liar").unwrap();
+        let error = plugin.process(&CanFrame::new(0x100, &[])).unwrap_err();
+        assert!(error.message().contains("outside its 65536 byte memory"));
+    }
+}
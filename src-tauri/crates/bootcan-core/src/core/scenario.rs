@@ -0,0 +1,431 @@
+//! Scripted test scenarios
+//!
+//! A scenario is a JSON or YAML list of steps (connect, send, wait for a
+//! frame, assert a signal value, logging). Steps run in order and each one
+//! is reported as passed, failed or skipped, so a scenario can serve as a
+//! lightweight HIL test. Values can refer to project variables as `${NAME}`.
+
+use super::filter::FilterSet;
+use super::message::CanFrame;
+use super::variables::Variables;
+use crate::error::BootCanError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// One scenario action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum ScenarioStep {
+    #[serde(rename_all = "camelCase")]
+    Connect {
+        channel_id: Option<String>,
+        interface_id: String,
+        bitrate: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Disconnect { channel_id: Option<String> },
+    #[serde(rename_all = "camelCase")]
+    Send {
+        channel_id: Option<String>,
+        id: u32,
+        #[serde(default)]
+        is_extended: bool,
+        data: Vec<u8>,
+    },
+    /// Pause for a fixed time
+    Wait { ms: u64 },
+    /// Pass when a received frame matches `filter` within the timeout
+    #[serde(rename_all = "camelCase")]
+    WaitForFrame {
+        channel_id: Option<String>,
+        filter: FilterSet,
+        timeout_ms: u64,
+    },
+    /// Pass when a received frame carries the signal within `tolerance` of `expected`
+    #[serde(rename_all = "camelCase")]
+    AssertSignal {
+        channel_id: Option<String>,
+        message_id: u32,
+        signal: String,
+        expected: f64,
+        #[serde(default)]
+        tolerance: f64,
+        timeout_ms: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    StartLog { file_path: String, format: String },
+    StopLog,
+}
+
+/// Scenario description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    pub name: String,
+    /// Channel used by steps that do not name one
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    /// Keep running after a failed step instead of skipping the rest
+    #[serde(default)]
+    pub continue_on_failure: bool,
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    /// Load a scenario from a .json, .yaml or .yml file, expanding `variables`
+    pub fn load(path: &Path, variables: &Variables) -> Result<Self, BootCanError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| BootCanError::Io(format!("Failed to read scenario file: {}", e)))?;
+        let value: serde_json::Value = match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("yaml") | Some("yml") => {
+                serde_yaml::from_str(&content).map_err(|e| BootCanError::Parse(format!("Invalid scenario: {}", e)))?
+            }
+            _ => serde_json::from_str(&content).map_err(|e| BootCanError::Parse(format!("Invalid scenario: {}", e)))?,
+        };
+        variables.resolve(&value).map_err(|e| BootCanError::Parse(format!("Invalid scenario: {}", e)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Outcome of one step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepResult {
+    pub index: usize,
+    pub action: String,
+    pub status: StepStatus,
+    pub message: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Outcome of a scenario run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioReport {
+    pub name: String,
+    pub passed: bool,
+    pub steps: Vec<StepResult>,
+}
+
+/// Environment the scenario steps act on
+#[async_trait]
+pub trait ScenarioHost: Send {
+    async fn connect(&mut self, channel_id: &str, interface_id: &str, bitrate: u32) -> Result<(), BootCanError>;
+
+    async fn disconnect(&mut self, channel_id: &str) -> Result<(), BootCanError>;
+
+    async fn send(&mut self, channel_id: &str, frame: CanFrame) -> Result<(), BootCanError>;
+
+    /// Next frame received on the channel (None on timeout)
+    async fn recv(&mut self, channel_id: &str, timeout: Duration) -> Result<Option<CanFrame>, BootCanError>;
+
+    /// Physical value of a signal in a frame, using the channel's DBC
+    fn decode_signal(&self, channel_id: &str, frame: &CanFrame, signal: &str) -> Option<f64>;
+
+    async fn start_log(&mut self, file_path: &str, format: &str) -> Result<(), BootCanError>;
+
+    async fn stop_log(&mut self) -> Result<(), BootCanError>;
+}
+
+impl ScenarioStep {
+    fn action(&self) -> &'static str {
+        match self {
+            Self::Connect { .. } => "connect",
+            Self::Disconnect { .. } => "disconnect",
+            Self::Send { .. } => "send",
+            Self::Wait { .. } => "wait",
+            Self::WaitForFrame { .. } => "waitForFrame",
+            Self::AssertSignal { .. } => "assertSignal",
+            Self::StartLog { .. } => "startLog",
+            Self::StopLog => "stopLog",
+        }
+    }
+
+    /// Run the step; Ok carries an optional detail message, Err the failure reason
+    async fn execute<'a>(
+        &'a self,
+        host: &mut dyn ScenarioHost,
+        default_channel: Option<&'a str>,
+    ) -> Result<Option<String>, BootCanError> {
+        let channel = |channel_id: &'a Option<String>| resolve_channel(channel_id, default_channel);
+
+        match self {
+            Self::Connect {
+                channel_id,
+                interface_id,
+                bitrate,
+            } => host.connect(channel(channel_id)?, interface_id, *bitrate).await.map(|_| None),
+            Self::Disconnect { channel_id } => host.disconnect(channel(channel_id)?).await.map(|_| None),
+            Self::Send {
+                channel_id,
+                id,
+                is_extended,
+                data,
+            } => {
+                let frame = if *is_extended {
+                    CanFrame::new_extended(*id, data)
+                } else {
+                    CanFrame::new(*id, data)
+                };
+                host.send(channel(channel_id)?, frame).await.map(|_| None)
+            }
+            Self::Wait { ms } => {
+                tokio::time::sleep(Duration::from_millis(*ms)).await;
+                Ok(None)
+            }
+            Self::WaitForFrame {
+                channel_id,
+                filter,
+                timeout_ms,
+            } => {
+                let channel = channel(channel_id)?;
+                let deadline = Instant::now() + Duration::from_millis(*timeout_ms);
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    match host.recv(channel, remaining).await? {
+                        Some(frame) if filter.matches(&frame) => {
+                            return Ok(Some(format!("Received 0x{:X}", frame.id)));
+                        }
+                        Some(_) => continue,
+                        None => {
+                            return Err(BootCanError::Timeout(format!("No matching frame within {} ms", timeout_ms)))
+                        }
+                    }
+                }
+            }
+            Self::AssertSignal {
+                channel_id,
+                message_id,
+                signal,
+                expected,
+                tolerance,
+                timeout_ms,
+            } => {
+                let channel = channel(channel_id)?;
+                let deadline = Instant::now() + Duration::from_millis(*timeout_ms);
+                let mut last = None;
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    let Some(frame) = host.recv(channel, remaining).await? else {
+                        return Err(BootCanError::Timeout(match last {
+                            Some(value) => format!("{} = {} (expected {} ± {})", signal, value, expected, tolerance),
+                            None => format!("Message 0x{:X} not received within {} ms", message_id, timeout_ms),
+                        }));
+                    };
+                    if frame.id != *message_id {
+                        continue;
+                    }
+                    let value = host
+                        .decode_signal(channel, &frame, signal)
+                        .ok_or_else(|| {
+                            let reason = format!("Signal {} cannot be decoded from 0x{:X}", signal, message_id);
+                            BootCanError::NotFound(reason)
+                        })?;
+                    if (value - expected).abs() <= *tolerance {
+                        return Ok(Some(format!("{} = {}", signal, value)));
+                    }
+                    last = Some(value);
+                }
+            }
+            Self::StartLog { file_path, format } => host.start_log(file_path, format).await.map(|_| None),
+            Self::StopLog => host.stop_log().await.map(|_| None),
+        }
+    }
+}
+
+fn resolve_channel<'a>(
+    channel_id: &'a Option<String>,
+    default_channel: Option<&'a str>,
+) -> Result<&'a str, BootCanError> {
+    channel_id
+        .as_deref()
+        .or(default_channel)
+        .ok_or_else(|| BootCanError::InvalidInput("No channel given for step".to_string()))
+}
+
+/// Execute a scenario, reporting each step result to `on_step` as it completes
+pub async fn run_scenario(
+    scenario: &Scenario,
+    host: &mut dyn ScenarioHost,
+    mut on_step: impl FnMut(&StepResult) + Send,
+) -> ScenarioReport {
+    let mut steps = Vec::with_capacity(scenario.steps.len());
+    let mut failed = false;
+
+    for (index, step) in scenario.steps.iter().enumerate() {
+        let started = Instant::now();
+        let (status, message) = if failed && !scenario.continue_on_failure {
+            (StepStatus::Skipped, None)
+        } else {
+            match step.execute(host, scenario.channel_id.as_deref()).await {
+                Ok(message) => (StepStatus::Passed, message),
+                Err(e) => {
+                    failed = true;
+                    (StepStatus::Failed, Some(e.to_string()))
+                }
+            }
+        };
+
+        let result = StepResult {
+            index,
+            action: step.action().to_string(),
+            status,
+            message,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        on_step(&result);
+        steps.push(result);
+    }
+
+    ScenarioReport {
+        name: scenario.name.clone(),
+        passed: !failed,
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Host answering every sent frame with the next queued response
+    #[derive(Default)]
+    struct MockHost {
+        responses: VecDeque<CanFrame>,
+        inbox: VecDeque<CanFrame>,
+        sent: Vec<CanFrame>,
+    }
+
+    #[async_trait]
+    impl ScenarioHost for MockHost {
+        async fn connect(&mut self, _: &str, _: &str, _: u32) -> Result<(), BootCanError> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self, _: &str) -> Result<(), BootCanError> {
+            Ok(())
+        }
+
+        async fn send(&mut self, _: &str, frame: CanFrame) -> Result<(), BootCanError> {
+            self.sent.push(frame);
+            self.inbox.extend(self.responses.pop_front());
+            Ok(())
+        }
+
+        async fn recv(&mut self, _: &str, _: Duration) -> Result<Option<CanFrame>, BootCanError> {
+            Ok(self.inbox.pop_front())
+        }
+
+        fn decode_signal(&self, _: &str, frame: &CanFrame, _: &str) -> Option<f64> {
+            frame.data.first().map(|b| *b as f64 * 0.5)
+        }
+
+        async fn start_log(&mut self, _: &str, _: &str) -> Result<(), BootCanError> {
+            Err(BootCanError::Other("Logging unavailable".to_string()))
+        }
+
+        async fn stop_log(&mut self) -> Result<(), BootCanError> {
+            Ok(())
+        }
+    }
+
+    const SCENARIO: &str = r#"
+name: Throttle request
+channelId: can0
+steps:
+  - action: connect
+    interfaceId: virtual0
+    bitrate: 500000
+  - action: send
+    id: 0x7E0
+    data: [2, 1, 0]
+  - action: waitForFrame
+    filter:
+      rules:
+        - IdExact: 0x7E8
+      logic: And
+    timeoutMs: 100
+  - action: send
+    id: 0x7E0
+    data: [2, 1, 1]
+  - action: assertSignal
+    messageId: 0x100
+    signal: Throttle
+    expected: 40
+    tolerance: 1
+    timeoutMs: 100
+"#;
+
+    #[tokio::test]
+    async fn test_passing_scenario() {
+        let scenario: Scenario = serde_yaml::from_str(SCENARIO).unwrap();
+        let mut host = MockHost {
+            responses: [CanFrame::new(0x7E8, &[1]), CanFrame::new(0x100, &[81])].into(),
+            ..Default::default()
+        };
+
+        let mut reported = 0;
+        let report = run_scenario(&scenario, &mut host, |_| reported += 1).await;
+        assert!(report.passed, "{:?}", report.steps);
+        assert_eq!(reported, 5);
+        assert_eq!(host.sent.len(), 2);
+        assert_eq!(report.steps[4].message.as_deref(), Some("Throttle = 40.5"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_skips_remaining_steps() {
+        let mut scenario: Scenario = serde_yaml::from_str(SCENARIO).unwrap();
+        scenario.steps.push(ScenarioStep::StopLog);
+        // No response to the first request
+        let mut host = MockHost::default();
+
+        let report = run_scenario(&scenario, &mut host, |_| {}).await;
+        assert!(!report.passed);
+        let statuses: Vec<StepStatus> = report.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                StepStatus::Passed,
+                StepStatus::Passed,
+                StepStatus::Failed,
+                StepStatus::Skipped,
+                StepStatus::Skipped,
+                StepStatus::Skipped
+            ]
+        );
+
+        scenario.continue_on_failure = true;
+        scenario.steps = vec![ScenarioStep::StartLog {
+            file_path: "out.csv".to_string(),
+            format: "csv".to_string(),
+        }];
+        let report = run_scenario(&scenario, &mut host, |_| {}).await;
+        assert_eq!(report.steps[0].message.as_deref(), Some("Logging unavailable"));
+    }
+
+    #[test]
+    fn test_load_expands_variables() {
+        let path = std::env::temp_dir().join(format!("bootcan-scenario-{}.yaml", std::process::id()));
+        std::fs::write(&path, "name: ${ECU} session\nsteps:\n  - action: send\n    id: ${TESTER_ID}\n    data: [2, 0x10, 3]\n").unwrap();
+
+        let mut variables = Variables::default();
+        variables.set("TESTER_ID", "0x7E0").unwrap();
+        assert!(Scenario::load(&path, &variables).unwrap_err().message().contains("ECU"));
+        variables.set("ECU", "Engine").unwrap();
+        let scenario = Scenario::load(&path, &variables).unwrap();
+        assert_eq!(scenario.name, "Engine session");
+        assert!(matches!(scenario.steps[0], ScenarioStep::Send { id: 0x7E0, .. }));
+        let _ = std::fs::remove_file(&path);
+    }
+}
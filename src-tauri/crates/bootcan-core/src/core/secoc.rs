@@ -0,0 +1,344 @@
+//! AUTOSAR SecOC authentication for configured message IDs
+//!
+//! Secured PDUs are laid out as authentic payload, truncated freshness value
+//! and truncated AES-128-CMAC, packed MSB first. The MAC is computed over
+//! data ID, authentic payload and the full freshness value. Freshness is a
+//! monotonic counter per channel and message ID.
+
+use super::message::{CanFrame, ChannelName};
+use crate::error::BootCanError;
+use aes::Aes128;
+use cmac::{Cmac, Mac};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// SecOC parameters for one message ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecOcConfig {
+    pub message_id: u32,
+    /// Data ID included in the MAC input
+    pub data_id: u16,
+    /// AES-128 key as 32 hex characters
+    pub key: String,
+    /// Length of the authentic (unsecured) payload in bytes
+    pub authentic_length: usize,
+    /// Length of the full freshness value in bits (at most 64)
+    pub freshness_bits: u8,
+    /// Number of freshness bits transmitted in the frame
+    pub truncated_freshness_bits: u8,
+    /// Number of MAC bits transmitted in the frame (at most 128)
+    pub mac_bits: u8,
+}
+
+impl SecOcConfig {
+    fn secured_length(&self) -> usize {
+        let tail_bits = self.truncated_freshness_bits as usize + self.mac_bits as usize;
+        self.authentic_length + tail_bits.div_ceil(8)
+    }
+
+    fn validate(&self) -> Result<[u8; 16], BootCanError> {
+        if self.freshness_bits > 64 || self.truncated_freshness_bits > self.freshness_bits {
+            return Err(BootCanError::InvalidInput(format!(
+                "SecOC 0x{:X}: invalid freshness lengths {}/{}",
+                self.message_id, self.truncated_freshness_bits, self.freshness_bits
+            )));
+        }
+        if self.mac_bits == 0 || self.mac_bits > 128 {
+            return Err(BootCanError::InvalidInput(format!(
+                "SecOC 0x{:X}: invalid MAC length {}", self.message_id, self.mac_bits
+            )));
+        }
+        if self.secured_length() > 64 {
+            return Err(BootCanError::InvalidInput(format!(
+                "SecOC 0x{:X}: secured PDU of {} bytes does not fit a CAN frame",
+                self.message_id,
+                self.secured_length()
+            )));
+        }
+        parse_key(&self.key).map_err(|e| e.context(format!("SecOC 0x{:X}", self.message_id)))
+    }
+}
+
+/// Frame whose authenticator could not be verified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecOcFailure {
+    pub channel: String,
+    pub message_id: u32,
+    pub timestamp: f64,
+    pub reason: String,
+}
+
+struct Profile {
+    config: SecOcConfig,
+    key: [u8; 16],
+}
+
+/// Generates and verifies secured PDUs for all configured message IDs
+#[derive(Default)]
+pub struct SecOcManager {
+    profiles: HashMap<u32, Profile>,
+    /// Next freshness value to send per (channel, message ID)
+    tx_freshness: HashMap<(String, u32), u64>,
+    /// Last accepted freshness value per (channel, message ID)
+    rx_freshness: HashMap<(ChannelName, u32), u64>,
+}
+
+impl SecOcManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace all configurations and reset freshness counters
+    pub fn set_configs(&mut self, configs: Vec<SecOcConfig>) -> Result<(), BootCanError> {
+        let mut profiles = HashMap::new();
+        for config in configs {
+            let key = config.validate()?;
+            profiles.insert(config.message_id, Profile { config, key });
+        }
+        self.profiles = profiles;
+        self.tx_freshness.clear();
+        self.rx_freshness.clear();
+        Ok(())
+    }
+
+    /// Get all configurations sorted by message ID
+    pub fn configs(&self) -> Vec<SecOcConfig> {
+        let mut configs: Vec<SecOcConfig> = self.profiles.values().map(|p| p.config.clone()).collect();
+        configs.sort_by_key(|c| c.message_id);
+        configs
+    }
+
+    /// Append freshness value and MAC to a frame about to be sent; frames with
+    /// unconfigured IDs are left untouched
+    pub fn protect(&mut self, channel: &str, frame: &mut CanFrame) -> Result<(), BootCanError> {
+        let Some(profile) = self.profiles.get(&frame.id) else {
+            return Ok(());
+        };
+        let config = &profile.config;
+        if frame.data.len() != config.authentic_length {
+            return Err(BootCanError::InvalidInput(format!(
+                "SecOC 0x{:X}: expected {} payload bytes, got {}",
+                frame.id,
+                config.authentic_length,
+                frame.data.len()
+            )));
+        }
+
+        let counter = self
+            .tx_freshness
+            .entry((channel.to_string(), frame.id))
+            .or_insert(1);
+        let freshness = *counter & mask(config.freshness_bits);
+        *counter += 1;
+
+        let mac = compute_mac(&profile.key, config, &frame.data, freshness);
+        let mut secured = frame.data;
+        secured.resize(config.secured_length(), 0);
+
+        let mut bit = config.authentic_length * 8;
+        write_bits(&mut secured, &mut bit, freshness, config.truncated_freshness_bits);
+        for (i, byte) in mac.iter().enumerate() {
+            let remaining = (config.mac_bits as usize).saturating_sub(i * 8).min(8) as u8;
+            if remaining == 0 {
+                break;
+            }
+            write_bits(&mut secured, &mut bit, (*byte >> (8 - remaining)) as u64, remaining);
+        }
+
+        frame.dlc = secured.len() as u8;
+        frame.data = secured;
+        Ok(())
+    }
+
+    /// Verify a received frame; returns None when the ID is not configured or
+    /// the authenticator is valid
+    pub fn verify(&mut self, frame: &CanFrame) -> Option<SecOcFailure> {
+        let profile = self.profiles.get(&frame.id)?;
+        let config = &profile.config;
+        let failure = |reason: String| SecOcFailure {
+            channel: frame.channel.to_string(),
+            message_id: frame.id,
+            timestamp: frame.timestamp,
+            reason,
+        };
+
+        if frame.data.len() < config.secured_length() {
+            return Some(failure(format!(
+                "Secured PDU too short ({} of {} bytes)",
+                frame.data.len(),
+                config.secured_length()
+            )));
+        }
+
+        let authentic = &frame.data[..config.authentic_length];
+        let mut bit = config.authentic_length * 8;
+        let truncated = read_bits(&frame.data, &mut bit, config.truncated_freshness_bits);
+        let received_mac: Vec<u64> = (0..config.mac_bits.div_ceil(8))
+            .map(|i| read_bits(&frame.data, &mut bit, (config.mac_bits - i * 8).min(8)))
+            .collect();
+
+        let key = (frame.channel.clone(), frame.id);
+        let latest = self.rx_freshness.get(&key).copied().unwrap_or(0);
+        let freshness = reconstruct_freshness(
+            latest,
+            truncated,
+            config.truncated_freshness_bits,
+            config.freshness_bits,
+        );
+        if freshness <= latest && self.rx_freshness.contains_key(&key) {
+            return Some(failure(format!("Stale freshness value {}", freshness)));
+        }
+
+        let mac = compute_mac(&profile.key, config, authentic, freshness);
+        let matches = received_mac.iter().enumerate().all(|(i, received)| {
+            let bits = (config.mac_bits as usize - i * 8).min(8) as u8;
+            (mac[i] >> (8 - bits)) as u64 == *received
+        });
+        if !matches {
+            return Some(failure(format!("MAC mismatch (freshness {})", freshness)));
+        }
+
+        self.rx_freshness.insert(key, freshness);
+        None
+    }
+}
+
+/// Rebuild the full freshness value from its transmitted low bits
+fn reconstruct_freshness(latest: u64, truncated: u64, truncated_bits: u8, full_bits: u8) -> u64 {
+    if truncated_bits >= full_bits {
+        return truncated;
+    }
+    let low_mask = mask(truncated_bits);
+    let candidate = (latest & !low_mask) | truncated;
+    if truncated > latest & low_mask {
+        candidate
+    } else {
+        // Low bits wrapped around since the last accepted value
+        candidate.wrapping_add(low_mask.wrapping_add(1)) & mask(full_bits)
+    }
+}
+
+fn compute_mac(key: &[u8; 16], config: &SecOcConfig, authentic: &[u8], freshness: u64) -> [u8; 16] {
+    let freshness_bytes = (config.freshness_bits as usize).div_ceil(8);
+    let mut input = Vec::with_capacity(2 + authentic.len() + freshness_bytes);
+    input.extend_from_slice(&config.data_id.to_be_bytes());
+    input.extend_from_slice(authentic);
+    input.extend_from_slice(&freshness.to_be_bytes()[8 - freshness_bytes..]);
+    aes_cmac(key, &input)
+}
+
+fn aes_cmac(key: &[u8; 16], message: &[u8]) -> [u8; 16] {
+    let mut mac = <Cmac<Aes128> as Mac>::new_from_slice(key).expect("AES-128 key length is fixed");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+fn mask(bits: u8) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
+
+/// Write the low `len` bits of `value` MSB first, starting at bit position `bit`
+fn write_bits(data: &mut [u8], bit: &mut usize, value: u64, len: u8) {
+    for i in (0..len).rev() {
+        if (value >> i) & 1 != 0 {
+            data[*bit / 8] |= 0x80 >> (*bit % 8);
+        }
+        *bit += 1;
+    }
+}
+
+/// Read `len` bits MSB first, starting at bit position `bit`
+fn read_bits(data: &[u8], bit: &mut usize, len: u8) -> u64 {
+    let mut value = 0u64;
+    for _ in 0..len {
+        let set = data[*bit / 8] & (0x80 >> (*bit % 8)) != 0;
+        value = (value << 1) | set as u64;
+        *bit += 1;
+    }
+    value
+}
+
+fn parse_key(key: &str) -> Result<[u8; 16], BootCanError> {
+    let key = key.trim();
+    if key.len() != 32 {
+        return Err(BootCanError::InvalidInput("key must be 32 hex characters".to_string()));
+    }
+    let mut bytes = [0u8; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[i * 2..i * 2 + 2], 16)
+            .map_err(|_| BootCanError::InvalidInput("key is not valid hex".to_string()))?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+
+    fn config() -> SecOcConfig {
+        SecOcConfig {
+            message_id: 0x123,
+            data_id: 0x0042,
+            key: KEY.to_string(),
+            authentic_length: 4,
+            freshness_bits: 32,
+            truncated_freshness_bits: 4,
+            mac_bits: 28,
+        }
+    }
+
+    #[test]
+    fn test_aes_cmac_rfc4493() {
+        let key = parse_key(KEY).unwrap();
+        let message = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        ];
+        assert_eq!(
+            aes_cmac(&key, &message),
+            [0x07, 0x0a, 0x16, 0xb4, 0x6b, 0x4d, 0x41, 0x44, 0xf7, 0x9b, 0xdd, 0x9d, 0xd0, 0x4a, 0x28, 0x7c]
+        );
+    }
+
+    #[test]
+    fn test_protect_and_verify() {
+        let mut tx = SecOcManager::new();
+        let mut rx = SecOcManager::new();
+        tx.set_configs(vec![config()]).unwrap();
+        rx.set_configs(vec![config()]).unwrap();
+
+        // Run past a wrap of the 4 transmitted freshness bits
+        for _ in 0..20 {
+            let mut frame = CanFrame::new(0x123, &[1, 2, 3, 4]).as_received("can0", 0.0);
+            tx.protect("can0", &mut frame).unwrap();
+            assert_eq!(frame.data.len(), 8);
+            assert!(rx.verify(&frame).is_none());
+        }
+
+        let mut frame = CanFrame::new(0x123, &[1, 2, 3, 4]).as_received("can0", 0.0);
+        tx.protect("can0", &mut frame).unwrap();
+        let mut tampered = frame.clone();
+        tampered.data[0] ^= 0x01;
+        assert!(rx.verify(&tampered).unwrap().reason.contains("MAC mismatch"));
+        assert!(rx.verify(&frame).is_none());
+        // Replaying the same frame must fail
+        assert!(rx.verify(&frame).is_some());
+    }
+
+    #[test]
+    fn test_unconfigured_id_untouched() {
+        let mut manager = SecOcManager::new();
+        manager.set_configs(vec![config()]).unwrap();
+        let mut frame = CanFrame::new(0x200, &[1, 2]);
+        manager.protect("can0", &mut frame).unwrap();
+        assert_eq!(frame.data, vec![1, 2]);
+        assert!(manager.verify(&frame).is_none());
+    }
+}
@@ -0,0 +1,250 @@
+//! Time series of one decoded signal, ready for plotting
+
+use super::dbc::DbcDatabase;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+
+/// Time window in seconds since connect or trace start; open ends when unset
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub end: Option<f64>,
+}
+
+impl TimeRange {
+    pub fn contains(&self, t: f64) -> bool {
+        self.start.is_none_or(|start| t >= start) && self.end.is_none_or(|end| t <= end)
+    }
+}
+
+/// Decode `signal` of `message_id` from the frames of `channel_id` within `range`
+///
+/// Returns `(timestamp, physical value)` pairs in frame order.
+pub fn extract_series<'a>(
+    frames: impl IntoIterator<Item = &'a CanFrame>,
+    db: &DbcDatabase,
+    channel_id: &str,
+    message_id: u32,
+    signal: &str,
+    range: TimeRange,
+) -> Result<Vec<(f64, f64)>, BootCanError> {
+    let message = db
+        .get_message(message_id)
+        .ok_or_else(|| BootCanError::NotFound(format!("Message 0x{:X} not found in DBC", message_id)))?;
+    if !message.signals.iter().any(|s| s.name == signal) {
+        return Err(BootCanError::NotFound(format!("Signal {} not found in message {}", signal, message.name)));
+    }
+
+    Ok(frames
+        .into_iter()
+        .filter(|frame| frame.id == message_id && frame.channel == channel_id && range.contains(frame.timestamp))
+        .filter_map(|frame| {
+            let decoded = db.decode_signal(message_id, signal, &frame.data)?;
+            Some((frame.timestamp, decoded.physical_value))
+        })
+        .collect())
+}
+
+/// Signal of a DBC message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalRef {
+    pub message_id: u32,
+    pub signal: String,
+}
+
+/// Summary of a signal over a series
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalStatistics {
+    pub message_id: u32,
+    pub signal: String,
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Population standard deviation
+    pub std_dev: Option<f64>,
+    /// First and last sample as `(t, value)`
+    pub first: Option<(f64, f64)>,
+    pub last: Option<(f64, f64)>,
+}
+
+impl SignalStatistics {
+    /// Compute the statistics of a series of `signal`
+    pub fn from_series(message_id: u32, signal: &str, points: &[(f64, f64)]) -> Self {
+        let mut stats = Self {
+            message_id,
+            signal: signal.to_string(),
+            first: points.first().copied(),
+            last: points.last().copied(),
+            ..Default::default()
+        };
+        let (mut mean, mut m2) = (0.0, 0.0);
+        for &(_, value) in points {
+            stats.count += 1;
+            stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+            stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+            let delta = value - mean;
+            mean += delta / stats.count as f64;
+            m2 += delta * (value - mean);
+        }
+        if stats.count > 0 {
+            stats.mean = Some(mean);
+            stats.std_dev = Some((m2 / stats.count as f64).sqrt());
+        }
+        stats
+    }
+}
+
+/// Aggregate of the samples falling into one time bin
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesBin {
+    /// Start of the bin in seconds
+    pub t: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub count: u32,
+}
+
+/// Split a series into `bins` equal time bins and return min/max/avg of each
+///
+/// Bins span `range`, or the series itself where the range is open. Empty
+/// bins are left out, so gaps in the data stay visible.
+pub fn bin_series(points: &[(f64, f64)], bins: usize, range: TimeRange) -> Vec<SeriesBin> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    let start = range.start.unwrap_or(first.0);
+    let end = range.end.unwrap_or(last.0);
+    let bins = bins.max(1);
+    let width = (end - start) / bins as f64;
+
+    let mut result: Vec<SeriesBin> = Vec::new();
+    let mut current: Option<(usize, SeriesBin, f64)> = None;
+    for &(t, value) in points {
+        let index = if width > 0.0 {
+            (((t - start) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        match &mut current {
+            Some((i, bin, sum)) if *i == index => {
+                bin.min = bin.min.min(value);
+                bin.max = bin.max.max(value);
+                bin.count += 1;
+                *sum += value;
+            }
+            _ => {
+                if let Some((_, bin, sum)) = current.take() {
+                    result.push(SeriesBin { avg: sum / bin.count as f64, ..bin });
+                }
+                let bin = SeriesBin {
+                    t: start + index as f64 * width,
+                    min: value,
+                    max: value,
+                    avg: value,
+                    count: 1,
+                };
+                current = Some((index, bin, value));
+            }
+        }
+    }
+    if let Some((_, bin, sum)) = current {
+        result.push(SeriesBin { avg: sum / bin.count as f64, ..bin });
+    }
+    result
+}
+
+/// Reduce a series to at most `max_points` evenly spaced points, keeping the first and last
+pub fn decimate(points: Vec<(f64, f64)>, max_points: usize) -> Vec<(f64, f64)> {
+    if max_points == 0 || points.len() <= max_points {
+        return points;
+    }
+    if max_points == 1 {
+        return points.last().copied().into_iter().collect();
+    }
+    let step = (points.len() - 1) as f64 / (max_points - 1) as f64;
+    (0..max_points)
+        .map(|i| points[(i as f64 * step).round() as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Engine: 8 Vector__XXX\n SG_ RPM : 0|16@1+ (1,0) [0|8000] \"rpm\" Vector__XXX\n";
+
+    fn engine(channel: &str, rpm: u16, timestamp: f64) -> CanFrame {
+        let mut frame = CanFrame::new(256, &[0; 8]);
+        frame.data[..2].copy_from_slice(&rpm.to_le_bytes());
+        frame.channel = channel.into();
+        frame.timestamp = timestamp;
+        frame
+    }
+
+    #[test]
+    fn test_extract_series() {
+        let db = DbcParser::parse(DBC).unwrap();
+        let frames = vec![
+            engine("can0", 800, 0.0),
+            engine("can1", 1, 0.5),
+            engine("can0", 900, 1.0),
+            CanFrame::new(0x300, &[0; 8]),
+            engine("can0", 1000, 2.0),
+        ];
+
+        let all = extract_series(&frames, &db, "can0", 256, "RPM", TimeRange::default()).unwrap();
+        assert_eq!(all, vec![(0.0, 800.0), (1.0, 900.0), (2.0, 1000.0)]);
+        let window = TimeRange {
+            start: Some(0.5),
+            end: Some(1.5),
+        };
+        assert_eq!(extract_series(&frames, &db, "can0", 256, "RPM", window).unwrap(), vec![(1.0, 900.0)]);
+        assert!(extract_series(&frames, &db, "can0", 256, "Speed", TimeRange::default()).is_err());
+    }
+
+    #[test]
+    fn test_decimate() {
+        let points: Vec<(f64, f64)> = (0..101).map(|i| (i as f64, i as f64)).collect();
+        let reduced = decimate(points.clone(), 5);
+        assert_eq!(reduced.iter().map(|p| p.0).collect::<Vec<_>>(), vec![0.0, 25.0, 50.0, 75.0, 100.0]);
+        assert_eq!(decimate(points.clone(), 0).len(), 101);
+        assert_eq!(decimate(points, 1), vec![(100.0, 100.0)]);
+    }
+
+    #[test]
+    fn test_bin_series() {
+        // A spike inside the first bin survives as its max
+        let points = vec![(0.0, 1.0), (0.5, 9.0), (0.9, 2.0), (1.2, 4.0), (3.9, 6.0), (4.0, 8.0)];
+        let bins = bin_series(&points, 4, TimeRange::default());
+        assert_eq!(bins.len(), 3);
+        assert_eq!((bins[0].t, bins[0].min, bins[0].max, bins[0].count), (0.0, 1.0, 9.0, 3));
+        assert!((bins[0].avg - 4.0).abs() < 1e-9);
+        assert_eq!((bins[1].t, bins[1].avg), (1.0, 4.0));
+        // Bin 2 is empty; the end point falls into the last bin
+        assert_eq!((bins[2].t, bins[2].count, bins[2].avg), (3.0, 2, 7.0));
+        assert!(bin_series(&[], 10, TimeRange::default()).is_empty());
+    }
+
+    #[test]
+    fn test_signal_statistics() {
+        let points = vec![(0.0, 2.0), (1.0, 4.0), (2.0, 4.0), (3.0, 4.0), (4.0, 5.0), (5.0, 5.0), (6.0, 7.0), (7.0, 9.0)];
+        let stats = SignalStatistics::from_series(256, "RPM", &points);
+        assert_eq!(stats.count, 8);
+        assert_eq!((stats.min, stats.max, stats.mean), (Some(2.0), Some(9.0), Some(5.0)));
+        assert!((stats.std_dev.unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!((stats.first, stats.last), (Some((0.0, 2.0)), Some((7.0, 9.0))));
+
+        let empty = SignalStatistics::from_series(256, "RPM", &[]);
+        assert_eq!((empty.count, empty.max, empty.std_dev), (0, None, None));
+    }
+}
@@ -0,0 +1,193 @@
+//! Backend decoding of selected signals, reporting only value changes
+//!
+//! A watcher decodes the frames of a channel with its DBC and yields a
+//! [`SignalChange`] when a watched signal moves by more than its deadband
+//! from the last reported value. The first value seen is always reported.
+//! Frames already decoded by the decode pool are taken as they are.
+
+use super::dbc::{DbcDatabase, DecodedSignal};
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Signal to watch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedSignal {
+    pub message_id: u32,
+    pub signal: String,
+    /// Minimum change of the physical value to report; any change when 0
+    #[serde(default)]
+    pub deadband: f64,
+}
+
+/// Value change of a watched signal, emitted as `signal-changed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalChange {
+    pub channel_id: String,
+    pub message_id: u32,
+    pub signal: String,
+    pub value: f64,
+    /// Last reported value; None for the first report
+    pub previous: Option<f64>,
+    pub unit: String,
+    pub value_name: Option<String>,
+    pub timestamp: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Watch {
+    signal: String,
+    deadband: f64,
+    last: Option<f64>,
+}
+
+/// Decodes watched signals of one channel and tracks their last reported values
+pub struct SignalWatcher {
+    channel_id: String,
+    db: DbcDatabase,
+    watches: HashMap<u32, Vec<Watch>>,
+}
+
+impl SignalWatcher {
+    /// Create a watcher; fails if a signal is not in the DBC
+    pub fn new(channel_id: &str, db: DbcDatabase, signals: &[WatchedSignal]) -> Result<Self, BootCanError> {
+        let mut watches: HashMap<u32, Vec<Watch>> = HashMap::new();
+        for watched in signals {
+            let message = db
+                .get_message(watched.message_id)
+                .ok_or_else(|| BootCanError::NotFound(format!("Message 0x{:X} not found in DBC", watched.message_id)))?;
+            if !message.signals.iter().any(|s| s.name == watched.signal) {
+                return Err(BootCanError::NotFound(format!(
+                    "Signal {} not found in message {}",
+                    watched.signal, message.name
+                )));
+            }
+            watches.entry(watched.message_id).or_default().push(Watch {
+                signal: watched.signal.clone(),
+                deadband: watched.deadband.abs(),
+                last: None,
+            });
+        }
+        Ok(Self {
+            channel_id: channel_id.to_string(),
+            db,
+            watches,
+        })
+    }
+
+    /// Decode a frame and return the watched signals that changed
+    pub fn process(&mut self, frame: &CanFrame) -> Vec<SignalChange> {
+        let Self { channel_id, db, watches } = self;
+        changes(channel_id, watches, frame, |signal| db.decode_signal(frame.id, signal, &frame.data))
+    }
+
+    /// Return the watched signals of a decoded frame that changed
+    pub fn process_decoded(&mut self, frame: &CanFrame, signals: &[DecodedSignal]) -> Vec<SignalChange> {
+        changes(&self.channel_id, &mut self.watches, frame, |signal| {
+            signals.iter().find(|s| s.name == signal).cloned()
+        })
+    }
+}
+
+/// Update the watches of `frame` with the values from `decode`, returning the changes
+fn changes(
+    channel_id: &str,
+    watches: &mut HashMap<u32, Vec<Watch>>,
+    frame: &CanFrame,
+    decode: impl Fn(&str) -> Option<DecodedSignal>,
+) -> Vec<SignalChange> {
+    let Some(watches) = watches.get_mut(&frame.id) else {
+        return Vec::new();
+    };
+    let mut changes = Vec::new();
+    for watch in watches {
+        let Some(decoded) = decode(&watch.signal) else {
+            continue;
+        };
+        let value = decoded.physical_value;
+        let changed = match watch.last {
+            None => true,
+            Some(last) if watch.deadband > 0.0 => (value - last).abs() >= watch.deadband,
+            Some(last) => value != last,
+        };
+        if !changed {
+            continue;
+        }
+        changes.push(SignalChange {
+            channel_id: channel_id.to_string(),
+            message_id: frame.id,
+            signal: decoded.name,
+            value,
+            previous: watch.last,
+            unit: decoded.unit,
+            value_name: decoded.value_name,
+            timestamp: frame.timestamp,
+        });
+        watch.last = Some(value);
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Engine: 8 Vector__XXX\n SG_ RPM : 0|16@1+ (1,0) [0|8000] \"rpm\" Vector__XXX\n SG_ CoolantTemp : 16|8@1+ (1,-40) [-40|215] \"degC\" Vector__XXX\n";
+
+    fn engine(rpm: u16, coolant: u8) -> CanFrame {
+        let mut frame = CanFrame::new(256, &[0; 8]);
+        frame.data[..2].copy_from_slice(&rpm.to_le_bytes());
+        frame.data[2] = coolant;
+        frame
+    }
+
+    fn watch(signal: &str, deadband: f64) -> WatchedSignal {
+        WatchedSignal {
+            message_id: 256,
+            signal: signal.to_string(),
+            deadband,
+        }
+    }
+
+    #[test]
+    fn test_reports_changes_only() {
+        let db = DbcParser::parse(DBC).unwrap();
+        let mut watcher = SignalWatcher::new("can0", db, &[watch("CoolantTemp", 0.0)]).unwrap();
+
+        let first = watcher.process(&engine(800, 130));
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].value, first[0].previous), (90.0, None));
+        // RPM is not watched, so its change is not reported
+        assert!(watcher.process(&engine(900, 130)).is_empty());
+        let change = watcher.process(&engine(900, 131));
+        assert_eq!((change[0].value, change[0].previous), (91.0, Some(90.0)));
+        assert!(watcher.process(&CanFrame::new(0x300, &[1])).is_empty());
+
+        let frame = engine(900, 135);
+        let signals = watcher.db.decode_message(frame.id, &frame.data);
+        assert_eq!(watcher.process_decoded(&frame, &signals)[0].previous, Some(91.0));
+    }
+
+    #[test]
+    fn test_deadband_and_validation() {
+        let db = DbcParser::parse(DBC).unwrap();
+        let mut watcher = SignalWatcher::new("can0", db.clone(), &[watch("RPM", 50.0)]).unwrap();
+
+        assert_eq!(watcher.process(&engine(800, 0)).len(), 1);
+        // Drifting in small steps is reported once the total reaches the deadband
+        assert!(watcher.process(&engine(830, 0)).is_empty());
+        assert!(watcher.process(&engine(849, 0)).is_empty());
+        assert_eq!(watcher.process(&engine(850, 0))[0].previous, Some(800.0));
+
+        assert!(SignalWatcher::new("can0", db.clone(), &[watch("Speed", 0.0)]).is_err());
+        let unknown = WatchedSignal {
+            message_id: 0x300,
+            ..watch("RPM", 0.0)
+        };
+        assert!(SignalWatcher::new("can0", db, &[unknown]).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Memory-bounded history of channel statistics
+//!
+//! Counter deltas and bus load are collected into per-second samples, which
+//! roll up into per-minute and per-hour samples. Each tier is a ring buffer,
+//! so a capture of any length keeps ten minutes at one second resolution, a
+//! day at one minute and a month at one hour. The per-second tier doubles as
+//! the throughput history behind load sparklines.
+
+use super::bus_stats::BusStats;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECOND_SAMPLES: usize = 600;
+const MINUTE_SAMPLES: usize = 24 * 60;
+const HOUR_SAMPLES: usize = 31 * 24;
+
+/// Resolution of a history tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryTier {
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+impl HistoryTier {
+    fn span_s(self) -> f64 {
+        match self {
+            Self::Seconds => 1.0,
+            Self::Minutes => 60.0,
+            Self::Hours => 3600.0,
+        }
+    }
+}
+
+/// Statistics of one time slot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySample {
+    /// Start of the slot in seconds since connect
+    pub t: f64,
+    pub bus_load_avg: f64,
+    pub bus_load_max: f64,
+    pub rx_frames: u64,
+    pub tx_frames: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub errors: u64,
+    /// Bus load readings averaged into the slot
+    #[serde(skip)]
+    load_readings: u64,
+}
+
+impl HistorySample {
+    fn empty(t: f64) -> Self {
+        Self { t, ..Default::default() }
+    }
+
+    /// Fold a finer sample into this one
+    fn merge(&mut self, other: &HistorySample) {
+        let readings = self.load_readings + other.load_readings;
+        if readings > 0 {
+            self.bus_load_avg = (self.bus_load_avg * self.load_readings as f64
+                + other.bus_load_avg * other.load_readings as f64)
+                / readings as f64;
+        }
+        self.load_readings = readings;
+        self.bus_load_max = self.bus_load_max.max(other.bus_load_max);
+        self.rx_frames += other.rx_frames;
+        self.tx_frames += other.tx_frames;
+        self.rx_bytes += other.rx_bytes;
+        self.tx_bytes += other.tx_bytes;
+        self.errors += other.errors;
+    }
+}
+
+/// Frame and payload byte rates of one second
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputSample {
+    /// Start of the second since connect
+    pub t: f64,
+    pub rx_frames: u64,
+    pub tx_frames: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+/// Samples of one tier, returned by `get_stats_history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryView {
+    /// Wall-clock time of `t = 0` in ms since the Unix epoch
+    pub start_unix_ms: u64,
+    pub tier: HistoryTier,
+    pub samples: Vec<HistorySample>,
+}
+
+struct Tier {
+    tier: HistoryTier,
+    capacity: usize,
+    samples: VecDeque<HistorySample>,
+    current: Option<HistorySample>,
+}
+
+impl Tier {
+    fn new(tier: HistoryTier, capacity: usize) -> Self {
+        Self {
+            tier,
+            capacity,
+            samples: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    fn slot_start(&self, t: f64) -> f64 {
+        (t / self.tier.span_s()).floor() * self.tier.span_s()
+    }
+
+    /// Add a sample of a finer tier; returns the slots it completed, oldest first
+    ///
+    /// Slots skipped since the previous sample, e.g. while an idle bus is
+    /// updated less than once per slot, are completed as empty slots.
+    fn add(&mut self, sample: &HistorySample) -> Vec<HistorySample> {
+        let slot = self.slot_start(sample.t);
+        let mut completed = Vec::new();
+        if let Some(current) = self.current.filter(|current| current.t != slot) {
+            completed.extend(self.close());
+            let span = self.tier.span_s();
+            let skipped = (((slot - current.t) / span).round() as usize).saturating_sub(1);
+            for i in (1..=skipped.min(self.capacity)).rev() {
+                let empty = HistorySample::empty(slot - i as f64 * span);
+                self.push(empty);
+                completed.push(empty);
+            }
+        }
+        self.current.get_or_insert_with(|| HistorySample::empty(slot)).merge(sample);
+        completed
+    }
+
+    fn close(&mut self) -> Option<HistorySample> {
+        let sample = self.current.take()?;
+        self.push(sample);
+        Some(sample)
+    }
+
+    fn push(&mut self, sample: HistorySample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+/// Tiered statistics history of a channel
+pub struct StatsHistory {
+    start_unix_ms: u64,
+    /// rx/tx frames, rx/tx bytes and errors at the previous update
+    last_counts: [u64; 5],
+    seconds: Tier,
+    minutes: Tier,
+    hours: Tier,
+}
+
+impl Default for StatsHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsHistory {
+    pub fn new() -> Self {
+        Self {
+            start_unix_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            last_counts: [0; 5],
+            seconds: Tier::new(HistoryTier::Seconds, SECOND_SAMPLES),
+            minutes: Tier::new(HistoryTier::Minutes, MINUTE_SAMPLES),
+            hours: Tier::new(HistoryTier::Hours, HOUR_SAMPLES),
+        }
+    }
+
+    /// The channel counters were reset; the next update counts from zero
+    pub fn counters_reset(&mut self) {
+        self.last_counts = [0; 5];
+    }
+
+    /// Account the counters and bus load of a channel at `t` seconds since connect
+    pub fn update(&mut self, t: f64, stats: &BusStats) {
+        let counts = [stats.rx_count, stats.tx_count, stats.rx_bytes, stats.tx_bytes, stats.error_count];
+        // Counters that went backwards were reset and count from zero again
+        let delta = |i: usize| {
+            let (now, last) = (counts[i], self.last_counts[i]);
+            if now >= last { now - last } else { now }
+        };
+        let reading = HistorySample {
+            t,
+            bus_load_avg: stats.bus_load,
+            bus_load_max: stats.bus_load,
+            rx_frames: delta(0),
+            tx_frames: delta(1),
+            rx_bytes: delta(2),
+            tx_bytes: delta(3),
+            errors: delta(4),
+            load_readings: 1,
+        };
+        self.last_counts = counts;
+
+        for second in self.seconds.add(&reading) {
+            for minute in self.minutes.add(&second) {
+                self.hours.add(&minute);
+            }
+        }
+    }
+
+    /// Per-second rates of the last `seconds` completed seconds, oldest first
+    pub fn throughput(&self, seconds: usize) -> Vec<ThroughputSample> {
+        let samples = &self.seconds.samples;
+        samples
+            .iter()
+            .skip(samples.len().saturating_sub(seconds))
+            .map(|s| ThroughputSample {
+                t: s.t,
+                rx_frames: s.rx_frames,
+                tx_frames: s.tx_frames,
+                rx_bytes: s.rx_bytes,
+                tx_bytes: s.tx_bytes,
+            })
+            .collect()
+    }
+
+    /// Completed samples of a tier, oldest first
+    pub fn view(&self, tier: HistoryTier) -> HistoryView {
+        let tier_data = match tier {
+            HistoryTier::Seconds => &self.seconds,
+            HistoryTier::Minutes => &self.minutes,
+            HistoryTier::Hours => &self.hours,
+        };
+        HistoryView {
+            start_unix_ms: self.start_unix_ms,
+            tier,
+            samples: tier_data.samples.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(rx: u64, bus_load: f64) -> BusStats {
+        BusStats {
+            rx_count: rx,
+            rx_bytes: rx * 8,
+            bus_load,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roll_up() {
+        let mut history = StatsHistory::new();
+        // Ten readings per second for two and a half minutes, 5 frames each
+        let mut rx = 0;
+        for i in 0..1500 {
+            rx += 5;
+            let load = if i == 30 { 90.0 } else { 10.0 };
+            history.update(i as f64 / 10.0, &stats(rx, load));
+        }
+
+        let seconds = history.view(HistoryTier::Seconds).samples;
+        assert_eq!(seconds.len(), 149);
+        assert_eq!((seconds[0].t, seconds[0].rx_frames, seconds[0].rx_bytes), (0.0, 50, 400));
+        assert_eq!(seconds[3].bus_load_max, 90.0);
+        assert!((seconds[3].bus_load_avg - 18.0).abs() < 1e-9);
+
+        let minutes = history.view(HistoryTier::Minutes).samples;
+        assert_eq!(minutes.len(), 2);
+        assert_eq!((minutes[1].t, minutes[1].rx_frames), (60.0, 3000));
+        assert_eq!(minutes[0].bus_load_max, 90.0);
+        assert!(history.view(HistoryTier::Hours).samples.is_empty());
+    }
+
+    #[test]
+    fn test_tiers_are_bounded() {
+        let mut history = StatsHistory::new();
+        for i in 0..(2 * 3600 + 10) {
+            history.update(i as f64, &stats(i, 1.0));
+        }
+        let seconds = history.view(HistoryTier::Seconds).samples;
+        assert_eq!(seconds.len(), SECOND_SAMPLES);
+        assert_eq!(seconds.last().unwrap().t, (2 * 3600 + 8) as f64);
+        assert_eq!(history.view(HistoryTier::Minutes).samples.len(), 120);
+        let hours = history.view(HistoryTier::Hours).samples;
+        assert_eq!(hours.len(), 1);
+        assert_eq!(hours[0].rx_frames, 3600 - 1);
+
+        let throughput = history.throughput(5);
+        assert_eq!(throughput.len(), 5);
+        assert_eq!((throughput[4].t, throughput[4].rx_frames), ((2 * 3600 + 8) as f64, 1));
+        assert_eq!(history.throughput(10_000).len(), SECOND_SAMPLES);
+    }
+
+    #[test]
+    fn test_idle_gaps_are_filled() {
+        let mut history = StatsHistory::new();
+        // Updated every five seconds, as on an idle bus, with one burst in between
+        history.update(0.2, &stats(10, 5.0));
+        history.update(5.2, &stats(10, 0.0));
+        history.update(10.2, &stats(30, 2.0));
+        history.update(11.2, &stats(30, 0.0));
+
+        let throughput = history.throughput(5);
+        let times: Vec<f64> = throughput.iter().map(|s| s.t).collect();
+        assert_eq!(times, vec![6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(throughput[4].rx_frames, 20);
+        assert!(throughput[..4].iter().all(|s| s.rx_frames == 0));
+        assert_eq!(history.view(HistoryTier::Seconds).samples.len(), 11);
+    }
+}
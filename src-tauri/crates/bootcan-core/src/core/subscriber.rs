@@ -0,0 +1,180 @@
+//! Subscribers of a channel's frames
+//!
+//! Frames are broadcast to every subscriber through a bounded queue. A
+//! subscriber falling further behind than the queue holds loses the oldest
+//! frames; `FrameSubscriber` counts those per subscriber so the losses show up
+//! in the statistics and diagnostics instead of going unnoticed.
+
+use super::message::CanFrame;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Frames a subscriber can fall behind before it loses frames, unless configured
+pub const DEFAULT_BROADCAST_CAPACITY: usize = 1000;
+/// Minimum time between two warnings about the same subscriber losing frames
+const LAG_WARNING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Frames lost by one subscriber, returned in the diagnostics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriberDrops {
+    pub name: String,
+    pub dropped: u64,
+}
+
+/// Drop counters of the subscribers of one channel
+#[derive(Default)]
+pub(crate) struct SubscriberRegistry {
+    /// Frames lost by all subscribers, including the ones gone since
+    total: AtomicU64,
+    subscribers: Mutex<Vec<(String, Weak<AtomicU64>)>>,
+}
+
+impl SubscriberRegistry {
+    fn register(&self, name: &str) -> Arc<AtomicU64> {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let mut subscribers = self.subscribers.lock();
+        subscribers.retain(|(_, counter)| counter.strong_count() > 0);
+        subscribers.push((name.to_string(), Arc::downgrade(&dropped)));
+        dropped
+    }
+
+    /// Frames lost by all subscribers since the channel was created
+    pub fn total_dropped(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Frames lost by each live subscriber, in subscription order
+    pub fn drops(&self) -> Vec<SubscriberDrops> {
+        self.subscribers
+            .lock()
+            .iter()
+            .filter_map(|(name, counter)| {
+                counter.upgrade().map(|counter| SubscriberDrops {
+                    name: name.clone(),
+                    dropped: counter.load(Ordering::Relaxed),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Receiver of the transmitted and received frames of a channel
+///
+/// Frames lost by falling behind are counted and logged at most once per
+/// second; `recv` then carries on with the oldest frame still queued.
+pub struct FrameSubscriber {
+    name: String,
+    channel: String,
+    rx: broadcast::Receiver<CanFrame>,
+    dropped: Arc<AtomicU64>,
+    registry: Arc<SubscriberRegistry>,
+    /// Frames lost since the last warning, and when it was logged
+    unreported: u64,
+    last_warning: Option<Instant>,
+}
+
+impl FrameSubscriber {
+    pub(crate) fn new(
+        name: &str,
+        channel: &str,
+        rx: broadcast::Receiver<CanFrame>,
+        registry: Arc<SubscriberRegistry>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            channel: channel.to_string(),
+            rx,
+            dropped: registry.register(name),
+            registry,
+            unreported: 0,
+            last_warning: None,
+        }
+    }
+
+    /// Next frame, or None once the channel is gone
+    ///
+    /// Cancel safe, so it can be used in `tokio::select!`.
+    pub async fn recv(&mut self) -> Option<CanFrame> {
+        loop {
+            match self.rx.recv().await {
+                Ok(frame) => return Some(frame),
+                Err(RecvError::Lagged(skipped)) => self.record_lag(skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Frames this subscriber lost so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn record_lag(&mut self, skipped: u64) {
+        self.dropped.fetch_add(skipped, Ordering::Relaxed);
+        self.registry.total.fetch_add(skipped, Ordering::Relaxed);
+        self.unreported += skipped;
+        if self.last_warning.is_none_or(|t| t.elapsed() >= LAG_WARNING_INTERVAL) {
+            tracing::warn!(
+                channel = %self.channel,
+                "{} falling behind, lost {} frames",
+                self.name,
+                self.unreported
+            );
+            self.unreported = 0;
+            self.last_warning = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_lagging_subscriber_counts_drops() {
+        let (tx, _) = broadcast::channel(4);
+        let registry = Arc::new(SubscriberRegistry::default());
+        let mut slow = FrameSubscriber::new("slow", "ch0", tx.subscribe(), registry.clone());
+        let mut fast = FrameSubscriber::new("fast", "ch0", tx.subscribe(), registry.clone());
+
+        for id in 0..10 {
+            tx.send(CanFrame::new(id, &[])).unwrap();
+            if id < 6 {
+                assert_eq!(fast.recv().await.unwrap().id, id);
+            }
+        }
+        // Only the last four frames are still queued
+        assert_eq!(slow.recv().await.unwrap().id, 6);
+        assert_eq!((slow.dropped(), fast.dropped()), (6, 0));
+        assert_eq!(fast.recv().await.unwrap().id, 6);
+
+        let drops = registry.drops();
+        assert_eq!(drops[0], SubscriberDrops { name: "slow".to_string(), dropped: 6 });
+        assert_eq!(drops[1].dropped, 0);
+        drop(tx);
+        while slow.recv().await.is_some() {}
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_gone_subscribers_keep_total() {
+        let (tx, _) = broadcast::channel(2);
+        let registry = Arc::new(SubscriberRegistry::default());
+        let mut first = FrameSubscriber::new("first", "ch0", tx.subscribe(), registry.clone());
+        for id in 0..5 {
+            tx.send(CanFrame::new(id, &[])).unwrap();
+        }
+        assert_eq!(first.recv().await.unwrap().id, 3);
+        drop(first);
+
+        let _second = FrameSubscriber::new("second", "ch0", tx.subscribe(), registry.clone());
+        assert_eq!(registry.total_dropped(), 3);
+        assert_eq!(registry.drops().len(), 1);
+        assert_eq!(registry.drops()[0].name, "second");
+    }
+}
@@ -0,0 +1,578 @@
+//! Authenticated TCP bridge between two bootCAN instances
+//!
+//! One instance listens, the other connects. Both send line-delimited JSON
+//! messages tagged by `type`. A session starts with a mutual challenge: each
+//! side sends a random nonce in `hello` and answers the peer's nonce with an
+//! AES-CMAC proof keyed by the shared secret, so the secret never crosses the
+//! wire. Both nonces then give a session key. Afterwards frames of the mapped
+//! channels flow in both directions as `sealed` messages, each carrying a
+//! sequence number and a CMAC under the session key, so frames cannot be
+//! forged, altered, replayed or reflected by someone on the path.
+
+use super::channel::ChannelHandle;
+use super::filter::FilterSet;
+use super::message::{CanFrame, FrameData};
+use crate::error::BootCanError;
+use aes::Aes128;
+use cmac::{Cmac, Mac};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+
+/// Port used when none is given
+pub const DEFAULT_BRIDGE_PORT: u16 = 47801;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_MESSAGE_LEN: u64 = 64 * 1024;
+/// Local frames waiting to be sent to the peer; more are dropped and counted
+const OUTGOING_CAPACITY: usize = 1024;
+/// Peer messages waiting to be handled; when full the socket is not read
+const INCOMING_CAPACITY: usize = 64;
+/// Frames we injected recently, used to keep them from being sent back
+const MAX_INJECTED: usize = 256;
+
+fn default_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    DEFAULT_BRIDGE_PORT
+}
+
+/// How this instance reaches its peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum BridgeEndpoint {
+    /// Wait for the peer, one session at a time
+    #[serde(rename_all = "camelCase")]
+    Listen {
+        #[serde(default = "default_bind_address")]
+        bind_address: String,
+        #[serde(default = "default_port")]
+        port: u16,
+    },
+    /// Connect to a listening peer, e.g. "bench-pc:47801"
+    Connect { address: String },
+}
+
+/// Pairing of a local channel with a channel of the peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeMapping {
+    pub local: String,
+    pub remote: String,
+    /// Frames of the local channel sent to the peer
+    #[serde(default)]
+    pub outgoing: FilterSet,
+    /// Frames of the remote channel transmitted locally
+    #[serde(default)]
+    pub incoming: FilterSet,
+}
+
+/// Bridge settings; both sides must use the same secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpBridgeConfig {
+    pub endpoint: BridgeEndpoint,
+    pub secret: String,
+    pub mappings: Vec<BridgeMapping>,
+}
+
+/// Counters of a running bridge
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpBridgeStatus {
+    /// Address of the authenticated peer, if connected
+    pub peer: Option<String>,
+    pub frames_sent: u64,
+    pub frames_received: u64,
+    /// Local frames not sent because the peer could not keep up
+    #[serde(default)]
+    pub frames_dropped: u64,
+    pub last_error: Option<String>,
+}
+
+/// Running bridge, kept in the application state
+pub struct TcpBridgeHandle {
+    pub cancel: watch::Sender<bool>,
+    pub status: Arc<RwLock<TcpBridgeStatus>>,
+}
+
+/// Side of the session, part of every proof so a peer cannot reflect ours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeRole {
+    Listener,
+    Connector,
+}
+
+impl BridgeRole {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Listener => "listener",
+            Self::Connector => "connector",
+        }
+    }
+
+    fn peer(self) -> Self {
+        match self {
+            Self::Listener => Self::Connector,
+            Self::Connector => Self::Listener,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum BridgeMessage {
+    Hello { nonce: String },
+    Auth { proof: String },
+    Frame { channel: String, frame: CanFrame },
+    /// Message after the handshake, authenticated with the session key
+    Sealed { seq: u64, body: String, mac: String },
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn cmac(key: &[u8; 16], role: BridgeRole, nonce: &str) -> Cmac<Aes128> {
+    let mut mac = <Cmac<Aes128> as Mac>::new_from_slice(key).expect("AES-128 key length is fixed");
+    mac.update(role.label().as_bytes());
+    mac.update(b":");
+    mac.update(nonce.as_bytes());
+    mac
+}
+
+/// Key derived from the shared secret (AES-CMAC-PRF-128, RFC 4615)
+pub fn derive_key(secret: &str) -> [u8; 16] {
+    let mut mac = <Cmac<Aes128> as Mac>::new_from_slice(&[0u8; 16]).expect("AES-128 key length is fixed");
+    mac.update(secret.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Proof that `role` knows the key, answering the peer's `nonce`
+pub fn proof(key: &[u8; 16], role: BridgeRole, nonce: &str) -> String {
+    to_hex(&cmac(key, role, nonce).finalize().into_bytes())
+}
+
+/// Check a proof in constant time
+pub fn verify_proof(key: &[u8; 16], role: BridgeRole, nonce: &str, proof: &str) -> bool {
+    from_hex(proof).is_some_and(|bytes| cmac(key, role, nonce).verify_slice(&bytes).is_ok())
+}
+
+/// Key of one session, derived from the shared key and both hello nonces
+pub fn session_key(key: &[u8; 16], listener_nonce: &str, connector_nonce: &str) -> [u8; 16] {
+    let mut mac = cmac(key, BridgeRole::Listener, listener_nonce);
+    mac.update(b":session:");
+    mac.update(connector_nonce.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Seals our messages and opens the peer's once the session is authenticated
+struct SessionAuth {
+    key: [u8; 16],
+    role: BridgeRole,
+    sent: u64,
+    received: u64,
+}
+
+impl SessionAuth {
+    fn new(key: [u8; 16], role: BridgeRole) -> Self {
+        Self { key, role, sent: 0, received: 0 }
+    }
+
+    fn mac(&self, role: BridgeRole, seq: u64, body: &str) -> Cmac<Aes128> {
+        let mut mac = cmac(&self.key, role, &seq.to_string());
+        mac.update(b":");
+        mac.update(body.as_bytes());
+        mac
+    }
+
+    fn seal(&mut self, message: &BridgeMessage) -> Result<BridgeMessage, BootCanError> {
+        let body = serde_json::to_string(message).map_err(|e| BootCanError::Other(e.to_string()))?;
+        let seq = self.sent;
+        self.sent += 1;
+        let mac = to_hex(&self.mac(self.role, seq, &body).finalize().into_bytes());
+        Ok(BridgeMessage::Sealed { seq, body, mac })
+    }
+
+    /// Check the peer's next message; anything else ends the session
+    fn open(&mut self, message: BridgeMessage) -> Result<BridgeMessage, BootCanError> {
+        let BridgeMessage::Sealed { seq, body, mac } = message else {
+            return Err(BootCanError::Protocol("Unsealed bridge message".to_string()));
+        };
+        let expected = self.mac(self.role.peer(), seq, &body);
+        if from_hex(&mac).is_none_or(|bytes| expected.verify_slice(&bytes).is_err()) {
+            return Err(BootCanError::Protocol("Bridge message failed authentication".to_string()));
+        }
+        if seq != self.received {
+            return Err(BootCanError::Protocol(format!(
+                "Bridge message {} out of sequence, expected {}", seq, self.received
+            )));
+        }
+        self.received += 1;
+        match serde_json::from_str(&body) {
+            Ok(BridgeMessage::Sealed { .. }) => Err(BootCanError::Protocol("Nested sealed bridge message".to_string())),
+            Ok(message) => Ok(message),
+            Err(e) => Err(BootCanError::Parse(format!("Invalid bridge message: {}", e))),
+        }
+    }
+}
+
+/// Local end of a mapping
+pub struct BridgeLink {
+    pub mapping: BridgeMapping,
+    pub channel: ChannelHandle,
+}
+
+/// Bridge state shared by its sessions
+pub struct TcpBridge {
+    key: [u8; 16],
+    links: Vec<BridgeLink>,
+    status: Arc<RwLock<TcpBridgeStatus>>,
+}
+
+impl TcpBridge {
+    pub fn new(
+        secret: &str,
+        links: Vec<BridgeLink>,
+        status: Arc<RwLock<TcpBridgeStatus>>,
+    ) -> Result<Self, BootCanError> {
+        if secret.is_empty() {
+            return Err(BootCanError::InvalidInput("The bridge needs a shared secret".to_string()));
+        }
+        if links.is_empty() {
+            return Err(BootCanError::InvalidInput("The bridge needs at least one channel mapping".to_string()));
+        }
+        for (i, link) in links.iter().enumerate() {
+            if links[..i].iter().any(|other| other.mapping.remote == link.mapping.remote) {
+                return Err(BootCanError::InvalidInput(format!(
+                    "Remote channel {} is mapped twice", link.mapping.remote
+                )));
+            }
+        }
+        Ok(Self {
+            key: derive_key(secret),
+            links,
+            status,
+        })
+    }
+
+    /// Accept peers one after another until cancelled
+    pub async fn serve(self, listener: TcpListener, mut cancel: watch::Receiver<bool>) {
+        loop {
+            let (stream, peer) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!("Bridge accept failed: {}", e);
+                        continue;
+                    }
+                },
+                _ = cancel.changed() => break,
+            };
+
+            tracing::info!("Bridge peer connected from {}", peer);
+            let _ = stream.set_nodelay(true);
+            let result = self.run_session(stream, &peer.to_string(), BridgeRole::Listener, &mut cancel).await;
+            if let Err(e) = result {
+                tracing::warn!("Bridge session with {} ended: {}", peer, e);
+                self.status.write().last_error = Some(e.to_string());
+            }
+            if *cancel.borrow() {
+                break;
+            }
+        }
+    }
+
+    async fn handshake<R, W>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        role: BridgeRole,
+    ) -> Result<SessionAuth, BootCanError>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let nonce = to_hex(&rand::random::<[u8; 16]>());
+        write_message(writer, &BridgeMessage::Hello { nonce: nonce.clone() }).await?;
+
+        let Some(BridgeMessage::Hello { nonce: peer_nonce }) = read_message(reader).await? else {
+            return Err(BootCanError::Protocol("Peer did not start the handshake".to_string()));
+        };
+        let answer = proof(&self.key, role, &peer_nonce);
+        write_message(writer, &BridgeMessage::Auth { proof: answer }).await?;
+
+        match read_message(reader).await? {
+            Some(BridgeMessage::Auth { proof }) if verify_proof(&self.key, role.peer(), &nonce, &proof) => {}
+            _ => return Err(BootCanError::Protocol("Authentication failed".to_string())),
+        }
+        let key = match role {
+            BridgeRole::Listener => session_key(&self.key, &nonce, &peer_nonce),
+            BridgeRole::Connector => session_key(&self.key, &peer_nonce, &nonce),
+        };
+        Ok(SessionAuth::new(key, role))
+    }
+
+    /// Authenticate and forward frames until the peer leaves or the bridge is cancelled
+    pub async fn run_session<S>(
+        &self,
+        stream: S,
+        peer: &str,
+        role: BridgeRole,
+        cancel: &mut watch::Receiver<bool>,
+    ) -> Result<(), BootCanError>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+        let mut auth = tokio::time::timeout(HANDSHAKE_TIMEOUT, self.handshake(&mut reader, &mut writer, role))
+            .await
+            .map_err(|_| BootCanError::Timeout("Handshake timed out".to_string()))??;
+        self.status.write().peer = Some(peer.to_string());
+        tracing::info!("Bridge session with {} authenticated", peer);
+
+        // Reading a line is not cancel safe, so it gets its own task
+        let (incoming_tx, mut incoming) = mpsc::channel(INCOMING_CAPACITY);
+        let reader_task = tokio::spawn(async move {
+            loop {
+                let message = read_message(&mut reader).await;
+                let done = !matches!(message, Ok(Some(_)));
+                if incoming_tx.send(message).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        let (frame_tx, mut outgoing) = mpsc::channel::<(usize, CanFrame)>(OUTGOING_CAPACITY);
+        let forwarders: Vec<_> = self
+            .links
+            .iter()
+            .enumerate()
+            .map(|(index, link)| {
+                let mut rx = link.channel.subscribe("TCP bridge");
+                let frame_tx = frame_tx.clone();
+                let status = self.status.clone();
+                tokio::spawn(async move {
+                    while let Some(frame) = rx.recv().await {
+                        match frame_tx.try_send((index, frame)) {
+                            Ok(()) => {}
+                            Err(mpsc::error::TrySendError::Full(_)) => status.write().frames_dropped += 1,
+                            Err(mpsc::error::TrySendError::Closed(_)) => break,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let mut injected: VecDeque<(usize, u32, FrameData)> = VecDeque::new();
+        let result = loop {
+            tokio::select! {
+                message = incoming.recv() => {
+                    let message = match message {
+                        Some(Ok(Some(message))) => auth.open(message),
+                        Some(Ok(None)) | None => break Ok(()),
+                        Some(Err(e)) => break Err(e),
+                    };
+                    let (channel, frame) = match message {
+                        Ok(BridgeMessage::Frame { channel, frame }) => (channel, frame),
+                        Ok(_) => continue,
+                        Err(e) => break Err(e),
+                    };
+                    let Some(index) = self.links.iter().position(|link| link.mapping.remote == channel) else {
+                        continue;
+                    };
+                    let link = &self.links[index];
+                    if !link.mapping.incoming.matches(&frame) {
+                        continue;
+                    }
+
+                    if injected.len() >= MAX_INJECTED {
+                        injected.pop_front();
+                    }
+                    injected.push_back((index, frame.id, frame.data));
+                    match link.channel.send(frame).await {
+                        Ok(()) => self.status.write().frames_received += 1,
+                        Err(e) => tracing::warn!("Bridge: sending on {} failed: {}", link.mapping.local, e),
+                    }
+                }
+                frame = outgoing.recv() => {
+                    let Some((index, frame)) = frame else {
+                        break Ok(());
+                    };
+                    // Frames we transmitted for the peer come back as tx, don't echo them
+                    if frame.direction.is_tx() {
+                        if let Some(pos) = injected
+                            .iter()
+                            .position(|(i, id, data)| *i == index && *id == frame.id && *data == frame.data)
+                        {
+                            injected.remove(pos);
+                            continue;
+                        }
+                    }
+                    let link = &self.links[index];
+                    if !link.mapping.outgoing.matches(&frame) {
+                        continue;
+                    }
+
+                    let message = BridgeMessage::Frame {
+                        channel: link.mapping.local.clone(),
+                        frame,
+                    };
+                    let sealed = match auth.seal(&message) {
+                        Ok(sealed) => sealed,
+                        Err(e) => break Err(e),
+                    };
+                    if let Err(e) = write_message(&mut writer, &sealed).await {
+                        break Err(e);
+                    }
+                    self.status.write().frames_sent += 1;
+                }
+                _ = cancel.changed() => break Ok(()),
+            }
+        };
+
+        reader_task.abort();
+        for task in forwarders {
+            task.abort();
+        }
+        self.status.write().peer = None;
+        result
+    }
+}
+
+async fn read_message<R>(reader: &mut R) -> Result<Option<BridgeMessage>, BootCanError>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let mut line = String::new();
+    let read = (&mut *reader)
+        .take(MAX_MESSAGE_LEN)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| BootCanError::Io(format!("Bridge read failed: {}", e)))?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') {
+        return Err(BootCanError::Protocol("Bridge message too long".to_string()));
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| BootCanError::Parse(format!("Invalid bridge message: {}", e)))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &BridgeMessage) -> Result<(), BootCanError> {
+    let mut line = serde_json::to_string(message).map_err(|e| BootCanError::Other(e.to_string()))?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| BootCanError::Io(format!("Bridge write failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::subscriber::DEFAULT_BROADCAST_CAPACITY;
+
+    fn bridge(secret: &str) -> TcpBridge {
+        let channel = ChannelHandle::spawn("can0", Default::default(), DEFAULT_BROADCAST_CAPACITY);
+        let link = BridgeLink {
+            mapping: BridgeMapping {
+                local: "can0".to_string(),
+                remote: "can0".to_string(),
+                outgoing: FilterSet::default(),
+                incoming: FilterSet::default(),
+            },
+            channel,
+        };
+        TcpBridge::new(secret, vec![link], Arc::new(RwLock::new(TcpBridgeStatus::default()))).unwrap()
+    }
+
+    #[test]
+    fn test_proofs() {
+        let key = derive_key("bench");
+        let answer = proof(&key, BridgeRole::Connector, "abcd");
+        assert!(verify_proof(&key, BridgeRole::Connector, "abcd", &answer));
+        // A reflected proof names the wrong role
+        assert!(!verify_proof(&key, BridgeRole::Listener, "abcd", &answer));
+        assert!(!verify_proof(&derive_key("other"), BridgeRole::Connector, "abcd", &answer));
+        assert!(!verify_proof(&key, BridgeRole::Connector, "abcd", "zz"));
+        assert_eq!(from_hex(&to_hex(&[0x00, 0xAB])), Some(vec![0x00, 0xAB]));
+    }
+
+    #[test]
+    fn test_sealed_messages() {
+        let key = session_key(&derive_key("bench"), "aa", "bb");
+        assert_ne!(key, session_key(&derive_key("bench"), "aa", "bc"));
+        let mut listener = SessionAuth::new(key, BridgeRole::Listener);
+        let mut connector = SessionAuth::new(key, BridgeRole::Connector);
+        let frame = |id| BridgeMessage::Frame { channel: "can0".to_string(), frame: CanFrame::new(id, &[1, 2]) };
+
+        let first = connector.seal(&frame(0x100)).unwrap();
+        let second = connector.seal(&frame(0x200)).unwrap();
+        let BridgeMessage::Sealed { seq, body, mac } = &second else { panic!("not sealed") };
+        let forged = BridgeMessage::Sealed { seq: *seq, body: body.replace("512", "513"), mac: mac.clone() };
+        assert!(listener.open(forged).is_err());
+        // Skipped or replayed messages are out of sequence
+        assert!(listener.open(second).is_err());
+        let replay = first.clone();
+        assert!(matches!(listener.open(first), Ok(BridgeMessage::Frame { frame, .. }) if frame.id == 0x100));
+        assert!(listener.open(replay).is_err());
+
+        // Our own messages reflected back, and unsealed frames, are refused
+        let own = listener.seal(&frame(0x300)).unwrap();
+        let mut listener_peer = SessionAuth::new(key, BridgeRole::Listener);
+        assert!(listener_peer.open(own).is_err());
+        assert!(connector.open(frame(0x300)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_handshake() {
+        for (secret, expect_ok) in [("bench", true), ("wrong", false)] {
+            let listener = bridge("bench");
+            let connector = bridge(secret);
+            let (a, b) = tokio::io::duplex(4096);
+            let (cancel_tx, cancel_rx) = watch::channel(false);
+
+            let (mut rx_a, mut rx_b) = (cancel_rx.clone(), cancel_rx);
+            let session_a = listener.run_session(a, "peer-a", BridgeRole::Listener, &mut rx_a);
+            let session_b = async {
+                let result = connector.run_session(b, "peer-b", BridgeRole::Connector, &mut rx_b).await;
+                let _ = cancel_tx.send(true);
+                result
+            };
+            let check = async {
+                if expect_ok {
+                    // Let the sessions authenticate, then stop them
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    assert_eq!(connector.status.read().peer.as_deref(), Some("peer-b"));
+                    let _ = cancel_tx.send(true);
+                }
+            };
+            let (result_a, result_b, _) = tokio::join!(session_a, session_b, check);
+
+            assert_eq!(result_a.is_ok(), expect_ok);
+            assert_eq!(result_b.is_ok(), expect_ok);
+            assert!(connector.status.read().peer.is_none());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Ranking of IDs by frame count and bus time over a window
+
+use super::message::CanFrame;
+use super::signal_series::TimeRange;
+use super::traffic_gen::frame_bits;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Frames to rank
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TalkerSource {
+    /// Traffic of a connected channel over the next `window_ms`
+    #[serde(rename_all = "camelCase")]
+    Live { channel_id: String, window_ms: u64 },
+    /// Frames of the loaded trace, of one channel when given
+    #[serde(rename_all = "camelCase")]
+    Trace {
+        #[serde(default)]
+        channel_id: Option<String>,
+        #[serde(default)]
+        time_range: Option<TimeRange>,
+        /// Bitrate the bus time is computed with; 500 kbit/s when unset
+        #[serde(default)]
+        bitrate: Option<u32>,
+    },
+}
+
+/// Traffic of one ID in the window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Talker {
+    pub id: u32,
+    pub is_extended: bool,
+    pub frames: u64,
+    /// Time the ID occupied the bus in ms (nominal frame length, no stuff bits)
+    pub bus_time_ms: f64,
+    /// Share of the window the ID kept the bus busy, in percent
+    pub bus_load: f64,
+}
+
+/// Top N IDs of a window, ranked by frame count and by bus time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopTalkers {
+    pub window_s: f64,
+    pub total_frames: u64,
+    pub by_count: Vec<Talker>,
+    pub by_bus_time: Vec<Talker>,
+}
+
+/// Counts frames and bus time per ID
+#[derive(Debug, Clone)]
+pub struct TalkerCounter {
+    bitrate: u32,
+    talkers: HashMap<(u32, bool), (u64, f64)>,
+}
+
+impl TalkerCounter {
+    pub fn new(bitrate: u32) -> Self {
+        Self {
+            bitrate: bitrate.max(1),
+            talkers: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, frame: &CanFrame) {
+        let bits = if frame.is_remote {
+            frame_bits(frame.is_extended, 0)
+        } else {
+            frame_bits(frame.is_extended, frame.dlc)
+        };
+        let entry = self.talkers.entry((frame.id, frame.is_extended)).or_default();
+        entry.0 += 1;
+        entry.1 += bits * 1000.0 / self.bitrate as f64;
+    }
+
+    /// Rank the IDs counted in a window of `window_s` seconds
+    pub fn report(&self, top: usize, window_s: f64) -> TopTalkers {
+        let talkers: Vec<Talker> = self
+            .talkers
+            .iter()
+            .map(|(&(id, is_extended), &(frames, bus_time_ms))| Talker {
+                id,
+                is_extended,
+                frames,
+                bus_time_ms,
+                bus_load: if window_s > 0.0 { bus_time_ms / 10.0 / window_s } else { 0.0 },
+            })
+            .collect();
+
+        let mut by_count = talkers.clone();
+        by_count.sort_by(|a, b| b.frames.cmp(&a.frames).then(a.id.cmp(&b.id)));
+        by_count.truncate(top);
+        let mut by_bus_time = talkers;
+        by_bus_time.sort_by(|a, b| b.bus_time_ms.total_cmp(&a.bus_time_ms).then(a.id.cmp(&b.id)));
+        by_bus_time.truncate(top);
+
+        TopTalkers {
+            window_s,
+            total_frames: self.talkers.values().map(|(frames, _)| frames).sum(),
+            by_count,
+            by_bus_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rankings_differ() {
+        let mut counter = TalkerCounter::new(500_000);
+        // Many short frames on 0x100, fewer long extended frames on 0x18FF0000
+        for _ in 0..10 {
+            counter.record(&CanFrame::new(0x100, &[0]));
+        }
+        for _ in 0..6 {
+            counter.record(&CanFrame::new_extended(0x18FF0000, &[0; 8]));
+        }
+        counter.record(&CanFrame::new(0x200, &[0; 8]));
+
+        let report = counter.report(2, 1.0);
+        assert_eq!(report.total_frames, 17);
+        let by_count: Vec<u32> = report.by_count.iter().map(|t| t.id).collect();
+        let by_time: Vec<u32> = report.by_bus_time.iter().map(|t| t.id).collect();
+        assert_eq!(by_count, vec![0x100, 0x18FF0000]);
+        assert_eq!(by_time, vec![0x18FF0000, 0x100]);
+    }
+
+    #[test]
+    fn test_bus_time() {
+        let mut counter = TalkerCounter::new(500_000);
+        counter.record(&CanFrame::new(0x100, &[0; 8]));
+        let talker = &counter.report(10, 0.01).by_count[0];
+        let expected_ms = frame_bits(false, 8) / 500.0;
+        assert!((talker.bus_time_ms - expected_ms).abs() < 1e-9);
+        // One frame of ~0.22 ms in a 10 ms window
+        assert!((talker.bus_load - expected_ms * 10.0).abs() < 1e-9);
+    }
+}
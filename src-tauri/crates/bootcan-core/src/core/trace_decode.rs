@@ -0,0 +1,162 @@
+//! Decoding of a whole trace against the loaded DBCs
+//!
+//! Frames are split into chunks that rayon workers decode in parallel. Every
+//! finished chunk reports progress, and workers check for cancellation before
+//! starting a chunk, so a long decode can be stopped within one chunk.
+
+use super::dbc::{DbcDatabase, DecodedSignal};
+use super::message::CanFrame;
+use super::signal_series::TimeRange;
+use crate::error::BootCanError;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::watch;
+
+/// Frames a worker decodes between two progress reports
+pub const DECODE_CHUNK_LEN: usize = 16_384;
+
+/// Signals of one frame of the trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedFrame {
+    pub timestamp: f64,
+    pub channel: String,
+    pub id: u32,
+    pub signals: Vec<DecodedSignal>,
+}
+
+/// Progress of a trace decode job, emitted to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeProgress {
+    pub job_id: String,
+    pub frames_done: usize,
+    pub frames_total: usize,
+    pub finished: bool,
+    pub error: Option<String>,
+}
+
+/// Decode the frames within `range` with the DBC of their channel
+///
+/// Frames without a DBC or an unknown ID are skipped; the result keeps trace
+/// order. `on_progress` is called from the workers with the number of frames
+/// done so far.
+pub fn decode_frames<F>(
+    frames: &[CanFrame],
+    databases: &HashMap<String, DbcDatabase>,
+    range: TimeRange,
+    cancel: &watch::Receiver<bool>,
+    on_progress: F,
+) -> Result<Vec<DecodedFrame>, BootCanError>
+where
+    F: Fn(usize) + Sync,
+{
+    let done = AtomicUsize::new(0);
+    let chunks: Option<Vec<Vec<DecodedFrame>>> = frames
+        .par_chunks(DECODE_CHUNK_LEN)
+        .map(|chunk| {
+            if *cancel.borrow() {
+                return None;
+            }
+            let decoded = chunk
+                .iter()
+                .filter(|frame| range.contains(frame.timestamp))
+                .filter_map(|frame| {
+                    let signals = databases.get(frame.channel.as_str())?.decode_message(frame.id, &frame.data);
+                    (!signals.is_empty()).then(|| DecodedFrame {
+                        timestamp: frame.timestamp,
+                        channel: frame.channel.to_string(),
+                        id: frame.id,
+                        signals,
+                    })
+                })
+                .collect();
+            on_progress(done.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len());
+            Some(decoded)
+        })
+        .collect();
+
+    chunks
+        .map(|chunks| chunks.into_iter().flatten().collect())
+        .ok_or_else(|| BootCanError::Cancelled("Trace decode cancelled".to_string()))
+}
+
+/// Write decoded frames as CSV with one row per signal
+pub fn write_csv<W: Write>(mut writer: W, frames: &[DecodedFrame]) -> Result<(), BootCanError> {
+    let write_err = |e: std::io::Error| BootCanError::Io(format!("Failed to write decoded trace: {}", e));
+    writeln!(writer, "Time,Channel,ID,Signal,Raw,Value,Unit,Label").map_err(write_err)?;
+    for frame in frames {
+        for signal in &frame.signals {
+            writeln!(
+                writer,
+                "{:.6},{},0x{:X},{},{},{},{},{}",
+                frame.timestamp,
+                frame.channel,
+                frame.id,
+                signal.name,
+                signal.raw_value,
+                signal.physical_value,
+                signal.unit,
+                signal.value_name.as_deref().unwrap_or("")
+            )
+            .map_err(write_err)?;
+        }
+    }
+    writer.flush().map_err(write_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = "VERSION \"\"\n\nBO_ 256 Engine: 8 Vector__XXX\n SG_ RPM : 0|16@1+ (1,0) [0|8000] \"rpm\" Vector__XXX\n";
+
+    fn trace(len: usize) -> Vec<CanFrame> {
+        (0..len)
+            .map(|i| {
+                let mut frame = CanFrame::new(if i % 2 == 0 { 256 } else { 0x300 }, &[0; 8]);
+                frame.data[..2].copy_from_slice(&(i as u16).to_le_bytes());
+                frame.channel = "can0".into();
+                frame.timestamp = i as f64 * 0.001;
+                frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_frames_in_order() {
+        let databases = HashMap::from([("can0".to_string(), DbcParser::parse(DBC).unwrap())]);
+        let frames = trace(DECODE_CHUNK_LEN * 3 + 10);
+        let (_cancel_tx, cancel) = watch::channel(false);
+        let reported = AtomicUsize::new(0);
+
+        let decoded = decode_frames(&frames, &databases, TimeRange::default(), &cancel, |done| {
+            reported.fetch_max(done, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(decoded.len(), frames.len().div_ceil(2));
+        assert!(decoded.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+        assert_eq!(decoded[3].signals[0].physical_value, 6.0);
+        assert_eq!(reported.load(Ordering::Relaxed), frames.len());
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &decoded[..1]).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "Time,Channel,ID,Signal,Raw,Value,Unit,Label\n0.000000,can0,0x100,RPM,0,0,rpm,\n"
+        );
+    }
+
+    #[test]
+    fn test_cancelled_decode_fails() {
+        let databases = HashMap::from([("can0".to_string(), DbcParser::parse(DBC).unwrap())]);
+        let (cancel_tx, cancel) = watch::channel(false);
+        cancel_tx.send(true).unwrap();
+        let result = decode_frames(&trace(100), &databases, TimeRange::default(), &cancel, |_| {});
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,469 @@
+use crate::core::dbc::DecodedSignal;
+use crate::core::message::{BusType, CanFrame};
+use crate::error::BootCanError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, RwLock};
+use tracing::Instrument;
+
+/// Trace file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceFormat {
+    Csv,
+    Trc,
+    /// Vector ASCII log
+    Asc,
+}
+
+impl TraceFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "trc" => Some(Self::Trc),
+            "asc" => Some(Self::Asc),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Trc => "trc",
+            Self::Asc => "asc",
+        }
+    }
+
+    /// File header written before the first frame
+    pub fn header(&self) -> String {
+        match self {
+            Self::Csv => "Time,ID,Extended,Remote,DLC,Data,Direction,Channel,Bus\n".to_string(),
+            // TRC format header (Peak format)
+            Self::Trc => format!(
+                "$FILEVERSION={}\n$STARTTIME={}\n",
+                "2.0",
+                Utc::now().format("%Y-%m-%d %H:%M:%S%.3f")
+            ),
+            Self::Asc => {
+                let date = Utc::now().format("%a %b %d %I:%M:%S%.3f %P %Y");
+                format!(
+                    "date {}\nbase hex  timestamps absolute\ninternal events logged\nBegin Triggerblock {}\n{}",
+                    date, date, "   0.000000 Start of measurement\n"
+                )
+            }
+        }
+    }
+
+    /// File header of a symbolic export; CSV gains message and signal columns
+    pub fn symbolic_header(&self) -> String {
+        match self {
+            Self::Csv => "Time,ID,Extended,Remote,DLC,Data,Direction,Channel,Bus,Message,Signals\n".to_string(),
+            _ => self.header(),
+        }
+    }
+
+    /// Written after the last frame
+    pub fn footer(&self) -> &'static str {
+        match self {
+            Self::Asc => "End TriggerBlock\n",
+            Self::Csv | Self::Trc => "",
+        }
+    }
+
+    /// One trace line for a frame
+    ///
+    /// TRC and ASC have no notion of LIN, so LIN frames are written like
+    /// standard CAN frames there; CSV keeps the bus in its last column.
+    pub fn format_frame(&self, frame: &CanFrame) -> String {
+        let mut line = String::new();
+        self.write_frame(frame, &mut line);
+        line
+    }
+
+    /// Append the trace line for a frame to `line`
+    ///
+    /// Formats in place, so a buffer reused across frames stops allocating
+    /// once it has grown to the longest line.
+    pub fn write_frame(&self, frame: &CanFrame, line: &mut String) {
+        match self {
+            Self::Csv => {
+                let _ = write!(line, "{:.6},", frame.timestamp);
+                write_id(line, frame);
+                let _ = write!(line, ",{},{},{},", frame.is_extended, frame.is_remote, frame.dlc);
+                write_data(line, &frame.data);
+                let bus = if frame.bus == BusType::Lin { "LIN" } else { "CAN" };
+                let _ = writeln!(line, ",{},{},{}", frame.direction, frame.channel, bus);
+            }
+            Self::Trc => {
+                // TRC format: Time,Type,ID,Data Length,Data
+                // Type: Rx/Tx, Extended flag
+                let type_str = match (frame.is_extended, frame.direction.is_rx()) {
+                    (true, true) => "Rx",
+                    (true, false) => "Tx",
+                    (false, true) => "rx",
+                    (false, false) => "tx",
+                };
+                // Time in ms
+                let _ = write!(line, " {:11.6} {} ", frame.timestamp * 1000.0, type_str);
+                write_id(line, frame);
+                let _ = write!(line, " {} ", frame.dlc);
+                write_data(line, &frame.data);
+                line.push('\n');
+            }
+            Self::Asc => {
+                let _ = write!(line, "{:11.6} {:<2} ", frame.timestamp, asc_channel(&frame.channel));
+                let id_start = line.len();
+                let _ = if frame.is_extended {
+                    write!(line, "{:X}x", frame.id)
+                } else {
+                    write!(line, "{:X}", frame.id)
+                };
+                // Left-aligned in 15 columns
+                while line.len() - id_start < 15 {
+                    line.push(' ');
+                }
+                let direction = if frame.direction.is_rx() { "Rx" } else { "Tx" };
+                let kind = if frame.is_remote { "r" } else { "d" };
+                let _ = write!(line, " {}   {} {:X} ", direction, kind, frame.dlc);
+                if !frame.is_remote {
+                    write_data(line, &frame.data);
+                }
+                line.truncate(line.trim_end().len());
+                line.push('\n');
+            }
+        }
+    }
+
+    /// Trace line for a frame with its message name and decoded signals
+    ///
+    /// CSV adds them as two columns, left empty for unknown messages; TRC and
+    /// ASC follow the frame with a comment line, which readers skip.
+    pub fn format_frame_symbolic(&self, frame: &CanFrame, symbols: Option<(&str, &[DecodedSignal])>) -> String {
+        let mut line = self.format_frame(frame);
+        let signals = symbols.map(|(_, signals)| format_signals(signals)).unwrap_or_default();
+        match (self, symbols) {
+            (Self::Csv, _) => {
+                line.pop();
+                let message = symbols.map_or("", |(message, _)| message);
+                line.push_str(&format!(",{},{}\n", csv_field(message), csv_field(&signals)));
+            }
+            (Self::Trc, Some((message, _))) => line.push_str(&format!(";   {}: {}\n", message, signals)),
+            (Self::Asc, Some((message, _))) => line.push_str(&format!("// {}: {}\n", message, signals)),
+            (Self::Trc | Self::Asc, None) => {}
+        }
+        line
+    }
+}
+
+/// Frame ID in hex: 2 digits for LIN, 8 for extended and 3 for standard CAN IDs
+fn write_id(line: &mut String, frame: &CanFrame) {
+    let _ = if frame.bus == BusType::Lin {
+        write!(line, "{:02X}", frame.id)
+    } else if frame.is_extended {
+        write!(line, "{:08X}", frame.id)
+    } else {
+        write!(line, "{:03X}", frame.id)
+    };
+}
+
+/// Data bytes in hex, separated by spaces
+fn write_data(line: &mut String, data: &[u8]) {
+    for (i, byte) in data.iter().enumerate() {
+        if i > 0 {
+            line.push(' ');
+        }
+        let _ = write!(line, "{:02X}", byte);
+    }
+}
+
+/// ASC channel number: the number the channel ID ends in plus one, e.g. 1 for can0
+fn asc_channel(channel: &str) -> u32 {
+    let digits = channel.rfind(|c: char| !c.is_ascii_digit()).map_or(channel, |i| &channel[i + 1..]);
+    digits.parse::<u32>().map_or(1, |n| n + 1)
+}
+
+/// Signals as `Name=value unit`, or `Name=label` for enumerated values, separated by `; `
+fn format_signals(signals: &[DecodedSignal]) -> String {
+    signals
+        .iter()
+        .map(|signal| match &signal.value_name {
+            Some(label) => format!("{}={}", signal.name, label),
+            None if signal.unit.is_empty() => format!("{}={}", signal.name, signal.physical_value),
+            None => format!("{}={} {}", signal.name, signal.physical_value, signal.unit),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Quote a CSV field if it contains a separator or quote
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Configuration for trace logging
+#[derive(Debug, Clone)]
+pub struct TraceLoggerConfig {
+    pub format: TraceFormat,
+    pub file_path: PathBuf,
+    pub auto_split: bool,
+    pub max_file_size_mb: Option<u64>,
+    pub max_file_duration_sec: Option<u64>,
+}
+
+impl Default for TraceLoggerConfig {
+    fn default() -> Self {
+        Self {
+            format: TraceFormat::Csv,
+            file_path: PathBuf::from("trace.csv"),
+            auto_split: false,
+            max_file_size_mb: None,
+            max_file_duration_sec: None,
+        }
+    }
+}
+
+/// Trace logger state
+pub struct TraceLogger {
+    config: Arc<RwLock<TraceLoggerConfig>>,
+    writer: Option<BufWriter<File>>,
+    message_tx: Option<mpsc::UnboundedSender<CanFrame>>,
+    message_rx: Option<mpsc::UnboundedReceiver<CanFrame>>,
+    start_time: Option<DateTime<Utc>>,
+    frame_count: u64,
+    current_file_size: u64,
+}
+
+impl TraceLogger {
+    pub fn new(config: TraceLoggerConfig) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            writer: None,
+            message_tx: Some(tx),
+            message_rx: Some(rx),
+            start_time: None,
+            frame_count: 0,
+            current_file_size: 0,
+        }
+    }
+
+    /// Get a sender for logging messages
+    pub fn get_sender(&self) -> Option<mpsc::UnboundedSender<CanFrame>> {
+        self.message_tx.clone()
+    }
+
+    /// Start logging to file
+    pub async fn start(&mut self) -> Result<(), BootCanError> {
+        if self.writer.is_some() {
+            return Err(BootCanError::Busy("Logger already started".to_string()));
+        }
+
+        let config = self.config.read().await;
+        let file = File::create(&config.file_path)
+            .await
+            .map_err(|e| BootCanError::Io(format!("Failed to create trace file: {}", e)))?;
+
+        let mut writer = BufWriter::new(file);
+
+        writer
+            .write_all(config.format.header().as_bytes())
+            .await
+            .map_err(|e| BootCanError::Io(format!("Failed to write trace header: {}", e)))?;
+
+        self.writer = Some(writer);
+        self.start_time = Some(Utc::now());
+        self.frame_count = 0;
+        self.current_file_size = 0;
+
+        // Start background writer task
+        if let Some(mut rx) = self.message_rx.take() {
+            let writer = self.writer.take().unwrap();
+            // Read config values before spawning
+            let config_format = {
+                let cfg = self.config.read().await;
+                cfg.format
+            };
+            let config_path = {
+                let cfg = self.config.read().await;
+                cfg.file_path.clone()
+            };
+            let config_auto_split = {
+                let cfg = self.config.read().await;
+                cfg.auto_split
+            };
+            let config_max_size = {
+                let cfg = self.config.read().await;
+                cfg.max_file_size_mb
+            };
+            let config_max_duration = {
+                let cfg = self.config.read().await;
+                cfg.max_file_duration_sec
+            };
+            let start_time = self.start_time.unwrap();
+            let span = tracing::info_span!("log", file = %config_path.display());
+
+            tokio::spawn(async move {
+                let mut writer = writer;
+                let mut frame_count = 0u64;
+                let mut current_file_size = 0u64;
+                let mut line = String::new();
+
+                while let Some(frame) = rx.recv().await {
+                    frame_count += 1;
+
+                    line.clear();
+                    config_format.write_frame(&frame, &mut line);
+
+                    if let Err(e) = writer.write_all(line.as_bytes()).await {
+                        tracing::error!("Failed to write trace line: {}", e);
+                        break;
+                    }
+
+                    current_file_size += line.len() as u64;
+
+                    // Check if we need to split file
+                    let should_split = if !config_auto_split {
+                        false
+                    } else if let Some(max_size) = config_max_size {
+                        current_file_size > max_size * 1024 * 1024
+                    } else if let Some(max_duration) = config_max_duration {
+                        let elapsed = (Utc::now() - start_time).num_seconds() as u64;
+                        elapsed > max_duration
+                    } else {
+                        false
+                    };
+
+                    if should_split {
+                        // Close and flush current file
+                        if let Err(e) = writer.write_all(config_format.footer().as_bytes()).await {
+                            tracing::error!("Failed to write trace footer: {}", e);
+                        }
+                        if let Err(e) = writer.flush().await {
+                            tracing::error!("Failed to flush trace file: {}", e);
+                        }
+
+                        // Create new file
+                        let new_path = Self::generate_split_path(&config_path, frame_count);
+                        let new_file = match File::create(&new_path).await {
+                            Ok(f) => f,
+                            Err(e) => {
+                                tracing::error!("Failed to create split file: {}", e);
+                                break;
+                            }
+                        };
+
+                        writer = BufWriter::new(new_file);
+
+                        // Write header to new file
+                        if let Err(e) = writer.write_all(config_format.header().as_bytes()).await {
+                            tracing::error!("Failed to write trace header: {}", e);
+                            break;
+                        }
+
+                        current_file_size = 0;
+                    }
+
+                    // Periodic flush (every 100 frames or 1 second)
+                    if frame_count % 100 == 0 {
+                        if let Err(e) = writer.flush().await {
+                            tracing::error!("Failed to flush trace file: {}", e);
+                        }
+                    }
+                }
+
+                // Final footer and flush
+                if let Err(e) = writer.write_all(config_format.footer().as_bytes()).await {
+                    tracing::error!("Failed to write trace footer: {}", e);
+                }
+                if let Err(e) = writer.flush().await {
+                    tracing::error!("Failed to final flush trace file: {}", e);
+                }
+            }.instrument(span));
+        }
+
+        Ok(())
+    }
+
+    /// Stop logging and close file
+    pub async fn stop(&mut self) -> Result<(), BootCanError> {
+        // Drop the sender to signal the writer task to stop
+        self.message_tx = None;
+
+        // Wait a bit for the writer to finish
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        if let Some(mut writer) = self.writer.take() {
+            writer
+                .flush()
+                .await
+                .map_err(|e| BootCanError::Io(format!("Failed to flush trace file: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Update configuration
+    pub async fn update_config(&mut self, config: TraceLoggerConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Get current frame count
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Generate split file path
+    fn generate_split_path(base_path: &PathBuf, split_num: u64) -> PathBuf {
+        let mut new_path = base_path.clone();
+        let stem = new_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("trace");
+        let ext = new_path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("csv");
+
+        if let Some(parent) = new_path.parent() {
+            new_path = parent.join(format!("{}_{}.{}", stem, split_num, ext));
+        } else {
+            new_path = PathBuf::from(format!("{}_{}.{}", stem, split_num, ext));
+        }
+
+        new_path
+    }
+}
+
+impl Default for TraceLogger {
+    fn default() -> Self {
+        Self::new(TraceLoggerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_format_from_extension() {
+        assert_eq!(TraceFormat::from_extension("csv"), Some(TraceFormat::Csv));
+        assert_eq!(TraceFormat::from_extension("trc"), Some(TraceFormat::Trc));
+        assert_eq!(TraceFormat::from_extension("txt"), None);
+    }
+
+    #[test]
+    fn test_trace_format_extension() {
+        assert_eq!(TraceFormat::Csv.extension(), "csv");
+        assert_eq!(TraceFormat::Trc.extension(), "trc");
+    }
+}
+
@@ -1,5 +1,9 @@
-use crate::core::message::CanFrame;
-use std::collections::VecDeque;
+use crate::core::dbc::DbcDatabase;
+use crate::core::message::{BusType, CanFrame, Direction};
+use crate::core::pcap::read_capture;
+use crate::core::trace_mutation::{apply_mutations, TraceMutation};
+use crate::error::BootCanError;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use tokio::fs;
 use rayon::prelude::*;
@@ -15,6 +19,8 @@ pub enum PlaybackState {
 /// Trace player for replaying log files
 pub struct TracePlayer {
     frames: VecDeque<CanFrame>,
+    /// Trace as loaded while `frames` holds a mutated copy
+    original_frames: Option<VecDeque<CanFrame>>,
     current_index: usize,
     playback_speed: f64,
     state: PlaybackState,
@@ -26,6 +32,7 @@ impl TracePlayer {
     pub fn new() -> Self {
         Self {
             frames: VecDeque::new(),
+            original_frames: None,
             current_index: 0,
             playback_speed: 1.0,
             state: PlaybackState::Stopped,
@@ -34,14 +41,14 @@ impl TracePlayer {
         }
     }
 
-    /// Load trace file (CSV or TRC format)
+    /// Load trace file (CSV or TRC format, or a pcap/pcapng capture of SocketCAN frames)
     /// progress_callback: Optional callback that receives (current_line) for progress reporting
     pub async fn load_file(
         &mut self, 
         path: PathBuf, 
         bus_to_channel: Option<std::collections::HashMap<u8, String>>,
         progress_callback: Option<Box<dyn Fn(usize) + Send + Sync>>,
-    ) -> Result<usize, String> {
+    ) -> Result<usize, BootCanError> {
         // Detect format from extension
         let format = path
             .extension()
@@ -49,15 +56,30 @@ impl TracePlayer {
             .and_then(|ext| match ext.to_lowercase().as_str() {
                 "csv" => Some(TraceFormat::Csv),
                 "trc" => Some(TraceFormat::Trc),
+                "pcap" | "pcapng" => Some(TraceFormat::Pcap),
                 _ => None,
             })
-            .ok_or_else(|| "Unknown file format. Expected .csv or .trc".to_string())?;
+            .ok_or_else(|| {
+                BootCanError::InvalidInput("Unknown file format. Expected .csv, .trc, .pcap or .pcapng".to_string())
+            })?;
+
+        if format == TraceFormat::Pcap {
+            let bytes = fs::read(&path)
+                .await
+                .map_err(|e| BootCanError::Io(format!("Failed to read capture file: {}", e)))?;
+            let frames = read_capture(&bytes, bus_to_channel.as_ref())?;
+            let count = self.replace_frames(frames);
+            if let Some(ref callback) = progress_callback {
+                callback(count);
+            }
+            return Ok(count);
+        }
 
         // Read entire file into memory for parallel processing
         // For large files (1.7M lines), this is acceptable (~100-200MB)
         let file_contents = fs::read_to_string(&path)
             .await
-            .map_err(|e| format!("Failed to read trace file: {}", e))?;
+            .map_err(|e| BootCanError::Io(format!("Failed to read trace file: {}", e)))?;
         
         let all_lines: Vec<&str> = file_contents.lines().collect();
         let total_lines = all_lines.len();
@@ -99,7 +121,7 @@ impl TracePlayer {
         let bus_to_channel_clone = bus_to_channel.clone();
         let start_time_days_clone = start_time_days;
         
-        let parsed_frames: Vec<Result<CanFrame, String>> = data_lines
+        let parsed_frames: Vec<Result<CanFrame, BootCanError>> = data_lines
             .par_iter()
             .enumerate()
             .map(|(idx, line)| {
@@ -111,48 +133,55 @@ impl TracePlayer {
                 }
                 
                 if line.trim().is_empty() {
-                    return Err("Empty line".to_string());
+                    return Err(BootCanError::Parse("Empty line".to_string()));
                 }
                 
                 match format {
                     TraceFormat::Csv => {
-                        Self::parse_csv_line(line).map_err(|e| e.to_string())
+                        Self::parse_csv_line(line)
                     }
                     TraceFormat::Trc => {
                         Self::parse_trc_line(line, start_time_days_clone, &bus_to_channel_clone)
                     }
+                    TraceFormat::Pcap => unreachable!("captures are read above"),
                 }
             })
             .collect();
         
-        // Collect successful frames and sort by timestamp
-        let mut frames: Vec<CanFrame> = parsed_frames
+        // Collect successful frames
+        let frames: Vec<CanFrame> = parsed_frames
             .into_iter()
             .filter_map(|r| r.ok())
             .collect();
+        let count = self.replace_frames(frames);
         
+        // Emit final progress
+        if let Some(ref callback) = progress_callback {
+            callback(total_lines);
+        }
+
+        Ok(count)
+    }
+
+    /// Make `frames` the loaded trace, stopped at its start
+    fn replace_frames(&mut self, mut frames: Vec<CanFrame>) -> usize {
         // Sort by timestamp to maintain chronological order
         frames.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap_or(std::cmp::Ordering::Equal));
         
         // Convert to VecDeque
         self.frames = frames.into_iter().collect();
+        self.original_frames = None;
 
         self.current_index = 0;
         self.state = PlaybackState::Stopped;
         self.playback_start_timestamp = 0.0;
-        
-        // Emit final progress
-        if let Some(ref callback) = progress_callback {
-            callback(total_lines);
-        }
-
-        Ok(self.frames.len())
+        self.frames.len()
     }
 
     /// Start playback
-    pub fn start(&mut self) -> Result<(), String> {
+    pub fn start(&mut self) -> Result<(), BootCanError> {
         if self.frames.is_empty() {
-            return Err("No frames loaded".to_string());
+            return Err(BootCanError::InvalidInput("No frames loaded".to_string()));
         }
 
         if self.current_index >= self.frames.len() {
@@ -163,7 +192,7 @@ impl TracePlayer {
         self.start_time = Some(tokio::time::Instant::now());
         if let Some(frame) = self.frames.get(self.current_index) {
             self.playback_start_timestamp = frame.timestamp;
-            log::info!("Starting playback: {} frames, first timestamp: {}", self.frames.len(), frame.timestamp);
+            tracing::info!("Starting playback: {} frames, first timestamp: {}", self.frames.len(), frame.timestamp);
         }
 
         Ok(())
@@ -218,7 +247,7 @@ impl TracePlayer {
 
         if self.current_index >= self.frames.len() {
             self.state = PlaybackState::Stopped;
-            log::info!("Playback finished: reached end of trace");
+            tracing::info!("Playback finished: reached end of trace");
             return None;
         }
 
@@ -247,6 +276,36 @@ impl TracePlayer {
         Some((current_frame, delay))
     }
 
+    /// Replace the loaded trace by a mutated copy (an empty list restores it)
+    ///
+    /// Playback is stopped and rewound; returns the resulting frame count.
+    pub fn set_mutations(
+        &mut self,
+        mutations: &[TraceMutation],
+        databases: &HashMap<String, DbcDatabase>,
+    ) -> Result<usize, BootCanError> {
+        if mutations.is_empty() {
+            if let Some(original) = self.original_frames.take() {
+                self.frames = original;
+            }
+        } else {
+            let original = self.original_frames.as_ref().unwrap_or(&self.frames);
+            let mutated = apply_mutations(original.iter().cloned(), mutations, databases)?;
+            if self.original_frames.is_none() {
+                self.original_frames = Some(std::mem::take(&mut self.frames));
+            }
+            self.frames = mutated.into();
+        }
+
+        self.stop();
+        Ok(self.frames.len())
+    }
+
+    /// Whether playback uses a mutated copy of the trace
+    pub fn is_modified(&self) -> bool {
+        self.original_frames.is_some()
+    }
+
     /// Get playback state
     pub fn get_state(&self) -> PlaybackState {
         self.state.clone()
@@ -268,25 +327,25 @@ impl TracePlayer {
     }
 
     /// Parse CSV line
-    fn parse_csv_line(line: &str) -> Result<CanFrame, String> {
+    fn parse_csv_line(line: &str) -> Result<CanFrame, BootCanError> {
         let parts: Vec<&str> = line.split(',').collect();
         if parts.len() < 8 {
-            return Err("Invalid CSV line format".to_string());
+            return Err(BootCanError::Parse("Invalid CSV line format".to_string()));
         }
 
         let timestamp = parts[0].trim().parse::<f64>().map_err(|e| {
-            format!("Failed to parse timestamp: {}", e)
+            BootCanError::Parse(format!("Failed to parse timestamp: {}", e))
         })?;
 
         let id_str = parts[1].trim().replace("0x", "").replace("0X", "");
         let id = u32::from_str_radix(&id_str, 16).map_err(|e| {
-            format!("Failed to parse ID: {}", e)
+            BootCanError::Parse(format!("Failed to parse ID: {}", e))
         })?;
 
         let is_extended = parts[2].trim().parse::<bool>().unwrap_or(false);
         let is_remote = parts[3].trim().parse::<bool>().unwrap_or(false);
         let dlc = parts[4].trim().parse::<u8>().map_err(|e| {
-            format!("Failed to parse DLC: {}", e)
+            BootCanError::Parse(format!("Failed to parse DLC: {}", e))
         })?;
 
         let data_str = parts[5].trim();
@@ -294,20 +353,26 @@ impl TracePlayer {
             .split_whitespace()
             .map(|b| u8::from_str_radix(b, 16))
             .collect();
-        let data = data.map_err(|e| format!("Failed to parse data: {:?}", e))?;
-
-        let direction = parts[6].trim().to_string();
-        let channel = parts[7].trim().to_string();
+        let data = data.map_err(|e| BootCanError::Parse(format!("Failed to parse data: {:?}", e)))?;
+
+        let direction = if parts[6].trim().eq_ignore_ascii_case("tx") { Direction::Tx } else { Direction::Rx };
+        let channel = parts[7].trim().into();
+        // Traces written before LIN support have no bus column
+        let bus = match parts.get(8).map(|bus| bus.trim()) {
+            Some("LIN") => BusType::Lin,
+            _ => BusType::Can,
+        };
 
         Ok(CanFrame {
             id,
             is_extended,
             is_remote,
             dlc,
-            data,
+            data: data.into(),
             timestamp,
             channel,
             direction,
+            bus,
         })
     }
 
@@ -320,10 +385,12 @@ impl TracePlayer {
         line: &str,
         start_time_days: Option<f64>,
         bus_to_channel: &Option<std::collections::HashMap<u8, String>>,
-    ) -> Result<CanFrame, String> {
+    ) -> Result<CanFrame, BootCanError> {
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() < 8 {
-            return Err(format!("Invalid TRC line format: not enough fields (got {}, need 8+). Line: {}", parts.len(), line));
+            return Err(BootCanError::Parse(format!(
+                "Invalid TRC line format: not enough fields (got {}, need 8+). Line: {}", parts.len(), line
+            )));
         }
 
         // Detect format: if parts[2] looks like a number, it's the bus (no Type field)
@@ -341,7 +408,7 @@ impl TracePlayer {
 
         // Parse time offset (column O) - milliseconds from STARTTIME
         let time_offset_ms = parts[time_offset_idx].trim().parse::<f64>().map_err(|e| {
-            format!("Failed to parse time offset '{}': {}", parts[time_offset_idx], e)
+            BootCanError::Parse(format!("Failed to parse time offset '{}': {}", parts[time_offset_idx], e))
         })?;
         
         // Calculate absolute timestamp
@@ -361,7 +428,7 @@ impl TracePlayer {
 
         // Parse bus number (column B)
         let bus_num = parts[bus_idx].trim().parse::<u8>().map_err(|e| {
-            format!("Failed to parse bus number '{}' at index {}: {}", parts[bus_idx], bus_idx, e)
+            BootCanError::Parse(format!("Failed to parse bus number '{}' at index {}: {}", parts[bus_idx], bus_idx, e))
         })?;
 
         // Map bus number to channel ID
@@ -369,19 +436,19 @@ impl TracePlayer {
             mapping.get(&bus_num)
                 .cloned()
                 .unwrap_or_else(|| {
-                    log::warn!("Bus {} not found in mapping, using fallback channel_{}. Available buses: {:?}", 
+                    tracing::warn!("Bus {} not found in mapping, using fallback channel_{}. Available buses: {:?}", 
                         bus_num, bus_num, mapping.keys().collect::<Vec<_>>());
                     format!("channel_{}", bus_num)
                 })
         } else {
-            log::warn!("No bus-to-channel mapping provided, using channel_{}", bus_num);
+            tracing::warn!("No bus-to-channel mapping provided, using channel_{}", bus_num);
             format!("channel_{}", bus_num)
         };
         
         // Parse ID (column I) - hex without 0x prefix
         let id_str = parts[id_idx].trim();
         let id = u32::from_str_radix(id_str, 16).map_err(|e| {
-            format!("Failed to parse ID '{}': {}", id_str, e)
+            BootCanError::Parse(format!("Failed to parse ID '{}': {}", id_str, e))
         })?;
 
         // Determine if extended (29-bit) - IDs > 0x7FF are extended
@@ -390,37 +457,38 @@ impl TracePlayer {
         // Parse direction (column d)
         let direction_str = parts[direction_idx].trim();
         let direction = if direction_str.to_lowercase().starts_with('r') {
-            "rx"
+            Direction::Rx
         } else {
-            "tx"
+            Direction::Tx
         };
 
         // Reserved (column R) - skip (usually "-")
         // Parse length/DLC (column L)
         let dlc = parts[dlc_idx].trim().parse::<u8>().map_err(|e| {
-            format!("Failed to parse DLC '{}' at index {}: {}", parts[dlc_idx], dlc_idx, e)
+            BootCanError::Parse(format!("Failed to parse DLC '{}' at index {}: {}", parts[dlc_idx], dlc_idx, e))
         })?;
 
         // Parse data (column D) - hex bytes starting at data_start_idx
         if parts.len() < data_start_idx + dlc as usize {
-            return Err(format!("Not enough data bytes: need {} but only have {} parts", 
-                data_start_idx + dlc as usize, parts.len()));
+            return Err(BootCanError::Parse(format!("Not enough data bytes: need {} but only have {} parts", 
+                data_start_idx + dlc as usize, parts.len())));
         }
         let data: Result<Vec<u8>, _> = parts[data_start_idx..data_start_idx + dlc as usize]
             .iter()
             .map(|b| u8::from_str_radix(b, 16))
             .collect();
-        let data = data.map_err(|e| format!("Failed to parse data: {:?}", e))?;
+        let data = data.map_err(|e| BootCanError::Parse(format!("Failed to parse data: {:?}", e)))?;
 
         Ok(CanFrame {
             id,
             is_extended,
             is_remote: false,
             dlc,
-            data,
+            data: data.into(),
             timestamp,
-            channel,
-            direction: direction.to_string(),
+            channel: channel.into(),
+            direction,
+            bus: BusType::Can,
         })
     }
 }
@@ -436,6 +504,7 @@ impl Default for TracePlayer {
 enum TraceFormat {
     Csv,
     Trc,
+    Pcap,
 }
 
 #[cfg(test)]
@@ -448,7 +517,18 @@ mod tests {
         let frame = TracePlayer::parse_csv_line(line).unwrap();
         assert_eq!(frame.id, 0x123);
         assert_eq!(frame.dlc, 8);
-        assert_eq!(frame.direction, "rx");
+        assert_eq!(frame.direction, Direction::Rx);
+        assert_eq!(frame.bus, BusType::Can);
+    }
+
+    #[test]
+    fn test_lin_frames_keep_their_bus() {
+        let mut lin = CanFrame::new(0x21, &[0xAB, 0xCD]);
+        lin.bus = BusType::Lin;
+        let line = crate::core::trace_logger::TraceFormat::Csv.format_frame(&lin);
+        assert!(line.starts_with("0.000000,21,false,false,2,AB CD,tx,,LIN"));
+        let frame = TracePlayer::parse_csv_line(line.trim_end()).unwrap();
+        assert_eq!((frame.id, frame.bus), (0x21, BusType::Lin));
     }
 
     #[test]
@@ -461,8 +541,25 @@ mod tests {
         let frame = TracePlayer::parse_trc_line(line, start_time_days, bus_to_channel).unwrap();
         assert_eq!(frame.id, 0x132);
         assert_eq!(frame.dlc, 8);
-        assert_eq!(frame.direction, "rx");
+        assert_eq!(frame.direction, Direction::Rx);
         assert_eq!(frame.channel, "channel_3"); // Default channel when no mapping
     }
+
+    #[test]
+    fn test_mutations_keep_original() {
+        let mut player = TracePlayer::new();
+        player.frames = [CanFrame::new(0x100, &[1]), CanFrame::new(0x200, &[2])].into();
+
+        let swap = [TraceMutation::ReplaceId { from: 0x100, to: 0x300 }];
+        player.set_mutations(&swap, &HashMap::new()).unwrap();
+        // Re-applying starts from the loaded trace, not the mutated copy
+        player.set_mutations(&swap, &HashMap::new()).unwrap();
+        assert!(player.is_modified());
+        assert_eq!(player.get_all_frames()[0].id, 0x300);
+
+        player.set_mutations(&[], &HashMap::new()).unwrap();
+        assert!(!player.is_modified());
+        assert_eq!(player.get_all_frames()[0].id, 0x100);
+    }
 }
 
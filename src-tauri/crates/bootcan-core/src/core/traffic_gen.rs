@@ -0,0 +1,253 @@
+//! Synthetic bus load generation
+//!
+//! Produces random or profile-weighted frames at a target frame rate or bus
+//! load, for stress-testing gateways and the receive path.
+
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// One entry of a weighted ID profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightedId {
+    pub id: u32,
+    #[serde(default)]
+    pub is_extended: bool,
+    pub dlc: u8,
+    /// Relative share of the generated frames
+    pub weight: u32,
+}
+
+/// Which frames to generate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TrafficProfile {
+    /// Uniformly random IDs, lengths and payloads
+    #[serde(rename_all = "camelCase")]
+    Random { is_extended: bool, min_dlc: u8, max_dlc: u8 },
+    /// IDs picked by weight with random payloads
+    Weighted { ids: Vec<WeightedId> },
+}
+
+/// Target rate of the generator
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum TrafficRate {
+    FramesPerSecond(f64),
+    /// Percent of the channel bitrate
+    BusLoad(f64),
+}
+
+/// Traffic generator settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficGenConfig {
+    pub profile: TrafficProfile,
+    pub rate: TrafficRate,
+    /// Seed for a reproducible frame sequence (random if unset)
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Schedule by generator ticks instead of wall-clock time, so the number
+    /// of frames per tick does not depend on timer jitter
+    #[serde(default)]
+    pub virtual_clock: bool,
+}
+
+/// Progress of a running generator, reported about once per second
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficGenStatus {
+    pub job_id: String,
+    pub frames_sent: u64,
+    pub send_errors: u64,
+    /// Achieved rate over the last report interval
+    pub frames_per_second: f64,
+}
+
+/// Nominal length of a classic CAN data frame in bits (without stuff bits)
+pub fn frame_bits(is_extended: bool, dlc: u8) -> f64 {
+    let overhead = if is_extended { 67.0 } else { 47.0 };
+    overhead + 8.0 * dlc.min(8) as f64
+}
+
+/// Generator producing frames according to a profile
+pub struct TrafficGenerator {
+    config: TrafficGenConfig,
+    weights: Option<WeightedIndex<u32>>,
+    rng: StdRng,
+}
+
+impl TrafficGenerator {
+    pub fn new(config: TrafficGenConfig) -> Result<Self, BootCanError> {
+        let weights = match &config.profile {
+            TrafficProfile::Random { min_dlc, max_dlc, .. } => {
+                if min_dlc > max_dlc || *max_dlc > 8 {
+                    return Err(BootCanError::InvalidInput(format!("Invalid DLC range {}..{}", min_dlc, max_dlc)));
+                }
+                None
+            }
+            TrafficProfile::Weighted { ids } => Some(
+                WeightedIndex::new(ids.iter().map(|e| e.weight))
+                    .map_err(|e| BootCanError::InvalidInput(format!("Invalid ID profile: {}", e)))?,
+            ),
+        };
+        match config.rate {
+            TrafficRate::FramesPerSecond(rate) | TrafficRate::BusLoad(rate) if rate <= 0.0 => {
+                return Err(BootCanError::InvalidInput("Traffic rate must be positive".to_string()));
+            }
+            TrafficRate::BusLoad(load) if load > 100.0 => {
+                return Err(BootCanError::InvalidInput(format!("Bus load {}% exceeds 100%", load)));
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            weights,
+            rng: config.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            config,
+        })
+    }
+
+    pub fn uses_virtual_clock(&self) -> bool {
+        self.config.virtual_clock
+    }
+
+    /// Frames per second needed to reach the configured rate at `bitrate`
+    pub fn frames_per_second(&self, bitrate: u32) -> f64 {
+        match self.config.rate {
+            TrafficRate::FramesPerSecond(fps) => fps,
+            TrafficRate::BusLoad(load) => load / 100.0 * bitrate as f64 / self.average_frame_bits(),
+        }
+    }
+
+    /// Expected frame length of the profile in bits
+    fn average_frame_bits(&self) -> f64 {
+        match &self.config.profile {
+            TrafficProfile::Random {
+                is_extended,
+                min_dlc,
+                max_dlc,
+            } => frame_bits(*is_extended, 0) + 4.0 * (*min_dlc + *max_dlc) as f64,
+            TrafficProfile::Weighted { ids } => {
+                let total: f64 = ids.iter().map(|e| e.weight as f64).sum();
+                ids.iter()
+                    .map(|e| frame_bits(e.is_extended, e.dlc) * e.weight as f64 / total)
+                    .sum()
+            }
+        }
+    }
+
+    /// Produce the next frame
+    pub fn next_frame(&mut self) -> CanFrame {
+        let (id, is_extended, dlc) = match &self.config.profile {
+            TrafficProfile::Weighted { ids } => {
+                let index = self.weights.as_ref().map_or(0, |w| w.sample(&mut self.rng));
+                (ids[index].id, ids[index].is_extended, ids[index].dlc.min(8))
+            }
+            TrafficProfile::Random {
+                is_extended,
+                min_dlc,
+                max_dlc,
+            } => {
+                let max_id = if *is_extended { 0x1FFF_FFFF } else { 0x7FF };
+                (
+                    self.rng.gen_range(0..=max_id),
+                    *is_extended,
+                    self.rng.gen_range(*min_dlc..=*max_dlc),
+                )
+            }
+        };
+
+        let mut data = vec![0u8; dlc as usize];
+        self.rng.fill(&mut data[..]);
+        if is_extended {
+            CanFrame::new_extended(id, &data)
+        } else {
+            CanFrame::new(id, &data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_load_rate() {
+        let generator = TrafficGenerator::new(TrafficGenConfig {
+            profile: TrafficProfile::Weighted {
+                ids: vec![WeightedId {
+                    id: 0x100,
+                    is_extended: false,
+                    dlc: 8,
+                    weight: 1,
+                }],
+            },
+            rate: TrafficRate::BusLoad(50.0),
+            seed: None,
+            virtual_clock: false,
+        })
+        .unwrap();
+        // 111-bit frames at half of 500 kbit/s
+        let fps = generator.frames_per_second(500_000);
+        assert!((fps - 250_000.0 / 111.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_weighted_profile() {
+        let mut generator = TrafficGenerator::new(TrafficGenConfig {
+            profile: TrafficProfile::Weighted {
+                ids: vec![
+                    WeightedId {
+                        id: 0x100,
+                        is_extended: false,
+                        dlc: 8,
+                        weight: 9,
+                    },
+                    WeightedId {
+                        id: 0x18FEF100,
+                        is_extended: true,
+                        dlc: 4,
+                        weight: 1,
+                    },
+                ],
+            },
+            rate: TrafficRate::FramesPerSecond(100.0),
+            seed: None,
+            virtual_clock: false,
+        })
+        .unwrap();
+
+        let frames: Vec<CanFrame> = (0..1000).map(|_| generator.next_frame()).collect();
+        let heavy = frames.iter().filter(|f| f.id == 0x100).count();
+        assert!((800..=980).contains(&heavy));
+        let light = frames.iter().find(|f| f.id == 0x18FEF100).unwrap();
+        assert!(light.is_extended);
+        assert_eq!(light.data.len(), 4);
+    }
+
+    #[test]
+    fn test_seeded_sequence() {
+        let config = TrafficGenConfig {
+            profile: TrafficProfile::Random {
+                is_extended: false,
+                min_dlc: 0,
+                max_dlc: 8,
+            },
+            rate: TrafficRate::FramesPerSecond(100.0),
+            seed: Some(42),
+            virtual_clock: true,
+        };
+        let mut first = TrafficGenerator::new(config.clone()).unwrap();
+        let mut second = TrafficGenerator::new(config).unwrap();
+
+        for _ in 0..100 {
+            let (a, b) = (first.next_frame(), second.next_frame());
+            assert_eq!((a.id, a.data), (b.id, b.data));
+        }
+    }
+}
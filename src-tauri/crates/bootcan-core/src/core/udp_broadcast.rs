@@ -0,0 +1,120 @@
+//! Frames as JSON datagrams over UDP
+//!
+//! Every frame is sent as one datagram holding the same JSON object as the
+//! "can-message" event, so any tool that can read UDP (Node-RED, Processing,
+//! `nc -ul`) can consume live traffic without a client library.
+
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, watch};
+
+/// Settings of a UDP stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UdpBroadcastConfig {
+    /// Destination, e.g. "127.0.0.1:5005" or a broadcast address
+    pub target: String,
+    pub channels: Vec<String>,
+    /// Skip frames we transmitted ourselves
+    #[serde(default)]
+    pub rx_only: bool,
+}
+
+/// Resolve the target and bind a socket able to reach it
+pub async fn open_socket(target: &str) -> Result<(UdpSocket, SocketAddr), BootCanError> {
+    let address = tokio::net::lookup_host(target)
+        .await
+        .map_err(|e| BootCanError::InvalidInput(format!("Invalid UDP target {}: {}", target, e)))?
+        .next()
+        .ok_or_else(|| BootCanError::InvalidInput(format!("UDP target {} did not resolve", target)))?;
+
+    let bind = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind)
+        .await
+        .map_err(|e| BootCanError::Io(format!("Failed to open UDP socket: {}", e)))?;
+    socket
+        .set_broadcast(true)
+        .map_err(|e| BootCanError::Io(format!("Failed to enable UDP broadcast: {}", e)))?;
+    Ok((socket, address))
+}
+
+/// Send frames from `frames` to `target` until cancelled
+pub async fn run_udp_broadcast(
+    socket: UdpSocket,
+    target: SocketAddr,
+    mut frames: mpsc::UnboundedReceiver<CanFrame>,
+    rx_only: bool,
+    mut cancel: watch::Receiver<bool>,
+) {
+    let mut failures = 0u64;
+    loop {
+        let frame = tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(frame) => frame,
+                None => break,
+            },
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    break;
+                }
+                continue;
+            }
+        };
+        if rx_only && !frame.direction.is_rx() {
+            continue;
+        }
+
+        let Ok(datagram) = serde_json::to_vec(&frame) else {
+            continue;
+        };
+        // Nobody listening is normal for UDP; only log the first failure
+        if let Err(e) = socket.send_to(&datagram, target).await {
+            if failures == 0 {
+                tracing::warn!("UDP stream to {}: {}", target, e);
+            }
+            failures += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::message::Direction;
+
+    #[tokio::test]
+    async fn test_frames_arrive_as_json() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = receiver.local_addr().unwrap().to_string();
+
+        let (socket, address) = open_socket(&target).await.unwrap();
+        let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        let task = tokio::spawn(run_udp_broadcast(socket, address, frame_rx, true, cancel_rx));
+
+        let mut own = CanFrame::new(0x100, &[0]);
+        own.direction = Direction::Tx;
+        frame_tx.send(own).unwrap();
+        let mut received = CanFrame::new(0x123, &[1, 2]);
+        received.direction = Direction::Rx;
+        frame_tx.send(received).unwrap();
+
+        let mut buffer = [0u8; 512];
+        let len = receiver.recv(&mut buffer).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&buffer[..len]).unwrap();
+        assert_eq!(value["id"], 0x123);
+        assert_eq!(value["data"], serde_json::json!([1, 2]));
+        assert_eq!(value["isExtended"], false);
+
+        cancel_tx.send(true).unwrap();
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_target() {
+        assert!(open_socket("not an address").await.is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use super::*;
+use crate::core::frame_link::FrameLink;
+use crate::core::isotp::{IsoTpConfig, IsoTpLink, DEFAULT_ISOTP_TIMEOUT};
+use crate::error::BootCanError;
+use std::time::Duration;
+
+/// Default P2 server response timeout
+pub const DEFAULT_P2_TIMEOUT: Duration = Duration::from_millis(150);
+/// Default P2* timeout after a response pending NRC
+pub const DEFAULT_P2_STAR_TIMEOUT: Duration = Duration::from_millis(5000);
+
+/// UDS client talking to one ECU over ISO-TP
+pub struct UdsClient<'a> {
+    transport: IsoTpLink<'a>,
+    p2: Duration,
+    p2_star: Duration,
+}
+
+impl<'a> UdsClient<'a> {
+    pub fn new(link: &'a mut dyn FrameLink, config: IsoTpConfig) -> Self {
+        Self {
+            transport: IsoTpLink::new(link, config, DEFAULT_ISOTP_TIMEOUT),
+            p2: DEFAULT_P2_TIMEOUT,
+            p2_star: DEFAULT_P2_STAR_TIMEOUT,
+        }
+    }
+
+    /// Override the P2 / P2* response timeouts
+    pub fn with_timeouts(mut self, p2: Duration, p2_star: Duration) -> Self {
+        self.p2 = p2;
+        self.p2_star = p2_star;
+        self
+    }
+
+    /// Send a request and wait for its positive response (including the response SID)
+    pub async fn request(&mut self, request: &[u8]) -> Result<Vec<u8>, BootCanError> {
+        let sid = *request.first().ok_or_else(|| BootCanError::InvalidInput("Empty UDS request".to_string()))?;
+        let response = self
+            .request_raw(request)
+            .await?
+            .ok_or_else(|| {
+                BootCanError::Timeout(format!("UDS timeout waiting for response to service 0x{:02X}", sid))
+            })?;
+
+        match response.as_slice() {
+            [NEGATIVE_RESPONSE, _, nrc, ..] => Err(BootCanError::Protocol(format!(
+                "UDS service 0x{:02X} rejected: NRC 0x{:02X} ({})",
+                sid,
+                nrc,
+                nrc_description(*nrc)
+            ))),
+            _ => Ok(response),
+        }
+    }
+
+    /// Send a request and return the final positive or negative response
+    /// (None on timeout); response pending NRCs are waited out
+    pub async fn request_raw(&mut self, request: &[u8]) -> Result<Option<Vec<u8>>, BootCanError> {
+        let sid = *request.first().ok_or_else(|| BootCanError::InvalidInput("Empty UDS request".to_string()))?;
+        self.transport.send(request).await?;
+
+        let mut timeout = self.p2;
+        loop {
+            let Some(response) = self.transport.recv(timeout).await? else {
+                return Ok(None);
+            };
+
+            match response.as_slice() {
+                [NEGATIVE_RESPONSE, rejected, NRC_RESPONSE_PENDING, ..] if *rejected == sid => {
+                    timeout = self.p2_star;
+                }
+                [NEGATIVE_RESPONSE, rejected, _, ..] if *rejected == sid => return Ok(Some(response)),
+                [first, ..] if *first == sid.wrapping_add(POSITIVE_RESPONSE_OFFSET) => {
+                    return Ok(Some(response))
+                }
+                // Late responses to earlier requests are ignored
+                _ => continue,
+            }
+        }
+    }
+
+    /// Switch diagnostic session
+    pub async fn diagnostic_session_control(&mut self, session: u8) -> Result<(), BootCanError> {
+        self.request(&[SID_DIAGNOSTIC_SESSION_CONTROL, session]).await?;
+        Ok(())
+    }
+
+    /// Reset the ECU (1 = hard reset)
+    pub async fn ecu_reset(&mut self, reset_type: u8) -> Result<(), BootCanError> {
+        self.request(&[SID_ECU_RESET, reset_type]).await?;
+        Ok(())
+    }
+
+    /// Send TesterPresent with the positive response suppressed
+    pub async fn tester_present(&mut self) -> Result<(), BootCanError> {
+        self.transport
+            .send(&[SID_TESTER_PRESENT, SUPPRESS_POSITIVE_RESPONSE])
+            .await
+    }
+
+    /// Start a routine and return its status record
+    pub async fn start_routine(&mut self, routine_id: u16, params: &[u8]) -> Result<Vec<u8>, BootCanError> {
+        let mut request = vec![SID_ROUTINE_CONTROL, 0x01];
+        request.extend_from_slice(&routine_id.to_be_bytes());
+        request.extend_from_slice(params);
+        let response = self.request(&request).await?;
+        Ok(response.get(4..).unwrap_or_default().to_vec())
+    }
+
+    /// Request a download of `length` bytes to `address`; returns the maximum
+    /// TransferData payload size accepted by the ECU
+    pub async fn request_download(&mut self, address: u32, length: u32) -> Result<usize, BootCanError> {
+        // No compression/encryption, 4-byte address and 4-byte length
+        let mut request = vec![SID_REQUEST_DOWNLOAD, 0x00, 0x44];
+        request.extend_from_slice(&address.to_be_bytes());
+        request.extend_from_slice(&length.to_be_bytes());
+        let response = self.request(&request).await?;
+
+        let size_len = (response.get(1).copied().unwrap_or(0) >> 4) as usize;
+        if size_len == 0 || size_len > 8 || response.len() < 2 + size_len {
+            return Err(BootCanError::Protocol("Invalid RequestDownload response".to_string()));
+        }
+        let max_block = response[2..2 + size_len]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        // The block length includes SID and block sequence counter
+        if max_block <= 2 {
+            return Err(BootCanError::Protocol(format!("ECU reported unusable block length {}", max_block)));
+        }
+        Ok(max_block - 2)
+    }
+
+    /// Transfer one block of a download
+    pub async fn transfer_data(&mut self, sequence: u8, data: &[u8]) -> Result<(), BootCanError> {
+        let mut request = Vec::with_capacity(data.len() + 2);
+        request.push(SID_TRANSFER_DATA);
+        request.push(sequence);
+        request.extend_from_slice(data);
+        let response = self.request(&request).await?;
+        if response.get(1) != Some(&sequence) {
+            return Err(BootCanError::Protocol(format!(
+                "TransferData response for wrong block (expected {})",
+                sequence
+            )));
+        }
+        Ok(())
+    }
+
+    /// Finish a download
+    pub async fn request_transfer_exit(&mut self) -> Result<(), BootCanError> {
+        self.request(&[SID_REQUEST_TRANSFER_EXIT]).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::isotp::DEFAULT_MAX_MESSAGE_LEN;
+    use crate::core::message::CanFrame;
+    use crate::core::frame_link::scripted::ScriptedLink;
+
+    /// ECU answering each request frame with the next scripted frames
+    fn ecu(responses: Vec<Vec<Vec<u8>>>) -> ScriptedLink {
+        ScriptedLink::with_bursts(responses, |_, data| CanFrame::new(0x7E8, data))
+    }
+
+    fn config() -> IsoTpConfig {
+        IsoTpConfig {
+            tx_id: 0x7E0,
+            rx_id: 0x7E8,
+            is_extended: false,
+            padding: None,
+            block_size: 0,
+            st_min: 0,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_pending_then_positive() {
+        let mut link = ecu(vec![vec![vec![0x03, 0x7F, 0x31, 0x78], vec![0x05, 0x71, 0x01, 0xFF, 0x00, 0x00]]]);
+        let mut client = UdsClient::new(&mut link, config());
+        let status = client.start_routine(0xFF00, &[]).await.unwrap();
+        assert_eq!(status, vec![0x00]);
+        assert_eq!(link.sent[0].data, vec![0x04, 0x31, 0x01, 0xFF, 0x00]);
+    }
+
+    #[tokio::test]
+    async fn test_negative_response() {
+        let mut link = ecu(vec![vec![vec![0x03, 0x7F, 0x10, 0x22]]]);
+        let mut client = UdsClient::new(&mut link, config());
+        let err = client.diagnostic_session_control(SESSION_PROGRAMMING).await.unwrap_err();
+        assert!(matches!(err, BootCanError::Protocol(_)));
+        assert!(err.message().contains("Conditions not correct"));
+    }
+
+    #[tokio::test]
+    async fn test_request_download_block_length() {
+        // 11-byte request: flow control after the first frame, then the response
+        let mut link = ecu(vec![vec![vec![0x30, 0, 0]], vec![vec![0x04, 0x74, 0x20, 0x01, 0x02]]]);
+        let mut client = UdsClient::new(&mut link, config());
+        assert_eq!(client.request_download(0x8000, 0x100).await.unwrap(), 256);
+    }
+}
@@ -0,0 +1,114 @@
+//! UDS (ISO 14229) diagnostics over ISO-TP
+//!
+//! Provides a client for the services needed by diagnostics and flashing,
+//! long-lived sessions that run concurrently per ECU, a table-driven ECU
+//! simulator, an NRC-based service scanner and ODX/PDX import for symbolic
+//! names, plus the service identifiers and negative response codes shared by them.
+
+pub mod client;
+pub mod odx;
+pub mod scanner;
+pub mod server;
+pub mod session;
+
+pub use client::UdsClient;
+pub use odx::{DescribedResponse, DiagDescription};
+pub use server::EcuSimConfig;
+pub use session::{UdsSession, UdsSessionInfo};
+
+pub const SID_DIAGNOSTIC_SESSION_CONTROL: u8 = 0x10;
+pub const SID_ECU_RESET: u8 = 0x11;
+pub const SID_CLEAR_DIAGNOSTIC_INFORMATION: u8 = 0x14;
+pub const SID_READ_DTC_INFORMATION: u8 = 0x19;
+pub const SID_READ_DATA_BY_IDENTIFIER: u8 = 0x22;
+pub const SID_SECURITY_ACCESS: u8 = 0x27;
+pub const SID_WRITE_DATA_BY_IDENTIFIER: u8 = 0x2E;
+pub const SID_TESTER_PRESENT: u8 = 0x3E;
+pub const SID_ROUTINE_CONTROL: u8 = 0x31;
+pub const SID_REQUEST_DOWNLOAD: u8 = 0x34;
+pub const SID_TRANSFER_DATA: u8 = 0x36;
+pub const SID_REQUEST_TRANSFER_EXIT: u8 = 0x37;
+
+/// Service ID of a negative response
+pub const NEGATIVE_RESPONSE: u8 = 0x7F;
+/// Offset added to the request SID in positive responses
+pub const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+/// Sub-function bit suppressing the positive response
+pub const SUPPRESS_POSITIVE_RESPONSE: u8 = 0x80;
+
+/// Negative response codes (ISO 14229-1)
+pub const NRC_SERVICE_NOT_SUPPORTED: u8 = 0x11;
+pub const NRC_SUB_FUNCTION_NOT_SUPPORTED: u8 = 0x12;
+pub const NRC_INCORRECT_LENGTH: u8 = 0x13;
+pub const NRC_REQUEST_SEQUENCE_ERROR: u8 = 0x24;
+pub const NRC_REQUEST_OUT_OF_RANGE: u8 = 0x31;
+pub const NRC_SECURITY_ACCESS_DENIED: u8 = 0x33;
+pub const NRC_INVALID_KEY: u8 = 0x35;
+pub const NRC_WRONG_BLOCK_SEQUENCE_COUNTER: u8 = 0x73;
+pub const NRC_RESPONSE_PENDING: u8 = 0x78;
+pub const NRC_SUB_FUNCTION_NOT_SUPPORTED_IN_SESSION: u8 = 0x7E;
+pub const NRC_SERVICE_NOT_SUPPORTED_IN_SESSION: u8 = 0x7F;
+
+/// Diagnostic sessions (ISO 14229-1)
+pub const SESSION_DEFAULT: u8 = 0x01;
+pub const SESSION_PROGRAMMING: u8 = 0x02;
+pub const SESSION_EXTENDED: u8 = 0x03;
+
+/// Routine: erase memory
+pub const ROUTINE_ERASE_MEMORY: u16 = 0xFF00;
+/// Routine: check programming dependencies
+pub const ROUTINE_CHECK_PROGRAMMING_DEPENDENCIES: u16 = 0xFF01;
+
+/// Name of a UDS service
+pub fn service_name(sid: u8) -> &'static str {
+    match sid {
+        0x10 => "DiagnosticSessionControl",
+        0x11 => "ECUReset",
+        0x14 => "ClearDiagnosticInformation",
+        0x19 => "ReadDTCInformation",
+        0x22 => "ReadDataByIdentifier",
+        0x23 => "ReadMemoryByAddress",
+        0x27 => "SecurityAccess",
+        0x28 => "CommunicationControl",
+        0x2E => "WriteDataByIdentifier",
+        0x2F => "InputOutputControlByIdentifier",
+        0x31 => "RoutineControl",
+        0x34 => "RequestDownload",
+        0x35 => "RequestUpload",
+        0x36 => "TransferData",
+        0x37 => "RequestTransferExit",
+        0x3D => "WriteMemoryByAddress",
+        0x3E => "TesterPresent",
+        0x85 => "ControlDTCSetting",
+        _ => "Unknown service",
+    }
+}
+
+/// Description of a UDS negative response code
+pub fn nrc_description(nrc: u8) -> &'static str {
+    match nrc {
+        0x10 => "General reject",
+        0x11 => "Service not supported",
+        0x12 => "Sub-function not supported",
+        0x13 => "Incorrect message length or invalid format",
+        0x14 => "Response too long",
+        0x21 => "Busy repeat request",
+        0x22 => "Conditions not correct",
+        0x24 => "Request sequence error",
+        0x25 => "No response from subnet component",
+        0x26 => "Failure prevents execution of requested action",
+        0x31 => "Request out of range",
+        0x33 => "Security access denied",
+        0x35 => "Invalid key",
+        0x36 => "Exceeded number of attempts",
+        0x37 => "Required time delay not expired",
+        0x70 => "Upload/download not accepted",
+        0x71 => "Transfer data suspended",
+        0x72 => "General programming failure",
+        0x73 => "Wrong block sequence counter",
+        0x78 => "Request correctly received, response pending",
+        0x7E => "Sub-function not supported in active session",
+        0x7F => "Service not supported in active session",
+        _ => "Unknown negative response code",
+    }
+}
@@ -0,0 +1,348 @@
+use super::*;
+use crate::error::BootCanError;
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// DTC defined in a diagnostic description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DtcDescription {
+    /// Display form (e.g. "P0123"), hex code if the description has none
+    pub display: String,
+    pub text: Option<String>,
+}
+
+/// Symbolic names for one ECU, imported from ODX-D or PDX
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagDescription {
+    pub ecu_name: Option<String>,
+    pub dids: HashMap<u16, String>,
+    pub routines: HashMap<u16, String>,
+    pub dtcs: HashMap<u32, DtcDescription>,
+}
+
+/// DTC from a ReadDTCInformation response with its symbolic text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DescribedDtc {
+    pub code: u32,
+    pub status: u8,
+    pub display: String,
+    pub text: Option<String>,
+}
+
+/// UDS response annotated with names from the diagnostic description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DescribedResponse {
+    pub service: String,
+    /// DID or routine name, or the NRC text of a negative response
+    pub name: Option<String>,
+    /// Response bytes after the service and identifier
+    pub payload: Vec<u8>,
+    pub dtcs: Vec<DescribedDtc>,
+}
+
+impl DiagDescription {
+    /// Load an ODX-D file or a PDX container (all ODX files inside are merged)
+    pub fn load(path: &str) -> Result<Self, BootCanError> {
+        let is_pdx = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdx"));
+        if !is_pdx {
+            let content = fs::read_to_string(path)
+                .map_err(|e| BootCanError::Io(format!("Failed to read ODX file: {}", e)))?;
+            return Self::parse_odx(&content);
+        }
+
+        let file = fs::File::open(path).map_err(|e| BootCanError::Io(format!("Failed to open PDX file: {}", e)))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| BootCanError::Parse(format!("Invalid PDX container: {}", e)))?;
+        let mut description = Self::default();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| BootCanError::Parse(e.to_string()))?;
+            if !entry.name().to_ascii_lowercase().contains(".odx") {
+                continue;
+            }
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .map_err(|e| BootCanError::Io(format!("Failed to read {} from PDX: {}", entry.name(), e)))?;
+            description.merge(Self::parse_odx(&content)?);
+        }
+        Ok(description)
+    }
+
+    /// Parse the DID, routine and DTC definitions of an ODX document
+    pub fn parse_odx(content: &str) -> Result<Self, BootCanError> {
+        let doc = Document::parse(content).map_err(|e| BootCanError::Parse(format!("Invalid ODX: {}", e)))?;
+        let mut description = Self {
+            ecu_name: doc
+                .descendants()
+                .find(|n| n.has_tag_name("BASE-VARIANT") || n.has_tag_name("ECU-VARIANT"))
+                .and_then(|n| child_text(n, "SHORT-NAME")),
+            ..Self::default()
+        };
+
+        // Requests are referenced by the services that name them
+        let requests: HashMap<&str, (u8, Option<u16>)> = doc
+            .descendants()
+            .filter(|n| n.has_tag_name("REQUEST"))
+            .filter_map(|n| Some((n.attribute("ID")?, request_identifier(n)?)))
+            .collect();
+
+        for service in doc.descendants().filter(|n| n.has_tag_name("DIAG-SERVICE")) {
+            let Some((sid, Some(identifier))) = service
+                .children()
+                .find(|n| n.has_tag_name("REQUEST-REF"))
+                .and_then(|n| n.attribute("ID-REF"))
+                .and_then(|id| requests.get(id))
+                .copied()
+            else {
+                continue;
+            };
+            let Some(name) = child_text(service, "LONG-NAME").or_else(|| child_text(service, "SHORT-NAME")) else {
+                continue;
+            };
+            match sid {
+                // Read services name the DID best; write services only fill gaps
+                SID_READ_DATA_BY_IDENTIFIER => {
+                    description.dids.insert(identifier, name);
+                }
+                SID_WRITE_DATA_BY_IDENTIFIER => {
+                    description.dids.entry(identifier).or_insert(name);
+                }
+                SID_ROUTINE_CONTROL => {
+                    description.routines.entry(identifier).or_insert(name);
+                }
+                _ => {}
+            }
+        }
+
+        for dtc in doc.descendants().filter(|n| n.has_tag_name("DTC")) {
+            let Some(code) = child_text(dtc, "TROUBLE-CODE").and_then(|t| parse_coded_value(&t)) else {
+                continue;
+            };
+            description.dtcs.insert(
+                code,
+                DtcDescription {
+                    display: child_text(dtc, "DISPLAY-TROUBLE-CODE").unwrap_or_else(|| format!("{:06X}", code)),
+                    text: child_text(dtc, "TEXT").or_else(|| child_text(dtc, "SHORT-NAME")),
+                },
+            );
+        }
+
+        Ok(description)
+    }
+
+    /// Add the definitions of another description (e.g. another layer of a PDX)
+    pub fn merge(&mut self, other: DiagDescription) {
+        if self.ecu_name.is_none() {
+            self.ecu_name = other.ecu_name;
+        }
+        for (did, name) in other.dids {
+            self.dids.entry(did).or_insert(name);
+        }
+        for (routine, name) in other.routines {
+            self.routines.entry(routine).or_insert(name);
+        }
+        for (code, dtc) in other.dtcs {
+            self.dtcs.entry(code).or_insert(dtc);
+        }
+    }
+
+    /// Annotate a response with DID, routine and DTC names
+    pub fn describe_response(&self, response: &[u8]) -> Option<DescribedResponse> {
+        let (&first, rest) = response.split_first()?;
+        if first == NEGATIVE_RESPONSE {
+            let sid = *rest.first()?;
+            let nrc = *rest.get(1)?;
+            return Some(DescribedResponse {
+                service: service_name(sid).to_string(),
+                name: Some(nrc_description(nrc).to_string()),
+                payload: Vec::new(),
+                dtcs: Vec::new(),
+            });
+        }
+
+        let sid = first.checked_sub(POSITIVE_RESPONSE_OFFSET)?;
+        let mut described = DescribedResponse {
+            service: service_name(sid).to_string(),
+            name: None,
+            payload: rest.to_vec(),
+            dtcs: Vec::new(),
+        };
+        match sid {
+            SID_READ_DATA_BY_IDENTIFIER | SID_WRITE_DATA_BY_IDENTIFIER if rest.len() >= 2 => {
+                let did = u16::from_be_bytes([rest[0], rest[1]]);
+                described.name = self.dids.get(&did).cloned();
+                described.payload = rest[2..].to_vec();
+            }
+            SID_ROUTINE_CONTROL if rest.len() >= 3 => {
+                let routine = u16::from_be_bytes([rest[1], rest[2]]);
+                described.name = self.routines.get(&routine).cloned();
+                described.payload = rest[3..].to_vec();
+            }
+            // reportDTCByStatusMask: sub-function, availability mask, then DTC and status records
+            SID_READ_DTC_INFORMATION if rest.first() == Some(&0x02) && rest.len() >= 2 => {
+                described.payload = Vec::new();
+                described.dtcs = rest[2..]
+                    .chunks_exact(4)
+                    .map(|record| {
+                        let code = u32::from_be_bytes([0, record[0], record[1], record[2]]);
+                        let known = self.dtcs.get(&code);
+                        DescribedDtc {
+                            code,
+                            status: record[3],
+                            display: known.map_or_else(|| format!("{:06X}", code), |d| d.display.clone()),
+                            text: known.and_then(|d| d.text.clone()),
+                        }
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+        Some(described)
+    }
+}
+
+fn child_text(node: Node, tag: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Coded values are decimal, some tools write hex with a 0x prefix
+fn parse_coded_value(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Service ID and DID / routine identifier of a request from its constant parameters
+fn request_identifier(request: Node) -> Option<(u8, Option<u16>)> {
+    let mut sid = None;
+    let mut identifier = None;
+    for param in request.descendants().filter(|n| n.has_tag_name("PARAM")) {
+        let Some(value) = child_text(param, "CODED-VALUE").and_then(|t| parse_coded_value(&t)) else {
+            continue;
+        };
+        let position = child_text(param, "BYTE-POSITION").and_then(|t| t.parse::<u32>().ok());
+        match param.attribute("SEMANTIC") {
+            Some("SERVICE-ID") => sid = Some(value as u8),
+            Some("ID") => identifier = Some(value as u16),
+            Some("SUBFUNCTION") => {}
+            _ if position == Some(0) => sid = Some(value as u8),
+            _ if identifier.is_none() && matches!(position, Some(1) | Some(2)) => identifier = Some(value as u16),
+            _ => {}
+        }
+    }
+    sid.map(|sid| (sid, identifier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ODX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ODX xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" VERSION="2.2.0">
+  <DIAG-LAYER-CONTAINER ID="DLC_Engine">
+    <SHORT-NAME>DLC_Engine</SHORT-NAME>
+    <BASE-VARIANTS>
+      <BASE-VARIANT ID="BV_Engine">
+        <SHORT-NAME>Engine</SHORT-NAME>
+        <DIAG-COMMS>
+          <DIAG-SERVICE ID="DS_ReadVIN">
+            <SHORT-NAME>Read_VIN</SHORT-NAME>
+            <LONG-NAME>Vehicle Identification Number</LONG-NAME>
+            <REQUEST-REF ID-REF="RQ_ReadVIN"/>
+          </DIAG-SERVICE>
+          <DIAG-SERVICE ID="DS_Erase">
+            <SHORT-NAME>EraseMemory</SHORT-NAME>
+            <REQUEST-REF ID-REF="RQ_Erase"/>
+          </DIAG-SERVICE>
+        </DIAG-COMMS>
+        <REQUESTS>
+          <REQUEST ID="RQ_ReadVIN">
+            <SHORT-NAME>RQ_ReadVIN</SHORT-NAME>
+            <PARAMS>
+              <PARAM SEMANTIC="SERVICE-ID" xsi:type="CODED-CONST">
+                <SHORT-NAME>SID</SHORT-NAME><BYTE-POSITION>0</BYTE-POSITION><CODED-VALUE>34</CODED-VALUE>
+              </PARAM>
+              <PARAM SEMANTIC="ID" xsi:type="CODED-CONST">
+                <SHORT-NAME>DID</SHORT-NAME><BYTE-POSITION>1</BYTE-POSITION><CODED-VALUE>61840</CODED-VALUE>
+              </PARAM>
+            </PARAMS>
+          </REQUEST>
+          <REQUEST ID="RQ_Erase">
+            <SHORT-NAME>RQ_Erase</SHORT-NAME>
+            <PARAMS>
+              <PARAM SEMANTIC="SERVICE-ID" xsi:type="CODED-CONST">
+                <SHORT-NAME>SID</SHORT-NAME><BYTE-POSITION>0</BYTE-POSITION><CODED-VALUE>49</CODED-VALUE>
+              </PARAM>
+              <PARAM SEMANTIC="SUBFUNCTION" xsi:type="CODED-CONST">
+                <SHORT-NAME>Start</SHORT-NAME><BYTE-POSITION>1</BYTE-POSITION><CODED-VALUE>1</CODED-VALUE>
+              </PARAM>
+              <PARAM SEMANTIC="ID" xsi:type="CODED-CONST">
+                <SHORT-NAME>RID</SHORT-NAME><BYTE-POSITION>2</BYTE-POSITION><CODED-VALUE>65280</CODED-VALUE>
+              </PARAM>
+            </PARAMS>
+          </REQUEST>
+        </REQUESTS>
+        <DIAG-DATA-DICTIONARY-SPEC>
+          <DTC-DOPS>
+            <DTC-DOP ID="DOP_DTC">
+              <SHORT-NAME>DTCs</SHORT-NAME>
+              <DTCS>
+                <DTC ID="DTC_P0123">
+                  <SHORT-NAME>ThrottleHigh</SHORT-NAME>
+                  <TROUBLE-CODE>291</TROUBLE-CODE>
+                  <DISPLAY-TROUBLE-CODE>P0123</DISPLAY-TROUBLE-CODE>
+                  <TEXT>Throttle position sensor high input</TEXT>
+                </DTC>
+              </DTCS>
+            </DTC-DOP>
+          </DTC-DOPS>
+        </DIAG-DATA-DICTIONARY-SPEC>
+      </BASE-VARIANT>
+    </BASE-VARIANTS>
+  </DIAG-LAYER-CONTAINER>
+</ODX>"#;
+
+    #[test]
+    fn test_parse_odx() {
+        let description = DiagDescription::parse_odx(ODX).unwrap();
+        assert_eq!(description.ecu_name.as_deref(), Some("Engine"));
+        assert_eq!(description.dids[&0xF190], "Vehicle Identification Number");
+        assert_eq!(description.routines[&ROUTINE_ERASE_MEMORY], "EraseMemory");
+        assert_eq!(description.dtcs[&0x000123].display, "P0123");
+    }
+
+    #[test]
+    fn test_describe_response() {
+        let description = DiagDescription::parse_odx(ODX).unwrap();
+
+        let vin = description.describe_response(&[0x62, 0xF1, 0x90, b'W', b'0']).unwrap();
+        assert_eq!(vin.service, "ReadDataByIdentifier");
+        assert_eq!(vin.name.as_deref(), Some("Vehicle Identification Number"));
+        assert_eq!(vin.payload, vec![b'W', b'0']);
+
+        let dtcs = description
+            .describe_response(&[0x59, 0x02, 0xFF, 0x00, 0x01, 0x23, 0x09, 0x00, 0x04, 0x56, 0x08])
+            .unwrap();
+        assert_eq!(dtcs.dtcs.len(), 2);
+        assert_eq!(dtcs.dtcs[0].text.as_deref(), Some("Throttle position sensor high input"));
+        assert_eq!(dtcs.dtcs[1].display, "000456");
+
+        let negative = description.describe_response(&[0x7F, 0x22, 0x31]).unwrap();
+        assert_eq!(negative.name.as_deref(), Some("Request out of range"));
+    }
+}
@@ -0,0 +1,331 @@
+use super::*;
+use crate::core::isotp::IsoTpConfig;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+/// Services never probed: session control is driven by the scan itself, while
+/// ECU reset, clearing DTCs and communication / DTC setting control change ECU state
+const SKIPPED_SERVICES: [u8; 5] = [
+    SID_DIAGNOSTIC_SESSION_CONTROL,
+    SID_ECU_RESET,
+    SID_CLEAR_DIAGNOSTIC_INFORMATION,
+    0x28,
+    0x85,
+];
+/// Services whose sub-functions are enumerated (all side-effect free)
+const SUB_FUNCTION_SERVICES: [u8; 3] = [SID_READ_DTC_INFORMATION, SID_SECURITY_ACCESS, SID_TESTER_PRESENT];
+
+fn default_sessions() -> Vec<u8> {
+    vec![SESSION_DEFAULT, SESSION_EXTENDED]
+}
+
+/// What to scan and where
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanConfig {
+    pub isotp: IsoTpConfig,
+    /// Sessions to scan in order
+    #[serde(default = "default_sessions")]
+    pub sessions: Vec<u8>,
+    /// Enumerate sub-functions of supported services
+    #[serde(default)]
+    pub scan_sub_functions: bool,
+    /// First and last DID to probe with ReadDataByIdentifier (None = skip)
+    #[serde(default)]
+    pub did_range: Option<(u16, u16)>,
+}
+
+/// How the ECU reacted to a probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeResult {
+    /// Positive response
+    Accepted,
+    /// Negative response showing the request was understood (e.g. wrong length, security)
+    Rejected(u8),
+    /// Not supported (NRC 0x11/0x12/0x31/0x7E/0x7F) or no response
+    Unsupported,
+}
+
+impl ProbeResult {
+    fn from_response(response: Option<&[u8]>, unsupported: &[u8]) -> Self {
+        match response {
+            Some([NEGATIVE_RESPONSE, _, nrc, ..]) if unsupported.contains(nrc) => Self::Unsupported,
+            Some([NEGATIVE_RESPONSE, _, nrc, ..]) => Self::Rejected(*nrc),
+            Some(_) => Self::Accepted,
+            None => Self::Unsupported,
+        }
+    }
+
+    fn is_supported(self) -> bool {
+        self != Self::Unsupported
+    }
+}
+
+/// Supported service or sub-function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceFinding {
+    pub sid: u8,
+    pub sub_function: Option<u8>,
+    pub result: ProbeResult,
+}
+
+/// DID that is readable or exists but is protected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidFinding {
+    pub did: u16,
+    pub result: ProbeResult,
+    pub data: Option<Vec<u8>>,
+}
+
+/// Findings for one diagnostic session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionReport {
+    pub session: u8,
+    /// Whether the ECU accepted the session change
+    pub entered: bool,
+    pub services: Vec<ServiceFinding>,
+    pub dids: Vec<DidFinding>,
+}
+
+/// Result of a complete scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanReport {
+    pub tx_id: u32,
+    pub rx_id: u32,
+    pub sessions: Vec<SessionReport>,
+}
+
+/// Progress of a scan (probes done out of the total for the current session)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub session: u8,
+    pub phase: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Enumerate services, sub-functions and DIDs accepted by an ECU in each session
+pub async fn scan<F>(
+    client: &mut UdsClient<'_>,
+    config: &ScanConfig,
+    cancel: &watch::Receiver<bool>,
+    mut on_progress: F,
+) -> Result<ScanReport, BootCanError>
+where
+    F: FnMut(ScanProgress) + Send,
+{
+    let check_cancel = || {
+        if *cancel.borrow() {
+            Err(BootCanError::Cancelled("UDS scan cancelled".to_string()))
+        } else {
+            Ok(())
+        }
+    };
+    let mut progress = |session: u8, phase: &str, done: usize, total: usize| {
+        on_progress(ScanProgress {
+            session,
+            phase: phase.to_string(),
+            done,
+            total,
+        })
+    };
+
+    let mut report = ScanReport {
+        tx_id: config.isotp.tx_id,
+        rx_id: config.isotp.rx_id,
+        sessions: Vec::new(),
+    };
+
+    for &session in config.sessions.iter() {
+        check_cancel()?;
+        let mut session_report = SessionReport {
+            session,
+            entered: false,
+            services: Vec::new(),
+            dids: Vec::new(),
+        };
+
+        let response = client
+            .request_raw(&[SID_DIAGNOSTIC_SESSION_CONTROL, session])
+            .await?;
+        session_report.entered = ProbeResult::from_response(response.as_deref(), &[]) == ProbeResult::Accepted;
+        if !session_report.entered {
+            report.sessions.push(session_report);
+            continue;
+        }
+
+        // Services: a bare request with sub-function 0 is enough to tell
+        // "not supported" apart from "supported but malformed"
+        let sids: Vec<u8> = (0x10..=0x3E)
+            .chain(0x83..=0x88)
+            .filter(|sid| !SKIPPED_SERVICES.contains(sid))
+            .collect();
+        for (i, &sid) in sids.iter().enumerate() {
+            check_cancel()?;
+            progress(session, "services", i, sids.len());
+            let response = client.request_raw(&[sid, 0x00]).await?;
+            let result = ProbeResult::from_response(
+                response.as_deref(),
+                &[NRC_SERVICE_NOT_SUPPORTED, NRC_SERVICE_NOT_SUPPORTED_IN_SESSION],
+            );
+            if result.is_supported() {
+                session_report.services.push(ServiceFinding {
+                    sid,
+                    sub_function: None,
+                    result,
+                });
+            }
+        }
+
+        if config.scan_sub_functions {
+            let services: Vec<u8> = session_report
+                .services
+                .iter()
+                .map(|s| s.sid)
+                .filter(|sid| SUB_FUNCTION_SERVICES.contains(sid))
+                .collect();
+            for sid in services {
+                for sub in 0x01..=0x7Fu8 {
+                    check_cancel()?;
+                    progress(session, "subFunctions", sub as usize, 0x7F);
+                    let response = client.request_raw(&[sid, sub]).await?;
+                    let result = ProbeResult::from_response(
+                        response.as_deref(),
+                        &[NRC_SUB_FUNCTION_NOT_SUPPORTED, NRC_SUB_FUNCTION_NOT_SUPPORTED_IN_SESSION],
+                    );
+                    if result.is_supported() {
+                        session_report.services.push(ServiceFinding {
+                            sid,
+                            sub_function: Some(sub),
+                            result,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some((first, last)) = config.did_range {
+            let total = last.saturating_sub(first) as usize + 1;
+            for (i, did) in (first..=last).enumerate() {
+                check_cancel()?;
+                progress(session, "dids", i, total);
+                let did_bytes = did.to_be_bytes();
+                let response = client
+                    .request_raw(&[SID_READ_DATA_BY_IDENTIFIER, did_bytes[0], did_bytes[1]])
+                    .await?;
+                let result = ProbeResult::from_response(response.as_deref(), &[NRC_REQUEST_OUT_OF_RANGE]);
+                if result.is_supported() {
+                    session_report.dids.push(DidFinding {
+                        did,
+                        result,
+                        data: response
+                            .filter(|_| result == ProbeResult::Accepted)
+                            .map(|r| r.get(3..).unwrap_or_default().to_vec()),
+                    });
+                }
+            }
+        }
+
+        tracing::info!(
+            "UDS scan 0x{:X} session 0x{:02X}: {} services, {} DIDs",
+            config.isotp.tx_id,
+            session,
+            session_report.services.len(),
+            session_report.dids.len()
+        );
+        report.sessions.push(session_report);
+    }
+
+    // Leave the ECU in the default session
+    let _ = client.request_raw(&[SID_DIAGNOSTIC_SESSION_CONTROL, SESSION_DEFAULT]).await;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::server::{EcuSimConfig, SimDid, UdsServer};
+    use super::*;
+    use crate::core::frame_link::FrameLink;
+    use crate::core::isotp::DEFAULT_MAX_MESSAGE_LEN;
+    use crate::core::message::CanFrame;
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Frame link answering single-frame requests from a simulated ECU
+    struct SimulatorLink {
+        server: UdsServer,
+        pending: Option<CanFrame>,
+    }
+
+    #[async_trait]
+    impl FrameLink for SimulatorLink {
+        async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError> {
+            let len = (frame.data[0] & 0x0F) as usize;
+            if let Some(response) = self.server.handle(&frame.data[1..1 + len]) {
+                let mut data = vec![response.len() as u8];
+                data.extend(response);
+                self.pending = Some(CanFrame::new(0x7E8, &data));
+            }
+            Ok(())
+        }
+
+        async fn recv(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+            Ok(self.pending.take())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_simulated_ecu() {
+        let isotp = IsoTpConfig {
+            tx_id: 0x7E0,
+            rx_id: 0x7E8,
+            is_extended: false,
+            padding: None,
+            block_size: 0,
+            st_min: 0,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        };
+        let sim = EcuSimConfig {
+            isotp: isotp.clone(),
+            dids: vec![SimDid {
+                did: 0xF190,
+                data: vec![0x42],
+                writable: false,
+            }],
+            dtcs: vec![],
+            security_levels: vec![],
+        };
+        let mut link = SimulatorLink {
+            server: UdsServer::new(&sim),
+            pending: None,
+        };
+        let mut client = UdsClient::new(&mut link, isotp.clone());
+        let config = ScanConfig {
+            isotp,
+            sessions: vec![SESSION_DEFAULT],
+            scan_sub_functions: false,
+            did_range: Some((0xF18F, 0xF191)),
+        };
+        let (_cancel_tx, cancel_rx) = watch::channel(false);
+
+        let report = scan(&mut client, &config, &cancel_rx, |_| {}).await.unwrap();
+        let session = &report.sessions[0];
+        assert!(session.entered);
+
+        let sids: Vec<u8> = session.services.iter().map(|s| s.sid).collect();
+        assert!(sids.contains(&SID_READ_DATA_BY_IDENTIFIER));
+        assert!(sids.contains(&SID_TESTER_PRESENT));
+        assert!(!sids.contains(&0x2F));
+
+        assert_eq!(session.dids.len(), 1);
+        assert_eq!(session.dids[0].did, 0xF190);
+        assert_eq!(session.dids[0].data, Some(vec![0x42]));
+    }
+}
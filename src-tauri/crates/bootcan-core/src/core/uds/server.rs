@@ -0,0 +1,415 @@
+use super::*;
+use crate::core::frame_link::FrameLink;
+use crate::core::isotp::{IsoTpConfig, IsoTpLink, DEFAULT_ISOTP_TIMEOUT};
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Maximum block length announced in RequestDownload responses
+const MAX_BLOCK_LENGTH: u16 = 0x0402;
+
+/// Data identifier served by the simulator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimDid {
+    pub did: u16,
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub writable: bool,
+}
+
+/// Stored DTC with its status byte
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimDtc {
+    /// 3-byte DTC number
+    pub dtc: u32,
+    pub status: u8,
+}
+
+/// Fixed seed/key pair for one security level (odd request seed sub-function)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimSecurityLevel {
+    pub level: u8,
+    pub seed: Vec<u8>,
+    pub key: Vec<u8>,
+}
+
+/// Response table of a simulated ECU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcuSimConfig {
+    /// `rx_id` is the request ID the ECU listens on, `tx_id` its response ID
+    pub isotp: IsoTpConfig,
+    #[serde(default)]
+    pub dids: Vec<SimDid>,
+    #[serde(default)]
+    pub dtcs: Vec<SimDtc>,
+    /// Security levels; when set, programming requires an unlocked level
+    #[serde(default)]
+    pub security_levels: Vec<SimSecurityLevel>,
+}
+
+/// UDS server state machine answering requests from a configured table
+pub struct UdsServer {
+    dids: HashMap<u16, SimDid>,
+    dtcs: Vec<SimDtc>,
+    security_levels: Vec<SimSecurityLevel>,
+    session: u8,
+    /// Level whose seed was sent and is waiting for a key
+    pending_seed: Option<u8>,
+    unlocked: Option<u8>,
+    /// Next expected block sequence counter of an active download
+    download_sequence: Option<u8>,
+    /// Bytes received by TransferData since the last RequestDownload
+    downloaded: usize,
+}
+
+impl UdsServer {
+    pub fn new(config: &EcuSimConfig) -> Self {
+        Self {
+            dids: config.dids.iter().map(|d| (d.did, d.clone())).collect(),
+            dtcs: config.dtcs.clone(),
+            security_levels: config.security_levels.clone(),
+            session: SESSION_DEFAULT,
+            pending_seed: None,
+            unlocked: None,
+            download_sequence: None,
+            downloaded: 0,
+        }
+    }
+
+    /// Handle one request; returns None when the response is suppressed
+    pub fn handle(&mut self, request: &[u8]) -> Option<Vec<u8>> {
+        let sid = *request.first()?;
+        let result = match sid {
+            SID_DIAGNOSTIC_SESSION_CONTROL => self.session_control(request),
+            SID_ECU_RESET => self.ecu_reset(request),
+            SID_TESTER_PRESENT => self.with_sub_function(request, |_| Ok(vec![])),
+            SID_READ_DATA_BY_IDENTIFIER => self.read_dids(request),
+            SID_WRITE_DATA_BY_IDENTIFIER => self.write_did(request),
+            SID_CLEAR_DIAGNOSTIC_INFORMATION => {
+                self.dtcs.clear();
+                Ok(vec![])
+            }
+            SID_READ_DTC_INFORMATION => self.read_dtcs(request),
+            SID_SECURITY_ACCESS => self.security_access(request),
+            SID_ROUTINE_CONTROL => self.routine_control(request),
+            SID_REQUEST_DOWNLOAD => self.request_download(request),
+            SID_TRANSFER_DATA => self.transfer_data(request),
+            SID_REQUEST_TRANSFER_EXIT => self.transfer_exit(),
+            _ => Err(NRC_SERVICE_NOT_SUPPORTED),
+        };
+
+        match result {
+            Ok(_) if Self::suppressed(request) => None,
+            Ok(payload) => {
+                let mut response = vec![sid.wrapping_add(POSITIVE_RESPONSE_OFFSET)];
+                response.extend(payload);
+                Some(response)
+            }
+            Err(nrc) => Some(vec![NEGATIVE_RESPONSE, sid, nrc]),
+        }
+    }
+
+    fn suppressed(request: &[u8]) -> bool {
+        matches!(request[0], SID_DIAGNOSTIC_SESSION_CONTROL | SID_ECU_RESET | SID_TESTER_PRESENT)
+            && request.get(1).is_some_and(|b| b & SUPPRESS_POSITIVE_RESPONSE != 0)
+    }
+
+    /// Run `f` with the sub-function (suppress bit removed) and echo it in the response
+    fn with_sub_function<F>(&mut self, request: &[u8], f: F) -> Result<Vec<u8>, u8>
+    where
+        F: FnOnce(&mut Self) -> Result<Vec<u8>, u8>,
+    {
+        if request.len() != 2 {
+            return Err(NRC_INCORRECT_LENGTH);
+        }
+        let mut response = vec![request[1] & !SUPPRESS_POSITIVE_RESPONSE];
+        response.extend(f(self)?);
+        Ok(response)
+    }
+
+    fn session_control(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        let session = request.get(1).map(|b| b & !SUPPRESS_POSITIVE_RESPONSE);
+        self.with_sub_function(request, |server| match session {
+            Some(s @ (SESSION_DEFAULT | SESSION_PROGRAMMING | SESSION_EXTENDED)) => {
+                server.session = s;
+                server.pending_seed = None;
+                server.unlocked = None;
+                server.download_sequence = None;
+                // P2 = 50 ms, P2* = 5000 ms (10 ms resolution)
+                Ok(vec![0x00, 0x32, 0x01, 0xF4])
+            }
+            _ => Err(NRC_SUB_FUNCTION_NOT_SUPPORTED),
+        })
+    }
+
+    fn ecu_reset(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        self.with_sub_function(request, |server| {
+            server.session = SESSION_DEFAULT;
+            server.pending_seed = None;
+            server.unlocked = None;
+            server.download_sequence = None;
+            Ok(vec![])
+        })
+    }
+
+    fn read_dids(&self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        if request.len() < 3 || !(request.len() - 1).is_multiple_of(2) {
+            return Err(NRC_INCORRECT_LENGTH);
+        }
+        let mut response = Vec::new();
+        for did in request[1..].chunks(2) {
+            let entry = self
+                .dids
+                .get(&u16::from_be_bytes([did[0], did[1]]))
+                .ok_or(NRC_REQUEST_OUT_OF_RANGE)?;
+            response.extend_from_slice(did);
+            response.extend_from_slice(&entry.data);
+        }
+        Ok(response)
+    }
+
+    fn write_did(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        if request.len() < 4 {
+            return Err(NRC_INCORRECT_LENGTH);
+        }
+        let did = u16::from_be_bytes([request[1], request[2]]);
+        let entry = self.dids.get_mut(&did).ok_or(NRC_REQUEST_OUT_OF_RANGE)?;
+        if !entry.writable {
+            return Err(NRC_REQUEST_OUT_OF_RANGE);
+        }
+        entry.data = request[3..].to_vec();
+        Ok(request[1..3].to_vec())
+    }
+
+    fn read_dtcs(&self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        match (request.get(1), request.get(2)) {
+            // reportNumberOfDTCByStatusMask
+            (Some(0x01), Some(mask)) => {
+                let count = self.dtcs.iter().filter(|d| d.status & mask != 0).count() as u16;
+                let count = count.to_be_bytes();
+                Ok(vec![0x01, 0xFF, 0x01, count[0], count[1]])
+            }
+            // reportDTCByStatusMask
+            (Some(0x02), Some(mask)) => {
+                let mut response = vec![0x02, 0xFF];
+                for dtc in self.dtcs.iter().filter(|d| d.status & mask != 0) {
+                    response.extend_from_slice(&dtc.dtc.to_be_bytes()[1..]);
+                    response.push(dtc.status);
+                }
+                Ok(response)
+            }
+            (Some(_), Some(_)) => Err(NRC_SUB_FUNCTION_NOT_SUPPORTED),
+            _ => Err(NRC_INCORRECT_LENGTH),
+        }
+    }
+
+    fn security_access(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        let sub = *request.get(1).ok_or(NRC_INCORRECT_LENGTH)?;
+        if sub == 0 || sub > 0x7E {
+            return Err(NRC_SUB_FUNCTION_NOT_SUPPORTED);
+        }
+        if sub % 2 == 1 {
+            let level = self
+                .security_levels
+                .iter()
+                .find(|l| l.level == sub)
+                .ok_or(NRC_SUB_FUNCTION_NOT_SUPPORTED)?;
+            let mut response = vec![sub];
+            if self.unlocked == Some(sub) {
+                // Already unlocked: zero seed
+                response.resize(1 + level.seed.len(), 0);
+            } else {
+                response.extend_from_slice(&level.seed);
+                self.pending_seed = Some(sub);
+            }
+            Ok(response)
+        } else {
+            let level = sub - 1;
+            if self.pending_seed != Some(level) {
+                return Err(NRC_REQUEST_SEQUENCE_ERROR);
+            }
+            self.pending_seed = None;
+            let expected = self.security_levels.iter().find(|l| l.level == level).map(|l| &l.key);
+            if expected.map(|k| k.as_slice()) != Some(&request[2..]) {
+                return Err(NRC_INVALID_KEY);
+            }
+            self.unlocked = Some(level);
+            Ok(vec![sub])
+        }
+    }
+
+    /// Programming services need the programming session and, if configured, security access
+    fn check_programming(&self) -> Result<(), u8> {
+        if self.session != SESSION_PROGRAMMING {
+            return Err(NRC_SERVICE_NOT_SUPPORTED_IN_SESSION);
+        }
+        if !self.security_levels.is_empty() && self.unlocked.is_none() {
+            return Err(NRC_SECURITY_ACCESS_DENIED);
+        }
+        Ok(())
+    }
+
+    fn routine_control(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        if request.len() < 4 {
+            return Err(NRC_INCORRECT_LENGTH);
+        }
+        if request[1] != 0x01 {
+            return Err(NRC_SUB_FUNCTION_NOT_SUPPORTED);
+        }
+        match u16::from_be_bytes([request[2], request[3]]) {
+            ROUTINE_ERASE_MEMORY | ROUTINE_CHECK_PROGRAMMING_DEPENDENCIES => {
+                self.check_programming()?;
+                Ok(vec![request[1], request[2], request[3], 0x00])
+            }
+            _ => Err(NRC_REQUEST_OUT_OF_RANGE),
+        }
+    }
+
+    fn request_download(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        self.check_programming()?;
+        let format = *request.get(2).ok_or(NRC_INCORRECT_LENGTH)?;
+        let expected_len = 3 + (format & 0x0F) as usize + (format >> 4) as usize;
+        if request.len() != expected_len {
+            return Err(NRC_INCORRECT_LENGTH);
+        }
+        self.download_sequence = Some(1);
+        self.downloaded = 0;
+        let block = MAX_BLOCK_LENGTH.to_be_bytes();
+        Ok(vec![0x20, block[0], block[1]])
+    }
+
+    fn transfer_data(&mut self, request: &[u8]) -> Result<Vec<u8>, u8> {
+        let expected = self.download_sequence.ok_or(NRC_REQUEST_SEQUENCE_ERROR)?;
+        let sequence = *request.get(1).ok_or(NRC_INCORRECT_LENGTH)?;
+        if sequence != expected {
+            return Err(NRC_WRONG_BLOCK_SEQUENCE_COUNTER);
+        }
+        if request.len() > MAX_BLOCK_LENGTH as usize {
+            return Err(NRC_INCORRECT_LENGTH);
+        }
+        self.downloaded += request.len() - 2;
+        self.download_sequence = Some(sequence.wrapping_add(1));
+        Ok(vec![sequence])
+    }
+
+    fn transfer_exit(&mut self) -> Result<Vec<u8>, u8> {
+        self.download_sequence.take().ok_or(NRC_REQUEST_SEQUENCE_ERROR)?;
+        tracing::info!("ECU simulator received download of {} bytes", self.downloaded);
+        Ok(vec![])
+    }
+}
+
+/// Serve UDS requests on a frame link until cancelled
+pub async fn run_server<L: FrameLink>(
+    mut link: L,
+    config: EcuSimConfig,
+    mut cancel: watch::Receiver<bool>,
+) -> Result<(), BootCanError> {
+    let mut server = UdsServer::new(&config);
+    let mut transport = IsoTpLink::new(&mut link, config.isotp.clone(), DEFAULT_ISOTP_TIMEOUT);
+
+    loop {
+        let request = tokio::select! {
+            request = transport.recv(Duration::from_secs(1)) => request,
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+
+        let request = match request {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("ECU simulator 0x{:X}: {}", config.isotp.rx_id, e);
+                continue;
+            }
+        };
+
+        if let Some(response) = server.handle(&request) {
+            transport.send(&response).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::isotp::DEFAULT_MAX_MESSAGE_LEN;
+
+    fn config() -> EcuSimConfig {
+        EcuSimConfig {
+            isotp: IsoTpConfig {
+                tx_id: 0x7E8,
+                rx_id: 0x7E0,
+                is_extended: false,
+                padding: None,
+                block_size: 0,
+                st_min: 0,
+                max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            },
+            dids: vec![SimDid {
+                did: 0xF190,
+                data: b"VIN".to_vec(),
+                writable: false,
+            }],
+            dtcs: vec![
+                SimDtc { dtc: 0x012345, status: 0x09 },
+                SimDtc { dtc: 0x0ABCDE, status: 0x00 },
+            ],
+            security_levels: vec![SimSecurityLevel {
+                level: 0x01,
+                seed: vec![0x12, 0x34],
+                key: vec![0xAB, 0xCD],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_read_did_and_dtcs() {
+        let mut server = UdsServer::new(&config());
+        assert_eq!(server.handle(&[0x22, 0xF1, 0x90]), Some(vec![0x62, 0xF1, 0x90, b'V', b'I', b'N']));
+        assert_eq!(server.handle(&[0x22, 0xF1, 0x91]), Some(vec![0x7F, 0x22, 0x31]));
+        assert_eq!(
+            server.handle(&[0x19, 0x02, 0xFF]),
+            Some(vec![0x59, 0x02, 0xFF, 0x01, 0x23, 0x45, 0x09])
+        );
+        assert_eq!(server.handle(&[0x3E, 0x80]), None);
+    }
+
+    #[test]
+    fn test_security_access() {
+        let mut server = UdsServer::new(&config());
+        assert_eq!(server.handle(&[0x27, 0x02, 0xAB, 0xCD]), Some(vec![0x7F, 0x27, 0x24]));
+        assert_eq!(server.handle(&[0x27, 0x01]), Some(vec![0x67, 0x01, 0x12, 0x34]));
+        assert_eq!(server.handle(&[0x27, 0x02, 0x00, 0x00]), Some(vec![0x7F, 0x27, 0x35]));
+        server.handle(&[0x27, 0x01]);
+        assert_eq!(server.handle(&[0x27, 0x02, 0xAB, 0xCD]), Some(vec![0x67, 0x02]));
+    }
+
+    #[test]
+    fn test_programming_flow() {
+        let mut server = UdsServer::new(&config());
+        let download = [0x34, 0x00, 0x44, 0, 0, 0x80, 0, 0, 0, 0, 4];
+        assert_eq!(server.handle(&download), Some(vec![0x7F, 0x34, 0x7F]));
+
+        server.handle(&[0x10, 0x02]);
+        assert_eq!(server.handle(&download), Some(vec![0x7F, 0x34, 0x33]));
+
+        server.handle(&[0x27, 0x01]);
+        server.handle(&[0x27, 0x02, 0xAB, 0xCD]);
+        assert_eq!(server.handle(&download), Some(vec![0x74, 0x20, 0x04, 0x02]));
+        assert_eq!(server.handle(&[0x36, 0x02, 1, 2]), Some(vec![0x7F, 0x36, 0x73]));
+        assert_eq!(server.handle(&[0x36, 0x01, 1, 2, 3, 4]), Some(vec![0x76, 0x01]));
+        assert_eq!(server.handle(&[0x37]), Some(vec![0x77]));
+        assert_eq!(server.downloaded, 4);
+    }
+}
@@ -0,0 +1,211 @@
+use super::UdsClient;
+use crate::core::frame_link::FrameLink;
+use crate::core::isotp::IsoTpConfig;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Description of an open diagnostic session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UdsSessionInfo {
+    pub id: String,
+    pub channel_id: String,
+    pub tx_id: u32,
+    pub rx_id: u32,
+    /// TesterPresent interval keeping the ECU in its session (None = disabled)
+    pub tester_present_ms: Option<u64>,
+}
+
+struct SessionRequest {
+    data: Vec<u8>,
+    reply: oneshot::Sender<Result<Vec<u8>, BootCanError>>,
+}
+
+/// Diagnostic session with one ECU, served by its own task
+///
+/// Every session owns its frame link, ISO-TP state and TesterPresent timer, so
+/// sessions to different request/response ID pairs on the same channel run
+/// independently. Dropping the session ends the task.
+pub struct UdsSession {
+    info: UdsSessionInfo,
+    requests: mpsc::Sender<SessionRequest>,
+}
+
+impl UdsSession {
+    /// Session ID derived from channel and ID pair
+    pub fn session_id(channel_id: &str, config: &IsoTpConfig) -> String {
+        format!("{}:{:X}:{:X}", channel_id, config.tx_id, config.rx_id)
+    }
+
+    /// Start the session task on `link`
+    pub fn spawn<L>(link: L, channel_id: String, config: IsoTpConfig, tester_present: Option<Duration>) -> Self
+    where
+        L: FrameLink + 'static,
+    {
+        let info = UdsSessionInfo {
+            id: Self::session_id(&channel_id, &config),
+            channel_id,
+            tx_id: config.tx_id,
+            rx_id: config.rx_id,
+            tester_present_ms: tester_present.map(|d| d.as_millis() as u64),
+        };
+        let (requests, mut rx) = mpsc::channel::<SessionRequest>(16);
+        let session_id = info.id.clone();
+
+        tokio::spawn(async move {
+            let mut link = link;
+            let mut client = UdsClient::new(&mut link, config);
+            let period = tester_present.unwrap_or(Duration::from_secs(3600));
+            let mut keepalive = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+
+            loop {
+                tokio::select! {
+                    request = rx.recv() => {
+                        let Some(request) = request else {
+                            break;
+                        };
+                        let result = client.request(&request.data).await;
+                        let _ = request.reply.send(result);
+                        // Any request keeps the ECU session alive
+                        keepalive.reset();
+                    }
+                    _ = keepalive.tick(), if tester_present.is_some() => {
+                        if let Err(e) = client.tester_present().await {
+                            tracing::warn!("TesterPresent failed for UDS session {}: {}", session_id, e);
+                        }
+                    }
+                }
+            }
+
+            tracing::info!("UDS session {} closed", session_id);
+        });
+
+        Self { info, requests }
+    }
+
+    pub fn info(&self) -> &UdsSessionInfo {
+        &self.info
+    }
+
+    /// Get a handle for sending requests without holding on to the session
+    pub fn requester(&self) -> UdsRequester {
+        UdsRequester {
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+/// Cloneable handle for sending requests to a session
+#[derive(Clone)]
+pub struct UdsRequester {
+    requests: mpsc::Sender<SessionRequest>,
+}
+
+impl UdsRequester {
+    /// Send a request and wait for the positive response
+    pub async fn request(&self, data: Vec<u8>) -> Result<Vec<u8>, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.requests
+            .send(SessionRequest { data, reply })
+            .await
+            .map_err(|_| BootCanError::NotConnected("UDS session closed".to_string()))?;
+        response
+            .await
+            .map_err(|_| BootCanError::NotConnected("UDS session closed".to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::isotp::DEFAULT_MAX_MESSAGE_LEN;
+    use crate::core::message::CanFrame;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// ECU answering ReadDataByIdentifier and recording TesterPresent frames
+    struct EchoEcu {
+        rx_id: u32,
+        sent: Arc<Mutex<Vec<CanFrame>>>,
+        pending: Option<CanFrame>,
+    }
+
+    #[async_trait]
+    impl FrameLink for EchoEcu {
+        async fn send(&mut self, frame: CanFrame) -> Result<(), BootCanError> {
+            if frame.data[1] == 0x22 {
+                self.pending = Some(CanFrame::new(self.rx_id, &[0x04, 0x62, frame.data[2], frame.data[3], 0x01]));
+            }
+            self.sent.lock().unwrap().push(frame);
+            Ok(())
+        }
+
+        async fn recv(&mut self, timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+            match self.pending.take() {
+                Some(frame) => Ok(Some(frame)),
+                None => {
+                    tokio::time::sleep(timeout).await;
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn config(tx_id: u32, rx_id: u32) -> IsoTpConfig {
+        IsoTpConfig {
+            tx_id,
+            rx_id,
+            is_extended: false,
+            padding: None,
+            block_size: 0,
+            st_min: 0,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_independent_sessions() {
+        let sent_a = Arc::new(Mutex::new(vec![]));
+        let sent_b = Arc::new(Mutex::new(vec![]));
+        let a = UdsSession::spawn(
+            EchoEcu { rx_id: 0x7E8, sent: sent_a.clone(), pending: None },
+            "can0".to_string(),
+            config(0x7E0, 0x7E8),
+            None,
+        );
+        let b = UdsSession::spawn(
+            EchoEcu { rx_id: 0x7E9, sent: sent_b.clone(), pending: None },
+            "can0".to_string(),
+            config(0x7E1, 0x7E9),
+            None,
+        );
+
+        let (requester_a, requester_b) = (a.requester(), b.requester());
+        let (ra, rb) = tokio::join!(
+            requester_a.request(vec![0x22, 0xF1, 0x90]),
+            requester_b.request(vec![0x22, 0xF1, 0x8C]),
+        );
+        assert_eq!(ra.unwrap(), vec![0x62, 0xF1, 0x90, 0x01]);
+        assert_eq!(rb.unwrap(), vec![0x62, 0xF1, 0x8C, 0x01]);
+        assert_eq!(a.info().id, "can0:7E0:7E8");
+        assert_eq!(sent_b.lock().unwrap()[0].id, 0x7E1);
+    }
+
+    #[tokio::test]
+    async fn test_tester_present_keepalive() {
+        let sent = Arc::new(Mutex::new(vec![]));
+        let _session = UdsSession::spawn(
+            EchoEcu { rx_id: 0x7E8, sent: sent.clone(), pending: None },
+            "can0".to_string(),
+            config(0x7E0, 0x7E8),
+            Some(Duration::from_millis(20)),
+        );
+
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        let sent = sent.lock().unwrap();
+        assert!(sent.len() >= 2);
+        assert_eq!(sent[0].data, vec![0x02, 0x3E, 0x80]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Named variables referenced as `${NAME}`
+//!
+//! A project defines values such as `TESTER_ID = 0x7E0` once; transmit
+//! templates and scenarios refer to them, so retargeting a project to another
+//! vehicle only means editing the variables. A string that is exactly one
+//! reference takes the variable's value, as a number when it reads as one
+//! (decimal or `0x` hex); references inside longer strings are replaced as text.
+
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Variables by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Variables(BTreeMap<String, String>);
+
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn undefined(name: &str) -> BootCanError {
+    BootCanError::NotFound(format!("Undefined variable: {}", name))
+}
+
+/// Number written as decimal, `0x` hex or a float
+fn parse_number(value: &str) -> Option<Value> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        return u64::from_str_radix(hex, 16).ok().map(Value::from);
+    }
+    if let Ok(int) = value.parse::<i64>() {
+        return Some(Value::from(int));
+    }
+    value.parse::<f64>().ok().and_then(|f| serde_json::Number::from_f64(f).map(Value::Number))
+}
+
+impl Variables {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), BootCanError> {
+        if !valid_name(name) {
+            return Err(BootCanError::InvalidInput(format!("Invalid variable name: {}", name)));
+        }
+        self.0.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Check every variable name
+    pub fn validate(&self) -> Result<(), BootCanError> {
+        match self.0.keys().find(|name| !valid_name(name)) {
+            Some(name) => Err(BootCanError::InvalidInput(format!("Invalid variable name: {}", name))),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the references in a string
+    pub fn expand_str(&self, text: &str) -> Result<String, BootCanError> {
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            expanded.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| BootCanError::Parse(format!("Unterminated variable reference in \"{}\"", text)))?;
+            let name = &rest[start + 2..start + end];
+            let value = self.get(name).ok_or_else(|| undefined(name))?;
+            expanded.push_str(value);
+            rest = &rest[start + end + 1..];
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+
+    /// Replace the references in every string of a JSON value
+    pub fn expand_value(&self, value: Value) -> Result<Value, BootCanError> {
+        Ok(match value {
+            Value::String(text) => {
+                let whole = text
+                    .strip_prefix("${")
+                    .and_then(|t| t.strip_suffix('}'))
+                    .filter(|name| valid_name(name));
+                match whole {
+                    Some(name) => {
+                        let value = self.get(name).ok_or_else(|| undefined(name))?;
+                        parse_number(value).unwrap_or_else(|| Value::String(value.to_string()))
+                    }
+                    None => Value::String(self.expand_str(&text)?),
+                }
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.expand_value(item))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, item)| Ok((key, self.expand_value(item)?)))
+                    .collect::<Result<_, BootCanError>>()?,
+            ),
+            other => other,
+        })
+    }
+
+    /// Expand a JSON value and deserialize the result
+    pub fn resolve<T: serde::de::DeserializeOwned>(&self, value: &Value) -> Result<T, BootCanError> {
+        let expanded = self.expand_value(value.clone())?;
+        serde_json::from_value(expanded).map_err(|e| BootCanError::InvalidInput(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn variables() -> Variables {
+        let mut variables = Variables::default();
+        variables.set("TESTER_ID", "0x7E0").unwrap();
+        variables.set("VIN", "WDB1234567").unwrap();
+        variables.set("SESSION", "3").unwrap();
+        variables
+    }
+
+    #[test]
+    fn test_expand_value() {
+        let expanded = variables()
+            .expand_value(json!({ "id": "${TESTER_ID}", "data": [2, 16, "${SESSION}"], "name": "VIN ${VIN}" }))
+            .unwrap();
+        assert_eq!(expanded, json!({ "id": 2016, "data": [2, 16, 3], "name": "VIN WDB1234567" }));
+        assert_eq!(variables().expand_value(json!("${VIN}")).unwrap(), json!("WDB1234567"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let variables = variables();
+        assert!(variables.expand_str("${ECU_ID}").unwrap_err().message().contains("ECU_ID"));
+        assert!(variables.expand_str("${TESTER_ID").is_err());
+        assert!(Variables::default().set("1ST", "x").is_err());
+        let invalid: Variables = serde_json::from_value(json!({ "A B": "1" })).unwrap();
+        assert!(invalid.validate().is_err());
+    }
+}
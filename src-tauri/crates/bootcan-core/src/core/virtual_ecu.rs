@@ -0,0 +1,550 @@
+//! Stateful simulated nodes
+//!
+//! A virtual ECU keeps named variables that are fed from received signals,
+//! change at per-state rates and are transmitted in periodic messages. Its
+//! behavior is a small state machine whose transitions depend on the variables,
+//! e.g. vehicle speed ramping up while a throttle signal is above zero.
+
+use super::dbc::DbcDatabase;
+use super::frame_link::FrameLink;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use parking_lot::RwLock;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+fn default_tick_ms() -> u64 {
+    10
+}
+
+/// Simulation variable with optional limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimVariable {
+    pub name: String,
+    #[serde(default)]
+    pub initial: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// DBC signal bound to a variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalBinding {
+    pub signal: String,
+    pub variable: String,
+    /// Amplitude of uniform noise added to transmitted values
+    #[serde(default)]
+    pub noise: f64,
+}
+
+/// Received message whose signals update variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimInput {
+    pub message_id: u32,
+    pub signals: Vec<SignalBinding>,
+}
+
+/// Periodically transmitted message encoded from variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimOutput {
+    pub message_id: u32,
+    #[serde(default)]
+    pub is_extended: bool,
+    pub period_ms: u64,
+    pub signals: Vec<SignalBinding>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+/// Comparison of a variable against a constant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    pub variable: String,
+    pub op: CompareOp,
+    pub value: f64,
+}
+
+/// Transition taken when all conditions hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transition {
+    pub to: String,
+    pub when: Vec<Condition>,
+}
+
+/// Continuous change of a variable while a state is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateRule {
+    pub variable: String,
+    /// Change per second
+    pub per_second: f64,
+    /// Multiply the rate by another variable (e.g. throttle position)
+    pub scale_by: Option<String>,
+}
+
+/// State of the node's state machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimState {
+    pub name: String,
+    /// Variables assigned when the state is entered
+    #[serde(default)]
+    pub on_enter: HashMap<String, f64>,
+    #[serde(default)]
+    pub rates: Vec<RateRule>,
+    /// Checked in order; the first one whose conditions hold is taken
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+}
+
+/// Description of a simulated node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualEcuConfig {
+    pub name: String,
+    pub variables: Vec<SimVariable>,
+    #[serde(default)]
+    pub inputs: Vec<SimInput>,
+    #[serde(default)]
+    pub outputs: Vec<SimOutput>,
+    /// First state is the initial one
+    pub states: Vec<SimState>,
+    /// Simulation step
+    #[serde(default = "default_tick_ms")]
+    pub tick_ms: u64,
+    /// Seed for reproducible output noise (random if unset)
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Advance simulation time by exactly one tick per step instead of the
+    /// measured wall-clock time
+    #[serde(default)]
+    pub virtual_clock: bool,
+}
+
+/// Snapshot of a running node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualEcuStatus {
+    pub name: String,
+    pub state: String,
+    pub variables: HashMap<String, f64>,
+}
+
+impl CompareOp {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Self::Lt => left < right,
+            Self::Le => left <= right,
+            Self::Gt => left > right,
+            Self::Ge => left >= right,
+            Self::Eq => left == right,
+            Self::Ne => left != right,
+        }
+    }
+}
+
+/// Running node as tracked by the application
+pub struct VirtualEcuHandle {
+    pub cancel: watch::Sender<bool>,
+    pub status: Arc<RwLock<VirtualEcuStatus>>,
+}
+
+/// Simulated node driven by `step`
+pub struct VirtualEcu {
+    config: VirtualEcuConfig,
+    database: DbcDatabase,
+    variables: HashMap<String, f64>,
+    state: usize,
+    /// Simulation time of the last transmission per output
+    last_sent: Vec<Option<f64>>,
+    time: f64,
+    rng: StdRng,
+}
+
+impl VirtualEcu {
+    /// Create a node; signals are looked up in `database`
+    pub fn new(config: VirtualEcuConfig, database: DbcDatabase) -> Result<Self, BootCanError> {
+        if config.states.is_empty() {
+            return Err(BootCanError::InvalidInput(format!("Virtual ECU {} has no states", config.name)));
+        }
+        let known = |name: &str| config.variables.iter().any(|v| v.name == name);
+        let state_names: Vec<&str> = config.states.iter().map(|s| s.name.as_str()).collect();
+
+        for state in &config.states {
+            let unknown = state
+                .on_enter
+                .keys()
+                .map(String::as_str)
+                .chain(state.rates.iter().map(|r| r.variable.as_str()))
+                .chain(state.rates.iter().filter_map(|r| r.scale_by.as_deref()))
+                .chain(state.transitions.iter().flat_map(|t| t.when.iter().map(|c| c.variable.as_str())))
+                .find(|name| !known(name));
+            if let Some(name) = unknown {
+                return Err(BootCanError::NotFound(format!("State {} uses unknown variable {}", state.name, name)));
+            }
+            if let Some(t) = state.transitions.iter().find(|t| !state_names.contains(&t.to.as_str())) {
+                return Err(BootCanError::NotFound(format!(
+                    "State {} has a transition to unknown state {}", state.name, t.to
+                )));
+            }
+        }
+
+        let messages = config
+            .inputs
+            .iter()
+            .map(|i| (i.message_id, &i.signals))
+            .chain(config.outputs.iter().map(|o| (o.message_id, &o.signals)));
+        for (message_id, bindings) in messages {
+            let message = database
+                .get_message(message_id)
+                .ok_or_else(|| BootCanError::NotFound(format!("Message 0x{:X} is not in the DBC", message_id)))?;
+            for binding in bindings.iter() {
+                if !message.signals.iter().any(|s| s.name == binding.signal) {
+                    return Err(BootCanError::NotFound(format!(
+                        "Signal {} is not in message {}", binding.signal, message.name
+                    )));
+                }
+                if !known(&binding.variable) {
+                    return Err(BootCanError::NotFound(format!(
+                        "Signal {} is bound to unknown variable {}", binding.signal, binding.variable
+                    )));
+                }
+            }
+        }
+
+        let variables = config.variables.iter().map(|v| (v.name.clone(), v.initial)).collect();
+        let last_sent = vec![None; config.outputs.len()];
+        let rng = config.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        let mut ecu = Self {
+            config,
+            database,
+            variables,
+            state: 0,
+            last_sent,
+            time: 0.0,
+            rng,
+        };
+        ecu.enter_state(0);
+        Ok(ecu)
+    }
+
+    pub fn tick(&self) -> Duration {
+        Duration::from_millis(self.config.tick_ms.max(1))
+    }
+
+    pub fn uses_virtual_clock(&self) -> bool {
+        self.config.virtual_clock
+    }
+
+    /// Update input variables from a received frame
+    pub fn handle_frame(&mut self, frame: &CanFrame) {
+        let Some(input) = self.config.inputs.iter().find(|i| i.message_id == frame.id) else {
+            return;
+        };
+        let values: Vec<(String, f64)> = input
+            .signals
+            .iter()
+            .filter_map(|binding| {
+                let decoded = self.database.decode_signal(frame.id, &binding.signal, &frame.data)?;
+                Some((binding.variable.clone(), decoded.physical_value))
+            })
+            .collect();
+        for (variable, value) in values {
+            self.set_variable(&variable, value);
+        }
+    }
+
+    /// Advance the simulation by `dt` seconds and return the frames due for transmission
+    pub fn step(&mut self, dt: f64) -> Vec<CanFrame> {
+        self.time += dt;
+
+        let rates: Vec<(String, f64)> = self.config.states[self.state]
+            .rates
+            .iter()
+            .map(|rate| {
+                let scale = rate.scale_by.as_ref().map_or(1.0, |v| self.variables[v]);
+                (rate.variable.clone(), rate.per_second * scale * dt)
+            })
+            .collect();
+        for (variable, delta) in rates {
+            let value = self.variables[&variable] + delta;
+            self.set_variable(&variable, value);
+        }
+
+        let next = self.config.states[self.state].transitions.iter().find(|t| {
+            t.when
+                .iter()
+                .all(|c| c.op.holds(self.variables[&c.variable], c.value))
+        });
+        if let Some(next) = next.and_then(|t| self.config.states.iter().position(|s| s.name == t.to)) {
+            if next != self.state {
+                self.enter_state(next);
+            }
+        }
+
+        self.due_outputs()
+    }
+
+    pub fn status(&self) -> VirtualEcuStatus {
+        VirtualEcuStatus {
+            name: self.config.name.clone(),
+            state: self.config.states[self.state].name.clone(),
+            variables: self.variables.clone(),
+        }
+    }
+
+    fn enter_state(&mut self, index: usize) {
+        self.state = index;
+        let assignments: Vec<(String, f64)> = self.config.states[index]
+            .on_enter
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        for (variable, value) in assignments {
+            self.set_variable(&variable, value);
+        }
+    }
+
+    /// Assign a variable, clamped to its limits
+    fn set_variable(&mut self, name: &str, value: f64) {
+        let Some(definition) = self.config.variables.iter().find(|v| v.name == name) else {
+            return;
+        };
+        let value = value
+            .max(definition.min.unwrap_or(f64::NEG_INFINITY))
+            .min(definition.max.unwrap_or(f64::INFINITY));
+        self.variables.insert(name.to_string(), value);
+    }
+
+    fn due_outputs(&mut self) -> Vec<CanFrame> {
+        let mut frames = Vec::new();
+        for (output, last_sent) in self.config.outputs.iter().zip(self.last_sent.iter_mut()) {
+            let period = output.period_ms as f64 / 1000.0;
+            if last_sent.is_some_and(|t| self.time - t < period - 1e-9) {
+                continue;
+            }
+            *last_sent = Some(self.time);
+
+            let dlc = self.database.get_message(output.message_id).map_or(8, |m| m.dlc);
+            let mut data = vec![0u8; dlc as usize];
+            for binding in &output.signals {
+                let mut value = self.variables[&binding.variable];
+                if binding.noise > 0.0 {
+                    value += self.rng.gen_range(-binding.noise..=binding.noise);
+                }
+                self.database
+                    .encode_signal(output.message_id, &binding.signal, &mut data, value);
+            }
+            frames.push(if output.is_extended {
+                CanFrame::new_extended(output.message_id, &data)
+            } else {
+                CanFrame::new(output.message_id, &data)
+            });
+        }
+        frames
+    }
+}
+
+/// Run a virtual ECU on `link` until cancelled, publishing its state to `status`
+pub async fn run_virtual_ecu<L: FrameLink>(
+    mut link: L,
+    mut ecu: VirtualEcu,
+    status: Arc<RwLock<VirtualEcuStatus>>,
+    mut cancel: watch::Receiver<bool>,
+) -> Result<(), BootCanError> {
+    let tick = ecu.tick();
+    let mut interval = tokio::time::interval(tick);
+    let mut last_step = tokio::time::Instant::now();
+
+    loop {
+        let received = tokio::select! {
+            frame = link.recv(tick) => frame?,
+            _ = interval.tick() => None,
+            _ = cancel.changed() => {
+                if *cancel.borrow() {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+
+        if let Some(frame) = received {
+            ecu.handle_frame(&frame);
+        }
+        if last_step.elapsed() < tick {
+            continue;
+        }
+
+        let now = tokio::time::Instant::now();
+        let dt = if ecu.uses_virtual_clock() { tick } else { now.duration_since(last_step) };
+        let frames = ecu.step(dt.as_secs_f64());
+        last_step = now;
+        for frame in frames {
+            link.send(frame).await?;
+        }
+        *status.write() = ecu.status();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::dbc::DbcParser;
+
+    const DBC: &str = r#"VERSION ""
+
+BO_ 256 Pedals: 8 Vector__XXX
+ SG_ Throttle : 0|8@1+ (1,0) [0|100] "%" Vector__XXX
+
+BO_ 512 Vehicle: 8 Vector__XXX
+ SG_ Speed : 0|16@1+ (0.1,0) [0|300] "km/h" Vector__XXX
+"#;
+
+    fn config() -> VirtualEcuConfig {
+        let binding = |signal: &str, variable: &str| SignalBinding {
+            signal: signal.to_string(),
+            variable: variable.to_string(),
+            noise: 0.0,
+        };
+        let variable = |name: &str, max: f64| SimVariable {
+            name: name.to_string(),
+            initial: 0.0,
+            min: Some(0.0),
+            max: Some(max),
+        };
+        let condition = |op, value| Condition {
+            variable: "throttle".to_string(),
+            op,
+            value,
+        };
+
+        VirtualEcuConfig {
+            name: "Vehicle".to_string(),
+            variables: vec![variable("throttle", 100.0), variable("speed", 200.0)],
+            inputs: vec![SimInput {
+                message_id: 256,
+                signals: vec![binding("Throttle", "throttle")],
+            }],
+            outputs: vec![SimOutput {
+                message_id: 512,
+                is_extended: false,
+                period_ms: 100,
+                signals: vec![binding("Speed", "speed")],
+            }],
+            states: vec![
+                SimState {
+                    name: "coasting".to_string(),
+                    on_enter: HashMap::new(),
+                    rates: vec![RateRule {
+                        variable: "speed".to_string(),
+                        per_second: -10.0,
+                        scale_by: None,
+                    }],
+                    transitions: vec![Transition {
+                        to: "accelerating".to_string(),
+                        when: vec![condition(CompareOp::Gt, 0.0)],
+                    }],
+                },
+                SimState {
+                    name: "accelerating".to_string(),
+                    on_enter: HashMap::new(),
+                    // 0.5 km/h per second per percent throttle
+                    rates: vec![RateRule {
+                        variable: "speed".to_string(),
+                        per_second: 0.5,
+                        scale_by: Some("throttle".to_string()),
+                    }],
+                    transitions: vec![Transition {
+                        to: "coasting".to_string(),
+                        when: vec![condition(CompareOp::Le, 0.0)],
+                    }],
+                },
+            ],
+            tick_ms: 10,
+            seed: None,
+            virtual_clock: false,
+        }
+    }
+
+    #[test]
+    fn test_speed_ramp() {
+        let database = DbcParser::parse(DBC).unwrap();
+        let mut ecu = VirtualEcu::new(config(), database).unwrap();
+
+        // Throttle 40% for one second: 20 km/h
+        ecu.handle_frame(&CanFrame::new(256, &[40, 0, 0, 0, 0, 0, 0, 0]));
+        ecu.step(0.0);
+        assert_eq!(ecu.status().state, "accelerating");
+        let mut last = Vec::new();
+        for _ in 0..10 {
+            last = ecu.step(0.1);
+        }
+        assert!((ecu.status().variables["speed"] - 20.0).abs() < 1e-6);
+        assert_eq!(last[0].id, 512);
+        assert_eq!(last[0].data[..2], [200, 0]);
+
+        // Released throttle: speed coasts down and stops at the limit
+        ecu.handle_frame(&CanFrame::new(256, &[0; 8]));
+        ecu.step(0.0);
+        assert_eq!(ecu.status().state, "coasting");
+        ecu.step(5.0);
+        assert_eq!(ecu.status().variables["speed"], 0.0);
+    }
+
+    #[test]
+    fn test_seeded_noise() {
+        let database = DbcParser::parse(DBC).unwrap();
+        let mut config = config();
+        config.variables[1].initial = 100.0;
+        config.outputs[0].signals[0].noise = 5.0;
+        config.seed = Some(7);
+
+        let run = || {
+            let mut ecu = VirtualEcu::new(config.clone(), database.clone()).unwrap();
+            (0..20)
+                .flat_map(|_| ecu.step(0.1))
+                .map(|frame| u16::from_le_bytes([frame.data[0], frame.data[1]]))
+                .collect::<Vec<u16>>()
+        };
+        let first = run();
+        assert_eq!(first, run());
+        // 100 km/h coasting down by 1 km/h per step, +-5 km/h of noise
+        assert!(first.iter().enumerate().all(|(i, raw)| {
+            let expected = 990.0 - 10.0 * i as f64;
+            (*raw as f64 - expected).abs() <= 50.0
+        }));
+        assert!(first.iter().enumerate().any(|(i, raw)| *raw != 990 - 10 * i as u16));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let database = DbcParser::parse(DBC).unwrap();
+        let mut bad = config();
+        bad.states[0].transitions[0].to = "flying".to_string();
+        assert!(VirtualEcu::new(bad, database.clone()).is_err());
+
+        let mut bad = config();
+        bad.outputs[0].signals[0].signal = "Rpm".to_string();
+        assert!(VirtualEcu::new(bad, database).is_err());
+    }
+}
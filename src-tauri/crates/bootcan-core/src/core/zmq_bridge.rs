@@ -0,0 +1,255 @@
+//! ZeroMQ PUB/SUB sockets carrying frames as msgpack
+//!
+//! Every message has two parts: the channel id as topic, so subscribers can
+//! filter by channel with a plain ZeroMQ subscription, and a msgpack map:
+//!
+//! | key          | type   | notes                                  |
+//! |--------------|--------|----------------------------------------|
+//! | `channel`    | str    | channel id                             |
+//! | `id`         | uint   | 11 or 29 bit identifier                |
+//! | `isExtended` | bool   | optional when injecting                |
+//! | `isRemote`   | bool   | optional when injecting                |
+//! | `data`       | bin    | an array of ints is accepted as well   |
+//! | `timestamp`  | float  | seconds since connect; ignored on SUB  |
+//! | `direction`  | str    | "rx" or "tx"; ignored on SUB           |
+//!
+//! Messages received on the optional SUB socket are transmitted on their
+//! channel. The channel comes from the map, or from the topic when absent.
+
+use super::channel::ChannelHandle;
+use super::message::CanFrame;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::{mpsc, watch};
+use zeromq::{PubSocket, Socket, SocketRecv, SocketSend, SubSocket, ZmqMessage};
+
+fn default_pub_endpoint() -> String {
+    "tcp://0.0.0.0:5555".to_string()
+}
+
+/// Settings of the ZeroMQ sockets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZmqBridgeConfig {
+    /// Endpoint the PUB socket binds to
+    #[serde(default = "default_pub_endpoint")]
+    pub pub_endpoint: String,
+    /// Endpoint a SUB socket binds to for injecting frames; no injection when unset
+    #[serde(default)]
+    pub sub_endpoint: Option<String>,
+    /// Channels to publish and to accept injected frames for
+    pub channels: Vec<String>,
+}
+
+/// Running sockets, kept in the application state
+pub struct ZmqBridgeHandle {
+    pub cancel: watch::Sender<bool>,
+}
+
+/// Frame in the msgpack schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZmqFrame {
+    #[serde(default)]
+    pub channel: String,
+    pub id: u32,
+    #[serde(default)]
+    pub is_extended: bool,
+    #[serde(default)]
+    pub is_remote: bool,
+    #[serde(default, with = "serde_bytes")]
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub timestamp: f64,
+    #[serde(default)]
+    pub direction: String,
+}
+
+impl From<&CanFrame> for ZmqFrame {
+    fn from(frame: &CanFrame) -> Self {
+        Self {
+            channel: frame.channel.to_string(),
+            id: frame.id,
+            is_extended: frame.is_extended,
+            is_remote: frame.is_remote,
+            data: frame.data.to_vec(),
+            timestamp: frame.timestamp,
+            direction: frame.direction.to_string(),
+        }
+    }
+}
+
+impl ZmqFrame {
+    fn into_can_frame(self) -> CanFrame {
+        let mut frame = if self.is_remote {
+            CanFrame::new_rtr(self.id, self.data.len().min(8) as u8)
+        } else if self.is_extended {
+            CanFrame::new_extended(self.id, &self.data)
+        } else {
+            CanFrame::new(self.id, &self.data)
+        };
+        frame.is_extended = self.is_extended || self.id > 0x7FF;
+        frame.channel = self.channel.into();
+        frame
+    }
+}
+
+/// Encode a frame as a topic + msgpack message
+pub fn encode_message(frame: &CanFrame) -> Result<ZmqMessage, BootCanError> {
+    let body = rmp_serde::to_vec_named(&ZmqFrame::from(frame)).map_err(|e| BootCanError::Other(e.to_string()))?;
+    let mut message = ZmqMessage::from(body);
+    message.prepend(&ZmqMessage::from(frame.channel.to_string()));
+    Ok(message)
+}
+
+fn empty_message() -> BootCanError {
+    BootCanError::Parse("Empty message".to_string())
+}
+
+/// Decode an injected message; the topic names the channel when the body does not
+pub fn decode_message(message: &ZmqMessage) -> Result<CanFrame, BootCanError> {
+    let body = message.iter().last().ok_or_else(empty_message)?;
+    let mut frame: ZmqFrame = rmp_serde::from_slice(body)
+        .map_err(|e| BootCanError::Parse(format!("Invalid frame: {}", e)))?;
+    if frame.channel.is_empty() && message.len() > 1 {
+        frame.channel = String::from_utf8_lossy(&message.get(0).ok_or_else(empty_message)?[..]).into_owned();
+    }
+    Ok(frame.into_can_frame())
+}
+
+/// Bind the PUB socket; returns it with the resolved endpoint
+pub async fn bind_publisher(endpoint: &str) -> Result<(PubSocket, String), BootCanError> {
+    let mut socket = PubSocket::new();
+    let bound = socket
+        .bind(endpoint)
+        .await
+        .map_err(|e| BootCanError::Io(format!("Failed to bind ZeroMQ PUB socket to {}: {}", endpoint, e)))?;
+    Ok((socket, bound.to_string()))
+}
+
+/// Bind a SUB socket subscribed to every topic
+pub async fn bind_subscriber(endpoint: &str) -> Result<SubSocket, BootCanError> {
+    let mut socket = SubSocket::new();
+    socket
+        .bind(endpoint)
+        .await
+        .map_err(|e| BootCanError::Io(format!("Failed to bind ZeroMQ SUB socket to {}: {}", endpoint, e)))?;
+    socket.subscribe("").await.map_err(|e| BootCanError::Io(e.to_string()))?;
+    Ok(socket)
+}
+
+/// Publish frames from `frames` until cancelled
+pub async fn run_publisher(
+    mut socket: PubSocket,
+    mut frames: mpsc::UnboundedReceiver<CanFrame>,
+    mut cancel: watch::Receiver<bool>,
+) {
+    loop {
+        let frame = tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(frame) => frame,
+                None => break,
+            },
+            _ = cancel.changed() => break,
+        };
+        let result = match encode_message(&frame) {
+            Ok(message) => socket.send(message).await.map_err(|e| BootCanError::Io(e.to_string())),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("ZeroMQ publish failed: {}", e);
+        }
+    }
+}
+
+/// Transmit frames received on the SUB socket until cancelled
+///
+/// Frames for channels outside `channels` are dropped.
+pub async fn run_subscriber(
+    mut socket: SubSocket,
+    channels: HashMap<String, ChannelHandle>,
+    mut cancel: watch::Receiver<bool>,
+) {
+    loop {
+        let message = tokio::select! {
+            message = socket.recv() => message,
+            _ = cancel.changed() => break,
+        };
+        let frame = match message.map_err(|e| BootCanError::Io(e.to_string())).and_then(|m| decode_message(&m)) {
+            Ok(frame) => frame,
+            Err(e) => {
+                tracing::warn!("ZeroMQ injection: {}", e);
+                continue;
+            }
+        };
+        let Some(channel) = channels.get(frame.channel.as_str()) else {
+            tracing::debug!("ZeroMQ injection for unknown channel {}", frame.channel);
+            continue;
+        };
+        if let Err(e) = channel.send(frame).await {
+            tracing::warn!("ZeroMQ injection failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let mut frame = CanFrame::new_extended(0x18FEF100, &[1, 2, 3]);
+        frame.channel = "can0".into();
+        let message = encode_message(&frame).unwrap();
+        assert_eq!(message.len(), 2);
+        assert_eq!(&message.get(0).unwrap()[..], b"can0");
+
+        let decoded = decode_message(&message).unwrap();
+        assert_eq!(decoded.id, 0x18FEF100);
+        assert!(decoded.is_extended);
+        assert_eq!(decoded.data, vec![1, 2, 3]);
+        assert_eq!(decoded.channel, "can0");
+
+        // Minimal injection body with data as an int array, channel from the topic
+        #[derive(Serialize)]
+        struct Minimal {
+            id: u32,
+            data: Vec<u8>,
+        }
+        let body = rmp_serde::to_vec_named(&Minimal { id: 0x123, data: vec![9] }).unwrap();
+        let mut message = ZmqMessage::from(body);
+        message.prepend(&ZmqMessage::from("can1"));
+        let decoded = decode_message(&message).unwrap();
+        assert_eq!((decoded.id, decoded.is_extended), (0x123, false));
+        assert_eq!(decoded.data, vec![9]);
+        assert_eq!(decoded.channel, "can1");
+    }
+
+    #[tokio::test]
+    async fn test_publish_to_subscriber() {
+        let (socket, endpoint) = bind_publisher("tcp://127.0.0.1:0").await.unwrap();
+        let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        let publisher = tokio::spawn(run_publisher(socket, frame_rx, cancel_rx));
+
+        let mut subscriber = SubSocket::new();
+        subscriber.connect(&endpoint).await.unwrap();
+        subscriber.subscribe("can0").await.unwrap();
+
+        // Subscriptions reach the publisher asynchronously, keep sending until one arrives
+        let mut frame = CanFrame::new(0x100, &[0xAA]);
+        frame.channel = "can0".into();
+        let received = loop {
+            frame_tx.send(frame.clone()).unwrap();
+            let wait = tokio::time::timeout(std::time::Duration::from_millis(50), subscriber.recv());
+            if let Ok(message) = wait.await {
+                break message.unwrap();
+            }
+        };
+        assert_eq!(decode_message(&received).unwrap().data, vec![0xAA]);
+
+        cancel_tx.send(true).unwrap();
+        publisher.await.unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+//! Error type of the core, the CLI and the app commands
+//!
+//! Errors reach the frontend as `{ kind, message }`, so callers can react to
+//! the kind (e.g. retry on `busy`) instead of matching message text. There is
+//! deliberately no conversion from `String`: every error picks its kind.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BootCanError {
+    /// The interface or its driver failed
+    #[error("{0}")]
+    Hal(String),
+    /// A file or message could not be parsed
+    #[error("{0}")]
+    Parse(String),
+    /// The channel or interface is not connected
+    #[error("{0}")]
+    NotConnected(String),
+    /// The resource is in use; trying again later may succeed
+    #[error("{0}")]
+    Busy(String),
+    /// Reading or writing a file or socket failed
+    #[error("{0}")]
+    Io(String),
+    /// A protocol exchange failed or was rejected by the other side
+    #[error("{0}")]
+    Protocol(String),
+    /// The SDO server aborted the transfer with `code` (CiA 301); a protocol error to the frontend
+    #[error("{message}")]
+    SdoAbort { code: u32, message: String },
+    /// No response arrived in time
+    #[error("{0}")]
+    Timeout(String),
+    /// A channel, job or other named item does not exist
+    #[error("{0}")]
+    NotFound(String),
+    /// An argument or configuration is out of range
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The operation was cancelled
+    #[error("{0}")]
+    Cancelled(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl BootCanError {
+    /// Name of the kind as seen by the frontend
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Hal(_) => "hal",
+            Self::Parse(_) => "parse",
+            Self::NotConnected(_) => "notConnected",
+            Self::Busy(_) => "busy",
+            Self::Io(_) => "io",
+            Self::Protocol(_) | Self::SdoAbort { .. } => "protocol",
+            Self::Timeout(_) => "timeout",
+            Self::NotFound(_) => "notFound",
+            Self::InvalidInput(_) => "invalidInput",
+            Self::Cancelled(_) => "cancelled",
+            Self::Other(_) => "other",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Hal(m)
+            | Self::Parse(m)
+            | Self::NotConnected(m)
+            | Self::Busy(m)
+            | Self::Io(m)
+            | Self::Protocol(m)
+            | Self::SdoAbort { message: m, .. }
+            | Self::Timeout(m)
+            | Self::NotFound(m)
+            | Self::InvalidInput(m)
+            | Self::Cancelled(m)
+            | Self::Other(m) => m,
+        }
+    }
+
+    /// Same kind of error, with `context` put in front of the message
+    pub fn context(self, context: impl std::fmt::Display) -> Self {
+        self.map_message(|m| format!("{}: {}", context, m))
+    }
+
+    /// One error for several: the kind of the first, the messages of all joined by `; `
+    pub fn join(errors: Vec<Self>) -> Option<Self> {
+        let mut errors = errors.into_iter();
+        let first = errors.next()?;
+        let rest: Vec<String> = errors.map(|e| e.message().to_string()).collect();
+        if rest.is_empty() {
+            return Some(first);
+        }
+        Some(first.map_message(|m| format!("{}; {}", m, rest.join("; "))))
+    }
+
+    fn map_message(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            Self::Hal(m) => Self::Hal(f(m)),
+            Self::Parse(m) => Self::Parse(f(m)),
+            Self::NotConnected(m) => Self::NotConnected(f(m)),
+            Self::Busy(m) => Self::Busy(f(m)),
+            Self::Io(m) => Self::Io(f(m)),
+            Self::Protocol(m) => Self::Protocol(f(m)),
+            Self::SdoAbort { code, message } => Self::SdoAbort { code, message: f(message) },
+            Self::Timeout(m) => Self::Timeout(f(m)),
+            Self::NotFound(m) => Self::NotFound(f(m)),
+            Self::InvalidInput(m) => Self::InvalidInput(f(m)),
+            Self::Cancelled(m) => Self::Cancelled(f(m)),
+            Self::Other(m) => Self::Other(f(m)),
+        }
+    }
+
+    /// Abort code, if an SDO server aborted the transfer
+    pub fn sdo_abort_code(&self) -> Option<u32> {
+        match self {
+            Self::SdoAbort { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Whether the same call may succeed when repeated later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Busy(_) | Self::Timeout(_))
+    }
+}
+
+impl Serialize for BootCanError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("BootCanError", 2)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", self.message())?;
+        error.end()
+    }
+}
+
+impl From<std::io::Error> for BootCanError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_with_kind_and_message() {
+        let error = BootCanError::NotConnected("Channel can0 is not connected".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "kind": "notConnected", "message": "Channel can0 is not connected" })
+        );
+        assert!(BootCanError::Busy("Flash in progress".to_string()).is_retryable());
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_io_errors_keep_their_kind() {
+        fn open() -> Result<(), BootCanError> {
+            std::fs::read("/nonexistent/bootcan.trc")?;
+            Ok(())
+        }
+        let error = open().unwrap_err().context("Trace");
+        assert_eq!(error.kind(), "io");
+        assert!(error.message().starts_with("Trace: "));
+    }
+
+    #[test]
+    fn test_joined_errors_keep_the_first_kind() {
+        assert!(BootCanError::join(Vec::new()).is_none());
+        let joined = BootCanError::join(vec![
+            BootCanError::Hal("can0 failed".to_string()),
+            BootCanError::Timeout("can1 timed out".to_string()),
+        ])
+        .unwrap();
+        assert_eq!((joined.kind(), joined.message()), ("hal", "can0 failed; can1 timed out"));
+    }
+}
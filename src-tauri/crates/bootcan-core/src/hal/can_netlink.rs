@@ -0,0 +1,167 @@
+//! Controller state of SocketCAN links over rtnetlink
+//!
+//! A minimal `RTM_GETLINK` client reading the CAN attributes of a link, the
+//! same `IFLA_CAN_STATE` and `IFLA_CAN_BERR_COUNTER` that `ip -details link`
+//! shows.
+
+use super::traits::{BusState, ControllerStatus};
+use nix::net::if_::if_nametoindex;
+use nix::sys::socket::{
+    recv, sendto, setsockopt, socket, sockopt, AddressFamily, MsgFlags, NetlinkAddr, SockFlag, SockProtocol,
+    SockType,
+};
+use nix::sys::time::{TimeVal, TimeValLike};
+use std::os::fd::AsRawFd;
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_GETLINK: u16 = 18;
+const NLM_F_REQUEST: u16 = 1;
+/// Length of `nlmsghdr` and of `ifinfomsg`
+const NLMSG_HDRLEN: usize = 16;
+const IFINFOMSG_LEN: usize = 16;
+const IFLA_LINKINFO: u16 = 18;
+const IFLA_INFO_KIND: u16 = 1;
+const IFLA_INFO_DATA: u16 = 2;
+const IFLA_CAN_STATE: u16 = 4;
+const IFLA_CAN_BERR_COUNTER: u16 = 8;
+/// Strips the nested and byte order flags from attribute types
+const NLA_TYPE_MASK: u16 = 0x3FFF;
+
+/// Read `IFLA_CAN_STATE` and `IFLA_CAN_BERR_COUNTER` of a CAN netdev
+///
+/// Blocks for the netlink round trip. None for non-CAN links such as vcan.
+pub fn read_controller_status(interface: &str) -> Option<ControllerStatus> {
+    let index = if_nametoindex(interface).ok()?;
+    let fd = socket(
+        AddressFamily::Netlink,
+        SockType::Raw,
+        SockFlag::SOCK_CLOEXEC,
+        SockProtocol::NetlinkRoute,
+    )
+    .ok()?;
+    setsockopt(&fd, sockopt::ReceiveTimeout, &TimeVal::milliseconds(200)).ok()?;
+
+    let request = link_request(index as i32);
+    sendto(fd.as_raw_fd(), &request, &NetlinkAddr::new(0, 0), MsgFlags::empty()).ok()?;
+    let mut reply = vec![0u8; 16 * 1024];
+    let len = recv(fd.as_raw_fd(), &mut reply, MsgFlags::empty()).ok()?;
+    parse_link_message(&reply[..len])
+}
+
+/// `RTM_GETLINK` request for the link with `index`
+fn link_request(index: i32) -> Vec<u8> {
+    let mut request = Vec::with_capacity(NLMSG_HDRLEN + IFINFOMSG_LEN);
+    request.extend_from_slice(&((NLMSG_HDRLEN + IFINFOMSG_LEN) as u32).to_ne_bytes());
+    request.extend_from_slice(&RTM_GETLINK.to_ne_bytes());
+    request.extend_from_slice(&NLM_F_REQUEST.to_ne_bytes());
+    // Sequence number and port ID, which the kernel fills in
+    request.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    // ifinfomsg: family AF_UNSPEC, padding, device type, index, flags, change mask
+    request.extend_from_slice(&[0; 4]);
+    request.extend_from_slice(&index.to_ne_bytes());
+    request.extend_from_slice(&[0; 8]);
+    request
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+/// Value of the attribute `kind` among the attributes in `data`
+fn attribute(data: &[u8], kind: u16) -> Option<&[u8]> {
+    let mut offset = 0;
+    while let (Some(len), Some(ty)) = (u16_at(data, offset), u16_at(data, offset + 2)) {
+        let len = len as usize;
+        let value = data.get(offset + 4..offset + len)?;
+        if ty & NLA_TYPE_MASK == kind {
+            return Some(value);
+        }
+        // Attributes are padded to 4 bytes; a length below the header would loop forever
+        offset += len.max(4).div_ceil(4) * 4;
+    }
+    None
+}
+
+/// Controller status in a `RTM_NEWLINK` reply; None for errors and non-CAN links
+fn parse_link_message(message: &[u8]) -> Option<ControllerStatus> {
+    let len = u32::from_ne_bytes(message.get(..4)?.try_into().ok()?) as usize;
+    if u16_at(message, 4)? != RTM_NEWLINK {
+        return None;
+    }
+    let link_info = attribute(message.get(NLMSG_HDRLEN + IFINFOMSG_LEN..len)?, IFLA_LINKINFO)?;
+    if attribute(link_info, IFLA_INFO_KIND)?.split(|&b| b == 0).next()? != b"can" {
+        return None;
+    }
+    let data = attribute(link_info, IFLA_INFO_DATA)?;
+    let state = attribute(data, IFLA_CAN_STATE)
+        .and_then(|state| Some(u32::from_ne_bytes(state.get(..4)?.try_into().ok()?)));
+    let state = match state {
+        Some(0) => BusState::Active,
+        Some(1) => BusState::Warning,
+        Some(2) => BusState::Passive,
+        Some(3) => BusState::BusOff,
+        _ => BusState::Unknown,
+    };
+    // Drivers without a berr-counter leave the attribute out
+    let counters = attribute(data, IFLA_CAN_BERR_COUNTER);
+    Some(ControllerStatus {
+        state,
+        tx_error_counter: counters.and_then(|c| u16_at(c, 0)),
+        rx_error_counter: counters.and_then(|c| u16_at(c, 2)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nla(kind: u16, value: &[u8]) -> Vec<u8> {
+        let mut attribute = ((4 + value.len()) as u16).to_ne_bytes().to_vec();
+        attribute.extend_from_slice(&kind.to_ne_bytes());
+        attribute.extend_from_slice(value);
+        attribute.resize(attribute.len().div_ceil(4) * 4, 0);
+        attribute
+    }
+
+    fn link_message(kind: &str, data: &[u8]) -> Vec<u8> {
+        let mut info = nla(IFLA_INFO_KIND, format!("{}\0", kind).as_bytes());
+        info.extend(nla(IFLA_INFO_DATA | 0x8000, data));
+        let mut message = link_request(5);
+        message[4..6].copy_from_slice(&RTM_NEWLINK.to_ne_bytes());
+        // An attribute before the link info, as the kernel sends many
+        message.extend(nla(3, b"can0\0"));
+        message.extend(nla(IFLA_LINKINFO | 0x8000, &info));
+        let len = message.len() as u32;
+        message[..4].copy_from_slice(&len.to_ne_bytes());
+        message
+    }
+
+    #[test]
+    fn test_parse_can_link() {
+        let mut data = nla(IFLA_CAN_STATE, &2u32.to_ne_bytes());
+        data.extend(nla(IFLA_CAN_BERR_COUNTER, &[128u16.to_ne_bytes(), 3u16.to_ne_bytes()].concat()));
+        let status = parse_link_message(&link_message("can", &data)).unwrap();
+        assert_eq!(status.state, BusState::Passive);
+        assert_eq!((status.tx_error_counter, status.rx_error_counter), (Some(128), Some(3)));
+
+        // Bus-off with TEC past 255, driver without berr-counter
+        let mut data = nla(IFLA_CAN_STATE, &3u32.to_ne_bytes());
+        data.extend(nla(IFLA_CAN_BERR_COUNTER, &[300u16.to_ne_bytes(), 0u16.to_ne_bytes()].concat()));
+        let status = parse_link_message(&link_message("can", &data)).unwrap();
+        assert_eq!((status.state, status.tx_error_counter), (BusState::BusOff, Some(300)));
+        let data = nla(IFLA_CAN_STATE, &0u32.to_ne_bytes());
+        assert_eq!(parse_link_message(&link_message("can", &data)).unwrap().rx_error_counter, None);
+    }
+
+    #[test]
+    fn test_parse_non_can_link() {
+        assert!(parse_link_message(&link_message("vcan", &[])).is_none());
+        // Error reply, truncated message
+        let mut error = link_message("can", &[]);
+        error[4..6].copy_from_slice(&2u16.to_ne_bytes());
+        assert!(parse_link_message(&error).is_none());
+        assert!(parse_link_message(&[0; 3]).is_none());
+        // Loopback answers the request, but is no CAN link
+        assert!(read_controller_status("lo").is_none());
+    }
+}
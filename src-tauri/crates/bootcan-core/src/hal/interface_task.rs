@@ -0,0 +1,284 @@
+//! Task owning a CAN interface
+//!
+//! The interface of a connected channel lives in its own task, and everything
+//! else talks to it through an `InterfaceHandle`. Callers never hold a channel
+//! lock while the interface works, so no blocking bridge between sync locks
+//! and async interface calls is needed.
+//!
+//! Interfaces without a receiver of their own are polled, every millisecond
+//! while frames arrive and backing off to `IDLE_POLL` on a silent bus.
+
+use super::traits::{CanInterface, ControllerStatus, FaultConfig, FrameReceiver, RX_QUEUE_LEN};
+use super::virtual_can::VirtualBusAttachment;
+use crate::core::message::CanFrame;
+use crate::error::BootCanError;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::Instrument;
+
+/// Poll interval of an interface while frames arrive
+const BUSY_POLL: Duration = Duration::from_millis(1);
+/// Longest poll interval of a silent interface
+const IDLE_POLL: Duration = Duration::from_millis(20);
+
+type Reply<T> = oneshot::Sender<Result<T, BootCanError>>;
+
+enum InterfaceRequest {
+    Connect { bitrate: u32, reply: Reply<()> },
+    Disconnect { reply: Reply<()> },
+    Send { frame: CanFrame, reply: Reply<()> },
+    TakeReceiver { reply: oneshot::Sender<FrameReceiver> },
+    ControllerStatus { reply: oneshot::Sender<Option<ControllerStatus>> },
+    SetFaultInjection { config: Option<FaultConfig>, reply: Reply<()> },
+    AttachVirtualBus { attachment: Option<VirtualBusAttachment>, reply: Reply<()> },
+}
+
+/// Cloneable handle for the task owning an interface
+///
+/// The task ends, dropping the interface, once every handle is dropped.
+#[derive(Clone)]
+pub struct InterfaceHandle {
+    id: String,
+    requests: mpsc::Sender<InterfaceRequest>,
+}
+
+impl InterfaceHandle {
+    /// Move `interface` into its own task
+    pub fn spawn(interface: Box<dyn CanInterface>) -> Self {
+        let id = interface.info().id;
+        let (requests, rx) = mpsc::channel(64);
+        tokio::spawn(run(interface, rx).instrument(tracing::info_span!("interface", interface = %id)));
+        Self { id, requests }
+    }
+
+    /// ID of the interface
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn request<T>(&self, request: InterfaceRequest, response: oneshot::Receiver<T>) -> Result<T, BootCanError> {
+        let closed = || BootCanError::NotConnected(format!("Interface {} is gone", self.id));
+        self.requests.send(request).await.map_err(|_| closed())?;
+        response.await.map_err(|_| closed())
+    }
+
+    pub async fn connect(&self, bitrate: u32) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::Connect { bitrate, reply }, response).await?
+    }
+
+    pub async fn disconnect(&self) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::Disconnect { reply }, response).await?
+    }
+
+    pub async fn send(&self, frame: CanFrame) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::Send { frame, reply }, response).await?
+    }
+
+    /// Receiver of the frames the interface gets from now on
+    ///
+    /// Interfaces without a receiver of their own are polled by the task.
+    pub async fn take_receiver(&self) -> Result<FrameReceiver, BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::TakeReceiver { reply }, response).await
+    }
+
+    pub async fn controller_status(&self) -> Option<ControllerStatus> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::ControllerStatus { reply }, response)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    pub async fn set_fault_injection(&self, config: Option<FaultConfig>) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::SetFaultInjection { config, reply }, response).await?
+    }
+
+    pub async fn attach_virtual_bus(&self, attachment: Option<VirtualBusAttachment>) -> Result<(), BootCanError> {
+        let (reply, response) = oneshot::channel();
+        self.request(InterfaceRequest::AttachVirtualBus { attachment, reply }, response).await?
+    }
+}
+
+async fn run(mut interface: Box<dyn CanInterface>, mut requests: mpsc::Receiver<InterfaceRequest>) {
+    // Receiver fed by polling `receive`, for interfaces without one of their own
+    let mut polled: Option<mpsc::Sender<Result<CanFrame, BootCanError>>> = None;
+    let mut poll_delay = BUSY_POLL;
+    let mut next_poll = Instant::now();
+
+    loop {
+        tokio::select! {
+            request = requests.recv() => {
+                let Some(request) = request else {
+                    break;
+                };
+                match request {
+                    InterfaceRequest::Connect { bitrate, reply } => {
+                        let _ = reply.send(interface.connect(bitrate).await);
+                    }
+                    InterfaceRequest::Disconnect { reply } => {
+                        polled = None;
+                        let _ = reply.send(interface.disconnect().await);
+                    }
+                    InterfaceRequest::Send { frame, reply } => {
+                        let _ = reply.send(interface.send(&frame).await);
+                        // A response is likely on its way
+                        poll_delay = BUSY_POLL;
+                        next_poll = next_poll.min(Instant::now() + BUSY_POLL);
+                    }
+                    InterfaceRequest::TakeReceiver { reply } => {
+                        let receiver = interface.take_receiver().unwrap_or_else(|| {
+                            let (tx, rx) = mpsc::channel(RX_QUEUE_LEN);
+                            polled = Some(tx);
+                            poll_delay = BUSY_POLL;
+                            next_poll = Instant::now();
+                            rx
+                        });
+                        let _ = reply.send(receiver);
+                    }
+                    InterfaceRequest::ControllerStatus { reply } => {
+                        let _ = reply.send(interface.controller_status());
+                    }
+                    InterfaceRequest::SetFaultInjection { config, reply } => {
+                        let _ = reply.send(interface.set_fault_injection(config));
+                    }
+                    InterfaceRequest::AttachVirtualBus { attachment, reply } => {
+                        let _ = reply.send(interface.attach_virtual_bus(attachment));
+                    }
+                }
+            }
+            _ = tokio::time::sleep_until(next_poll), if polled.is_some() => {
+                if let Some(tx) = &polled {
+                    match poll_frames(interface.as_mut(), tx).await {
+                        Some(frames) => poll_delay = next_poll_delay(poll_delay, frames),
+                        None => polled = None,
+                    }
+                }
+                next_poll = Instant::now() + poll_delay;
+            }
+        }
+    }
+}
+
+/// Poll interval after a poll that found `frames` frames
+///
+/// Doubles on every empty poll up to `IDLE_POLL`, and drops back to
+/// `BUSY_POLL` as soon as frames arrive.
+fn next_poll_delay(delay: Duration, frames: usize) -> Duration {
+    if frames > 0 {
+        BUSY_POLL
+    } else {
+        (delay * 2).min(IDLE_POLL)
+    }
+}
+
+/// Pass every frame waiting in the interface to `tx`
+///
+/// Returns the number of frames and errors passed on, or None once the receiver is gone.
+async fn poll_frames(
+    interface: &mut dyn CanInterface,
+    tx: &mpsc::Sender<Result<CanFrame, BootCanError>>,
+) -> Option<usize> {
+    let mut passed = 0;
+    loop {
+        let result = match interface.receive().await {
+            Ok(Some(frame)) => Ok(frame),
+            Ok(None) => return Some(passed),
+            Err(e) => Err(e),
+        };
+        let failed = result.is_err();
+        tx.send(result).await.ok()?;
+        passed += 1;
+        if failed {
+            return Some(passed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::traits::{BusState, CanFilter, InterfaceInfo};
+    use crate::hal::virtual_can::VirtualCanInterface;
+    use async_trait::async_trait;
+
+    /// Interface without a receiver, delivering its queued frames on `receive`
+    struct Polled {
+        queue: Vec<CanFrame>,
+    }
+
+    #[async_trait]
+    impl CanInterface for Polled {
+        fn info(&self) -> InterfaceInfo {
+            InterfaceInfo {
+                id: "polled".to_string(),
+                name: "Polled".to_string(),
+                interface_type: "test".to_string(),
+                available: true,
+            }
+        }
+        async fn connect(&mut self, _bitrate: u32) -> Result<(), BootCanError> {
+            Ok(())
+        }
+        async fn disconnect(&mut self) -> Result<(), BootCanError> {
+            Ok(())
+        }
+        fn is_connected(&self) -> bool {
+            true
+        }
+        async fn send(&mut self, frame: &CanFrame) -> Result<(), BootCanError> {
+            self.queue.push(frame.clone());
+            Ok(())
+        }
+        async fn receive(&mut self) -> Result<Option<CanFrame>, BootCanError> {
+            Ok(self.queue.pop())
+        }
+        fn set_filter(&mut self, _filter: Option<CanFilter>) -> Result<(), BootCanError> {
+            Ok(())
+        }
+        fn get_bus_state(&self) -> BusState {
+            BusState::Active
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_requests_on_current_thread_runtime() {
+        let handle = InterfaceHandle::spawn(Box::new(VirtualCanInterface::new("vcan0")));
+        assert!(handle.send(CanFrame::new(0x1, &[1])).await.is_err());
+        handle.connect(500_000).await.unwrap();
+        let mut rx = handle.take_receiver().await.unwrap();
+
+        handle.send(CanFrame::new(0x123, &[1, 2])).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap().id, 0x123);
+        assert!(handle.set_fault_injection(None).await.is_ok());
+        assert!(handle.controller_status().await.is_none());
+        handle.disconnect().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_interfaces_without_receiver_are_polled() {
+        let handle = InterfaceHandle::spawn(Box::new(Polled { queue: Vec::new() }));
+        assert_eq!(handle.id(), "polled");
+        let mut rx = handle.take_receiver().await.unwrap();
+        handle.send(CanFrame::new(0x42, &[])).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap().id, 0x42);
+        assert!(handle.attach_virtual_bus(None).await.is_err());
+
+        // A silent interface is polled less and less often, until frames arrive
+        let delays: Vec<_> = std::iter::successors(Some(BUSY_POLL), |&d| Some(next_poll_delay(d, 0)))
+            .take(8)
+            .collect();
+        assert_eq!(delays[1], BUSY_POLL * 2);
+        assert_eq!(delays[7], IDLE_POLL);
+        assert_eq!(next_poll_delay(IDLE_POLL, 1), BUSY_POLL);
+
+        // Dropping the last handle stops the task and ends the stream
+        drop(handle);
+        assert!(rx.recv().await.is_none());
+    }
+}
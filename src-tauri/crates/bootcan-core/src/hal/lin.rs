@@ -0,0 +1,274 @@
+//! LIN interfaces
+//!
+//! LIN channels run in the same channel task as CAN channels: `LinCanAdapter`
+//! wraps a `LinInterface` into a `CanInterface`, converting frames at the
+//! boundary, so statistics, filters, logging and the frame events need no LIN
+//! specific path.
+
+use super::traits::{BusState, CanFilter, CanInterface, FrameReceiver, InterfaceInfo, RX_QUEUE_LEN};
+use crate::core::lin::{LinFrame, MAX_LIN_ID};
+use crate::core::message::{BusType, CanFrame, Direction, FrameData};
+use crate::error::BootCanError;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Frames seen on a connected LIN bus
+pub type LinReceiver = mpsc::Receiver<Result<LinFrame, BootCanError>>;
+
+/// Trait for LIN interface implementations, acting as master
+#[async_trait]
+pub trait LinInterface: Send + Sync {
+    fn info(&self) -> InterfaceInfo;
+
+    /// Connect to the bus at `baudrate`, typically 19200 or 9600 bit/s
+    async fn connect(&mut self, baudrate: u32) -> Result<(), BootCanError>;
+
+    async fn disconnect(&mut self) -> Result<(), BootCanError>;
+
+    fn is_connected(&self) -> bool;
+
+    /// Send the header of a frame followed by our response
+    async fn publish(&mut self, frame: &LinFrame) -> Result<(), BootCanError>;
+
+    /// Send only the header of `id`, for the slave publishing it to respond
+    async fn request(&mut self, id: u8) -> Result<(), BootCanError>;
+
+    /// Start delivering the frames seen on the bus; called once per connection
+    fn take_receiver(&mut self) -> Option<LinReceiver>;
+}
+
+/// Runs a LIN interface as the interface of a channel
+///
+/// Frames sent to the channel are published; remote frames only send the
+/// header. Received frames come out marked as `BusType::Lin`.
+pub struct LinCanAdapter {
+    inner: Box<dyn LinInterface>,
+    /// Task converting the received frames
+    pump: Option<JoinHandle<()>>,
+}
+
+impl LinCanAdapter {
+    pub fn new(inner: Box<dyn LinInterface>) -> Self {
+        Self { inner, pump: None }
+    }
+
+    fn stop_pump(&mut self) {
+        if let Some(pump) = self.pump.take() {
+            pump.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl CanInterface for LinCanAdapter {
+    fn info(&self) -> InterfaceInfo {
+        self.inner.info()
+    }
+
+    fn bus(&self) -> BusType {
+        BusType::Lin
+    }
+
+    async fn connect(&mut self, bitrate: u32) -> Result<(), BootCanError> {
+        self.inner.connect(bitrate).await
+    }
+
+    async fn disconnect(&mut self) -> Result<(), BootCanError> {
+        self.stop_pump();
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn send(&mut self, frame: &CanFrame) -> Result<(), BootCanError> {
+        if frame.is_remote {
+            if frame.id > MAX_LIN_ID as u32 {
+                return Err(BootCanError::InvalidInput(format!("LIN frame ID 0x{:X} is out of range", frame.id)));
+            }
+            self.inner.request(frame.id as u8).await
+        } else {
+            self.inner.publish(&LinFrame::from_can_frame(frame)?).await
+        }
+    }
+
+    async fn receive(&mut self) -> Result<Option<CanFrame>, BootCanError> {
+        Ok(None)
+    }
+
+    fn take_receiver(&mut self) -> Option<FrameReceiver> {
+        let mut lin_rx = self.inner.take_receiver()?;
+        let (tx, rx) = mpsc::channel(RX_QUEUE_LEN);
+        self.pump = Some(tokio::spawn(async move {
+            while let Some(result) = lin_rx.recv().await {
+                if tx.send(result.map(|frame| frame.to_can_frame())).await.is_err() {
+                    break;
+                }
+            }
+        }));
+        Some(rx)
+    }
+
+    /// LIN adapters have no acceptance filter; the channel's filter still applies
+    fn set_filter(&mut self, _filter: Option<CanFilter>) -> Result<(), BootCanError> {
+        Ok(())
+    }
+
+    fn get_bus_state(&self) -> BusState {
+        if self.inner.is_connected() {
+            BusState::Active
+        } else {
+            BusState::Unknown
+        }
+    }
+}
+
+impl Drop for LinCanAdapter {
+    fn drop(&mut self) {
+        self.stop_pump();
+    }
+}
+
+/// Virtual LIN bus for testing without an adapter
+///
+/// Published frames are echoed back. A header is answered like a slave would,
+/// with the data last published for its ID; IDs never published stay silent.
+pub struct VirtualLinInterface {
+    id: String,
+    connected: bool,
+    responses: HashMap<u8, FrameData>,
+    tx: Option<mpsc::Sender<Result<LinFrame, BootCanError>>>,
+}
+
+impl VirtualLinInterface {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            connected: false,
+            responses: HashMap::new(),
+            tx: None,
+        }
+    }
+
+    fn deliver(&self, frame: LinFrame) {
+        if let Some(tx) = &self.tx {
+            // Frames nobody takes are dropped, like on a real bus
+            let _ = tx.try_send(Ok(frame));
+        }
+    }
+}
+
+#[async_trait]
+impl LinInterface for VirtualLinInterface {
+    fn info(&self) -> InterfaceInfo {
+        InterfaceInfo {
+            id: self.id.clone(),
+            name: format!("Virtual LIN: {}", self.id),
+            interface_type: "lin".to_string(),
+            available: true,
+        }
+    }
+
+    async fn connect(&mut self, _baudrate: u32) -> Result<(), BootCanError> {
+        if self.connected {
+            return Err(BootCanError::Busy("Already connected".to_string()));
+        }
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), BootCanError> {
+        if !self.connected {
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
+        }
+        self.connected = false;
+        self.tx = None;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    async fn publish(&mut self, frame: &LinFrame) -> Result<(), BootCanError> {
+        if !self.connected {
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
+        }
+        self.responses.insert(frame.id, frame.data);
+        self.deliver(frame.clone());
+        Ok(())
+    }
+
+    async fn request(&mut self, id: u8) -> Result<(), BootCanError> {
+        if !self.connected {
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
+        }
+        if let Some(data) = self.responses.get(&id) {
+            let mut answer = LinFrame::new(id, data);
+            answer.direction = Direction::Rx;
+            self.deliver(answer);
+        }
+        Ok(())
+    }
+
+    fn take_receiver(&mut self) -> Option<LinReceiver> {
+        if !self.connected || self.tx.is_some() {
+            return None;
+        }
+        let (tx, rx) = mpsc::channel(RX_QUEUE_LEN);
+        self.tx = Some(tx);
+        Some(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::bus_stats::StatsEmission;
+    use crate::core::channel::{ChannelConfig, ChannelHandle};
+    use crate::core::subscriber::DEFAULT_BROADCAST_CAPACITY;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_virtual_slave_answers_headers() {
+        let mut lin = VirtualLinInterface::new("vlin0");
+        lin.connect(19_200).await.unwrap();
+        let mut rx = lin.take_receiver().unwrap();
+
+        lin.request(0x10).await.unwrap();
+        lin.publish(&LinFrame::new(0x10, &[1, 2])).await.unwrap();
+        lin.request(0x10).await.unwrap();
+        // The echo of the published frame, then the answer; nothing answered the first header
+        assert_eq!(rx.recv().await.unwrap().unwrap().direction, Direction::Tx);
+        let answer = rx.recv().await.unwrap().unwrap();
+        assert_eq!((answer.id, answer.data, answer.direction.as_str()), (0x10, [1, 2].into(), "rx"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_lin_channel_marks_frames() {
+        let channel = ChannelHandle::spawn("lin0", StatsEmission::default(), DEFAULT_BROADCAST_CAPACITY);
+        let config = ChannelConfig {
+            interface_id: "vlin0".to_string(),
+            bitrate: 19_200,
+            listen_only: false,
+        };
+        channel.connect(config, Box::new(|_| {})).await.unwrap();
+        let mut rx = channel.subscribe("test");
+
+        channel.send(CanFrame::new(0x21, &[0xAB])).await.unwrap();
+        channel.send(CanFrame::new_rtr(0x21, 1)).await.unwrap();
+        // Both transmissions and the two frames seen on the bus: the echo and the answer
+        let mut frames = Vec::new();
+        while frames.len() < 4 {
+            frames.push(rx.recv().await.unwrap());
+        }
+        assert!(frames.iter().all(|frame| frame.bus == BusType::Lin && frame.id == 0x21));
+        let received: Vec<_> = frames.iter().filter(|frame| frame.direction.is_rx()).collect();
+        assert!(received.len() == 2 && received.iter().all(|frame| frame.data == [0xAB]));
+        assert!(channel.send(CanFrame::new(0x100, &[])).await.is_err());
+        channel.disconnect().await.unwrap();
+    }
+}
@@ -1,6 +1,12 @@
+pub mod interface_task;
+pub mod lin;
+pub mod slcan_lin;
+pub mod synthetic;
 pub mod traits;
 pub mod virtual_can;
 
+#[cfg(target_os = "linux")]
+pub mod can_netlink;
 #[cfg(target_os = "linux")]
 pub mod socketcan;
 
@@ -4,10 +4,15 @@
 //! PCAN USB adapters on Windows and macOS. It uses FFI bindings to the
 //! PCANBasic library.
 
-use super::traits::{BusState, CanFilter, CanInterface, InterfaceInfo};
+use super::traits::{BusState, CanFilter, CanInterface, ControllerStatus, FrameReceiver, InterfaceInfo, RX_QUEUE_LEN};
 use crate::core::message::CanFrame;
+use crate::error::BootCanError;
 use async_trait::async_trait;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
 /// PCAN channel identifiers
 #[repr(u16)]
@@ -141,6 +146,8 @@ pub struct PcanInterface {
     connected: bool,
     bitrate: u32,
     start_time: Option<Instant>,
+    /// Thread feeding the receiver handed out by `take_receiver`, and its stop flag
+    reader: Option<(JoinHandle<()>, Arc<AtomicBool>)>,
 }
 
 impl PcanInterface {
@@ -154,8 +161,60 @@ impl PcanInterface {
             connected: false,
             bitrate: 0,
             start_time: None,
+            reader: None,
         }
     }
+
+    fn stop_reader(&mut self) {
+        if let Some((thread, stop)) = self.reader.take() {
+            stop.store(true, Ordering::Relaxed);
+            let _ = thread.join();
+        }
+    }
+
+    /// Bus state from `CAN_GetStatus`; None without the library or an initialized channel
+    fn read_bus_state(&self) -> Option<BusState> {
+        let status = basic::get_status(self.channel? as u16)?;
+        // Anything but the bus status flags is an error of the call itself
+        if status & !BUS_STATUS_FLAGS != 0 {
+            return None;
+        }
+        Some(bus_state_from_status(status))
+    }
+}
+
+/// Flags of `CAN_GetStatus` describing the bus; 0 is error active
+const BUS_STATUS_FLAGS: u32 = PcanError::BusLight as u32
+    | PcanError::BusHeavy as u32
+    | PcanError::BusPassive as u32
+    | PcanError::BusOff as u32;
+
+/// Read frames on a dedicated thread until `stop` is set or the receiver is dropped
+fn spawn_reader(
+    id: String,
+    stop: Arc<AtomicBool>,
+    tx: mpsc::Sender<Result<CanFrame, BootCanError>>,
+) -> Result<JoinHandle<()>, BootCanError> {
+    std::thread::Builder::new()
+        .name(format!("pcan-rx-{}", id))
+        .spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                // In a real implementation, this would wait on the handle set with
+                // CAN_SetValue(channel, PCAN_RECEIVE_EVENT, ...) and then drain
+                // CAN_Read until PCAN_ERROR_QRCVEMPTY, sending each frame. The stub
+                // receives nothing, so it only waits.
+                let frame: Option<Result<CanFrame, BootCanError>> = None;
+                match frame {
+                    Some(result) => {
+                        if tx.blocking_send(result).is_err() {
+                            break;
+                        }
+                    }
+                    None => std::thread::sleep(Duration::from_millis(10)),
+                }
+            }
+        })
+        .map_err(|e| BootCanError::Hal(format!("Failed to start PCAN reader: {}", e)))
 }
 
 // FFI declarations for PCAN-Basic API
@@ -187,6 +246,35 @@ mod ffi {
     pub const PCAN_MESSAGE_RTR: u8 = 0x01;
 }
 
+/// PCANBasic loaded at runtime, so the app starts without the driver installed
+mod basic {
+    use libloading::Library;
+    use std::sync::OnceLock;
+
+    #[cfg(target_os = "windows")]
+    const LIBRARY: &str = "PCANBasic.dll";
+    #[cfg(target_os = "macos")]
+    const LIBRARY: &str = "libPCBUSB.dylib";
+
+    fn library() -> Option<&'static Library> {
+        static HANDLE: OnceLock<Option<Library>> = OnceLock::new();
+        // SAFETY: PCANBasic runs no initialisation code with preconditions on load
+        HANDLE.get_or_init(|| unsafe { Library::new(LIBRARY) }.ok()).as_ref()
+    }
+
+    /// `CAN_GetStatus` of a channel; None if the library is missing
+    pub fn get_status(channel: u16) -> Option<u32> {
+        let library = library()?;
+        // SAFETY: the signature matches PCANBasic.h: TPCANStatus CAN_GetStatus(TPCANHandle)
+        unsafe {
+            let get_status = library
+                .get::<unsafe extern "system" fn(u16) -> u32>(b"CAN_GetStatus\0")
+                .ok()?;
+            Some(get_status(channel))
+        }
+    }
+}
+
 #[async_trait]
 impl CanInterface for PcanInterface {
     fn info(&self) -> InterfaceInfo {
@@ -198,14 +286,14 @@ impl CanInterface for PcanInterface {
         }
     }
 
-    async fn connect(&mut self, bitrate: u32) -> Result<(), String> {
+    async fn connect(&mut self, bitrate: u32) -> Result<(), BootCanError> {
         if self.connected {
-            return Err("Already connected".to_string());
+            return Err(BootCanError::Busy("Already connected".to_string()));
         }
 
         let _channel = self
             .channel
-            .ok_or("Invalid PCAN channel")?;
+            .ok_or_else(|| BootCanError::InvalidInput("Invalid PCAN channel".to_string()))?;
 
         let _pcan_bitrate = PcanBitrate::from_bps(bitrate);
 
@@ -214,7 +302,7 @@ impl CanInterface for PcanInterface {
         
         // For now, we simulate a successful connection
         // TODO: Add actual PCAN FFI bindings
-        log::warn!(
+        tracing::warn!(
             "PCAN interface {} - using stub implementation. Real PCAN support requires PCANBasic library.",
             self.id
         );
@@ -223,23 +311,24 @@ impl CanInterface for PcanInterface {
         self.connected = true;
         self.start_time = Some(Instant::now());
 
-        log::info!("PCAN {} connected at {} bps (stub)", self.id, bitrate);
+        tracing::info!("PCAN {} connected at {} bps (stub)", self.id, bitrate);
 
         Ok(())
     }
 
-    async fn disconnect(&mut self) -> Result<(), String> {
+    async fn disconnect(&mut self) -> Result<(), BootCanError> {
         if !self.connected {
-            return Err("Not connected".to_string());
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
         }
 
         // In a real implementation, this would call:
         // CAN_Uninitialize(channel as u16)
 
+        self.stop_reader();
         self.connected = false;
         self.start_time = None;
 
-        log::info!("PCAN {} disconnected", self.id);
+        tracing::info!("PCAN {} disconnected", self.id);
 
         Ok(())
     }
@@ -248,12 +337,12 @@ impl CanInterface for PcanInterface {
         self.connected
     }
 
-    async fn send(&mut self, frame: &CanFrame) -> Result<(), String> {
+    async fn send(&mut self, frame: &CanFrame) -> Result<(), BootCanError> {
         if !self.connected {
-            return Err("Not connected".to_string());
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
         }
 
-        let _channel = self.channel.ok_or("Invalid PCAN channel")?;
+        let _channel = self.channel.ok_or_else(|| BootCanError::InvalidInput("Invalid PCAN channel".to_string()))?;
 
         // Build PCAN message structure
         #[cfg(any(target_os = "windows", target_os = "macos"))]
@@ -280,7 +369,7 @@ impl CanInterface for PcanInterface {
             // CAN_Write(channel as u16, &msg)
         }
 
-        log::trace!(
+        tracing::trace!(
             "PCAN {} TX: ID=0x{:X} DLC={} Data={:?}",
             self.id,
             frame.id,
@@ -291,12 +380,12 @@ impl CanInterface for PcanInterface {
         Ok(())
     }
 
-    async fn receive(&mut self) -> Result<Option<CanFrame>, String> {
+    async fn receive(&mut self) -> Result<Option<CanFrame>, BootCanError> {
         if !self.connected {
-            return Err("Not connected".to_string());
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
         }
 
-        let _channel = self.channel.ok_or("Invalid PCAN channel")?;
+        let _channel = self.channel.ok_or_else(|| BootCanError::InvalidInput("Invalid PCAN channel".to_string()))?;
 
         // In a real implementation, this would call:
         // CAN_Read(channel as u16, &msg, &timestamp)
@@ -306,15 +395,34 @@ impl CanInterface for PcanInterface {
         Ok(None)
     }
 
-    fn set_filter(&mut self, _filter: Option<CanFilter>) -> Result<(), String> {
+    fn take_receiver(&mut self) -> Option<FrameReceiver> {
+        if !self.connected || self.reader.is_some() {
+            return None;
+        }
+        let (tx, rx) = mpsc::channel(RX_QUEUE_LEN);
+        let stop = Arc::new(AtomicBool::new(false));
+        match spawn_reader(self.id.clone(), stop.clone(), tx) {
+            Ok(thread) => {
+                self.reader = Some((thread, stop));
+                Some(rx)
+            }
+            Err(e) => {
+                // Falls back to polling `receive`
+                tracing::error!("{}", e);
+                None
+            }
+        }
+    }
+
+    fn set_filter(&mut self, _filter: Option<CanFilter>) -> Result<(), BootCanError> {
         if !self.connected {
-            return Err("Not connected".to_string());
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
         }
 
         // PCAN filter implementation would use CAN_SetValue with
         // PCAN_ACCEPTANCE_FILTER_* parameters
 
-        log::warn!("PCAN filter setting not yet implemented");
+        tracing::warn!("PCAN filter setting not yet implemented");
         Ok(())
     }
 
@@ -322,10 +430,38 @@ impl CanInterface for PcanInterface {
         if !self.connected {
             return BusState::Unknown;
         }
+        self.read_bus_state().unwrap_or(BusState::Unknown)
+    }
 
-        // In a real implementation, this would call:
-        // CAN_GetValue(channel, PCAN_BUSSTATUS, ...)
-        
+    fn controller_status(&mut self) -> Option<ControllerStatus> {
+        if !self.connected {
+            return None;
+        }
+
+        // CAN_GetStatus only carries the state; PCANBasic has no TEC/REC query
+        Some(ControllerStatus {
+            state: self.read_bus_state()?,
+            tx_error_counter: None,
+            rx_error_counter: None,
+        })
+    }
+}
+
+impl Drop for PcanInterface {
+    fn drop(&mut self) {
+        self.stop_reader();
+    }
+}
+
+/// Map a `CAN_GetStatus` value to a bus state
+pub fn bus_state_from_status(status: u32) -> BusState {
+    if status & PcanError::BusOff as u32 != 0 {
+        BusState::BusOff
+    } else if status & PcanError::BusPassive as u32 != 0 {
+        BusState::Passive
+    } else if status & (PcanError::BusHeavy as u32 | PcanError::BusLight as u32) != 0 {
+        BusState::Warning
+    } else {
         BusState::Active
     }
 }
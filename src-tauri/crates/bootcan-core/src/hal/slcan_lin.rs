@@ -0,0 +1,280 @@
+//! LIN adapters speaking an SLCAN-style serial protocol
+//!
+//! Commands and frames are ASCII lines ending in `\r`, as with SLCAN CAN
+//! adapters, with the adapter acting as LIN master:
+//!
+//! - `L<baud>\r` sets the LIN baudrate in bit/s, `O\r` opens and `C\r` closes the bus
+//! - `t<iii><l><dd…>\r` publishes a frame, header and response; frames seen on
+//!   the bus, including slave responses, are reported the same way
+//! - `r<iii>0\r` sends only the header, for a slave to respond
+//! - a lone `\r` acknowledges a command, `\x07` reports an error
+//!
+//! Interface IDs are `lin:` followed by the serial port, e.g. `lin:/dev/ttyACM0`.
+
+use super::lin::{LinInterface, LinReceiver};
+use super::traits::{InterfaceInfo, RX_QUEUE_LEN};
+use crate::core::lin::{LinFrame, MAX_LIN_DATA_LEN, MAX_LIN_ID};
+use crate::core::message::Direction;
+use crate::error::BootCanError;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serialport::SerialPort;
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Speed of the serial link to the adapter, not of the LIN bus
+const SERIAL_BAUDRATE: u32 = 115_200;
+/// How long the reader waits for bytes before checking its stop flag
+const READ_TIMEOUT: Duration = Duration::from_millis(50);
+/// Longest line the adapter sends: `t`, ID, length, 8 data bytes
+const MAX_LINE_LEN: usize = 1 + 3 + 1 + 2 * MAX_LIN_DATA_LEN;
+
+/// LIN adapter on a serial port
+pub struct SlcanLinInterface {
+    port_name: String,
+    port: Option<Mutex<Box<dyn SerialPort>>>,
+    /// Thread feeding the receiver handed out by `take_receiver`, and its stop flag
+    reader: Option<(JoinHandle<()>, Arc<AtomicBool>)>,
+}
+
+impl SlcanLinInterface {
+    /// Interface for the adapter on `port_name`, e.g. `/dev/ttyACM0` or `COM3`
+    pub fn new(port_name: &str) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            port: None,
+            reader: None,
+        }
+    }
+
+    fn write(&self, command: &str) -> Result<(), BootCanError> {
+        let port = self
+            .port
+            .as_ref()
+            .ok_or_else(|| BootCanError::NotConnected("Not connected".to_string()))?;
+        port.lock()
+            .write_all(command.as_bytes())
+            .map_err(|e| BootCanError::Io(format!("Failed to write to {}: {}", self.port_name, e)))
+    }
+
+    fn stop_reader(&mut self) {
+        if let Some((thread, stop)) = self.reader.take() {
+            stop.store(true, Ordering::Relaxed);
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Command publishing a frame
+fn encode_frame(frame: &LinFrame) -> String {
+    let data: String = frame.data.iter().map(|b| format!("{:02X}", b)).collect();
+    format!("t{:03X}{}{}\r", frame.id, frame.data.len(), data)
+}
+
+/// Command sending the header of `id`
+fn encode_header(id: u8) -> String {
+    format!("r{:03X}0\r", id)
+}
+
+/// Frame or error reported by the adapter; None for acknowledgements
+fn decode_line(line: &str) -> Option<Result<LinFrame, BootCanError>> {
+    if line.contains('\x07') {
+        return Some(Err(BootCanError::Hal("LIN adapter reported an error".to_string())));
+    }
+    let body = line.strip_prefix('t')?;
+    let parse = || -> Option<LinFrame> {
+        let id = u8::from_str_radix(body.get(..3)?, 16).ok().filter(|&id| id <= MAX_LIN_ID)?;
+        let len = body.get(3..4)?.parse::<usize>().ok().filter(|&len| len <= MAX_LIN_DATA_LEN)?;
+        let hex = body.get(4..)?;
+        if hex.len() != 2 * len || !hex.is_ascii() {
+            return None;
+        }
+        let data = (0..len)
+            .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let mut frame = LinFrame::new(id, &data);
+        frame.direction = Direction::Rx;
+        Some(frame)
+    };
+    Some(parse().ok_or_else(|| BootCanError::Parse(format!("Malformed LIN frame from adapter: {:?}", line))))
+}
+
+/// Read lines on a dedicated thread until `stop` is set or the receiver is dropped
+fn spawn_reader(
+    port_name: String,
+    mut port: Box<dyn SerialPort>,
+    stop: Arc<AtomicBool>,
+    tx: mpsc::Sender<Result<LinFrame, BootCanError>>,
+) -> Result<JoinHandle<()>, BootCanError> {
+    std::thread::Builder::new()
+        .name(format!("lin-rx-{}", port_name))
+        .spawn(move || {
+            let mut line = String::new();
+            let mut buf = [0u8; 256];
+            while !stop.load(Ordering::Relaxed) {
+                let count = match port.read(&mut buf) {
+                    Ok(count) => count,
+                    Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::Interrupted) => continue,
+                    Err(e) => {
+                        let _ = tx.blocking_send(Err(BootCanError::Io(format!("{}: {}", port_name, e))));
+                        break;
+                    }
+                };
+                for &byte in &buf[..count] {
+                    if byte != b'\r' {
+                        // Garbage without line ends must not grow the line forever
+                        if line.len() <= MAX_LINE_LEN {
+                            line.push(byte as char);
+                        }
+                        continue;
+                    }
+                    if let Some(result) = decode_line(&line) {
+                        if tx.blocking_send(result).is_err() {
+                            return;
+                        }
+                    }
+                    line.clear();
+                }
+            }
+        })
+        .map_err(|e| BootCanError::Hal(format!("Failed to start LIN reader: {}", e)))
+}
+
+/// Serial ports of USB adapters, as LIN interfaces
+pub fn enumerate_lin_interfaces() -> Vec<InterfaceInfo> {
+    let Ok(ports) = serialport::available_ports() else {
+        return Vec::new();
+    };
+    ports
+        .into_iter()
+        .filter_map(|port| match port.port_type {
+            serialport::SerialPortType::UsbPort(usb) => Some(InterfaceInfo {
+                id: format!("lin:{}", port.port_name),
+                name: format!(
+                    "LIN: {} ({})",
+                    usb.product.as_deref().unwrap_or("serial adapter"),
+                    port.port_name
+                ),
+                interface_type: "lin".to_string(),
+                available: true,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[async_trait]
+impl LinInterface for SlcanLinInterface {
+    fn info(&self) -> InterfaceInfo {
+        InterfaceInfo {
+            id: format!("lin:{}", self.port_name),
+            name: format!("LIN: {}", self.port_name),
+            interface_type: "lin".to_string(),
+            available: true,
+        }
+    }
+
+    async fn connect(&mut self, baudrate: u32) -> Result<(), BootCanError> {
+        if self.port.is_some() {
+            return Err(BootCanError::Busy("Already connected".to_string()));
+        }
+        let port = serialport::new(&self.port_name, SERIAL_BAUDRATE)
+            .timeout(READ_TIMEOUT)
+            .open()
+            .map_err(|e| BootCanError::Hal(format!("Failed to open {}: {}", self.port_name, e)))?;
+        self.port = Some(Mutex::new(port));
+        // Close first in case the adapter was left open
+        let opened = self
+            .write("C\r")
+            .and_then(|()| self.write(&format!("L{}\r", baudrate)))
+            .and_then(|()| self.write("O\r"));
+        if opened.is_err() {
+            self.port = None;
+        }
+        opened
+    }
+
+    async fn disconnect(&mut self) -> Result<(), BootCanError> {
+        if self.port.is_none() {
+            return Err(BootCanError::NotConnected("Not connected".to_string()));
+        }
+        let closed = self.write("C\r");
+        self.stop_reader();
+        self.port = None;
+        closed
+    }
+
+    fn is_connected(&self) -> bool {
+        self.port.is_some()
+    }
+
+    async fn publish(&mut self, frame: &LinFrame) -> Result<(), BootCanError> {
+        // A few bytes into the driver's buffer; not worth a blocking task
+        self.write(&encode_frame(frame))
+    }
+
+    async fn request(&mut self, id: u8) -> Result<(), BootCanError> {
+        self.write(&encode_header(id & MAX_LIN_ID))
+    }
+
+    fn take_receiver(&mut self) -> Option<LinReceiver> {
+        if self.reader.is_some() {
+            return None;
+        }
+        let reader_port = match self.port.as_ref()?.lock().try_clone() {
+            Ok(port) => port,
+            Err(e) => {
+                tracing::error!("Failed to share {} with the reader: {}", self.port_name, e);
+                return None;
+            }
+        };
+        let (tx, rx) = mpsc::channel(RX_QUEUE_LEN);
+        let stop = Arc::new(AtomicBool::new(false));
+        match spawn_reader(self.port_name.clone(), reader_port, stop.clone(), tx) {
+            Ok(thread) => {
+                self.reader = Some((thread, stop));
+                Some(rx)
+            }
+            Err(e) => {
+                tracing::error!("{}", e);
+                None
+            }
+        }
+    }
+}
+
+impl Drop for SlcanLinInterface {
+    fn drop(&mut self) {
+        self.stop_reader();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_commands() {
+        assert_eq!(encode_frame(&LinFrame::new(0x21, &[0x01, 0xAB])), "t021201AB\r");
+        assert_eq!(encode_frame(&LinFrame::new(0x3C, &[])), "t03C0\r");
+        assert_eq!(encode_header(0x10), "r0100\r");
+    }
+
+    #[test]
+    fn test_decode_lines() {
+        let frame = decode_line("t021201AB").unwrap().unwrap();
+        assert_eq!((frame.id, frame.direction.as_str()), (0x21, "rx"));
+        assert_eq!(frame.data, [0x01, 0xAB]);
+
+        assert!(decode_line("").is_none());
+        assert!(decode_line("z").is_none());
+        assert!(decode_line("\x07").unwrap().is_err());
+        // ID out of range, length not matching the data
+        assert!(decode_line("t04000").unwrap().is_err());
+        assert!(decode_line("t0212AB").unwrap().is_err());
+    }
+}
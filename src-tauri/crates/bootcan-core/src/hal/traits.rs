@@ -1,6 +1,15 @@
-use crate::core::message::CanFrame;
+use super::virtual_can::VirtualBusAttachment;
+use crate::core::message::{BusType, CanFrame};
+use crate::error::BootCanError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+/// Frames of a connected interface, delivered as they arrive
+pub type FrameReceiver = mpsc::Receiver<Result<CanFrame, BootCanError>>;
+
+/// Received frames buffered between an interface and its receive loop
+pub const RX_QUEUE_LEN: usize = 4096;
 
 /// Information about an available CAN interface
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,7 +19,7 @@ pub struct InterfaceInfo {
     pub id: String,
     /// Human-readable name
     pub name: String,
-    /// Interface type (socketcan, pcan, virtual)
+    /// Interface type (socketcan, pcan, virtual, lin)
     #[serde(rename = "type")]
     pub interface_type: String,
     /// Whether the interface is currently available
@@ -23,26 +32,109 @@ pub trait CanInterface: Send + Sync {
     /// Get interface information
     fn info(&self) -> InterfaceInfo;
 
+    /// Bus the interface is attached to
+    fn bus(&self) -> BusType {
+        BusType::Can
+    }
+
     /// Connect to the CAN bus with specified bitrate
-    async fn connect(&mut self, bitrate: u32) -> Result<(), String>;
+    async fn connect(&mut self, bitrate: u32) -> Result<(), BootCanError>;
 
     /// Disconnect from the CAN bus
-    async fn disconnect(&mut self) -> Result<(), String>;
+    async fn disconnect(&mut self) -> Result<(), BootCanError>;
 
     /// Check if connected
     fn is_connected(&self) -> bool;
 
     /// Send a CAN frame
-    async fn send(&mut self, frame: &CanFrame) -> Result<(), String>;
+    async fn send(&mut self, frame: &CanFrame) -> Result<(), BootCanError>;
 
     /// Receive a CAN frame (non-blocking, returns None if no frame available)
-    async fn receive(&mut self) -> Result<Option<CanFrame>, String>;
+    async fn receive(&mut self) -> Result<Option<CanFrame>, BootCanError>;
+
+    /// Start delivering received frames as they arrive, instead of through `receive`
+    ///
+    /// Called once per connection; interfaces returning None are polled.
+    fn take_receiver(&mut self) -> Option<FrameReceiver> {
+        None
+    }
 
     /// Set receive filter (pass None to receive all)
-    fn set_filter(&mut self, filter: Option<CanFilter>) -> Result<(), String>;
+    fn set_filter(&mut self, filter: Option<CanFilter>) -> Result<(), BootCanError>;
 
     /// Get current bus state
     fn get_bus_state(&self) -> BusState;
+
+    /// Read the controller state and error counters from the hardware, if it reports them
+    fn controller_status(&mut self) -> Option<ControllerStatus> {
+        None
+    }
+
+    /// Configure fault injection (pass None to disable)
+    fn set_fault_injection(&mut self, _config: Option<FaultConfig>) -> Result<(), BootCanError> {
+        Err(BootCanError::Hal(format!("{} does not support fault injection", self.info().name)))
+    }
+
+    /// Attach to a shared virtual bus (pass None to detach)
+    fn attach_virtual_bus(&mut self, _attachment: Option<VirtualBusAttachment>) -> Result<(), BootCanError> {
+        Err(BootCanError::Hal(format!("{} cannot be attached to a virtual bus", self.info().name)))
+    }
+}
+
+/// Delivery latency applied to each frame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LatencyDistribution {
+    #[serde(rename_all = "camelCase")]
+    Fixed { ms: f64 },
+    #[serde(rename_all = "camelCase")]
+    Uniform { min_ms: f64, max_ms: f64 },
+    #[serde(rename_all = "camelCase")]
+    Normal { mean_ms: f64, std_dev_ms: f64 },
+}
+
+/// Faults injected into the frames delivered by a virtual interface
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FaultConfig {
+    /// Probability (0-1) that a frame is lost
+    #[serde(default)]
+    pub drop_probability: f64,
+    /// Probability (0-1) that one payload bit is flipped
+    #[serde(default)]
+    pub corrupt_probability: f64,
+    /// Probability (0-1) that a frame is delivered twice
+    #[serde(default)]
+    pub duplicate_probability: f64,
+    #[serde(default)]
+    pub latency: Option<LatencyDistribution>,
+    /// Seed for reproducible fault patterns (random if unset)
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl FaultConfig {
+    /// Check that probabilities and latencies are in range
+    pub fn validate(&self) -> Result<(), BootCanError> {
+        let probabilities = [
+            ("Drop", self.drop_probability),
+            ("Corruption", self.corrupt_probability),
+            ("Duplication", self.duplicate_probability),
+        ];
+        if let Some((name, p)) = probabilities.iter().find(|(_, p)| !(0.0..=1.0).contains(p)) {
+            return Err(BootCanError::InvalidInput(format!("{} probability {} is not between 0 and 1", name, p)));
+        }
+        let valid_latency = match self.latency {
+            None => true,
+            Some(LatencyDistribution::Fixed { ms }) => ms >= 0.0,
+            Some(LatencyDistribution::Uniform { min_ms, max_ms }) => min_ms >= 0.0 && min_ms <= max_ms,
+            Some(LatencyDistribution::Normal { mean_ms, std_dev_ms }) => mean_ms >= 0.0 && std_dev_ms >= 0.0,
+        };
+        if !valid_latency {
+            return Err(BootCanError::InvalidInput("Invalid latency distribution".to_string()));
+        }
+        Ok(())
+    }
 }
 
 /// CAN message filter
@@ -110,6 +202,17 @@ impl Default for BusState {
     }
 }
 
+/// Controller state and error counters as reported by the hardware
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControllerStatus {
+    pub state: BusState,
+    /// Transmit error counter (TEC), if the driver reports it
+    pub tx_error_counter: Option<u16>,
+    /// Receive error counter (REC), if the driver reports it
+    pub rx_error_counter: Option<u16>,
+}
+
 /// Enumerates available CAN interfaces on the system
 pub fn enumerate_interfaces() -> Vec<InterfaceInfo> {
     let mut interfaces = Vec::new();
@@ -129,6 +232,14 @@ pub fn enumerate_interfaces() -> Vec<InterfaceInfo> {
         available: true,
     });
 
+    // Generated traffic at full bus load, for stress tests
+    interfaces.push(InterfaceInfo {
+        id: "synth0".to_string(),
+        name: "Synthetic traffic".to_string(),
+        interface_type: "virtual".to_string(),
+        available: true,
+    });
+
     // Enumerate SocketCAN interfaces on Linux
     #[cfg(target_os = "linux")]
     {
@@ -145,11 +256,20 @@ pub fn enumerate_interfaces() -> Vec<InterfaceInfo> {
         }
     }
 
+    // LIN: a virtual bus and serial adapters
+    interfaces.push(InterfaceInfo {
+        id: "vlin0".to_string(),
+        name: "Virtual LIN 0".to_string(),
+        interface_type: "lin".to_string(),
+        available: true,
+    });
+    interfaces.extend(super::slcan_lin::enumerate_lin_interfaces());
+
     interfaces
 }
 
 #[cfg(target_os = "linux")]
-fn enumerate_socketcan_interfaces() -> Result<Vec<InterfaceInfo>, String> {
+fn enumerate_socketcan_interfaces() -> Result<Vec<InterfaceInfo>, BootCanError> {
     use std::fs;
 
     let mut interfaces = Vec::new();
@@ -183,7 +303,7 @@ fn enumerate_socketcan_interfaces() -> Result<Vec<InterfaceInfo>, String> {
 }
 
 #[cfg(any(target_os = "windows", target_os = "macos"))]
-fn enumerate_pcan_interfaces() -> Result<Vec<InterfaceInfo>, String> {
+fn enumerate_pcan_interfaces() -> Result<Vec<InterfaceInfo>, BootCanError> {
     // PCAN USB device enumeration
     // In a real implementation, this would call the PCAN API to enumerate devices
     let interfaces = vec![
@@ -0,0 +1,8 @@
+//! bootCAN core: CAN interfaces, protocols and file formats
+//!
+//! Shared by the Tauri application and the headless `bootcan-cli` binary;
+//! nothing in here depends on the UI.
+
+pub mod core;
+pub mod error;
+pub mod hal;
@@ -0,0 +1,122 @@
+//! Heap allocations on the frame path
+//!
+//! A counting global allocator tracks the allocations of the current thread,
+//! so the tests can run in parallel without seeing each other's.
+
+use bootcan_core::core::bus_stats::StatsEmission;
+use bootcan_core::core::channel::{ChannelConfig, ChannelHandle};
+use bootcan_core::core::frame_batch::{FrameBatcher, MAX_PENDING_FRAMES};
+use bootcan_core::core::message::{CanFrame, ChannelName, Direction};
+use bootcan_core::core::subscriber::{FrameSubscriber, DEFAULT_BROADCAST_CAPACITY};
+use bootcan_core::core::trace_logger::TraceFormat;
+use parking_lot::Mutex;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Frames received before counting, while queues and buffers grow
+const WARMUP_FRAMES: usize = 2_000;
+/// Frames counted through the receive path
+const FRAMES: usize = 20_000;
+/// Frames between two flushes of the event batcher
+const FLUSH_EVERY: usize = 1_000;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations made by this thread while awaiting `future`
+async fn allocations_async<T>(future: impl Future<Output = T>) -> (usize, T) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = future.await;
+    (ALLOCATIONS.with(Cell::get) - before, result)
+}
+
+/// Allocations made by this thread while running `f`
+fn allocations<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (ALLOCATIONS.with(Cell::get) - before, result)
+}
+
+#[test]
+fn test_frame_clones_do_not_allocate() {
+    let channel = ChannelName::from("can0");
+    let mut frame = CanFrame::new(0x123, &[1, 2, 3, 4, 5, 6, 7, 8]).as_received(channel.as_str(), 0.0);
+
+    let (count, clones) = allocations(|| {
+        let mut clones = Vec::with_capacity(1_000);
+        for _ in 0..1_000 {
+            frame.channel = channel.clone();
+            frame.direction = Direction::Tx;
+            clones.push(frame.clone());
+        }
+        clones
+    });
+
+    // Only the vector itself
+    assert_eq!(count, 1);
+    assert!(clones.iter().all(|f| f.channel == "can0" && f.direction.is_tx()));
+}
+
+/// Receive frames, format them for the trace log and flush the batcher now and then
+async fn receive(rx: &mut FrameSubscriber, batcher: &Mutex<FrameBatcher>, line: &mut String, frames: usize) {
+    for n in 1..=frames {
+        let frame = rx.recv().await.expect("channel closed");
+        line.clear();
+        TraceFormat::Csv.write_frame(&frame, line);
+        if n % FLUSH_EVERY == 0 {
+            let mut batcher = batcher.lock();
+            if let Some(batch) = batcher.flush() {
+                batcher.recycle(batch.frames);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_receive_path_allocations_per_frame() {
+    // One thread, so the counter sees the interface, channel and subscriber tasks
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let count = runtime.block_on(async {
+        let batcher = Arc::new(Mutex::new(FrameBatcher::new("synth0", MAX_PENDING_FRAMES)));
+        let pending = batcher.clone();
+        let channel = ChannelHandle::spawn("synth0", StatsEmission::default(), DEFAULT_BROADCAST_CAPACITY);
+        let mut rx = channel.subscribe("test");
+        let config = ChannelConfig { interface_id: "synth0".to_string(), bitrate: u32::MAX, listen_only: false };
+        channel.connect(config, Box::new(move |frame| pending.lock().push(frame))).await.unwrap();
+
+        let mut line = String::new();
+        receive(&mut rx, &batcher, &mut line, WARMUP_FRAMES).await;
+        let (count, ()) = allocations_async(receive(&mut rx, &batcher, &mut line, FRAMES)).await;
+        channel.disconnect().await.unwrap();
+        count
+    });
+
+    // Received, filtered, batched, broadcast and formatted for the trace log
+    let per_frame = count as f64 / FRAMES as f64;
+    println!("{} allocations for {} frames, {:.2} per frame", count, FRAMES, per_frame);
+    assert!(per_frame < 0.5, "{:.2} allocations per frame", per_frame);
+}
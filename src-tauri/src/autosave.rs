@@ -0,0 +1,149 @@
+//! Workspace autosave and crash recovery
+//!
+//! The frontend hands its configuration to `update_autosave` whenever it
+//! changes, and a background task writes the latest snapshot to
+//! `autosave.json` in the app data directory. A `session.lock` marker exists
+//! while the app runs and is removed on a clean exit, so a marker found at
+//! startup means the previous session died and its snapshot can be restored.
+
+use crate::error::BootCanError;
+use crate::project::ProjectFile;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often a changed configuration is written
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const SNAPSHOT_FILE: &str = "autosave.json";
+const MARKER_FILE: &str = "session.lock";
+
+/// Configuration written by the autosave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutosaveSnapshot {
+    /// Time of the snapshot in ms since the Unix epoch
+    pub saved_at: u64,
+    pub project: ProjectFile,
+}
+
+/// Autosave state of the running session
+#[derive(Default)]
+pub struct Autosave {
+    /// Directory of the snapshot and marker; autosave is off without one
+    dir: Option<PathBuf>,
+    /// Configuration not written yet
+    pending: Option<ProjectFile>,
+    /// Snapshot left behind by a session that did not exit cleanly
+    recovery: Option<AutosaveSnapshot>,
+}
+
+impl Autosave {
+    /// Start a session in `dir`, picking up the snapshot of a crashed session
+    pub fn open(dir: PathBuf) -> Self {
+        let crashed = dir.join(MARKER_FILE).exists();
+        let recovery = if crashed {
+            fs::read_to_string(dir.join(SNAPSHOT_FILE))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+        } else {
+            None
+        };
+        if recovery.is_some() {
+            tracing::warn!("Previous session did not exit cleanly, autosave available for recovery");
+        }
+
+        if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::write(dir.join(MARKER_FILE), b"")) {
+            tracing::error!("Autosave disabled, cannot write to {}: {}", dir.display(), e);
+            return Self { recovery, ..Default::default() };
+        }
+        Self {
+            dir: Some(dir),
+            pending: None,
+            recovery,
+        }
+    }
+
+    /// Remember the current configuration for the next write
+    pub fn stage(&mut self, project: ProjectFile) {
+        self.pending = Some(project);
+    }
+
+    /// Write the staged configuration, if any
+    pub fn flush(&mut self) -> Result<(), BootCanError> {
+        let (Some(dir), Some(project)) = (&self.dir, self.pending.take()) else {
+            return Ok(());
+        };
+        let snapshot = AutosaveSnapshot {
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            project,
+        };
+        let json = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| BootCanError::Other(format!("Failed to serialize autosave: {}", e)))?;
+        // Write next to the snapshot and rename, so a crash mid-write keeps the previous one
+        let tmp = dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, dir.join(SNAPSHOT_FILE)))
+            .map_err(|e| BootCanError::Io(format!("Failed to write autosave: {}", e)))
+    }
+
+    /// Flush and mark the session as cleanly ended
+    pub fn close(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::error!("{}", e);
+        }
+        if let Some(dir) = self.dir.take() {
+            let _ = fs::remove_file(dir.join(MARKER_FILE));
+        }
+    }
+
+    /// Snapshot of the crashed previous session, until it is discarded
+    pub fn recovery(&self) -> Option<&AutosaveSnapshot> {
+        self.recovery.as_ref()
+    }
+
+    pub fn discard_recovery(&mut self) {
+        self.recovery = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> ProjectFile {
+        ProjectFile {
+            version: "1.0".to_string(),
+            channels: Vec::new(),
+            filters: Vec::new(),
+            transmit_jobs: Vec::new(),
+            secoc: Vec::new(),
+            layout: Default::default(),
+            variables: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_recovery_after_crash_only() {
+        let dir = std::env::temp_dir().join(format!("bootcan-autosave-{}", uuid::Uuid::new_v4()));
+
+        // Crashed session: the marker stays behind
+        let mut session = Autosave::open(dir.clone());
+        assert!(session.recovery().is_none());
+        session.stage(project());
+        session.flush().unwrap();
+        drop(session);
+
+        let mut session = Autosave::open(dir.clone());
+        assert_eq!(session.recovery().unwrap().project.version, "1.0");
+        session.close();
+
+        let session = Autosave::open(dir.clone());
+        assert!(session.recovery().is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,283 @@
+//! Project bundles
+//!
+//! A bundle is a zip archive holding a project file together with the DBC
+//! and SYM files it references and, optionally, trace logs. The project in
+//! the bundle refers to its databases by paths inside the archive, so an
+//! extracted bundle loads like any other project.
+
+use crate::error::BootCanError;
+use crate::project::ProjectFile;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Name of the project file inside a bundle
+const BUNDLE_PROJECT: &str = "project.bootcan";
+const DBC_DIR: &str = "dbc";
+const TRACE_DIR: &str = "traces";
+/// Largest uncompressed size of all entries of a bundle being imported
+const MAX_BUNDLE_SIZE: u64 = 4 << 30;
+
+/// Result of `import_project_bundle`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedBundle {
+    /// Extracted project file, ready for `load_project`
+    pub project_path: String,
+    /// Extracted trace logs
+    pub trace_files: Vec<String>,
+}
+
+/// Archive names of the files in a bundle, unique per directory
+#[derive(Default)]
+struct EntryNames {
+    by_source: HashMap<PathBuf, String>,
+    used: HashSet<String>,
+}
+
+impl EntryNames {
+    /// Name for `source` in `dir`; a file added twice gets the same name
+    fn name(&mut self, dir: &str, source: &Path) -> (String, bool) {
+        if let Some(name) = self.by_source.get(source) {
+            return (name.clone(), false);
+        }
+        let file_name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "file".to_string());
+        let mut name = format!("{}/{}", dir, file_name);
+        let mut n = 1;
+        while self.used.contains(&name) {
+            n += 1;
+            name = format!("{}/{}_{}", dir, n, file_name);
+        }
+        self.used.insert(name.clone());
+        self.by_source.insert(source.to_path_buf(), name.clone());
+        (name, true)
+    }
+}
+
+/// Write `project`, loaded from `project_dir`, its databases and `traces` to a bundle
+pub fn write_bundle<W: Write + Seek>(
+    writer: W,
+    project: &ProjectFile,
+    project_dir: &Path,
+    traces: &[String],
+) -> Result<(), BootCanError> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut names = EntryNames::default();
+    let mut project = project.clone();
+
+    for channel in &mut project.channels {
+        let Some(path) = &channel.dbc_file else {
+            continue;
+        };
+        let source = project_dir.join(path);
+        let (name, new) = names.name(DBC_DIR, &source);
+        if new {
+            // A missing file is fine when the project embeds the database
+            match fs::read(&source) {
+                Ok(content) => add_file(&mut zip, &name, &content, options)?,
+                Err(_) if channel.dbc_content.is_some() => {}
+                Err(e) => return Err(BootCanError::Io(format!("Failed to read DBC {}: {}", source.display(), e))),
+            }
+        }
+        channel.dbc_file = Some(name);
+    }
+
+    for trace in traces {
+        let source = Path::new(trace);
+        let content = fs::read(source).map_err(|e| BootCanError::Io(format!("Failed to read trace {}: {}", trace, e)))?;
+        let (name, new) = names.name(TRACE_DIR, source);
+        if new {
+            add_file(&mut zip, &name, &content, options)?;
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&project)
+        .map_err(|e| BootCanError::Other(format!("Failed to serialize project: {}", e)))?;
+    add_file(&mut zip, BUNDLE_PROJECT, json.as_bytes(), options)?;
+    zip.finish().map_err(|e| BootCanError::Io(format!("Failed to write bundle: {}", e)))?;
+    Ok(())
+}
+
+fn add_file<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    content: &[u8],
+    options: SimpleFileOptions,
+) -> Result<(), BootCanError> {
+    zip.start_file(name, options)
+        .and_then(|_| zip.write_all(content).map_err(Into::into))
+        .map_err(|e| BootCanError::Io(format!("Failed to add {} to bundle: {}", name, e)))
+}
+
+/// Extract a bundle into `target_dir`, which must not hold a bundled project already
+///
+/// Every entry is checked before anything is written: its path must stay in
+/// `target_dir` and must not exist yet, and the entries together must not
+/// exceed `MAX_BUNDLE_SIZE` uncompressed.
+pub fn extract_bundle<R: Read + Seek>(reader: R, target_dir: &Path) -> Result<ImportedBundle, BootCanError> {
+    let mut archive = ZipArchive::new(reader)
+        .map_err(|e| BootCanError::Parse(format!("Invalid project bundle: {}", e)))?;
+    if archive.index_for_name(BUNDLE_PROJECT).is_none() {
+        return Err(BootCanError::Parse(format!("Project bundle contains no {}", BUNDLE_PROJECT)));
+    }
+    let project_path = target_dir.join(BUNDLE_PROJECT);
+    if project_path.exists() {
+        return Err(BootCanError::InvalidInput(format!("{} already contains a project", target_dir.display())));
+    }
+
+    let mut entries = Vec::with_capacity(archive.len());
+    let mut files = HashSet::new();
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i).map_err(|e| BootCanError::Parse(e.to_string()))?;
+        // Entries pointing outside the target directory are refused
+        let name = entry.enclosed_name().ok_or_else(|| {
+            BootCanError::InvalidInput(format!("Project bundle contains an unsafe path: {}", entry.name()))
+        })?;
+        let path = target_dir.join(&name);
+        if !entry.is_dir() && (path.exists() || !files.insert(path.clone())) {
+            return Err(BootCanError::InvalidInput(format!("{} already exists", path.display())));
+        }
+        total = total.saturating_add(entry.size());
+        if total > MAX_BUNDLE_SIZE {
+            return Err(BootCanError::InvalidInput(format!(
+                "Project bundle is larger than {} MiB uncompressed", MAX_BUNDLE_SIZE >> 20
+            )));
+        }
+        entries.push((i, name, path, entry.is_dir(), entry.size()));
+    }
+
+    let mut trace_files = Vec::new();
+    for (i, name, path, is_dir, size) in entries {
+        if is_dir {
+            fs::create_dir_all(&path)
+                .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", path.display(), e)))?;
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", dir.display(), e)))?;
+        }
+        let entry = archive.by_index(i).map_err(|e| BootCanError::Parse(e.to_string()))?;
+        // Never replace a file, even one created since the check
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", path.display(), e)))?;
+        // One byte more than declared tells a lying size apart
+        let written = std::io::copy(&mut entry.take(size + 1), &mut file)
+            .map_err(|e| BootCanError::Io(format!("Failed to extract {}: {}", name.display(), e)))?;
+        if written > size {
+            return Err(BootCanError::Parse(format!("{} is larger than declared in the bundle", name.display())));
+        }
+        if name.starts_with(TRACE_DIR) {
+            trace_files.push(path.to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(ImportedBundle {
+        project_path: project_path.to_string_lossy().into_owned(),
+        trace_files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bootcan-bundle-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn project(dbc_files: &[&str]) -> ProjectFile {
+        serde_json::from_value(serde_json::json!({
+            "version": "1.1",
+            "channels": dbc_files
+                .iter()
+                .enumerate()
+                .map(|(i, dbc)| serde_json::json!({ "id": format!("can{}", i), "name": "", "interfaceId": null, "bitrate": 500000, "dbcFile": dbc }))
+                .collect::<Vec<_>>(),
+            "filters": [],
+            "transmitJobs": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_rewrites_paths() {
+        let source = temp_dir("source");
+        fs::create_dir_all(source.join("a")).unwrap();
+        fs::create_dir_all(source.join("b")).unwrap();
+        fs::write(source.join("a/bus.dbc"), "VERSION \"a\"").unwrap();
+        fs::write(source.join("b/bus.dbc"), "VERSION \"b\"").unwrap();
+        let trace = source.join("drive.asc");
+        fs::write(&trace, "date Mon Jan 1").unwrap();
+
+        // Two channels share a database; another file has the same name
+        let project = project(&["a/bus.dbc", "a/bus.dbc", "b/bus.dbc"]);
+        let mut bundle = Cursor::new(Vec::new());
+        write_bundle(&mut bundle, &project, &source, &[trace.to_string_lossy().into_owned()]).unwrap();
+
+        let target = temp_dir("target");
+        let imported = extract_bundle(Cursor::new(bundle.get_ref()), &target).unwrap();
+        let bundled: ProjectFile = serde_json::from_str(&fs::read_to_string(&imported.project_path).unwrap()).unwrap();
+        let dbc_files: Vec<_> = bundled.channels.iter().map(|ch| ch.dbc_file.clone().unwrap()).collect();
+        assert_eq!(dbc_files, vec!["dbc/bus.dbc", "dbc/bus.dbc", "dbc/2_bus.dbc"]);
+        assert_eq!(fs::read_to_string(target.join("dbc/2_bus.dbc")).unwrap(), "VERSION \"b\"");
+        assert_eq!(imported.trace_files, vec![target.join("traces/drive.asc").to_string_lossy().into_owned()]);
+
+        // A second import into the same directory would overwrite the first
+        assert!(extract_bundle(Cursor::new(bundle.get_ref()), &target).is_err());
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn test_rejects_unsafe_entries() {
+        let mut bundle = Cursor::new(Vec::new());
+        let mut zip = ZipWriter::new(&mut bundle);
+        let options = SimpleFileOptions::default();
+        add_file(&mut zip, BUNDLE_PROJECT, b"{}", options).unwrap();
+        add_file(&mut zip, "../escape.dbc", b"", options).unwrap();
+        zip.finish().unwrap();
+
+        let target = temp_dir("unsafe");
+        let err = extract_bundle(Cursor::new(bundle.get_ref()), &target).unwrap_err();
+        assert!(err.message().contains("unsafe path"));
+        assert!(!target.parent().unwrap().join("escape.dbc").exists());
+        // Nothing is extracted before all entries are checked
+        assert!(!target.join(BUNDLE_PROJECT).exists());
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn test_refuses_to_overwrite_files() {
+        let mut bundle = Cursor::new(Vec::new());
+        let mut zip = ZipWriter::new(&mut bundle);
+        let options = SimpleFileOptions::default();
+        add_file(&mut zip, BUNDLE_PROJECT, b"{}", options).unwrap();
+        add_file(&mut zip, "dbc/bus.dbc", b"bundled", options).unwrap();
+        zip.finish().unwrap();
+
+        let target = temp_dir("overwrite");
+        fs::create_dir_all(target.join(DBC_DIR)).unwrap();
+        fs::write(target.join("dbc/bus.dbc"), "mine").unwrap();
+        let err = extract_bundle(Cursor::new(bundle.get_ref()), &target).unwrap_err();
+        assert!(err.message().contains("already exists"));
+        assert_eq!(fs::read_to_string(target.join("dbc/bus.dbc")).unwrap(), "mine");
+        assert!(!target.join(BUNDLE_PROJECT).exists());
+        let _ = fs::remove_dir_all(&target);
+    }
+}
@@ -1,32 +1,296 @@
 //! Tauri IPC commands for frontend-backend communication
 
-use crate::core::bus_stats::BusStats;
-use crate::core::channel::{ChannelConfig, ChannelState};
+use crate::core::anomaly::{AnomalyConfig, AnomalyDetector};
+use crate::core::auto_responder::{self, AutoResponder, ResponseRule};
+use crate::core::bus_stats::{BusStats, ExtendedBusStats, StatsEmission};
+use crate::core::id_stats::{self, IdHeatmap, IdStats};
+use crate::core::canopen::sdo::{SdoClient, DEFAULT_SDO_TIMEOUT};
+use crate::core::canopen::pdo;
+use crate::core::bootloader::{self, BootloaderTarget, FirmwareImage, FlashProgress, FlashStage};
+use crate::core::influx::{self, InfluxExportConfig, InfluxExportHandle, InfluxExportStatus, InfluxWriter, SignalSampler};
+use crate::core::isotp::IsoTpConfig;
+use crate::core::obd::{self, ObdServer, ObdSimConfig};
+use crate::core::plugin::{self, PluginOutput, PluginRecord, WasmPlugin};
+use crate::core::scenario::{self, Scenario, ScenarioHost, ScenarioReport};
+use crate::core::secoc::SecOcConfig;
+use crate::core::stats_history::{HistoryTier, HistoryView, ThroughputSample};
+use crate::core::signal_series::{self, SeriesBin, SignalRef, SignalStatistics, TimeRange};
+use crate::core::signal_watch::{SignalWatcher, WatchedSignal};
+use crate::core::decode_pool::{DecodePoolStats, DecodedFrame, DECODED_QUEUE_LEN};
+use crate::core::gvret::{self, GvretServerConfig, GvretServerHandle};
+use crate::core::tcp_bridge::{
+    BridgeEndpoint, BridgeLink, BridgeRole, TcpBridge, TcpBridgeConfig, TcpBridgeHandle, TcpBridgeStatus,
+};
+use crate::core::zmq_bridge::{self, ZmqBridgeConfig, ZmqBridgeHandle};
+use crate::core::udp_broadcast::{self, UdpBroadcastConfig};
+use crate::core::top_talkers::{TalkerCounter, TalkerSource, TopTalkers};
+use crate::core::traffic_gen::{TrafficGenConfig, TrafficGenStatus, TrafficGenerator};
+use crate::core::virtual_ecu::{self, VirtualEcu, VirtualEcuConfig, VirtualEcuHandle, VirtualEcuStatus};
+use crate::core::uds::scanner::{self, ScanConfig, ScanProgress, ScanReport};
+use crate::core::variables::Variables;
+use crate::core::uds::{server, DescribedResponse, DiagDescription, EcuSimConfig, UdsClient, UdsSession, UdsSessionInfo};
+use crate::core::cycle_monitor::{CycleCounts, CycleMonitor, CycleMonitorConfig};
+use crate::core::ccp::{CcpMaster, CcpTarget, DaqElement, DaqListSize, DEFAULT_CCP_TIMEOUT};
+use crate::core::canopen::{EdsParser, NmtCommand, NmtMonitor, NodeStatus, PdoDecoder, PdoMapping};
+use crate::core::channel::{ChannelConfig, ChannelDiagnostics, ChannelHandle};
+use crate::core::frame_batch::{FrameBatcher, MAX_PENDING_FRAMES};
+use crate::core::frame_store::FrameDelta;
 use crate::core::message::{CanFrame, FramePayload};
 use crate::core::trace_logger::{TraceLogger, TraceLoggerConfig, TraceFormat};
+use crate::core::trace_mutation::TraceMutation;
+use crate::core::trace_decode::{self, DecodeProgress};
+use crate::core::trace_export;
 use crate::core::trace_player::PlaybackState;
-use crate::core::dbc::{DbcParser, SymParser, DecodedSignal};
+use crate::core::dbc::{DbcDatabase, DbcParser, LdfParser, SymParser, DecodedSignal};
 use crate::core::filter::FilterSet;
-use crate::hal::traits::{enumerate_interfaces, InterfaceInfo};
-use crate::AppState;
-use std::path::PathBuf;
+use crate::core::frame_link::{ChannelLink, FrameLink};
+use crate::core::gateway::{self, Gateway, GatewayConfig, GatewayHandle, SignalOverride};
+use crate::core::j1939::request::{DEFAULT_REQUEST_TIMEOUT, DEFAULT_TOOL_ADDRESS};
+use crate::core::j1939::{self, DiagnosticMessage, DmType, J1939Decoder, PgnResponse, GLOBAL_ADDRESS};
+use crate::error::BootCanError;
+use crate::hal::traits::{enumerate_interfaces, FaultConfig, InterfaceInfo};
+use crate::hal::virtual_can::{SharedVirtualBus, VirtualCanBus};
+use crate::autosave::AutosaveSnapshot;
+use crate::bundle::{self, ImportedBundle};
+use crate::project::{
+    AppliedProject, DbcSaveOptions, LayoutStore, LoadedProject, PreparedProject, ProjectChannel, ProjectFile, ProjectFilter, ProjectTransmitJob,
+    PROJECT_VERSION,
+};
+use crate::recent_projects::RecentProjects;
+use crate::settings::{AppSettings, FrameDelivery};
+use crate::rest::{self, RestServerInfo};
+use crate::rpc;
+use crate::diagnostics::WarningRecord;
+use crate::{AppState, CcpSession};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, State};
 use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch, Mutex as TokioMutex, Notify};
+use tracing::Instrument;
 use serde::{Deserialize, Serialize};
 use std::fs;
 
+/// Unchanged statistics refreshes after which the stats loop of a channel
+/// waits for traffic instead of ticking
+const IDLE_STATS_TICKS: u32 = 10;
+/// Refresh interval of a quiet channel, for controller state changes without traffic
+const IDLE_STATS_INTERVAL: Duration = Duration::from_secs(5);
+/// How often an idle frame batching task checks whether the channel disconnected
+const IDLE_BATCH_CHECK: Duration = Duration::from_secs(1);
+
 /// Bus statistics with channel ID for per-channel tracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelBusStats {
     pub channel_id: String,
     #[serde(flatten)]
-    pub stats: BusStats,
+    pub stats: ExtendedBusStats,
+}
+
+/// Per-ID statistics of a channel, emitted as `id-stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelIdStats {
+    pub channel_id: String,
+    pub ids: Vec<IdStats>,
+}
+
+/// Receive loop callback passing frames to the frontend
+///
+/// Frames go to the frame store when the frontend polls, and are otherwise
+/// emitted in `can-messages` batches every `event_batch_ms`, or one by one as
+/// `can-message` when batching is off.
+fn frame_emitter(state: &AppState, app: AppHandle, channel_id: &str) -> Box<dyn Fn(&CanFrame) + Send + Sync> {
+    let (delivery, batch_ms) = {
+        let settings = state.settings.read();
+        (settings.values().frame_delivery, settings.values().event_batch_ms)
+    };
+    if delivery == FrameDelivery::Store {
+        let store = state.frame_store.clone();
+        return Box::new(move |frame| store.lock().push(frame));
+    }
+    if batch_ms == 0 {
+        return Box::new(move |frame| {
+            if let Err(e) = app.emit("can-message", frame) {
+                tracing::error!("Failed to emit can-message event: {:?}", e);
+            }
+        });
+    }
+
+    let batcher = Arc::new(Mutex::new(FrameBatcher::new(channel_id, MAX_PENDING_FRAMES)));
+    let pending = batcher.clone();
+    let wake = Arc::new(Notify::new());
+    let woken = wake.clone();
+    let span = tracing::info_span!("emit", channel = %channel_id);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(batch_ms));
+        loop {
+            interval.tick().await;
+            // The receive loop has ended once it dropped its side
+            let done = Arc::strong_count(&batcher) == 1;
+            let (dropped, batch) = {
+                let mut batcher = batcher.lock();
+                (batcher.dropped(), batcher.flush())
+            };
+            if dropped > 0 {
+                tracing::warn!("Frontend falling behind, {} frames not emitted", dropped);
+            }
+            let idle = batch.is_none();
+            if let Some(batch) = batch {
+                if let Err(e) = app.emit("can-messages", &batch) {
+                    tracing::error!("Failed to emit can-messages event: {:?}", e);
+                }
+                batcher.lock().recycle(batch.frames);
+            }
+            if done {
+                break;
+            }
+            if idle {
+                // Nothing arrived for a whole window; sleep until the next frame
+                let _ = tokio::time::timeout(IDLE_BATCH_CHECK, woken.notified()).await;
+                interval.reset();
+            }
+        }
+    }.instrument(span));
+    Box::new(move |frame| {
+        pending.lock().push(frame);
+        wake.notify_one();
+    })
+}
+
+/// Callback passing transmitted frames to the frontend
+///
+/// Like received frames, they go to the frame store when the frontend polls;
+/// otherwise each one is emitted as `can-message`.
+fn sent_frame_emitter(state: &AppState, app: AppHandle) -> Box<dyn Fn(&CanFrame) + Send + Sync> {
+    if state.settings.read().values().frame_delivery == FrameDelivery::Store {
+        let store = state.frame_store.clone();
+        return Box::new(move |frame| store.lock().push(frame));
+    }
+    Box::new(move |frame| {
+        if let Err(e) = app.emit("can-message", frame) {
+            tracing::error!("Failed to emit can-message event: {:?}", e);
+        }
+    })
+}
+
+/// Refresh the bus load of a connected channel and emit `bus-stats` at the
+/// channel's stats interval and `id-stats` every second until it disconnects
+///
+/// Once the statistics stopped changing, the loop waits for the next frame
+/// or state change, refreshing only every `IDLE_STATS_INTERVAL` meanwhile.
+/// The statistics history fills the seconds skipped in between as empty.
+fn spawn_stats_loop(app: AppHandle, channel: ChannelHandle) {
+    tokio::spawn(async move {
+        let channel_id = channel.id().to_string();
+        let mut last_id_stats = std::time::Instant::now();
+        let mut last_emitted = None;
+        let mut id_stats_total = None;
+        let Ok(snapshot) = channel.snapshot().await else {
+            return;
+        };
+        let mut emission = snapshot.stats_emission;
+        let (mut last_key, mut quiet_ticks, mut seen) = (None, 0, 0);
+
+        loop {
+            if quiet_ticks < IDLE_STATS_TICKS {
+                tokio::time::sleep(emission.interval()).await;
+            } else {
+                let _ = tokio::time::timeout(IDLE_STATS_INTERVAL, channel.wait_for_activity(seen)).await;
+            }
+
+            // Bus load, controller state and history are refreshed by the channel task
+            let Ok(Some(snapshot)) = channel.update_stats().await else {
+                break;
+            };
+            emission = snapshot.stats_emission;
+            let stats = &snapshot.stats.base;
+            let total_messages = stats.tx_count + stats.rx_count;
+            seen = total_messages + stats.error_count;
+
+            let key = (
+                total_messages,
+                stats.error_count,
+                stats.controller_state,
+                stats.tx_error_counter,
+                stats.rx_error_counter,
+                stats.bus_load.to_bits(),
+            );
+            quiet_ticks = if last_key == Some(key) { quiet_ticks + 1 } else { 0 };
+            last_key = Some(key);
+            if !emission.on_change || last_emitted != Some(key) {
+                last_emitted = Some(key);
+                let bus_stats = ChannelBusStats {
+                    channel_id: channel_id.clone(),
+                    stats: snapshot.stats,
+                };
+                let _ = app.emit("bus-stats", bus_stats);
+            }
+
+            if last_id_stats.elapsed() >= Duration::from_secs(1)
+                && (!emission.on_change || id_stats_total != Some(total_messages))
+            {
+                last_id_stats = std::time::Instant::now();
+                id_stats_total = Some(total_messages);
+                if let Ok(ids) = channel.id_stats().await {
+                    let _ = app.emit("id-stats", ChannelIdStats {
+                        channel_id: channel_id.clone(),
+                        ids,
+                    });
+                }
+            }
+        }
+    });
+}
+
+/// Set how often a channel emits `bus-stats`, optionally only when they change
+///
+/// The channel is created if needed, so the setting can be applied before connecting.
+#[tauri::command]
+pub async fn set_stats_emission(
+    state: State<'_, AppState>,
+    channel_id: String,
+    emission: StatsEmission,
+) -> Result<(), BootCanError> {
+    let channel = state.channel_manager.write().get_or_create_channel(&channel_id);
+    channel.set_stats_emission(emission).await
+}
+
+/// Bus load and frame counts of a channel at one-second, one-minute or one-hour resolution
+#[tauri::command]
+pub async fn get_stats_history(
+    state: State<'_, AppState>,
+    channel_id: String,
+    tier: HistoryTier,
+) -> Result<HistoryView, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    channel.history(tier).await
+}
+
+/// Per-second rx/tx frame and byte rates of a channel over the last `seconds` (up to ten minutes)
+#[tauri::command]
+pub async fn get_throughput_history(
+    state: State<'_, AppState>,
+    channel_id: String,
+    seconds: Option<usize>,
+) -> Result<Vec<ThroughputSample>, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    channel.throughput(seconds.unwrap_or(60)).await
+}
+
+/// Get the statistics emission settings of a channel
+#[tauri::command]
+pub async fn get_stats_emission(state: State<'_, AppState>, channel_id: String) -> Result<StatsEmission, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    Ok(channel.snapshot().await?.stats_emission)
 }
 
 /// Get list of available CAN interfaces
 #[tauri::command]
-pub async fn get_interfaces() -> Result<Vec<InterfaceInfo>, String> {
+pub async fn get_interfaces() -> Result<Vec<InterfaceInfo>, BootCanError> {
     Ok(enumerate_interfaces())
 }
 
@@ -36,8 +300,9 @@ pub async fn connect(
     state: State<'_, AppState>,
     app: AppHandle,
     interface_id: String,
-    bitrate: u32,
-) -> Result<(), String> {
+    bitrate: Option<u32>,
+) -> Result<(), BootCanError> {
+    let bitrate = bitrate.unwrap_or(state.settings.read().values().default_bitrate);
     let config = ChannelConfig {
         interface_id: interface_id.clone(),
         bitrate,
@@ -52,110 +317,11 @@ pub async fn connect(
         channel
     };
 
-    // Connect the channel
-    {
-        let mut ch = channel.write();
-        let connect_result = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(ch.connect(config))
-        });
-        connect_result?;
-    }
-
-    // Start the receive loop
-    let channel_clone = channel.clone();
-    let app_clone = app.clone();
-
-    // Spawn receive loop using spawn_blocking to avoid Send issues
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_millis(1));
-        
-        loop {
-            interval.tick().await;
-            
-            // Check connection status and receive in a synchronous block
-            let result = tokio::task::spawn_blocking({
-                let channel = channel_clone.clone();
-                let app = app_clone.clone();
-                move || {
-                    let mut ch = channel.write();
-                    // Use the public receive method
-                    let receive_result = tokio::runtime::Handle::current().block_on(ch.receive());
-                    match receive_result {
-                        Ok(Some(frame)) => {
-                            // Frame was received and passed filter - emit to frontend
-                            if let Err(e) = app.emit("can-message", &frame) {
-                                log::error!("Failed to emit can-message event: {:?}", e);
-                            }
-                        }
-                        Ok(None) => {
-                            // No frame available or filtered out - continue
-                        }
-                        Err(e) => {
-                            log::error!("Receive error: {}", e);
-                        }
-                    }
-                    Ok::<(), String>(())
-                }
-            }).await;
-            
-            if let Err(e) = result {
-                log::error!("Error in receive loop: {:?}", e);
-                break;
-            }
-        }
-    });
+    // Connect the channel, emitting every received frame that passed the filter
+    channel.connect(config, frame_emitter(&state, app.clone(), &interface_id)).await?;
 
     // Start statistics update loop
-    let channel_stats = channel.clone();
-    let app_stats = app.clone();
-    let bitrate_for_stats = bitrate;
-    let channel_id_for_stats = interface_id.clone();
-    
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_millis(100));
-        let mut last_total_messages = 0u64;
-        let mut last_update_time = std::time::Instant::now();
-        
-        loop {
-            interval.tick().await;
-            
-            let result = {
-                let mut ch = channel_stats.write();
-                
-                if ch.state != ChannelState::Connected {
-                    None
-                } else {
-                    // Calculate message rate for bus load
-                    let now = std::time::Instant::now();
-                    let elapsed = now.duration_since(last_update_time).as_secs_f64();
-                    
-                    if elapsed > 0.0 {
-                        let total_messages = ch.stats.tx_count + ch.stats.rx_count;
-                        let message_delta = total_messages.saturating_sub(last_total_messages);
-                        let messages_per_second = message_delta as f64 / elapsed;
-                        
-                        // Update bus load
-                        ch.stats.update_bus_load(messages_per_second, bitrate_for_stats);
-                        
-                        last_total_messages = total_messages;
-                        last_update_time = now;
-                    }
-                    
-                    Some(ChannelBusStats {
-                        channel_id: channel_id_for_stats.clone(),
-                        stats: ch.stats.clone(),
-                    })
-                }
-            };
-            
-            match result {
-                Some(channel_stats) => {
-                    let _ = app_stats.emit("bus-stats", channel_stats);
-                }
-                None => break,
-            }
-        }
-    });
+    spawn_stats_loop(app.clone(), channel);
 
     Ok(())
 }
@@ -167,8 +333,9 @@ pub async fn connect_channel(
     app: AppHandle,
     channel_id: String,
     interface_id: String,
-    bitrate: u32,
-) -> Result<(), String> {
+    bitrate: Option<u32>,
+) -> Result<(), BootCanError> {
+    let bitrate = bitrate.unwrap_or(state.settings.read().values().default_bitrate);
     let config = ChannelConfig {
         interface_id: interface_id.clone(),
         bitrate,
@@ -183,166 +350,30 @@ pub async fn connect_channel(
         channel
     };
 
-    // Connect - acquire lock, connect, release immediately
-    {
-        let mut ch = channel.write();
-        // For non-async connect, we need to block on the future
-        // Since virtual CAN is synchronous, this should work
-        let connect_result = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(ch.connect(config))
-        });
-        connect_result?;
-    }
-
-    // Start the receive loop
-    let channel_clone = channel.clone();
-    let app_clone = app.clone();
-    let channel_id_clone = channel_id.clone();
-
-    // Spawn receive loop using spawn_blocking to avoid Send issues
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_millis(1));
-        
-        loop {
-            interval.tick().await;
-            
-            // Check connection status and receive in a synchronous block
-            let result = tokio::task::spawn_blocking({
-                let channel = channel_clone.clone();
-                let app = app_clone.clone();
-                move || {
-                    let mut ch = channel.write();
-                    
-                    // Check if still connected
-                    if ch.state != ChannelState::Connected {
-                        return Ok::<bool, String>(false);
-                    }
-                    
-                    // Use the public receive method
-                    let rx_result = tokio::runtime::Handle::current()
-                        .block_on(ch.receive());
-                    
-                    match rx_result {
-                        Ok(Some(frame)) => {
-                            // Frame received and passed filter - emit to frontend
-                            if let Err(e) = app.emit("can-message", &frame) {
-                                log::error!("Failed to emit can-message event: {:?}", e);
-                            }
-                            Ok::<bool, String>(true)
-                        }
-                        Ok(None) => {
-                            // No frame available or filtered out - continue
-                            Ok::<bool, String>(true)
-                        }
-                        Err(e) => {
-                            log::error!("Receive error: {}", e);
-                            Ok::<bool, String>(true)
-                        }
-                    }
-                }
-            }).await;
-            
-            match result {
-                Ok(Ok(should_continue)) => {
-                    if !should_continue {
-                        break;
-                    }
-                }
-                Ok(Err(e)) => {
-                    log::error!("Receive error: {}", e);
-                }
-                Err(e) => {
-                    log::error!("Task error: {:?}", e);
-                    break;
-                }
-            }
-        }
-        
-        log::info!("Receive loop ended for channel {}", channel_id_clone);
-    });
+    // Connect, emitting every received frame that passed the filter
+    channel.connect(config, frame_emitter(&state, app.clone(), &channel_id)).await?;
 
-    log::info!("Connected channel {} to {} at {} bps", channel_id, interface_id, bitrate);
+    tracing::info!("Connected channel {} to {} at {} bps", channel_id, interface_id, bitrate);
     
     // Start statistics update loop
-    let channel_stats = channel.clone();
-    let app_stats = app.clone();
-    let bitrate_for_stats = bitrate;
-    let channel_id_for_stats = channel_id.clone();
-    
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_millis(100));
-        let mut last_total_messages = 0u64;
-        let mut last_update_time = std::time::Instant::now();
-        
-        loop {
-            interval.tick().await;
-            
-            let result = {
-                let mut ch = channel_stats.write();
-                
-                if ch.state != ChannelState::Connected {
-                    None
-                } else {
-                    // Calculate message rate for bus load
-                    let now = std::time::Instant::now();
-                    let elapsed = now.duration_since(last_update_time).as_secs_f64();
-                    
-                    if elapsed > 0.0 {
-                        let total_messages = ch.stats.tx_count + ch.stats.rx_count;
-                        let message_delta = total_messages.saturating_sub(last_total_messages);
-                        let messages_per_second = message_delta as f64 / elapsed;
-                        
-                        // Update bus load
-                        ch.stats.update_bus_load(messages_per_second, bitrate_for_stats);
-                        
-                        last_total_messages = total_messages;
-                        last_update_time = now;
-                    }
-                    
-                    Some(ChannelBusStats {
-                        channel_id: channel_id_for_stats.clone(),
-                        stats: ch.stats.clone(),
-                    })
-                }
-            };
-            
-            match result {
-                Some(channel_stats) => {
-                    let _ = app_stats.emit("bus-stats", channel_stats);
-                }
-                None => break,
-            }
-        }
-    });
+    spawn_stats_loop(app.clone(), channel);
 
-    log::info!("Connected to {} at {} bps", interface_id, bitrate);
+    tracing::info!("Connected to {} at {} bps", interface_id, bitrate);
     Ok(())
 }
 
 /// Disconnect from the current CAN interface (legacy)
 #[tauri::command]
-pub async fn disconnect(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn disconnect(state: State<'_, AppState>) -> Result<(), BootCanError> {
     let channel = {
         let manager = state.channel_manager.read();
         manager.get_active_channel()
     };
 
     if let Some(channel) = channel {
-        let channel_id = {
-            let ch = channel.read();
-            ch.id.clone()
-        };
-        
-        // Disconnect in a blocking context
-        tokio::task::spawn_blocking({
-            let channel = channel.clone();
-            move || {
-                let mut ch = channel.write();
-                tokio::runtime::Handle::current().block_on(ch.disconnect())
-            }
-        }).await.map_err(|e| e.to_string())??;
-        
-        log::info!("Disconnected from {}", channel_id);
+        channel.disconnect().await?;
+
+        tracing::info!("Disconnected from {}", channel.id());
     }
 
     Ok(())
@@ -353,23 +384,16 @@ pub async fn disconnect(state: State<'_, AppState>) -> Result<(), String> {
 pub async fn disconnect_channel(
     state: State<'_, AppState>,
     channel_id: String,
-) -> Result<(), String> {
+) -> Result<(), BootCanError> {
     let channel = {
         let manager = state.channel_manager.read();
         manager.get_channel(&channel_id)
     };
 
     if let Some(channel) = channel {
-        // Disconnect in a blocking context
-        tokio::task::spawn_blocking({
-            let channel = channel.clone();
-            move || {
-                let mut ch = channel.write();
-                tokio::runtime::Handle::current().block_on(ch.disconnect())
-            }
-        }).await.map_err(|e| e.to_string())??;
-        
-        log::info!("Disconnected channel {}", channel_id);
+        channel.disconnect().await?;
+
+        tracing::info!("Disconnected channel {}", channel_id);
     }
 
     Ok(())
@@ -381,8 +405,8 @@ pub async fn send_message(
     state: State<'_, AppState>,
     app: AppHandle,
     frame: FramePayload,
-) -> Result<(), String> {
-    log::info!("send_message called with frame ID: 0x{:X}", frame.id);
+) -> Result<(), BootCanError> {
+    tracing::info!("send_message called with frame ID: 0x{:X}", frame.id);
     
     let channel = {
         let mut manager = state.channel_manager.write();
@@ -397,65 +421,47 @@ pub async fn send_message(
             if let Some(active_id) = active_id {
                 manager.get_or_create_channel(&active_id)
             } else {
-                return Err("No channel specified and no active channel".to_string());
+                return Err(BootCanError::InvalidInput("No channel specified and no active channel".to_string()));
             }
         }
     };
 
-    // Create base frame
-    let can_frame: CanFrame = frame.into();
+    // Create base frame, appending the SecOC authenticator if configured
+    let mut can_frame: CanFrame = frame.into();
+    state.secoc.write().protect(channel.id(), &mut can_frame)?;
 
-    // Send in a blocking context and get the frame with proper timestamp
-    let sent_frame = tokio::task::spawn_blocking({
-        let channel = channel.clone();
-        let frame = can_frame.clone();
-        move || {
-            let mut ch = channel.write();
-            
-            // Get timestamp AFTER acquiring write lock, right before send
-            let timestamp = ch.get_timestamp();
-            let channel_id = ch.id.clone();
-            
-            // Create the frame we'll emit with proper metadata
-            let mut tx_frame = frame.clone();
-            tx_frame.channel = channel_id;
-            tx_frame.timestamp = timestamp;
-            tx_frame.direction = "tx".to_string();
-            
-            // Send the frame
-            let result = tokio::runtime::Handle::current().block_on(ch.send(frame));
-            
-            result.map(|_| tx_frame)
-        }
-    }).await.map_err(|e| e.to_string())??;
+    // Send and get the frame with its channel, direction and timestamp
+    let sent_frame = channel.transmit(can_frame).await?;
 
-    log::info!("Frame sent successfully, emitting event with timestamp {}", sent_frame.timestamp);
+    tracing::info!("Frame sent successfully, timestamp {}", sent_frame.timestamp);
 
-    // Emit the sent frame to the frontend
-    if let Err(e) = app.emit("can-message", &sent_frame) {
-        log::error!("Failed to emit can-message event: {:?}", e);
-    }
+    // Pass the sent frame to the frontend
+    sent_frame_emitter(&state, app)(&sent_frame);
 
     Ok(())
 }
 
 /// Get current bus statistics
 #[tauri::command]
-pub async fn get_bus_stats(state: State<'_, AppState>) -> Result<BusStats, String> {
+pub async fn get_bus_stats(state: State<'_, AppState>) -> Result<BusStats, BootCanError> {
     let channel = {
         let manager = state.channel_manager.read();
         manager.get_active_channel()
     };
 
     match channel {
-        Some(channel) => {
-            let ch = channel.read();
-            Ok(ch.stats.clone())
-        }
+        Some(channel) => Ok(channel.snapshot().await?.stats.base),
         None => Ok(BusStats::default()),
     }
 }
 
+/// Per-ID counters and timing of a channel, ordered by ID
+#[tauri::command]
+pub async fn get_id_stats(state: State<'_, AppState>, channel_id: String) -> Result<Vec<IdStats>, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    channel.id_stats().await
+}
+
 /// Start periodic message transmission
 #[tauri::command]
 pub async fn start_periodic_transmit(
@@ -463,7 +469,17 @@ pub async fn start_periodic_transmit(
     app: AppHandle,
     frame: FramePayload,
     interval_ms: u64,
-) -> Result<String, String> {
+) -> Result<String, BootCanError> {
+    spawn_periodic_transmit(&state, app, frame, interval_ms)
+}
+
+/// Spawn a periodic transmit job; returns its job ID
+fn spawn_periodic_transmit(
+    state: &AppState,
+    app: AppHandle,
+    frame: FramePayload,
+    interval_ms: u64,
+) -> Result<String, BootCanError> {
     let job_id = uuid::Uuid::new_v4().to_string();
     
     let channel = {
@@ -479,7 +495,7 @@ pub async fn start_periodic_transmit(
             if let Some(active_id) = active_id {
                 manager.get_or_create_channel(&active_id)
             } else {
-                return Err("No channel specified and no active channel".to_string());
+                return Err(BootCanError::InvalidInput("No channel specified and no active channel".to_string()));
             }
         }
     };
@@ -496,6 +512,8 @@ pub async fn start_periodic_transmit(
     let can_frame: CanFrame = frame.into();
     let job_id_clone = job_id.clone();
     let periodic_jobs = state.periodic_jobs.clone();
+    let secoc = state.secoc.clone();
+    let emit_sent = sent_frame_emitter(state, app);
 
     // Spawn periodic transmit task
     tokio::spawn(async move {
@@ -504,51 +522,24 @@ pub async fn start_periodic_transmit(
         loop {
             tokio::select! {
                 _ = interval.tick() => {
-                    let result = tokio::task::spawn_blocking({
-                        let channel = channel.clone();
-                        let frame = can_frame.clone();
-                        move || {
-                            let mut ch = channel.write();
-                            
-                            if ch.state != ChannelState::Connected {
-                                return (false, None);
-                            }
-                            
-                            // Get timestamp right before send
-                            let timestamp = ch.get_timestamp();
-                            let channel_id = ch.id.clone();
-                            
-                            // Create TX frame with proper metadata
-                            let mut tx_frame = frame.clone();
-                            tx_frame.channel = channel_id;
-                            tx_frame.timestamp = timestamp;
-                            tx_frame.direction = "tx".to_string();
-                            
-                            let send_result = tokio::runtime::Handle::current()
-                                .block_on(ch.send(frame));
-                            
-                            match send_result {
-                                Ok(()) => (true, Some(tx_frame)),
-                                Err(_) => (true, None),
-                            }
-                        }
-                    }).await;
-                    
-                    match result {
-                        Ok((should_continue, maybe_frame)) => {
-                            if !should_continue {
-                                break;
-                            }
-                            if let Some(tx_frame) = maybe_frame {
-                                let _ = app.emit("can-message", tx_frame);
-                            }
-                        }
-                        Err(_) => break,
+                    // Each transmission needs a fresh SecOC freshness value
+                    let mut frame = can_frame.clone();
+                    if let Err(e) = secoc.write().protect(channel.id(), &mut frame) {
+                        tracing::error!("Periodic transmit job {}: {}", job_id_clone, e);
+                        break;
+                    }
+
+                    if !channel.is_connected().await {
+                        break;
+                    }
+                    // Failed sends are skipped; the job keeps its schedule
+                    if let Ok(tx_frame) = channel.transmit(frame).await {
+                        emit_sent(&tx_frame);
                     }
                 }
                 _ = cancel_rx.changed() => {
                     if *cancel_rx.borrow() {
-                        log::info!("Periodic transmit job {} cancelled", job_id_clone);
+                        tracing::info!("Periodic transmit job {} cancelled", job_id_clone);
                         break;
                     }
                 }
@@ -561,7 +552,7 @@ pub async fn start_periodic_transmit(
             jobs.remove(&job_id_clone);
         }
         
-        log::info!("Periodic transmit job {} ended", job_id_clone);
+        tracing::info!("Periodic transmit job {} ended", job_id_clone);
     });
 
     Ok(job_id)
@@ -572,7 +563,7 @@ pub async fn start_periodic_transmit(
 pub async fn stop_periodic_transmit(
     state: State<'_, AppState>,
     job_id: String,
-) -> Result<(), String> {
+) -> Result<(), BootCanError> {
     let cancel_tx = {
         let jobs = state.periodic_jobs.read();
         jobs.get(&job_id).cloned()
@@ -580,9 +571,9 @@ pub async fn stop_periodic_transmit(
     
     if let Some(tx) = cancel_tx {
         let _ = tx.send(true);
-        log::info!("Sent cancel signal to job {}", job_id);
+        tracing::info!("Sent cancel signal to job {}", job_id);
     } else {
-        log::warn!("Job {} not found", job_id);
+        tracing::warn!("Job {} not found", job_id);
     }
     
     Ok(())
@@ -594,7 +585,7 @@ pub async fn set_filter(
     state: State<'_, AppState>,
     id: Option<u32>,
     mask: Option<u32>,
-) -> Result<(), String> {
+) -> Result<(), BootCanError> {
     let channel = {
         let manager = state.channel_manager.read();
         manager.get_active_channel()
@@ -602,7 +593,7 @@ pub async fn set_filter(
 
     if let Some(_channel) = channel {
         // TODO: Implement filter setting via HAL
-        log::info!("Filter set: id={:?}, mask={:?}", id, mask);
+        tracing::info!("Filter set: id={:?}, mask={:?}", id, mask);
     }
 
     Ok(())
@@ -614,34 +605,183 @@ pub async fn set_advanced_filter(
     state: State<'_, AppState>,
     channel_id: String,
     filter: FilterSet,
-) -> Result<(), String> {
+) -> Result<(), BootCanError> {
     let channel = {
         let manager = state.channel_manager.read();
         manager.get_channel(&channel_id)
     };
 
     if let Some(channel) = channel {
-        let mut ch = channel.write();
-        ch.set_filter(filter);
-        log::info!("Advanced filter set for channel {}", channel_id);
+        channel.set_filter(filter).await?;
+        tracing::info!("Advanced filter set for channel {}", channel_id);
     } else {
-        return Err(format!("Channel {} not found", channel_id));
+        return Err(BootCanError::NotFound(format!("Channel {} not found", channel_id)));
+    }
+
+    Ok(())
+}
+
+/// Set drop, corruption, duplication and latency faults on a virtual channel (None disables them)
+#[tauri::command]
+pub async fn set_fault_injection(
+    state: State<'_, AppState>,
+    channel_id: String,
+    config: Option<FaultConfig>,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    channel.set_fault_injection(config).await?;
+    tracing::info!("Fault injection for channel {}: {:?}", channel_id, config);
+    Ok(())
+}
+
+/// Get the fault injection settings of a channel
+#[tauri::command]
+pub async fn get_fault_injection(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Option<FaultConfig>, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    Ok(channel.snapshot().await?.faults)
+}
+
+/// A named virtual bus and the channels currently connected to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualBusInfo {
+    pub name: String,
+    /// Channels attached to the bus
+    pub channels: Vec<String>,
+    /// Attached channels that are connected and exchange frames
+    pub nodes: Vec<String>,
+}
+
+fn all_channels(state: &AppState) -> Vec<ChannelHandle> {
+    let manager = state.channel_manager.read();
+    manager
+        .get_channel_ids()
+        .iter()
+        .filter_map(|id| manager.get_channel(id))
+        .collect()
+}
+
+fn get_virtual_bus(state: &AppState, name: &str) -> Result<SharedVirtualBus, BootCanError> {
+    state
+        .virtual_buses
+        .read()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| BootCanError::NotFound(format!("Virtual bus {} not found", name)))
+}
+
+/// Create a named virtual bus
+#[tauri::command]
+pub async fn create_virtual_bus(state: State<'_, AppState>, name: String) -> Result<(), BootCanError> {
+    let mut buses = state.virtual_buses.write();
+    if buses.contains_key(&name) {
+        return Err(BootCanError::Busy(format!("Virtual bus {} already exists", name)));
+    }
+    buses.insert(name.clone(), Arc::new(parking_lot::Mutex::new(VirtualCanBus::new())));
+    tracing::info!("Virtual bus {} created", name);
+    Ok(())
+}
+
+/// Delete a virtual bus, detaching all of its channels
+#[tauri::command]
+pub async fn delete_virtual_bus(state: State<'_, AppState>, name: String) -> Result<(), BootCanError> {
+    state
+        .virtual_buses
+        .write()
+        .remove(&name)
+        .ok_or_else(|| BootCanError::NotFound(format!("Virtual bus {} not found", name)))?;
+
+    let channels = all_channels(&state);
+    for channel in channels {
+        let attached = channel.snapshot().await?.virtual_bus == Some(name.clone());
+        if attached {
+            channel.detach_virtual_bus().await?;
+        }
+    }
+    tracing::info!("Virtual bus {} deleted", name);
+    Ok(())
+}
+
+/// Attach a virtual channel to a bus; it leaves any bus it was attached to
+#[tauri::command]
+pub async fn attach_virtual_channel(
+    state: State<'_, AppState>,
+    bus_name: String,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    let bus = get_virtual_bus(&state, &bus_name)?;
+    let channel = get_channel(&state, &channel_id)?;
+    channel.attach_virtual_bus(&bus_name, bus).await?;
+    tracing::info!("Channel {} attached to virtual bus {}", channel_id, bus_name);
+    Ok(())
+}
+
+/// Detach a channel from its virtual bus (it falls back to loopback)
+#[tauri::command]
+pub async fn detach_virtual_channel(state: State<'_, AppState>, channel_id: String) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    channel.detach_virtual_bus().await
+}
+
+/// List the virtual buses with their channels
+#[tauri::command]
+pub async fn list_virtual_buses(state: State<'_, AppState>) -> Result<Vec<VirtualBusInfo>, BootCanError> {
+    let mut attachments: Vec<(String, String)> = Vec::new();
+    for channel in all_channels(&state) {
+        let snapshot = channel.snapshot().await?;
+        if let Some(bus) = snapshot.virtual_bus {
+            attachments.push((bus, snapshot.id));
+        }
     }
 
+    let mut buses: Vec<VirtualBusInfo> = state
+        .virtual_buses
+        .read()
+        .iter()
+        .map(|(name, bus)| {
+            let mut channels: Vec<String> = attachments
+                .iter()
+                .filter(|(bus_name, _)| bus_name == name)
+                .map(|(_, channel_id)| channel_id.clone())
+                .collect();
+            channels.sort();
+            VirtualBusInfo {
+                name: name.clone(),
+                channels,
+                nodes: bus.lock().node_ids(),
+            }
+        })
+        .collect();
+    buses.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(buses)
+}
+
+/// Put a frame directly onto a virtual bus, as if sent by an external node
+#[tauri::command]
+pub async fn inject_virtual_bus_frame(
+    state: State<'_, AppState>,
+    bus_name: String,
+    frame: FramePayload,
+) -> Result<(), BootCanError> {
+    let bus = get_virtual_bus(&state, &bus_name)?;
+    let frame: CanFrame = frame.into();
+    bus.lock().broadcast("", &frame);
     Ok(())
 }
 
 /// Clear all received messages (frontend handles this, but we can reset stats)
 #[tauri::command]
-pub async fn clear_messages(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn clear_messages(state: State<'_, AppState>) -> Result<(), BootCanError> {
     let channel = {
         let manager = state.channel_manager.read();
         manager.get_active_channel()
     };
 
     if let Some(channel) = channel {
-        let mut ch = channel.write();
-        ch.stats.reset();
+        channel.reset_stats().await?;
     }
 
     Ok(())
@@ -653,17 +793,26 @@ pub async fn start_logging(
     state: State<'_, AppState>,
     app: AppHandle,
     file_path: String,
-    format: String,
-) -> Result<(), String> {
-    let format = match format.to_lowercase().as_str() {
-        "csv" => TraceFormat::Csv,
-        "trc" => TraceFormat::Trc,
-        _ => return Err("Invalid format. Use 'csv' or 'trc'".to_string()),
+    format: Option<String>,
+) -> Result<(), BootCanError> {
+    let (default_format, file_path) = {
+        let settings = state.settings.read();
+        (settings.values().log_format, settings.values().log_path(&file_path))
     };
+    let format = match format.map(|f| f.to_lowercase()).as_deref() {
+        None => default_format,
+        Some("csv") => TraceFormat::Csv,
+        Some("trc") => TraceFormat::Trc,
+        Some("asc") => TraceFormat::Asc,
+        _ => return Err(BootCanError::InvalidInput("Invalid format. Use 'csv', 'trc' or 'asc'".to_string())),
+    };
+    if let Some(dir) = file_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| BootCanError::Io(format!("Failed to create log directory: {}", e)))?;
+    }
 
     let config = TraceLoggerConfig {
         format,
-        file_path: PathBuf::from(file_path),
+        file_path,
         auto_split: false,
         max_file_size_mb: None,
         max_file_duration_sec: None,
@@ -681,12 +830,13 @@ pub async fn start_logging(
         };
 
         if let Some(channel) = channel {
-            let mut rx = channel.read().subscribe();
+            let mut rx = channel.subscribe("Trace logger");
             let sender_clone = sender.clone();
             let app_clone = app.clone();
+            let span = tracing::info_span!("log", channel = %channel.id());
 
             tokio::spawn(async move {
-                while let Ok(frame) = rx.recv().await {
+                while let Some(frame) = rx.recv().await {
                     // Send to logger
                     if sender_clone.send(frame.clone()).is_err() {
                         break;
@@ -694,7 +844,8 @@ pub async fn start_logging(
                     // Also emit to frontend
                     let _ = app_clone.emit("can-message", frame);
                 }
-            });
+                tracing::info!("Logging ended");
+            }.instrument(span));
         }
     }
 
@@ -704,7 +855,7 @@ pub async fn start_logging(
 
 /// Stop trace logging
 #[tauri::command]
-pub async fn stop_logging(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn stop_logging(state: State<'_, AppState>) -> Result<(), BootCanError> {
     let logger_opt = {
         let mut guard = state.trace_logger.write();
         guard.take()
@@ -723,36 +874,36 @@ pub async fn load_trace(
     file_path: String,
     bus_to_channel_map: Option<std::collections::HashMap<String, String>>,
     channel_name_to_id_map: Option<std::collections::HashMap<String, String>>,
-) -> Result<usize, String> {
+) -> Result<usize, BootCanError> {
     // Build bus-to-channel mapping
     // If provided by frontend, use it; otherwise build from DBC databases
     let bus_to_channel = if let Some(map) = bus_to_channel_map {
-        log::info!("Using provided bus-to-channel mapping (names): {:?}", map);
-        log::info!("Channel name-to-ID mapping: {:?}", channel_name_to_id_map);
+        tracing::info!("Using provided bus-to-channel mapping (names): {:?}", map);
+        tracing::info!("Channel name-to-ID mapping: {:?}", channel_name_to_id_map);
         
         // Convert string keys to u8 and resolve channel names to IDs
         let mut resolved_map = std::collections::HashMap::new();
         for (bus_num_str, channel_name) in map.iter() {
             // Parse bus number from string key
             let bus_num = bus_num_str.parse::<u8>()
-                .map_err(|e| format!("Invalid bus number '{}': {}", bus_num_str, e))?;
+                .map_err(|e| BootCanError::InvalidInput(format!("Invalid bus number '{}': {}", bus_num_str, e)))?;
             
             // If channel names are provided, resolve them to channel IDs
             if let Some(ref name_to_id) = channel_name_to_id_map {
                 if let Some(channel_id) = name_to_id.get(channel_name) {
                     resolved_map.insert(bus_num, channel_id.clone());
-                    log::info!("Resolved bus {} -> channel name '{}' -> channel ID '{}'", bus_num, channel_name, channel_id);
+                    tracing::info!("Resolved bus {} -> channel name '{}' -> channel ID '{}'", bus_num, channel_name, channel_id);
                 } else {
-                    log::warn!("Channel name '{}' not found in name-to-ID mapping, using name as-is", channel_name);
+                    tracing::warn!("Channel name '{}' not found in name-to-ID mapping, using name as-is", channel_name);
                     resolved_map.insert(bus_num, channel_name.clone());
                 }
             } else {
                 // No name-to-ID mapping provided, assume values are already channel IDs
-                log::warn!("No name-to-ID mapping provided, using channel name '{}' as channel ID", channel_name);
+                tracing::warn!("No name-to-ID mapping provided, using channel name '{}' as channel ID", channel_name);
                 resolved_map.insert(bus_num, channel_name.clone());
             }
         }
-        log::info!("Final resolved mapping: {:?}", resolved_map);
+        tracing::info!("Final resolved mapping: {:?}", resolved_map);
         Some(resolved_map)
     } else {
         // Build bus-to-channel mapping from DBC database channel IDs
@@ -770,7 +921,7 @@ pub async fn load_trace(
             // Bus 1 -> first DBC channel, Bus 2 -> second DBC channel, etc.
             for (idx, channel_id) in dbc_channel_ids.iter().enumerate() {
                 mapping.insert((idx + 1) as u8, channel_id.clone());
-                log::debug!("Mapping bus {} -> channel {}", idx + 1, channel_id);
+                tracing::debug!("Mapping bus {} -> channel {}", idx + 1, channel_id);
             }
         } else {
             // Fallback: if no DBC files are loaded, use channel manager channel IDs
@@ -779,20 +930,20 @@ pub async fn load_trace(
             channel_ids.sort(); // Sort for consistent ordering
             for (idx, channel_id) in channel_ids.iter().enumerate() {
                 mapping.insert((idx + 1) as u8, channel_id.clone());
-                log::debug!("Mapping bus {} -> channel {} (no DBC)", idx + 1, channel_id);
+                tracing::debug!("Mapping bus {} -> channel {} (no DBC)", idx + 1, channel_id);
             }
         }
         
-        log::info!("Auto-generated bus to channel mapping: {:?}", mapping);
+        tracing::info!("Auto-generated bus to channel mapping: {:?}", mapping);
         if mapping.is_empty() {
-            log::warn!("No channels found for bus-to-channel mapping!");
+            tracing::warn!("No channels found for bus-to-channel mapping!");
             None
         } else {
             Some(mapping)
         }
     };
 
-    log::info!("Passing bus-to-channel mapping to trace player: {:?}", bus_to_channel);
+    tracing::info!("Passing bus-to-channel mapping to trace player: {:?}", bus_to_channel);
     
     // Create progress callback to emit events
     let app_clone = app.clone();
@@ -805,11 +956,11 @@ pub async fn load_trace(
         let result = player.load_file(PathBuf::from(file_path), bus_to_channel, progress_callback).await;
         match result {
             Ok(c) => {
-                log::info!("Successfully loaded {} frames from trace file", c);
+                tracing::info!("Successfully loaded {} frames from trace file", c);
                 Ok(c)
             }
             Err(e) => {
-                log::error!("Failed to load trace file: {}", e);
+                tracing::error!("Failed to load trace file: {}", e);
                 Err(e)
             }
         }
@@ -822,19 +973,25 @@ pub async fn load_trace(
 }
 
 /// Start trace playback
+///
+/// With `transmit`, frames are also sent on the channel they were recorded on
+/// (frames of unknown or disconnected channels are only emitted).
 #[tauri::command]
 pub async fn start_playback(
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+    transmit: Option<bool>,
+) -> Result<(), BootCanError> {
     {
         let mut player = state.trace_player.write().await;
         player.start()?;
     }
 
-    // Start playback loop - just emit frames, don't send to hardware
+    // Start playback loop - emit frames, and send them to hardware only on request
     let player_clone = state.trace_player.clone();
     let app_clone = app.clone();
+    let channel_manager = transmit.unwrap_or(false).then(|| state.channel_manager.clone());
+    let span = tracing::info_span!("playback", transmit = channel_manager.is_some());
 
     tokio::spawn(async move {
         loop {
@@ -846,25 +1003,33 @@ pub async fn start_playback(
                 }
             };
 
+            if let Some(channel) = channel_manager.as_ref().and_then(|m| m.read().get_channel(&frame.channel)) {
+                if channel.is_connected().await {
+                    if let Err(e) = channel.send(frame.clone()).await {
+                        tracing::warn!(channel = %frame.channel, "Replay: failed to send 0x{:X}: {}", frame.id, e);
+                    }
+                }
+            }
+
             // Wait for the delay
             tokio::time::sleep(delay).await;
 
             // Emit to frontend (this is what the plot needs)
             // The frame already has the correct channel set from bus mapping
             if let Err(e) = app_clone.emit("can-message", &frame) {
-                log::error!("Failed to emit can-message event: {:?}", e);
+                tracing::error!("Failed to emit can-message event: {:?}", e);
             } else {
-                log::trace!("Emitted frame: ID=0x{:X} channel={} timestamp={}", frame.id, frame.channel, frame.timestamp);
+                tracing::trace!("Emitted frame: ID=0x{:X} channel={} timestamp={}", frame.id, frame.channel, frame.timestamp);
             }
         }
-    });
+    }.instrument(span));
 
     Ok(())
 }
 
 /// Stop trace playback
 #[tauri::command]
-pub async fn stop_playback(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn stop_playback(state: State<'_, AppState>) -> Result<(), BootCanError> {
     let mut player = state.trace_player.write().await;
     player.stop();
     Ok(())
@@ -872,7 +1037,7 @@ pub async fn stop_playback(state: State<'_, AppState>) -> Result<(), String> {
 
 /// Pause trace playback
 #[tauri::command]
-pub async fn pause_playback(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn pause_playback(state: State<'_, AppState>) -> Result<(), BootCanError> {
     let mut player = state.trace_player.write().await;
     player.pause();
     Ok(())
@@ -880,7 +1045,7 @@ pub async fn pause_playback(state: State<'_, AppState>) -> Result<(), String> {
 
 /// Resume trace playback
 #[tauri::command]
-pub async fn resume_playback(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn resume_playback(state: State<'_, AppState>) -> Result<(), BootCanError> {
     let mut player = state.trace_player.write().await;
     player.resume();
     Ok(())
@@ -891,7 +1056,7 @@ pub async fn resume_playback(state: State<'_, AppState>) -> Result<(), String> {
 pub async fn set_playback_speed(
     state: State<'_, AppState>,
     speed: f64,
-) -> Result<(), String> {
+) -> Result<(), BootCanError> {
     let mut player = state.trace_player.write().await;
     player.set_speed(speed);
     Ok(())
@@ -901,7 +1066,7 @@ pub async fn set_playback_speed(
 #[tauri::command]
 pub async fn get_playback_state(
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<String, BootCanError> {
     let player = state.trace_player.read().await;
     Ok(match player.get_state() {
         PlaybackState::Stopped => "stopped".to_string(),
@@ -910,24 +1075,41 @@ pub async fn get_playback_state(
     })
 }
 
+/// Apply mutation rules to the loaded trace for modified replay
+///
+/// Signal rules use the DBC of each frame's channel. An empty list restores
+/// the trace as loaded. Returns the frame count.
+#[tauri::command]
+pub async fn set_replay_mutations(
+    state: State<'_, AppState>,
+    mutations: Vec<TraceMutation>,
+) -> Result<usize, BootCanError> {
+    let databases = state.dbc_databases.read().clone();
+    let mut player = state.trace_player.write().await;
+    player.set_mutations(&mutations, &databases)
+}
+
 /// Get all frames from loaded trace (for immediate decoding)
 #[tauri::command]
 pub async fn get_trace_frames(
     state: State<'_, AppState>,
-) -> Result<Vec<CanFrame>, String> {
+) -> Result<Vec<CanFrame>, BootCanError> {
     let player = state.trace_player.read().await;
     Ok(player.get_all_frames())
 }
 
-/// Load a DBC or SYM file for a channel
+/// Load a DBC, SYM or LDF file for a channel
 #[tauri::command]
 pub async fn load_dbc(
     state: State<'_, AppState>,
     channel_id: String,
     file_path: String,
-) -> Result<usize, String> {
-    let db = if file_path.to_lowercase().ends_with(".sym") {
+) -> Result<usize, BootCanError> {
+    let lower = file_path.to_lowercase();
+    let db = if lower.ends_with(".sym") {
         SymParser::parse_file(&file_path)?
+    } else if lower.ends_with(".ldf") {
+        LdfParser::parse_file(&file_path)?
     } else {
         DbcParser::parse_file(&file_path)?
     };
@@ -948,17 +1130,27 @@ pub async fn decode_message(
     channel_id: String,
     message_id: u32,
     data: Vec<u8>,
-) -> Result<Vec<DecodedSignal>, String> {
+) -> Result<Vec<DecodedSignal>, BootCanError> {
     let db = {
         let databases = state.dbc_databases.read();
         databases.get(&channel_id).cloned()
     };
     
-    if let Some(db) = db {
-        Ok(db.decode_message(message_id, &data))
+    let signals = if let Some(db) = db {
+        db.decode_message(message_id, &data)
     } else {
-        Ok(vec![])
+        vec![]
+    };
+
+    // Fall back to CANopen PDO mappings for messages not described by the DBC
+    if signals.is_empty() {
+        let decoders = state.pdo_decoders.read();
+        if let Some(pdo_signals) = decoders.get(&channel_id).and_then(|d| d.decode(message_id, &data)) {
+            return Ok(pdo_signals);
+        }
     }
+
+    Ok(signals)
 }
 
 /// Batch decode multiple messages (for performance with large trace files)
@@ -973,12 +1165,13 @@ pub struct DecodeRequest {
 pub async fn decode_messages_batch(
     state: State<'_, AppState>,
     requests: Vec<DecodeRequest>,
-) -> Result<Vec<Vec<DecodedSignal>>, String> {
+) -> Result<Vec<Vec<DecodedSignal>>, BootCanError> {
     // Clone databases to avoid holding the lock during parallel processing
     let databases: std::collections::HashMap<String, crate::core::dbc::DbcDatabase> = {
         let db_guard = state.dbc_databases.read();
         db_guard.clone()
     };
+    let pdo_decoders: HashMap<String, PdoDecoder> = state.pdo_decoders.read().clone();
     
     // Use rayon for parallel processing
     // Rayon automatically uses all available CPU cores
@@ -987,11 +1180,20 @@ pub async fn decode_messages_batch(
     let results: Vec<Vec<DecodedSignal>> = requests
         .par_iter()
         .map(|req| {
-            if let Some(db) = databases.get(&req.channel_id) {
+            let signals = if let Some(db) = databases.get(&req.channel_id) {
                 db.decode_message(req.message_id, &req.data)
             } else {
                 vec![]
+            };
+            if signals.is_empty() {
+                if let Some(pdo_signals) = pdo_decoders
+                    .get(&req.channel_id)
+                    .and_then(|d| d.decode(req.message_id, &req.data))
+                {
+                    return pdo_signals;
+                }
             }
+            signals
         })
         .collect();
     
@@ -1004,7 +1206,7 @@ pub async fn get_message_info(
     state: State<'_, AppState>,
     channel_id: String,
     message_id: u32,
-) -> Result<Option<serde_json::Value>, String> {
+) -> Result<Option<serde_json::Value>, BootCanError> {
     let db = {
         let databases = state.dbc_databases.read();
         databases.get(&channel_id).cloned()
@@ -1012,7 +1214,7 @@ pub async fn get_message_info(
     
     if let Some(db) = db {
         if let Some(message) = db.get_message(message_id) {
-            Ok(Some(serde_json::to_value(message).map_err(|e| e.to_string())?))
+            Ok(Some(serde_json::to_value(message).map_err(|e| BootCanError::Other(e.to_string()))?))
         } else {
             Ok(None)
         }
@@ -1044,7 +1246,7 @@ pub struct MessageWithSignals {
 #[tauri::command]
 pub async fn get_all_signals(
     state: State<'_, AppState>,
-) -> Result<Vec<MessageWithSignals>, String> {
+) -> Result<Vec<MessageWithSignals>, BootCanError> {
     let databases = {
         let db_map = state.dbc_databases.read();
         db_map.clone()
@@ -1085,77 +1287,491 @@ pub async fn get_all_signals(
     Ok(result)
 }
 
-/// Project file structures
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ProjectChannel {
-    pub id: String,
-    pub name: String,
-    pub interface_id: Option<String>,
-    pub bitrate: u32,
-    pub dbc_file: Option<String>,
+/// Decode one signal from the loaded trace as `(t, value)` pairs for plotting
+///
+/// Frames of `channel_id` are decoded with that channel's DBC; the series is
+/// thinned to `max_points` when given.
+#[tauri::command]
+pub async fn get_signal_series(
+    state: State<'_, AppState>,
+    channel_id: String,
+    message_id: u32,
+    signal: String,
+    time_range: Option<TimeRange>,
+    max_points: Option<usize>,
+) -> Result<Vec<(f64, f64)>, BootCanError> {
+    let db = get_dbc(&state, &channel_id)?;
+    let player = state.trace_player.read().await;
+    let frames = player.get_all_frames();
+    drop(player);
+
+    tokio::task::spawn_blocking(move || {
+        let series = signal_series::extract_series(
+            &frames,
+            &db,
+            &channel_id,
+            message_id,
+            &signal,
+            time_range.unwrap_or_default(),
+        )?;
+        Ok(signal_series::decimate(series, max_points.unwrap_or(0)))
+    })
+    .await
+    .map_err(|e| BootCanError::Other(e.to_string()))?
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ProjectFilter {
-    #[serde(flatten)]
-    pub data: serde_json::Value,
+/// Decode one signal from the loaded trace as min/max/avg per time bin
+///
+/// Renders long captures as a faithful envelope with at most `bins` points.
+#[tauri::command]
+pub async fn get_signal_envelope(
+    state: State<'_, AppState>,
+    channel_id: String,
+    message_id: u32,
+    signal: String,
+    time_range: Option<TimeRange>,
+    bins: usize,
+) -> Result<Vec<SeriesBin>, BootCanError> {
+    let db = get_dbc(&state, &channel_id)?;
+    let player = state.trace_player.read().await;
+    let frames = player.get_all_frames();
+    drop(player);
+
+    let range = time_range.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let series = signal_series::extract_series(&frames, &db, &channel_id, message_id, &signal, range)?;
+        Ok(signal_series::bin_series(&series, bins, range))
+    })
+    .await
+    .map_err(|e| BootCanError::Other(e.to_string()))?
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ProjectTransmitJob {
-    pub id: String,
-    pub frame: FramePayload,
-    pub interval_ms: u64,
-    pub enabled: bool,
+/// Min/max/mean/std dev, first/last value and sample count of signals in the loaded trace
+#[tauri::command]
+pub async fn get_signal_statistics(
+    state: State<'_, AppState>,
+    channel_id: String,
+    signals: Vec<SignalRef>,
+    time_range: Option<TimeRange>,
+) -> Result<Vec<SignalStatistics>, BootCanError> {
+    let db = get_dbc(&state, &channel_id)?;
+    let player = state.trace_player.read().await;
+    let frames = player.get_all_frames();
+    drop(player);
+
+    let range = time_range.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        signals
+            .iter()
+            .map(|s| {
+                let series = signal_series::extract_series(&frames, &db, &channel_id, s.message_id, &s.signal, range)?;
+                Ok(SignalStatistics::from_series(s.message_id, &s.signal, &series))
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| BootCanError::Other(e.to_string()))?
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ProjectFile {
-    pub version: String,
-    pub channels: Vec<ProjectChannel>,
-    pub filters: Vec<ProjectFilter>,
-    pub transmit_jobs: Vec<ProjectTransmitJob>,
+/// Frame counts per ID and time bucket of the loaded trace, for an ID-vs-time heatmap
+#[tauri::command]
+pub async fn get_id_heatmap(
+    state: State<'_, AppState>,
+    channel_id: Option<String>,
+    bucket_ms: f64,
+    time_range: Option<TimeRange>,
+) -> Result<IdHeatmap, BootCanError> {
+    let player = state.trace_player.read().await;
+    let frames = player.get_all_frames();
+    drop(player);
+
+    let heatmap = tokio::task::spawn_blocking(move || {
+        id_stats::id_heatmap(&frames, channel_id.as_deref(), bucket_ms / 1000.0, time_range.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| BootCanError::Other(e.to_string()))??;
+    Ok(heatmap)
+}
+
+/// Decode the loaded trace with the DBC of each channel and write the signals to a CSV file
+///
+/// Decoding runs in parallel; progress is emitted as `trace-decode-progress`
+/// events. Returns the job ID.
+#[tauri::command]
+pub async fn start_trace_export(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    file_path: String,
+    time_range: Option<TimeRange>,
+) -> Result<String, BootCanError> {
+    let databases: HashMap<String, DbcDatabase> = state.dbc_databases.read().clone();
+    if databases.is_empty() {
+        return Err(BootCanError::NotFound("No DBC loaded".to_string()));
+    }
+    let player = state.trace_player.read().await;
+    let frames = player.get_all_frames();
+    drop(player);
+    let file = fs::File::create(&file_path)
+        .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", file_path, e)))?;
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.trace_decodes.write().insert(job_id.clone(), cancel_tx);
+    let trace_decodes = state.trace_decodes.clone();
+
+    tracing::info!("Trace export {} started: {} frames to {}", job_id, frames.len(), file_path);
+    let job = job_id.clone();
+    tokio::task::spawn_blocking(move || {
+        let frames_total = frames.len();
+        let emit = |frames_done: usize, finished: bool, error: Option<String>| {
+            let progress = DecodeProgress {
+                job_id: job.clone(),
+                frames_done,
+                frames_total,
+                finished,
+                error,
+            };
+            if let Err(e) = app.emit("trace-decode-progress", &progress) {
+                tracing::error!("Failed to emit trace-decode-progress event: {:?}", e);
+            }
+        };
+
+        let range = time_range.unwrap_or_default();
+        let result = trace_decode::decode_frames(&frames, &databases, range, &cancel_rx, |done| emit(done, false, None))
+            .and_then(|decoded| trace_decode::write_csv(std::io::BufWriter::new(file), &decoded));
+
+        match result {
+            Ok(()) => {
+                tracing::info!("Trace export {} finished", job);
+                emit(frames_total, true, None);
+            }
+            Err(e) => {
+                tracing::error!("Trace export {} failed: {}", job, e);
+                emit(0, true, Some(e.to_string()));
+            }
+        }
+        trace_decodes.write().remove(&job);
+    });
+
+    Ok(job_id)
+}
+
+/// Write the loaded trace to a .csv, .trc or .asc file, returning the number of frames
+///
+/// With `symbolic`, frames are annotated with the message and signal names
+/// from the DBC of their channel.
+#[tauri::command]
+pub async fn export_trace(
+    state: State<'_, AppState>,
+    file_path: String,
+    symbolic: bool,
+) -> Result<usize, BootCanError> {
+    let format = Path::new(&file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(TraceFormat::from_extension)
+        .ok_or_else(|| BootCanError::InvalidInput(format!("{}: expected a .csv, .trc or .asc file", file_path)))?;
+    let databases: HashMap<String, DbcDatabase> = if symbolic {
+        state.dbc_databases.read().clone()
+    } else {
+        HashMap::new()
+    };
+    let frames = state.trace_player.read().await.get_all_frames();
+    if frames.is_empty() {
+        return Err(BootCanError::NotFound("No trace loaded".to_string()));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let file = fs::File::create(&file_path)
+            .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", file_path, e)))?;
+        let out = std::io::BufWriter::new(file);
+        let database = |channel: &str| databases.get(channel);
+        if symbolic {
+            trace_export::write_trace(out, format, &frames, Some(&database))?;
+        } else {
+            trace_export::write_trace(out, format, &frames, None)?;
+        }
+        tracing::info!("Exported {} frames to {}", frames.len(), file_path);
+        Ok(frames.len())
+    })
+    .await
+    .map_err(|e| BootCanError::Other(e.to_string()))?
+}
+
+/// Cancel a running trace export (takes effect within one chunk of frames)
+#[tauri::command]
+pub async fn cancel_trace_export(state: State<'_, AppState>, job_id: String) -> Result<(), BootCanError> {
+    match state.trace_decodes.read().get(&job_id) {
+        Some(cancel_tx) => {
+            let _ = cancel_tx.send(true);
+            Ok(())
+        }
+        None => Err(BootCanError::NotFound(format!("Trace export {} not found", job_id))),
+    }
+}
+
+/// Decode watched signals in the backend and emit `signal-changed` only when a value changes
+///
+/// Replaces any watch already running on the channel.
+#[tauri::command]
+pub async fn start_signal_watch(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    signals: Vec<WatchedSignal>,
+) -> Result<(), BootCanError> {
+    let db = get_dbc(&state, &channel_id)?;
+    let mut watcher = SignalWatcher::new(&channel_id, db, &signals)?;
+
+    // Stop the previous watch before attaching, so it never outlives the DBC it was started with
+    let mut cancel_rx = register_monitor(&state.signal_watches, &channel_id);
+    let watches = state.signal_watches.clone();
+    let mut decoded = match subscribe_decoded(&state, std::slice::from_ref(&channel_id), "Signal watch") {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            unregister_monitor(&watches, &channel_id, &cancel_rx);
+            return Err(e);
+        }
+    };
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                received = decoded.recv() => {
+                    let Some(decoded) = received else {
+                        break;
+                    };
+                    for change in watcher.process_decoded(&decoded.frame, &decoded.signals) {
+                        if let Err(e) = app.emit("signal-changed", &change) {
+                            tracing::error!("Failed to emit signal-changed event: {:?}", e);
+                        }
+                    }
+                }
+                _ = cancel_rx.changed() => {
+                    if *cancel_rx.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+        unregister_monitor(&watches, &channel_id, &cancel_rx);
+        tracing::info!("Signal watch ended for channel {}", channel_id);
+    });
+
+    Ok(())
+}
+
+/// Stop the signal watch on a channel
+#[tauri::command]
+pub async fn stop_signal_watch(state: State<'_, AppState>, channel_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.signal_watches, &channel_id);
+    Ok(())
+}
+
+/// Flag new IDs, DLC changes and sudden rate changes on a channel as `bus-anomaly` events
+///
+/// Replaces any anomaly monitor already running on the channel.
+#[tauri::command]
+pub async fn start_anomaly_monitor(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    config: Option<AnomalyConfig>,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let mut detector = AnomalyDetector::new(&channel_id, config.unwrap_or_default());
+
+    spawn_channel_monitor(
+        state.anomaly_monitors.clone(),
+        channel,
+        channel_id,
+        "Anomaly",
+        move |frame| {
+            for anomaly in detector.process(frame) {
+                if let Err(e) = app.emit("bus-anomaly", &anomaly) {
+                    tracing::error!("Failed to emit bus-anomaly event: {:?}", e);
+                }
+            }
+        },
+    );
+
+    Ok(())
+}
+
+/// Stop the anomaly monitor on a channel
+#[tauri::command]
+pub async fn stop_anomaly_monitor(state: State<'_, AppState>, channel_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.anomaly_monitors, &channel_id);
+    Ok(())
+}
+
+/// Supervise message cycle times on a channel, emitting `cycle-violation` when an ID starts deviating
+///
+/// Expected periods come from the channel's DBC and/or `config`; returns the
+/// number of supervised IDs.
+#[tauri::command]
+pub async fn start_cycle_monitor(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    config: Option<CycleMonitorConfig>,
+) -> Result<usize, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let config = config.unwrap_or_default();
+    let db = state.dbc_databases.read().get(&channel_id).cloned();
+    let monitor = CycleMonitor::new(&channel_id, &config, db.as_ref());
+    if monitor.is_empty() {
+        return Err(BootCanError::InvalidInput("No cycle times in the DBC or configuration".to_string()));
+    }
+    let supervised = monitor.len();
+    let checks = state.cycle_checks.clone();
+    checks.write().insert(channel_id.clone(), monitor);
+
+    let monitor_channel_id = channel_id.clone();
+    spawn_channel_monitor(
+        state.cycle_monitors.clone(),
+        channel,
+        channel_id,
+        "Cycle time",
+        move |frame| {
+            let violation = {
+                let mut checks = checks.write();
+                checks
+                    .get_mut(&monitor_channel_id)
+                    .and_then(|monitor| monitor.process(frame))
+            };
+            if let Some(violation) = violation {
+                if let Err(e) = app.emit("cycle-violation", &violation) {
+                    tracing::error!("Failed to emit cycle-violation event: {:?}", e);
+                }
+            }
+        },
+    );
+
+    Ok(supervised)
+}
+
+/// Stop cycle-time supervision on a channel; its counters stay available
+#[tauri::command]
+pub async fn stop_cycle_monitor(state: State<'_, AppState>, channel_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.cycle_monitors, &channel_id);
+    Ok(())
+}
+
+/// Cycle-time counters per supervised ID of a channel
+#[tauri::command]
+pub async fn get_cycle_violations(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Vec<CycleCounts>, BootCanError> {
+    let checks = state.cycle_checks.read();
+    Ok(checks.get(&channel_id).map(|m| m.counts()).unwrap_or_default())
+}
+
+/// Top `top` IDs by frame count and by bus time, live over a window or from the loaded trace
+#[tauri::command]
+pub async fn get_top_talkers(
+    state: State<'_, AppState>,
+    source: TalkerSource,
+    top: usize,
+) -> Result<TopTalkers, BootCanError> {
+    match source {
+        TalkerSource::Live { channel_id, window_ms } => {
+            let channel = get_channel(&state, &channel_id)?;
+            let snapshot = channel.snapshot().await?;
+            if !snapshot.is_connected() {
+                return Err(BootCanError::NotConnected(format!("Channel {} is not connected", channel_id)));
+            }
+            let (mut rx, bitrate) = (channel.subscribe("Top talkers"), snapshot.config.bitrate);
+            let mut counter = TalkerCounter::new(bitrate);
+            let window = Duration::from_millis(window_ms);
+            let deadline = tokio::time::Instant::now() + window;
+            while let Ok(Some(frame)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+                counter.record(&frame);
+            }
+            Ok(counter.report(top, window.as_secs_f64()))
+        }
+        TalkerSource::Trace { channel_id, time_range, bitrate } => {
+            let player = state.trace_player.read().await;
+            let frames = player.get_all_frames();
+            drop(player);
+
+            let range = time_range.unwrap_or_default();
+            let mut counter = TalkerCounter::new(bitrate.unwrap_or(state.settings.read().values().default_bitrate));
+            let mut span: Option<(f64, f64)> = None;
+            for frame in frames
+                .iter()
+                .filter(|f| channel_id.as_ref().is_none_or(|c| &f.channel == c) && range.contains(f.timestamp))
+            {
+                counter.record(frame);
+                let (first, last) = span.get_or_insert((frame.timestamp, frame.timestamp));
+                *first = first.min(frame.timestamp);
+                *last = last.max(frame.timestamp);
+            }
+            let window_s = match (range.start, range.end, span) {
+                (Some(start), Some(end), _) => end - start,
+                (start, end, Some((first, last))) => end.unwrap_or(last) - start.unwrap_or(first),
+                _ => 0.0,
+            };
+            Ok(counter.report(top, window_s))
+        }
+    }
 }
 
-/// Save project to file
+/// Save project to file, with the layout and variables of the applied project
 #[tauri::command]
 pub async fn save_project(
+    state: State<'_, AppState>,
     file_path: String,
-    channels: Vec<ProjectChannel>,
+    mut channels: Vec<ProjectChannel>,
     filters: Vec<ProjectFilter>,
     transmit_jobs: Vec<ProjectTransmitJob>,
-) -> Result<(), String> {
+    secoc: Option<Vec<SecOcConfig>>,
+    dbc_options: Option<DbcSaveOptions>,
+) -> Result<(), BootCanError> {
+    for channel in channels.iter_mut().filter(|ch| ch.stats_emission.is_none()) {
+        if let Ok(running) = get_channel(&state, &channel.id) {
+            channel.stats_emission = Some(running.snapshot().await?.stats_emission);
+        }
+    }
+    let project_dir = Path::new(&file_path).parent().unwrap_or(Path::new(""));
+    for channel in &mut channels {
+        channel.store_dbc(project_dir, dbc_options.unwrap_or_default());
+    }
+
     let project = ProjectFile {
-        version: "1.0".to_string(),
+        version: PROJECT_VERSION.to_string(),
         channels,
         filters,
         transmit_jobs,
+        secoc: secoc.unwrap_or_default(),
+        layout: state.layout.read().clone(),
+        variables: state.applied_project.read().variables.clone(),
     };
+    project.channel_filters()?;
 
     let json = serde_json::to_string_pretty(&project)
-        .map_err(|e| format!("Failed to serialize project: {}", e))?;
+        .map_err(|e| BootCanError::Other(format!("Failed to serialize project: {}", e)))?;
 
     fs::write(&file_path, json)
-        .map_err(|e| format!("Failed to write project file: {}", e))?;
+        .map_err(|e| BootCanError::Io(format!("Failed to write project file: {}", e)))?;
 
-    log::info!("Project saved to {}", file_path);
+    tracing::info!("Project saved to {}", file_path);
+    remember_project(&state, &file_path, &project);
     Ok(())
 }
 
-/// Load project from file
+/// Load project from file; `apply_project` installs it
 #[tauri::command]
 pub async fn load_project(
+    state: State<'_, AppState>,
     file_path: String,
-) -> Result<ProjectFile, String> {
+) -> Result<LoadedProject, BootCanError> {
     let contents = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read project file: {}", e))?;
+        .map_err(|e| BootCanError::Io(format!("Failed to read project file: {}", e)))?;
 
     let project: ProjectFile = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse project file: {}", e))?;
+        .map_err(|e| BootCanError::Parse(format!("Failed to parse project file: {}", e)))?;
+    project.channel_filters()?;
 
     // Validate and clean up project data
     let available_interfaces = enumerate_interfaces();
@@ -1164,22 +1780,28 @@ pub async fn load_project(
         .map(|i| i.id.clone())
         .collect();
 
+    let project_dir = Path::new(&file_path).parent().unwrap_or(Path::new(""));
+    let mut dbc_conflicts = Vec::new();
+
     // Validate channels - set interface_id to None if interface doesn't exist
     let validated_channels: Vec<ProjectChannel> = project.channels
         .into_iter()
         .map(|mut ch| {
             if let Some(ref interface_id) = ch.interface_id {
                 if !available_interface_ids.contains(interface_id) {
-                    log::warn!("Interface {} not available, setting to None", interface_id);
+                    tracing::warn!("Interface {} not available, setting to None", interface_id);
                     ch.interface_id = None;
                 }
             }
-            // Validate DBC file exists
-            if let Some(ref dbc_path) = ch.dbc_file {
-                if !PathBuf::from(dbc_path).exists() {
-                    log::warn!("DBC file {} not found, setting to None", dbc_path);
-                    ch.dbc_file = None;
-                }
+            // Resolve relative DBC paths and compare the files with the saved hash
+            if let Some(conflict) = ch.resolve_dbc(project_dir) {
+                tracing::warn!(
+                    "DBC file {} is {:?}{}",
+                    conflict.path,
+                    conflict.kind,
+                    if conflict.used_embedded { ", using the embedded copy" } else { "" }
+                );
+                dbc_conflicts.push(conflict);
             }
             ch
         })
@@ -1190,8 +1812,2230 @@ pub async fn load_project(
         channels: validated_channels,
         filters: project.filters,
         transmit_jobs: project.transmit_jobs,
+        secoc: project.secoc,
+        layout: project.layout,
+        variables: project.variables,
+    };
+
+    tracing::info!("Project loaded from {}", file_path);
+    remember_project(&state, &file_path, &validated_project);
+    Ok(LoadedProject {
+        project: validated_project,
+        dbc_conflicts,
+    })
+}
+
+/// Record a loaded or saved project in the recent projects list
+fn remember_project(state: &AppState, file_path: &str, project: &ProjectFile) {
+    let mut recent = state.recent_projects.write();
+    recent.record(file_path, project);
+    if let Err(e) = recent.save() {
+        tracing::error!("{}", e);
+    }
+}
+
+/// Recently loaded and saved projects, pinned ones first
+#[tauri::command]
+pub async fn get_recent_projects(state: State<'_, AppState>) -> Result<RecentProjects, BootCanError> {
+    Ok(state.recent_projects.read().clone())
+}
+
+/// Pin or unpin a recent project; pinned projects are never dropped from the list
+#[tauri::command]
+pub async fn pin_recent_project(state: State<'_, AppState>, path: String, pinned: bool) -> Result<(), BootCanError> {
+    let mut recent = state.recent_projects.write();
+    recent.set_pinned(&path, pinned)?;
+    recent.save()
+}
+
+/// Remove a project from the recent projects list
+#[tauri::command]
+pub async fn remove_recent_project(state: State<'_, AppState>, path: String) -> Result<(), BootCanError> {
+    let mut recent = state.recent_projects.write();
+    recent.remove(&path);
+    recent.save()
+}
+
+/// Enable or disable reopening the last project on startup
+#[tauri::command]
+pub async fn set_reopen_last_project(state: State<'_, AppState>, enabled: bool) -> Result<(), BootCanError> {
+    let mut recent = state.recent_projects.write();
+    recent.reopen_last = enabled;
+    recent.save()
+}
+
+/// Application settings
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<AppSettings, BootCanError> {
+    Ok(state.settings.read().values().clone())
+}
+
+/// Replace the application settings
+///
+/// The stats interval applies to channels created afterwards, batching and frame
+/// delivery to channels connected afterwards.
+#[tauri::command]
+pub async fn update_settings(state: State<'_, AppState>, settings: AppSettings) -> Result<AppSettings, BootCanError> {
+    state.settings.write().update(settings.clone())?;
+    let mut manager = state.channel_manager.write();
+    manager.set_default_stats_emission(settings.stats_emission());
+    manager.set_broadcast_capacity(settings.broadcast_capacity);
+    Ok(settings)
+}
+
+/// Frames received since `since`, for a frontend polling the frame store
+///
+/// Returns at most `max_frames` (default 10000) of the newest frames, plus the
+/// latest frame of every ID that changed.
+#[tauri::command]
+pub async fn get_frame_delta(
+    state: State<'_, AppState>,
+    since: u64,
+    max_frames: Option<usize>,
+) -> Result<FrameDelta, BootCanError> {
+    Ok(state.frame_store.lock().delta(since, max_frames.unwrap_or(10_000)))
+}
+
+/// Forget the frames in the frame store
+#[tauri::command]
+pub async fn clear_frame_store(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    state.frame_store.lock().clear();
+    Ok(())
+}
+
+/// Project to open on startup: the last one, when reopening is enabled and it still exists
+#[tauri::command]
+pub async fn get_startup_project(state: State<'_, AppState>) -> Result<Option<String>, BootCanError> {
+    let recent = state.recent_projects.read();
+    if !recent.reopen_last {
+        return Ok(None);
+    }
+    Ok(recent.last().map(|p| p.path.clone()))
+}
+
+/// Zip a saved project with its DBC/SYM files and the given trace logs
+///
+/// The project inside the bundle refers to the databases by their paths in the archive.
+#[tauri::command]
+pub async fn export_project_bundle(
+    project_path: String,
+    bundle_path: String,
+    trace_files: Option<Vec<String>>,
+) -> Result<(), BootCanError> {
+    let contents = fs::read_to_string(&project_path)
+        .map_err(|e| BootCanError::Io(format!("Failed to read project file: {}", e)))?;
+    let project: ProjectFile = serde_json::from_str(&contents)
+        .map_err(|e| BootCanError::Parse(format!("Failed to parse project file: {}", e)))?;
+    let project_dir = Path::new(&project_path).parent().unwrap_or(Path::new(""));
+
+    let file = fs::File::create(&bundle_path)
+        .map_err(|e| BootCanError::Io(format!("Failed to create bundle: {}", e)))?;
+    bundle::write_bundle(file, &project, project_dir, &trace_files.unwrap_or_default())?;
+    tracing::info!("Project {} bundled to {}", project_path, bundle_path);
+    Ok(())
+}
+
+/// Extract a project bundle into `target_dir`; the extracted project is opened with `load_project`
+#[tauri::command]
+pub async fn import_project_bundle(bundle_path: String, target_dir: String) -> Result<ImportedBundle, BootCanError> {
+    let file = fs::File::open(&bundle_path)
+        .map_err(|e| BootCanError::Io(format!("Failed to open bundle: {}", e)))?;
+    let imported = bundle::extract_bundle(file, Path::new(&target_dir))?;
+    tracing::info!("Project bundle {} extracted to {}", bundle_path, target_dir);
+    Ok(imported)
+}
+
+/// Install a project in the backend: create its channels, load their DBCs,
+/// install the filters and register its transmit jobs, starting the enabled ones
+///
+/// The project is validated and its files loaded completely before anything
+/// changes. Only then is the previously applied project torn down and the new
+/// one installed; should installing fail halfway, what was installed is torn
+/// down again.
+#[tauri::command]
+pub async fn apply_project(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    project: ProjectFile,
+) -> Result<AppliedProject, BootCanError> {
+    let prepared = PreparedProject::prepare(&project)?;
+    if let Err(e) = teardown_applied_project(&state).await {
+        // The previous project is gone regardless; the new one still goes in
+        tracing::warn!("Previous project not torn down cleanly: {}", e);
+    }
+
+    let mut applied = AppliedProject::default();
+    if let Err(e) = install_project(&state, &app, prepared, project, &mut applied).await {
+        *state.applied_project.write() = applied;
+        if let Err(teardown) = teardown_applied_project(&state).await {
+            tracing::warn!("Partly applied project not torn down cleanly: {}", teardown);
+        }
+        return Err(e);
+    }
+
+    tracing::info!(
+        "Project applied: {} channels, {} transmit jobs",
+        applied.channel_ids.len(),
+        applied.transmit_jobs.len()
+    );
+    *state.applied_project.write() = applied.clone();
+    Ok(applied)
+}
+
+/// Install a prepared project, recording in `applied` what is in place so far
+async fn install_project(
+    state: &AppState,
+    app: &AppHandle,
+    prepared: PreparedProject,
+    project: ProjectFile,
+    applied: &mut AppliedProject,
+) -> Result<(), BootCanError> {
+    let mut dbcs = prepared.dbcs;
+    for project_channel in &prepared.channels {
+        let channel = state.channel_manager.write().get_or_create_channel(&project_channel.id);
+        applied.channel_ids.push(project_channel.id.clone());
+        channel
+            .configure(ChannelConfig {
+                interface_id: project_channel.interface_id.clone().unwrap_or_default(),
+                bitrate: project_channel.bitrate,
+                listen_only: false,
+            })
+            .await?;
+        if let Some(emission) = project_channel.stats_emission {
+            channel.set_stats_emission(emission).await?;
+        }
+        channel
+            .set_filter(prepared.filters.get(&project_channel.id).cloned().unwrap_or_default())
+            .await?;
+        if let Some(db) = dbcs.remove(&project_channel.id) {
+            state.dbc_databases.write().insert(project_channel.id.clone(), db);
+        }
+    }
+    if let Some(first) = applied.channel_ids.first() {
+        state.channel_manager.write().set_active_channel(first);
+    }
+    if !prepared.secoc.is_empty() {
+        state.secoc.write().set_configs(prepared.secoc)?;
+        applied.secoc = true;
+    }
+    *state.layout.write() = project.layout;
+    applied.variables = project.variables;
+
+    for job in prepared.transmit_jobs {
+        if job.enabled {
+            let job_id = spawn_periodic_transmit(state, app.clone(), job.frame.clone(), job.interval_ms)?;
+            applied.running_jobs.insert(job.id.clone(), job_id);
+        }
+        applied.transmit_jobs.insert(job.id.clone(), job);
+    }
+    Ok(())
+}
+
+/// Reverse `apply_project`: stop the project's transmit jobs, disconnect and
+/// remove its channels and unload their DBCs
+#[tauri::command]
+pub async fn teardown_project(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    teardown_applied_project(&state).await
+}
+
+/// Start a transmit job registered by the applied project; returns the backend job ID
+#[tauri::command]
+pub async fn start_project_transmit(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    job_id: String,
+) -> Result<String, BootCanError> {
+    let job = {
+        let applied = state.applied_project.read();
+        if let Some(running) = applied.running_jobs.get(&job_id) {
+            if state.periodic_jobs.read().contains_key(running) {
+                return Ok(running.clone());
+            }
+        }
+        applied
+            .transmit_jobs
+            .get(&job_id)
+            .cloned()
+            .ok_or_else(|| BootCanError::NotFound(format!("Transmit job {} is not registered by the project", job_id)))?
     };
+    let frame = job.resolve_frame(&state.applied_project.read().variables)?;
+    let backend_id = spawn_periodic_transmit(&state, app, frame, job.interval_ms)?;
+    state.applied_project.write().running_jobs.insert(job_id, backend_id.clone());
+    Ok(backend_id)
+}
+
+/// Variables of the applied project
+#[tauri::command]
+pub async fn get_project_variables(state: State<'_, AppState>) -> Result<Variables, BootCanError> {
+    Ok(state.applied_project.read().variables.clone())
+}
+
+/// Replace the variables of the applied project
+///
+/// Scenarios and transmit jobs started afterwards use the new values; every
+/// registered template must still resolve.
+#[tauri::command]
+pub async fn set_project_variables(state: State<'_, AppState>, variables: Variables) -> Result<(), BootCanError> {
+    variables.validate()?;
+    let mut applied = state.applied_project.write();
+    for job in applied.transmit_jobs.values() {
+        job.resolve_frame(&variables)?;
+    }
+    applied.variables = variables;
+    Ok(())
+}
+
+async fn teardown_applied_project(state: &AppState) -> Result<(), BootCanError> {
+    let applied = std::mem::take(&mut *state.applied_project.write());
+    {
+        let jobs = state.periodic_jobs.read();
+        for tx in applied.running_jobs.values().filter_map(|id| jobs.get(id)) {
+            let _ = tx.send(true);
+        }
+    }
 
-    log::info!("Project loaded from {}", file_path);
-    Ok(validated_project)
+    // A failure does not stop the rest from being torn down
+    let mut errors = Vec::new();
+    for channel_id in &applied.channel_ids {
+        let channel = state.channel_manager.read().get_channel(channel_id);
+        if let Some(channel) = channel {
+            if let Err(e) = channel.disconnect().await {
+                errors.push(e.context(format!("Channel {}", channel_id)));
+            }
+        }
+        state.channel_manager.write().remove_channel(channel_id);
+        state.dbc_databases.write().remove(channel_id);
+    }
+    if applied.secoc {
+        if let Err(e) = state.secoc.write().set_configs(Vec::new()) {
+            errors.push(e.context("SecOC"));
+        }
+    }
+    *state.layout.write() = LayoutStore::default();
+
+    if !applied.channel_ids.is_empty() {
+        tracing::info!("Project torn down: {} channels removed", applied.channel_ids.len());
+    }
+    BootCanError::join(errors).map_or(Ok(()), Err)
+}
+
+/// Store a frontend setting (view layout, column order, plot configuration) with the project
+#[tauri::command]
+pub async fn set_layout_value(
+    state: State<'_, AppState>,
+    key: String,
+    value: serde_json::Value,
+) -> Result<(), BootCanError> {
+    state.layout.write().set(&key, value)
+}
+
+/// Get a frontend setting stored with the project
+#[tauri::command]
+pub async fn get_layout_value(
+    state: State<'_, AppState>,
+    key: String,
+) -> Result<Option<serde_json::Value>, BootCanError> {
+    Ok(state.layout.read().get(&key).cloned())
+}
+
+/// Get all frontend settings, or those whose key starts with `prefix`
+#[tauri::command]
+pub async fn get_layout(
+    state: State<'_, AppState>,
+    prefix: Option<String>,
+) -> Result<BTreeMap<String, serde_json::Value>, BootCanError> {
+    Ok(state.layout.read().entries(prefix.as_deref()))
+}
+
+/// Remove a frontend setting from the project
+#[tauri::command]
+pub async fn remove_layout_value(state: State<'_, AppState>, key: String) -> Result<(), BootCanError> {
+    state.layout.write().remove(&key);
+    Ok(())
+}
+
+/// Stage the current configuration for the next autosave
+#[tauri::command]
+pub async fn update_autosave(state: State<'_, AppState>, mut project: ProjectFile) -> Result<(), BootCanError> {
+    project.layout = state.layout.read().clone();
+    project.variables = state.applied_project.read().variables.clone();
+    state.autosave.write().stage(project);
+    Ok(())
+}
+
+/// Configuration autosaved by a session that did not exit cleanly, if any
+#[tauri::command]
+pub async fn get_autosave_recovery(state: State<'_, AppState>) -> Result<Option<AutosaveSnapshot>, BootCanError> {
+    Ok(state.autosave.read().recovery().cloned())
+}
+
+/// Forget the recoverable configuration once it was restored or declined
+#[tauri::command]
+pub async fn discard_autosave_recovery(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    state.autosave.write().discard_recovery();
+    Ok(())
+}
+
+/// Look up an existing channel by ID
+fn get_channel(state: &AppState, channel_id: &str) -> Result<ChannelHandle, BootCanError> {
+    let manager = state.channel_manager.read();
+    manager
+        .get_channel(channel_id)
+        .ok_or_else(|| BootCanError::NotFound(format!("Channel {} not found", channel_id)))
+}
+
+/// Get a copy of the DBC loaded for a channel
+fn get_dbc(state: &AppState, channel_id: &str) -> Result<DbcDatabase, BootCanError> {
+    state
+        .dbc_databases
+        .read()
+        .get(channel_id)
+        .cloned()
+        .ok_or_else(|| BootCanError::NotFound(format!("No DBC loaded for channel {}", channel_id)))
+}
+
+/// Spawn a task feeding every frame of a channel to `on_frame` until it is cancelled
+///
+/// The cancellation sender is registered in `monitors` under the channel ID;
+/// starting a new monitor for the same channel stops the previous one.
+fn spawn_channel_monitor<F>(
+    monitors: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    channel: ChannelHandle,
+    channel_id: String,
+    name: &'static str,
+    mut on_frame: F,
+) where
+    F: FnMut(&CanFrame) + Send + 'static,
+{
+    let mut cancel_rx = register_monitor(&monitors, &channel_id);
+    let mut rx = channel.subscribe(&format!("{} monitor", name));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                received = rx.recv() => {
+                    match received {
+                        Some(frame) => on_frame(&frame),
+                        None => break,
+                    }
+                }
+                _ = cancel_rx.changed() => {
+                    if *cancel_rx.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        unregister_monitor(&monitors, &channel_id, &cancel_rx);
+        tracing::info!("{} monitor ended for channel {}", name, channel_id);
+    });
+}
+
+/// Register a monitor of a channel in `monitors`, stopping the previous one
+///
+/// Returns the receiver the monitor stops on.
+fn register_monitor(
+    monitors: &RwLock<HashMap<String, watch::Sender<bool>>>,
+    channel_id: &str,
+) -> watch::Receiver<bool> {
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    if let Some(previous) = monitors.write().insert(channel_id.to_string(), cancel_tx) {
+        let _ = previous.send(true);
+    }
+    cancel_rx
+}
+
+/// Remove an ended monitor from `monitors`
+fn unregister_monitor(
+    monitors: &RwLock<HashMap<String, watch::Sender<bool>>>,
+    channel_id: &str,
+    cancel_rx: &watch::Receiver<bool>,
+) {
+    let mut monitors = monitors.write();
+    // Only remove our own entry; a restarted monitor may have replaced it
+    if monitors
+        .get(channel_id)
+        .is_some_and(|tx| tx.subscribe().same_channel(cancel_rx))
+    {
+        monitors.remove(channel_id);
+    }
+}
+
+/// Stop a monitor started with `spawn_channel_monitor`
+fn stop_channel_monitor(monitors: &RwLock<HashMap<String, watch::Sender<bool>>>, channel_id: &str) {
+    if let Some(cancel_tx) = monitors.write().remove(channel_id) {
+        let _ = cancel_tx.send(true);
+    }
+}
+
+/// Merge the frames of several channels into one queue
+///
+/// The forwarding tasks end once the returned receiver is dropped and the
+/// next frame arrives. Unknown channels are an error.
+fn subscribe_channels(
+    state: &AppState,
+    channel_ids: &[String],
+    name: &'static str,
+) -> Result<mpsc::UnboundedReceiver<CanFrame>, BootCanError> {
+    let channels = channel_ids
+        .iter()
+        .map(|id| get_channel(state, id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+    for channel in channels {
+        let mut rx = channel.subscribe(name);
+        let frame_tx = frame_tx.clone();
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                if frame_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    Ok(frame_rx)
+}
+
+/// Decoded frames of several channels, decoded by the shared decode pool
+///
+/// Every channel needs a DBC. The channels are fed to the pool until the
+/// returned receiver is dropped and the next decoded frame arrives. Frames
+/// the consumer falls too far behind on are dropped and counted by the pool.
+fn subscribe_decoded(
+    state: &AppState,
+    channel_ids: &[String],
+    name: &'static str,
+) -> Result<mpsc::Receiver<Arc<DecodedFrame>>, BootCanError> {
+    let feeds = channel_ids
+        .iter()
+        .map(|id| Ok(state.decode_pool.attach(&get_channel(state, id)?, get_dbc(state, id)?)))
+        .collect::<Result<Vec<_>, BootCanError>>()?;
+
+    let pool = state.decode_pool.clone();
+    let mut decoded = pool.subscribe();
+    let (frame_tx, frame_rx) = mpsc::channel(DECODED_QUEUE_LEN);
+    tokio::spawn(async move {
+        loop {
+            match decoded.recv().await {
+                Ok(frame) if feeds.iter().any(|feed| feed.channel() == frame.frame.channel.as_str()) => {
+                    match frame_tx.try_send(frame) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => pool.count_lost(1),
+                        Err(mpsc::error::TrySendError::Closed(_)) => break,
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("{} falling behind the decoder, lost {} frames", name, skipped);
+                    pool.count_lost(skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(frame_rx)
+}
+
+/// Start decoding J1939 DM1/DM2 messages on a channel
+///
+/// Decoded fault lists are emitted as `j1939-dm` events and kept for `get_j1939_faults`.
+#[tauri::command]
+pub async fn start_j1939_diagnostics(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let faults = state.j1939_faults.clone();
+    let mut decoder = J1939Decoder::new();
+
+    spawn_channel_monitor(
+        state.j1939_monitors.clone(),
+        channel,
+        channel_id.clone(),
+        "J1939 diagnostics",
+        move |frame| {
+            if let Some(message) = decoder.process(frame) {
+                {
+                    let mut faults = faults.write();
+                    let entries = faults.entry(channel_id.clone()).or_default();
+                    entries.retain(|m| {
+                        m.source_address != message.source_address || m.dm_type != message.dm_type
+                    });
+                    entries.push(message.clone());
+                }
+                let _ = app.emit("j1939-dm", &message);
+            }
+        },
+    );
+
+    Ok(())
+}
+
+/// Stop decoding J1939 diagnostic messages on a channel
+#[tauri::command]
+pub async fn stop_j1939_diagnostics(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.j1939_monitors, &channel_id);
+    Ok(())
+}
+
+/// Get the latest DM1/DM2 fault lists decoded on a channel
+#[tauri::command]
+pub async fn get_j1939_faults(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Vec<DiagnosticMessage>, BootCanError> {
+    let faults = state.j1939_faults.read();
+    let mut messages = faults.get(&channel_id).cloned().unwrap_or_default();
+    messages.sort_by_key(|m| (m.source_address, m.dm_type == DmType::Dm2));
+    Ok(messages)
+}
+
+/// Request a PGN (e.g. component ID or VIN) and wait for the responses
+///
+/// Without a destination the request is global and every answering node is returned.
+#[tauri::command]
+pub async fn request_pgn(
+    state: State<'_, AppState>,
+    channel_id: String,
+    pgn: u32,
+    destination: Option<u8>,
+    source_address: Option<u8>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<PgnResponse>, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+    let mut link = ChannelLink::new(channel);
+    j1939::request::request_pgn(
+        &mut link,
+        pgn,
+        destination.unwrap_or(GLOBAL_ADDRESS),
+        source_address.unwrap_or(DEFAULT_TOOL_ADDRESS),
+        timeout,
+    )
+    .await
+}
+
+/// Start tracking CANopen NMT state and heartbeats on a channel
+///
+/// Node state changes are emitted as `canopen-nmt` events.
+#[tauri::command]
+pub async fn start_canopen_monitor(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let nodes = state.canopen_nodes.clone();
+    nodes.write().insert(channel_id.clone(), NmtMonitor::new());
+
+    let monitor_channel_id = channel_id.clone();
+    spawn_channel_monitor(
+        state.canopen_monitors.clone(),
+        channel,
+        channel_id,
+        "CANopen NMT",
+        move |frame| {
+            let status = {
+                let mut nodes = nodes.write();
+                nodes
+                    .get_mut(&monitor_channel_id)
+                    .and_then(|monitor| monitor.process(frame))
+            };
+            if let Some(status) = status {
+                let _ = app.emit("canopen-nmt", &status);
+            }
+        },
+    );
+
+    Ok(())
+}
+
+/// Stop tracking CANopen NMT state on a channel
+#[tauri::command]
+pub async fn stop_canopen_monitor(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.canopen_monitors, &channel_id);
+    Ok(())
+}
+
+/// Get the known CANopen nodes and their NMT state on a channel
+#[tauri::command]
+pub async fn get_canopen_nodes(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Vec<NodeStatus>, BootCanError> {
+    let nodes = state.canopen_nodes.read();
+    Ok(nodes.get(&channel_id).map(|m| m.nodes()).unwrap_or_default())
+}
+
+/// Send an NMT module control command (node_id 0 addresses all nodes)
+#[tauri::command]
+pub async fn send_nmt_command(
+    state: State<'_, AppState>,
+    channel_id: String,
+    command: NmtCommand,
+    node_id: u8,
+) -> Result<(), BootCanError> {
+    if node_id > 127 {
+        return Err(BootCanError::InvalidInput(format!("Invalid CANopen node ID {}", node_id)));
+    }
+    let channel = get_channel(&state, &channel_id)?;
+    let mut link = ChannelLink::new(channel);
+    link.send(command.to_frame(node_id)).await
+}
+
+/// Read an object dictionary entry from a node via SDO
+#[tauri::command]
+pub async fn sdo_read(
+    state: State<'_, AppState>,
+    channel_id: String,
+    node_id: u8,
+    index: u16,
+    subindex: u8,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<u8>, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_SDO_TIMEOUT);
+    let mut link = ChannelLink::new(channel);
+    let mut client = SdoClient::new(&mut link, node_id, timeout)?;
+    client.upload(index, subindex).await
+}
+
+/// Write an object dictionary entry on a node via SDO
+#[tauri::command]
+pub async fn sdo_write(
+    state: State<'_, AppState>,
+    channel_id: String,
+    node_id: u8,
+    index: u16,
+    subindex: u8,
+    data: Vec<u8>,
+    timeout_ms: Option<u64>,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_SDO_TIMEOUT);
+    let mut link = ChannelLink::new(channel);
+    let mut client = SdoClient::new(&mut link, node_id, timeout)?;
+    client.download(index, subindex, &data).await
+}
+
+/// Load a CANopen EDS file for a node and install its default PDO mappings
+#[tauri::command]
+pub async fn load_eds(
+    state: State<'_, AppState>,
+    channel_id: String,
+    node_id: u8,
+    file_path: String,
+) -> Result<usize, BootCanError> {
+    crate::core::canopen::validate_node_id(node_id)?;
+    let eds = EdsParser::parse_file(&file_path, node_id)?;
+    let mapping_count = eds.pdo_mappings.len();
+
+    {
+        let mut decoders = state.pdo_decoders.write();
+        let decoder = decoders.entry(channel_id.clone()).or_default();
+        decoder.set_dictionary(node_id, eds.dictionary);
+        decoder.set_node_mappings(node_id, eds.pdo_mappings);
+    }
+
+    tracing::info!(
+        "Loaded EDS {} for node {} on channel {} ({} PDOs)",
+        file_path,
+        node_id,
+        channel_id,
+        mapping_count
+    );
+    Ok(mapping_count)
+}
+
+/// Read the active PDO mappings from a node via SDO and install them
+#[tauri::command]
+pub async fn read_pdo_mappings(
+    state: State<'_, AppState>,
+    channel_id: String,
+    node_id: u8,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<PdoMapping>, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_SDO_TIMEOUT);
+    let dictionary = {
+        let decoders = state.pdo_decoders.read();
+        decoders
+            .get(&channel_id)
+            .map(|d| d.dictionary(node_id))
+            .unwrap_or_default()
+    };
+
+    let mut link = ChannelLink::new(channel);
+    let mut client = SdoClient::new(&mut link, node_id, timeout)?;
+    let mappings = pdo::read_pdo_mappings(&mut client, node_id, &dictionary).await?;
+
+    {
+        let mut decoders = state.pdo_decoders.write();
+        decoders
+            .entry(channel_id)
+            .or_default()
+            .set_node_mappings(node_id, mappings.clone());
+    }
+
+    Ok(mappings)
+}
+
+/// Get the PDO mappings configured for a channel
+#[tauri::command]
+pub async fn get_pdo_mappings(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<Vec<PdoMapping>, BootCanError> {
+    let decoders = state.pdo_decoders.read();
+    Ok(decoders.get(&channel_id).map(|d| d.mappings()).unwrap_or_default())
+}
+
+fn ccp_timeout(timeout_ms: Option<u64>) -> Duration {
+    timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_CCP_TIMEOUT)
+}
+
+/// The open CCP session with a target
+fn get_ccp_session(state: &AppState, channel_id: &str, target: &CcpTarget) -> Result<CcpSession, BootCanError> {
+    let session_id = target.session_id(channel_id);
+    state
+        .ccp_sessions
+        .read()
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| BootCanError::NotConnected(format!("No CCP session with {}; connect first", session_id)))
+}
+
+/// Open a CCP session with an ECU; returns its CCP version (main, release)
+///
+/// The session is kept until `ccp_disconnect`, with its own frame link and
+/// command counter. Sessions with several ECUs on one channel are told apart
+/// by their CRO/DTO identifiers. `timeout_ms` applies to every command of the session.
+#[tauri::command]
+pub async fn ccp_connect(
+    state: State<'_, AppState>,
+    channel_id: String,
+    target: CcpTarget,
+    timeout_ms: Option<u64>,
+) -> Result<(u8, u8), BootCanError> {
+    let session_id = target.session_id(&channel_id);
+    if state.ccp_sessions.read().contains_key(&session_id) {
+        return Err(BootCanError::Busy(format!("CCP session {} is already open", session_id)));
+    }
+
+    let link = ChannelLink::new(get_channel(&state, &channel_id)?);
+    let mut master = CcpMaster::new(link, target, ccp_timeout(timeout_ms));
+    master.connect().await?;
+    let version = master.get_version().await?;
+    state
+        .ccp_sessions
+        .write()
+        .insert(session_id.clone(), Arc::new(TokioMutex::new(master)));
+
+    tracing::info!("CCP session {} opened", session_id);
+    Ok(version)
+}
+
+/// End (or temporarily pause) a CCP session
+///
+/// The session is closed either way; a paused ECU is resumed with `ccp_connect`.
+#[tauri::command]
+pub async fn ccp_disconnect(
+    state: State<'_, AppState>,
+    channel_id: String,
+    target: CcpTarget,
+    temporary: bool,
+) -> Result<(), BootCanError> {
+    let session = get_ccp_session(&state, &channel_id, &target)?;
+    state.ccp_sessions.write().remove(&target.session_id(&channel_id));
+    let result = session.lock().await.disconnect(temporary).await;
+    result
+}
+
+/// Read ECU memory via CCP
+#[tauri::command]
+pub async fn ccp_upload(
+    state: State<'_, AppState>,
+    channel_id: String,
+    target: CcpTarget,
+    address: u32,
+    extension: u8,
+    size: usize,
+) -> Result<Vec<u8>, BootCanError> {
+    let session = get_ccp_session(&state, &channel_id, &target)?;
+    let result = session.lock().await.upload(address, extension, size).await;
+    result
+}
+
+/// Write ECU memory via CCP
+#[tauri::command]
+pub async fn ccp_download(
+    state: State<'_, AppState>,
+    channel_id: String,
+    target: CcpTarget,
+    address: u32,
+    extension: u8,
+    data: Vec<u8>,
+) -> Result<(), BootCanError> {
+    let session = get_ccp_session(&state, &channel_id, &target)?;
+    let result = session.lock().await.download(address, extension, &data).await;
+    result
+}
+
+/// Get the size of a CCP DAQ list
+#[tauri::command]
+pub async fn ccp_get_daq_size(
+    state: State<'_, AppState>,
+    channel_id: String,
+    target: CcpTarget,
+    daq_list: u8,
+    dto_id: u32,
+) -> Result<DaqListSize, BootCanError> {
+    let session = get_ccp_session(&state, &channel_id, &target)?;
+    let result = session.lock().await.get_daq_size(daq_list, dto_id).await;
+    result
+}
+
+/// Configure one CCP DAQ element (SET_DAQ_PTR followed by WRITE_DAQ)
+#[tauri::command]
+pub async fn ccp_set_daq_element(
+    state: State<'_, AppState>,
+    channel_id: String,
+    target: CcpTarget,
+    element: DaqElement,
+) -> Result<(), BootCanError> {
+    let session = get_ccp_session(&state, &channel_id, &target)?;
+    let result = session.lock().await.configure_daq_element(&element).await;
+    result
+}
+
+/// Replace the SecOC configuration (keys come from the project file)
+#[tauri::command]
+pub async fn set_secoc_config(
+    state: State<'_, AppState>,
+    configs: Vec<SecOcConfig>,
+) -> Result<(), BootCanError> {
+    state.secoc.write().set_configs(configs)
+}
+
+/// Get the active SecOC configuration
+#[tauri::command]
+pub async fn get_secoc_config(state: State<'_, AppState>) -> Result<Vec<SecOcConfig>, BootCanError> {
+    Ok(state.secoc.read().configs())
+}
+
+/// Start verifying SecOC authenticators on a channel
+///
+/// Frames that fail verification are emitted as `secoc-failure` events.
+#[tauri::command]
+pub async fn start_secoc_verification(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let secoc = state.secoc.clone();
+
+    spawn_channel_monitor(
+        state.secoc_monitors.clone(),
+        channel,
+        channel_id,
+        "SecOC",
+        move |frame| {
+            if !frame.direction.is_rx() {
+                return;
+            }
+            let failure = secoc.write().verify(frame);
+            if let Some(failure) = failure {
+                if let Err(e) = app.emit("secoc-failure", &failure) {
+                    tracing::error!("Failed to emit secoc-failure event: {:?}", e);
+                }
+            }
+        },
+    );
+
+    Ok(())
+}
+
+/// Stop SecOC verification on a channel
+#[tauri::command]
+pub async fn stop_secoc_verification(
+    state: State<'_, AppState>,
+    channel_id: String,
+) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.secoc_monitors, &channel_id);
+    Ok(())
+}
+
+/// Start flashing a firmware file (Intel HEX, or raw binary at `base_address`)
+///
+/// Progress is emitted as `flash-progress` events; returns the job ID.
+#[tauri::command]
+pub async fn start_flash(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    file_path: String,
+    base_address: Option<u32>,
+    target: BootloaderTarget,
+) -> Result<String, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let image = FirmwareImage::load(&file_path, base_address.unwrap_or(0))?;
+    let job_id = uuid::Uuid::new_v4().to_string();
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.flash_jobs.write().insert(job_id.clone(), cancel_tx);
+    let flash_jobs = state.flash_jobs.clone();
+    let flash_progress = state.flash_progress.clone();
+    flash_progress.write().insert(
+        job_id.clone(),
+        FlashProgress {
+            job_id: job_id.clone(),
+            stage: FlashStage::EnterBootloader,
+            bytes_done: 0,
+            bytes_total: image.total_size(),
+            error: None,
+        },
+    );
+
+    tracing::info!(
+        "Flash job {} started: {} ({} bytes) on channel {}",
+        job_id,
+        file_path,
+        image.total_size(),
+        channel_id
+    );
+
+    let job = job_id.clone();
+    tokio::spawn(async move {
+        let bytes_total = image.total_size();
+        let emit = |stage: FlashStage, bytes_done: usize, error: Option<String>| {
+            let progress = FlashProgress {
+                job_id: job.clone(),
+                stage,
+                bytes_done,
+                bytes_total,
+                error,
+            };
+            if let Err(e) = app.emit("flash-progress", &progress) {
+                tracing::error!("Failed to emit flash-progress event: {:?}", e);
+            }
+            flash_progress.write().insert(job.clone(), progress);
+        };
+
+        let mut link = ChannelLink::new(channel);
+        let mut protocol = target.connect(&mut link);
+        let result = bootloader::run_flash(protocol.as_mut(), &image, &cancel_rx, |stage, done| {
+            emit(stage, done, None)
+        })
+        .await;
+
+        match result {
+            Ok(()) => tracing::info!("Flash job {} finished", job),
+            Err(e) => {
+                tracing::error!("Flash job {} failed: {}", job, e);
+                emit(FlashStage::Failed, 0, Some(e.to_string()));
+            }
+        }
+
+        flash_jobs.write().remove(&job);
+    });
+
+    Ok(job_id)
+}
+
+/// Cancel a running flash job (takes effect between blocks)
+#[tauri::command]
+pub async fn cancel_flash(state: State<'_, AppState>, job_id: String) -> Result<(), BootCanError> {
+    match state.flash_jobs.read().get(&job_id) {
+        Some(cancel_tx) => {
+            let _ = cancel_tx.send(true);
+            Ok(())
+        }
+        None => Err(BootCanError::NotFound(format!("Flash job {} not found", job_id))),
+    }
+}
+
+/// Open a diagnostic session to one ECU; several sessions can run on the same
+/// channel as long as their request/response ID pairs differ
+#[tauri::command]
+pub async fn open_uds_session(
+    state: State<'_, AppState>,
+    channel_id: String,
+    config: IsoTpConfig,
+    tester_present_ms: Option<u64>,
+) -> Result<UdsSessionInfo, BootCanError> {
+    let session_id = UdsSession::session_id(&channel_id, &config);
+    if state.uds_sessions.read().contains_key(&session_id) {
+        return Err(BootCanError::Busy(format!("UDS session {} is already open", session_id)));
+    }
+
+    let channel = get_channel(&state, &channel_id)?;
+    let session = UdsSession::spawn(
+        ChannelLink::new(channel),
+        channel_id,
+        config,
+        tester_present_ms.map(Duration::from_millis),
+    );
+    let info = session.info().clone();
+    state.uds_sessions.write().insert(session_id, session);
+
+    tracing::info!("UDS session {} opened", info.id);
+    Ok(info)
+}
+
+/// Close a diagnostic session
+#[tauri::command]
+pub async fn close_uds_session(state: State<'_, AppState>, session_id: String) -> Result<(), BootCanError> {
+    state
+        .uds_sessions
+        .write()
+        .remove(&session_id)
+        .map(|_| ())
+        .ok_or_else(|| BootCanError::NotFound(format!("UDS session {} not found", session_id)))
+}
+
+/// List open diagnostic sessions
+#[tauri::command]
+pub async fn get_uds_sessions(state: State<'_, AppState>) -> Result<Vec<UdsSessionInfo>, BootCanError> {
+    let mut sessions: Vec<UdsSessionInfo> = state
+        .uds_sessions
+        .read()
+        .values()
+        .map(|s| s.info().clone())
+        .collect();
+    sessions.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(sessions)
+}
+
+/// Send a raw UDS request on an open session and return the positive response
+#[tauri::command]
+pub async fn uds_request(
+    state: State<'_, AppState>,
+    session_id: String,
+    data: Vec<u8>,
+) -> Result<Vec<u8>, BootCanError> {
+    let requester = state
+        .uds_sessions
+        .read()
+        .get(&session_id)
+        .map(|s| s.requester())
+        .ok_or_else(|| BootCanError::NotFound(format!("UDS session {} not found", session_id)))?;
+    requester.request(data).await
+}
+
+/// Import an ODX-D file or PDX container for symbolic DID, routine and DTC names
+///
+/// The description applies to the session with `session_id`, which does not need to be open yet.
+#[tauri::command]
+pub async fn load_odx(
+    state: State<'_, AppState>,
+    session_id: String,
+    file_path: String,
+) -> Result<DiagDescription, BootCanError> {
+    let description = DiagDescription::load(&file_path)?;
+    tracing::info!(
+        "Loaded diagnostic description {} for UDS session {} ({} DIDs, {} routines, {} DTCs)",
+        file_path,
+        session_id,
+        description.dids.len(),
+        description.routines.len(),
+        description.dtcs.len()
+    );
+    state.diag_descriptions.write().insert(session_id, description.clone());
+    Ok(description)
+}
+
+/// Annotate a UDS response with the names from the session's diagnostic description
+#[tauri::command]
+pub async fn describe_uds_response(
+    state: State<'_, AppState>,
+    session_id: String,
+    response: Vec<u8>,
+) -> Result<Option<DescribedResponse>, BootCanError> {
+    let descriptions = state.diag_descriptions.read();
+    let description = descriptions
+        .get(&session_id)
+        .ok_or_else(|| {
+            BootCanError::NotFound(format!("No diagnostic description loaded for UDS session {}", session_id))
+        })?;
+    Ok(description.describe_response(&response))
+}
+
+/// Start a virtual ECU answering UDS requests from a response table
+///
+/// Returns the simulator ID (channel and request/response ID pair).
+#[tauri::command]
+pub async fn start_ecu_simulator(
+    state: State<'_, AppState>,
+    channel_id: String,
+    config: EcuSimConfig,
+) -> Result<String, BootCanError> {
+    let simulator_id = UdsSession::session_id(&channel_id, &config.isotp);
+    let channel = get_channel(&state, &channel_id)?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    if let Some(previous) = state.ecu_simulators.write().insert(simulator_id.clone(), cancel_tx) {
+        let _ = previous.send(true);
+    }
+
+    let simulators = state.ecu_simulators.clone();
+    let id = simulator_id.clone();
+    tokio::spawn(async move {
+        if let Err(e) = server::run_server(ChannelLink::new(channel), config, cancel_rx.clone()).await {
+            tracing::error!("ECU simulator {} stopped: {}", id, e);
+        }
+
+        let mut simulators = simulators.write();
+        // Only remove our own entry; a restarted simulator may have replaced it
+        if simulators
+            .get(&id)
+            .is_some_and(|tx| tx.subscribe().same_channel(&cancel_rx))
+        {
+            simulators.remove(&id);
+        }
+    });
+
+    tracing::info!("ECU simulator {} started", simulator_id);
+    Ok(simulator_id)
+}
+
+/// Start a simulated OBD-II ECU (mode 01/03/04/09 on 0x7DF)
+///
+/// Without a config an idling engine preset is used. Returns the simulator ID;
+/// stop it with `stop_ecu_simulator`.
+#[tauri::command]
+pub async fn start_obd_simulator(
+    state: State<'_, AppState>,
+    channel_id: String,
+    config: Option<ObdSimConfig>,
+) -> Result<String, BootCanError> {
+    let config = config.unwrap_or_default();
+    // Validate before replacing a running simulator
+    ObdServer::new(&config)?;
+    let simulator_id = format!("{}:obd:{:X}", channel_id, config.response_id);
+    let channel = get_channel(&state, &channel_id)?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    if let Some(previous) = state.ecu_simulators.write().insert(simulator_id.clone(), cancel_tx) {
+        let _ = previous.send(true);
+    }
+
+    let simulators = state.ecu_simulators.clone();
+    let id = simulator_id.clone();
+    tokio::spawn(async move {
+        if let Err(e) = obd::run_obd_ecu(ChannelLink::new(channel), config, cancel_rx.clone()).await {
+            tracing::error!("OBD simulator {} stopped: {}", id, e);
+        }
+
+        let mut simulators = simulators.write();
+        if simulators
+            .get(&id)
+            .is_some_and(|tx| tx.subscribe().same_channel(&cancel_rx))
+        {
+            simulators.remove(&id);
+        }
+    });
+
+    tracing::info!("OBD simulator {} started", simulator_id);
+    Ok(simulator_id)
+}
+
+/// Stop a virtual ECU
+#[tauri::command]
+pub async fn stop_ecu_simulator(state: State<'_, AppState>, simulator_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.ecu_simulators, &simulator_id);
+    Ok(())
+}
+
+/// Progress or result of a UDS scan, emitted as `uds-scan` events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UdsScanEvent {
+    pub scan_id: String,
+    pub progress: Option<ScanProgress>,
+    pub report: Option<ScanReport>,
+    pub error: Option<String>,
+}
+
+/// Start scanning which services, sub-functions and DIDs an ECU accepts
+///
+/// `timeout_ms` is the per-request response timeout (default 100 ms). The
+/// report is delivered in the final `uds-scan` event; returns the scan ID.
+#[tauri::command]
+pub async fn start_uds_scan(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    config: ScanConfig,
+    timeout_ms: Option<u64>,
+) -> Result<String, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let scan_id = uuid::Uuid::new_v4().to_string();
+    let p2 = Duration::from_millis(timeout_ms.unwrap_or(100));
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.uds_scans.write().insert(scan_id.clone(), cancel_tx);
+    let scans = state.uds_scans.clone();
+
+    tracing::info!(
+        "UDS scan {} started on channel {} (0x{:X} -> 0x{:X})",
+        scan_id,
+        channel_id,
+        config.isotp.tx_id,
+        config.isotp.rx_id
+    );
+
+    let id = scan_id.clone();
+    tokio::spawn(async move {
+        let emit = |event: UdsScanEvent| {
+            if let Err(e) = app.emit("uds-scan", &event) {
+                tracing::error!("Failed to emit uds-scan event: {:?}", e);
+            }
+        };
+
+        let mut link = ChannelLink::new(channel);
+        let mut client = UdsClient::new(&mut link, config.isotp.clone())
+            .with_timeouts(p2, crate::core::uds::client::DEFAULT_P2_STAR_TIMEOUT);
+        let result = scanner::scan(&mut client, &config, &cancel_rx, |progress| {
+            emit(UdsScanEvent {
+                scan_id: id.clone(),
+                progress: Some(progress),
+                report: None,
+                error: None,
+            })
+        })
+        .await;
+
+        let (report, error) = match result {
+            Ok(report) => (Some(report), None),
+            Err(e) => {
+                tracing::error!("UDS scan {} failed: {}", id, e);
+                (None, Some(e.to_string()))
+            }
+        };
+        emit(UdsScanEvent {
+            scan_id: id.clone(),
+            progress: None,
+            report,
+            error,
+        });
+
+        scans.write().remove(&id);
+    });
+
+    Ok(scan_id)
+}
+
+/// Cancel a running UDS scan
+#[tauri::command]
+pub async fn cancel_uds_scan(state: State<'_, AppState>, scan_id: String) -> Result<(), BootCanError> {
+    match state.uds_scans.read().get(&scan_id) {
+        Some(cancel_tx) => {
+            let _ = cancel_tx.send(true);
+            Ok(())
+        }
+        None => Err(BootCanError::NotFound(format!("UDS scan {} not found", scan_id))),
+    }
+}
+
+/// Start generating synthetic traffic on a channel
+///
+/// Status is emitted about once per second as `traffic-generator` events.
+#[tauri::command]
+pub async fn start_traffic_generator(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    config: TrafficGenConfig,
+) -> Result<String, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let mut generator = TrafficGenerator::new(config)?;
+    let frames_per_second = generator.frames_per_second(channel.snapshot().await?.config.bitrate);
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let (cancel_tx, mut cancel_rx) = watch::channel(false);
+    state.traffic_generators.write().insert(job_id.clone(), cancel_tx);
+    let generators = state.traffic_generators.clone();
+
+    tracing::info!(
+        "Traffic generator {} started on {} at {:.0} frames/s",
+        job_id,
+        channel_id,
+        frames_per_second
+    );
+
+    let task_job_id = job_id.clone();
+    tokio::spawn(async move {
+        // Frames are sent in small batches so rates above the timer resolution are reachable
+        const TICK: Duration = Duration::from_millis(10);
+        let mut tick = tokio::time::interval(TICK);
+        let start = tokio::time::Instant::now();
+        let mut ticks = 0u32;
+        let mut status = TrafficGenStatus {
+            job_id: task_job_id.clone(),
+            frames_sent: 0,
+            send_errors: 0,
+            frames_per_second: 0.0,
+        };
+        let mut scheduled = 0u64;
+        let mut last_report = (start, 0u64);
+
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    ticks += 1;
+                    let elapsed = if generator.uses_virtual_clock() { TICK * ticks } else { start.elapsed() };
+                    let due = (elapsed.as_secs_f64() * frames_per_second) as u64;
+                    // Don't try to catch up on more than 100 ms of backlog
+                    let max_batch = (frames_per_second / 10.0).ceil() as u64 + 1;
+                    let batch_size = due.saturating_sub(scheduled).min(max_batch);
+                    scheduled = due;
+                    let batch: Vec<CanFrame> = (0..batch_size).map(|_| generator.next_frame()).collect();
+
+                    if !channel.is_connected().await {
+                        tracing::warn!("Traffic generator {}: channel disconnected", task_job_id);
+                        break;
+                    }
+                    let mut errors = 0;
+                    for frame in batch {
+                        if channel.send(frame).await.is_err() {
+                            errors += 1;
+                        }
+                    }
+                    status.frames_sent += batch_size - errors;
+                    status.send_errors += errors;
+
+                    let elapsed = last_report.0.elapsed().as_secs_f64();
+                    if elapsed >= 1.0 {
+                        status.frames_per_second = (status.frames_sent - last_report.1) as f64 / elapsed;
+                        last_report = (tokio::time::Instant::now(), status.frames_sent);
+                        let _ = app.emit("traffic-generator", &status);
+                    }
+                }
+                _ = cancel_rx.changed() => {
+                    if *cancel_rx.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        generators.write().remove(&task_job_id);
+        tracing::info!(
+            "Traffic generator {} stopped after {} frames",
+            task_job_id,
+            status.frames_sent
+        );
+    });
+
+    Ok(job_id)
+}
+
+/// Stop a running traffic generator
+#[tauri::command]
+pub async fn stop_traffic_generator(state: State<'_, AppState>, job_id: String) -> Result<(), BootCanError> {
+    match state.traffic_generators.read().get(&job_id) {
+        Some(cancel_tx) => {
+            let _ = cancel_tx.send(true);
+            Ok(())
+        }
+        None => Err(BootCanError::NotFound(format!("Traffic generator {} not found", job_id))),
+    }
+}
+
+/// Answer frames on a channel according to request/response rules
+///
+/// Replaces the rule set of an auto-responder already running on the channel.
+#[tauri::command]
+pub async fn start_auto_responder(
+    state: State<'_, AppState>,
+    channel_id: String,
+    rules: Vec<ResponseRule>,
+) -> Result<(), BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let rule_count = rules.len();
+    let responder = AutoResponder::new(rules)?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    if let Some(previous) = state.auto_responders.write().insert(channel_id.clone(), cancel_tx) {
+        let _ = previous.send(true);
+    }
+
+    let responders = state.auto_responders.clone();
+    let id = channel_id.clone();
+    tokio::spawn(async move {
+        if let Err(e) =
+            auto_responder::run_auto_responder(ChannelLink::new(channel), responder, cancel_rx.clone()).await
+        {
+            tracing::error!("Auto-responder on {} stopped: {}", id, e);
+        }
+
+        let mut responders = responders.write();
+        if responders
+            .get(&id)
+            .is_some_and(|tx| tx.subscribe().same_channel(&cancel_rx))
+        {
+            responders.remove(&id);
+        }
+    });
+
+    tracing::info!("Auto-responder started on {} with {} rules", channel_id, rule_count);
+    Ok(())
+}
+
+/// Stop the auto-responder on a channel
+#[tauri::command]
+pub async fn stop_auto_responder(state: State<'_, AppState>, channel_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.auto_responders, &channel_id);
+    Ok(())
+}
+
+/// Start a simulated node on a channel, using the channel's DBC for its signals
+///
+/// Returns the node ID (channel and node name); a node with the same ID is replaced.
+#[tauri::command]
+pub async fn start_virtual_ecu(
+    state: State<'_, AppState>,
+    channel_id: String,
+    config: VirtualEcuConfig,
+) -> Result<String, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let database = state
+        .dbc_databases
+        .read()
+        .get(&channel_id)
+        .cloned()
+        .ok_or_else(|| BootCanError::NotFound(format!("No DBC loaded for channel {}", channel_id)))?;
+    let ecu_id = format!("{}:{}", channel_id, config.name);
+    let ecu = VirtualEcu::new(config, database)?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    let status = Arc::new(RwLock::new(ecu.status()));
+    let handle = VirtualEcuHandle {
+        cancel: cancel_tx,
+        status: status.clone(),
+    };
+    if let Some(previous) = state.virtual_ecus.write().insert(ecu_id.clone(), handle) {
+        let _ = previous.cancel.send(true);
+    }
+
+    let ecus = state.virtual_ecus.clone();
+    let id = ecu_id.clone();
+    tokio::spawn(async move {
+        if let Err(e) = virtual_ecu::run_virtual_ecu(ChannelLink::new(channel), ecu, status, cancel_rx.clone()).await {
+            tracing::error!("Virtual ECU {} stopped: {}", id, e);
+        }
+
+        let mut ecus = ecus.write();
+        if ecus
+            .get(&id)
+            .is_some_and(|handle| handle.cancel.subscribe().same_channel(&cancel_rx))
+        {
+            ecus.remove(&id);
+        }
+    });
+
+    tracing::info!("Virtual ECU {} started", ecu_id);
+    Ok(ecu_id)
+}
+
+/// Stop a simulated node
+#[tauri::command]
+pub async fn stop_virtual_ecu(state: State<'_, AppState>, ecu_id: String) -> Result<(), BootCanError> {
+    match state.virtual_ecus.write().remove(&ecu_id) {
+        Some(handle) => {
+            let _ = handle.cancel.send(true);
+            Ok(())
+        }
+        None => Err(BootCanError::NotFound(format!("Virtual ECU {} not found", ecu_id))),
+    }
+}
+
+/// Get the current state and variables of a simulated node
+#[tauri::command]
+pub async fn get_virtual_ecu_status(
+    state: State<'_, AppState>,
+    ecu_id: String,
+) -> Result<VirtualEcuStatus, BootCanError> {
+    state
+        .virtual_ecus
+        .read()
+        .get(&ecu_id)
+        .map(|handle| handle.status.read().clone())
+        .ok_or_else(|| BootCanError::NotFound(format!("Virtual ECU {} not found", ecu_id)))
+}
+
+/// Bridge two channels, applying signal overrides to forwarded frames
+///
+/// Returns the gateway ID ("channelA<->channelB").
+#[tauri::command]
+pub async fn start_gateway(state: State<'_, AppState>, config: GatewayConfig) -> Result<String, BootCanError> {
+    let channel_a = get_channel(&state, &config.channel_a)?;
+    let channel_b = get_channel(&state, &config.channel_b)?;
+    let (database_a, database_b) = {
+        let databases = state.dbc_databases.read();
+        (
+            databases.get(&config.channel_a).cloned(),
+            databases.get(&config.channel_b).cloned(),
+        )
+    };
+    let overrides = Arc::new(RwLock::new(config.overrides.clone()));
+    let gateway = Gateway::new(&config, overrides.clone(), database_a, database_b)?;
+    let gateway_id = format!("{}<->{}", config.channel_a, config.channel_b);
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    let handle = GatewayHandle {
+        cancel: cancel_tx,
+        overrides,
+    };
+    if let Some(previous) = state.gateways.write().insert(gateway_id.clone(), handle) {
+        let _ = previous.cancel.send(true);
+    }
+
+    let gateways = state.gateways.clone();
+    let id = gateway_id.clone();
+    tokio::spawn(async move {
+        let result = gateway::run_gateway(
+            ChannelLink::new(channel_a),
+            ChannelLink::new(channel_b),
+            gateway,
+            cancel_rx.clone(),
+        )
+        .await;
+        if let Err(e) = result {
+            tracing::error!("Gateway {} stopped: {}", id, e);
+        }
+
+        let mut gateways = gateways.write();
+        if gateways
+            .get(&id)
+            .is_some_and(|handle| handle.cancel.subscribe().same_channel(&cancel_rx))
+        {
+            gateways.remove(&id);
+        }
+    });
+
+    tracing::info!("Gateway {} started", gateway_id);
+    Ok(gateway_id)
+}
+
+/// Replace the signal overrides of a running gateway
+#[tauri::command]
+pub async fn set_gateway_overrides(
+    state: State<'_, AppState>,
+    gateway_id: String,
+    overrides: Vec<SignalOverride>,
+) -> Result<(), BootCanError> {
+    let gateways = state.gateways.read();
+    let handle = gateways
+        .get(&gateway_id)
+        .ok_or_else(|| BootCanError::NotFound(format!("Gateway {} not found", gateway_id)))?;
+    *handle.overrides.write() = overrides;
+    Ok(())
+}
+
+/// Stop a gateway
+#[tauri::command]
+pub async fn stop_gateway(state: State<'_, AppState>, gateway_id: String) -> Result<(), BootCanError> {
+    match state.gateways.write().remove(&gateway_id) {
+        Some(handle) => {
+            let _ = handle.cancel.send(true);
+            Ok(())
+        }
+        None => Err(BootCanError::NotFound(format!("Gateway {} not found", gateway_id))),
+    }
+}
+
+/// Decoded output of a plugin for one frame, as emitted in "plugin-output" events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginEvent {
+    pub plugin_id: String,
+    pub frame_id: u32,
+    pub timestamp: f64,
+    pub records: Vec<PluginRecord>,
+    pub events: Vec<String>,
+}
+
+/// Load a WASM plugin and feed it the frames received on a channel
+///
+/// Returns the plugin ID ("channel:name"). Frames the plugin asks to send are
+/// only transmitted with `allow_transmit`.
+#[tauri::command]
+pub async fn load_plugin(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    channel_id: String,
+    file_path: String,
+    allow_transmit: Option<bool>,
+) -> Result<String, BootCanError> {
+    let channel = get_channel(&state, &channel_id)?;
+    let wasm_plugin = WasmPlugin::load(std::path::Path::new(&file_path))?;
+    let plugin_id = format!("{}:{}", channel_id, wasm_plugin.name());
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    if let Some(previous) = state.plugins.write().insert(plugin_id.clone(), cancel_tx) {
+        let _ = previous.send(true);
+    }
+
+    let plugins = state.plugins.clone();
+    let id = plugin_id.clone();
+    tokio::spawn(async move {
+        let on_output = |frame: &CanFrame, output: &PluginOutput| {
+            let event = PluginEvent {
+                plugin_id: id.clone(),
+                frame_id: frame.id,
+                timestamp: frame.timestamp,
+                records: output.records.clone(),
+                events: output.events.clone(),
+            };
+            let _ = app.emit("plugin-output", event);
+        };
+        let result = plugin::run_plugin(
+            ChannelLink::new(channel),
+            wasm_plugin,
+            allow_transmit.unwrap_or(false),
+            cancel_rx.clone(),
+            on_output,
+        )
+        .await;
+        if let Err(e) = result {
+            tracing::error!("Plugin {} stopped: {}", id, e);
+        }
+
+        let mut plugins = plugins.write();
+        if plugins.get(&id).is_some_and(|tx| tx.subscribe().same_channel(&cancel_rx)) {
+            plugins.remove(&id);
+        }
+    });
+
+    tracing::info!("Plugin {} loaded from {}", plugin_id, file_path);
+    Ok(plugin_id)
+}
+
+/// Stop and unload a plugin
+#[tauri::command]
+pub async fn unload_plugin(state: State<'_, AppState>, plugin_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.plugins, &plugin_id);
+    Ok(())
+}
+
+/// IDs of the loaded plugins
+#[tauri::command]
+pub async fn list_plugins(state: State<'_, AppState>) -> Result<Vec<String>, BootCanError> {
+    let mut ids: Vec<String> = state.plugins.read().keys().cloned().collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Scenario host acting on the application's channels
+struct AppScenarioHost<'a> {
+    state: State<'a, AppState>,
+    app: AppHandle,
+    /// Receive subscriptions, opened on first use of a channel
+    links: HashMap<String, ChannelLink>,
+}
+
+impl AppScenarioHost<'_> {
+    fn link(&mut self, channel_id: &str) -> Result<&mut ChannelLink, BootCanError> {
+        if !self.links.contains_key(channel_id) {
+            let channel = get_channel(&self.state, channel_id)?;
+            self.links.insert(channel_id.to_string(), ChannelLink::new(channel));
+        }
+        Ok(self.links.get_mut(channel_id).expect("link was just inserted"))
+    }
+}
+
+#[async_trait::async_trait]
+impl ScenarioHost for AppScenarioHost<'_> {
+    async fn connect(&mut self, channel_id: &str, interface_id: &str, bitrate: u32) -> Result<(), BootCanError> {
+        connect_channel(
+            self.state.clone(),
+            self.app.clone(),
+            channel_id.to_string(),
+            interface_id.to_string(),
+            Some(bitrate),
+        )
+        .await?;
+        self.links.remove(channel_id);
+        self.link(channel_id).map(|_| ())
+    }
+
+    async fn disconnect(&mut self, channel_id: &str) -> Result<(), BootCanError> {
+        self.links.remove(channel_id);
+        disconnect_channel(self.state.clone(), channel_id.to_string()).await
+    }
+
+    async fn send(&mut self, channel_id: &str, frame: CanFrame) -> Result<(), BootCanError> {
+        self.link(channel_id)?.send(frame).await
+    }
+
+    async fn recv(&mut self, channel_id: &str, timeout: Duration) -> Result<Option<CanFrame>, BootCanError> {
+        self.link(channel_id)?.recv(timeout).await
+    }
+
+    fn decode_signal(&self, channel_id: &str, frame: &CanFrame, signal: &str) -> Option<f64> {
+        let databases = self.state.dbc_databases.read();
+        databases
+            .get(channel_id)?
+            .decode_signal(frame.id, signal, &frame.data)
+            .map(|decoded| decoded.physical_value)
+    }
+
+    async fn start_log(&mut self, file_path: &str, format: &str) -> Result<(), BootCanError> {
+        start_logging(self.state.clone(), self.app.clone(), file_path.to_string(), Some(format.to_string())).await
+    }
+
+    async fn stop_log(&mut self) -> Result<(), BootCanError> {
+        stop_logging(self.state.clone()).await
+    }
+}
+
+/// Run a JSON/YAML test scenario and return the per-step report
+///
+/// Each step result is also emitted as a "scenario-step" event while the scenario runs.
+#[tauri::command]
+pub async fn run_scenario_file(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    file_path: String,
+) -> Result<ScenarioReport, BootCanError> {
+    let variables = state.applied_project.read().variables.clone();
+    let scenario = Scenario::load(std::path::Path::new(&file_path), &variables)?;
+    tracing::info!("Running scenario {} ({} steps)", scenario.name, scenario.steps.len());
+
+    let mut host = AppScenarioHost {
+        state,
+        app: app.clone(),
+        links: HashMap::new(),
+    };
+    let report = scenario::run_scenario(&scenario, &mut host, |step| {
+        let _ = app.emit("scenario-step", step);
+    })
+    .await;
+
+    tracing::info!("Scenario {} {}", report.name, if report.passed { "passed" } else { "failed" });
+    Ok(report)
+}
+
+/// Start writing decoded signal values to InfluxDB or a line protocol file
+///
+/// Frames are decoded with the DBC loaded for their channel; channels without
+/// a DBC are skipped.
+#[tauri::command]
+pub async fn start_influx_export(state: State<'_, AppState>, config: InfluxExportConfig) -> Result<(), BootCanError> {
+    if state.influx_export.read().is_some() {
+        return Err(BootCanError::Busy("InfluxDB export already running".to_string()));
+    }
+
+    let databases: HashMap<String, DbcDatabase> = state
+        .dbc_databases
+        .read()
+        .iter()
+        .filter(|(id, _)| config.channels.is_empty() || config.channels.contains(id))
+        .map(|(id, db)| (id.clone(), db.clone()))
+        .collect();
+    if databases.is_empty() {
+        return Err(BootCanError::NotFound("No DBC loaded for the exported channels".to_string()));
+    }
+    let writer = InfluxWriter::open(&config.sink)?;
+
+    let channel_ids: Vec<String> = databases
+        .keys()
+        .filter(|id| state.channel_manager.read().get_channel(id).is_some())
+        .cloned()
+        .collect();
+    let frame_rx = subscribe_decoded(&state, &channel_ids, "InfluxDB export")?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    let status = Arc::new(RwLock::new(InfluxExportStatus::default()));
+    *state.influx_export.write() = Some(InfluxExportHandle {
+        cancel: cancel_tx,
+        status: status.clone(),
+    });
+
+    let sampler = SignalSampler::new(&config, databases);
+    let interval = Duration::from_millis(config.interval_ms);
+    let handle = state.influx_export.clone();
+    tokio::spawn(async move {
+        influx::run_influx_export(frame_rx, sampler, writer, interval, status, cancel_rx.clone()).await;
+        let mut handle = handle.write();
+        if handle
+            .as_ref()
+            .is_some_and(|h| h.cancel.subscribe().same_channel(&cancel_rx))
+        {
+            *handle = None;
+        }
+    });
+
+    tracing::info!("InfluxDB export started ({} ms interval)", config.interval_ms);
+    Ok(())
+}
+
+/// Stop the InfluxDB export after writing the pending values
+#[tauri::command]
+pub async fn stop_influx_export(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    if let Some(export) = state.influx_export.write().take() {
+        let _ = export.cancel.send(true);
+    }
+    Ok(())
+}
+
+/// Counters of the running InfluxDB export (None when stopped)
+#[tauri::command]
+pub async fn get_influx_export_status(state: State<'_, AppState>) -> Result<Option<InfluxExportStatus>, BootCanError> {
+    Ok(state.influx_export.read().as_ref().map(|export| export.status.read().clone()))
+}
+
+/// Stream frames of the given channels as JSON datagrams to a UDP endpoint
+///
+/// Returns the resolved target, which identifies the stream.
+#[tauri::command]
+pub async fn start_udp_broadcast(
+    state: State<'_, AppState>,
+    config: UdpBroadcastConfig,
+) -> Result<String, BootCanError> {
+    let (socket, target) = udp_broadcast::open_socket(&config.target).await?;
+    let frames = subscribe_channels(&state, &config.channels, "UDP stream")?;
+    let stream_id = target.to_string();
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    if let Some(previous) = state.udp_broadcasts.write().insert(stream_id.clone(), cancel_tx) {
+        let _ = previous.send(true);
+    }
+
+    let streams = state.udp_broadcasts.clone();
+    let id = stream_id.clone();
+    tokio::spawn(async move {
+        udp_broadcast::run_udp_broadcast(socket, target, frames, config.rx_only, cancel_rx.clone()).await;
+        let mut streams = streams.write();
+        if streams
+            .get(&id)
+            .is_some_and(|tx| tx.subscribe().same_channel(&cancel_rx))
+        {
+            streams.remove(&id);
+        }
+    });
+
+    tracing::info!("UDP stream to {} started", stream_id);
+    Ok(stream_id)
+}
+
+/// Stop a UDP JSON stream
+#[tauri::command]
+pub async fn stop_udp_broadcast(state: State<'_, AppState>, stream_id: String) -> Result<(), BootCanError> {
+    stop_channel_monitor(&state.udp_broadcasts, &stream_id);
+    Ok(())
+}
+
+/// Serve the given channels to SavvyCAN over the GVRET protocol; returns the bound port
+///
+/// Channels are exposed as GVRET buses in the given order. Bus settings sent
+/// by the client are ignored, configure the channels here instead.
+#[tauri::command]
+pub async fn start_gvret_server(state: State<'_, AppState>, config: GvretServerConfig) -> Result<u16, BootCanError> {
+    if let Some(server) = state.gvret_server.read().as_ref() {
+        return Err(BootCanError::Busy(format!("GVRET server already running on port {}", server.port)));
+    }
+    if config.channels.is_empty() {
+        return Err(BootCanError::InvalidInput("Select at least one channel".to_string()));
+    }
+    let buses = config
+        .channels
+        .iter()
+        .map(|id| get_channel(&state, id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let listener = tokio::net::TcpListener::bind((config.bind_address.as_str(), config.port))
+        .await
+        .map_err(|e| BootCanError::Io(format!("Failed to bind GVRET server: {}", e)))?;
+    let port = listener.local_addr()?.port();
+
+    let (tx, cancel_rx) = watch::channel(false);
+    *state.gvret_server.write() = Some(GvretServerHandle { port, cancel: tx });
+    tokio::spawn(gvret::serve(listener, buses, cancel_rx));
+
+    tracing::info!("GVRET server listening on {}:{}", config.bind_address, port);
+    Ok(port)
+}
+
+/// Stop the GVRET server and disconnect its clients
+#[tauri::command]
+pub async fn stop_gvret_server(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    if let Some(server) = state.gvret_server.write().take() {
+        let _ = server.cancel.send(true);
+    }
+    Ok(())
+}
+
+/// Bridge channels to another bootCAN instance over TCP
+///
+/// Listening returns once the port is bound; connecting returns once the
+/// connection is open. Authentication runs in the background, see the status.
+#[tauri::command]
+pub async fn start_tcp_bridge(state: State<'_, AppState>, config: TcpBridgeConfig) -> Result<(), BootCanError> {
+    if state.tcp_bridge.read().is_some() {
+        return Err(BootCanError::Busy("TCP bridge already running".to_string()));
+    }
+    let links = config
+        .mappings
+        .iter()
+        .map(|mapping| {
+            Ok(BridgeLink {
+                mapping: mapping.clone(),
+                channel: get_channel(&state, &mapping.local)?,
+            })
+        })
+        .collect::<Result<Vec<_>, BootCanError>>()?;
+    let status = Arc::new(RwLock::new(TcpBridgeStatus::default()));
+    let bridge = TcpBridge::new(&config.secret, links, status.clone())?;
+
+    let (cancel_tx, mut cancel_rx) = watch::channel(false);
+    match &config.endpoint {
+        BridgeEndpoint::Listen { bind_address, port } => {
+            let listener = tokio::net::TcpListener::bind((bind_address.as_str(), *port))
+                .await
+                .map_err(|e| BootCanError::Io(format!("Failed to bind TCP bridge: {}", e)))?;
+            tracing::info!("TCP bridge listening on {}:{}", bind_address, port);
+            tokio::spawn(bridge.serve(listener, cancel_rx));
+        }
+        BridgeEndpoint::Connect { address } => {
+            let stream = tokio::net::TcpStream::connect(address.as_str())
+                .await
+                .map_err(|e| BootCanError::Io(format!("Failed to connect to {}: {}", address, e)))?;
+            let _ = stream.set_nodelay(true);
+            let peer = address.clone();
+            let bridges = state.tcp_bridge.clone();
+            tokio::spawn(async move {
+                let result = bridge.run_session(stream, &peer, BridgeRole::Connector, &mut cancel_rx).await;
+                if let Err(e) = result {
+                    tracing::warn!("TCP bridge to {} ended: {}", peer, e);
+                    bridge_status_error(&bridges, &cancel_rx, e.to_string());
+                }
+            });
+        }
+    }
+
+    *state.tcp_bridge.write() = Some(TcpBridgeHandle {
+        cancel: cancel_tx,
+        status,
+    });
+    Ok(())
+}
+
+/// Record why a bridge stopped while keeping its status readable
+fn bridge_status_error(bridges: &RwLock<Option<TcpBridgeHandle>>, cancel_rx: &watch::Receiver<bool>, error: String) {
+    if let Some(bridge) = bridges.read().as_ref() {
+        if bridge.cancel.subscribe().same_channel(cancel_rx) {
+            bridge.status.write().last_error = Some(error);
+        }
+    }
+}
+
+/// Stop the TCP bridge and close its connection
+#[tauri::command]
+pub async fn stop_tcp_bridge(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    if let Some(bridge) = state.tcp_bridge.write().take() {
+        let _ = bridge.cancel.send(true);
+    }
+    Ok(())
+}
+
+/// Connection and counters of the TCP bridge (None when stopped)
+#[tauri::command]
+pub async fn get_tcp_bridge_status(state: State<'_, AppState>) -> Result<Option<TcpBridgeStatus>, BootCanError> {
+    Ok(state.tcp_bridge.read().as_ref().map(|bridge| bridge.status.read().clone()))
+}
+
+/// Publish frames of the given channels on a ZeroMQ PUB socket; returns the bound endpoint
+///
+/// With a SUB endpoint, frames published to it are transmitted on the same channels.
+#[tauri::command]
+pub async fn start_zmq_bridge(state: State<'_, AppState>, config: ZmqBridgeConfig) -> Result<String, BootCanError> {
+    if state.zmq_bridge.read().is_some() {
+        return Err(BootCanError::Busy("ZeroMQ bridge already running".to_string()));
+    }
+    if config.channels.is_empty() {
+        return Err(BootCanError::InvalidInput("Select at least one channel".to_string()));
+    }
+    let channels = config
+        .channels
+        .iter()
+        .map(|id| Ok((id.clone(), get_channel(&state, id)?)))
+        .collect::<Result<HashMap<_, _>, BootCanError>>()?;
+
+    let (publisher, endpoint) = zmq_bridge::bind_publisher(&config.pub_endpoint).await?;
+    let subscriber = match &config.sub_endpoint {
+        Some(sub_endpoint) => Some(zmq_bridge::bind_subscriber(sub_endpoint).await?),
+        None => None,
+    };
+    let frames = subscribe_channels(&state, &config.channels, "ZeroMQ publisher")?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    tokio::spawn(zmq_bridge::run_publisher(publisher, frames, cancel_rx.clone()));
+    if let Some(subscriber) = subscriber {
+        tokio::spawn(zmq_bridge::run_subscriber(subscriber, channels, cancel_rx));
+    }
+    *state.zmq_bridge.write() = Some(ZmqBridgeHandle { cancel: cancel_tx });
+
+    tracing::info!("ZeroMQ publisher bound to {}", endpoint);
+    Ok(endpoint)
+}
+
+/// Close the ZeroMQ sockets
+#[tauri::command]
+pub async fn stop_zmq_bridge(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    if let Some(bridge) = state.zmq_bridge.write().take() {
+        let _ = bridge.cancel.send(true);
+    }
+    Ok(())
+}
+
+/// Start the local JSON-RPC bridge used by external tooling; returns the bound port
+///
+/// The server only listens on the loopback interface.
+#[tauri::command]
+pub async fn start_rpc_server(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    port: Option<u16>,
+) -> Result<u16, BootCanError> {
+    if let Some(server) = state.rpc_server.read().as_ref() {
+        return Err(BootCanError::Busy(format!("RPC server already running on port {}", server.port)));
+    }
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port.unwrap_or(rpc::DEFAULT_RPC_PORT)))
+        .await
+        .map_err(|e| BootCanError::Io(format!("Failed to bind RPC server: {}", e)))?;
+    let port = listener.local_addr()?.port();
+
+    let (tx, cancel_rx) = watch::channel(false);
+    *state.rpc_server.write() = Some(rpc::RpcServerHandle { port, cancel: tx });
+    tokio::spawn(rpc::serve(listener, app, cancel_rx));
+
+    tracing::info!("RPC server listening on 127.0.0.1:{}", port);
+    Ok(port)
+}
+
+/// Stop the JSON-RPC bridge and close its connections
+#[tauri::command]
+pub async fn stop_rpc_server(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    if let Some(server) = state.rpc_server.write().take() {
+        let _ = server.cancel.send(true);
+    }
+    Ok(())
+}
+
+/// Start the REST API; returns the bound port and the API key clients must send
+///
+/// A random key is generated when none is given. Listens on loopback only
+/// unless `bind_address` says otherwise.
+#[tauri::command]
+pub async fn start_rest_server(
+    app: AppHandle,
+    bind_address: Option<String>,
+    port: Option<u16>,
+    api_key: Option<String>,
+) -> Result<RestServerInfo, BootCanError> {
+    let bind_address = bind_address.unwrap_or_else(|| rest::DEFAULT_REST_BIND.to_string());
+    rest::start(app, &bind_address, port.unwrap_or(rest::DEFAULT_REST_PORT), api_key).await
+}
+
+/// Stop the REST API
+#[tauri::command]
+pub async fn stop_rest_server(state: State<'_, AppState>) -> Result<(), BootCanError> {
+    if let Some(server) = state.rest_server.write().take() {
+        let _ = server.cancel.send(true);
+    }
+    Ok(())
+}
+
+/// Channel tasks answering later than this are reported unresponsive
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A background job started by a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobDiagnostics {
+    pub kind: String,
+    pub id: String,
+    /// False once the task ended without the job being stopped
+    pub running: bool,
+}
+
+/// Task states, queue depths and recent warnings, returned by `get_diagnostics`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub channels: Vec<ChannelDiagnostics>,
+    pub jobs: Vec<JobDiagnostics>,
+    /// Whether a trace logger is running
+    pub logging: bool,
+    pub playback: String,
+    /// Latest warnings and errors, oldest first
+    pub recent_warnings: Vec<WarningRecord>,
+    pub decode_pool: DecodePoolStats,
+}
+
+/// State of every channel task and background job, for reports of frames no longer arriving
+#[tauri::command]
+pub async fn get_diagnostics(state: State<'_, AppState>) -> Result<Diagnostics, BootCanError> {
+    let mut channels = Vec::new();
+    for channel in all_channels(&state) {
+        channels.push(channel.diagnostics(DIAGNOSTICS_TIMEOUT).await);
+    }
+    channels.sort_by(|a, b| a.channel_id.cmp(&b.channel_id));
+
+    let cancellable = [
+        ("periodicTransmit", &state.periodic_jobs),
+        ("j1939Monitor", &state.j1939_monitors),
+        ("canopenMonitor", &state.canopen_monitors),
+        ("signalWatch", &state.signal_watches),
+        ("anomalyMonitor", &state.anomaly_monitors),
+        ("cycleMonitor", &state.cycle_monitors),
+        ("secocMonitor", &state.secoc_monitors),
+        ("flash", &state.flash_jobs),
+        ("traceExport", &state.trace_decodes),
+        ("ecuSimulator", &state.ecu_simulators),
+        ("udsScan", &state.uds_scans),
+        ("trafficGenerator", &state.traffic_generators),
+        ("autoResponder", &state.auto_responders),
+        ("plugin", &state.plugins),
+        ("udpBroadcast", &state.udp_broadcasts),
+    ];
+    let mut jobs: Vec<JobDiagnostics> = cancellable
+        .iter()
+        .flat_map(|(kind, senders)| {
+            senders
+                .read()
+                .iter()
+                .map(|(id, cancel)| JobDiagnostics {
+                    kind: kind.to_string(),
+                    id: id.clone(),
+                    running: !cancel.is_closed(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    jobs.sort_by(|a, b| (&a.kind, &a.id).cmp(&(&b.kind, &b.id)));
+
+    let logging = state.trace_logger.read().is_some();
+    let playback = get_playback_state(state.clone()).await?;
+    Ok(Diagnostics {
+        channels,
+        jobs,
+        logging,
+        playback,
+        recent_warnings: state.recent_warnings.snapshot(),
+        decode_pool: state.decode_pool.stats(),
+    })
 }
@@ -1,8 +0,0 @@
-pub mod channel;
-pub mod message;
-pub mod bus_stats;
-pub mod trace_logger;
-pub mod trace_player;
-pub mod dbc;
-pub mod filter;
-
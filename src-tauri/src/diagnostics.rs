@@ -0,0 +1,187 @@
+//! Logging setup and recent warnings
+//!
+//! Log output is filtered by `RUST_LOG` as before, while warnings and errors
+//! are always kept in a small ring buffer, with the channel of the span they
+//! happened in, so `get_diagnostics` can show them after the fact.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::Attributes;
+use tracing::{Event, Id, Level, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Warnings and errors kept for `get_diagnostics`
+pub const RECENT_WARNINGS: usize = 200;
+
+/// A logged warning or error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarningRecord {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Channel of the innermost span with a `channel` field
+    pub channel: Option<String>,
+}
+
+/// Ring buffer of the latest warnings and errors
+#[derive(Clone, Default)]
+pub struct RecentWarnings {
+    records: Arc<Mutex<VecDeque<WarningRecord>>>,
+}
+
+impl RecentWarnings {
+    fn push(&self, record: WarningRecord) {
+        let mut records = self.records.lock();
+        if records.len() == RECENT_WARNINGS {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Recorded warnings, oldest first
+    pub fn snapshot(&self) -> Vec<WarningRecord> {
+        self.records.lock().iter().cloned().collect()
+    }
+
+    /// Layer recording every warning and error into this buffer
+    pub fn layer<S>(&self) -> impl Layer<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        // Info spans are needed too, for the channel they carry
+        let filter = filter_fn(|metadata| {
+            let level = if metadata.is_span() { Level::INFO } else { Level::WARN };
+            *metadata.level() <= level
+        });
+        WarningLayer { warnings: self.clone() }.with_filter(filter)
+    }
+}
+
+/// Install the global subscriber, returning the buffer of recent warnings
+pub fn init_tracing() -> RecentWarnings {
+    let warnings = RecentWarnings::default();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .with(warnings.layer())
+        .init();
+    warnings
+}
+
+/// Channel a span was opened for
+struct SpanChannel(String);
+
+/// Collects the message and the `channel` field of an event or span
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    channel: Option<String>,
+    fields: Vec<String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "channel" => self.channel = Some(value.to_string()),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = Some(format!("{:?}", value)),
+            "channel" => self.channel = Some(format!("{:?}", value)),
+            name => self.fields.push(format!("{}={:?}", name, value)),
+        }
+    }
+}
+
+struct WarningLayer {
+    warnings: RecentWarnings,
+}
+
+impl<S> Layer<S> for WarningLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(channel), Some(span)) = (visitor.channel, ctx.span(id)) {
+            span.extensions_mut().insert(SpanChannel(channel));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let channel = visitor.channel.or_else(|| {
+            ctx.event_scope(event)?
+                .find_map(|span| span.extensions().get::<SpanChannel>().map(|c| c.0.clone()))
+        });
+        let mut message = visitor.message.unwrap_or_default();
+        for field in visitor.fields {
+            message.push(' ');
+            message.push_str(&field);
+        }
+        let metadata = event.metadata();
+        self.warnings.push(WarningRecord {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+            level: if *metadata.level() == Level::ERROR { "error" } else { "warn" }.to_string(),
+            target: metadata.target().to_string(),
+            message,
+            channel,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(f: impl FnOnce()) -> Vec<WarningRecord> {
+        let warnings = RecentWarnings::default();
+        let subscriber = tracing_subscriber::registry().with(warnings.layer());
+        tracing::subscriber::with_default(subscriber, f);
+        warnings.snapshot()
+    }
+
+    #[test]
+    fn test_warnings_keep_channel_of_span() {
+        let records = capture(|| {
+            let _span = tracing::info_span!("channel", channel = "can0").entered();
+            tracing::info!("Connected");
+            let _send = tracing::info_span!("send", id = 0x123).entered();
+            tracing::warn!(skipped = 12, "Subscriber lagging");
+        });
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].message, "Subscriber lagging skipped=12");
+        assert_eq!(records[0].channel.as_deref(), Some("can0"));
+        assert_eq!(records[0].level, "warn");
+    }
+
+    #[test]
+    fn test_oldest_warnings_are_dropped() {
+        let records = capture(|| {
+            for i in 0..RECENT_WARNINGS + 5 {
+                tracing::error!("Failure {}", i);
+            }
+        });
+        assert_eq!(records.len(), RECENT_WARNINGS);
+        assert_eq!(records[0].message, "Failure 5");
+        assert!(records[0].channel.is_none());
+    }
+}
@@ -1,16 +1,52 @@
+mod autosave;
+mod bundle;
 mod commands;
-mod core;
-mod hal;
+mod diagnostics;
+mod project;
+mod recent_projects;
+mod rest;
+mod rpc;
+mod settings;
 
+use bootcan_core::{core, error, hal};
+
+use autosave::Autosave;
 use commands::*;
+use diagnostics::RecentWarnings;
+use core::canopen::{NmtMonitor, PdoDecoder};
 use core::channel::ChannelManager;
+use core::cycle_monitor::CycleMonitor;
 use core::dbc::DbcDatabase;
+use core::decode_pool::DecodePool;
+use core::j1939::DiagnosticMessage;
+use core::secoc::SecOcManager;
+use core::uds::{DiagDescription, UdsSession};
+use core::bootloader::FlashProgress;
+use core::ccp::CcpMaster;
+use core::frame_link::ChannelLink;
+use core::gateway::GatewayHandle;
+use core::gvret::GvretServerHandle;
+use core::influx::InfluxExportHandle;
+use core::tcp_bridge::TcpBridgeHandle;
+use core::zmq_bridge::ZmqBridgeHandle;
+use core::virtual_ecu::VirtualEcuHandle;
+use hal::virtual_can::SharedVirtualBus;
+use project::{AppliedProject, LayoutStore};
+use recent_projects::RecentProjects;
+use rest::RestServerHandle;
+use rpc::RpcServerHandle;
+use settings::Settings;
+use core::frame_store::FrameStore;
 use core::trace_logger::TraceLogger;
 use core::trace_player::TracePlayer;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use tauri::Manager;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{watch, RwLock as TokioRwLock};
+use tokio::sync::{watch, Mutex as TokioMutex, RwLock as TokioRwLock};
+
+/// CCP master of an open session; commands on one session run one at a time
+pub type CcpSession = Arc<TokioMutex<CcpMaster<ChannelLink>>>;
 
 /// Application state shared across all Tauri commands
 pub struct AppState {
@@ -23,6 +59,86 @@ pub struct AppState {
     pub trace_player: Arc<TokioRwLock<TracePlayer>>,
     /// DBC databases loaded per channel (channel_id -> DBC database)
     pub dbc_databases: Arc<RwLock<HashMap<String, DbcDatabase>>>,
+    /// Active J1939 diagnostic monitors per channel with their cancellation senders
+    pub j1939_monitors: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Latest DM1/DM2 messages per channel (one entry per source address and type)
+    pub j1939_faults: Arc<RwLock<HashMap<String, Vec<DiagnosticMessage>>>>,
+    /// Active CANopen NMT monitors per channel with their cancellation senders
+    pub canopen_monitors: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// CANopen node state tracked per channel
+    pub canopen_nodes: Arc<RwLock<HashMap<String, NmtMonitor>>>,
+    /// CANopen PDO mappings per channel, used as a fallback when decoding messages
+    pub pdo_decoders: Arc<RwLock<HashMap<String, PdoDecoder>>>,
+    /// Backend signal-change watches per channel with their cancellation senders
+    pub signal_watches: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Active bus anomaly monitors per channel with their cancellation senders
+    pub anomaly_monitors: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Active cycle-time monitors per channel with their cancellation senders
+    pub cycle_monitors: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Cycle-time counters per channel, kept after the monitor stops
+    pub cycle_checks: Arc<RwLock<HashMap<String, CycleMonitor>>>,
+    /// Frontend settings saved with the project
+    pub layout: Arc<RwLock<LayoutStore>>,
+    /// Recently loaded and saved projects
+    pub recent_projects: Arc<RwLock<RecentProjects>>,
+    /// Application settings from the app config directory
+    pub settings: Arc<RwLock<Settings>>,
+    /// Received frames kept for polling when frames are not emitted as events
+    pub frame_store: Arc<Mutex<FrameStore>>,
+    /// Autosaved configuration and crash recovery
+    pub autosave: Arc<RwLock<Autosave>>,
+    /// What the last `apply_project` installed, reversed by `teardown_project`
+    pub applied_project: Arc<RwLock<AppliedProject>>,
+    /// SecOC configuration and freshness counters
+    pub secoc: Arc<RwLock<SecOcManager>>,
+    /// Active SecOC verification monitors per channel
+    pub secoc_monitors: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Running firmware flash jobs (job_id -> cancel sender)
+    pub flash_jobs: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Latest progress of every flash job started this session, for polling clients
+    pub flash_progress: Arc<RwLock<HashMap<String, FlashProgress>>>,
+    /// Running decoded trace exports (job_id -> cancel sender)
+    pub trace_decodes: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Open UDS diagnostic sessions keyed by channel and request/response ID pair
+    pub uds_sessions: Arc<RwLock<HashMap<String, UdsSession>>>,
+    /// Open CCP sessions keyed by channel and CRO/DTO identifier pair
+    pub ccp_sessions: Arc<RwLock<HashMap<String, CcpSession>>>,
+    /// ODX/PDX diagnostic descriptions keyed by UDS session ID
+    pub diag_descriptions: Arc<RwLock<HashMap<String, DiagDescription>>>,
+    /// Running UDS ECU simulators (simulator_id -> cancel sender)
+    pub ecu_simulators: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Running UDS scans (scan_id -> cancel sender)
+    pub uds_scans: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Running traffic generators (job_id -> cancel sender)
+    pub traffic_generators: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Active auto-responders per channel with their cancellation senders
+    pub auto_responders: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Running simulated nodes keyed by channel and node name
+    pub virtual_ecus: Arc<RwLock<HashMap<String, VirtualEcuHandle>>>,
+    /// Named virtual buses that virtual channels can be attached to
+    pub virtual_buses: Arc<RwLock<HashMap<String, SharedVirtualBus>>>,
+    /// Running channel gateways keyed by "channelA<->channelB"
+    pub gateways: Arc<RwLock<HashMap<String, GatewayHandle>>>,
+    /// Loaded WASM plugins keyed by "channel:name" with their cancellation senders
+    pub plugins: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Running InfluxDB signal export
+    pub influx_export: Arc<RwLock<Option<InfluxExportHandle>>>,
+    /// UDP JSON streams keyed by target address with their cancellation senders
+    pub udp_broadcasts: Arc<RwLock<HashMap<String, watch::Sender<bool>>>>,
+    /// Running GVRET server exposing channels to SavvyCAN
+    pub gvret_server: Arc<RwLock<Option<GvretServerHandle>>>,
+    /// Running instance-to-instance TCP bridge
+    pub tcp_bridge: Arc<RwLock<Option<TcpBridgeHandle>>>,
+    /// Running ZeroMQ publisher and injection subscriber
+    pub zmq_bridge: Arc<RwLock<Option<ZmqBridgeHandle>>>,
+    /// Running JSON-RPC bridge for external tooling
+    pub rpc_server: Arc<RwLock<Option<RpcServerHandle>>>,
+    /// Running REST API for headless operation
+    pub rest_server: Arc<RwLock<Option<RestServerHandle>>>,
+    /// Latest warnings and errors, for `get_diagnostics`
+    pub recent_warnings: RecentWarnings,
+    /// Worker threads decoding live frames for signal watches and the InfluxDB export
+    pub decode_pool: Arc<DecodePool>,
 }
 
 impl Default for AppState {
@@ -33,18 +149,68 @@ impl Default for AppState {
             trace_logger: Arc::new(RwLock::new(None)),
             trace_player: Arc::new(TokioRwLock::new(TracePlayer::new())),
             dbc_databases: Arc::new(RwLock::new(HashMap::new())),
+            j1939_monitors: Arc::new(RwLock::new(HashMap::new())),
+            j1939_faults: Arc::new(RwLock::new(HashMap::new())),
+            canopen_monitors: Arc::new(RwLock::new(HashMap::new())),
+            canopen_nodes: Arc::new(RwLock::new(HashMap::new())),
+            pdo_decoders: Arc::new(RwLock::new(HashMap::new())),
+            signal_watches: Arc::new(RwLock::new(HashMap::new())),
+            anomaly_monitors: Arc::new(RwLock::new(HashMap::new())),
+            cycle_monitors: Arc::new(RwLock::new(HashMap::new())),
+            cycle_checks: Arc::new(RwLock::new(HashMap::new())),
+            layout: Arc::new(RwLock::new(LayoutStore::default())),
+            recent_projects: Arc::new(RwLock::new(RecentProjects::default())),
+            settings: Arc::new(RwLock::new(Settings::default())),
+            frame_store: Arc::new(Mutex::new(FrameStore::default())),
+            autosave: Arc::new(RwLock::new(Autosave::default())),
+            applied_project: Arc::new(RwLock::new(AppliedProject::default())),
+            secoc: Arc::new(RwLock::new(SecOcManager::new())),
+            secoc_monitors: Arc::new(RwLock::new(HashMap::new())),
+            flash_jobs: Arc::new(RwLock::new(HashMap::new())),
+            flash_progress: Arc::new(RwLock::new(HashMap::new())),
+            trace_decodes: Arc::new(RwLock::new(HashMap::new())),
+            uds_sessions: Arc::new(RwLock::new(HashMap::new())),
+            ccp_sessions: Arc::new(RwLock::new(HashMap::new())),
+            diag_descriptions: Arc::new(RwLock::new(HashMap::new())),
+            ecu_simulators: Arc::new(RwLock::new(HashMap::new())),
+            uds_scans: Arc::new(RwLock::new(HashMap::new())),
+            traffic_generators: Arc::new(RwLock::new(HashMap::new())),
+            auto_responders: Arc::new(RwLock::new(HashMap::new())),
+            virtual_ecus: Arc::new(RwLock::new(HashMap::new())),
+            virtual_buses: Arc::new(RwLock::new(HashMap::new())),
+            gateways: Arc::new(RwLock::new(HashMap::new())),
+            plugins: Arc::new(RwLock::new(HashMap::new())),
+            influx_export: Arc::new(RwLock::new(None)),
+            udp_broadcasts: Arc::new(RwLock::new(HashMap::new())),
+            gvret_server: Arc::new(RwLock::new(None)),
+            tcp_bridge: Arc::new(RwLock::new(None)),
+            zmq_bridge: Arc::new(RwLock::new(None)),
+            rpc_server: Arc::new(RwLock::new(None)),
+            rest_server: Arc::new(RwLock::new(None)),
+            recent_warnings: RecentWarnings::default(),
+            decode_pool: Arc::new(DecodePool::default()),
         }
     }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::init();
+    let recent_warnings = diagnostics::init_tracing();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(AppState::default())
+        .manage(AppState {
+            recent_warnings,
+            ..AppState::default()
+        })
+        .setup(|app| {
+            rest::autostart(app.app_handle());
+            start_autosave(app.app_handle());
+            load_recent_projects(app.app_handle());
+            load_settings(app.app_handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_interfaces,
             connect,
@@ -53,6 +219,11 @@ pub fn run() {
             disconnect_channel,
             send_message,
             get_bus_stats,
+            get_id_stats,
+            set_stats_emission,
+            get_stats_emission,
+            get_stats_history,
+            get_throughput_history,
             start_periodic_transmit,
             stop_periodic_transmit,
             start_logging,
@@ -65,16 +236,179 @@ pub fn run() {
             resume_playback,
             set_playback_speed,
             get_playback_state,
+            set_replay_mutations,
             load_dbc,
             decode_message,
             decode_messages_batch,
             get_message_info,
             get_all_signals,
+            get_signal_series,
+            get_signal_envelope,
+            get_signal_statistics,
+            get_id_heatmap,
+            start_trace_export,
+            export_trace,
+            cancel_trace_export,
+            start_signal_watch,
+            stop_signal_watch,
+            start_anomaly_monitor,
+            stop_anomaly_monitor,
+            get_top_talkers,
+            start_cycle_monitor,
+            stop_cycle_monitor,
+            get_cycle_violations,
             set_advanced_filter,
+            set_fault_injection,
+            get_fault_injection,
+            create_virtual_bus,
+            delete_virtual_bus,
+            attach_virtual_channel,
+            detach_virtual_channel,
+            list_virtual_buses,
+            inject_virtual_bus_frame,
             save_project,
             load_project,
+            apply_project,
+            teardown_project,
+            start_project_transmit,
+            get_project_variables,
+            set_project_variables,
+            set_layout_value,
+            get_layout_value,
+            get_layout,
+            remove_layout_value,
+            get_recent_projects,
+            pin_recent_project,
+            remove_recent_project,
+            set_reopen_last_project,
+            get_startup_project,
+            get_settings,
+            update_settings,
+            get_frame_delta,
+            clear_frame_store,
+            export_project_bundle,
+            import_project_bundle,
+            update_autosave,
+            get_autosave_recovery,
+            discard_autosave_recovery,
+            start_j1939_diagnostics,
+            stop_j1939_diagnostics,
+            get_j1939_faults,
+            request_pgn,
+            start_canopen_monitor,
+            stop_canopen_monitor,
+            get_canopen_nodes,
+            send_nmt_command,
+            sdo_read,
+            sdo_write,
+            load_eds,
+            read_pdo_mappings,
+            get_pdo_mappings,
+            ccp_connect,
+            ccp_disconnect,
+            ccp_upload,
+            ccp_download,
+            ccp_get_daq_size,
+            ccp_set_daq_element,
+            set_secoc_config,
+            get_secoc_config,
+            start_secoc_verification,
+            stop_secoc_verification,
+            start_flash,
+            cancel_flash,
+            open_uds_session,
+            close_uds_session,
+            get_uds_sessions,
+            uds_request,
+            load_odx,
+            describe_uds_response,
+            start_ecu_simulator,
+            start_obd_simulator,
+            stop_ecu_simulator,
+            start_uds_scan,
+            cancel_uds_scan,
+            start_traffic_generator,
+            stop_traffic_generator,
+            start_auto_responder,
+            stop_auto_responder,
+            start_virtual_ecu,
+            stop_virtual_ecu,
+            get_virtual_ecu_status,
+            start_gateway,
+            set_gateway_overrides,
+            stop_gateway,
+            load_plugin,
+            unload_plugin,
+            list_plugins,
+            run_scenario_file,
+            start_influx_export,
+            stop_influx_export,
+            get_influx_export_status,
+            start_udp_broadcast,
+            stop_udp_broadcast,
+            start_gvret_server,
+            stop_gvret_server,
+            start_tcp_bridge,
+            stop_tcp_bridge,
+            get_tcp_bridge_status,
+            start_zmq_bridge,
+            stop_zmq_bridge,
+            start_rpc_server,
+            stop_rpc_server,
+            start_rest_server,
+            stop_rest_server,
+            get_diagnostics,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().autosave.write().close();
+            }
+        });
+}
+
+/// Read the recent projects list from the app config directory
+fn load_recent_projects(app: &tauri::AppHandle) {
+    match app.path().app_config_dir() {
+        Ok(dir) => *app.state::<AppState>().recent_projects.write() = RecentProjects::open(&dir),
+        Err(e) => tracing::error!("Recent projects are not kept, no app config directory: {}", e),
+    }
+}
+
+/// Read the application settings from the app config directory
+fn load_settings(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    match app.path().app_config_dir() {
+        Ok(dir) => *state.settings.write() = Settings::open(&dir),
+        Err(e) => tracing::error!("Settings are not kept, no app config directory: {}", e),
+    }
+    let settings = state.settings.read().values().clone();
+    let mut manager = state.channel_manager.write();
+    manager.set_default_stats_emission(settings.stats_emission());
+    manager.set_broadcast_capacity(settings.broadcast_capacity);
+}
+
+/// Open the autosave in the app data directory and write it periodically
+fn start_autosave(app: &tauri::AppHandle) {
+    let dir = match app.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::error!("Autosave disabled, no app data directory: {}", e);
+            return;
+        }
+    };
+    let autosave = app.state::<AppState>().autosave.clone();
+    *autosave.write() = Autosave::open(dir);
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(autosave::AUTOSAVE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = autosave.write().flush() {
+                tracing::error!("{}", e);
+            }
+        }
+    });
 }
 
@@ -0,0 +1,636 @@
+//! Project files and their application to the backend
+//!
+//! Applying a project happens in two steps: `PreparedProject::prepare` parses
+//! DBCs, converts filters and checks transmit jobs without touching any state,
+//! so a project that fails validation leaves the running configuration alone.
+//! Only a fully prepared project is installed by `apply_project`.
+//!
+//! DBC references are stored relative to the project file where possible,
+//! together with a hash of the database; the database itself can be embedded
+//! so the project also opens on machines without the file.
+
+use crate::core::bus_stats::StatsEmission;
+use crate::core::dbc::{DbcDatabase, DbcParser, LdfParser, SymParser};
+use crate::core::filter::{FilterLogic, FilterRule, FilterSet};
+use crate::core::message::FramePayload;
+use crate::core::secoc::{SecOcConfig, SecOcManager};
+use crate::core::variables::Variables;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Version written by `save_project`; 1.1 stores typed filters
+pub const PROJECT_VERSION: &str = "1.1";
+
+/// Project file structures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectChannel {
+    pub id: String,
+    pub name: String,
+    pub interface_id: Option<String>,
+    pub bitrate: u32,
+    pub dbc_file: Option<String>,
+    /// Statistics emission settings; taken from the running channel when not given on save
+    #[serde(default)]
+    pub stats_emission: Option<StatsEmission>,
+    /// Hash of the DBC content when the project was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dbc_hash: Option<String>,
+    /// Embedded DBC content, used when the file is missing or has changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dbc_content: Option<String>,
+}
+
+/// How `save_project` stores DBC references
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbcSaveOptions {
+    /// Store paths below the project directory relative to the project file
+    #[serde(default = "default_true")]
+    pub relative_paths: bool,
+    /// Embed the database content in the project file
+    #[serde(default)]
+    pub embed: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DbcSaveOptions {
+    fn default() -> Self {
+        Self {
+            relative_paths: true,
+            embed: false,
+        }
+    }
+}
+
+/// Why the DBC referenced by a project could not be used as is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DbcConflictKind {
+    /// The file does not exist or cannot be read
+    Missing,
+    /// The file content differs from the content the project was saved with
+    Modified,
+}
+
+/// DBC reference of a loaded project that did not match the file on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbcConflict {
+    pub channel_id: String,
+    pub path: String,
+    pub kind: DbcConflictKind,
+    /// The embedded copy is used instead of the file
+    pub used_embedded: bool,
+}
+
+/// FNV-1a hash of a database, to notice changed files; not a security measure
+pub fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("fnv1a64:{:016x}", hash)
+}
+
+/// `path` relative to `dir` when it lies below it
+fn relative_path(path: &Path, dir: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?;
+    let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    Some(parts.join("/"))
+}
+
+impl ProjectChannel {
+    /// Rewrite the DBC reference for a project file saved in `project_dir`
+    pub fn store_dbc(&mut self, project_dir: &Path, options: DbcSaveOptions) {
+        self.dbc_hash = None;
+        self.dbc_content = None;
+        let Some(path) = self.dbc_file.clone() else {
+            return;
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                self.dbc_hash = Some(content_hash(&content));
+                if options.embed {
+                    self.dbc_content = Some(content);
+                }
+            }
+            Err(e) => tracing::warn!("Cannot read DBC {} of channel {}: {}", path, self.id, e),
+        }
+        if options.relative_paths {
+            if let Some(relative) = relative_path(Path::new(&path), project_dir) {
+                self.dbc_file = Some(relative);
+            }
+        }
+    }
+
+    /// Resolve the DBC reference of a project file loaded from `project_dir`
+    ///
+    /// Relative paths become absolute. The embedded copy stays in use when the
+    /// file is missing or changed; otherwise it is dropped so the file is loaded.
+    pub fn resolve_dbc(&mut self, project_dir: &Path) -> Option<DbcConflict> {
+        let path = self.dbc_file.as_ref()?;
+        let path = project_dir.join(path);
+        self.dbc_file = Some(path.to_string_lossy().into_owned());
+        self.check_dbc(fs::read_to_string(&path).ok().as_deref())
+    }
+
+    /// Compare the referenced DBC with the file content on disk
+    fn check_dbc(&mut self, on_disk: Option<&str>) -> Option<DbcConflict> {
+        let kind = match on_disk {
+            None => DbcConflictKind::Missing,
+            Some(content) if self.dbc_hash.as_ref().is_some_and(|hash| *hash != content_hash(content)) => {
+                DbcConflictKind::Modified
+            }
+            Some(_) => {
+                self.dbc_content = None;
+                return None;
+            }
+        };
+        let conflict = DbcConflict {
+            channel_id: self.id.clone(),
+            path: self.dbc_file.clone().unwrap_or_default(),
+            kind,
+            used_embedded: self.dbc_content.is_some(),
+        };
+        if kind == DbcConflictKind::Missing && self.dbc_content.is_none() {
+            self.dbc_file = None;
+        }
+        Some(conflict)
+    }
+
+    /// Parse the channel's DBC, from the embedded copy when there is one
+    fn load_dbc(&self) -> Result<Option<DbcDatabase>, BootCanError> {
+        let Some(path) = &self.dbc_file else {
+            return Ok(None);
+        };
+        let lower = path.to_lowercase();
+        let extension = lower.rsplit('.').next().unwrap_or_default();
+        let db = match (&self.dbc_content, extension) {
+            (Some(content), "sym") => SymParser::parse(content),
+            (Some(content), "ldf") => LdfParser::parse(content),
+            (Some(content), _) => DbcParser::parse(content),
+            (None, "sym") => SymParser::parse_file(path),
+            (None, "ldf") => LdfParser::parse_file(path),
+            (None, _) => DbcParser::parse_file(path),
+        };
+        db.map(Some)
+    }
+}
+
+/// Named filter stored in a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFilter {
+    pub name: String,
+    /// Channel the filter is installed on; every project channel when unset
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    /// Only enabled filters are installed, at most one per channel
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub filter: FilterSet,
+}
+
+/// Filter entry as found in project files of any version
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredFilter {
+    Typed(ProjectFilter),
+    /// Filter panel rule wrapped in `data`, written before filters were typed
+    Legacy(serde_json::Value),
+}
+
+/// Convert a filter panel rule of an old project file into a backend rule
+fn legacy_rule(data: &serde_json::Value) -> Result<FilterRule, BootCanError> {
+    let rule = data.get("data").unwrap_or(data);
+    let number = |key: &str, default: u64| rule.get(key).and_then(|v| v.as_u64()).unwrap_or(default);
+    let flag = |key: &str| rule.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match rule.get("type").and_then(|v| v.as_str()) {
+        Some("idRange") => Ok(FilterRule::IdRange {
+            min: number("idMin", 0) as u32,
+            max: number("idMax", 0x7FF) as u32,
+        }),
+        Some("idExact") => Ok(FilterRule::IdExact(number("idExact", 0) as u32)),
+        Some("dlcRange") => Ok(FilterRule::DlcRange {
+            min: number("dlcMin", 0) as u8,
+            max: number("dlcMax", 8) as u8,
+        }),
+        Some("direction") => Ok(FilterRule::Direction { rx: flag("rx"), tx: flag("tx") }),
+        Some("extendedId") => Ok(FilterRule::ExtendedId(flag("extended"))),
+        Some("remoteFrame") => Ok(FilterRule::RemoteFrame(flag("remote"))),
+        Some(other) => Err(BootCanError::Parse(format!("Unknown filter type {}", other))),
+        None => Err(BootCanError::Parse("Filter without a type".to_string())),
+    }
+}
+
+/// Read typed filters, merging the rules of an old project into one filter for all channels
+fn deserialize_filters<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<ProjectFilter>, D::Error> {
+    let stored = Vec::<StoredFilter>::deserialize(deserializer)?;
+    let mut filters = Vec::new();
+    let mut legacy_rules = Vec::new();
+    for filter in stored {
+        match filter {
+            StoredFilter::Typed(filter) => filters.push(filter),
+            // A typed filter that failed to parse; report why instead of treating it as a legacy rule
+            StoredFilter::Legacy(data) if data.get("filter").is_some() => {
+                let error = serde_json::from_value::<ProjectFilter>(data).err();
+                return Err(serde::de::Error::custom(format!(
+                    "Invalid filter: {}",
+                    error.map(|e| e.to_string()).unwrap_or_default()
+                )));
+            }
+            StoredFilter::Legacy(data) => legacy_rules.push(legacy_rule(&data).map_err(serde::de::Error::custom)?),
+        }
+    }
+    if !legacy_rules.is_empty() {
+        filters.push(ProjectFilter {
+            name: "Filter".to_string(),
+            channel_id: None,
+            enabled: true,
+            filter: FilterSet::new(legacy_rules, FilterLogic::And),
+        });
+    }
+    Ok(filters)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTransmitJob {
+    pub id: String,
+    pub frame: FramePayload,
+    pub interval_ms: u64,
+    pub enabled: bool,
+    /// Frame with `${NAME}` references to project variables; replaces `frame` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<serde_json::Value>,
+}
+
+impl ProjectTransmitJob {
+    /// Frame to send, with the template resolved against `variables`
+    pub fn resolve_frame(&self, variables: &Variables) -> Result<FramePayload, BootCanError> {
+        let Some(template) = &self.template else {
+            return Ok(self.frame.clone());
+        };
+        let mut frame: FramePayload =
+            variables.resolve(template).map_err(|e| e.context(format!("Transmit job {}", self.id)))?;
+        if frame.channel.is_none() {
+            frame.channel = self.frame.channel.clone();
+        }
+        Ok(frame)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFile {
+    pub version: String,
+    pub channels: Vec<ProjectChannel>,
+    #[serde(deserialize_with = "deserialize_filters")]
+    pub filters: Vec<ProjectFilter>,
+    pub transmit_jobs: Vec<ProjectTransmitJob>,
+    #[serde(default)]
+    pub secoc: Vec<SecOcConfig>,
+    /// View layouts, column settings and plot configurations of the frontend
+    #[serde(default, skip_serializing_if = "LayoutStore::is_empty")]
+    pub layout: LayoutStore,
+    /// Values referenced as `${NAME}` by transmit templates and scenarios
+    #[serde(default, skip_serializing_if = "Variables::is_empty")]
+    pub variables: Variables,
+}
+
+impl ProjectFile {
+    /// Validate the filters and resolve the enabled one of each channel
+    pub fn channel_filters(&self) -> Result<HashMap<String, FilterSet>, BootCanError> {
+        let mut installed = HashMap::new();
+        for filter in &self.filters {
+            filter.filter.validate().map_err(|e| e.context(format!("Filter {}", filter.name)))?;
+            if let Some(channel_id) = &filter.channel_id {
+                if !self.channels.iter().any(|ch| &ch.id == channel_id) {
+                    return Err(BootCanError::NotFound(format!(
+                        "Filter {} uses channel {} which is not in the project", filter.name, channel_id
+                    )));
+                }
+            }
+            if !filter.enabled {
+                continue;
+            }
+            let targets = self
+                .channels
+                .iter()
+                .map(|ch| &ch.id)
+                .filter(|id| filter.channel_id.as_ref().is_none_or(|target| target == *id));
+            for channel_id in targets {
+                if installed.insert(channel_id.clone(), filter.filter.clone()).is_some() {
+                    return Err(BootCanError::InvalidInput(format!(
+                        "More than one filter is enabled for channel {}", channel_id
+                    )));
+                }
+            }
+        }
+        Ok(installed)
+    }
+}
+
+/// Longest accepted layout key
+const MAX_LAYOUT_KEY_LEN: usize = 128;
+/// Largest accepted layout value, serialized
+const MAX_LAYOUT_VALUE_BYTES: usize = 256 * 1024;
+
+/// Key-value store of frontend settings that are saved with the project
+///
+/// Keys are free-form; dotted prefixes such as `plot.` or `columns.` group
+/// the settings of one view.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LayoutStore(BTreeMap<String, serde_json::Value>);
+
+impl LayoutStore {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.0.get(key)
+    }
+
+    pub fn set(&mut self, key: &str, value: serde_json::Value) -> Result<(), BootCanError> {
+        if key.is_empty() || key.len() > MAX_LAYOUT_KEY_LEN {
+            return Err(BootCanError::InvalidInput(format!(
+                "Layout keys must have 1 to {} characters", MAX_LAYOUT_KEY_LEN
+            )));
+        }
+        let size = serde_json::to_vec(&value).map(|v| v.len()).unwrap_or(usize::MAX);
+        if size > MAX_LAYOUT_VALUE_BYTES {
+            return Err(BootCanError::InvalidInput(format!(
+                "Layout value of {} is {} bytes, at most {} are kept", key, size, MAX_LAYOUT_VALUE_BYTES
+            )));
+        }
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<serde_json::Value> {
+        self.0.remove(key)
+    }
+
+    /// Entries whose key starts with `prefix`, or all entries
+    pub fn entries(&self, prefix: Option<&str>) -> BTreeMap<String, serde_json::Value> {
+        self.0
+            .iter()
+            .filter(|(key, _)| prefix.is_none_or(|prefix| key.starts_with(prefix)))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// A project read from disk, with the DBC references that did not match their files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedProject {
+    #[serde(flatten)]
+    pub project: ProjectFile,
+    pub dbc_conflicts: Vec<DbcConflict>,
+}
+
+/// A validated project, ready to be installed
+pub struct PreparedProject {
+    pub channels: Vec<ProjectChannel>,
+    /// Parsed DBC per channel ID
+    pub dbcs: HashMap<String, DbcDatabase>,
+    /// Enabled filter per channel ID
+    pub filters: HashMap<String, FilterSet>,
+    /// Transmit jobs with their channel resolved
+    pub transmit_jobs: Vec<ProjectTransmitJob>,
+    pub secoc: Vec<SecOcConfig>,
+}
+
+impl PreparedProject {
+    /// Validate a project and load everything it references, without side effects
+    pub fn prepare(project: &ProjectFile) -> Result<Self, BootCanError> {
+        let mut channel_ids = HashSet::new();
+        for channel in &project.channels {
+            if channel.id.is_empty() {
+                return Err(BootCanError::InvalidInput(format!("Channel {} has no ID", channel.name)));
+            }
+            if !channel_ids.insert(channel.id.as_str()) {
+                return Err(BootCanError::InvalidInput(format!("Channel {} appears twice in the project", channel.id)));
+            }
+        }
+
+        let mut dbcs = HashMap::new();
+        for channel in &project.channels {
+            if let Some(db) = channel.load_dbc().map_err(|e| e.context(format!("Channel {}", channel.id)))? {
+                dbcs.insert(channel.id.clone(), db);
+            }
+        }
+
+        let filters = project.channel_filters()?;
+        project.variables.validate()?;
+
+        let default_channel = project.channels.first().map(|ch| ch.id.clone());
+        let mut transmit_jobs = Vec::with_capacity(project.transmit_jobs.len());
+        for job in &project.transmit_jobs {
+            if job.interval_ms == 0 {
+                return Err(BootCanError::InvalidInput(format!("Transmit job {} has no interval", job.id)));
+            }
+            let mut job = job.clone();
+            job.frame = job.resolve_frame(&project.variables)?;
+            let channel = job
+                .frame
+                .channel
+                .clone()
+                .filter(|c| !c.is_empty())
+                .or_else(|| default_channel.clone())
+                .ok_or_else(|| BootCanError::InvalidInput(format!("Transmit job {} has no channel", job.id)))?;
+            if !channel_ids.contains(channel.as_str()) {
+                return Err(BootCanError::NotFound(format!(
+                    "Transmit job {} uses channel {} which is not in the project", job.id, channel
+                )));
+            }
+            job.frame.channel = Some(channel);
+            transmit_jobs.push(job);
+        }
+
+        // Installing the SecOC profiles cannot fail once their keys check out here
+        SecOcManager::new().set_configs(project.secoc.clone())?;
+
+        Ok(Self {
+            channels: project.channels.clone(),
+            dbcs,
+            filters,
+            transmit_jobs,
+            secoc: project.secoc.clone(),
+        })
+    }
+}
+
+/// What `apply_project` installed, so `teardown_project` can reverse it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedProject {
+    pub channel_ids: Vec<String>,
+    /// Registered transmit jobs keyed by their project job ID
+    pub transmit_jobs: HashMap<String, ProjectTransmitJob>,
+    /// Backend job IDs of the running project jobs, keyed by project job ID
+    pub running_jobs: HashMap<String, String>,
+    /// Whether the project installed SecOC profiles
+    pub secoc: bool,
+    /// Project variables, used by transmit templates and scenarios
+    pub variables: Variables,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(channels: &[&str], filters: serde_json::Value, jobs: serde_json::Value) -> serde_json::Result<ProjectFile> {
+        let channels: Vec<_> = channels
+            .iter()
+            .map(|id| json!({ "id": id, "name": id, "interfaceId": null, "bitrate": 500000, "dbcFile": null }))
+            .collect();
+        serde_json::from_value(json!({
+            "version": "1.0",
+            "channels": channels,
+            "filters": filters,
+            "transmitJobs": jobs,
+        }))
+    }
+
+    fn project(channels: &[&str], filters: serde_json::Value, jobs: serde_json::Value) -> ProjectFile {
+        parse(channels, filters, jobs).unwrap()
+    }
+
+    fn job(id: &str, channel: Option<&str>) -> serde_json::Value {
+        json!({
+            "id": id,
+            "frame": { "id": 0x100, "isExtended": false, "isRemote": false, "dlc": 1, "data": [1], "channel": channel },
+            "intervalMs": 100,
+            "enabled": false,
+        })
+    }
+
+    #[test]
+    fn test_prepare_resolves_filters_and_jobs() {
+        let filters = json!([
+            { "data": { "type": "idRange", "idMin": 256, "idMax": 511 } },
+            { "data": { "type": "direction", "rx": true } },
+        ]);
+        let prepared = PreparedProject::prepare(&project(&["can0", "can1"], filters, json!([job("a", None), job("b", Some("can1"))]))).unwrap();
+
+        // Rules of an old project become one filter on every channel
+        for channel in ["can0", "can1"] {
+            let filter = &prepared.filters[channel];
+            assert!(matches!(filter.rules[0], FilterRule::IdRange { min: 256, max: 511 }));
+            assert!(matches!(filter.rules[1], FilterRule::Direction { rx: true, tx: false }));
+        }
+        let channels: Vec<_> = prepared.transmit_jobs.iter().map(|j| j.frame.channel.clone().unwrap()).collect();
+        assert_eq!(channels, vec!["can0", "can1"]);
+    }
+
+    #[test]
+    fn test_transmit_templates_use_variables() {
+        let mut template = job("diag", None);
+        template["template"] = json!({ "id": "${TESTER_ID}", "isExtended": false, "isRemote": false, "dlc": 2, "data": [2, "${SESSION}"] });
+        let mut project = project(&["can0"], json!([]), json!([template]));
+        assert!(PreparedProject::prepare(&project).err().unwrap().message().contains("Undefined variable"));
+
+        project.variables.set("TESTER_ID", "0x7E0").unwrap();
+        project.variables.set("SESSION", "3").unwrap();
+        let prepared = PreparedProject::prepare(&project).unwrap();
+        let frame = &prepared.transmit_jobs[0].frame;
+        assert_eq!((frame.id, frame.data.clone(), frame.channel.as_deref()), (0x7E0, vec![2, 3], Some("can0")));
+
+        // Retargeting only needs other values
+        project.variables.set("TESTER_ID", "0x7E1").unwrap();
+        assert_eq!(prepared.transmit_jobs[0].resolve_frame(&project.variables).unwrap().id, 0x7E1);
+    }
+
+    #[test]
+    fn test_typed_filters() {
+        let filter = |name: &str, channel: Option<&str>, enabled: bool| {
+            json!({
+                "name": name,
+                "channelId": channel,
+                "enabled": enabled,
+                "filter": { "rules": [{ "IdExact": 0x100 }], "logic": "Or" },
+            })
+        };
+        let filters = json!([filter("Engine", Some("can1"), true), filter("All", None, false)]);
+        let project = project(&["can0", "can1"], filters, json!([]));
+        let installed = project.channel_filters().unwrap();
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed["can1"].logic, FilterLogic::Or);
+
+        let clash = parse(&["can0"], json!([filter("A", None, true), filter("B", Some("can0"), true)]), json!([])).unwrap();
+        assert!(clash.channel_filters().is_err());
+        let unknown = parse(&["can0"], json!([filter("A", Some("can9"), false)]), json!([])).unwrap();
+        assert!(unknown.channel_filters().is_err());
+        let invalid = json!([{ "name": "A", "filter": { "rules": [{ "Signal": 1 }] } }]);
+        assert!(parse(&["can0"], invalid, json!([])).unwrap_err().to_string().contains("Invalid filter"));
+    }
+
+    #[test]
+    fn test_layout_store() {
+        let mut layout = LayoutStore::default();
+        layout.set("plot.signals", json!(["RPM"])).unwrap();
+        layout.set("plot.timeWindow", json!(10)).unwrap();
+        layout.set("columns.trace", json!(["id", "data"])).unwrap();
+        assert!(layout.set("", json!(1)).is_err());
+        assert!(layout.set("big", json!("x".repeat(MAX_LAYOUT_VALUE_BYTES))).is_err());
+
+        assert_eq!(layout.entries(Some("plot.")).len(), 2);
+        assert_eq!(layout.remove("plot.timeWindow"), Some(json!(10)));
+        assert_eq!(layout.entries(None).len(), 2);
+
+        // Saved with the project and left out of projects without settings
+        let mut project = project(&["can0"], json!([]), json!([]));
+        assert!(!serde_json::to_string(&project).unwrap().contains("layout"));
+        project.layout = layout;
+        let reloaded: ProjectFile = serde_json::from_str(&serde_json::to_string(&project).unwrap()).unwrap();
+        assert_eq!(reloaded.layout.get("columns.trace"), Some(&json!(["id", "data"])));
+    }
+
+    #[test]
+    fn test_dbc_references() {
+        assert_eq!(relative_path(Path::new("/work/proj/dbc/bus.dbc"), Path::new("/work/proj")).as_deref(), Some("dbc/bus.dbc"));
+        assert_eq!(relative_path(Path::new("/other/bus.dbc"), Path::new("/work/proj")), None);
+
+        let saved = "BO_ 256 Engine: 8 ECU\n";
+        let mut channel = project(&["can0"], json!([]), json!([])).channels.remove(0);
+        channel.dbc_file = Some("/work/bus.dbc".to_string());
+        channel.dbc_hash = Some(content_hash(saved));
+        channel.dbc_content = Some(saved.to_string());
+
+        // Unchanged file: the file is loaded and the embedded copy dropped
+        assert!(channel.clone().check_dbc(Some(saved)).is_none());
+        let conflict = channel.clone().check_dbc(Some("BO_ 512 Brake: 8 ECU\n")).unwrap();
+        assert_eq!((conflict.kind, conflict.used_embedded), (DbcConflictKind::Modified, true));
+
+        channel.dbc_content = None;
+        let conflict = channel.check_dbc(None).unwrap();
+        assert_eq!((conflict.kind, conflict.used_embedded), (DbcConflictKind::Missing, false));
+        assert_eq!(channel.dbc_file, None);
+    }
+
+    #[test]
+    fn test_prepare_rejects_invalid_projects() {
+        assert!(PreparedProject::prepare(&project(&["can0", "can0"], json!([]), json!([]))).is_err());
+        assert!(parse(&["can0"], json!([{ "data": { "type": "signal" } }]), json!([])).is_err());
+        let empty_range = json!([{ "name": "A", "filter": { "rules": [{ "IdRange": { "min": 2, "max": 1 } }] } }]);
+        assert!(PreparedProject::prepare(&project(&["can0"], empty_range, json!([]))).is_err());
+        assert!(PreparedProject::prepare(&project(&["can0"], json!([]), json!([job("a", Some("can9"))]))).is_err());
+        assert!(PreparedProject::prepare(&project(&[], json!([]), json!([job("a", None)]))).is_err());
+
+        let mut missing_dbc = project(&["can0"], json!([]), json!([]));
+        missing_dbc.channels[0].dbc_file = Some("/nonexistent/bus.dbc".to_string());
+        assert!(PreparedProject::prepare(&missing_dbc).is_err());
+    }
+}
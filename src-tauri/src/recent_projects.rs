@@ -0,0 +1,195 @@
+//! Recently opened projects
+//!
+//! Every project saved or loaded is recorded with a short summary in
+//! `recent_projects.json` in the app config directory. Pinned entries are
+//! kept; the others are limited to the most recent `MAX_RECENT`.
+
+use crate::error::BootCanError;
+use crate::project::ProjectFile;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Unpinned entries kept
+const MAX_RECENT: usize = 20;
+
+const RECENT_FILE: &str = "recent_projects.json";
+
+/// What a project contains, shown next to its path
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSummary {
+    pub channels: usize,
+    pub dbc_files: usize,
+    pub filters: usize,
+    pub transmit_jobs: usize,
+}
+
+impl From<&ProjectFile> for ProjectSummary {
+    fn from(project: &ProjectFile) -> Self {
+        Self {
+            channels: project.channels.len(),
+            dbc_files: project.channels.iter().filter(|ch| ch.dbc_file.is_some()).count(),
+            filters: project.filters.len(),
+            transmit_jobs: project.transmit_jobs.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentProject {
+    pub path: String,
+    pub name: String,
+    /// Last load or save in ms since the Unix epoch
+    pub last_opened: u64,
+    #[serde(default)]
+    pub pinned: bool,
+    pub summary: ProjectSummary,
+}
+
+/// Recent projects and the startup behaviour, as stored and returned by `get_recent_projects`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentProjects {
+    /// Open the most recent project on startup
+    #[serde(default)]
+    pub reopen_last: bool,
+    /// Pinned projects first, then the most recently opened
+    #[serde(default)]
+    pub projects: Vec<RecentProject>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl RecentProjects {
+    /// Read the list from `dir`; an unreadable list starts empty
+    pub fn open(dir: &Path) -> Self {
+        let file = dir.join(RECENT_FILE);
+        let mut recent: Self = fs::read_to_string(&file)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        recent.file = Some(file);
+        recent
+    }
+
+    /// Move a project to the top of the list
+    pub fn record(&mut self, path: &str, project: &ProjectFile) {
+        let pinned = self.projects.iter().any(|p| p.path == path && p.pinned);
+        self.projects.retain(|p| p.path != path);
+        // Newest first, so entries recorded within the same millisecond keep their order
+        self.projects.insert(0, RecentProject {
+            path: path.to_string(),
+            name: Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string()),
+            last_opened: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            pinned,
+            summary: project.into(),
+        });
+        self.sort();
+    }
+
+    pub fn set_pinned(&mut self, path: &str, pinned: bool) -> Result<(), BootCanError> {
+        let entry = self
+            .projects
+            .iter_mut()
+            .find(|p| p.path == path)
+            .ok_or_else(|| BootCanError::NotFound(format!("{} is not a recent project", path)))?;
+        entry.pinned = pinned;
+        self.sort();
+        Ok(())
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.projects.retain(|p| p.path != path);
+    }
+
+    /// Most recently opened project that still exists
+    pub fn last(&self) -> Option<&RecentProject> {
+        self.projects
+            .iter()
+            .filter(|p| Path::new(&p.path).exists())
+            .min_by_key(|p| Reverse(p.last_opened))
+    }
+
+    fn sort(&mut self) {
+        self.projects
+            .sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened.cmp(&a.last_opened)));
+        let mut unpinned = 0;
+        self.projects.retain(|p| {
+            unpinned += usize::from(!p.pinned);
+            p.pinned || unpinned <= MAX_RECENT
+        });
+    }
+
+    /// Write the list back to the config directory
+    pub fn save(&self) -> Result<(), BootCanError> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| BootCanError::Other(format!("Failed to serialize recent projects: {}", e)))?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", dir.display(), e)))?;
+        }
+        fs::write(file, json).map_err(|e| BootCanError::Io(format!("Failed to write recent projects: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(channels: usize) -> ProjectFile {
+        serde_json::from_value(serde_json::json!({
+            "version": "1.1",
+            "channels": (0..channels)
+                .map(|i| serde_json::json!({ "id": format!("can{}", i), "name": "", "interfaceId": null, "bitrate": 500000, "dbcFile": "bus.dbc" }))
+                .collect::<Vec<_>>(),
+            "filters": [],
+            "transmitJobs": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_and_pin() {
+        let mut recent = RecentProjects::default();
+        recent.record("/p/a.bootcan", &project(1));
+        recent.record("/p/b.bootcan", &project(2));
+        recent.set_pinned("/p/a.bootcan", true).unwrap();
+        recent.record("/p/c.bootcan", &project(0));
+
+        let paths: Vec<_> = recent.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(paths, vec!["a", "c", "b"]);
+        assert_eq!(recent.projects[2].summary, ProjectSummary { channels: 2, dbc_files: 2, filters: 0, transmit_jobs: 0 });
+
+        // Reopening keeps the pin
+        recent.record("/p/a.bootcan", &project(3));
+        assert!(recent.projects[0].pinned);
+        assert!(recent.set_pinned("/p/x.bootcan", true).is_err());
+        recent.remove("/p/b.bootcan");
+        assert_eq!(recent.projects.len(), 2);
+    }
+
+    #[test]
+    fn test_unpinned_entries_are_limited() {
+        let mut recent = RecentProjects::default();
+        recent.record("/p/pinned.bootcan", &project(0));
+        recent.set_pinned("/p/pinned.bootcan", true).unwrap();
+        for i in 0..MAX_RECENT + 5 {
+            recent.record(&format!("/p/{}.bootcan", i), &project(0));
+        }
+        assert_eq!(recent.projects.len(), MAX_RECENT + 1);
+        assert_eq!(recent.projects[0].name, "pinned");
+    }
+}
@@ -0,0 +1,383 @@
+//! REST API for headless operation (CI rigs, scripts)
+//!
+//! JSON over HTTP on top of the same commands the UI uses. Every request must
+//! carry the API key as `Authorization: Bearer <key>` or `X-Api-Key: <key>`.
+//!
+//! | method | path                             | body / result                           |
+//! |--------|----------------------------------|-----------------------------------------|
+//! | GET    | /api/interfaces                  | available interfaces                    |
+//! | GET    | /api/channels                    | channels with connection state          |
+//! | POST   | /api/channels/{id}/connect       | `{interfaceId, bitrate?}`               |
+//! | POST   | /api/channels/{id}/disconnect    |                                         |
+//! | POST   | /api/channels/{id}/send          | `{id, isExtended?, isRemote?, data}`    |
+//! | GET    | /api/channels/{id}/stats         | bus statistics                          |
+//! | POST   | /api/logging/start               | `{filePath, format?}` (csv or trc)      |
+//! | POST   | /api/logging/stop                |                                         |
+//! | POST   | /api/flash                       | `{channelId, filePath, baseAddress?, target}` → `{jobId}` |
+//! | GET    | /api/flash/{jobId}               | latest flash progress                   |
+//! | DELETE | /api/flash/{jobId}               | cancel the job                          |
+//!
+//! Errors are answered with a 4xx or 5xx status by error kind and
+//! `{"error": "...", "kind": "..."}`.
+
+use crate::commands;
+use crate::core::bootloader::BootloaderTarget;
+use crate::core::message::{dlc_for_len, FramePayload};
+use crate::error::BootCanError;
+use crate::AppState;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+/// Port used when none is given
+pub const DEFAULT_REST_PORT: u16 = 47802;
+/// Address used when none is given; other hosts are only served on request
+pub const DEFAULT_REST_BIND: &str = "127.0.0.1";
+
+/// Running server, kept in the application state
+pub struct RestServerHandle {
+    pub port: u16,
+    pub cancel: watch::Sender<bool>,
+}
+
+/// Where the server listens and the key clients must send
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestServerInfo {
+    pub port: u16,
+    pub api_key: String,
+}
+
+struct ApiError(StatusCode, BootCanError);
+
+impl From<BootCanError> for ApiError {
+    fn from(error: BootCanError) -> Self {
+        let status = match error {
+            BootCanError::NotFound(_) => StatusCode::NOT_FOUND,
+            BootCanError::Busy(_) | BootCanError::NotConnected(_) => StatusCode::CONFLICT,
+            BootCanError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            BootCanError::Hal(_) | BootCanError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        Self(status, error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1.message(), "kind": self.1.kind() }))).into_response()
+    }
+}
+
+type ApiResult = Result<Json<Value>, ApiError>;
+
+fn to_json<T: Serialize>(value: T) -> ApiResult {
+    serde_json::to_value(value)
+        .map(Json)
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, BootCanError::Other(e.to_string())))
+}
+
+#[derive(Clone)]
+struct RestContext {
+    app: AppHandle,
+    api_key: Arc<String>,
+}
+
+/// Key from the `X-Api-Key` or bearer `Authorization` header
+fn provided_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get("x-api-key") {
+        return key.to_str().ok();
+    }
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Compare without leaking the position of the first difference
+fn keys_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn require_api_key(State(ctx): State<RestContext>, request: Request, next: Next) -> Response {
+    match provided_key(request.headers()) {
+        Some(key) if keys_match(key, &ctx.api_key) => next.run(request).await,
+        _ => {
+            let error = BootCanError::InvalidInput("Missing or invalid API key".to_string());
+            ApiError(StatusCode::UNAUTHORIZED, error).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectBody {
+    interface_id: String,
+    /// Default bitrate of the settings when not given
+    #[serde(default)]
+    bitrate: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendBody {
+    id: u32,
+    #[serde(default)]
+    is_extended: bool,
+    #[serde(default)]
+    is_remote: bool,
+    #[serde(default)]
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoggingBody {
+    file_path: String,
+    /// Default log format of the settings when not given
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashBody {
+    channel_id: String,
+    file_path: String,
+    #[serde(default)]
+    base_address: Option<u32>,
+    target: BootloaderTarget,
+}
+
+async fn interfaces() -> ApiResult {
+    to_json(commands::get_interfaces().await?)
+}
+
+async fn channels(State(ctx): State<RestContext>) -> ApiResult {
+    let state = ctx.app.state::<AppState>();
+    let handles: Vec<_> = {
+        let manager = state.channel_manager.read();
+        manager
+            .get_channel_ids()
+            .into_iter()
+            .filter_map(|id| manager.get_channel(&id))
+            .collect()
+    };
+    let mut channels: Vec<Value> = Vec::new();
+    for channel in handles {
+        let ch = channel.snapshot().await?;
+        channels.push(json!({
+            "id": ch.id,
+            "interfaceId": ch.config.interface_id,
+            "bitrate": ch.config.bitrate,
+            "connected": ch.is_connected(),
+        }));
+    }
+    to_json(channels)
+}
+
+async fn connect(
+    State(ctx): State<RestContext>,
+    Path(channel_id): Path<String>,
+    Json(body): Json<ConnectBody>,
+) -> ApiResult {
+    let app = ctx.app.clone();
+    commands::connect_channel(app.state::<AppState>(), app.clone(), channel_id, body.interface_id, body.bitrate)
+        .await?;
+    to_json(Value::Null)
+}
+
+async fn disconnect(State(ctx): State<RestContext>, Path(channel_id): Path<String>) -> ApiResult {
+    commands::disconnect_channel(ctx.app.state::<AppState>(), channel_id).await?;
+    to_json(Value::Null)
+}
+
+async fn send(State(ctx): State<RestContext>, Path(channel_id): Path<String>, Json(body): Json<SendBody>) -> ApiResult {
+    let frame = FramePayload {
+        id: body.id,
+        is_extended: body.is_extended || body.id > 0x7FF,
+        is_remote: body.is_remote,
+        dlc: dlc_for_len(body.data.len())?,
+        data: body.data,
+        channel: Some(channel_id),
+    };
+    let app = ctx.app.clone();
+    commands::send_message(app.state::<AppState>(), app.clone(), frame).await?;
+    to_json(Value::Null)
+}
+
+async fn stats(State(ctx): State<RestContext>, Path(channel_id): Path<String>) -> ApiResult {
+    let channel = ctx
+        .app
+        .state::<AppState>()
+        .channel_manager
+        .read()
+        .get_channel(&channel_id)
+        .ok_or_else(|| BootCanError::NotFound(format!("Channel {} not found", channel_id)))?;
+    let stats = channel.snapshot().await?.stats;
+    to_json(stats)
+}
+
+async fn start_logging(State(ctx): State<RestContext>, Json(body): Json<LoggingBody>) -> ApiResult {
+    let app = ctx.app.clone();
+    commands::start_logging(app.state::<AppState>(), app.clone(), body.file_path, body.format).await?;
+    to_json(Value::Null)
+}
+
+async fn stop_logging(State(ctx): State<RestContext>) -> ApiResult {
+    commands::stop_logging(ctx.app.state::<AppState>()).await?;
+    to_json(Value::Null)
+}
+
+async fn start_flash(State(ctx): State<RestContext>, Json(body): Json<FlashBody>) -> ApiResult {
+    let app = ctx.app.clone();
+    let job_id = commands::start_flash(
+        app.state::<AppState>(),
+        app.clone(),
+        body.channel_id,
+        body.file_path,
+        body.base_address,
+        body.target,
+    )
+    .await?;
+    to_json(json!({ "jobId": job_id }))
+}
+
+async fn flash_progress(State(ctx): State<RestContext>, Path(job_id): Path<String>) -> ApiResult {
+    let progress = ctx.app.state::<AppState>().flash_progress.read().get(&job_id).cloned();
+    match progress {
+        Some(progress) => to_json(progress),
+        None => Err(BootCanError::NotFound(format!("Flash job {} not found", job_id)).into()),
+    }
+}
+
+async fn cancel_flash(State(ctx): State<RestContext>, Path(job_id): Path<String>) -> ApiResult {
+    commands::cancel_flash(ctx.app.state::<AppState>(), job_id).await?;
+    to_json(Value::Null)
+}
+
+fn router(ctx: RestContext) -> Router {
+    Router::new()
+        .route("/api/interfaces", get(interfaces))
+        .route("/api/channels", get(channels))
+        .route("/api/channels/{id}/connect", post(connect))
+        .route("/api/channels/{id}/disconnect", post(disconnect))
+        .route("/api/channels/{id}/send", post(send))
+        .route("/api/channels/{id}/stats", get(stats))
+        .route("/api/logging/start", post(start_logging))
+        .route("/api/logging/stop", post(stop_logging))
+        .route("/api/flash", post(start_flash))
+        .route("/api/flash/{job_id}", get(flash_progress).delete(cancel_flash))
+        .layer(middleware::from_fn_with_state(ctx.clone(), require_api_key))
+        .with_state(ctx)
+}
+
+/// Bind and start serving; a random API key is generated when none is given
+pub async fn start(
+    app: AppHandle,
+    bind_address: &str,
+    port: u16,
+    api_key: Option<String>,
+) -> Result<RestServerInfo, BootCanError> {
+    let state = app.state::<AppState>();
+    if let Some(server) = state.rest_server.read().as_ref() {
+        return Err(BootCanError::Busy(format!("REST server already running on port {}", server.port)));
+    }
+    let api_key = match api_key {
+        Some(key) if key.trim().is_empty() => {
+            return Err(BootCanError::InvalidInput("The API key must not be empty".to_string()))
+        }
+        Some(key) => key,
+        None => uuid::Uuid::new_v4().simple().to_string(),
+    };
+
+    let listener = TcpListener::bind((bind_address, port))
+        .await
+        .map_err(|e| BootCanError::Io(format!("Failed to bind REST server: {}", e)))?;
+    let port = listener.local_addr()?.port();
+
+    let (cancel_tx, mut cancel_rx) = watch::channel(false);
+    *state.rest_server.write() = Some(RestServerHandle { port, cancel: cancel_tx });
+
+    let router = router(RestContext {
+        app: app.clone(),
+        api_key: Arc::new(api_key.clone()),
+    });
+    tokio::spawn(async move {
+        let shutdown = async move {
+            let _ = cancel_rx.changed().await;
+        };
+        if let Err(e) = axum::serve(listener, router).with_graceful_shutdown(shutdown).await {
+            tracing::error!("REST server failed: {}", e);
+        }
+    });
+
+    tracing::info!("REST server listening on {}:{}", bind_address, port);
+    Ok(RestServerInfo { port, api_key })
+}
+
+/// Start the server at launch when `BOOTCAN_REST_API_KEY` is set
+///
+/// `BOOTCAN_REST_PORT` and `BOOTCAN_REST_BIND` override the defaults.
+pub fn autostart(app: &AppHandle) {
+    let Ok(api_key) = std::env::var("BOOTCAN_REST_API_KEY") else {
+        return;
+    };
+    let port = std::env::var("BOOTCAN_REST_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_REST_PORT);
+    let bind_address = std::env::var("BOOTCAN_REST_BIND").unwrap_or_else(|_| DEFAULT_REST_BIND.to_string());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = start(app, &bind_address, port, Some(api_key)).await {
+            tracing::error!("REST server autostart failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_provided_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(provided_key(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer s3cret"));
+        assert_eq!(provided_key(&headers), Some("s3cret"));
+
+        headers.insert("x-api-key", HeaderValue::from_static("other"));
+        assert_eq!(provided_key(&headers), Some("other"));
+
+        let mut basic = HeaderMap::new();
+        basic.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(provided_key(&basic), None);
+    }
+
+    #[test]
+    fn test_keys_match() {
+        assert!(keys_match("s3cret", "s3cret"));
+        assert!(!keys_match("s3creT", "s3cret"));
+        assert!(!keys_match("s3cre", "s3cret"));
+        assert!(!keys_match("", "s3cret"));
+    }
+}
@@ -0,0 +1,342 @@
+//! Local JSON-RPC bridge for external tooling (e.g. the Python client)
+//!
+//! Line-delimited JSON over TCP on the loopback interface. Each request line
+//! `{"id": 1, "method": "send", "params": {...}}` is answered by one line with
+//! the same `id` and either `result` or an `error` of `{"kind", "message"}`.
+//! Frames of subscribed channels are pushed as
+//! `{"method": "frame", "params": <frame>}` notifications. Frames that do not
+//! fit the output queue of a slow client are dropped and reported by a
+//! `{"method": "framesLost", "params": {"channelId", "count"}}` notification.
+
+use crate::commands;
+use crate::core::message::{dlc_for_len, FramePayload};
+use crate::error::BootCanError;
+use crate::AppState;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Port used when none is given
+pub const DEFAULT_RPC_PORT: u16 = 47800;
+/// Lines waiting to be written to one client
+const OUTPUT_CAPACITY: usize = 1024;
+
+/// Running server, kept in the application state
+pub struct RpcServerHandle {
+    pub port: u16,
+    pub cancel: watch::Sender<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcResponse {
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BootCanError>,
+}
+
+impl RpcResponse {
+    pub fn new(id: Value, result: Result<Value, BootCanError>) -> Self {
+        match result {
+            Ok(result) => Self {
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => Self {
+                id,
+                result: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelParams {
+    channel_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameParams {
+    channel_id: String,
+    id: u32,
+    #[serde(default)]
+    is_extended: bool,
+    #[serde(default)]
+    is_remote: bool,
+    #[serde(default)]
+    data: Vec<u8>,
+    /// Only used by startPeriodic
+    #[serde(default)]
+    interval_ms: u64,
+}
+
+impl FrameParams {
+    fn payload(&self) -> Result<FramePayload, BootCanError> {
+        Ok(FramePayload {
+            id: self.id,
+            is_extended: self.is_extended,
+            is_remote: self.is_remote,
+            dlc: dlc_for_len(self.data.len())?,
+            data: self.data.clone(),
+            channel: Some(self.channel_id.clone()),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DecodeParams {
+    channel_id: String,
+    id: u32,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobParams {
+    job_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScenarioParams {
+    file_path: String,
+}
+
+fn params<T: DeserializeOwned>(value: Value) -> Result<T, BootCanError> {
+    serde_json::from_value(value).map_err(|e| BootCanError::InvalidInput(format!("Invalid params: {}", e)))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, BootCanError> {
+    serde_json::to_value(value).map_err(|e| BootCanError::Other(e.to_string()))
+}
+
+/// Per-connection state
+struct Connection {
+    app: AppHandle,
+    out: mpsc::Sender<String>,
+    /// Frame forwarding tasks per subscribed channel
+    subscriptions: HashMap<String, JoinHandle<()>>,
+}
+
+impl Connection {
+    async fn dispatch(&mut self, request: RpcRequest) -> Result<Value, BootCanError> {
+        let app = self.app.clone();
+        let state = app.state::<AppState>();
+        match request.method.as_str() {
+            "listChannels" => to_value(state.channel_manager.read().get_channel_ids()),
+            "send" => {
+                let p: FrameParams = params(request.params)?;
+                commands::send_message(state, app.clone(), p.payload()?).await?;
+                Ok(Value::Null)
+            }
+            "subscribe" => {
+                let p: ChannelParams = params(request.params)?;
+                self.subscribe(p.channel_id)?;
+                Ok(Value::Null)
+            }
+            "unsubscribe" => {
+                let p: ChannelParams = params(request.params)?;
+                if let Some(task) = self.subscriptions.remove(&p.channel_id) {
+                    task.abort();
+                }
+                Ok(Value::Null)
+            }
+            "decode" => {
+                let p: DecodeParams = params(request.params)?;
+                to_value(commands::decode_message(state, p.channel_id, p.id, p.data).await?)
+            }
+            "startPeriodic" => {
+                let p: FrameParams = params(request.params)?;
+                if p.interval_ms == 0 {
+                    return Err(BootCanError::InvalidInput("intervalMs must be positive".to_string()));
+                }
+                let job_id =
+                    commands::start_periodic_transmit(state, app.clone(), p.payload()?, p.interval_ms).await?;
+                to_value(job_id)
+            }
+            "stopPeriodic" => {
+                let p: JobParams = params(request.params)?;
+                commands::stop_periodic_transmit(state, p.job_id).await?;
+                Ok(Value::Null)
+            }
+            "runScenario" => {
+                let p: ScenarioParams = params(request.params)?;
+                to_value(commands::run_scenario_file(state, app.clone(), p.file_path).await?)
+            }
+            other => Err(BootCanError::NotFound(format!("Unknown method {}", other))),
+        }
+    }
+
+    /// Forward every frame of a channel to the client as notifications, dropping what the client cannot take
+    fn subscribe(&mut self, channel_id: String) -> Result<(), BootCanError> {
+        let channel = self
+            .app
+            .state::<AppState>()
+            .channel_manager
+            .read()
+            .get_channel(&channel_id)
+            .ok_or_else(|| BootCanError::NotFound(format!("Channel {} not found", channel_id)))?;
+        let mut rx = channel.subscribe("RPC client");
+        let out = self.out.clone();
+
+        let name = channel_id.clone();
+        let task = tokio::spawn(async move {
+            let mut lost = 0u64;
+            while let Some(frame) = rx.recv().await {
+                if lost > 0 {
+                    let params = serde_json::json!({ "channelId": name, "count": lost });
+                    match out.try_send(serde_json::json!({ "method": "framesLost", "params": params }).to_string()) {
+                        Ok(()) => lost = 0,
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            lost += 1;
+                            continue;
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => break,
+                    }
+                }
+                let notification = serde_json::json!({ "method": "frame", "params": frame });
+                match out.try_send(notification.to_string()) {
+                    Ok(()) => {}
+                    Err(mpsc::error::TrySendError::Full(_)) => lost += 1,
+                    Err(mpsc::error::TrySendError::Closed(_)) => break,
+                }
+            }
+        });
+        if let Some(previous) = self.subscriptions.insert(channel_id, task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+}
+
+async fn handle_connection(stream: TcpStream, app: AppHandle, mut cancel: watch::Receiver<bool>) {
+    let (reader, mut writer) = stream.into_split();
+    let (out, mut out_rx) = mpsc::channel::<String>(OUTPUT_CAPACITY);
+    tokio::spawn(async move {
+        while let Some(mut line) = out_rx.recv().await {
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut connection = Connection {
+        app,
+        out,
+        subscriptions: HashMap::new(),
+    };
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line,
+            _ = cancel.changed() => break,
+        };
+        let line = match line {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!("RPC connection error: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<RpcRequest>(&line) {
+            Ok(request) => {
+                let id = request.id.clone();
+                RpcResponse::new(id, connection.dispatch(request).await)
+            }
+            Err(e) => RpcResponse::new(Value::Null, Err(BootCanError::Parse(format!("Invalid request: {}", e)))),
+        };
+        // Responses are never dropped; a client that stops reading holds up its own requests
+        if let Ok(line) = serde_json::to_string(&response) {
+            if connection.out.send(line).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    for (_, task) in connection.subscriptions.drain() {
+        task.abort();
+    }
+}
+
+/// Accept connections until cancelled
+///
+/// Requests on one connection are handled in order, so a long call such as
+/// `runScenario` delays the responses that follow it.
+pub async fn serve(listener: TcpListener, app: AppHandle, mut cancel: watch::Receiver<bool>) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    tracing::info!("RPC client connected from {}", peer);
+                    tokio::spawn(handle_connection(stream, app.clone(), cancel.clone()));
+                }
+                Err(e) => tracing::warn!("RPC accept failed: {}", e),
+            },
+            _ = cancel.changed() => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request: RpcRequest = serde_json::from_str(
+            r#"{"id": 7, "method": "send", "params": {"channelId": "can0", "id": 291, "data": [1, 2]}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.method, "send");
+
+        let frame: FrameParams = params(request.params).unwrap();
+        let payload = frame.payload().unwrap();
+        assert_eq!(payload.id, 0x123);
+        assert_eq!(payload.dlc, 2);
+        assert_eq!(payload.channel.as_deref(), Some("can0"));
+
+        assert!(params::<FrameParams>(serde_json::json!({ "id": 1 })).is_err());
+        let data = [0u8; 9];
+        let long: FrameParams = params(serde_json::json!({ "channelId": "can0", "id": 1, "data": data })).unwrap();
+        assert_eq!(long.payload().unwrap_err().kind(), "invalidInput");
+    }
+
+    #[test]
+    fn test_response_encoding() {
+        let ok = RpcResponse::new(Value::from(1), Ok(Value::Null));
+        assert_eq!(serde_json::to_string(&ok).unwrap(), r#"{"id":1,"result":null}"#);
+
+        let err = RpcResponse::new(Value::from("a"), Err(BootCanError::NotFound("Unknown method x".to_string())));
+        assert_eq!(
+            serde_json::to_string(&err).unwrap(),
+            r#"{"id":"a","error":{"kind":"notFound","message":"Unknown method x"}}"#
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Application settings
+//!
+//! Defaults that apply across projects, kept in `settings.json` in the app
+//! config directory. Missing or unreadable settings fall back to the built-in
+//! defaults.
+
+use crate::core::bus_stats::StatsEmission;
+use crate::core::subscriber::DEFAULT_BROADCAST_CAPACITY;
+use crate::core::trace_logger::TraceFormat;
+use crate::error::BootCanError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Longest accepted event batching window
+const MAX_EVENT_BATCH_MS: u64 = 1000;
+/// Accepted range of the per-channel broadcast capacity
+const BROADCAST_CAPACITY_RANGE: std::ops::RangeInclusive<usize> = 16..=1_000_000;
+
+/// How received frames reach the frontend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameDelivery {
+    /// Emitted as `can-message` or `can-messages` events
+    #[default]
+    Events,
+    /// Kept in the backend frame store and polled with `get_frame_delta`
+    Store,
+}
+
+/// Settings as stored and returned by `get_settings`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppSettings {
+    /// Bitrates offered in the connection dialogs
+    pub preferred_bitrates: Vec<u32>,
+    /// Bitrate used when a command does not name one
+    pub default_bitrate: u32,
+    /// Directory of trace logs started with a relative file name
+    pub log_directory: Option<String>,
+    /// Format of trace logs started without one
+    pub log_format: TraceFormat,
+    /// Interval at which received frames are emitted as one batch; 0 emits every frame
+    pub event_batch_ms: u64,
+    /// Whether received frames are emitted or kept for polling
+    pub frame_delivery: FrameDelivery,
+    /// Statistics interval of new channels
+    pub stats_interval_ms: u64,
+    /// Frames a subscriber of a new channel, like the trace logger, can fall
+    /// behind before it loses frames
+    pub broadcast_capacity: usize,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            preferred_bitrates: vec![125_000, 250_000, 500_000, 1_000_000],
+            default_bitrate: 500_000,
+            log_directory: None,
+            log_format: TraceFormat::Csv,
+            event_batch_ms: 16,
+            frame_delivery: FrameDelivery::Events,
+            stats_interval_ms: StatsEmission::default().interval_ms,
+            broadcast_capacity: DEFAULT_BROADCAST_CAPACITY,
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn validate(&self) -> Result<(), BootCanError> {
+        if self.default_bitrate == 0 || self.preferred_bitrates.contains(&0) {
+            return Err(BootCanError::InvalidInput("Bitrates must be greater than 0".to_string()));
+        }
+        if self.event_batch_ms > MAX_EVENT_BATCH_MS {
+            return Err(BootCanError::InvalidInput(format!(
+                "Event batching window must be at most {} ms", MAX_EVENT_BATCH_MS
+            )));
+        }
+        if self.stats_interval_ms < StatsEmission::MIN_INTERVAL_MS {
+            return Err(BootCanError::InvalidInput(format!(
+                "Stats interval must be at least {} ms", StatsEmission::MIN_INTERVAL_MS
+            )));
+        }
+        if !BROADCAST_CAPACITY_RANGE.contains(&self.broadcast_capacity) {
+            return Err(BootCanError::InvalidInput(format!(
+                "Broadcast capacity must be between {} and {} frames",
+                BROADCAST_CAPACITY_RANGE.start(),
+                BROADCAST_CAPACITY_RANGE.end()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Statistics emission of new channels
+    pub fn stats_emission(&self) -> StatsEmission {
+        StatsEmission {
+            interval_ms: self.stats_interval_ms,
+            ..Default::default()
+        }
+    }
+
+    /// Path of a trace log; relative paths are placed in the log directory
+    pub fn log_path(&self, file_path: &str) -> PathBuf {
+        match &self.log_directory {
+            Some(dir) if Path::new(file_path).is_relative() => Path::new(dir).join(file_path),
+            _ => PathBuf::from(file_path),
+        }
+    }
+}
+
+/// Settings of the running application and where they are kept
+#[derive(Debug, Default)]
+pub struct Settings {
+    values: AppSettings,
+    file: Option<PathBuf>,
+}
+
+impl Settings {
+    /// Read the settings from `dir`; unreadable settings start from the defaults
+    pub fn open(dir: &Path) -> Self {
+        let file = dir.join(SETTINGS_FILE);
+        let values = fs::read_to_string(&file)
+            .ok()
+            .and_then(|json| serde_json::from_str::<AppSettings>(&json).ok())
+            .filter(|values| match values.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Ignoring settings in {}: {}", file.display(), e);
+                    false
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            values,
+            file: Some(file),
+        }
+    }
+
+    pub fn values(&self) -> &AppSettings {
+        &self.values
+    }
+
+    /// Replace the settings and write them to the config directory
+    pub fn update(&mut self, values: AppSettings) -> Result<(), BootCanError> {
+        values.validate()?;
+        self.values = values;
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&self.values)
+            .map_err(|e| BootCanError::Other(format!("Failed to serialize settings: {}", e)))?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| BootCanError::Io(format!("Failed to create {}: {}", dir.display(), e)))?;
+        }
+        fs::write(file, json).map_err(|e| BootCanError::Io(format!("Failed to write settings: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_settings_use_defaults() {
+        let values: AppSettings =
+            serde_json::from_str(r#"{"defaultBitrate":250000,"logFormat":"trc","frameDelivery":"store"}"#).unwrap();
+        assert_eq!(values.default_bitrate, 250_000);
+        assert_eq!(values.frame_delivery, FrameDelivery::Store);
+        assert_eq!(values.log_format, TraceFormat::Trc);
+        assert_eq!(values.preferred_bitrates, AppSettings::default().preferred_bitrates);
+
+        let invalid = AppSettings { stats_interval_ms: 1, ..Default::default() };
+        assert!(invalid.validate().is_err());
+        let invalid = AppSettings { preferred_bitrates: vec![0], ..Default::default() };
+        assert!(invalid.validate().is_err());
+        let invalid = AppSettings { broadcast_capacity: 0, ..Default::default() };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_update_persists() {
+        let dir = std::env::temp_dir().join(format!("bootcan-settings-{}", uuid::Uuid::new_v4()));
+        let mut settings = Settings::open(&dir);
+        assert_eq!(settings.values(), &AppSettings::default());
+
+        let values = AppSettings {
+            log_directory: Some("/logs".to_string()),
+            ..Default::default()
+        };
+        settings.update(values.clone()).unwrap();
+        assert!(settings.update(AppSettings { event_batch_ms: 5000, ..Default::default() }).is_err());
+        assert_eq!(Settings::open(&dir).values(), &values);
+        assert_eq!(values.log_path("run.csv"), Path::new("/logs/run.csv"));
+        assert_eq!(values.log_path("/tmp/run.csv"), Path::new("/tmp/run.csv"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
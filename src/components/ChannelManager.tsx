@@ -28,9 +28,10 @@ export function ChannelManager() {
       const filePath = await open({
         title: "Load DBC/SYM File",
         filters: [
-          { name: "DBC/SYM Files", extensions: ["dbc", "sym"] },
+          { name: "DBC/SYM/LDF Files", extensions: ["dbc", "sym", "ldf"] },
           { name: "DBC Files", extensions: ["dbc"] },
           { name: "SYM Files", extensions: ["sym"] },
+          { name: "LIN Description Files", extensions: ["ldf"] },
         ],
         multiple: false,
       });
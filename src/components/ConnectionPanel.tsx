@@ -1,12 +1,8 @@
 import { useCanStore } from "../stores/canStore";
 import { SignalIcon } from "./icons";
+import { bitrateLabel } from "../types/settings";
 
-const BITRATE_OPTIONS = [
-  { value: 125000, label: "125 kbit/s" },
-  { value: 250000, label: "250 kbit/s" },
-  { value: 500000, label: "500 kbit/s" },
-  { value: 1000000, label: "1 Mbit/s" },
-];
+const FALLBACK_BITRATES = [125000, 250000, 500000, 1000000];
 
 export function ConnectionPanel() {
   const {
@@ -18,7 +14,9 @@ export function ConnectionPanel() {
     disconnect,
     setSelectedInterface,
     setSelectedBitrate,
+    settings,
   } = useCanStore();
+  const bitrates = settings?.preferredBitrates ?? FALLBACK_BITRATES;
 
   const isConnected = connectionStatus === "connected";
   const isConnecting = connectionStatus === "connecting";
@@ -62,9 +60,9 @@ export function ConnectionPanel() {
             onChange={(e) => setSelectedBitrate(Number(e.target.value))}
             disabled={isConnected || isConnecting}
           >
-            {BITRATE_OPTIONS.map((option) => (
-              <option key={option.value} value={option.value}>
-                {option.label}
+            {bitrates.map((bitrate) => (
+              <option key={bitrate} value={bitrate}>
+                {bitrateLabel(bitrate)}
               </option>
             ))}
           </select>
@@ -1,24 +1,10 @@
-import { useState } from "react";
 import { useCanStore } from "../stores/canStore";
 import { invoke } from "@tauri-apps/api/core";
 import { PlusIcon, XMarkIcon } from "./icons";
-
-interface FilterRule {
-  type: "idRange" | "idExact" | "dlcRange" | "direction" | "extendedId" | "remoteFrame";
-  idMin?: number;
-  idMax?: number;
-  idExact?: number;
-  dlcMin?: number;
-  dlcMax?: number;
-  rx?: boolean;
-  tx?: boolean;
-  extended?: boolean;
-  remote?: boolean;
-}
+import { FilterRule, toBackendFilterSet } from "../types/filter";
 
 export function FilterPanel() {
-  const { activeChannel, filters, setFilters } = useCanStore();
-  const [logic, setLogic] = useState<"and" | "or">("and");
+  const { activeChannel, filters, setFilters, filterLogic: logic, setFilterLogic: setLogic } = useCanStore();
 
   const addFilter = () => {
     const newFilter: FilterRule = {
@@ -43,36 +29,9 @@ export function FilterPanel() {
     if (!activeChannel) return;
 
     try {
-      // Convert frontend filters to backend format
-      const backendFilters: any[] = filters.map((f) => {
-        switch (f.type) {
-          case "idRange":
-            return {
-              IdRange: { min: f.idMin || 0, max: f.idMax || 0x7FF },
-            };
-          case "idExact":
-            return { IdExact: f.idExact || 0 };
-          case "dlcRange":
-            return {
-              DlcRange: { min: f.dlcMin || 0, max: f.dlcMax || 8 },
-            };
-          case "direction":
-            return { Direction: { rx: f.rx || false, tx: f.tx || false } };
-          case "extendedId":
-            return { ExtendedId: f.extended || false };
-          case "remoteFrame":
-            return { RemoteFrame: f.remote || false };
-          default:
-            return null;
-        }
-      }).filter((f) => f !== null);
-
       await invoke("set_advanced_filter", {
         channelId: activeChannel,
-        filter: {
-          rules: backendFilters,
-          logic: logic === "and" ? "And" : "Or",
-        },
+        filter: toBackendFilterSet(filters, logic),
       });
     } catch (error) {
       console.error("Failed to apply filters:", error);
@@ -4,6 +4,7 @@ import "uplot/dist/uPlot.min.css";
 import { useCanStore, CanFrame } from "../stores/canStore";
 import { SignalSelector } from "./SignalSelector";
 import { PauseIcon, PlayIcon, TrashIcon, XMarkIcon, FolderOpenIcon } from "./icons";
+import { errorMessage } from "../types/error";
 import { open } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
@@ -55,9 +56,10 @@ export function PlotPanel() {
       const filePath = await open({
         title: "Import Trace File",
         filters: [
-          { name: "Trace Files", extensions: ["csv", "trc"] },
+          { name: "Trace Files", extensions: ["csv", "trc", "pcap", "pcapng"] },
           { name: "CSV", extensions: ["csv"] },
           { name: "TRC", extensions: ["trc"] },
+          { name: "Wireshark Capture", extensions: ["pcap", "pcapng"] },
         ],
         multiple: false,
       });
@@ -283,7 +285,7 @@ export function PlotPanel() {
         // Playback would cause real-time updates which is slow for large traces
         } catch (error) {
           console.error("Failed to load trace file:", error);
-          alert(`Failed to load trace file: ${error}`);
+          alert(`Failed to load trace file: ${errorMessage(error)}`);
         }
       }
     } catch (error) {
@@ -35,12 +35,13 @@ export function TraceManager() {
         filters: [
           { name: "CSV", extensions: ["csv"] },
           { name: "TRC", extensions: ["trc"] },
+          { name: "ASC", extensions: ["asc"] },
         ],
         defaultPath: `can_trace_${new Date().toISOString().replace(/[:.]/g, "-")}.csv`,
       });
 
       if (filePath) {
-        const format = filePath.endsWith(".trc") ? "trc" : "csv";
+        const format = filePath.endsWith(".trc") ? "trc" : filePath.endsWith(".asc") ? "asc" : "csv";
         await startLogging(filePath, format);
       }
     } catch (error) {
@@ -61,9 +62,10 @@ export function TraceManager() {
       const filePath = await open({
         title: "Load Trace File",
         filters: [
-          { name: "Trace Files", extensions: ["csv", "trc"] },
+          { name: "Trace Files", extensions: ["csv", "trc", "pcap", "pcapng"] },
           { name: "CSV", extensions: ["csv"] },
           { name: "TRC", extensions: ["trc"] },
+          { name: "Wireshark Capture", extensions: ["pcap", "pcapng"] },
         ],
         multiple: false,
       });
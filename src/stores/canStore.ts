@@ -1,6 +1,8 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { BackendFilterSet, FilterLogic, FilterRule, fromBackendRule, toBackendFilterSet } from "../types/filter";
+import { AppSettings } from "../types/settings";
 
 // CAN Message Types
 export interface CanFrame {
@@ -12,6 +14,22 @@ export interface CanFrame {
   timestamp: number;
   channel: string;
   direction: "rx" | "tx";
+  bus?: "lin"; // Absent for CAN frames
+}
+
+// Received frames of one channel, emitted together as `can-messages`
+export interface FrameBatch {
+  channelId: string;
+  seq: number; // Sequence number of the first frame; a jump means frames were dropped
+  frames: CanFrame[];
+}
+
+// Changes in the backend frame store since the last poll (get_frame_delta)
+export interface FrameDelta {
+  nextSeq: number;
+  missed: number; // Frames since the last poll that were not returned
+  frames: CanFrame[];
+  latest: Array<{ frame: CanFrame; count: number; cycleTimeMs: number }>;
 }
 
 // Extended frame info for monitor view
@@ -22,13 +40,31 @@ export interface MonitorEntry {
   lastTimestamp: number;
 }
 
+export type ControllerState = "active" | "warning" | "passive" | "busOff" | "unknown";
+
+export interface StateTransition {
+  from: ControllerState;
+  to: ControllerState;
+  timestamp: number;
+}
+
 export interface BusStats {
   busLoad: number;
   txCount: number;
   rxCount: number;
   errorCount: number;
+  txBytes: number;
+  rxBytes: number;
   txErrorCounter: number;
   rxErrorCounter: number;
+  controllerState: ControllerState;
+  stateTransitions: number;
+  lastTransition: StateTransition | null;
+  msgRate: number;
+  firstMsgTime: number | null;
+  lastMsgTime: number | null;
+  uniqueIds: number;
+  droppedFrames: number; // Frames subscribers lost by falling behind
 }
 
 export interface ChannelBusStats {
@@ -39,7 +75,7 @@ export interface ChannelBusStats {
 export interface InterfaceInfo {
   id: string;
   name: string;
-  type: "socketcan" | "pcan" | "virtual";
+  type: "socketcan" | "pcan" | "virtual" | "lin";
   available: boolean;
 }
 
@@ -87,10 +123,8 @@ interface CanState {
   activeChannel: string | null;
   
   // Filter state
-  filters: Array<{
-    type: string;
-    [key: string]: any;
-  }>;
+  filters: FilterRule[];
+  filterLogic: FilterLogic;
 
   // Message buffers - trace collects all, monitor shows latest per ID with metadata
   traceMessages: CanFrame[];
@@ -105,7 +139,10 @@ interface CanState {
   // Trace logging state
   isLogging: boolean;
   logFilePath: string | null;
-  logFormat: "csv" | "trc";
+  logFormat: "csv" | "trc" | "asc";
+
+  // Application settings, loaded on initialization
+  settings: AppSettings | null;
   
   // Trace playback state
   playbackState: "stopped" | "playing" | "paused";
@@ -163,7 +200,7 @@ interface CanState {
   stopRecording: () => void;
   
   // Trace logging actions
-  startLogging: (filePath: string, format: "csv" | "trc") => Promise<void>;
+  startLogging: (filePath: string, format: "csv" | "trc" | "asc") => Promise<void>;
   stopLogging: () => Promise<void>;
   
   // Trace playback actions
@@ -188,11 +225,16 @@ interface CanState {
   disconnectChannel: (id: string) => Promise<void>;
   
   // Filter actions
-  setFilters: (filters: Array<{ type: string; [key: string]: any }>) => void;
+  setFilters: (filters: FilterRule[]) => void;
+  setFilterLogic: (logic: FilterLogic) => void;
+
+  // Settings actions
+  updateSettings: (settings: AppSettings) => Promise<void>;
   
   // Project file actions
   saveProject: (filePath: string) => Promise<void>;
   loadProject: (filePath: string) => Promise<void>;
+  restoreProject: (project: ProjectData) => Promise<void>;
   
   // Plot actions
   setViewTab: (tab: "monitor" | "plot") => void;
@@ -204,9 +246,77 @@ interface CanState {
   setPlotData: (data: Map<string, PlotDataPoint[]>) => void;
 }
 
+// Project file contents as returned by load_project and the autosave
+type ProjectData = {
+  version: string;
+  channels: Array<{
+    id: string;
+    name: string;
+    interfaceId: string | null;
+    bitrate: number;
+    dbcFile: string | null;
+  }>;
+  filters: Array<{ name: string; channelId: string | null; enabled: boolean; filter: BackendFilterSet }>;
+  transmitJobs: Array<{
+    id: string;
+    frame: {
+      id: number;
+      isExtended: boolean;
+      isRemote: boolean;
+      dlc: number;
+      data: number[];
+      channel?: string;
+    };
+    intervalMs: number;
+    enabled: boolean;
+  }>;
+};
+
+// Project file contents for the current configuration (used by save and autosave)
+function buildProject(state: CanState) {
+  // Use loadedDbcFiles Map to get the actual DBC file path
+  const channels = state.channels.map(ch => ({
+    id: ch.id,
+    name: ch.name,
+    interfaceId: ch.interfaceId,
+    bitrate: ch.bitrate,
+    dbcFile: state.loadedDbcFiles.get(ch.id) || ch.dbcFile || null,
+  }));
+
+  // The filter panel rules are stored as one filter for all channels
+  const filters = state.filters.length > 0
+    ? [{ name: "Filter", channelId: null, enabled: true, filter: toBackendFilterSet(state.filters, state.filterLogic) }]
+    : [];
+
+  // Exclude backendJobId, which is runtime-only
+  const transmitJobs = state.transmitJobs.map(job => ({
+    id: job.id,
+    frame: {
+      id: job.frame.id,
+      isExtended: job.frame.isExtended,
+      isRemote: job.frame.isRemote,
+      dlc: job.frame.dlc,
+      data: job.frame.data,
+      channel: job.frame.channel || undefined,
+    },
+    intervalMs: job.intervalMs,
+    enabled: false, // Always save as disabled
+  }));
+
+  return { version: "1.1", channels, filters, transmitJobs };
+}
+
+const AUTOSAVE_DEBOUNCE_MS = 2000;
+let autosaveTimer: ReturnType<typeof setTimeout> | null = null;
+
 // Event listener cleanup
 let unlistenMessage: UnlistenFn | null = null;
 let unlistenStats: UnlistenFn | null = null;
+let unlistenBatch: UnlistenFn | null = null;
+let frameStorePoll: ReturnType<typeof setInterval> | null = null;
+
+// Interval at which the frame store is polled when the backend keeps the frames
+const FRAME_STORE_POLL_MS = 100;
 let isInitialized = false;
 
 export const useCanStore = create<CanState>((set, get) => ({
@@ -218,6 +328,7 @@ export const useCanStore = create<CanState>((set, get) => ({
   channels: [],
   activeChannel: null,
   filters: [],
+  filterLogic: "and",
   traceMessages: [],
   monitorMessages: new Map<string, MonitorEntry>(),
   maxMessages: 10000,
@@ -227,6 +338,7 @@ export const useCanStore = create<CanState>((set, get) => ({
   isLogging: false,
   logFilePath: null,
   logFormat: "csv",
+  settings: null,
   playbackState: "stopped",
   playbackSpeed: 1.0,
   loadedTraceFile: null,
@@ -280,61 +392,61 @@ export const useCanStore = create<CanState>((set, get) => ({
         set({ selectedInterface: interfaces[0].id });
       }
 
-      // Set up event listeners for incoming messages
-      console.log("Setting up can-message listener...");
-      unlistenMessage = await listen<CanFrame>("can-message", async (event) => {
+      // Apply received frames to the monitor, trace and plots; the monitor is
+      // left alone when it is updated from the frame store instead
+      const handleFrames = (frames: CanFrame[], updateMonitor = true) => {
         const state = get();
-        if (state.isPaused) return;
+        if (state.isPaused || frames.length === 0) return;
 
-        const newFrame = event.payload;
-        
         // Check if this is from trace playback (has loadedTraceFile and playback is active)
         const isTracePlayback = state.loadedTraceFile !== null && 
                                 (state.playbackState === "playing" || state.playbackState === "paused");
-        
-        // Include channel in key so same ID on different channels are separate
-        const monitorKey = `${newFrame.channel}-${newFrame.id}-${newFrame.direction}`;
-        
+        // Trace playback messages are already loaded into traceMessages, and the
+        // monitor and plots only follow live data
+        if (isTracePlayback) return;
+
         set((s) => {
-          // Only update monitor messages for live CAN data, not trace playback
-          let newMonitorMessages = s.monitorMessages;
-          if (!isTracePlayback) {
-            newMonitorMessages = new Map(s.monitorMessages);
-            const existing = newMonitorMessages.get(monitorKey);
-            
-            let cycleTime = 0;
-            let count = 1;
-            
-            if (existing) {
-              count = existing.count + 1;
-              // Calculate cycle time in ms
-              cycleTime = (newFrame.timestamp - existing.lastTimestamp) * 1000;
-            }
-            
-            newMonitorMessages.set(monitorKey, {
-              frame: newFrame,
-              count,
-              cycleTime,
-              lastTimestamp: newFrame.timestamp,
-            });
-          }
-          
-          // Only append to trace if recording is active (live data only, not trace playback)
+          const newMonitorMessages = updateMonitor ? new Map(s.monitorMessages) : s.monitorMessages;
           let newTraceMessages = s.traceMessages;
           let newRecordingStartTime = s.recordingStartTime;
-          
-          if (s.isRecording && !isTracePlayback) {
-            // Live recording: If this is the first message after starting recording, use its timestamp as reference
-            if (newRecordingStartTime === null) {
-              newRecordingStartTime = newFrame.timestamp;
+          const recorded: CanFrame[] = [];
+          for (const newFrame of frames) {
+            if (updateMonitor) {
+              // Include channel in key so same ID on different channels are separate
+              const monitorKey = `${newFrame.channel}-${newFrame.id}-${newFrame.direction}`;
+              const existing = newMonitorMessages.get(monitorKey);
+
+              let cycleTime = 0;
+              let count = 1;
+
+              if (existing) {
+                count = existing.count + 1;
+                // Calculate cycle time in ms
+                cycleTime = (newFrame.timestamp - existing.lastTimestamp) * 1000;
+              }
+
+              newMonitorMessages.set(monitorKey, {
+                frame: newFrame,
+                count,
+                cycleTime,
+                lastTimestamp: newFrame.timestamp,
+              });
+            }
+
+            // Only append to trace if recording is active
+            if (s.isRecording) {
+              // If this is the first message after starting recording, use its timestamp as reference
+              if (newRecordingStartTime === null) {
+                newRecordingStartTime = newFrame.timestamp;
+              }
+              // Calculate relative timestamp
+              recorded.push({ ...newFrame, timestamp: newFrame.timestamp - newRecordingStartTime });
             }
-            // Calculate relative timestamp
-            const relativeTimestamp = newFrame.timestamp - newRecordingStartTime;
-            const frameWithRelativeTime = { ...newFrame, timestamp: relativeTimestamp };
-            newTraceMessages = [...s.traceMessages, frameWithRelativeTime].slice(-s.maxMessages);
           }
-          // Note: Trace playback messages are already loaded into traceMessages, so we don't add them again
-          
+          if (recorded.length > 0) {
+            newTraceMessages = [...s.traceMessages, ...recorded].slice(-s.maxMessages);
+          }
+
           return {
             traceMessages: newTraceMessages,
             monitorMessages: newMonitorMessages,
@@ -343,10 +455,11 @@ export const useCanStore = create<CanState>((set, get) => ({
         });
 
         // Decode signals for plot if not paused and signals are selected
-        // Skip real-time updates during trace playback (data is loaded all at once, not incrementally)
         const currentState = get();
-        
-        if (!currentState.isPlotPaused && currentState.selectedPlotSignals.length > 0 && !isTracePlayback) {
+        if (currentState.isPlotPaused || currentState.selectedPlotSignals.length === 0) {
+          return;
+        }
+        for (const newFrame of frames) {
           // Find signals that match this message
           const matchingSignals = currentState.selectedPlotSignals.filter(
             (sig) => sig.channelId === newFrame.channel && sig.messageId === newFrame.id
@@ -403,11 +516,66 @@ export const useCanStore = create<CanState>((set, get) => ({
             // Message doesn't match any selected signals, skip
           }
         }
+      };
+
+      // Set up event listeners for incoming messages
+      console.log("Setting up can-message listener...");
+      unlistenMessage = await listen<CanFrame>("can-message", (event) => {
+        handleFrames([event.payload]);
+      });
+
+      // Received frames arrive in batches; a sequence gap means frames were dropped
+      const nextSeq = new Map<string, number>();
+      unlistenBatch = await listen<FrameBatch>("can-messages", (event) => {
+        const batch = event.payload;
+        const expected = nextSeq.get(batch.channelId);
+        if (expected !== undefined && batch.seq > expected) {
+          console.warn(`${batch.seq - expected} frames on ${batch.channelId} were not delivered`);
+        }
+        nextSeq.set(batch.channelId, batch.seq + batch.frames.length);
+        handleFrames(batch.frames);
       });
       console.log("can-message listener set up");
 
+      // With backend frame delivery, poll the changes instead
+      let storeSeq = 0;
+      let polling = false;
+      frameStorePoll = setInterval(async () => {
+        if (polling || get().settings?.frameDelivery !== "store") return;
+        polling = true;
+        try {
+          const delta = await invoke<FrameDelta>("get_frame_delta", { since: storeSeq, maxFrames: get().maxMessages });
+          storeSeq = delta.nextSeq;
+          if (delta.missed > 0) {
+            console.warn(`${delta.missed} frames not fetched from the frame store`);
+          }
+          const state = get();
+          const isTracePlayback = state.loadedTraceFile !== null &&
+                                  (state.playbackState === "playing" || state.playbackState === "paused");
+          if (!state.isPaused && !isTracePlayback && delta.latest.length > 0) {
+            set((s) => {
+              const newMonitorMessages = new Map(s.monitorMessages);
+              for (const entry of delta.latest) {
+                newMonitorMessages.set(`${entry.frame.channel}-${entry.frame.id}-${entry.frame.direction}`, {
+                  frame: entry.frame,
+                  count: entry.count,
+                  cycleTime: entry.cycleTimeMs,
+                  lastTimestamp: entry.frame.timestamp,
+                });
+              }
+              return { monitorMessages: newMonitorMessages };
+            });
+          }
+          handleFrames(delta.frames, false);
+        } catch (error) {
+          console.error("Failed to poll the frame store:", error);
+        } finally {
+          polling = false;
+        }
+      }, FRAME_STORE_POLL_MS);
+
       // Set up event listeners for bus statistics
-      unlistenStats = await listen<{ channelId: string; busLoad: number; txCount: number; rxCount: number; errorCount: number; txErrorCounter: number; rxErrorCounter: number }>("bus-stats", (event) => {
+      unlistenStats = await listen<BusStats & { channelId: string }>("bus-stats", (event) => {
         const stats = event.payload;
         set((s) => {
           const newStats = new Map(s.channelBusStats);
@@ -416,13 +584,86 @@ export const useCanStore = create<CanState>((set, get) => ({
             txCount: stats.txCount,
             rxCount: stats.rxCount,
             errorCount: stats.errorCount,
+            txBytes: stats.txBytes,
+            rxBytes: stats.rxBytes,
             txErrorCounter: stats.txErrorCounter,
             rxErrorCounter: stats.rxErrorCounter,
+            controllerState: stats.controllerState,
+            stateTransitions: stats.stateTransitions,
+            lastTransition: stats.lastTransition,
+            msgRate: stats.msgRate,
+            firstMsgTime: stats.firstMsgTime,
+            lastMsgTime: stats.lastMsgTime,
+            uniqueIds: stats.uniqueIds,
+            droppedFrames: stats.droppedFrames,
           });
           return { channelBusStats: newStats };
         });
       });
       
+      // Defaults from the application settings
+      const settings = await invoke<AppSettings>("get_settings");
+      set({ settings, selectedBitrate: settings.defaultBitrate, logFormat: settings.logFormat });
+
+      // Offer the configuration of a session that ended in a crash
+      const recovery = await invoke<{ savedAt: number; project: ProjectData } | null>("get_autosave_recovery");
+      let recovered = false;
+      if (recovery) {
+        const savedAt = new Date(recovery.savedAt).toLocaleString();
+        if (window.confirm(`bootCAN did not exit cleanly. Restore the configuration autosaved at ${savedAt}?`)) {
+          try {
+            await get().restoreProject(recovery.project);
+            recovered = true;
+          } catch (error) {
+            console.error("Failed to restore autosaved configuration:", error);
+          }
+        }
+        await invoke("discard_autosave_recovery");
+      }
+
+      // Otherwise reopen the last project when enabled in the recent projects settings
+      const startupProject = recovered ? null : await invoke<string | null>("get_startup_project");
+      if (startupProject) {
+        try {
+          await get().loadProject(startupProject);
+        } catch (error) {
+          console.error(`Failed to reopen ${startupProject}:`, error);
+        }
+      }
+
+      // Hand configuration changes to the backend autosave
+      useCanStore.subscribe((state, prev) => {
+        if (
+          state.channels === prev.channels &&
+          state.filters === prev.filters &&
+          state.filterLogic === prev.filterLogic &&
+          state.transmitJobs === prev.transmitJobs &&
+          state.loadedDbcFiles === prev.loadedDbcFiles
+        ) {
+          return;
+        }
+        if (autosaveTimer) clearTimeout(autosaveTimer);
+        autosaveTimer = setTimeout(() => {
+          invoke("update_autosave", { project: buildProject(useCanStore.getState()) }).catch((error) =>
+            console.error("Failed to update autosave:", error)
+          );
+        }, AUTOSAVE_DEBOUNCE_MS);
+      });
+
+      // Keep the plot configuration in the backend layout store, saved with the project
+      useCanStore.subscribe((state, prev) => {
+        if (state.selectedPlotSignals !== prev.selectedPlotSignals) {
+          invoke("set_layout_value", { key: "plot.signals", value: state.selectedPlotSignals }).catch((error) =>
+            console.error("Failed to store plot signals:", error)
+          );
+        }
+        if (state.plotTimeWindow !== prev.plotTimeWindow) {
+          invoke("set_layout_value", { key: "plot.timeWindow", value: state.plotTimeWindow }).catch((error) =>
+            console.error("Failed to store plot time window:", error)
+          );
+        }
+      });
+
       console.log("Backend initialized successfully");
     } catch (error) {
       console.error("Failed to initialize backend:", error);
@@ -492,10 +733,15 @@ export const useCanStore = create<CanState>((set, get) => ({
     }
   },
 
-  clearMessages: () => set({ 
-    traceMessages: [], 
-    monitorMessages: new Map<string, MonitorEntry>() 
-  }),
+  clearMessages: () => {
+    if (get().settings?.frameDelivery === "store") {
+      invoke("clear_frame_store").catch((error) => console.error("Failed to clear the frame store:", error));
+    }
+    set({
+      traceMessages: [],
+      monitorMessages: new Map<string, MonitorEntry>()
+    });
+  },
   
   getDisplayMessages: () => {
     const state = get();
@@ -635,7 +881,7 @@ export const useCanStore = create<CanState>((set, get) => ({
   stopRecording: () => set({ isRecording: false, recordingStartTime: null }),
   
   // Trace logging actions
-  startLogging: async (filePath: string, format: "csv" | "trc") => {
+  startLogging: async (filePath: string, format: "csv" | "trc" | "asc") => {
     try {
       await invoke("start_logging", { filePath, format });
       set({ isLogging: true, logFilePath: filePath, logFormat: format });
@@ -925,26 +1171,76 @@ export const useCanStore = create<CanState>((set, get) => ({
   
   // Filter actions
   setFilters: (filters) => set({ filters }),
+  setFilterLogic: (filterLogic) => set({ filterLogic }),
+
+  // Settings actions
+  updateSettings: async (settings: AppSettings) => {
+    const updated = await invoke<AppSettings>("update_settings", { settings });
+    set({ settings: updated });
+  },
   
   // Project file actions
   saveProject: async (filePath: string) => {
-    const state = get();
+    const project = buildProject(get());
+
+    try {
+      await invoke("save_project", {
+        filePath,
+        channels: project.channels,
+        filters: project.filters,
+        transmitJobs: project.transmitJobs,
+      });
+      console.log("Project saved successfully");
+    } catch (error) {
+      console.error("Failed to save project:", error);
+      throw error;
+    }
+  },
+  
+  loadProject: async (filePath: string) => {
+    try {
+      const project = await invoke<ProjectData & {
+        dbcConflicts: Array<{ channelId: string; path: string; kind: string; usedEmbedded: boolean }>;
+      }>("load_project", { filePath });
+
+      for (const conflict of project.dbcConflicts) {
+        console.warn(
+          `DBC ${conflict.path} of channel ${conflict.channelId} is ${conflict.kind}` +
+            (conflict.usedEmbedded ? ", using the copy embedded in the project" : "")
+        );
+      }
+
+      await get().restoreProject(project);
+      console.log("Project loaded successfully");
+    } catch (error) {
+      console.error("Failed to load project:", error);
+      throw error;
+    }
+  },
+
+  restoreProject: async (project: ProjectData) => {
+    // Install channels, DBCs, filters and transmit jobs in the backend; on
+    // failure nothing is changed there and the current state is kept
+    await invoke("apply_project", { project });
     
-    // Convert channels to project format
-    // Use loadedDbcFiles Map to get the actual DBC file path
-    const projectChannels = state.channels.map(ch => ({
+    // Restore channels
+    const restoredChannels = project.channels.map(ch => ({
       id: ch.id,
       name: ch.name,
       interfaceId: ch.interfaceId,
       bitrate: ch.bitrate,
-      dbcFile: state.loadedDbcFiles.get(ch.id) || ch.dbcFile || null,
+      dbcFile: ch.dbcFile,
+      connectionStatus: "disconnected" as ConnectionStatus,
     }));
     
-    // Convert filters to project format
-    const projectFilters = state.filters.map(f => ({ data: f }));
+    // Restore filters; the filter panel shows the first enabled one
+    const panelFilter = project.filters.find(f => f.enabled);
+    const restoredFilters = (panelFilter?.filter.rules ?? [])
+      .map(fromBackendRule)
+      .filter((r): r is FilterRule => r !== null);
     
-    // Convert transmit jobs to project format (exclude backendJobId)
-    const projectTransmitJobs = state.transmitJobs.map(job => ({
+    // Restore transmit jobs (all disabled on load)
+    const restoredTransmitJobs = project.transmitJobs.map(job => ({
       id: job.id,
       frame: {
         id: job.frame.id,
@@ -952,123 +1248,40 @@ export const useCanStore = create<CanState>((set, get) => ({
         isRemote: job.frame.isRemote,
         dlc: job.frame.dlc,
         data: job.frame.data,
-        channel: job.frame.channel || undefined,
+        timestamp: 0,
+        channel: job.frame.channel || "",
+        direction: "tx" as const,
       },
       intervalMs: job.intervalMs,
-      enabled: false, // Always save as disabled
+      enabled: false,
+      backendJobId: undefined,
     }));
     
-    try {
-      await invoke("save_project", {
-        filePath,
-        channels: projectChannels,
-        filters: projectFilters,
-        transmitJobs: projectTransmitJobs,
-      });
-      console.log("Project saved successfully");
-    } catch (error) {
-      console.error("Failed to save project:", error);
-      throw error;
-    }
-  },
-  
-  loadProject: async (filePath: string) => {
-    try {
-      const project = await invoke<{
-        version: string;
-        channels: Array<{
-          id: string;
-          name: string;
-          interfaceId: string | null;
-          bitrate: number;
-          dbcFile: string | null;
-        }>;
-        filters: Array<{ data: any }>;
-        transmitJobs: Array<{
-          id: string;
-          frame: {
-            id: number;
-            isExtended: boolean;
-            isRemote: boolean;
-            dlc: number;
-            data: number[];
-            channel?: string;
-          };
-          intervalMs: number;
-          enabled: boolean;
-        }>;
-      }>("load_project", { filePath });
-      
-      // Restore channels
-      const restoredChannels = project.channels.map(ch => ({
-        id: ch.id,
-        name: ch.name,
-        interfaceId: ch.interfaceId,
-        bitrate: ch.bitrate,
-        dbcFile: ch.dbcFile,
-        connectionStatus: "disconnected" as ConnectionStatus,
-      }));
-      
-      // Restore filters
-      const restoredFilters = project.filters.map(f => f.data);
-      
-      // Restore transmit jobs (all disabled on load)
-      const restoredTransmitJobs = project.transmitJobs.map(job => ({
-        id: job.id,
-        frame: {
-          id: job.frame.id,
-          isExtended: job.frame.isExtended,
-          isRemote: job.frame.isRemote,
-          dlc: job.frame.dlc,
-          data: job.frame.data,
-          timestamp: 0,
-          channel: job.frame.channel || "",
-          direction: "tx" as const,
-        },
-        intervalMs: job.intervalMs,
-        enabled: false,
-        backendJobId: undefined,
-      }));
-      
-      // Restore loadedDbcFiles Map from restored channels
-      const restoredDbcFiles = new Map<string, string>();
-      for (const channel of restoredChannels) {
-        if (channel.dbcFile) {
-          restoredDbcFiles.set(channel.id, channel.dbcFile);
-        }
-      }
-      
-      // Update state first
-      set({
-        channels: restoredChannels,
-        filters: restoredFilters,
-        transmitJobs: restoredTransmitJobs,
-        activeChannel: restoredChannels.length > 0 ? restoredChannels[0].id : null,
-        loadedDbcFiles: restoredDbcFiles,
-      });
-      
-      // Load DBC files if they exist (this will also update the backend)
-      for (const channel of restoredChannels) {
-        if (channel.dbcFile) {
-          try {
-            await get().loadDbc(channel.id, channel.dbcFile);
-          } catch (error) {
-            console.warn(`Failed to load DBC file ${channel.dbcFile} for channel ${channel.id}:`, error);
-            // Remove from loadedDbcFiles if loading failed
-            set((s) => {
-              const newMap = new Map(s.loadedDbcFiles);
-              newMap.delete(channel.id);
-              return { loadedDbcFiles: newMap };
-            });
-            // Continue loading even if DBC fails
-          }
-        }
+    // Restore loadedDbcFiles Map from restored channels
+    const restoredDbcFiles = new Map<string, string>();
+    for (const channel of restoredChannels) {
+      if (channel.dbcFile) {
+        restoredDbcFiles.set(channel.id, channel.dbcFile);
       }
-      
-      console.log("Project loaded successfully");
-    } catch (error) {
-      console.error("Failed to load project:", error);
-      throw error;
+    }
+    
+    set({
+      channels: restoredChannels,
+      filters: restoredFilters,
+      filterLogic: panelFilter?.filter.logic === "Or" ? "or" : "and",
+      transmitJobs: restoredTransmitJobs,
+      activeChannel: restoredChannels.length > 0 ? restoredChannels[0].id : null,
+      loadedDbcFiles: restoredDbcFiles,
+    });
+
+    // Plot configuration is kept in the project's layout store
+    const layout = await invoke<Record<string, any>>("get_layout", { prefix: "plot." });
+    set({ selectedPlotSignals: [], plotData: new Map() });
+    for (const signal of (layout["plot.signals"] ?? []) as PlotSignal[]) {
+      get().addPlotSignal(signal);
+    }
+    if (typeof layout["plot.timeWindow"] === "number") {
+      set({ plotTimeWindow: layout["plot.timeWindow"] });
     }
   },
 
@@ -1143,5 +1356,13 @@ export const cleanupCanStore = () => {
     unlistenStats();
     unlistenStats = null;
   }
+  if (unlistenBatch) {
+    unlistenBatch();
+    unlistenBatch = null;
+  }
+  if (frameStorePoll) {
+    clearInterval(frameStorePoll);
+    frameStorePoll = null;
+  }
 };
 
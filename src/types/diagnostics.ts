@@ -0,0 +1,50 @@
+// Task states, queue depths and recent warnings (get_diagnostics)
+export interface SubscriberDrops {
+  name: string;
+  dropped: number;
+}
+
+export interface ChannelDiagnostics {
+  channelId: string;
+  task: "running" | "unresponsive" | "stopped";
+  state: string | null; // null when the task did not answer
+  commandQueue: number;
+  rxQueue: number; // Frames from the interface not yet handled by the channel task
+  broadcastQueue: number;
+  broadcastCapacity: number; // Frames a subscriber can fall behind before it loses frames
+  subscribers: SubscriberDrops[];
+  droppedFrames: number; // Lost by all subscribers, including the ones gone since
+  rxCount: number;
+  txCount: number;
+}
+
+export interface JobDiagnostics {
+  kind: string;
+  id: string;
+  running: boolean; // False once the task ended without the job being stopped
+}
+
+export interface WarningRecord {
+  timestampMs: number;
+  level: "warn" | "error";
+  target: string;
+  message: string;
+  channel: string | null;
+}
+
+export interface DecodePoolStats {
+  workers: number;
+  channels: string[]; // Channels whose frames are decoded
+  decoded: number;
+  dropped: number; // Not decoded because their worker was too far behind
+  lost: number; // Decoded, but lost by consumers that fell behind
+}
+
+export interface Diagnostics {
+  channels: ChannelDiagnostics[];
+  jobs: JobDiagnostics[];
+  logging: boolean;
+  playback: "stopped" | "playing" | "paused";
+  recentWarnings: WarningRecord[]; // Oldest first
+  decodePool: DecodePoolStats;
+}
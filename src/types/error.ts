@@ -0,0 +1,30 @@
+// Error rejected by backend commands; react to `kind` instead of the message text
+export type BootCanErrorKind =
+  | "hal"
+  | "parse"
+  | "notConnected"
+  | "busy" // Trying again later may succeed
+  | "io"
+  | "protocol"
+  | "timeout"
+  | "notFound"
+  | "invalidInput"
+  | "cancelled"
+  | "other";
+
+export interface BootCanError {
+  kind: BootCanErrorKind;
+  message: string;
+}
+
+export function isBootCanError(error: unknown): error is BootCanError {
+  return typeof error === "object" && error !== null && "kind" in error && "message" in error;
+}
+
+export function errorMessage(error: unknown): string {
+  return isBootCanError(error) ? error.message : String(error);
+}
+
+export function isRetryable(error: unknown): boolean {
+  return isBootCanError(error) && (error.kind === "busy" || error.kind === "timeout");
+}
@@ -0,0 +1,61 @@
+// Filter panel rules and their backend (FilterSet) representation
+
+export interface FilterRule {
+  type: "idRange" | "idExact" | "dlcRange" | "direction" | "extendedId" | "remoteFrame";
+  idMin?: number;
+  idMax?: number;
+  idExact?: number;
+  dlcMin?: number;
+  dlcMax?: number;
+  rx?: boolean;
+  tx?: boolean;
+  extended?: boolean;
+  remote?: boolean;
+}
+
+export type FilterLogic = "and" | "or";
+
+// Rule as serialized by the backend, e.g. { IdRange: { min, max } } or { IdExact: 256 }
+export type BackendFilterRule = Record<string, any>;
+
+export interface BackendFilterSet {
+  rules: BackendFilterRule[];
+  logic: "And" | "Or";
+}
+
+export function toBackendRule(f: FilterRule): BackendFilterRule | null {
+  switch (f.type) {
+    case "idRange":
+      return { IdRange: { min: f.idMin || 0, max: f.idMax || 0x7FF } };
+    case "idExact":
+      return { IdExact: f.idExact || 0 };
+    case "dlcRange":
+      return { DlcRange: { min: f.dlcMin || 0, max: f.dlcMax || 8 } };
+    case "direction":
+      return { Direction: { rx: f.rx || false, tx: f.tx || false } };
+    case "extendedId":
+      return { ExtendedId: f.extended || false };
+    case "remoteFrame":
+      return { RemoteFrame: f.remote || false };
+    default:
+      return null;
+  }
+}
+
+// Rules the filter panel cannot show (e.g. data patterns) are dropped
+export function fromBackendRule(rule: BackendFilterRule): FilterRule | null {
+  if (rule.IdRange) return { type: "idRange", idMin: rule.IdRange.min, idMax: rule.IdRange.max };
+  if (rule.IdExact !== undefined) return { type: "idExact", idExact: rule.IdExact };
+  if (rule.DlcRange) return { type: "dlcRange", dlcMin: rule.DlcRange.min, dlcMax: rule.DlcRange.max };
+  if (rule.Direction) return { type: "direction", rx: rule.Direction.rx, tx: rule.Direction.tx };
+  if (rule.ExtendedId !== undefined) return { type: "extendedId", extended: rule.ExtendedId };
+  if (rule.RemoteFrame !== undefined) return { type: "remoteFrame", remote: rule.RemoteFrame };
+  return null;
+}
+
+export function toBackendFilterSet(rules: FilterRule[], logic: FilterLogic): BackendFilterSet {
+  return {
+    rules: rules.map(toBackendRule).filter((r): r is BackendFilterRule => r !== null),
+    logic: logic === "and" ? "And" : "Or",
+  };
+}
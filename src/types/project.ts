@@ -1,16 +1,44 @@
 // Project file type definitions for bootCAN project files
 
+import { BackendFilterSet } from "./filter";
+
 export interface ProjectChannel {
   id: string;
   name: string;
   interfaceId: string | null;
   bitrate: number;
   dbcFile: string | null; // File path, will be validated on load
+  statsEmission?: StatsEmission; // Applied to the backend channel on load
+  dbcHash?: string; // Hash of the DBC content at save time
+  dbcContent?: string; // Embedded DBC, used when the file is missing or changed
+}
+
+// Options of save_project; DBC paths below the project directory are stored relative by default
+export interface DbcSaveOptions {
+  relativePaths?: boolean;
+  embed?: boolean;
+}
+
+// Returned by load_project for every DBC that did not match the saved reference
+export interface DbcConflict {
+  channelId: string;
+  path: string;
+  kind: "missing" | "modified";
+  usedEmbedded: boolean;
 }
 
+export interface StatsEmission {
+  intervalMs: number;
+  onChange: boolean;
+}
+
+// Named filter; files before version 1.1 stored untyped filter panel rules,
+// which load_project converts into one filter for all channels
 export interface ProjectFilter {
-  type: string;
-  [key: string]: any;
+  name: string;
+  channelId: string | null; // null applies the filter to every project channel
+  enabled: boolean; // At most one enabled filter per channel
+  filter: BackendFilterSet;
 }
 
 export interface ProjectTransmitJob {
@@ -25,6 +53,7 @@ export interface ProjectTransmitJob {
   };
   intervalMs: number;
   enabled: boolean;
+  template?: Record<string, unknown>; // Frame with "${NAME}" references to project variables, replaces frame
   // Note: backendJobId is not saved as it's runtime-only
 }
 
@@ -33,5 +62,41 @@ export interface ProjectFile {
   channels: ProjectChannel[];
   filters: ProjectFilter[];
   transmitJobs: ProjectTransmitJob[];
+  layout?: Record<string, unknown>; // Frontend settings, see set_layout_value; keys like "plot.signals"
+  variables?: Record<string, string>; // Referenced as "${NAME}" by transmit templates and scenarios
 }
 
+
+// Returned by apply_project; registered jobs can be started with start_project_transmit
+export interface AppliedProject {
+  channelIds: string[];
+  transmitJobs: Record<string, ProjectTransmitJob>;
+  runningJobs: Record<string, string>; // project job ID -> backend job ID
+  secoc: boolean;
+  variables: Record<string, string>; // Edited with set_project_variables
+}
+
+// Recent projects list (get_recent_projects), kept in the app config directory
+export interface RecentProject {
+  path: string;
+  name: string;
+  lastOpened: number; // ms since the Unix epoch
+  pinned: boolean;
+  summary: {
+    channels: number;
+    dbcFiles: number;
+    filters: number;
+    transmitJobs: number;
+  };
+}
+
+export interface RecentProjects {
+  reopenLast: boolean; // Open the most recent project on startup
+  projects: RecentProject[]; // Pinned first, then most recent
+}
+
+// Returned by import_project_bundle; open projectPath with load_project
+export interface ImportedBundle {
+  projectPath: string;
+  traceFiles: string[];
+}
@@ -0,0 +1,17 @@
+// Application settings (get_settings / update_settings), kept in the app config directory
+export interface AppSettings {
+  preferredBitrates: number[]; // Offered in the connection dialogs
+  defaultBitrate: number;
+  logDirectory: string | null; // Relative log file names are placed here
+  logFormat: "csv" | "trc" | "asc";
+  eventBatchMs: number; // Interval of the received frame batches; 0 emits every frame on its own
+  frameDelivery: "events" | "store"; // "store" keeps frames in the backend for polling
+  statsIntervalMs: number; // Statistics interval of new channels
+  broadcastCapacity: number; // Frames a subscriber of a new channel can fall behind before it loses frames
+}
+
+export function bitrateLabel(bitrate: number): string {
+  return bitrate >= 1000000 && bitrate % 1000000 === 0
+    ? `${bitrate / 1000000} Mbit/s`
+    : `${bitrate / 1000} kbit/s`;
+}